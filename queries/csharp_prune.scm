@@ -1,3 +1,5 @@
 ; Capture C# method/constructor bodies
 (method_declaration body: (block) @body)
 (constructor_declaration body: (block) @body)
+; Property/indexer/event accessor bodies: `get { ... }`, `set { ... }`
+(accessor_declaration body: (block) @body)
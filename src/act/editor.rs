@@ -1,10 +1,11 @@
 use std::path::Path;
 use anyhow::{Context, Result};
-use crate::inspector::{exported_language_config, extract_symbols_from_source};
+use crate::inspector::{exported_language_config, extract_symbols_from_source, SymbolKind};
 use crate::act::auto_healer::try_auto_heal;
 
 pub struct AstEdit {
-    /// e.g. "class:Auth" or "function:login" or just the bare identifier "login"
+    /// e.g. "class:Auth" or "function:login" or just the bare identifier "login".
+    /// The kind prefix is matched by normalized category, so "fn:login" also works.
     pub target: String,
     pub action: String, // "replace_body", "replace", "delete"
     pub code: String,
@@ -27,9 +28,11 @@ pub fn apply_ast_edits(
 
     for edit in edits {
         // Simple search: target can be exactly "kind:name" or just "name"
-        let sym = symbols.iter().find(|s| {
-            let full_name = format!("{}:{}", s.kind, s.name);
-            edit.target == full_name || edit.target == s.name
+        let sym = symbols.iter().find(|s| match edit.target.split_once(':') {
+            Some((kind, name)) => {
+                name == s.name && (kind == s.kind || SymbolKind::from_raw(kind) == s.category())
+            }
+            None => edit.target == s.name,
         });
 
         if let Some(s) = sym {
@@ -120,6 +123,8 @@ fn check_write_permission(path: &Path) -> Result<()> {
 
 /// Walk the tree-sitter AST and collect human-readable descriptions of ERROR nodes.
 /// These are passed to the Auto-Healer so the LLM knows *what* went wrong.
+/// Positions are `line:col` from tree-sitter's `Point`, both 1-based here; `col` is a
+/// byte offset within the line, so it runs ahead of the visual column after multibyte text.
 fn collect_ts_errors(node: tree_sitter::Node, source: &str) -> Vec<String> {
     let mut errors = Vec::new();
     collect_ts_errors_inner(node, source, &mut errors);
@@ -284,4 +289,31 @@ mod tests {
         assert!(check_write_permission(f.path()).is_ok(),
             "Writable file should pass permission guard");
     }
+
+    // ── 5. Kind-prefixed targets ───────────────────────────────────────────────
+    /// The Rust driver reports `function`; an agent writing `fn:` must still hit it.
+    #[test]
+    fn kind_prefix_matches_normalized_category() {
+        let f = temp_file("fn login() {}\n\nstruct Auth;\n");
+        let edits = vec![AstEdit {
+            target: "fn:login".to_string(),
+            action: "replace".to_string(),
+            code: "fn login() { let _ = 1; }".to_string(),
+        }];
+        let out = apply_ast_edits(f.path(), edits, None).expect("fn: prefix should resolve");
+        assert!(
+            out.contains("let _ = 1;"),
+            "Edit should be applied, got: {out}"
+        );
+
+        let wrong_kind = vec![AstEdit {
+            target: "struct:login".to_string(),
+            action: "delete".to_string(),
+            code: String::new(),
+        }];
+        assert!(
+            apply_ast_edits(f.path(), wrong_kind, None).is_err(),
+            "Category mismatch must not match a function"
+        );
+    }
 }
@@ -586,3 +586,201 @@ pub fn compare_symbol(
 
     Ok(out)
 }
+
+/// Diff two different symbols — e.g. `handle_v1` vs `handle_v2`, possibly in
+/// different files — to spot drift. Both are located like `read_source`
+/// (qualified names work); the result is a unified line diff of their bodies.
+pub fn compare_symbols(
+    repo_root: &Path,
+    path_a: &str,
+    symbol_a: &str,
+    path_b: &str,
+    symbol_b: &str,
+) -> Result<String> {
+    let snapshot = |path: &str, symbol: &str| -> Result<CheckpointRecord> {
+        let (path, symbol) = (path.trim(), symbol.trim());
+        if path.is_empty() || symbol.is_empty() {
+            return Err(anyhow!(
+                "Missing required args: path_a, symbol_a, path_b, symbol_b"
+            ));
+        }
+        let abs = resolve_path(repo_root, path);
+        let code = read_symbol(&abs, symbol).with_context(|| {
+            format!("Failed to extract symbol `{symbol}` from {}", abs.display())
+        })?;
+        Ok(CheckpointRecord {
+            tag: symbol.to_string(),
+            path: normalize_checkpoint_path(repo_root, &abs),
+            symbol: symbol.to_string(),
+            code,
+            created_unix_ms: now_unix_ms(),
+        })
+    };
+    let rec_a = snapshot(path_a, symbol_a)?;
+    let rec_b = snapshot(path_b, symbol_b)?;
+    let (loc_a, body_a) = split_symbol_header(&rec_a.code);
+    let (loc_b, body_b) = split_symbol_header(&rec_b.code);
+
+    let mut out = format!(
+        "## Comparison: `{}` (`{}`) vs `{}` (`{}`)\n\n",
+        rec_a.symbol, rec_a.path, rec_b.symbol, rec_b.path
+    );
+    if body_a.trim() == body_b.trim() {
+        out.push_str(&format!(
+            "✅ **NO STRUCTURAL DIFF** — `{}` and `{}` have identical source.\n",
+            rec_a.symbol, rec_b.symbol
+        ));
+        return Ok(out);
+    }
+    // Copies that differ only in their own name (`handle_v1` → `handle_v2`).
+    let leaf = |s: &str| s.rsplit([':', '.']).next().unwrap_or(s).to_string();
+    let (name_a, name_b) = (leaf(&rec_a.symbol), leaf(&rec_b.symbol));
+    if name_a != name_b && replace_identifier(body_a, &name_a, &name_b).trim() == body_b.trim() {
+        out.push_str(&format!(
+            "✅ **NO STRUCTURAL DIFF** — `{}` and `{}` are identical apart from the name.\n",
+            rec_a.symbol, rec_b.symbol
+        ));
+        return Ok(out);
+    }
+
+    let a: Vec<&str> = body_a.trim_end().lines().collect();
+    let b: Vec<&str> = body_b.trim_end().lines().collect();
+    let ops = diff_lines(&a, &b);
+    let added = ops.iter().filter(|(op, _)| *op == '+').count();
+    let removed = ops.iter().filter(|(op, _)| *op == '-').count();
+    out.push_str(&format!(
+        "- a: {loc_a}\n- b: {loc_b}\n- {} → {} lines: +{added} / -{removed}\n\n",
+        a.len(),
+        b.len()
+    ));
+    out.push_str("```diff\n");
+    out.push_str(&format!("--- a/{}\n+++ b/{}\n", rec_a.path, rec_b.path));
+    out.push_str(&unified_hunks(&ops));
+    out.push_str("```\n");
+    Ok(out)
+}
+
+/// `text` with every whole-identifier occurrence of `from` replaced by `to`, so
+/// renaming `f` leaves `if`, `for` and `self` alone.
+fn replace_identifier(text: &str, from: &str, to: &str) -> String {
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (at, _) in text.match_indices(from) {
+        let before = bytes[..at].last().copied();
+        let after = bytes.get(at + from.len()).copied();
+        if at < last || before.is_some_and(is_word) || after.is_some_and(is_word) {
+            continue;
+        }
+        out.push_str(&text[last..at]);
+        out.push_str(to);
+        last = at + from.len();
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Splits `read_symbol` output into its `kind `name` — path:Lx-Ly` locator and
+/// the source body (disambiguation, scope and doc header lines dropped).
+fn split_symbol_header(code: &str) -> (&str, &str) {
+    let mut locator = "";
+    let mut rest = code;
+    while let Some((line, tail)) = rest.split_once('\n') {
+        let header = line.starts_with("// ⚠️ Disambiguation")
+            || line.starts_with("// scope: ")
+            || line.starts_with("// doc: ");
+        if locator.is_empty() && !header && line.starts_with("// ") && line.contains("` — ") {
+            locator = &line[3..];
+        } else if !header {
+            break;
+        }
+        rest = tail;
+    }
+    (locator, rest)
+}
+
+/// Line diff of `a` → `b` via longest common subsequence, as `(' ' | '-' | '+', line)`.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(char, &'a str)> {
+    let (n, m) = (a.len(), b.len());
+    // lcs[i][j] = LCS length of a[i..] and b[j..]. read_symbol caps bodies at
+    // 500 lines, so the table stays small.
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            ops.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(('-', a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    ops.extend(a[i..].iter().map(|l| ('-', *l)));
+    ops.extend(b[j..].iter().map(|l| ('+', *l)));
+    ops
+}
+
+/// `@@` hunks with 3 lines of context around each run of changes in `ops`, as
+/// built by [`diff_lines`]; shared with `apply_rename`'s preview diff.
+pub(crate) fn unified_hunks(ops: &[(char, &str)]) -> String {
+    const CONTEXT: usize = 3;
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != ' ')
+        .map(|(k, _)| k)
+        .collect();
+
+    // 1-based (a, b) line numbers at which each op starts.
+    let mut pos = Vec::with_capacity(ops.len());
+    let (mut line_a, mut line_b) = (1, 1);
+    for (op, _) in ops {
+        pos.push((line_a, line_b));
+        if *op != '+' {
+            line_a += 1;
+        }
+        if *op != '-' {
+            line_b += 1;
+        }
+    }
+
+    let mut out = String::new();
+    let mut i = 0;
+    while i < changed.len() {
+        // Grow the hunk while the next change is within reach of its context.
+        let mut j = i;
+        while j + 1 < changed.len() && changed[j + 1] - changed[j] <= 2 * CONTEXT {
+            j += 1;
+        }
+        let start = changed[i].saturating_sub(CONTEXT);
+        let end = (changed[j] + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let len_a = hunk.iter().filter(|(op, _)| *op != '+').count();
+        let len_b = hunk.iter().filter(|(op, _)| *op != '-').count();
+        out.push_str(&format!(
+            "@@ -{},{len_a} +{},{len_b} @@\n",
+            pos[start].0, pos[start].1
+        ));
+        for (op, line) in hunk {
+            out.push_str(&format!("{op}{line}\n"));
+        }
+        i = j + 1;
+    }
+    out
+}
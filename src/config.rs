@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,13 +13,305 @@ pub struct TokenEstimatorConfig {
 ///
 /// Note: `.gitignore` is always respected by the scanner; these are additional
 /// hard skips for noisy monorepo directories.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ScanConfig {
     /// Directory *names* to skip anywhere in the tree (e.g. "generated", "tmp").
     ///
     /// These are compared against path components, not full paths.
     pub exclude_dir_names: Vec<String>,
+    /// Slice and map files that `.gitattributes` marks `linguist-generated` or
+    /// `linguist-vendored`. Off by default: such files are listed but not read.
+    pub include_linguist_generated: bool,
+    /// Globs that classify a file as a test. Patterns containing `/` match the
+    /// repo-relative path; the rest match the file name.
+    pub test_patterns: Vec<String>,
+    /// Default test-file treatment for slices and maps; overridable per call.
+    pub include_tests: IncludeTests,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            exclude_dir_names: Vec::new(),
+            include_linguist_generated: false,
+            test_patterns: [
+                "**/tests/**",
+                "**/test/**",
+                "**/__tests__/**",
+                "*_test.go",
+                "*_test.rs",
+                "*.test.ts",
+                "*.test.tsx",
+                "*.test.js",
+                "*.spec.ts",
+                "*.spec.tsx",
+                "*.spec.js",
+                "test_*.py",
+                "*_test.py",
+            ]
+            .map(String::from)
+            .to_vec(),
+            include_tests: IncludeTests::All,
+        }
+    }
+}
+
+/// What a scan does with files matched by [`ScanConfig::test_patterns`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncludeTests {
+    /// Keep every file.
+    #[default]
+    All,
+    /// Drop test files (feature work).
+    Exclude,
+    /// Keep only test files (test writing).
+    Only,
+}
+
+impl IncludeTests {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "all" => Some(Self::All),
+            "exclude" => Some(Self::Exclude),
+            "only" => Some(Self::Only),
+            _ => None,
+        }
+    }
+}
+
+/// Fine-tuning for skeleton rendering (see `skeleton_mode`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkeletonConfig {
+    /// Replace runs of two or more adjacent declarations with trivial bodies by a single
+    /// `// ... (N trivial methods)` line. Shrinks getter/setter-heavy files.
+    pub collapse_runs: bool,
+    /// A pruned body counts as trivial when it spans at most this many source lines.
+    pub trivial_max_lines: usize,
+    /// Keep the first N lines of each pruned body (guard clauses, early returns)
+    /// before the placeholder. 0 prunes the whole body.
+    pub keep_head_lines: usize,
+    /// Comments mentioning one of these tags (whole word, case-insensitive) survive
+    /// comment stripping, e.g. add `"SAFETY"`, `"SECURITY"` or `"NOTE"`.
+    pub keep_comment_tags: Vec<String>,
+    /// Keep every comment and Python module docstring instead of stripping them.
+    pub keep_all_comments: bool,
+    /// Also drop declarations outside the public API (private / unexported items),
+    /// leaving only the module's public contract.
+    pub api_only: bool,
+    /// File extensions (e.g. `"py"`, `"proto"`) whose import lines are kept as-is
+    /// instead of being collapsed into a single `// ... (N imports)` hint.
+    pub keep_imports_for: Vec<String>,
+    /// String literals in kept code longer than this many bytes (base64 blobs, inline
+    /// SVG) become `"...<N bytes elided>"`. 0 keeps every string whole.
+    pub max_string_chars: usize,
+}
+
+impl Default for SkeletonConfig {
+    fn default() -> Self {
+        Self {
+            collapse_runs: false,
+            trivial_max_lines: 3,
+            keep_head_lines: 0,
+            keep_comment_tags: vec!["TODO".into(), "FIXME".into()],
+            keep_all_comments: false,
+            api_only: false,
+            keep_imports_for: Vec::new(),
+            max_string_chars: 1_000,
+        }
+    }
+}
+
+/// How a slice is serialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SliceFormat {
+    /// `<cortexast>` document with one CDATA `<file path="...">` element per file.
+    #[default]
+    Xml,
+    /// A `## path` heading and a fenced code block per file.
+    Markdown,
+}
+
+/// What a skeleton slice shows for a file whose language has a driver but whose
+/// parse failed or timed out (`slicer.parse_failure`). Every choice is headed by a
+/// `PARSE_FAILED` marker so the content is known to be raw.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseFailure {
+    /// The whole file, verbatim.
+    Full,
+    /// The first 50 lines / 2 KB, like a language without a driver.
+    #[default]
+    Truncate,
+    /// The marker alone.
+    Skip,
+}
+
+/// Model-family preset for slices (`slicer.profile`): picks the [`SliceFormat`] and
+/// how much of `budget_tokens` a slice may fill.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SliceProfile {
+    /// XML, full budget. The behaviour before profiles existed.
+    #[default]
+    Generic,
+    /// XML tags, which Claude models are tuned to read, with 10% budget headroom:
+    /// their tokenizer splits code finer than the 4-chars-per-token estimate.
+    Claude,
+    /// Markdown headings and fences, the layout GPT models see most; full budget.
+    Gpt,
+}
+
+impl SliceProfile {
+    pub fn format(self) -> SliceFormat {
+        match self {
+            Self::Generic | Self::Claude => SliceFormat::Xml,
+            Self::Gpt => SliceFormat::Markdown,
+        }
+    }
+
+    /// The share of a requested `budget_tokens` a slice may fill.
+    pub fn budget_tokens(self, requested: usize) -> usize {
+        match self {
+            Self::Generic | Self::Gpt => requested,
+            Self::Claude => requested - requested / 10,
+        }
+    }
+}
+
+/// What the slicer does to file content before it is shipped to a model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlicerConfig {
+    /// Replace credentials with `<REDACTED>` in slices. On by default.
+    pub redact_secrets: bool,
+    /// Globs for files whose every `key = value` / `key: value` value is redacted.
+    /// Patterns containing `/` match the repo-relative path; the rest match the
+    /// file name, case-insensitively. Only config/data files (`.env`, YAML, JSON,
+    /// TOML, INI, `.properties`) are blanked this way; source files are not.
+    pub secret_patterns: Vec<String>,
+    /// Put a file's opening module doc (Rust `//!`, Python module docstring, Go package
+    /// comment) back on top of its skeleton, which otherwise strips it with the other
+    /// comments. Only the first paragraph is kept. On by default.
+    pub preserve_module_header: bool,
+    /// Model-family preset: `"generic"` (default), `"claude"` or `"gpt"`.
+    pub profile: SliceProfile,
+    /// Explicit output format (`"xml"` or `"markdown"`); overrides the profile's.
+    pub format: Option<SliceFormat>,
+    /// Prefix slice lines that `git blame` attributes to one of the last N commits (or
+    /// to uncommitted edits) with `~`. `0` = off. Ignored outside a git checkout.
+    pub annotate_recent_commits: usize,
+    /// Fallback for files that fail to parse in skeleton mode: `"full"`, `"truncate"`
+    /// (default) or `"skip"`.
+    pub parse_failure: ParseFailure,
+}
+
+impl SlicerConfig {
+    /// `format` if set, else the profile's.
+    pub fn output_format(&self) -> SliceFormat {
+        self.format.unwrap_or(self.profile.format())
+    }
+}
+
+impl Default for SlicerConfig {
+    fn default() -> Self {
+        Self {
+            redact_secrets: true,
+            secret_patterns: [".env*", "*secret*", "*credentials*"]
+                .map(String::from)
+                .to_vec(),
+            preserve_module_header: true,
+            profile: SliceProfile::default(),
+            format: None,
+            annotate_recent_commits: 0,
+            parse_failure: ParseFailure::default(),
+        }
+    }
+}
+
+/// Which MCP tools the stdio server exposes.
+///
+/// Read from the server's workspace root (not a per-call `repoPath`), so an agent
+/// can't lift a restriction by pointing a call at another checkout.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Tools hidden from `tools/list` and rejected by `tools/call`,
+    /// e.g. `["run_diagnostics"]` to forbid spawning `cargo` / `npx`.
+    pub disabled_tools: Vec<String>,
+    /// Allowlist mode: when non-empty, only these tools are exposed.
+    /// `disabled_tools` still applies on top.
+    pub enabled_tools: Vec<String>,
+}
+
+impl McpConfig {
+    pub fn is_tool_enabled(&self, name: &str) -> bool {
+        (self.enabled_tools.is_empty() || self.enabled_tools.iter().any(|t| t == name))
+            && !self.disabled_tools.iter().any(|t| t == name)
+    }
+}
+
+/// `run_diagnostics` subprocess settings (also used by `context_pack`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    /// Seconds `cargo check` / `npx tsc` may run before it is killed and reported as
+    /// timed out, e.g. while stuck on a network fetch or a build-directory lock.
+    /// `0` = no limit.
+    pub timeout_secs: u64,
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self { timeout_secs: 120 }
+    }
+}
+
+/// Guards around tree-sitter parsing, so one pathological file (e.g. a huge generated
+/// source) can't stall a repo-wide walk such as `find_usages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseConfig {
+    /// Files larger than this are never parsed: skeletons show a "too large to parse"
+    /// stub and AST searches skip them. `0` = no cap.
+    pub max_parse_bytes: usize,
+    /// Wall-clock budget for parsing one file, in milliseconds; a parse running longer
+    /// is cancelled and the file skipped like an oversized one. `0` = no limit.
+    pub max_parse_millis: u64,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            // Well above `token_estimator.max_file_bytes`: only generated monsters hit it.
+            max_parse_bytes: 4 * 1024 * 1024,
+            max_parse_millis: 10_000,
+        }
+    }
+}
+
+/// Resource limits for running on shared machines (e.g. CI runners).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    /// Size of the rayon thread pool used for file reading, embedding and the parallel
+    /// walks. `0` = one thread per core. Overridden by `CORTEXAST_THREADS`.
+    pub max_threads: usize,
+}
+
+impl PerformanceConfig {
+    /// Size the global rayon pool from `max_threads`. The pool is process-wide and can
+    /// only be built once, so the first call wins and later calls are no-ops.
+    pub fn install_thread_pool(&self) {
+        if self.max_threads > 0 {
+            let _ = rayon::ThreadPoolBuilder::new()
+                .num_threads(self.max_threads)
+                .build_global();
+        }
+    }
 }
 
 /// Hard safety ceiling: files larger than this are **always** skipped, regardless of config.
@@ -86,12 +379,19 @@ impl Default for HugeCodebaseConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    /// Where `active_context.xml`, the vector DB (`db/`) and checkpoints are written.
+    /// Overridden by `--out-dir` / `CORTEXAST_OUT_DIR`.
     pub output_dir: PathBuf,
     /// Settings that govern file discovery and exclusion.
     pub scan: ScanConfig,
     pub token_estimator: TokenEstimatorConfig,
     /// When true, generate "skeleton" file content (function bodies pruned) for supported languages.
     pub skeleton_mode: bool,
+    /// How skeletons are rendered when `skeleton_mode` (or a skeleton-only slice) is on.
+    pub skeleton: SkeletonConfig,
+    /// What slices contain and how they are formatted: redaction, module headers,
+    /// model profile, recent-commit markers and the parse-failure fallback.
+    pub slicer: SlicerConfig,
     /// Vector search defaults when using `--query`.
     pub vector_search: VectorSearchConfig,
     /// Settings that govern huge monorepo / multi-service workspace behaviour.
@@ -99,6 +399,15 @@ pub struct Config {
     /// List of active languages for dynamic grammar loading (Wasm).
     /// Defaults to ["rust", "typescript", "python"].
     pub active_languages: Vec<String>,
+    /// Parse paths with a given language driver regardless of their extension, e.g.
+    /// `{ ".inc": "php", "schema.txt": "sql" }`. Keys starting with `.` are extensions,
+    /// other keys exact file names; values are driver names (or an extension they own).
+    pub language_overrides: BTreeMap<String, String>,
+    /// MCP tool exposure policy (disable / allowlist tools).
+    pub mcp: McpConfig,
+    pub diagnostics: DiagnosticsConfig,
+    pub parse: ParseConfig,
+    pub performance: PerformanceConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -111,6 +420,80 @@ pub struct VectorSearchConfig {
     /// Default max number of unique file paths to return for vector search.
     /// (If CLI `--query-limit` is provided, it wins. If omitted, we may auto-tune.)
     pub default_query_limit: usize,
+    /// Re-embed changed files before every query (JIT refresh). Set to false — or export
+    /// `CORTEXAST_NO_REFRESH=1` — to search the existing index as-is on large, static checkouts.
+    pub auto_refresh: bool,
+    /// Keep repeated-query results in the model's `query_cache.json` so they survive across calls.
+    /// When false the cache lives only as long as one loaded index.
+    pub persist_query_cache: bool,
+    /// Cosine similarity (0.0–1.0) a file must reach to be returned by vector search.
+    /// Exact symbol hits always pass; `0.0` keeps the top `query_limit` regardless.
+    pub min_score: f32,
+    /// File extensions (e.g. `["rs", "ts", "py"]`) eligible for embedding. Files outside
+    /// the list are never embedded nor returned by vector search. Empty = every text file.
+    pub index_extensions: Vec<String>,
+    /// How many files (best path match first) the MCP query slice checks for re-embedding
+    /// before searching. `0` = auto: `query_limit * 12`, clamped to 80–400. Raising it lets
+    /// a query reach changed files whose paths don't mention the query terms, at the cost
+    /// of embedding more files on the first query.
+    pub max_candidates: usize,
+    /// Points a query term earns for appearing in a candidate's file name.
+    pub filename_term_weight: i32,
+    /// Points a query term earns for appearing elsewhere in the path (e.g. a directory).
+    pub path_term_weight: i32,
+    /// Synonyms added to a query term before path scoring, e.g.
+    /// `{ "auth": ["authentication", "login"] }`. Keys match whole terms, case-insensitively.
+    pub query_expansions: BTreeMap<String, Vec<String>>,
+    /// Also search with the expanded query and alternate its hits with the original
+    /// query's. Costs a second embedding and search; off by default.
+    pub expand_embedding_query: bool,
+    /// Models an MCP `deep_slice` call may pick with `embed_model`, besides `model`.
+    /// Anything else is rejected, so a client cannot make the server download an
+    /// arbitrary Hub repo or load a local path as a model.
+    pub allowed_models: Vec<String>,
+}
+
+impl VectorSearchConfig {
+    /// Whether a per-call `embed_model` may select `model_id` (see `allowed_models`).
+    pub fn allows_model(&self, model_id: &str) -> bool {
+        model_id == self.model || self.allowed_models.iter().any(|m| m == model_id)
+    }
+
+    /// Candidate pool size for a query returning `limit` files.
+    pub fn candidate_cap(&self, limit: usize) -> usize {
+        if self.max_candidates > 0 {
+            self.max_candidates
+        } else {
+            (limit * 12).clamp(80, 400)
+        }
+    }
+
+    /// `terms` followed by their [`query_expansions`](Self::query_expansions), all
+    /// lower-cased, without duplicates.
+    pub fn expand_query_terms(&self, terms: &[String]) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        let mut push = |t: String| {
+            if !out.contains(&t) {
+                out.push(t);
+            }
+        };
+        for t in terms {
+            push(t.to_lowercase());
+        }
+        for t in terms {
+            let Some((_, synonyms)) = self
+                .query_expansions
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(t))
+            else {
+                continue;
+            };
+            for s in synonyms.iter().flat_map(|s| s.split_whitespace()) {
+                push(s.to_lowercase());
+            }
+        }
+        out
+    }
 }
 
 impl Default for VectorSearchConfig {
@@ -119,6 +502,25 @@ impl Default for VectorSearchConfig {
             model: "minishlab/potion-retrieval-32M".to_string(),
             chunk_lines: 40,
             default_query_limit: 30,
+            auto_refresh: true,
+            persist_query_cache: true,
+            min_score: 0.0,
+            index_extensions: Vec::new(),
+            max_candidates: 0,
+            filename_term_weight: 30,
+            path_term_weight: 10,
+            query_expansions: BTreeMap::new(),
+            expand_embedding_query: false,
+            allowed_models: [
+                "minishlab/potion-base-2M",
+                "minishlab/potion-base-4M",
+                "minishlab/potion-base-8M",
+                "minishlab/potion-base-32M",
+                "minishlab/potion-retrieval-32M",
+                "minishlab/potion-multilingual-128M",
+            ]
+            .map(String::from)
+            .to_vec(),
         }
     }
 }
@@ -130,6 +532,8 @@ impl Default for Config {
             scan: ScanConfig::default(),
             token_estimator: TokenEstimatorConfig::default(),
             skeleton_mode: true,
+            skeleton: SkeletonConfig::default(),
+            slicer: SlicerConfig::default(),
             vector_search: VectorSearchConfig::default(),
             huge_codebase: HugeCodebaseConfig::default(),
             active_languages: vec![
@@ -137,6 +541,11 @@ impl Default for Config {
                 "typescript".to_string(),
                 "python".to_string(),
             ],
+            language_overrides: BTreeMap::new(),
+            mcp: McpConfig::default(),
+            diagnostics: DiagnosticsConfig::default(),
+            parse: ParseConfig::default(),
+            performance: PerformanceConfig::default(),
         }
     }
 }
@@ -144,10 +553,40 @@ impl Default for Config {
 pub fn load_config(repo_root: &Path) -> Config {
     let primary = repo_root.join(".cortexast.json");
 
-    let text = std::fs::read_to_string(&primary);
-    let Ok(text) = text else {
-        return Config::default();
-    };
+    let mut cfg = std::fs::read_to_string(&primary)
+        .ok()
+        .and_then(|text| serde_json::from_str::<Config>(&text).ok())
+        .unwrap_or_default();
+
+    apply_env_overrides(&mut cfg);
+    cfg
+}
+
+/// Environment variable that overrides `output_dir` (relative paths resolve against the
+/// repo root). Lets parallel CI jobs write context + vector DB to job-specific dirs.
+pub const OUT_DIR_ENV: &str = "CORTEXAST_OUT_DIR";
+
+/// Environment variable that forces `vector_search.auto_refresh = false` when set to a
+/// truthy value (`1`, `true`, `yes`).
+pub const NO_REFRESH_ENV: &str = "CORTEXAST_NO_REFRESH";
+
+/// Environment variable that overrides `performance.max_threads` (a positive integer).
+pub const THREADS_ENV: &str = "CORTEXAST_THREADS";
 
-    serde_json::from_str::<Config>(&text).unwrap_or_else(|_| Config::default())
+fn apply_env_overrides(cfg: &mut Config) {
+    if let Some(dir) = std::env::var_os(OUT_DIR_ENV).filter(|v| !v.is_empty()) {
+        cfg.output_dir = PathBuf::from(dir);
+    }
+    if std::env::var(NO_REFRESH_ENV)
+        .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+    {
+        cfg.vector_search.auto_refresh = false;
+    }
+    if let Some(n) = std::env::var(THREADS_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+    {
+        cfg.performance.max_threads = n;
+    }
 }
@@ -100,8 +100,8 @@ impl FileExplorer for CsvEngine {
             let cells: Vec<String> = (0..col_count)
                 .map(|i| {
                     let cell = row.get(i).map(|s| s.as_str()).unwrap_or("");
-                    let truncated = if cell.len() > 40 {
-                        format!("{}…", &cell[..39])
+                    let truncated = if cell.chars().count() > 40 {
+                        format!("{}…", cell.chars().take(39).collect::<String>())
                     } else {
                         cell.to_string()
                     };
@@ -5,8 +5,20 @@ use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use tree_sitter::{Language, Node, Parser, Query, QueryCursor, StreamingIterator};
 
+use crate::chronos::unified_hunks;
+use crate::config::{IncludeTests, ParseConfig, SkeletonConfig};
+use crate::scanner::{LinguistAttributes, LinguistKind, TestFilter};
 use crate::universal::render_universal_skeleton;
+use crate::workspace::{detect_manifest_kind, ManifestKind};
 
+/// A named declaration extracted by a language driver.
+///
+/// Position units: `line` / `line_end` are tree-sitter rows (0-indexed, split on `\n`
+/// only, so they agree with `str::lines`). `start_byte` / `end_byte` are UTF-8 byte
+/// offsets into the source and always fall on char boundaries. Wherever a column is
+/// reported it is a tree-sitter `Point` column — a *byte* offset within the line, not
+/// a character count — so CJK text or emoji ahead of a token push it past the
+/// visual column.
 #[derive(Debug, Clone, Serialize)]
 pub struct Symbol {
     pub name: String,
@@ -18,11 +30,77 @@ pub struct Symbol {
     /// 0-indexed end line (inclusive-ish; derived from tree-sitter end position)
     pub line_end: u32,
 
+    /// UTF-8 byte offset of the declaration start.
     pub start_byte: usize,
+    /// UTF-8 byte offset one past the declaration end.
     pub end_byte: usize,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
+
+    /// Cyclomatic complexity (1 + decision points) of callable symbols. Only filled in
+    /// by `analyze_file` and [`extract_symbols_with_complexity`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub complexity: Option<u32>,
+
+    /// Attributes, decorators and annotations on the declaration (`#[get("/")]`,
+    /// `@app.route("/")`, `@GetMapping`), outermost first, each on one line.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+}
+
+impl Symbol {
+    /// Language-neutral category of `kind`. Match on this rather than the raw string.
+    pub fn category(&self) -> SymbolKind {
+        SymbolKind::from_raw(&self.kind)
+    }
+}
+
+/// Normalized symbol category shared by every driver.
+///
+/// Drivers emit free-form `kind` strings (`function`, `method`, `fn`, `constructor`,
+/// `rpc`, …) which are kept verbatim on [`Symbol::kind`] for display; consumers that
+/// need to reason about *what* a symbol is should go through [`Symbol::category`] so a
+/// new grammar's vocabulary can't silently fall through a string match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    /// Free functions, closures bound to names, macros.
+    Function,
+    /// Functions scoped to a type: methods, constructors, accessors, RPCs.
+    Method,
+    /// Structs, enums, classes, unions, records, type aliases, messages.
+    Type,
+    /// Traits, interfaces, protocols.
+    Trait,
+    /// Constants, statics, module-level variables.
+    Const,
+    /// Modules, namespaces, packages.
+    Module,
+    Other,
+}
+
+impl SymbolKind {
+    pub fn from_raw(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "fn" | "function" | "func" | "arrow_function" | "lambda" | "macro" | "def" => {
+                Self::Function
+            }
+            "method" | "constructor" | "ctor" | "getter" | "setter" | "accessor" | "property"
+            | "rpc" => Self::Method,
+            "struct" | "enum" | "class" | "union" | "record" | "type" | "type_alias"
+            | "typedef" | "message" | "object" => Self::Type,
+            "trait" | "interface" | "protocol" => Self::Trait,
+            "const" | "constant" | "static" | "var" | "let" | "variable" => Self::Const,
+            "mod" | "module" | "namespace" | "package" => Self::Module,
+            _ => Self::Other,
+        }
+    }
+
+    /// Functions and methods — anything that can enclose a call site.
+    pub fn is_callable(self) -> bool {
+        matches!(self, Self::Function | Self::Method)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -33,10 +111,67 @@ pub struct FileSymbols {
     pub symbols: Vec<Symbol>,
 }
 
+/// Imports and exports of one file, without its symbols — see [`imports_exports`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleSurface {
+    pub file: String,
+    pub imports: Vec<String>,
+    pub exports: Vec<String>,
+}
+
 fn normalize_path_for_output(p: &Path) -> String {
     p.to_string_lossy().replace('\\', "/")
 }
 
+/// How the walking tools (`find_usages`, `blast_radius`, `find_implementations`,
+/// `propagation_checklist`) print file paths.
+#[derive(Debug, Clone, Copy)]
+pub enum PathStyle<'a> {
+    /// Relative to this root — normally the resolved repo root — so output is
+    /// portable across machines. Files outside it stay absolute.
+    RelativeTo(&'a Path),
+    /// Absolute paths (`absolute_paths: true`).
+    Absolute,
+}
+
+impl PathStyle<'_> {
+    /// `path` as the tools print it, with `/` separators.
+    pub fn show(self, path: &Path) -> String {
+        match self {
+            Self::RelativeTo(root) if root.is_relative() => match std::env::current_dir() {
+                Ok(cwd) => PathStyle::RelativeTo(&cwd.join(root)).show(path),
+                Err(_) => normalize_path_for_output(path),
+            },
+            Self::RelativeTo(root) => match path.strip_prefix(root) {
+                Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+                Ok(rel) => normalize_path_for_output(rel),
+                Err(_) => normalize_path_for_output(path),
+            },
+            Self::Absolute => normalize_path_for_output(path),
+        }
+    }
+}
+
+/// Which optional [`LanguageDriver`] hooks a driver actually implements.
+/// Symbol extraction (`extract_skeleton`) is mandatory and therefore not listed.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct DriverCapabilities {
+    /// `find_imports` — feeds repo-map import edges.
+    pub imports: bool,
+    /// `find_exports` — public surface for repo maps.
+    pub exports: bool,
+    /// `body_prune_ranges` — skeleton mode strips bodies instead of returning full text.
+    pub body_prune: bool,
+}
+
+/// One registered driver, as reported by the `languages` action / `--languages`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageInfo {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub capabilities: DriverCapabilities,
+}
+
 pub trait LanguageDriver: Send + Sync {
     fn name(&self) -> &'static str;
     /// Primary file extensions handled by this driver (lowercase, without dot).
@@ -96,6 +231,11 @@ pub trait LanguageDriver: Send + Sync {
         root: Node,
         language: Language,
     ) -> Result<Vec<Symbol>>;
+
+    /// Optional hooks this driver overrides. Keep in sync when adding one.
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities::default()
+    }
 }
 
 fn apply_replacements(source_text: &str, mut reps: Vec<(usize, usize, String)>) -> String {
@@ -123,9 +263,325 @@ fn apply_replacements(source_text: &str, mut reps: Vec<(usize, usize, String)>)
     out
 }
 
-fn contains_todo_fixme(s: &str) -> bool {
+/// Merge runs of two or more adjacent declarations whose pruned bodies span at most
+/// `opts.trivial_max_lines` lines into one `// ... (N trivial methods)` replacement.
+///
+/// Declarations are adjacent when only whitespace separates them, so a doc comment,
+/// attribute or decorator ends the run and keeps the annotated signature visible.
+fn collapse_trivial_runs(
+    path: &Path,
+    source_text: &str,
+    root: Node,
+    mut ranges: Vec<(usize, usize, String)>,
+    opts: &SkeletonConfig,
+) -> Vec<(usize, usize, String)> {
+    if !opts.collapse_runs || ranges.len() < 2 {
+        return ranges;
+    }
+    ranges.sort_by_key(|r| r.0);
+
+    // Declaration span enclosing each trivial body (the nearest ancestor that starts
+    // before the body); None for bodies over the threshold.
+    let decls: Vec<Option<(usize, usize)>> = ranges
+        .iter()
+        .map(|&(s, e, _)| {
+            let lines = source_text.get(s..e)?.matches('\n').count() + 1;
+            if lines > opts.trivial_max_lines {
+                return None;
+            }
+            let mut node = root.descendant_for_byte_range(s, e)?;
+            while node.start_byte() >= s {
+                node = node.parent()?;
+            }
+            Some((node.start_byte(), node.end_byte()))
+        })
+        .collect();
+
+    let marker = if path_ext_lower(path) == "py" {
+        "#"
+    } else {
+        "//"
+    };
+    let mut out: Vec<(usize, usize, String)> = Vec::with_capacity(ranges.len());
+    let mut i = 0;
+    while i < ranges.len() {
+        if let Some((run_start, mut run_end)) = decls[i] {
+            let mut j = i;
+            while let Some(&Some((next_start, next_end))) = decls.get(j + 1) {
+                let gap_is_blank = next_start >= run_end
+                    && source_text
+                        .get(run_end..next_start)
+                        .is_some_and(|g| g.trim().is_empty());
+                if !gap_is_blank {
+                    break;
+                }
+                run_end = next_end;
+                j += 1;
+            }
+            if j > i {
+                out.push((
+                    run_start,
+                    run_end,
+                    format!("{marker} ... ({} trivial methods)", j - i + 1),
+                ));
+                // Drop anything nested inside the collapsed span.
+                i = j + 1;
+                while i < ranges.len() && ranges[i].0 < run_end {
+                    i += 1;
+                }
+                continue;
+            }
+        }
+        out.push(std::mem::take(&mut ranges[i]));
+        i += 1;
+    }
+    out
+}
+
+/// Tree-sitter node kinds that hold a whole string literal, quotes included.
+const STRING_NODE_KINDS: &[&str] = &[
+    "string",
+    "string_literal",
+    "raw_string_literal",
+    "template_string",
+    "interpreted_string_literal",
+    "encapsed_string",
+    "verbatim_string_literal",
+];
+
+/// `skeleton.max_string_chars`: replace each string literal longer than `max` bytes
+/// with `"...<N bytes elided>"`, a plain string in every supported language, so one
+/// embedded blob can't bloat an otherwise small skeleton. Strings overlapping an
+/// existing range (a pruned body, a dropped declaration) are left to that range.
+fn elide_long_strings(
+    root: Node,
+    max: usize,
+    ranges: &[(usize, usize, String)],
+) -> Vec<(usize, usize, String)> {
+    let mut out = Vec::new();
+    if max == 0 {
+        return out;
+    }
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        let (s, e) = (node.start_byte(), node.end_byte());
+        if e - s <= max || ranges.iter().any(|r| r.0 <= s && e <= r.1) {
+            // Too short to hold a long string, or inside a replaced range.
+            continue;
+        }
+        if STRING_NODE_KINDS.contains(&node.kind()) {
+            if !ranges.iter().any(|r| s < r.1 && r.0 < e) {
+                out.push((s, e, format!("\"...<{} bytes elided>\"", e - s)));
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    out
+}
+
+/// Move each pruned range past the first `opts.keep_head_lines` lines of its body, so
+/// guard clauses and early returns stay visible ahead of the placeholder.
+///
+/// Brace bodies keep their `{` line, the head lines and the closing `}` line, with
+/// `/* ... */` at the body's indentation. Indentation-delimited bodies (Python) keep
+/// the head lines and end with `...` at the suite's indentation. Bodies no longer than
+/// the head are kept whole; collapsed-run markers are left alone.
+fn keep_body_heads(
+    source_text: &str,
+    ranges: Vec<(usize, usize, String)>,
+    opts: &SkeletonConfig,
+) -> Vec<(usize, usize, String)> {
+    let n = opts.keep_head_lines;
+    if n == 0 {
+        return ranges;
+    }
+
+    // Byte length of the first `n` lines of `text`, or None when nothing but
+    // whitespace follows them.
+    let head_len = |text: &str| -> Option<usize> {
+        let end = text.match_indices('\n').nth(n - 1)?.0 + 1;
+        (!text[end..].trim().is_empty()).then_some(end)
+    };
+
+    ranges
+        .into_iter()
+        .filter_map(|(s, e, rep)| {
+            let Some(body) = source_text.get(s..e) else {
+                return Some((s, e, rep));
+            };
+            if is_collapsed_run_marker(rep.trim()) {
+                return Some((s, e, rep));
+            }
+
+            if rep.trim_start().starts_with('{') {
+                let (Some(open_nl), Some(close_nl)) = (body.find('\n'), body.rfind('\n')) else {
+                    return Some((s, e, rep));
+                };
+                if open_nl == close_nl || body[close_nl + 1..].trim() != "}" {
+                    return Some((s, e, rep));
+                }
+                let content_start = s + open_nl + 1;
+                let keep = head_len(&body[open_nl + 1..close_nl + 1])?;
+                let indent = line_indent_at_byte(source_text, content_start);
+                Some((
+                    content_start + keep,
+                    s + close_nl + 1,
+                    format!("{indent}/* ... */\n"),
+                ))
+            } else {
+                let keep = head_len(body)?;
+                let indent = line_indent_at_byte(source_text, s);
+                Some((s + keep, e, format!("{indent}...\n")))
+            }
+        })
+        .collect()
+}
+
+/// Whether a declaration belongs to the module's public API, per language:
+/// Rust `pub` items (plus trait and trait-impl members), TS/JS names the driver
+/// reports as exported (plus non-`private` members of them), Go capitalised names,
+/// Python names without a leading `_` (dunders count) and, elsewhere, anything
+/// not declared `private`.
+fn is_api_symbol(
+    ext: &str,
+    sym: &Symbol,
+    nested: bool,
+    source_text: &str,
+    root: Node,
+    exports: &std::collections::HashSet<&str>,
+) -> bool {
+    let decl = source_text.get(sym.start_byte..).unwrap_or("").trim_start();
+    match ext {
+        "rs" => decl.starts_with("pub ") || in_rust_trait_or_trait_impl(root, sym.start_byte),
+        "py" => {
+            !sym.name.starts_with('_') || (sym.name.starts_with("__") && sym.name.ends_with("__"))
+        }
+        "go" => sym.name.starts_with(|c: char| c.is_ascii_uppercase()),
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => {
+            exports.contains(sym.name.as_str())
+                || (nested && !decl.starts_with("private ") && !decl.starts_with('#'))
+        }
+        _ => !decl.starts_with("private "),
+    }
+}
+
+/// Rust members of a trait or a trait impl carry no `pub` but are as public as the trait.
+fn in_rust_trait_or_trait_impl(root: Node, byte: usize) -> bool {
+    let mut node = root.descendant_for_byte_range(byte, byte);
+    while let Some(n) = node {
+        match n.kind() {
+            "trait_item" => return true,
+            "impl_item" => return n.child_by_field_name("trait").is_some(),
+            _ => node = n.parent(),
+        }
+    }
+    false
+}
+
+/// Whole-line byte range of a dropped declaration, including the attributes,
+/// decorators and doc comments directly above it.
+fn widen_decl_range(root: Node, source_text: &str, start: usize, end: usize) -> (usize, usize) {
+    let (mut start, mut end) = (start, end);
+    if let Some(mut node) = root.descendant_for_byte_range(start, end) {
+        if let Some(p) = node.parent().filter(|p| p.kind() == "decorated_definition") {
+            node = p;
+        }
+        start = start.min(node.start_byte());
+        end = end.max(node.end_byte());
+        let mut prev = node.prev_sibling();
+        while let Some(p) = prev {
+            let attached =
+                matches!(p.kind(), "attribute_item" | "decorator") || p.kind().ends_with("comment");
+            let gap = source_text.get(p.end_byte()..start).unwrap_or("\n\n");
+            if !attached || gap.matches('\n').count() > 1 || !gap.trim().is_empty() {
+                break;
+            }
+            start = p.start_byte();
+            prev = p.prev_sibling();
+        }
+    }
+    let line_start = source_text[..start].rfind('\n').map_or(0, |i| i + 1);
+    if source_text[line_start..start].trim().is_empty() {
+        start = line_start;
+    }
+    if let Some(nl) = source_text[end..].find('\n') {
+        if source_text[end..end + nl].trim().is_empty() {
+            end += nl + 1;
+        }
+    }
+    (start, end)
+}
+
+/// `(start_byte, end_byte, replacement)` edits, as applied by [`apply_replacements`].
+type Replacements = Vec<(usize, usize, String)>;
+
+/// `skeleton.api_only`: remove every declaration outside the public API (see
+/// [`is_api_symbol`]) and the body ranges inside them. Members of a dropped
+/// container go with it. Returns `(kept ranges, removed declarations)`.
+fn drop_non_api(
+    path: &Path,
+    source_text: &str,
+    root: Node,
+    symbols: &[Symbol],
+    exports: &[String],
+    ranges: Replacements,
+) -> (Replacements, Replacements) {
+    let ext = path_ext_lower(path);
+    let exports: std::collections::HashSet<&str> = exports.iter().map(String::as_str).collect();
+    let mut sorted: Vec<&Symbol> = symbols.iter().collect();
+    sorted.sort_by_key(|s| (s.start_byte, std::cmp::Reverse(s.end_byte)));
+
+    let mut dropped: Vec<(usize, usize)> = Vec::new();
+    for (i, sym) in sorted.iter().enumerate() {
+        if dropped.last().is_some_and(|&(_, e)| sym.start_byte < e) {
+            continue;
+        }
+        let nested = sorted[..i]
+            .iter()
+            .any(|o| o.start_byte <= sym.start_byte && sym.end_byte <= o.end_byte);
+        if !is_api_symbol(&ext, sym, nested, source_text, root, &exports) {
+            dropped.push(widen_decl_range(
+                root,
+                source_text,
+                sym.start_byte,
+                sym.end_byte,
+            ));
+        }
+    }
+
+    let kept = ranges
+        .into_iter()
+        .filter(|(s, e, _)| !dropped.iter().any(|&(ds, de)| *s < de && ds < *e))
+        .collect();
+    let removed = dropped
+        .into_iter()
+        .map(|(s, e)| (s, e, String::new()))
+        .collect();
+    (kept, removed)
+}
+
+/// The summary line emitted by [`collapse_trivial_runs`]; survives comment stripping.
+fn is_collapsed_run_marker(trimmed: &str) -> bool {
+    (trimmed.starts_with("// ... (") || trimmed.starts_with("# ... ("))
+        && trimmed.ends_with(" trivial methods)")
+}
+
+/// Whether `s` mentions one of `tags` as a whole word, ignoring ASCII case.
+fn mentions_comment_tag(s: &str, tags: &[String]) -> bool {
     let up = s.to_ascii_uppercase();
-    up.contains("TODO") || up.contains("FIXME")
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    tags.iter()
+        .map(|t| t.trim().to_ascii_uppercase())
+        .filter(|t| !t.is_empty())
+        .any(|tag| {
+            up.match_indices(&tag).any(|(at, _)| {
+                let before = up.as_bytes()[..at].last().copied();
+                let after = up.as_bytes().get(at + tag.len()).copied();
+                !before.is_some_and(is_word) && !after.is_some_and(is_word)
+            })
+        })
 }
 
 fn is_comment_only_line_trimmed(t: &str) -> bool {
@@ -154,7 +610,7 @@ fn strip_trailing_whitespace(text: &str) -> String {
     out
 }
 
-fn strip_python_module_docstring_if_present(text: &str) -> String {
+fn strip_python_module_docstring_if_present(text: &str, keep_tags: &[String]) -> String {
     let mut lines: Vec<&str> = text.lines().collect();
     let mut start_idx: usize = 0;
 
@@ -211,7 +667,7 @@ fn strip_python_module_docstring_if_present(text: &str) -> String {
         return text.to_string();
     }
 
-    if contains_todo_fixme(&combined) {
+    if mentions_comment_tag(&combined, keep_tags) {
         return text.to_string();
     }
 
@@ -224,7 +680,79 @@ fn strip_python_module_docstring_if_present(text: &str) -> String {
     out
 }
 
-fn strip_comment_only_lines_and_blocks(text: &str) -> String {
+/// Opening paragraph of a file's module doc — Rust `//!` lines, a Python module
+/// docstring or a Go package comment — or `None` when the file has none. Licence
+/// comments, shebangs and inner attributes ahead of it are skipped.
+pub fn module_header(path: &Path, source_text: &str) -> Option<String> {
+    let lines: Vec<&str> = source_text.lines().collect();
+    let header: Vec<String> = match path_ext_lower(path).as_str() {
+        "rs" => {
+            let mut in_block = false;
+            let start = lines.iter().position(|l| {
+                let t = l.trim();
+                if in_block {
+                    in_block = !t.contains("*/");
+                    return false;
+                }
+                if t.starts_with("/*") && !t.starts_with("/*!") {
+                    in_block = !t.contains("*/");
+                    return false;
+                }
+                !(t.is_empty()
+                    || t.starts_with("#![")
+                    || (t.starts_with("//") && !t.starts_with("//!")))
+            })?;
+            lines[start..]
+                .iter()
+                .map(|l| l.trim())
+                .take_while(|t| t.starts_with("//!") && !t[3..].trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+        "py" => {
+            let start = lines
+                .iter()
+                .position(|l| !(l.trim().is_empty() || l.trim_start().starts_with('#')))?;
+            let first = lines[start].trim();
+            let quote = ["\"\"\"", "'''"]
+                .into_iter()
+                .find(|q| first.starts_with(q))?;
+            if first[3..].contains(quote) {
+                vec![first.to_string()]
+            } else {
+                // Cut at the first blank line, closing the quotes ourselves.
+                let mut out = vec![first.to_string()];
+                for l in &lines[start + 1..] {
+                    if l.trim().is_empty() {
+                        out.push(quote.to_string());
+                        break;
+                    }
+                    out.push(l.to_string());
+                    if l.contains(quote) {
+                        break;
+                    }
+                }
+                out
+            }
+        }
+        "go" => {
+            let package = lines.iter().position(|l| l.starts_with("package "))?;
+            let start = lines[..package]
+                .iter()
+                .rposition(|l| !l.starts_with("//"))
+                .map_or(0, |i| i + 1);
+            lines[start..package]
+                .iter()
+                .take_while(|l| l.trim() != "//")
+                .map(|l| l.to_string())
+                .collect()
+        }
+        _ => return None,
+    };
+    (!header.is_empty()).then(|| header.join("\n") + "\n")
+}
+
+fn strip_comment_only_lines_and_blocks(text: &str, keep_tags: &[String]) -> String {
     let mut out_lines: Vec<String> = Vec::new();
     let mut i: usize = 0;
     let lines: Vec<&str> = text.lines().collect();
@@ -233,7 +761,7 @@ fn strip_comment_only_lines_and_blocks(text: &str) -> String {
         let line = lines[i];
         let trimmed = line.trim_start();
 
-        if contains_todo_fixme(trimmed) {
+        if mentions_comment_tag(trimmed, keep_tags) || is_collapsed_run_marker(trimmed) {
             out_lines.push(line.to_string());
             i += 1;
             continue;
@@ -244,7 +772,7 @@ fn strip_comment_only_lines_and_blocks(text: &str) -> String {
             // Preserve our own skeleton placeholders and truncation markers.
             let keep = trimmed.contains("/* ... */")
                 || trimmed.contains("TRUNCATED")
-                || contains_todo_fixme(trimmed);
+                || mentions_comment_tag(trimmed, keep_tags);
             if keep {
                 out_lines.push(line.to_string());
                 i += 1;
@@ -270,7 +798,7 @@ fn strip_comment_only_lines_and_blocks(text: &str) -> String {
                 }
             }
 
-            if contains_todo_fixme(&block_text) {
+            if mentions_comment_tag(&block_text, keep_tags) {
                 let end = j.min(lines.len().saturating_sub(1));
                 for l in lines.iter().take(end + 1).skip(i) {
                     out_lines.push((*l).to_string());
@@ -281,7 +809,7 @@ fn strip_comment_only_lines_and_blocks(text: &str) -> String {
         }
 
         if is_comment_only_line_trimmed(trimmed) {
-            // Drop comment-only lines unless TODO/FIXME (handled above).
+            // Drop comment-only lines unless tagged (handled above).
             i += 1;
             continue;
         }
@@ -426,17 +954,27 @@ fn collapse_empty_newlines(text: &str) -> String {
     out
 }
 
-fn clean_skeleton_text(path: &Path, text: &str) -> String {
+fn clean_skeleton_text(path: &Path, text: &str, opts: &SkeletonConfig) -> String {
     // Order matters: strip whitespace first to make comment/import detection more consistent.
+    let ext = path_ext_lower(path);
     let mut out = strip_trailing_whitespace(text);
-    out = strip_comment_only_lines_and_blocks(&out);
+    if !opts.keep_all_comments {
+        out = strip_comment_only_lines_and_blocks(&out, &opts.keep_comment_tags);
 
-    if path_ext_lower(path) == "py" {
-        out = strip_python_module_docstring_if_present(&out);
+        if ext == "py" {
+            out = strip_python_module_docstring_if_present(&out, &opts.keep_comment_tags);
+        }
     }
 
-    // Nuclear optimization: delete ALL imports and replace with a single hint line.
-    out = nuke_all_imports(&out);
+    // Nuclear optimization: delete ALL imports and replace with a single hint line,
+    // unless `keep_imports_for` lists this extension (imports are the signal there).
+    let keep_imports = opts
+        .keep_imports_for
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext));
+    if !keep_imports {
+        out = nuke_all_imports(&out);
+    }
 
     // Flatten indentation for brace-based languages (preserve Python/YAML).
     out = flatten_indentation_for_braces(path, &out);
@@ -468,6 +1006,17 @@ fn line_indent_at_byte(source_text: &str, byte_idx: usize) -> String {
     source_text[i..j].to_string()
 }
 
+/// Binary-safe, lossy read of a source file: `None` when it contains a NUL byte,
+/// otherwise its text with invalid UTF-8 (e.g. a Latin-1 `é` in a legacy file)
+/// replaced by U+FFFD, so one odd byte doesn't fail the whole file.
+fn read_source_lossy(path: &Path) -> Result<Option<String>> {
+    let raw = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if raw.contains(&0u8) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&raw).into_owned()))
+}
+
 /// Render a "skeleton" version of a file by pruning function/method bodies.
 ///
 /// This is designed to be *high-signal, low-noise* context for LLMs.
@@ -487,28 +1036,27 @@ pub fn render_skeleton(path: &Path) -> Result<String> {
     let language = driver.language_for_path(&abs);
 
     // Binary-safe read: detect null bytes before attempting UTF-8 decode.
-    let raw = std::fs::read(&abs).with_context(|| format!("Failed to read {}", abs.display()))?;
-    if raw.contains(&0u8) {
+    let Some(source_text) = read_source_lossy(&abs)? else {
         return Ok("/* BINARY_FILE — skipped */\n".to_string());
-    }
-    let source_text = String::from_utf8_lossy(&raw).into_owned();
+    };
 
     // Safety net: bail out before Tree-sitter on minified/machine-generated content.
     if is_minified_or_generated(&source_text) {
         return Ok("/* MINIFIED_OR_GENERATED — skipped */\n".to_string());
     }
+    if let Some(stub) = too_large_to_parse_stub(&source_text) {
+        return Ok(stub);
+    }
 
     let source = source_text.as_bytes();
 
     let mut parser = driver.make_parser(&abs)?;
-    let tree = parser
-        .parse(source_text.as_str(), None)
-        .ok_or_else(|| anyhow!("Failed to parse file"))?;
+    let tree = parse_with_limits(&mut parser, &source_text)?;
     let root = tree.root_node();
 
     let ranges = driver.body_prune_ranges(&abs, &source_text, source, root, language)?;
     let out = apply_replacements(&source_text, ranges);
-    Ok(clean_skeleton_text(&abs, &out))
+    Ok(clean_skeleton_text(&abs, &out, &SkeletonConfig::default()))
 }
 
 /// Like render_skeleton(), but uses the provided source text (avoids double file reads).
@@ -525,6 +1073,9 @@ pub fn render_skeleton_from_source(path: &Path, source_text: &str) -> Result<Str
     if is_minified_or_generated(source_text) {
         return Ok("/* MINIFIED_OR_GENERATED — skipped */\n".to_string());
     }
+    if let Some(stub) = too_large_to_parse_stub(source_text) {
+        return Ok(stub);
+    }
 
     let cfg = language_config().read().unwrap();
     let driver = cfg
@@ -535,14 +1086,77 @@ pub fn render_skeleton_from_source(path: &Path, source_text: &str) -> Result<Str
     let source = source_text.as_bytes();
 
     let mut parser = driver.make_parser(&abs)?;
-    let tree = parser
-        .parse(source_text, None)
-        .ok_or_else(|| anyhow!("Failed to parse file"))?;
+    let tree = parse_with_limits(&mut parser, source_text)?;
     let root = tree.root_node();
 
     let ranges = driver.body_prune_ranges(&abs, source_text, source, root, language)?;
     let out = apply_replacements(source_text, ranges);
-    Ok(clean_skeleton_text(&abs, &out))
+    Ok(clean_skeleton_text(&abs, &out, &SkeletonConfig::default()))
+}
+
+/// Line marker standing in for the focus window while the skeleton is cleaned.
+const FOCUS_WINDOW_PLACEHOLDER: &str = "__CORTEXAST_FOCUS_WINDOW__";
+
+/// Skeleton of the whole file with lines `start_line..=end_line` (1-based) kept
+/// verbatim: bodies overlapping the window stay unpruned, and the window itself
+/// skips comment/import stripping and indentation flattening. "Skeleton
+/// everywhere, detail here" for a targeted edit in a large file.
+pub fn render_skeleton_window(path: &Path, start_line: usize, end_line: usize) -> Result<String> {
+    let abs: PathBuf = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get current dir")?
+            .join(path)
+    };
+
+    let Some(source_text) = read_source_lossy(&abs)? else {
+        return Ok("/* BINARY_FILE — skipped */\n".to_string());
+    };
+
+    let starts = line_starts(&source_text);
+    let line_count = source_text.lines().count();
+    if start_line == 0 || end_line < start_line || start_line > line_count {
+        return Err(anyhow!(
+            "Invalid line window {start_line}-{end_line} for {} ({line_count} lines)",
+            abs.display()
+        ));
+    }
+    let end_line = end_line.min(line_count);
+    let win_start = starts[start_line - 1];
+    let win_end = starts.get(end_line).copied().unwrap_or(source_text.len());
+    let window = &source_text[win_start..win_end];
+
+    // Same safety nets as render_skeleton, but the window is still worth returning.
+    if is_minified_or_generated(&source_text) || too_large_to_parse_stub(&source_text).is_some() {
+        return Ok(window.to_string());
+    }
+
+    let cfg = language_config().read().unwrap();
+    let driver = cfg
+        .driver_for_path(&abs)
+        .ok_or_else(|| anyhow!("Unsupported file extension: {}", abs.display()))?;
+    let language = driver.language_for_path(&abs);
+
+    let source = source_text.as_bytes();
+    let mut parser = driver.make_parser(&abs)?;
+    let tree = parse_with_limits(&mut parser, &source_text)?;
+    let root = tree.root_node();
+
+    let mut ranges: Vec<(usize, usize, String)> = driver
+        .body_prune_ranges(&abs, &source_text, source, root, language)?
+        .into_iter()
+        .filter(|(s, e, _)| *e <= win_start || *s >= win_end)
+        .collect();
+    let window_body = window.strip_suffix('\n').unwrap_or(window);
+    ranges.push((
+        win_start,
+        win_start + window_body.len(),
+        FOCUS_WINDOW_PLACEHOLDER.to_string(),
+    ));
+    let out = apply_replacements(&source_text, ranges);
+    let out = clean_skeleton_text(&abs, &out, &SkeletonConfig::default());
+    Ok(out.replacen(FOCUS_WINDOW_PLACEHOLDER, window_body, 1))
 }
 
 /// Return true when a source text looks minified or machine-generated.
@@ -559,14 +1173,95 @@ pub fn is_minified_or_generated(source_text: &str) -> bool {
         .any(|l| l.len() > MAX_SAFE_LINE_CHARS)
 }
 
-/// Attempt to skeletonize a file, returning None when the file type isn't supported.
+/// Install the `parse` limits from a loaded config for every later parse, like
+/// [`apply_language_overrides`].
+pub fn apply_parse_limits(limits: &ParseConfig) {
+    *parse_limits().write().unwrap() = limits.clone();
+}
+
+fn parse_limits() -> &'static std::sync::RwLock<ParseConfig> {
+    static LIMITS: OnceLock<std::sync::RwLock<ParseConfig>> = OnceLock::new();
+    LIMITS.get_or_init(|| std::sync::RwLock::new(ParseConfig::default()))
+}
+
+/// Skeleton stand-in for a file over `parse.max_parse_bytes`; `None` when it may be parsed.
+fn too_large_to_parse_stub(source_text: &str) -> Option<String> {
+    let max = parse_limits().read().unwrap().max_parse_bytes;
+    (max > 0 && source_text.len() > max).then(|| {
+        format!(
+            "/* TOO_LARGE_TO_PARSE — {} bytes, over parse.max_parse_bytes ({max}) — skipped */\n",
+            source_text.len()
+        )
+    })
+}
+
+/// Parse `source_text` within the installed [`ParseConfig`] limits: files over
+/// `max_parse_bytes` are refused up front, and a parse running past `max_parse_millis`
+/// is cancelled, so one pathological file can't stall a repo-wide walk.
+fn parse_with_limits(parser: &mut Parser, source_text: &str) -> Result<tree_sitter::Tree> {
+    use std::ops::ControlFlow;
+    use std::time::{Duration, Instant};
+
+    let limits = parse_limits().read().unwrap().clone();
+    if limits.max_parse_bytes > 0 && source_text.len() > limits.max_parse_bytes {
+        return Err(anyhow!(
+            "too large to parse ({} bytes, over parse.max_parse_bytes {})",
+            source_text.len(),
+            limits.max_parse_bytes
+        ));
+    }
+    if limits.max_parse_millis == 0 {
+        return parser
+            .parse(source_text, None)
+            .ok_or_else(|| anyhow!("Failed to parse file"));
+    }
+
+    let started = Instant::now();
+    let budget = Duration::from_millis(limits.max_parse_millis);
+    let mut timed_out = false;
+    let mut cancel = |_: &tree_sitter::ParseState| {
+        timed_out = started.elapsed() >= budget;
+        if timed_out {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let bytes = source_text.as_bytes();
+    let tree = parser.parse_with_options(
+        &mut |offset, _| bytes.get(offset..).unwrap_or_default(),
+        None,
+        Some(tree_sitter::ParseOptions::new().progress_callback(&mut cancel)),
+    );
+    // A cancelled parse leaves state behind; the next parse must start fresh.
+    parser.reset();
+    match tree {
+        Some(tree) => Ok(tree),
+        None if timed_out => Err(anyhow!(
+            "parse timed out after {}ms (parse.max_parse_millis)",
+            limits.max_parse_millis
+        )),
+        None => Err(anyhow!("Failed to parse file")),
+    }
+}
+
+/// Attempt to skeletonize a file, returning None when the file type isn't supported
+/// and an error when its driver fails to parse it.
 ///
-/// This is intended for slicer fallbacks: unsupported file types should not default to full content.
-pub fn try_render_skeleton_from_source(path: &Path, source_text: &str) -> Result<Option<String>> {
+/// This is intended for slicer fallbacks: unsupported file types should not default to
+/// full content, and parse failures follow `slicer.parse_failure`.
+pub fn try_render_skeleton_from_source(
+    path: &Path,
+    source_text: &str,
+    opts: &SkeletonConfig,
+) -> Result<Option<String>> {
     // Safety net: skip minified / machine-generated files before any parsing.
     if is_minified_or_generated(source_text) {
         return Ok(Some("/* MINIFIED_OR_GENERATED — skipped */\n".to_string()));
     }
+    if let Some(stub) = too_large_to_parse_stub(source_text) {
+        return Ok(Some(stub));
+    }
     let abs: PathBuf = if path.is_absolute() {
         path.to_path_buf()
     } else {
@@ -606,24 +1301,46 @@ pub fn try_render_skeleton_from_source(path: &Path, source_text: &str) -> Result
 
     let mut parser = driver.make_parser(&abs)?;
 
-    let Some(tree) = parser.parse(source_text, None) else {
-        // Parse failures degrade to full content at higher layers (or truncation).
-        return Ok(None);
-    };
+    let tree = parse_with_limits(&mut parser, source_text)?;
     let root = tree.root_node();
 
-    let ranges = driver.body_prune_ranges(&abs, source_text, source, root, language)?;
+    let ranges = driver.body_prune_ranges(&abs, source_text, source, root, language.clone())?;
+    let (ranges, removed) = if opts.api_only {
+        let symbols = driver.extract_skeleton(&abs, source, root, language.clone())?;
+        let exports = driver.find_exports(&abs, source, root, language)?;
+        drop_non_api(&abs, source_text, root, &symbols, &exports, ranges)
+    } else {
+        (ranges, Vec::new())
+    };
+    let ranges = collapse_trivial_runs(&abs, source_text, root, ranges, opts);
+    let mut ranges = keep_body_heads(source_text, ranges, opts);
+    ranges.extend(removed);
+    let strings = elide_long_strings(root, opts.max_string_chars, &ranges);
+    ranges.extend(strings);
     let out = apply_replacements(source_text, ranges);
-    Ok(Some(clean_skeleton_text(&abs, &out)))
+    Ok(Some(clean_skeleton_text(&abs, &out, opts)))
 }
 
 pub struct LanguageConfig {
     drivers: Vec<Box<dyn LanguageDriver>>,
     by_ext: HashMap<String, usize>,
+    /// `language_overrides` from the config: lowercase `.ext` or file name -> language.
+    overrides: HashMap<String, String>,
 }
 
 impl LanguageConfig {
     pub fn driver_for_path(&self, path: &Path) -> Option<&dyn LanguageDriver> {
+        // Explicit overrides win: file name first, then extension.
+        if !self.overrides.is_empty() {
+            let lang = self
+                .overrides
+                .get(&file_name_lower(path))
+                .or_else(|| self.overrides.get(&format!(".{}", path_ext_lower(path))));
+            if let Some(d) = lang.and_then(|l| self.driver_for_language(l)) {
+                return Some(d);
+            }
+        }
+
         let ext = path_ext_lower(path);
         if let Some(&idx) = self.by_ext.get(&ext) {
             let d = self.drivers.get(idx).map(|x| x.as_ref());
@@ -640,6 +1357,35 @@ impl LanguageConfig {
             .find(|d| d.handles_path(path))
             .map(|d| d.as_ref())
     }
+
+    /// Driver named `lang` (e.g. `php`), or the one registered for extension `lang` (e.g. `ts`).
+    fn driver_for_language(&self, lang: &str) -> Option<&dyn LanguageDriver> {
+        self.drivers
+            .iter()
+            .position(|d| d.name() == lang)
+            .or_else(|| self.by_ext.get(lang).copied())
+            .and_then(|idx| self.drivers.get(idx))
+            .map(|d| d.as_ref())
+    }
+
+    /// Replace the path -> language overrides. Keys starting with `.` are extensions,
+    /// anything else an exact file name; both match case-insensitively.
+    pub fn set_language_overrides(
+        &mut self,
+        overrides: &std::collections::BTreeMap<String, String>,
+    ) {
+        self.overrides = normalize_language_overrides(overrides);
+    }
+}
+
+fn normalize_language_overrides(
+    overrides: &std::collections::BTreeMap<String, String>,
+) -> HashMap<String, String> {
+    overrides
+        .iter()
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_lowercase()))
+        .filter(|(k, v)| !k.is_empty() && k != "." && !v.is_empty())
+        .collect()
 }
 
 impl Default for LanguageConfig {
@@ -653,6 +1399,7 @@ impl Default for LanguageConfig {
         let mut cfg = Self {
             drivers,
             by_ext: HashMap::new(),
+            overrides: HashMap::new(),
         };
 
         for (idx, d) in cfg.drivers.iter().enumerate() {
@@ -674,6 +1421,8 @@ fn lang_extensions(lang: &str) -> Vec<&'static str> {
         "c"        => vec!["c", "h"],
         "c_sharp"  => vec!["cs"],
         "java"     => vec!["java"],
+        "kotlin"   => vec!["kt"],
+        "swift"    => vec!["swift"],
         "ruby"     => vec!["rb", "rake"],
         "dart"     => vec!["dart"],
         // Structured markup / config — routed through tree-sitter so
@@ -730,6 +1479,18 @@ impl LanguageConfig {
         self.drivers.iter().map(|d| d.name().to_string()).collect()
     }
 
+    /// Every registered driver with its extensions and capability flags.
+    pub fn describe(&self) -> Vec<LanguageInfo> {
+        self.drivers
+            .iter()
+            .map(|d| LanguageInfo {
+                name: d.name().to_string(),
+                extensions: d.extensions().iter().map(|s| s.to_string()).collect(),
+                capabilities: d.capabilities(),
+            })
+            .collect()
+    }
+
     pub fn extensions_for_language(&self, lang: &str) -> Vec<String> {
         if let Some(d) = self.drivers.iter().find(|d| d.name() == lang) {
             d.extensions().iter().map(|s| s.to_string()).collect()
@@ -743,6 +1504,16 @@ pub fn exported_language_config() -> &'static std::sync::RwLock<LanguageConfig>
     language_config()
 }
 
+/// Install `language_overrides` from a loaded config into the shared driver registry,
+/// so inspect, skeleton, map and find_usages all resolve overridden paths alike.
+pub fn apply_language_overrides(overrides: &std::collections::BTreeMap<String, String>) {
+    let wanted = normalize_language_overrides(overrides);
+    if language_config().read().unwrap().overrides == wanted {
+        return;
+    }
+    language_config().write().unwrap().overrides = wanted;
+}
+
 fn language_config() -> &'static std::sync::RwLock<LanguageConfig> {
     static CFG: OnceLock<std::sync::RwLock<LanguageConfig>> = OnceLock::new();
     CFG.get_or_init(|| {
@@ -787,6 +1558,14 @@ impl LanguageDriver for RustDriver {
         tree_sitter_rust::language()
     }
 
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            imports: true,
+            exports: true,
+            body_prune: true,
+        }
+    }
+
     fn find_imports(
         &self,
         _path: &Path,
@@ -820,7 +1599,7 @@ impl LanguageDriver for RustDriver {
                                     (visibility_modifier) @vis
                   name: (identifier) @name
               )
-              (#match? @vis \"^pub\")"#,
+              (#match? @vis "^pub")"#,
             "name",
         )?);
         exports.extend(run_query_strings(
@@ -832,7 +1611,7 @@ impl LanguageDriver for RustDriver {
                                     (visibility_modifier) @vis
                   name: (type_identifier) @name
               )
-              (#match? @vis \"^pub\")"#,
+              (#match? @vis "^pub")"#,
             "name",
         )?);
         exports.extend(run_query_strings(
@@ -844,7 +1623,7 @@ impl LanguageDriver for RustDriver {
                                     (visibility_modifier) @vis
                   name: (type_identifier) @name
               )
-              (#match? @vis \"^pub\")"#,
+              (#match? @vis "^pub")"#,
             "name",
         )?);
         exports.extend(run_query_strings(
@@ -856,7 +1635,7 @@ impl LanguageDriver for RustDriver {
                                     (visibility_modifier) @vis
                   name: (type_identifier) @name
               )
-              (#match? @vis \"^pub\")"#,
+              (#match? @vis "^pub")"#,
             "name",
         )?);
         Ok(exports)
@@ -985,6 +1764,14 @@ impl LanguageDriver for TypeScriptDriver {
         }
     }
 
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            imports: true,
+            exports: true,
+            body_prune: true,
+        }
+    }
+
     fn find_imports(
         &self,
         _path: &Path,
@@ -1165,6 +1952,35 @@ impl LanguageDriver for PythonDriver {
         tree_sitter_python::language()
     }
 
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            imports: true,
+            exports: false,
+            body_prune: true,
+        }
+    }
+
+    fn find_imports(
+        &self,
+        _path: &Path,
+        source: &[u8],
+        root: Node,
+        language: Language,
+    ) -> Result<Vec<String>> {
+        let mut imports = run_query_strings(
+            source,
+            root,
+            &language,
+            r#"(import_statement name: [
+                (dotted_name) @mod
+                (aliased_import name: (dotted_name) @mod)
+            ])"#,
+            "mod",
+        )?;
+        imports.extend(python_from_imports(source, root, &language)?);
+        Ok(imports)
+    }
+
     fn extract_skeleton(
         &self,
         _path: &Path,
@@ -1376,6 +2192,53 @@ impl LanguageDriver for WasmDriver {
         self.language.clone()
     }
 
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            imports: matches!(self.lang.as_str(), "java" | "kotlin" | "swift" | "go"),
+            exports: false,
+            // Pruning is driven by the optional `<lang>.scm` query next to the grammar.
+            body_prune: self.prune_scm.is_some(),
+        }
+    }
+
+    /// Java, Kotlin and Swift imports as dotted paths (`com.acme.util.Strings`,
+    /// `com.acme.util.*`, `Foundation`), read off the import nodes' text so one
+    /// walk serves all three grammars; Go imports as their unquoted package paths.
+    /// Other Wasm languages report none.
+    fn find_imports(
+        &self,
+        _path: &Path,
+        source: &[u8],
+        root: Node,
+        _language: Language,
+    ) -> Result<Vec<String>> {
+        if !matches!(self.lang.as_str(), "java" | "kotlin" | "swift" | "go") {
+            return Ok(vec![]);
+        }
+        let mut imports: Vec<String> = Vec::new();
+        let mut cursor = root.walk();
+        if self.lang == "go" {
+            for child in root.children(&mut cursor) {
+                go_import_paths(source, child, &mut imports);
+            }
+            return Ok(imports);
+        }
+        for child in root.children(&mut cursor) {
+            // Kotlin nests its `import_header`s in an `import_list`.
+            if child.kind() == "import_list" {
+                let mut inner = child.walk();
+                imports.extend(
+                    child
+                        .children(&mut inner)
+                        .filter_map(|n| dotted_import_path(source, n)),
+                );
+            } else {
+                imports.extend(dotted_import_path(source, child));
+            }
+        }
+        Ok(imports)
+    }
+
     /// Extract symbols using language-specific tree-sitter queries.
     /// Each Wasm language gets accurate node-type patterns so that
     /// `read_source`, `find_usages`, and `deep_slice` all work correctly.
@@ -1466,8 +2329,27 @@ impl LanguageDriver for WasmDriver {
                     (struct_declaration    name: (identifier) @name) @def
                     (enum_declaration      name: (identifier) @name) @def
                 "#;
-                syms.extend(run_query(source, root, &language, q_fn,    "function", true).unwrap_or_default());
-                syms.extend(run_query(source, root, &language, q_class,  "class",    true).unwrap_or_default());
+                let q_property = r#"
+                    (property_declaration name: (identifier) @name) @def
+                "#;
+                // Separate queries: older grammars lack these node types, and one
+                // unknown node would otherwise fail the whole query.
+                let q_record = r#"
+                    (record_declaration name: (identifier) @name) @def
+                "#;
+                // `name` is an identifier or a qualified name (`Acme.Billing`).
+                let q_namespace = r#"
+                    (namespace_declaration name: (_) @name) @def
+                "#;
+                let q_file_namespace = r#"
+                    (file_scoped_namespace_declaration name: (_) @name) @def
+                "#;
+                syms.extend(run_query(source, root, &language, q_fn,             "function",  true).unwrap_or_default());
+                syms.extend(run_query(source, root, &language, q_class,          "class",     true).unwrap_or_default());
+                syms.extend(run_query(source, root, &language, q_record,         "class",     true).unwrap_or_default());
+                syms.extend(run_query(source, root, &language, q_property,       "property",  true).unwrap_or_default());
+                syms.extend(run_query(source, root, &language, q_namespace,      "namespace", true).unwrap_or_default());
+                syms.extend(run_query(source, root, &language, q_file_namespace, "namespace", true).unwrap_or_default());
             }
 
             // ── Ruby ──────────────────────────────────────────────────────────
@@ -1536,6 +2418,56 @@ impl LanguageDriver for WasmDriver {
     }
 }
 
+/// `import static com.acme.Util.trim;` -> `com.acme.Util.trim`, `import a.b.* as c`
+/// -> `a.b.*`, `import struct Foo.Bar` -> `Foo.Bar`. `None` for non-import nodes.
+fn dotted_import_path(source: &[u8], node: Node) -> Option<String> {
+    if !matches!(node.kind(), "import_declaration" | "import_header") {
+        return None;
+    }
+    let text = node_text(source, node).trim().trim_end_matches(';');
+    let text = text.strip_prefix("import")?;
+    let text = text.split(" as ").next().unwrap_or(text);
+    // Java `static`, Swift attributes (`@testable`) and declaration-kind imports.
+    const KEYWORDS: [&str; 9] = [
+        "static",
+        "struct",
+        "class",
+        "enum",
+        "protocol",
+        "typealias",
+        "func",
+        "let",
+        "var",
+    ];
+    let path: String = text
+        .split_whitespace()
+        .skip_while(|w| w.starts_with('@') || KEYWORDS.contains(w))
+        .collect();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Go `import "fmt"` / `import ( u "github.com/google/uuid" ... )` -> the package
+/// paths, aliases and quotes dropped. Non-import nodes add nothing.
+fn go_import_paths(source: &[u8], node: Node, out: &mut Vec<String>) {
+    match node.kind() {
+        "import_declaration" | "import_spec_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                go_import_paths(source, child, out);
+            }
+        }
+        "import_spec" => {
+            if let Some(path) = node.child_by_field_name("path") {
+                let text = node_text(source, path).trim_matches(|c| c == '"' || c == '`');
+                if !text.is_empty() {
+                    out.push(text.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 fn run_query_byte_ranges(
     source: &[u8],
     root: Node,
@@ -1640,6 +2572,63 @@ fn run_query_strings(
     Ok(out)
 }
 
+/// Module strings of every `from X import ...` statement. A bare relative prefix
+/// (`from . import y`, `from .. import z`) yields one entry per imported name (`.y`,
+/// `..z`), since each of those names is usually a sibling module; the mapper falls
+/// back to the package `__init__.py` when it is not.
+fn python_from_imports(source: &[u8], root: Node, language: &Language) -> Result<Vec<String>> {
+    let query = Query::new(
+        language,
+        r#"(import_from_statement module_name: (_) @mod) @stmt"#,
+    )
+    .context("Failed to compile tree-sitter query")?;
+    let mod_idx = query.capture_index_for_name("mod");
+    let stmt_idx = query.capture_index_for_name("stmt");
+    let mut cursor = QueryCursor::new();
+
+    let mut out: Vec<String> = Vec::new();
+    let mut matches = cursor.matches(&query, root, source);
+    while let Some(m) = matches.next() {
+        let (mut module, mut stmt) = (None, None);
+        for c in m.captures {
+            if Some(c.index) == mod_idx {
+                module = Some(c.node);
+            } else if Some(c.index) == stmt_idx {
+                stmt = Some(c.node);
+            }
+        }
+        let (Some(module), Some(stmt)) = (module, stmt) else {
+            continue;
+        };
+        let text = node_text(source, module).trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        if !text.chars().all(|c| c == '.') {
+            out.push(text);
+            continue;
+        }
+
+        let mut walker = stmt.walk();
+        let names: Vec<String> = stmt
+            .children_by_field_name("name", &mut walker)
+            .filter_map(|n| match n.kind() {
+                "aliased_import" => n.child_by_field_name("name"),
+                _ => Some(n),
+            })
+            .map(|n| node_text(source, n).trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        if names.is_empty() {
+            // `from . import *`
+            out.push(text);
+        } else {
+            out.extend(names.into_iter().map(|n| format!("{text}{n}")));
+        }
+    }
+    Ok(out)
+}
+
 fn dedup_sorted(mut v: Vec<String>) -> Vec<String> {
     v.sort();
     v.dedup();
@@ -1699,16 +2688,288 @@ fn run_query(
             start_byte: def_node.start_byte(),
             end_byte: def_node.end_byte(),
             signature,
+            complexity: None,
+            attributes: declaration_attributes(def_node, source),
         });
     }
 
     Ok(out)
 }
 
+/// Node kinds that hold one attribute, decorator or annotation.
+const ATTRIBUTE_KINDS: &[&str] = &[
+    "attribute_item",
+    "decorator",
+    "annotation",
+    "marker_annotation",
+    "attribute_list",
+];
+
+/// Attribute / decorator / annotation text attached to the declaration `def`,
+/// outermost first, whitespace collapsed: items just before it (Rust `#[...]`,
+/// TypeScript member decorators; comments in between are skipped), decorators on a
+/// wrapping Python `decorated_definition` or TypeScript `export`, and those inside
+/// the declaration itself or its Java-style `modifiers`.
+fn declaration_attributes(def: Node, source: &[u8]) -> Vec<String> {
+    let mut found: Vec<Node> = Vec::new();
+    let mut prev = def.prev_named_sibling();
+    while let Some(n) = prev {
+        if ATTRIBUTE_KINDS.contains(&n.kind()) {
+            found.push(n);
+        } else if !n.kind().contains("comment") {
+            break;
+        }
+        prev = n.prev_named_sibling();
+    }
+    found.reverse();
+
+    if let Some(parent) = def
+        .parent()
+        .filter(|p| matches!(p.kind(), "decorated_definition" | "export_statement"))
+    {
+        let mut cursor = parent.walk();
+        found.extend(
+            parent
+                .named_children(&mut cursor)
+                .filter(|n| n.kind() == "decorator"),
+        );
+    }
+    let mut cursor = def.walk();
+    for child in def.named_children(&mut cursor) {
+        if ATTRIBUTE_KINDS.contains(&child.kind()) {
+            found.push(child);
+        } else if child.kind() == "modifiers" {
+            let mut inner = child.walk();
+            found.extend(
+                child
+                    .named_children(&mut inner)
+                    .filter(|n| ATTRIBUTE_KINDS.contains(&n.kind())),
+            );
+        }
+    }
+    found
+        .into_iter()
+        .map(|n| {
+            node_text(source, n)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Build files — line-based target extraction
+// ---------------------------------------------------------------------------
+
+/// Build files read with a line parser instead of a tree-sitter grammar, picked by
+/// file name. They yield symbols (their targets) but no imports, exports or skeleton.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuildFile {
+    Make,
+    CMake,
+}
+
+impl BuildFile {
+    fn for_path(path: &Path) -> Option<Self> {
+        match (
+            file_name_lower(path).as_str(),
+            path_ext_lower(path).as_str(),
+        ) {
+            ("makefile" | "gnumakefile", _) | (_, "mk" | "mak") => Some(Self::Make),
+            ("cmakelists.txt", _) | (_, "cmake") => Some(Self::CMake),
+            _ => None,
+        }
+    }
+
+    fn symbols(self, source_text: &str) -> Vec<Symbol> {
+        match self {
+            Self::Make => make_targets(source_text),
+            Self::CMake => cmake_targets(source_text),
+        }
+    }
+}
+
+/// Byte offset at which each line of `text` starts.
+fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Rule targets of a Makefile: kind `phony` for those listed under `.PHONY`, `target`
+/// otherwise. Special (`.SUFFIXES`), pattern (`%.o`) and variable (`$(BIN)`) targets
+/// are skipped, as are variable assignments, recipes and `define` blocks.
+fn make_targets(source_text: &str) -> Vec<Symbol> {
+    let lines: Vec<&str> = source_text.lines().collect();
+    let starts = line_starts(source_text);
+    let mut phony: std::collections::HashSet<String> = Default::default();
+    // (name, first line, last line, rule header)
+    let mut rules: Vec<(String, usize, usize, String)> = Vec::new();
+    let mut in_define = false;
+    let mut i = 0;
+    while i < lines.len() {
+        let first = i;
+        // Join `\`-continued lines into one logical line.
+        let mut logical = lines[i].to_string();
+        while logical.ends_with('\\') && i + 1 < lines.len() {
+            logical.pop();
+            logical.truncate(logical.trim_end().len());
+            i += 1;
+            logical.push(' ');
+            logical.push_str(lines[i].trim());
+        }
+        i += 1;
+
+        let trimmed = logical.trim();
+        let keyword = trimmed.split_whitespace().next().unwrap_or("");
+        if in_define {
+            in_define = keyword != "endef";
+            continue;
+        }
+        if keyword == "define" {
+            in_define = true;
+            continue;
+        }
+        if logical.starts_with('\t') || trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let text = trimmed.split('#').next().unwrap_or("");
+        let Some(colon) = text.find(':') else {
+            continue;
+        };
+        let (head, rest) = text.split_at(colon);
+        let prereqs = rest.trim_start_matches(':');
+        // `X := y`, `X ::= y`, `X = a:b` and `target: X = y` are assignments, not rules.
+        if head.contains('=') || prereqs.contains('=') {
+            continue;
+        }
+        if head.trim() == ".PHONY" {
+            phony.extend(prereqs.split_whitespace().map(str::to_string));
+            continue;
+        }
+
+        // The rule runs through its tab-indented recipe lines.
+        let mut last = i - 1;
+        while i < lines.len() && lines[i].starts_with('\t') {
+            last = i;
+            i += 1;
+        }
+        for name in head.split_whitespace() {
+            if name.starts_with('.') || name.contains('%') || name.contains('$') {
+                continue;
+            }
+            if rules.iter().any(|r| r.0 == name) {
+                continue;
+            }
+            rules.push((name.to_string(), first, last, text.trim().to_string()));
+        }
+    }
+
+    rules
+        .into_iter()
+        .map(|(name, first, last, header)| Symbol {
+            kind: if phony.contains(&name) {
+                "phony"
+            } else {
+                "target"
+            }
+            .to_string(),
+            name,
+            line: first as u32,
+            line_end: last as u32,
+            start_byte: starts[first],
+            end_byte: starts[last] + lines[last].len(),
+            signature: Some(header),
+            complexity: None,
+            attributes: Vec::new(),
+        })
+        .collect()
+}
+
+/// `project`, `add_executable`, `add_library` and `add_custom_target` names in a
+/// CMake file, as kinds `project`, `executable`, `library` and `target`.
+fn cmake_targets(source_text: &str) -> Vec<Symbol> {
+    const COMMANDS: &[(&str, &str)] = &[
+        ("project", "project"),
+        ("add_executable", "executable"),
+        ("add_library", "library"),
+        ("add_custom_target", "target"),
+    ];
+
+    let starts = line_starts(source_text);
+    let line_of = |byte: usize| starts.partition_point(|&s| s <= byte).saturating_sub(1);
+    let bytes = source_text.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut out: Vec<Symbol> = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        // Comments run to end of line; `#` inside an argument list is rare enough to ignore.
+        if bytes[pos] == b'#' {
+            pos = source_text[pos..]
+                .find('\n')
+                .map_or(bytes.len(), |n| pos + n);
+            continue;
+        }
+        if !is_word(bytes[pos]) || (pos > 0 && is_word(bytes[pos - 1])) {
+            pos += 1;
+            continue;
+        }
+        let word_end = (pos..bytes.len())
+            .find(|&j| !is_word(bytes[j]))
+            .unwrap_or(bytes.len());
+        let command = source_text[pos..word_end].to_ascii_lowercase();
+        let start = pos;
+        pos = word_end;
+
+        let Some(&(_, kind)) = COMMANDS.iter().find(|(c, _)| *c == command) else {
+            continue;
+        };
+        let after = source_text[word_end..].trim_start();
+        if !after.starts_with('(') {
+            continue;
+        }
+        let open = bytes.len() - after.len();
+        let Some(close) = source_text[open..].find(')').map(|n| open + n) else {
+            break;
+        };
+        let args = &source_text[open + 1..close];
+        pos = close + 1;
+
+        let Some(name) = args
+            .split_whitespace()
+            .next()
+            .map(|a| a.trim_matches('"'))
+            .filter(|a| !a.is_empty() && !a.contains('$'))
+        else {
+            continue;
+        };
+        let (line, line_end) = (line_of(start), line_of(close));
+        out.push(Symbol {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: line as u32,
+            line_end: line_end as u32,
+            start_byte: start,
+            end_byte: close + 1,
+            signature: Some(
+                source_text[start..=close]
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            complexity: None,
+            attributes: Vec::new(),
+        });
+    }
+    out
+}
+
 /// Parse a single file and extract symbols (functions/structs/classes) using tree-sitter.
 ///
 /// - Lines are 0-indexed.
 /// - `file` is emitted as the provided path string (normalized to '/').
+/// - Makefiles and CMake files list their build targets instead (line-based).
 pub fn analyze_file(path: &Path) -> Result<FileSymbols> {
     let abs: PathBuf = if path.is_absolute() {
         path.to_path_buf()
@@ -1718,27 +2979,46 @@ pub fn analyze_file(path: &Path) -> Result<FileSymbols> {
             .join(path)
     };
 
+    let Some(source_text) = read_source_lossy(&abs)? else {
+        return Err(anyhow!("Binary file — no symbols: {}", abs.display()));
+    };
+    analyze_source_at(path, &abs, &source_text)
+}
+
+/// In-memory counterpart of [`analyze_file`]: parse `source_text` as if it were
+/// `path` (only the extension picks the language) without touching the filesystem.
+pub fn analyze_source(path: &Path, source_text: &str) -> Result<FileSymbols> {
+    analyze_source_at(path, path, source_text)
+}
+
+fn analyze_source_at(path: &Path, abs: &Path, source_text: &str) -> Result<FileSymbols> {
+    if let Some(build_file) = BuildFile::for_path(abs) {
+        return Ok(FileSymbols {
+            file: normalize_path_for_output(path),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            symbols: build_file.symbols(source_text),
+        });
+    }
+
     let cfg = language_config().read().unwrap();
     let driver = cfg
-        .driver_for_path(&abs)
+        .driver_for_path(abs)
         .ok_or_else(|| anyhow!("Unsupported file extension: {}", abs.display()))?;
-    let language = driver.language_for_path(&abs);
+    let language = driver.language_for_path(abs);
 
-    let source_text = std::fs::read_to_string(&abs)
-        .with_context(|| format!("Failed to read {}", abs.display()))?;
     let source = source_text.as_bytes();
 
-    let mut parser = driver.make_parser(&abs)?;
+    let mut parser = driver.make_parser(abs)?;
 
-    let tree = parser
-        .parse(source_text.as_str(), None)
-        .ok_or_else(|| anyhow!("Failed to parse file"))?;
+    let tree = parse_with_limits(&mut parser, source_text)?;
 
     let root = tree.root_node();
 
-    let mut symbols = driver.extract_skeleton(&abs, source, root, language.clone())?;
-    let mut imports = driver.find_imports(&abs, source, root, language.clone())?;
-    let mut exports = driver.find_exports(&abs, source, root, language)?;
+    let mut symbols = driver.extract_skeleton(abs, source, root, language.clone())?;
+    annotate_complexity(&mut symbols, root);
+    let mut imports = driver.find_imports(abs, source, root, language.clone())?;
+    let mut exports = driver.find_exports(abs, source, root, language)?;
 
     // Stable ordering: by line then name.
     symbols.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.name.cmp(&b.name)));
@@ -1754,6 +3034,32 @@ pub fn analyze_file(path: &Path) -> Result<FileSymbols> {
     })
 }
 
+/// The import/export half of [`analyze_file`]: runs only the driver's
+/// `find_imports` / `find_exports`, skipping symbol extraction and complexity.
+pub fn imports_exports(path: &Path) -> Result<ModuleSurface> {
+    let Some(source_text) = read_source_lossy(path)? else {
+        return Err(anyhow!("Binary file — no imports: {}", path.display()));
+    };
+    let cfg = language_config().read().unwrap();
+    let driver = cfg
+        .driver_for_path(path)
+        .ok_or_else(|| anyhow!("Unsupported file extension: {}", path.display()))?;
+    let language = driver.language_for_path(path);
+    let source = source_text.as_bytes();
+
+    let mut parser = driver.make_parser(path)?;
+    let tree = parse_with_limits(&mut parser, &source_text)?;
+    let root = tree.root_node();
+
+    let imports = driver.find_imports(path, source, root, language.clone())?;
+    let exports = driver.find_exports(path, source, root, language)?;
+    Ok(ModuleSurface {
+        file: normalize_path_for_output(path),
+        imports: dedup_sorted(imports),
+        exports: dedup_sorted(exports),
+    })
+}
+
 /// Extract all top-level symbols from source text without a disk read.
 ///
 /// Used by the vector store for:
@@ -1762,6 +3068,15 @@ pub fn analyze_file(path: &Path) -> Result<FileSymbols> {
 ///
 /// Returns an empty vec for unsupported file types (graceful fallback to line-chunking).
 pub fn extract_symbols_from_source(path: &Path, source_text: &str) -> Vec<Symbol> {
+    extract_symbols_impl(path, source_text, false)
+}
+
+/// [`extract_symbols_from_source`] plus [`Symbol::complexity`] on every callable.
+pub fn extract_symbols_with_complexity(path: &Path, source_text: &str) -> Vec<Symbol> {
+    extract_symbols_impl(path, source_text, true)
+}
+
+fn extract_symbols_impl(path: &Path, source_text: &str, with_complexity: bool) -> Vec<Symbol> {
     if is_minified_or_generated(source_text) {
         return vec![];
     }
@@ -1774,6 +3089,9 @@ pub fn extract_symbols_from_source(path: &Path, source_text: &str) -> Vec<Symbol
             Err(_) => return vec![],
         }
     };
+    if let Some(build_file) = BuildFile::for_path(&abs) {
+        return build_file.symbols(source_text);
+    }
 
     let cfg = language_config().read().unwrap();
     let Some(driver) = cfg.driver_for_path(&abs) else {
@@ -1787,7 +3105,7 @@ pub fn extract_symbols_from_source(path: &Path, source_text: &str) -> Vec<Symbol
         return vec![];
     };
 
-    let Some(tree) = parser.parse(source_text, None) else {
+    let Ok(tree) = parse_with_limits(&mut parser, source_text) else {
         return vec![];
     };
 
@@ -1795,6 +3113,9 @@ pub fn extract_symbols_from_source(path: &Path, source_text: &str) -> Vec<Symbol
 
     match driver.extract_skeleton(&abs, source, root, language) {
         Ok(mut syms) => {
+            if with_complexity {
+                annotate_complexity(&mut syms, root);
+            }
             syms.sort_by(|a, b| a.line.cmp(&b.line));
             syms
         }
@@ -1802,6 +3123,88 @@ pub fn extract_symbols_from_source(path: &Path, source_text: &str) -> Vec<Symbol
     }
 }
 
+// ---------------------------------------------------------------------------
+// Cyclomatic complexity
+// ---------------------------------------------------------------------------
+
+/// Node kinds that open a branch, across the core grammars and the common Wasm ones
+/// (Go, Java, C/C++, C#, PHP, Ruby). Boolean short-circuits are counted separately
+/// through their `&&` / `||` / `and` / `or` operator tokens.
+const DECISION_NODE_KINDS: &[&str] = &[
+    "if_statement",
+    "if_expression",
+    "elif_clause",
+    "else_if_clause",
+    "for_statement",
+    "for_expression",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "foreach_statement",
+    "while_statement",
+    "while_expression",
+    "do_statement",
+    "case_statement",
+    "switch_case",
+    "switch_section",
+    "switch_block_statement_group",
+    "expression_case",
+    "type_case",
+    "match_arm",
+    "catch_clause",
+    "except_clause",
+    "rescue",
+    "conditional_expression",
+    "ternary_expression",
+];
+
+/// McCabe cyclomatic complexity of the subtree under `node`: 1 + every decision node
+/// (`if`, loops, `case` / match arms, `catch`, ternaries) + every `&&` / `||`.
+/// Nested closures count toward their enclosing function.
+pub fn cyclomatic_complexity(node: Node) -> u32 {
+    let mut complexity = 1u32;
+    let mut cursor = node.walk();
+    'walk: loop {
+        let n = cursor.node();
+        let kind = n.kind();
+        let is_decision = if n.is_named() {
+            DECISION_NODE_KINDS.contains(&kind)
+        } else {
+            matches!(kind, "&&" | "||" | "and" | "or")
+        };
+        if is_decision {
+            complexity += 1;
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == node {
+                break 'walk;
+            }
+            if cursor.goto_next_sibling() {
+                continue 'walk;
+            }
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+    complexity
+}
+
+/// Fill [`Symbol::complexity`] for callable symbols from their declaration node.
+fn annotate_complexity(symbols: &mut [Symbol], root: Node) {
+    for sym in symbols.iter_mut() {
+        if !sym.category().is_callable() {
+            continue;
+        }
+        if let Some(def) = root.descendant_for_byte_range(sym.start_byte, sym.end_byte) {
+            sym.complexity = Some(cyclomatic_complexity(def));
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tool: read_symbol — The X-Ray
 // ---------------------------------------------------------------------------
@@ -1829,6 +3232,119 @@ pub fn read_symbol_with_options(
     skeleton_only: bool,
     instance_index: Option<usize>,
 ) -> Result<String> {
+    read_symbol_by(
+        path,
+        SymbolSelector::Name {
+            name: symbol_name,
+            instance_index,
+        },
+        skeleton_only,
+    )
+}
+
+/// Batch [`read_symbol_with_options`] under a token budget (~4 chars per token).
+///
+/// Symbols are filled in request order. The first one that does not fit whole falls
+/// back to its skeleton, then to a head cut at a line boundary; it spends the rest of
+/// the budget, and every later symbol is listed as omitted in a closing note.
+/// `expand` returns extra text for a symbol read in full (e.g. `expand_types`).
+pub fn read_symbols_within_budget(
+    path: &Path,
+    names: &[&str],
+    skeleton_only: bool,
+    budget_tokens: usize,
+    mut expand: impl FnMut(&str) -> String,
+) -> String {
+    const CHARS_PER_TOKEN: usize = 4;
+    // Below this a truncated head is noise; omit the symbol instead.
+    const MIN_HEAD_CHARS: usize = 200;
+
+    let budget_chars = budget_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let mut used = 0usize;
+    let mut parts: Vec<String> = Vec::new();
+    let mut omitted: Vec<&str> = Vec::new();
+    let mut exhausted = false;
+
+    for &name in names {
+        if exhausted {
+            omitted.push(name);
+            continue;
+        }
+        let remaining = budget_chars.saturating_sub(used);
+        let full = match read_symbol_with_options(path, name, skeleton_only, None) {
+            Ok(mut text) => {
+                text.push_str(&expand(name));
+                text
+            }
+            Err(e) => format!("// ERROR reading `{name}`: {e}"),
+        };
+        if full.len() <= remaining {
+            used += full.len();
+            parts.push(full);
+            continue;
+        }
+
+        let full_tokens = full.len().div_ceil(CHARS_PER_TOKEN);
+        if !skeleton_only {
+            if let Ok(skel) = read_symbol_with_options(path, name, true, None) {
+                let skel = format!(
+                    "// ✂️ skeleton only: the full body (~{full_tokens} tokens) exceeds the remaining budget.\n{skel}"
+                );
+                if skel.len() <= remaining {
+                    used += skel.len();
+                    parts.push(skel);
+                    continue;
+                }
+            }
+        }
+
+        if remaining >= MIN_HEAD_CHARS {
+            let mut cut = remaining.min(full.len());
+            while !full.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            let head = &full[..full[..cut].rfind('\n').unwrap_or(0)];
+            let shown = head.lines().count();
+            parts.push(format!(
+                "{head}\n// ✂️ truncated: {shown} of {} lines shown (~{full_tokens} tokens in full). \
+                Read `{name}` on its own to see the rest.",
+                full.lines().count()
+            ));
+        } else {
+            omitted.push(name);
+        }
+        exhausted = true;
+    }
+
+    let mut out = parts.join("\n\n");
+    if !omitted.is_empty() {
+        let list: Vec<String> = omitted.iter().map(|n| format!("`{n}`")).collect();
+        out.push_str(&format!(
+            "\n\n> ✂️ Budget reached ({budget_tokens} tokens): omitted {}. \
+            Raise `budget_tokens` or request them in a separate call.\n",
+            list.join(", ")
+        ));
+    }
+    out
+}
+
+/// Like [`read_symbol`], but picks the innermost declaration whose range encloses
+/// the 1-based `line` — for when a stack trace or diagnostic gives a line, not a
+/// name. Rust `impl` blocks count, so a line between methods yields the impl.
+pub fn read_symbol_at_line(path: &Path, line: usize, skeleton_only: bool) -> Result<String> {
+    read_symbol_by(path, SymbolSelector::Line(line), skeleton_only)
+}
+
+enum SymbolSelector<'a> {
+    Name {
+        name: &'a str,
+        instance_index: Option<usize>,
+    },
+    /// 1-based line number.
+    Line(usize),
+}
+
+fn read_symbol_by(path: &Path, selector: SymbolSelector, skeleton_only: bool) -> Result<String> {
     let abs: PathBuf = if path.is_absolute() {
         path.to_path_buf()
     } else {
@@ -1837,11 +3353,9 @@ pub fn read_symbol_with_options(
             .join(path)
     };
 
-    let raw = std::fs::read(&abs).with_context(|| format!("Failed to read {}", abs.display()))?;
-    if raw.contains(&0u8) {
+    let Some(source_text) = read_source_lossy(&abs)? else {
         return Err(anyhow!("Binary file — cannot extract symbol"));
-    }
-    let source_text = String::from_utf8_lossy(&raw).into_owned();
+    };
 
     let cfg = language_config().read().unwrap();
     let Some(driver) = cfg.driver_for_path(&abs) else {
@@ -1854,9 +3368,8 @@ pub fn read_symbol_with_options(
     let source = source_text.as_bytes();
 
     let mut parser = driver.make_parser(&abs)?;
-    let tree = parser
-        .parse(&source_text, None)
-        .ok_or_else(|| anyhow!("Tree-sitter parse failed for {}", abs.display()))?;
+    let tree = parse_with_limits(&mut parser, &source_text)
+        .map_err(|e| anyhow!("Tree-sitter parse failed for {}: {e}", abs.display()))?;
     let root = tree.root_node();
 
     // ── Step 1: gather all named declarations with byte offsets ──────────
@@ -1882,12 +3395,70 @@ pub fn read_symbol_with_options(
         candidates.extend(impl_blocks);
     }
 
-    // ── Step 2: find best match (exact → case-insensitive), collect ALL instances ──
-    let mut all_matches: Vec<&(String, String, usize, usize)> = candidates
+    let by_line = matches!(selector, SymbolSelector::Line(_));
+    let (symbol_name, instance_index) = match selector {
+        SymbolSelector::Name {
+            name,
+            instance_index,
+        } => (name, instance_index),
+        SymbolSelector::Line(line) => {
+            let total_lines = source_text.lines().count();
+            if line == 0 || line > total_lines {
+                return Err(anyhow!(
+                    "Line {line} is out of range: {} has {total_lines} lines (line numbers are 1-based).",
+                    abs.display()
+                ));
+            }
+            let line_start = offsets[line - 1];
+            let next_line_start = offsets.get(line).copied().unwrap_or(source_text.len());
+            // Innermost = shortest range overlapping the line; ties keep the earlier candidate.
+            let Some(best) = candidates
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, _, start, end))| *start < next_line_start && line_start < *end)
+                .min_by_key(|(_, (_, _, start, end))| end - start)
+                .map(|(i, _)| i)
+            else {
+                return Err(anyhow!(
+                    "No declaration encloses line {line} in {}. The line may be top-level code, \
+                    an import or a comment; use cortex_code_explorer(action=map_overview) to see the declarations in this file.",
+                    abs.display()
+                ));
+            };
+            let name = candidates[best].0.as_str();
+            // Step 2 selects among same-name candidates in order, so pass its position.
+            let instance = candidates[..best].iter().filter(|c| c.0 == name).count();
+            (name, Some(instance))
+        }
+    };
+
+    // ── Step 2: find best match (exact → case-insensitive), collect ALL instances ──
+    let mut all_matches: Vec<&(String, String, usize, usize)> = candidates
         .iter()
         .filter(|(name, _, _, _)| name == symbol_name)
         .collect();
 
+    // `Foo::bar` / `Class.method` / `module.func`: keep the `bar` declarations whose
+    // enclosing impl/class/mod chain (or the file's own module) ends with the qualifier.
+    if all_matches.is_empty() {
+        if let Some((qualifiers, leaf)) = split_qualified_name(symbol_name) {
+            let module = file_module_name(&abs);
+            all_matches = candidates
+                .iter()
+                .filter(|(name, _, start, end)| {
+                    name == leaf
+                        && decl_node_at(root, source, *start, *end).is_some_and(|decl| {
+                            qualifier_matches(
+                                &scope_names(decl, source),
+                                &qualifiers,
+                                module.as_deref(),
+                            )
+                        })
+                })
+                .collect();
+        }
+    }
+
     if all_matches.is_empty() {
         all_matches = candidates
             .iter()
@@ -1937,19 +3508,17 @@ pub fn read_symbol_with_options(
         .filter(|&b| b == b'\n')
         .count()
         + 1;
-    let end_line = source_text[..*end_byte]
-        .bytes()
-        .filter(|&b| b == b'\n')
-        .count()
-        + 1;
+    // Skeleton ranges run to the start of the following line; that newline is not
+    // part of the last source line.
+    let end_line = start_line + body.trim_end_matches('\n').matches('\n').count();
     let symbol_lines = end_line.saturating_sub(start_line) + 1;
 
     // Build disambiguation preamble when multiple instances exist.
-    let disambiguation = if total_matches > 1 {
+    let disambiguation = if total_matches > 1 && !by_line {
         format!(
             "// ⚠️ Disambiguation: Found {total_matches} instances of `{name}` in this file. \
 Showing instance {} of {total_matches} (1-based). \
-Use `instance_index` param (0-based, 0..{}) to select a specific one. \
+Use `instance_index` param (0-based, 0..{}) or a qualified name (`Type::method`, `Class.method`) to select a specific one. \
 Consider using find_usages to inspect all occurrences across the codebase.\n",
             idx + 1,
             total_matches - 1,
@@ -1958,33 +3527,27 @@ Consider using find_usages to inspect all occurrences across the codebase.\n",
         String::new()
     };
 
-    let header = format!(
+    let mut header = format!(
         "{disambiguation}// {kind} `{name}` — {}:L{start_line}-L{end_line}\n",
         abs.display()
     );
 
+    // Hover-style context: enclosing mod/namespace/class chain + leading doc comment.
+    if let Some(decl) = decl_node_at(root, source, *start_byte, *end_byte) {
+        let scope = enclosing_scope_chain(decl, source);
+        if !scope.is_empty() {
+            header.push_str(&format!("// scope: {}\n", scope.join(" › ")));
+        }
+        for line in leading_doc_lines(decl, source, driver.name()) {
+            header.push_str(&format!("// doc: {line}\n"));
+        }
+    }
+
     let body = if skeleton_only {
-        // Reuse the same pruning logic as render_skeleton(), but apply only the
-        // replacements that fall within this symbol's byte range.
-        let mut ranges =
+        // Reuse the same pruning logic as render_skeleton().
+        let ranges =
             driver.body_prune_ranges(&abs, &source_text, source, root, language.clone())?;
-        ranges.retain(|(s, e, _)| {
-            // Keep only ranges that overlap with the extracted symbol region.
-            *e > *start_byte && *s < *end_byte
-        });
-        let adjusted = ranges
-            .into_iter()
-            .map(|(s, e, rep)| {
-                let s2 = s.max(*start_byte);
-                let e2 = e.min(*end_byte);
-                let ss = s2.saturating_sub(*start_byte);
-                let ee = e2.saturating_sub(*start_byte);
-                (ss, ee, rep)
-            })
-            .collect::<Vec<_>>();
-
-        let skeleton = apply_replacements(body, adjusted);
-        clean_skeleton_text(&abs, &skeleton)
+        skeleton_of_range(&abs, &source_text, &ranges, *start_byte, *end_byte)
     } else {
         body.to_string()
     };
@@ -2010,6 +3573,684 @@ Consider using find_usages to inspect all occurrences across the codebase.\n",
     }
 }
 
+/// Skeleton of `source_text[start..end]`: only the replacements from
+/// `body_prune_ranges` that fall within that byte range are applied.
+fn skeleton_of_range(
+    abs: &Path,
+    source_text: &str,
+    ranges: &[(usize, usize, String)],
+    start: usize,
+    end: usize,
+) -> String {
+    let adjusted = ranges
+        .iter()
+        // Keep only ranges that overlap with the extracted symbol region.
+        .filter(|(s, e, _)| *e > start && *s < end)
+        .map(|(s, e, rep)| {
+            let ss = (*s).max(start) - start;
+            let ee = (*e).min(end) - start;
+            (ss, ee, rep.clone())
+        })
+        .collect::<Vec<_>>();
+
+    let skeleton = apply_replacements(&source_text[start..end], adjusted);
+    clean_skeleton_text(abs, &skeleton, &SkeletonConfig::default())
+}
+
+// ---------------------------------------------------------------------------
+// deep_slice extension: focus_symbol
+// ---------------------------------------------------------------------------
+
+/// One declaration of a [`SymbolFocus`]: 1-based inclusive lines and its rendered text.
+#[derive(Debug, Clone)]
+pub struct FocusedDecl {
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+}
+
+/// A symbol with its neighbouring declarations, as used by `deep_slice` with
+/// `focus_symbol`.
+#[derive(Debug, Clone)]
+pub struct SymbolFocus {
+    /// The requested symbol, in full.
+    pub target: FocusedDecl,
+    /// Preceding siblings, skeletonized, nearest first.
+    pub before: Vec<FocusedDecl>,
+    /// Following siblings, skeletonized, nearest first.
+    pub after: Vec<FocusedDecl>,
+    pub total_lines: usize,
+}
+
+/// Find `symbol_name` in `source_text` and pick up to `context_symbols` sibling
+/// declarations on each side — declarations sharing its innermost enclosing symbol
+/// (e.g. the other methods of its class), or the file's top level. Siblings are
+/// skeletonized; the target is kept whole.
+pub fn symbol_focus(
+    path: &Path,
+    source_text: &str,
+    symbol_name: &str,
+    context_symbols: usize,
+) -> Result<SymbolFocus> {
+    let cfg = language_config().read().unwrap();
+    let Some(driver) = cfg.driver_for_path(path) else {
+        return Err(anyhow!(
+            "Unsupported file type: {}",
+            path.extension().and_then(|e| e.to_str()).unwrap_or("?")
+        ));
+    };
+    let language = driver.language_for_path(path);
+    let source = source_text.as_bytes();
+    let mut parser = driver.make_parser(path)?;
+    let tree = parse_with_limits(&mut parser, source_text)
+        .map_err(|e| anyhow!("Tree-sitter parse failed for {}: {e}", path.display()))?;
+    let root = tree.root_node();
+
+    let offsets = line_byte_offsets(source_text);
+    let byte_of = |line: usize| offsets.get(line).copied().unwrap_or(source_text.len());
+    // (name, first line, last line, is impl), 0-based; duplicates (e.g. export wrappers) dropped.
+    let mut decls: Vec<(String, usize, usize, bool)> = driver
+        .extract_skeleton(path, source, root, language.clone())?
+        .into_iter()
+        .map(|sym| (sym.name, sym.line as usize, sym.line_end as usize, false))
+        .collect();
+    // Rust `impl` blocks are not skeleton symbols, but they scope their methods.
+    if driver.name() == "rust" {
+        let line_of = |byte: usize| source_text[..byte].matches('\n').count();
+        for (name, _, start, end) in rust_impl_byte_ranges(source, root, &language) {
+            let last = line_of(end.saturating_sub(1).max(start));
+            decls.push((name, line_of(start), last, true));
+        }
+    }
+    decls.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(&a.2)));
+    decls.dedup_by(|a, b| a.1 == b.1 && a.2 == b.2);
+
+    // A type's impl block shares its name; prefer the type itself.
+    let Some(target) = decls
+        .iter()
+        .position(|d| d.0 == symbol_name && !d.3)
+        .or_else(|| decls.iter().position(|d| d.0 == symbol_name))
+    else {
+        return Err(anyhow!(
+            "Symbol `{symbol_name}` not found in {}. Use cortex_code_explorer(action=map_overview) \
+            to see the declarations in this file.",
+            path.display()
+        ));
+    };
+
+    // Innermost declaration strictly enclosing `i` (None = top level).
+    let container = |i: usize| {
+        let (s, e) = (decls[i].1, decls[i].2);
+        (0..decls.len())
+            .filter(|&j| j != i && decls[j].1 <= s && e <= decls[j].2)
+            .filter(|&j| (decls[j].1, decls[j].2) != (s, e))
+            .min_by_key(|&j| decls[j].2 - decls[j].1)
+    };
+    let scope = container(target);
+    let siblings: Vec<usize> = (0..decls.len())
+        .filter(|&j| j != target && container(j) == scope)
+        .collect();
+
+    let ranges = driver.body_prune_ranges(path, source_text, source, root, language)?;
+    let render = |i: usize, skeleton: bool| {
+        let (name, s, e, _) = &decls[i];
+        let (start, end) = (byte_of(*s), byte_of(*e + 1));
+        let text = if skeleton {
+            skeleton_of_range(path, source_text, &ranges, start, end)
+        } else {
+            source_text[start..end].to_string()
+        };
+        FocusedDecl {
+            name: name.clone(),
+            start_line: s + 1,
+            end_line: e + 1,
+            text,
+        }
+    };
+
+    let (t_start, t_end) = (decls[target].1, decls[target].2);
+    Ok(SymbolFocus {
+        target: render(target, false),
+        before: siblings
+            .iter()
+            .rev()
+            .filter(|&&j| decls[j].2 < t_start)
+            .take(context_symbols)
+            .map(|&j| render(j, true))
+            .collect(),
+        after: siblings
+            .iter()
+            .filter(|&&j| decls[j].1 > t_end)
+            .take(context_symbols)
+            .map(|&j| render(j, true))
+            .collect(),
+        total_lines: source_text.lines().count(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// read_symbol extension: expand_types
+// ---------------------------------------------------------------------------
+
+/// Token cap for the definitions appended by [`expand_symbol_types`].
+const EXPAND_TYPES_TOKEN_CAP: usize = 4_000;
+/// Max distinct type names pulled from one signature.
+const EXPAND_TYPES_MAX: usize = 8;
+
+/// Std / runtime container names that are never worth resolving.
+const EXPAND_TYPES_SKIP: &[&str] = &[
+    "Self", "String", "Vec", "Option", "Result", "Box", "Arc", "Rc", "RefCell", "Mutex", "RwLock",
+    "HashMap", "HashSet", "BTreeMap", "BTreeSet", "Path", "PathBuf", "Cow", "Some", "None", "Ok",
+    "Err", "Fn", "FnMut", "FnOnce", "Send", "Sync", "Sized", "Into", "From", "AsRef", "Iterator",
+    "Future", "Pin", "Duration", "Instant", "Promise", "Array", "Record", "Partial", "Readonly",
+    "Map", "Set", "Date", "Error", "Object", "List", "Dict", "Optional", "Any", "Union", "Tuple",
+    "Callable", "Iterable", "Sequence", "Mapping",
+];
+
+/// Skeletonized definitions of the types named in `symbol_name`'s signature, resolved
+/// in `path` first and then across `workspace_root`, stopping at
+/// [`EXPAND_TYPES_TOKEN_CAP`]. Backs `read_source(expand_types: true)`; returns an
+/// empty string when the signature references no resolvable types.
+pub fn expand_symbol_types(
+    path: &Path,
+    symbol_name: &str,
+    instance_index: Option<usize>,
+    workspace_root: &Path,
+) -> Result<String> {
+    let Some(source_text) = read_source_lossy(path)? else {
+        return Ok(String::new());
+    };
+    let syms = extract_symbols_from_source(path, &source_text);
+    let instances: Vec<&Symbol> = syms.iter().filter(|s| s.name == symbol_name).collect();
+    let idx = instance_index
+        .unwrap_or(0)
+        .min(instances.len().saturating_sub(1));
+    let Some(sym) = instances.get(idx) else {
+        return Ok(String::new());
+    };
+
+    let end = sym.end_byte.min(source_text.len());
+    let decl = source_text.get(sym.start_byte.min(end)..end).unwrap_or("");
+    let names = signature_type_names(signature_head(decl), symbol_name, EXPAND_TYPES_MAX);
+    if names.is_empty() {
+        return Ok(String::new());
+    }
+
+    let is_type_def = |s: &Symbol| matches!(s.category(), SymbolKind::Type | SymbolKind::Trait);
+
+    // 1) Same file.
+    let mut resolved: HashMap<String, PathBuf> = HashMap::new();
+    for name in &names {
+        if syms.iter().any(|s| &s.name == name && is_type_def(s)) {
+            resolved.insert(name.clone(), path.to_path_buf());
+        }
+    }
+
+    // 2) Workspace — one shared definition walk for every name still pending.
+    let pending: Vec<&str> = names
+        .iter()
+        .filter(|n| !resolved.contains_key(*n))
+        .map(String::as_str)
+        .collect();
+    for (name, (file, _)) in first_definitions(workspace_root, &pending, is_type_def) {
+        resolved.insert(name, file);
+    }
+
+    // 3) Render in signature order within the token cap.
+    let mut body = String::new();
+    let mut shown = 0usize;
+    let mut used_tokens = 0usize;
+    let mut over_cap: Vec<&str> = Vec::new();
+    for name in &names {
+        let Some(file) = resolved.get(name) else {
+            continue;
+        };
+        let Ok(def) = read_symbol_with_options(file, name, true, None) else {
+            continue;
+        };
+        let cost = def.len() / 4;
+        if used_tokens + cost > EXPAND_TYPES_TOKEN_CAP {
+            over_cap.push(name);
+            continue;
+        }
+        used_tokens += cost;
+        shown += 1;
+        body.push_str("\n\n");
+        body.push_str(&def);
+    }
+
+    let unresolved: Vec<&str> = names
+        .iter()
+        .filter(|n| !resolved.contains_key(*n))
+        .map(String::as_str)
+        .collect();
+    if shown == 0 && over_cap.is_empty() && unresolved.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut out = format!("\n\n// ── expand_types: {shown} related type(s) ──");
+    out.push_str(&body);
+    if !over_cap.is_empty() {
+        out.push_str(&format!(
+            "\n\n// ✂️ expand_types cap (~{EXPAND_TYPES_TOKEN_CAP} tokens) reached; not shown: {}",
+            over_cap.join(", ")
+        ));
+    }
+    if !unresolved.is_empty() {
+        out.push_str(&format!(
+            "\n// unresolved (external or not a type): {}",
+            unresolved.join(", ")
+        ));
+    }
+    Ok(out)
+}
+
+/// Declaration head of a symbol: the text before its body opens (`{` for brace
+/// languages, the line-ending `:` of a Python `def` / `class`).
+fn signature_head(decl: &str) -> &str {
+    let brace = decl.find('{');
+    let colon = decl.find(":\n").or_else(|| decl.find(":\r\n"));
+    let cut = match (brace, colon) {
+        (Some(a), Some(b)) => a.min(b),
+        (a, b) => a.or(b).unwrap_or(decl.len()),
+    };
+    &decl[..cut]
+}
+
+/// Capitalised identifiers in `head` (the cross-language convention for type names),
+/// minus `symbol_name`, generic single letters and [`EXPAND_TYPES_SKIP`]; at most `max`.
+fn signature_type_names(head: &str, symbol_name: &str, max: usize) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tok in head.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        if tok.len() < 2
+            || tok == symbol_name
+            || !tok.starts_with(|c: char| c.is_ascii_uppercase())
+            || EXPAND_TYPES_SKIP.contains(&tok)
+            || out.iter().any(|o| o == tok)
+        {
+            continue;
+        }
+        out.push(tok.to_string());
+        if out.len() >= max {
+            break;
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// read_symbol extension: include_tests
+// ---------------------------------------------------------------------------
+
+/// Token cap for the tests appended by [`find_symbol_tests`].
+const INCLUDE_TESTS_TOKEN_CAP: usize = 4_000;
+/// Max test functions appended for one symbol.
+const INCLUDE_TESTS_MAX: usize = 8;
+
+/// Skeletonized test functions across `workspace_root` whose name contains
+/// `symbol_name` (compared like `search_symbols`: case, `_` and `-` ignored), e.g.
+/// `parse_config_works` or `TestParseConfig` for `parse_config`. A function counts as a
+/// test when its file matches `test_patterns`, a `#[...test...]` attribute or
+/// `@...test...` decorator sits on it, or its name starts with `test`. Backs
+/// `read_source(include_tests: true)`, capped at [`INCLUDE_TESTS_TOKEN_CAP`].
+pub fn find_symbol_tests(
+    symbol_name: &str,
+    workspace_root: &Path,
+    test_patterns: &[String],
+) -> Result<String> {
+    let key = fuzzy_key(symbol_name.trim());
+    if key.is_empty() {
+        return Err(anyhow!("symbol_name is empty"));
+    }
+    let tests = TestFilter::new(test_patterns, IncludeTests::All);
+
+    let mut found: Vec<(String, usize, PathBuf, String, usize)> = Vec::new();
+    for entry in build_walker(workspace_root, GitignoreMode::Respect, Default::default()).flatten()
+    {
+        let file = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let supported = language_config()
+            .read()
+            .unwrap()
+            .driver_for_path(file)
+            .is_some();
+        if !supported {
+            continue;
+        }
+        let Ok(Some(text)) = read_source_lossy(file) else {
+            continue;
+        };
+        if !fuzzy_key(&text).contains(&key) {
+            continue;
+        }
+        let rel = normalize_path_for_output(file.strip_prefix(workspace_root).unwrap_or(file));
+        let test_file = tests.is_test(&rel);
+        let lines: Vec<&str> = text.lines().collect();
+        let syms = extract_symbols_from_source(file, &text);
+        for (i, sym) in syms.iter().enumerate() {
+            if !matches!(sym.category(), SymbolKind::Function | SymbolKind::Method) {
+                continue;
+            }
+            let name_key = fuzzy_key(&sym.name);
+            if !name_key.contains(&key) {
+                continue;
+            }
+            if !(test_file
+                || name_key.starts_with("test")
+                || has_test_marker(&lines, sym.line as usize))
+            {
+                continue;
+            }
+            let instance = syms[..i].iter().filter(|s| s.name == sym.name).count();
+            found.push((
+                rel.clone(),
+                sym.line as usize + 1,
+                file.to_path_buf(),
+                sym.name.clone(),
+                instance,
+            ));
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    if found.is_empty() {
+        return Ok(format!(
+            "\n\n// include_tests: no test functions named after `{symbol_name}` found"
+        ));
+    }
+
+    let total = found.len();
+    let mut body = String::new();
+    let mut shown = 0usize;
+    let mut used_tokens = 0usize;
+    let mut not_shown: Vec<String> = Vec::new();
+    for (rel, line, file, name, instance) in found {
+        let def = (shown < INCLUDE_TESTS_MAX)
+            .then(|| read_symbol_with_options(&file, &name, true, Some(instance)).ok())
+            .flatten();
+        let Some(def) = def.filter(|d| used_tokens + d.len() / 4 <= INCLUDE_TESTS_TOKEN_CAP) else {
+            not_shown.push(format!("{rel}:{line} {name}"));
+            continue;
+        };
+        used_tokens += def.len() / 4;
+        shown += 1;
+        body.push_str(&format!("\n\n// {rel}:{line}\n{def}"));
+    }
+
+    let mut out =
+        format!("\n\n// ── include_tests: {shown} of {total} test(s) for `{symbol_name}` ──");
+    out.push_str(&body);
+    if !not_shown.is_empty() {
+        out.push_str(&format!(
+            "\n\n// ✂️ include_tests cap ({INCLUDE_TESTS_MAX} tests / ~{INCLUDE_TESTS_TOKEN_CAP} tokens) reached; not shown: {}",
+            not_shown.join(", ")
+        ));
+    }
+    Ok(out)
+}
+
+/// Whether the attribute / decorator lines right above 0-based `line` (or on it, when the
+/// declaration starts at its attributes) mark a test: `#[test]`, `#[tokio::test]`,
+/// `@pytest.mark...`.
+fn has_test_marker(lines: &[&str], line: usize) -> bool {
+    let Some(head) = lines.get(..=line) else {
+        return false;
+    };
+    head.iter()
+        .rev()
+        .map(|l| l.trim())
+        .enumerate()
+        .take_while(|(i, l)| *i == 0 || l.starts_with("#[") || l.starts_with('@'))
+        .any(|(_, l)| (l.starts_with("#[") || l.starts_with('@')) && l.contains("test"))
+}
+
+/// Outermost AST node that starts at the first non-blank byte of `[start, end)`.
+fn decl_node_at<'t>(root: Node<'t>, source: &[u8], start: usize, end: usize) -> Option<Node<'t>> {
+    let mut s = start;
+    while s < end && s < source.len() && source[s].is_ascii_whitespace() {
+        s += 1;
+    }
+    let mut node = root.named_descendant_for_byte_range(s, s)?;
+    while let Some(parent) = node.parent() {
+        // A Python `block` starts at its first statement; that statement is the decl.
+        if parent.id() == root.id()
+            || parent.start_byte() != node.start_byte()
+            || parent.kind() == "block"
+        {
+            break;
+        }
+        node = parent;
+    }
+    Some(node)
+}
+
+/// Chain of enclosing `mod` / `impl` / `trait` / `class` / `namespace` nodes,
+/// outermost first (e.g. `["mod server", "impl ServerState"]`).
+fn enclosing_scope_chain(decl: Node, source: &[u8]) -> Vec<String> {
+    let field = |n: Node, f: &str| {
+        n.child_by_field_name(f)
+            .map(|c| node_text(source, c).to_string())
+    };
+
+    let mut chain: Vec<String> = Vec::new();
+    let mut cur = decl.parent();
+    while let Some(n) = cur {
+        let label = match n.kind() {
+            "mod_item" => field(n, "name").map(|x| format!("mod {x}")),
+            "impl_item" => match (field(n, "trait"), field(n, "type")) {
+                (Some(t), Some(ty)) => Some(format!("impl {t} for {ty}")),
+                (None, Some(ty)) => Some(format!("impl {ty}")),
+                _ => None,
+            },
+            "trait_item" => field(n, "name").map(|x| format!("trait {x}")),
+            "class_declaration"
+            | "abstract_class_declaration"
+            | "class_definition"
+            | "class_specifier"
+            | "class" => field(n, "name").map(|x| format!("class {x}")),
+            "interface_declaration" => field(n, "name").map(|x| format!("interface {x}")),
+            "internal_module" | "namespace_definition" | "namespace_declaration" => {
+                field(n, "name").map(|x| format!("namespace {x}"))
+            }
+            _ => None,
+        };
+        chain.extend(label);
+        cur = n.parent();
+    }
+    chain.reverse();
+    chain
+}
+
+/// Splits `Foo::bar` / `Class.method` / `pkg.module.func` into qualifiers and the
+/// bare name. `None` for unqualified names or empty segments.
+fn split_qualified_name(name: &str) -> Option<(Vec<&str>, &str)> {
+    let sep = if name.contains("::") {
+        "::"
+    } else if name.contains('.') {
+        "."
+    } else {
+        return None;
+    };
+    let mut parts: Vec<&str> = name.split(sep).map(str::trim).collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    let leaf = parts.pop()?;
+    Some((parts, leaf))
+}
+
+/// Module name a file declares implicitly: its stem, or the directory name for
+/// `mod.rs` / `__init__.py` / `index.ts`-style entry files.
+fn file_module_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "__init__" | "index") {
+        return path
+            .parent()
+            .and_then(|d| d.file_name())
+            .and_then(|n| n.to_str())
+            .map(str::to_string);
+    }
+    Some(stem.to_string())
+}
+
+/// Names each enclosing scope of `decl` answers to, outermost first — the same
+/// nodes as [`enclosing_scope_chain`]. An `impl Display for Foo<T>` level answers
+/// to both `Foo` and `Display`.
+fn scope_names(decl: Node, source: &[u8]) -> Vec<Vec<String>> {
+    // `crate::x::Foo<T>` → `Foo`.
+    let bare = |n: Node| {
+        let t = node_text(source, n);
+        let t = t.split('<').next().unwrap_or(t).trim();
+        t.rsplit("::").next().unwrap_or(t).trim().to_string()
+    };
+
+    let mut levels: Vec<Vec<String>> = Vec::new();
+    let mut cur = decl.parent();
+    while let Some(n) = cur {
+        let names: Vec<String> = match n.kind() {
+            "impl_item" => ["type", "trait"]
+                .iter()
+                .filter_map(|f| n.child_by_field_name(f))
+                .map(bare)
+                .collect(),
+            "mod_item"
+            | "trait_item"
+            | "class_declaration"
+            | "abstract_class_declaration"
+            | "class_definition"
+            | "class_specifier"
+            | "class"
+            | "interface_declaration"
+            | "internal_module"
+            | "namespace_definition"
+            | "namespace_declaration" => n
+                .child_by_field_name("name")
+                .map(bare)
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() {
+            levels.push(names);
+        }
+        cur = n.parent();
+    }
+    levels.reverse();
+    levels
+}
+
+/// Whether `qualifiers` name the innermost scopes of a declaration. Qualifiers
+/// left over once the scopes run out must end with the file's own module
+/// (`server.handle`, `crate::server::Foo::bar`).
+fn qualifier_matches(levels: &[Vec<String>], qualifiers: &[&str], module: Option<&str>) -> bool {
+    let mut levels = levels.iter().rev();
+    let mut rest = qualifiers;
+    while let Some((q, head)) = rest.split_last() {
+        match levels.next() {
+            Some(names) if names.iter().any(|n| n == q) => rest = head,
+            Some(_) => return false,
+            None => return module == Some(*q),
+        }
+    }
+    true
+}
+
+/// Doc comment attached to `decl`, with comment markers stripped.
+///
+/// Rust: contiguous `///` / `/** */` lines above the item (attributes skipped).
+/// TS/JS: the `/** ... */` JSDoc block above the declaration (or its `export`).
+/// Python: the docstring (first statement of the body).
+/// Other languages: any contiguous comment block directly above.
+fn leading_doc_lines(decl: Node, source: &[u8], lang: &str) -> Vec<String> {
+    const MAX_DOC_LINES: usize = 12;
+
+    let raw: Vec<String> = if lang == "python" {
+        decl.child_by_field_name("body")
+            .and_then(|b| b.named_child(0))
+            .filter(|st| st.kind() == "expression_statement")
+            .and_then(|st| st.named_child(0))
+            .filter(|lit| lit.kind() == "string")
+            .map(|lit| {
+                let t = node_text(source, lit).trim_start_matches(['r', 'R', 'u', 'U']);
+                let t = t.trim_matches('"').trim_matches('\'');
+                vec![t.to_string()]
+            })
+            .unwrap_or_default()
+    } else {
+        let mut anchor = decl;
+        if let Some(p) = decl.parent() {
+            if matches!(p.kind(), "export_statement" | "decorated_definition") {
+                anchor = p;
+            }
+        }
+
+        let mut blocks: Vec<String> = Vec::new();
+        let mut next_row = anchor.start_position().row;
+        let mut cur = anchor.prev_sibling();
+        while let Some(sib) = cur {
+            let kind = sib.kind();
+            if lang == "rust" && kind == "attribute_item" {
+                next_row = sib.start_position().row;
+                cur = sib.prev_sibling();
+                continue;
+            }
+            if !kind.contains("comment") {
+                break;
+            }
+            // Line comments may include their trailing newline.
+            let end = sib.end_position();
+            let end_row = if end.column == 0 && end.row > sib.start_position().row {
+                end.row - 1
+            } else {
+                end.row
+            };
+            if end_row + 1 < next_row {
+                break; // blank line gap — not attached to this item
+            }
+            let text = node_text(source, sib);
+            let is_doc = match lang {
+                "rust" => {
+                    (text.starts_with("///") && !text.starts_with("////"))
+                        || text.starts_with("/**")
+                }
+                "typescript" => text.starts_with("/**"),
+                _ => true,
+            };
+            if !is_doc {
+                break;
+            }
+            blocks.push(text.to_string());
+            next_row = sib.start_position().row;
+            cur = sib.prev_sibling();
+        }
+        blocks.reverse();
+        blocks
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    for block in &raw {
+        for line in block.lines() {
+            let mut l = line.trim();
+            for marker in ["///", "/**", "*/", "//", "*", "#"] {
+                l = l.strip_prefix(marker).unwrap_or(l);
+            }
+            let l = l.trim_end_matches("*/").trim();
+            if l.is_empty() && lines.is_empty() {
+                continue;
+            }
+            lines.push(l.to_string());
+        }
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.truncate(MAX_DOC_LINES);
+    lines
+}
+
 /// Compute byte offset of the start of each line (0-indexed).
 fn line_byte_offsets(text: &str) -> Vec<usize> {
     let mut offsets = vec![0usize];
@@ -2065,63 +4306,799 @@ fn find_named_decls_raw(
             out.push((name_text, def_start, def_end));
         }
     }
-    out
+    out
+}
+
+/// Find Rust `impl` blocks by byte range.
+/// Returns `(name, "impl", start_byte, end_byte)` tuples.
+fn rust_impl_byte_ranges(
+    source: &[u8],
+    root: Node,
+    language: &Language,
+) -> Vec<(String, String, usize, usize)> {
+    let mut out: Vec<(String, String, usize, usize)> = Vec::new();
+
+    // impl Foo { ... }
+    for (name, start, end) in find_named_decls_raw(
+        source,
+        root,
+        language,
+        r#"(impl_item type: (type_identifier) @name) @def"#,
+    ) {
+        out.push((name, "impl".to_string(), start, end));
+    }
+
+    // impl<T> Foo<T> { ... }
+    for (name, start, end) in find_named_decls_raw(
+        source,
+        root,
+        language,
+        r#"(impl_item type: (generic_type type: (type_identifier) @name)) @def"#,
+    ) {
+        out.push((name, "impl".to_string(), start, end));
+    }
+
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Walk configuration shared by map_repo / find_usages
+// ---------------------------------------------------------------------------
+
+/// How directory walks treat `.gitignore` (and `.ignore`, git excludes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GitignoreMode {
+    /// Honour ignore files (default).
+    #[default]
+    Respect,
+    /// Disable ignore files, but still hard-exclude VCS internals (`.git`, `.hg`, `.svn`)
+    /// and the caller-supplied exclude list. Picks up gitignored generated code without
+    /// dragging in the whole `node_modules` / `target` universe.
+    ExcludeVcs,
+    /// Disable ignore files entirely (legacy `ignore_gitignore=true`).
+    Off,
+}
+
+const VCS_DIR_NAMES: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Build a walker for `abs_dir` honouring `mode` and skipping any directory whose
+/// name is in `excluded_dirs`.
+fn build_walker(
+    abs_dir: &Path,
+    mode: GitignoreMode,
+    excluded_dirs: std::collections::HashSet<String>,
+) -> ignore::Walk {
+    build_walker_to_depth(abs_dir, mode, excluded_dirs, None)
+}
+
+/// [`build_walker`] that stops descending `max_depth` levels below `abs_dir`
+/// (files directly in `abs_dir` are depth 1).
+fn build_walker_to_depth(
+    abs_dir: &Path,
+    mode: GitignoreMode,
+    excluded_dirs: std::collections::HashSet<String>,
+    max_depth: Option<usize>,
+) -> ignore::Walk {
+    ignore::WalkBuilder::new(abs_dir)
+        .standard_filters(mode == GitignoreMode::Respect)
+        .hidden(true)
+        .max_depth(max_depth)
+        .filter_entry(move |dent| {
+            if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+                if let Some(name) = dent.path().file_name().and_then(|s| s.to_str()) {
+                    if excluded_dirs.contains(name) {
+                        return false;
+                    }
+                    if mode == GitignoreMode::ExcludeVcs && VCS_DIR_NAMES.contains(&name) {
+                        return false;
+                    }
+                }
+            }
+            true
+        })
+        .build()
+}
+
+/// `.gitattributes` `linguist-generated` / `linguist-vendored` hints for a walk rooted
+/// at `abs_dir`. Like ignore files they only apply in [`GitignoreMode::Respect`]; the
+/// other modes exist precisely to surface generated code. Definition lookups don't
+/// consult them, since a handwritten call site may resolve into generated code.
+fn linguist_hints(abs_dir: &Path, mode: GitignoreMode) -> LinguistAttributes {
+    if mode == GitignoreMode::Respect {
+        LinguistAttributes::discover(abs_dir)
+    } else {
+        LinguistAttributes::default()
+    }
+}
+
+/// Drain a walker into the list of regular files it yields plus its error count.
+fn collect_walk_files(walker: ignore::Walk) -> (Vec<PathBuf>, usize) {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut errors: usize = 0;
+    for entry_result in walker {
+        match entry_result {
+            Ok(entry) if entry.path().is_file() => files.push(entry.into_path()),
+            Ok(_) => {}
+            Err(_) => errors += 1,
+        }
+    }
+    (files, errors)
+}
+
+// ---------------------------------------------------------------------------
+// Tool: find_usages — The AST-Tracer
+// ---------------------------------------------------------------------------
+
+/// Find all semantic usages of `symbol_name` across code files under `target_dir`.
+///
+/// Algorithm:
+///  1. Walk `target_dir` with `ignore::WalkBuilder` (honours `.gitignore` unless
+///     `ignore_mode` says otherwise).
+///  2. For each supported-language file containing `symbol_name` as a substring
+///     (fast pre-filter), parse with tree-sitter.
+///  3. Recursively visit AST leaf nodes: collect `identifier`, `type_identifier`,
+///     `field_identifier`, `property_identifier` nodes whose text == `symbol_name`.
+///  4. Prune entire comment / string subtrees — zero false positives from docs or
+///     string constants.
+///  5. Return a dense listing with 2-line context windows.
+///
+/// Works even when the project currently **fails to compile** because it uses the
+/// raw AST, not an LSP or compiler. Paths are printed per `paths`.
+pub fn find_usages(
+    target_dir: &Path,
+    symbol_name: &str,
+    ignore_mode: GitignoreMode,
+    exclude_dirs: &[String],
+    paths: PathStyle,
+) -> Result<String> {
+    find_usages_with_sort(
+        target_dir,
+        symbol_name,
+        ignore_mode,
+        exclude_dirs,
+        paths,
+        UsageSort::Path,
+    )
+}
+
+/// Order of the hits within each [`find_usages_with_sort`] category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageSort {
+    /// File path, then line (default; deterministic).
+    #[default]
+    Path,
+    /// Most relevant first: files closest to a definition of the symbol (the
+    /// defining file, then its directory, then its parents), ties broken by the
+    /// file's PageRank in the import graph (see [`crate::mapper::centrality`]),
+    /// then path and line. Costs an extra import-graph pass over `target_dir`.
+    Relevance,
+}
+
+/// [`find_usages`] with the hits in each category ordered by `sort`.
+pub fn find_usages_with_sort(
+    target_dir: &Path,
+    symbol_name: &str,
+    ignore_mode: GitignoreMode,
+    exclude_dirs: &[String],
+    paths: PathStyle,
+    sort: UsageSort,
+) -> Result<String> {
+    use std::collections::BTreeMap;
+
+    let mut all_results: Vec<UsageMatch> = Vec::new();
+    // Display path -> walked path, for ranking by relevance.
+    let mut abs_by_file: HashMap<String, PathBuf> = HashMap::new();
+    let abs_dir = walk_usages(
+        target_dir,
+        symbol_name,
+        ignore_mode,
+        exclude_dirs,
+        true,
+        true,
+        |path, source_text, hits, syms| {
+            let text_lines: Vec<&str> = source_text.lines().collect();
+            let display_path = paths.show(path);
+            abs_by_file.insert(display_path.clone(), path.to_path_buf());
+
+            for (row_0, category) in hits {
+                // A definition is not "in" itself; a nested one is in its parent.
+                let enclosing = enclosing_callable(
+                    syms.iter()
+                        .filter(|s| !(s.line == row_0 && s.name == symbol_name)),
+                    row_0,
+                )
+                .map(|s| format!("{} {}()", s.kind, s.name));
+                all_results.push(UsageMatch {
+                    category,
+                    file: display_path.clone(),
+                    line_1: row_0 + 1,
+                    enclosing,
+                    context: extract_context_lines(&text_lines, row_0 as usize, 2),
+                });
+            }
+        },
+    )?;
+
+    if all_results.is_empty() {
+        return Ok(format!(
+            "No usages of `{}` found in {}.",
+            symbol_name,
+            abs_dir.display()
+        ));
+    }
+
+    let relevance = match sort {
+        UsageSort::Path => HashMap::new(),
+        UsageSort::Relevance => usage_relevance(&abs_dir, &all_results, &abs_by_file),
+    };
+    let sort_items = |items: &mut Vec<UsageMatch>| {
+        items.sort_by(|a, b| {
+            let rank_a = relevance.get(&a.file).copied().unwrap_or_default();
+            let rank_b = relevance.get(&b.file).copied().unwrap_or_default();
+            rank_b
+                .proximity
+                .cmp(&rank_a.proximity)
+                .then_with(|| rank_b.pagerank.total_cmp(&rank_a.pagerank))
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line_1.cmp(&b.line_1))
+        })
+    };
+
+    let mut by_cat: BTreeMap<&'static str, Vec<UsageMatch>> = BTreeMap::new();
+    for m in all_results {
+        by_cat.entry(m.category).or_default().push(m);
+    }
+
+    let order: [&'static str; 7] = [
+        "Definitions",
+        "Calls",
+        "Type Refs",
+        "Field Accesses",
+        "Field Inits",
+        "Other",
+        "Lexical",
+    ];
+    let total: usize = by_cat.values().map(|v| v.len()).sum();
+    let mut out = match sort {
+        UsageSort::Path => format!("{} usage(s) of `{symbol_name}` found:\n\n", total),
+        UsageSort::Relevance => {
+            format!("{total} usage(s) of `{symbol_name}` found, most relevant first:\n\n")
+        }
+    };
+
+    for cat in order {
+        let Some(mut items) = by_cat.remove(cat) else {
+            continue;
+        };
+        sort_items(&mut items);
+        out.push_str(&format!("### {cat} ({})\n\n", items.len()));
+        if cat == "Lexical" {
+            out.push_str(
+                "_Lower confidence: word matches in files no language driver parses; \
+                 comments and strings are only skipped heuristically._\n\n",
+            );
+        }
+        for m in &items {
+            out.push_str(&m.header());
+            out.push_str(&format!("Context:\n{}\n\n", m.context));
+        }
+    }
+
+    // Any future categories (shouldn't happen) — append deterministically.
+    for (cat, mut items) in by_cat {
+        sort_items(&mut items);
+        out.push_str(&format!("### {cat} ({})\n\n", items.len()));
+        for m in &items {
+            out.push_str(&m.header());
+            out.push_str(&format!("Context:\n{}\n\n", m.context));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Rank of a file's hits under [`UsageSort::Relevance`]; higher is more relevant.
+#[derive(Debug, Clone, Copy, Default)]
+struct UsageRelevance {
+    /// `usize::MAX` for a file defining the symbol, otherwise the number of leading
+    /// directory components shared with the nearest defining file.
+    proximity: usize,
+    pagerank: f64,
+}
+
+/// [`UsageRelevance`] per display path in `results`. Definitions come from the
+/// `Definitions` hits; PageRank from [`crate::mapper::centrality`] over `abs_dir`
+/// (files outside its import graph rank 0).
+fn usage_relevance(
+    abs_dir: &Path,
+    results: &[UsageMatch],
+    abs_by_file: &HashMap<String, PathBuf>,
+) -> HashMap<String, UsageRelevance> {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let root = canonical(abs_dir);
+    let pagerank: HashMap<PathBuf, f64> = crate::mapper::centrality(&root, &root, usize::MAX)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|f| (root.join(&f.path), f.pagerank))
+        .collect();
+    let def_files: Vec<PathBuf> = results
+        .iter()
+        .filter(|m| m.category == "Definitions")
+        .filter_map(|m| abs_by_file.get(&m.file))
+        .map(|p| canonical(p))
+        .collect();
+
+    abs_by_file
+        .iter()
+        .map(|(file, abs)| {
+            let abs = canonical(abs);
+            let dir = abs.parent().unwrap_or(&abs);
+            let proximity = def_files
+                .iter()
+                .map(|def| {
+                    if *def == abs {
+                        return usize::MAX;
+                    }
+                    let def_dir = def.parent().unwrap_or(def);
+                    dir.components()
+                        .zip(def_dir.components())
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                })
+                .max()
+                .unwrap_or(0);
+            let rank = UsageRelevance {
+                proximity,
+                pagerank: pagerank.get(&abs).copied().unwrap_or(0.0),
+            };
+            (file.clone(), rank)
+        })
+        .collect()
+}
+
+/// Shared walk behind [`find_usages`] and [`usage_counts`]: calls `on_file` with
+/// each file's path, text and sorted, deduplicated `(row_0, category)` hits, plus
+/// the file's `extract_skeleton` symbols when `with_symbols` is set (empty
+/// otherwise, sparing callers that only count). With `lexical`, text files that no
+/// driver parses are searched by [`lexical_refs`] instead of skipped; their hits
+/// are all `"Lexical"` and carry no symbols. Returns the absolute search root.
+fn walk_usages(
+    target_dir: &Path,
+    symbol_name: &str,
+    ignore_mode: GitignoreMode,
+    exclude_dirs: &[String],
+    with_symbols: bool,
+    lexical: bool,
+    mut on_file: impl FnMut(&Path, &str, Vec<(u32, &'static str)>, &[Symbol]),
+) -> Result<PathBuf> {
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get cwd")?
+            .join(target_dir)
+    };
+
+    let excluded_dir_set: std::collections::HashSet<String> = exclude_dirs
+        .iter()
+        .map(|s| s.trim().trim_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let walker = build_walker(&abs_dir, ignore_mode, excluded_dir_set);
+    let linguist = linguist_hints(&abs_dir, ignore_mode);
+
+    let cfg_lock = language_config().read().unwrap();
+    let cfg = &*cfg_lock;
+
+    for entry_result in walker {
+        let Ok(entry) = entry_result else { continue };
+        let path = entry.path();
+        if !path.is_file() || linguist.classify(path).is_some() {
+            continue;
+        }
+
+        // Files without a driver are only read for the lexical fallback.
+        let driver = cfg.driver_for_path(path);
+        if driver.is_none() && (!lexical || !lexical_candidate(path)) {
+            continue;
+        }
+
+        let Ok(raw) = std::fs::read(path) else {
+            continue;
+        };
+        if raw.contains(&0u8) {
+            continue; // binary
+        }
+        let Ok(source_text) = std::str::from_utf8(&raw) else {
+            continue;
+        };
+
+        // Hot path: fast substring pre-filter before paying the tree-sitter parse cost.
+        if !source_text.contains(symbol_name) {
+            continue;
+        }
+
+        let Some(driver) = driver else {
+            let limit = parse_limits().read().unwrap().max_parse_bytes;
+            if limit > 0 && source_text.len() > limit {
+                continue;
+            }
+            let hits = lexical_refs(source_text, symbol_name);
+            if !hits.is_empty() {
+                on_file(path, source_text, hits, &[]);
+            }
+            continue;
+        };
+        let source = source_text.as_bytes();
+
+        let mut parser = match driver.make_parser(path) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("[cortexast] parser init failed for {}: {e}", path.display());
+                continue;
+            }
+        };
+        let Ok(tree) = parse_with_limits(&mut parser, source_text) else {
+            continue;
+        };
+        let root = tree.root_node();
+
+        // AST-level reference collection — excludes comments and string literals.
+        let mut hits: Vec<(u32, &'static str)> = Vec::new();
+        collect_identifier_refs(root, source, symbol_name, &mut hits);
+
+        if hits.is_empty() {
+            continue;
+        }
+
+        hits.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        hits.dedup();
+
+        let syms: Vec<Symbol> = if with_symbols {
+            let language = driver.language_for_path(path);
+            driver
+                .extract_skeleton(path, source, root, language)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        on_file(path, source_text, hits, &syms);
+    }
+
+    Ok(abs_dir)
+}
+
+/// Per-file usage counts for `symbol_name` under `target_dir`: the same AST
+/// matching as [`find_usages`] without context windows, aggregated per file like
+/// [`propagation_checklist`]. Paths are printed per `paths`; sorted by count
+/// (descending), then path.
+pub fn usage_counts(
+    target_dir: &Path,
+    symbol_name: &str,
+    ignore_mode: GitignoreMode,
+    exclude_dirs: &[String],
+    paths: PathStyle,
+) -> Result<Vec<(String, usize)>> {
+    use std::collections::BTreeMap;
+
+    let mut by_file: BTreeMap<PathBuf, usize> = BTreeMap::new();
+    walk_usages(
+        target_dir,
+        symbol_name,
+        ignore_mode,
+        exclude_dirs,
+        false,
+        false,
+        |path, _, hits, _| {
+            *by_file.entry(path.to_path_buf()).or_insert(0) += hits.len();
+        },
+    )?;
+
+    let mut counts: Vec<(String, usize)> = by_file
+        .into_iter()
+        .map(|(path, n)| (paths.show(&path), n))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
+
+/// Compact `path: count` listing of [`usage_counts`] for the `counts_only` mode of
+/// `find_usages`.
+pub fn format_usage_counts(symbol_name: &str, counts: &[(String, usize)]) -> String {
+    if counts.is_empty() {
+        return format!("No usages of `{symbol_name}` found.");
+    }
+    let total: usize = counts.iter().map(|(_, n)| n).sum();
+    let mut out = format!(
+        "{total} usage(s) of `{symbol_name}` in {} file(s):\n\n",
+        counts.len()
+    );
+    for (path, n) in counts {
+        out.push_str(&format!("{path}: {n}\n"));
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tool: find_definition — AST-walk fallback for the symbol index
+// ---------------------------------------------------------------------------
+
+/// Every declaration named exactly `symbol_name` under `target_dir`, as
+/// `(repo-relative file, 1-based line, raw kind)` sorted by file then line.
+///
+/// This is the ground-truth path used when the persistent symbol index
+/// (`symbols.json`) is cold or stale: a full walk with a substring pre-filter
+/// before any file is parsed.
+pub fn find_definition_sites(
+    repo_root: &Path,
+    target_dir: &Path,
+    symbol_name: &str,
+) -> Result<Vec<(String, u32, String)>> {
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        repo_root.join(target_dir)
+    };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
+
+    let mut sites: Vec<(String, u32, String)> = Vec::new();
+    walk_definitions(&abs_dir, &[symbol_name], |path, sym| {
+        let rel = normalize_path_for_output(path.strip_prefix(repo_root).unwrap_or(path));
+        sites.push((rel, sym.line + 1, sym.kind.clone()));
+        true
+    });
+    sites.sort();
+    Ok(sites)
+}
+
+/// The definition lookup shared by `find_definition`, `dependencies` and
+/// `expand_types`: walks `abs_dir` (gitignore-aware, supported languages only) and
+/// calls `on_def` for every declaration whose name is in `names`. Files that do not
+/// mention any of the names are skipped before parsing. `on_def` returns `false` to
+/// stop the walk.
+fn walk_definitions(
+    abs_dir: &Path,
+    names: &[&str],
+    mut on_def: impl FnMut(&Path, &Symbol) -> bool,
+) {
+    for path in definition_candidate_files(abs_dir) {
+        let Ok(Some(text)) = read_source_lossy(&path) else {
+            continue;
+        };
+        if !names.iter().any(|n| text.contains(n)) {
+            continue;
+        }
+        for sym in extract_symbols_from_source(&path, &text) {
+            if names.contains(&sym.name.as_str()) && !on_def(&path, &sym) {
+                return;
+            }
+        }
+    }
+}
+
+/// The files [`find_definition_sites`] would parse under `abs_dir`: gitignore-aware,
+/// supported languages only. Lets a caller check an index covers the same scope.
+pub fn definition_candidate_files(abs_dir: &Path) -> Vec<PathBuf> {
+    let cfg = language_config().read().unwrap();
+    build_walker(abs_dir, GitignoreMode::Respect, Default::default())
+        .flatten()
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .map(ignore::DirEntry::into_path)
+        .filter(|p| cfg.driver_for_path(p).is_some())
+        .collect()
+}
+
+/// First declaration of each name in `names` under `abs_dir` that `accept` admits,
+/// via [`walk_definitions`]; the walk stops once every name is resolved.
+fn first_definitions(
+    abs_dir: &Path,
+    names: &[&str],
+    accept: impl Fn(&Symbol) -> bool,
+) -> HashMap<String, (PathBuf, Symbol)> {
+    let mut found: HashMap<String, (PathBuf, Symbol)> = HashMap::new();
+    if names.is_empty() {
+        return found;
+    }
+    walk_definitions(abs_dir, names, |path, sym| {
+        if accept(sym) && !found.contains_key(&sym.name) {
+            found.insert(sym.name.clone(), (path.to_path_buf(), sym.clone()));
+        }
+        found.len() < names.len()
+    });
+    found
+}
+
+// ---------------------------------------------------------------------------
+// Tool: search_symbols — fuzzy lookup over the symbol table
+// ---------------------------------------------------------------------------
+
+/// One declaration matched by [`search_symbols_fuzzy`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolMatch {
+    pub name: String,
+    /// Raw kind from the language driver (`function`, `struct`, `class`, ...).
+    pub kind: String,
+    /// Relative to `target_dir`.
+    pub file: String,
+    /// 1-based.
+    pub line: u32,
+}
+
+/// Declarations under `target_dir` whose names look like `pattern`, best first.
+///
+/// Lexical matching over extracted symbol names (no embeddings), ignoring case, `_`
+/// and `-` so `processPayment` finds `process_payment`: exact names rank first, then
+/// prefix and substring matches, then names within a small edit distance of
+/// `pattern`. Ties go to the name closest in length, then to file and line.
+pub fn search_symbols_fuzzy(target_dir: &Path, pattern: &str) -> Result<Vec<SymbolMatch>> {
+    let needle = fuzzy_key(pattern.trim());
+    if needle.is_empty() {
+        return Err(anyhow!("pattern is empty"));
+    }
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get cwd")?
+            .join(target_dir)
+    };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
+    // One typo per four characters, at least one.
+    let max_distance = (needle.chars().count() / 4).max(1);
+
+    let mut ranked: Vec<((u8, usize, usize), SymbolMatch)> = Vec::new();
+    for entry in build_walker(&abs_dir, GitignoreMode::Respect, Default::default()).flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let supported = language_config()
+            .read()
+            .unwrap()
+            .driver_for_path(path)
+            .is_some();
+        if !supported {
+            continue;
+        }
+        let Ok(Some(text)) = read_source_lossy(path) else {
+            continue;
+        };
+        let rel = normalize_path_for_output(path.strip_prefix(&abs_dir).unwrap_or(path));
+        for sym in extract_symbols_from_source(path, &text) {
+            let Some(rank) = fuzzy_rank(&needle, &fuzzy_key(&sym.name), max_distance) else {
+                continue;
+            };
+            ranked.push((
+                rank,
+                SymbolMatch {
+                    name: sym.name,
+                    kind: sym.kind,
+                    file: rel.clone(),
+                    line: sym.line + 1,
+                },
+            ));
+        }
+    }
+    ranked.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then_with(|| a.1.file.cmp(&b.1.file))
+            .then_with(|| a.1.line.cmp(&b.1.line))
+    });
+    ranked.dedup_by(|a, b| a.1.name == b.1.name && a.1.file == b.1.file && a.1.line == b.1.line);
+    Ok(ranked.into_iter().map(|(_, m)| m).collect())
+}
+
+/// Lower-cased `name` without `_` / `-`, so naming conventions compare equal.
+fn fuzzy_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `(tier, edit distance, length difference)` of `candidate` against `needle`,
+/// lower is better; `None` when it is no match at all. Tiers: 0 exact, 1 prefix,
+/// 2 substring, 3 within `max_distance` edits.
+fn fuzzy_rank(needle: &str, candidate: &str, max_distance: usize) -> Option<(u8, usize, usize)> {
+    let len_diff = candidate.chars().count().abs_diff(needle.chars().count());
+    let tier = if candidate == needle {
+        0
+    } else if candidate.starts_with(needle) {
+        1
+    } else if candidate.contains(needle) {
+        2
+    } else if len_diff <= max_distance {
+        let distance = edit_distance(needle, candidate);
+        return (distance <= max_distance).then_some((3, distance, len_diff));
+    } else {
+        return None;
+    };
+    Some((tier, 0, len_diff))
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
 }
 
-/// Find Rust `impl` blocks by byte range.
-/// Returns `(name, "impl", start_byte, end_byte)` tuples.
-fn rust_impl_byte_ranges(
-    source: &[u8],
-    root: Node,
-    language: &Language,
-) -> Vec<(String, String, usize, usize)> {
-    let mut out: Vec<(String, String, usize, usize)> = Vec::new();
-
-    // impl Foo { ... }
-    for (name, start, end) in find_named_decls_raw(
-        source,
-        root,
-        language,
-        r#"(impl_item type: (type_identifier) @name) @def"#,
-    ) {
-        out.push((name, "impl".to_string(), start, end));
+/// Markdown listing of [`search_symbols_fuzzy`] results for the `search_symbols`
+/// action, capped at `limit` entries.
+pub fn format_symbol_matches(pattern: &str, matches: &[SymbolMatch], limit: usize) -> String {
+    if matches.is_empty() {
+        return format!(
+            "No symbol names like `{pattern}` found.\n\
+            Tip: search_symbols matches names only; use find_usages for references \
+            or cortex_code_explorer(action=deep_slice, query=...) to search by meaning."
+        );
+    }
+    let mut out = format!("## Symbols like `{pattern}` ({})\n\n", matches.len());
+    for m in matches.iter().take(limit) {
+        out.push_str(&format!(
+            "- `{}` {} — {}:L{}\n",
+            m.kind, m.name, m.file, m.line
+        ));
     }
-
-    // impl<T> Foo<T> { ... }
-    for (name, start, end) in find_named_decls_raw(
-        source,
-        root,
-        language,
-        r#"(impl_item type: (generic_type type: (type_identifier) @name)) @def"#,
-    ) {
-        out.push((name, "impl".to_string(), start, end));
+    if matches.len() > limit {
+        out.push_str(&format!(
+            "\n> {} more not shown; narrow target_dir or the pattern.\n",
+            matches.len() - limit
+        ));
     }
-
     out
 }
 
 // ---------------------------------------------------------------------------
-// Tool: find_usages — The AST-Tracer
+// Tool: list_symbols — every declaration of one kind
 // ---------------------------------------------------------------------------
 
-/// Find all semantic usages of `symbol_name` across code files under `target_dir`.
-///
-/// Algorithm:
-///  1. Walk `target_dir` with `ignore::WalkBuilder` (honours `.gitignore`).
-///  2. For each supported-language file containing `symbol_name` as a substring
-///     (fast pre-filter), parse with tree-sitter.
-///  3. Recursively visit AST leaf nodes: collect `identifier`, `type_identifier`,
-///     `field_identifier`, `property_identifier` nodes whose text == `symbol_name`.
-///  4. Prune entire comment / string subtrees — zero false positives from docs or
-///     string constants.
-///  5. Return a dense listing with 2-line context windows.
-///
-/// Works even when the project currently **fails to compile** because it uses the
-/// raw AST, not an LSP or compiler.
-pub fn find_usages(target_dir: &Path, symbol_name: &str) -> Result<String> {
-    use ignore::WalkBuilder;
-    use std::collections::BTreeMap;
+/// One declaration returned by [`list_symbols_by_kind`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolListing {
+    pub name: String,
+    /// Raw kind from the language driver (`function`, `struct`, `class`, ...).
+    pub kind: String,
+    /// Relative to `target_dir`.
+    pub file: String,
+    /// 1-based.
+    pub line: u32,
+    /// Declaration head on one line, without the body.
+    pub signature: String,
+}
 
+/// Every declaration under `target_dir` whose kind matches `kind`, in file and line
+/// order.
+///
+/// `kind` is normalized through [`SymbolKind::from_raw`], so `trait` also lists
+/// interfaces and protocols and `function` lists `fn` / `func` / `def`; a plural
+/// (`traits`) is accepted. A kind with no category (e.g. `impl`) is compared against
+/// the raw driver kind, ignoring case.
+pub fn list_symbols_by_kind(target_dir: &Path, kind: &str) -> Result<Vec<SymbolListing>> {
+    let raw = kind.trim().to_ascii_lowercase();
+    if raw.is_empty() {
+        return Err(anyhow!("kind is empty"));
+    }
+    let category = match SymbolKind::from_raw(&raw) {
+        SymbolKind::Other => raw
+            .strip_suffix('s')
+            .map(SymbolKind::from_raw)
+            .unwrap_or(SymbolKind::Other),
+        c => c,
+    };
     let abs_dir: PathBuf = if target_dir.is_absolute() {
         target_dir.to_path_buf()
     } else {
@@ -2129,147 +5106,325 @@ pub fn find_usages(target_dir: &Path, symbol_name: &str) -> Result<String> {
             .context("Failed to get cwd")?
             .join(target_dir)
     };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
 
-    let walker = WalkBuilder::new(&abs_dir)
-        .standard_filters(true) // respects .gitignore, .git/info/exclude, default ignores
-        .hidden(true) // skip dot-dirs like .git, node_modules handled by standard_filters
-        .build();
-
-    let cfg_lock = language_config().read().unwrap();
-    let cfg = &*cfg_lock;
-    let mut all_results: Vec<UsageMatch> = Vec::new();
-
-    for entry_result in walker {
-        let Ok(entry) = entry_result else { continue };
+    let mut out: Vec<SymbolListing> = Vec::new();
+    for entry in build_walker(&abs_dir, GitignoreMode::Respect, Default::default()).flatten() {
         let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-
-        // Only process files with a supported language driver.
-        if cfg.driver_for_path(path).is_none() {
-            continue;
-        }
-
-        let Ok(raw) = std::fs::read(path) else {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
             continue;
-        };
-        if raw.contains(&0u8) {
-            continue; // binary
         }
-        let Ok(source_text) = std::str::from_utf8(&raw) else {
-            continue;
-        };
-
-        // Hot path: fast substring pre-filter before paying the tree-sitter parse cost.
-        if !source_text.contains(symbol_name) {
+        let supported = language_config()
+            .read()
+            .unwrap()
+            .driver_for_path(path)
+            .is_some();
+        if !supported {
             continue;
         }
-
-        let Some(driver) = cfg.driver_for_path(path) else {
+        let Ok(Some(text)) = read_source_lossy(path) else {
             continue;
         };
-        let source = source_text.as_bytes();
-
-        let mut parser = match driver.make_parser(path) {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("[cortexast] parser init failed for {}: {e}", path.display());
+        let rel = normalize_path_for_output(path.strip_prefix(&abs_dir).unwrap_or(path));
+        for sym in extract_symbols_from_source(path, &text) {
+            let matches = match category {
+                SymbolKind::Other => sym.kind.eq_ignore_ascii_case(&raw),
+                c => sym.category() == c,
+            };
+            if !matches {
                 continue;
             }
-        };
-        let Some(tree) = parser.parse(source_text, None) else {
-            continue;
-        };
-        let root = tree.root_node();
-
-        // AST-level reference collection — excludes comments and string literals.
-        let mut hits: Vec<(u32, &'static str)> = Vec::new();
-        collect_identifier_refs(root, source, symbol_name, &mut hits);
-
-        if hits.is_empty() {
-            continue;
-        }
-
-        hits.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
-        hits.dedup();
-
-        let text_lines: Vec<&str> = source_text.lines().collect();
-        let display_path = path.to_string_lossy();
-
-        for (row_0, category) in hits {
-            all_results.push(UsageMatch {
-                category,
-                file: display_path.to_string(),
-                line_1: row_0 + 1,
-                context: extract_context_lines(&text_lines, row_0 as usize, 2),
+            let signature = sym.signature.clone().unwrap_or_else(|| {
+                first_line_signature(text.get(sym.start_byte..sym.end_byte).unwrap_or(""))
+            });
+            out.push(SymbolListing {
+                name: sym.name,
+                kind: sym.kind,
+                file: rel.clone(),
+                line: sym.line + 1,
+                signature,
             });
         }
     }
+    out.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    out.dedup_by(|a, b| a.name == b.name && a.file == b.file && a.line == b.line);
+    Ok(out)
+}
 
-    if all_results.is_empty() {
-        return Ok(format!(
-            "No usages of `{}` found in {}.",
-            symbol_name,
-            abs_dir.display()
+/// Markdown listing of [`list_symbols_by_kind`] results for the `list_symbols`
+/// action, grouped by file, until `budget_tokens` (~4 chars/token) is spent.
+pub fn format_symbols_by_kind(
+    kind: &str,
+    listings: &[SymbolListing],
+    budget_tokens: usize,
+) -> String {
+    const CHARS_PER_TOKEN: usize = 4;
+
+    if listings.is_empty() {
+        return format!(
+            "No `{kind}` symbols found.\n\
+            Tip: kinds are function, method, type, trait, const and module, or a raw \
+            driver kind such as struct or impl."
+        );
+    }
+    let budget_chars = budget_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let mut body = String::new();
+    let mut shown = 0usize;
+    let mut current_file: Option<&str> = None;
+    for l in listings {
+        let mut block = String::new();
+        if current_file != Some(l.file.as_str()) {
+            block.push_str(&format!("\n### {}\n", l.file));
+        }
+        block.push_str(&format!(
+            "- L{} `{}` {} — `{}`\n",
+            l.line, l.kind, l.name, l.signature
         ));
+        if body.len() + block.len() > budget_chars {
+            break;
+        }
+        body.push_str(&block);
+        current_file = Some(l.file.as_str());
+        shown += 1;
     }
-
-    let mut by_cat: BTreeMap<&'static str, Vec<UsageMatch>> = BTreeMap::new();
-    for m in all_results {
-        by_cat.entry(m.category).or_default().push(m);
+    let mut out = format!("## `{kind}` symbols ({shown} of {})\n", listings.len());
+    out.push_str(&body);
+    if shown < listings.len() {
+        out.push_str(&format!(
+            "\n> ✂️ Budget reached: {} more not shown. Raise `budget_tokens` or narrow `target_dir`.\n",
+            listings.len() - shown
+        ));
     }
+    out
+}
 
-    let order: [&'static str; 5] = [
-        "Calls",
-        "Type Refs",
-        "Field Accesses",
-        "Field Inits",
-        "Other",
-    ];
-    let total: usize = by_cat.values().map(|v| v.len()).sum();
-    let mut out = format!("{} usage(s) of `{symbol_name}` found:\n\n", total);
+// ---------------------------------------------------------------------------
+// Tool: find_by_attribute — declarations carrying a given annotation
+// ---------------------------------------------------------------------------
 
-    for cat in order {
-        let Some(mut items) = by_cat.remove(cat) else {
+/// One declaration returned by [`find_by_attribute`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AttributeMatch {
+    pub name: String,
+    /// Raw kind from the language driver (`function`, `struct`, `class`, ...).
+    pub kind: String,
+    /// Relative to `target_dir`.
+    pub file: String,
+    /// 1-based.
+    pub line: u32,
+    /// The attribute / decorator that matched, as written (whitespace collapsed).
+    pub attribute: String,
+}
+
+/// Every declaration under `target_dir` with an attribute, decorator or annotation
+/// containing `pattern` (case-insensitive), in file and line order. A declaration
+/// with several matching attributes is listed once per attribute.
+///
+/// Useful for framework-driven code: `#[get(` or `@app.route` finds HTTP handlers,
+/// `#[test]` finds tests, `@Deprecated` finds deprecated APIs.
+pub fn find_by_attribute(target_dir: &Path, pattern: &str) -> Result<Vec<AttributeMatch>> {
+    let needle = pattern.trim().to_ascii_lowercase();
+    if needle.is_empty() {
+        return Err(anyhow!("attribute pattern is empty"));
+    }
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get cwd")?
+            .join(target_dir)
+    };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
+
+    let mut out: Vec<AttributeMatch> = Vec::new();
+    for entry in build_walker(&abs_dir, GitignoreMode::Respect, Default::default()).flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let supported = language_config()
+            .read()
+            .unwrap()
+            .driver_for_path(path)
+            .is_some();
+        if !supported {
+            continue;
+        }
+        let Ok(Some(text)) = read_source_lossy(path) else {
             continue;
         };
-        items.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line_1.cmp(&b.line_1)));
-        out.push_str(&format!("### {cat} ({})\n\n", items.len()));
-        for m in &items {
-            out.push_str(&format!("[{}:{}]\n", m.file, m.line_1));
-            out.push_str(&format!("Context:\n{}\n\n", m.context));
+        let rel = normalize_path_for_output(path.strip_prefix(&abs_dir).unwrap_or(path));
+        for sym in extract_symbols_from_source(path, &text) {
+            for attribute in &sym.attributes {
+                if !attribute.to_ascii_lowercase().contains(&needle) {
+                    continue;
+                }
+                out.push(AttributeMatch {
+                    name: sym.name.clone(),
+                    kind: sym.kind.clone(),
+                    file: rel.clone(),
+                    line: sym.line + 1,
+                    attribute: attribute.clone(),
+                });
+            }
         }
     }
+    out.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    out.dedup_by(|a, b| {
+        a.name == b.name && a.file == b.file && a.line == b.line && a.attribute == b.attribute
+    });
+    Ok(out)
+}
 
-    // Any future categories (shouldn't happen) — append deterministically.
-    for (cat, mut items) in by_cat {
-        items.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line_1.cmp(&b.line_1)));
-        out.push_str(&format!("### {cat} ({})\n\n", items.len()));
-        for m in &items {
-            out.push_str(&format!("[{}:{}]\n", m.file, m.line_1));
-            out.push_str(&format!("Context:\n{}\n\n", m.context));
+/// Markdown listing of [`find_by_attribute`] results for the `find_by_attribute`
+/// action, grouped by file, until `budget_tokens` (~4 chars/token) is spent.
+pub fn format_attribute_matches(
+    pattern: &str,
+    matches: &[AttributeMatch],
+    budget_tokens: usize,
+) -> String {
+    const CHARS_PER_TOKEN: usize = 4;
+
+    if matches.is_empty() {
+        return format!(
+            "No declarations with an attribute matching `{pattern}`.\n\
+            Tip: match part of the attribute text, e.g. `#[test]`, `route` or `Deprecated`."
+        );
+    }
+    let budget_chars = budget_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let mut body = String::new();
+    let mut shown = 0usize;
+    let mut current_file: Option<&str> = None;
+    for m in matches {
+        let mut block = String::new();
+        if current_file != Some(m.file.as_str()) {
+            block.push_str(&format!("\n### {}\n", m.file));
+        }
+        block.push_str(&format!(
+            "- L{} `{}` {} — `{}`\n",
+            m.line, m.kind, m.name, m.attribute
+        ));
+        if body.len() + block.len() > budget_chars {
+            break;
         }
+        body.push_str(&block);
+        current_file = Some(m.file.as_str());
+        shown += 1;
     }
-
-    Ok(out)
+    let mut out = format!("## Attribute `{pattern}` ({shown} of {})\n", matches.len());
+    out.push_str(&body);
+    if shown < matches.len() {
+        out.push_str(&format!(
+            "\n> ✂️ Budget reached: {} more not shown. Raise `budget_tokens` or narrow `target_dir`.\n",
+            matches.len() - shown
+        ));
+    }
+    out
 }
 
 // ---------------------------------------------------------------------------
 // Tool: propagation_checklist — Cross-Boundary Awareness
 // ---------------------------------------------------------------------------
 
+/// Files listed by [`propagation_checklist`] before it truncates. A symbol touching
+/// more files than this is "ubiquitous" (see the `blast_radius` risk summary).
+const MAX_CHECKLIST_FILES: usize = 50;
+/// Files listed by `propagation_checklist` with `format: "json"` before `truncated` is set.
+const MAX_CHECKLIST_JSON_FILES: usize = 1_000;
+
+/// Output format for [`propagation_checklist`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecklistFormat {
+    /// Checkbox list grouped by domain, capped for LLMs (default).
+    #[default]
+    Markdown,
+    /// [`PropagationReport`] JSON for CI gates.
+    Json,
+}
+
+/// One file referencing the symbol, as listed in a [`PropagationReport`] group.
+#[derive(Debug, Clone, Serialize)]
+pub struct PropagationHit {
+    pub path: String,
+    pub count: usize,
+    /// 1-based, sorted, unique.
+    pub lines: Vec<u32>,
+}
+
+/// Usage sites of a shared symbol grouped by domain (`proto`, `rust`, `ts`, `tauri`,
+/// `python`, `other`), as emitted by `propagation_checklist` with `format: "json"`.
+/// Empty groups are omitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct PropagationReport {
+    pub symbol: String,
+    pub groups: std::collections::BTreeMap<String, Vec<PropagationHit>>,
+    /// Files beyond the first 1000 were dropped.
+    pub truncated: bool,
+}
+
+/// Group keys of [`PropagationReport`] in checklist order, with their Markdown titles.
+const CHECKLIST_SECTIONS: &[(&str, &str)] = &[
+    ("proto", "📝 Protocol Buffers (Contracts)"),
+    ("rust", "🦀 Rust (Backend/Services)"),
+    ("ts", "🧩 TypeScript (Frontend/UI)"),
+    ("tauri", "⚡ Tauri Commands (Rust → Frontend Bridge)"),
+    ("python", "🐍 Python (Scripts/MLX)"),
+    ("other", "📦 Other Definitions"),
+];
+
 /// Generate a cross-language propagation checklist for `symbol_name`.
 ///
 /// Walks `target_dir` (honours `.gitignore`) and performs AST-accurate identifier
 /// matching (no comment/string false positives). Output is grouped by domain to
-/// reduce propagation drop across repos/services.
+/// reduce propagation drop across repos/services. Paths are printed per `paths`.
 pub fn propagation_checklist(
     target_dir: &Path,
     symbol_name: &str,
     aliases: &[String],
     ignore_gitignore: bool,
+    format: ChecklistFormat,
+    paths: PathStyle,
 ) -> Result<String> {
+    let mut report = propagation_report(target_dir, symbol_name, aliases, ignore_gitignore, paths)?;
+    match format {
+        ChecklistFormat::Json => {
+            let mut budget = MAX_CHECKLIST_JSON_FILES;
+            for hits in report.groups.values_mut() {
+                if hits.len() > budget {
+                    hits.truncate(budget);
+                    report.truncated = true;
+                }
+                budget -= hits.len();
+            }
+            report.groups.retain(|_, hits| !hits.is_empty());
+            Ok(serde_json::to_string_pretty(&report)?)
+        }
+        ChecklistFormat::Markdown => {
+            let abs_dir: PathBuf = if target_dir.is_absolute() {
+                target_dir.to_path_buf()
+            } else {
+                std::env::current_dir()
+                    .context("Failed to get cwd")?
+                    .join(target_dir)
+            };
+            Ok(render_propagation_checklist(&report, &abs_dir))
+        }
+    }
+}
+
+/// Walk behind [`propagation_checklist`]: every file under `target_dir` referencing
+/// `symbol_name` (or an alias / casing variant), grouped by domain.
+fn propagation_report(
+    target_dir: &Path,
+    symbol_name: &str,
+    aliases: &[String],
+    ignore_gitignore: bool,
+    paths: PathStyle,
+) -> Result<PropagationReport> {
     use ignore::WalkBuilder;
     use std::collections::{BTreeMap, HashSet};
 
@@ -2344,7 +5499,7 @@ pub fn propagation_checklist(
                 continue;
             }
         };
-        let Some(tree) = parser.parse(source_text, None) else {
+        let Ok(tree) = parse_with_limits(&mut parser, source_text) else {
             continue;
         };
         let root = tree.root_node();
@@ -2362,10 +5517,7 @@ pub fn propagation_checklist(
         lines_1.sort_unstable();
         lines_1.dedup();
 
-        let rel = path
-            .strip_prefix(&abs_dir)
-            .map(|p| p.to_string_lossy().replace('\\', "/"))
-            .unwrap_or_else(|_| path.to_string_lossy().replace('\\', "/"));
+        let rel = paths.show(path);
 
         hits_by_file
             .entry(rel)
@@ -2484,10 +5636,41 @@ pub fn propagation_checklist(
         }
     }
 
+    let mut groups: BTreeMap<String, Vec<PropagationHit>> = BTreeMap::new();
+    for (key, mut items) in [
+        ("proto", proto),
+        ("rust", rust),
+        ("ts", ts),
+        ("tauri", tauri_cmds),
+        ("python", py),
+        ("other", other),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        let hits = items
+            .into_iter()
+            .map(|(path, count, lines)| PropagationHit { path, count, lines })
+            .collect();
+        groups.insert(key.to_string(), hits);
+    }
+
+    Ok(PropagationReport {
+        symbol: symbol_name.to_string(),
+        groups,
+        truncated: false,
+    })
+}
+
+/// Markdown checkbox rendering of a [`PropagationReport`], capped at
+/// [`MAX_CHECKLIST_FILES`] files and 8000 chars.
+fn render_propagation_checklist(report: &PropagationReport, abs_dir: &Path) -> String {
     // Blast radius guardrails (hard caps): prevent token explosions.
-    const MAX_CHECKLIST_FILES: usize = 50;
     const MAX_CHARS_TOTAL: usize = 8_000;
 
+    let symbol_name = report.symbol.as_str();
+
     let mut out = String::new();
     out.push_str(&format!(
         "## 📋 Propagation Checklist for `{}`\n",
@@ -2495,14 +5678,7 @@ pub fn propagation_checklist(
     ));
     out.push_str("*Review and update these files to ensure cross-service consistency.*\n\n");
 
-    proto.sort_by(|a, b| a.0.cmp(&b.0));
-    rust.sort_by(|a, b| a.0.cmp(&b.0));
-    ts.sort_by(|a, b| a.0.cmp(&b.0));
-    tauri_cmds.sort_by(|a, b| a.0.cmp(&b.0));
-    py.sort_by(|a, b| a.0.cmp(&b.0));
-    other.sort_by(|a, b| a.0.cmp(&b.0));
-
-    let total_files_affected = proto.len() + rust.len() + ts.len() + tauri_cmds.len() + py.len() + other.len();
+    let total_files_affected: usize = report.groups.values().map(Vec::len).sum();
     let mut total_files_printed: usize = 0;
     let truncated_by_file_limit = std::cell::Cell::new(false);
     let truncated_by_char_limit = std::cell::Cell::new(false);
@@ -2534,14 +5710,19 @@ pub fn propagation_checklist(
         }
     };
 
-    let mut write_section = |title: &str, items: &Vec<(String, usize, Vec<u32>)>| {
+    let mut write_section = |title: &str, items: &[PropagationHit]| {
         if items.is_empty() || truncated_by_char_limit.get() || truncated_by_file_limit.get() {
             return;
         }
         if !push(&format!("### {}\n", title)) {
             return;
         }
-        for (p, n, lines) in items {
+        for PropagationHit {
+            path: p,
+            count: n,
+            lines,
+        } in items
+        {
             if total_files_printed >= MAX_CHECKLIST_FILES {
                 truncated_by_file_limit.set(true);
                 break;
@@ -2572,12 +5753,11 @@ pub fn propagation_checklist(
         let _ = push("\n");
     };
 
-    write_section("📝 Protocol Buffers (Contracts)", &proto);
-    write_section("🦀 Rust (Backend/Services)", &rust);
-    write_section("🧩 TypeScript (Frontend/UI)", &ts);
-    write_section("⚡ Tauri Commands (Rust → Frontend Bridge)", &tauri_cmds);
-    write_section("🐍 Python (Scripts/MLX)", &py);
-    write_section("📦 Other Definitions", &other);
+    for &(key, title) in CHECKLIST_SECTIONS {
+        if let Some(items) = report.groups.get(key) {
+            write_section(title, items);
+        }
+    }
 
     if truncated_by_file_limit.get() {
         let remaining = total_files_affected.saturating_sub(total_files_printed);
@@ -2586,14 +5766,14 @@ pub fn propagation_checklist(
         ));
     }
 
-    if proto.is_empty() && rust.is_empty() && ts.is_empty() && tauri_cmds.is_empty() && py.is_empty() && other.is_empty() {
+    if report.groups.is_empty() {
         out.push_str(&format!(
             "No AST-accurate usages found under {}.\n",
             abs_dir.display()
         ));
     }
 
-    Ok(out)
+    out
 }
 
 fn generate_casing_variants(base_name: &str) -> Vec<String> {
@@ -2749,9 +5929,32 @@ struct UsageMatch {
     category: &'static str,
     file: String,
     line_1: u32,
+    /// Tightest enclosing function/method, e.g. `fn process_order()`.
+    enclosing: Option<String>,
     context: String,
 }
 
+impl UsageMatch {
+    /// `[file:line]`, followed by ` in fn name()` when the hit is inside a callable.
+    fn header(&self) -> String {
+        match &self.enclosing {
+            Some(e) => format!("[{}:{}] in {e}\n", self.file, self.line_1),
+            None => format!("[{}:{}]\n", self.file, self.line_1),
+        }
+    }
+}
+
+/// Tightest function/method among `syms` whose line range contains `row_0`,
+/// as [`call_hierarchy`] attributes call sites to their caller.
+fn enclosing_callable<'a>(
+    syms: impl IntoIterator<Item = &'a Symbol>,
+    row_0: u32,
+) -> Option<&'a Symbol> {
+    syms.into_iter()
+        .filter(|s| s.line <= row_0 && row_0 <= s.line_end && s.category().is_callable())
+        .min_by_key(|s| row_0 - s.line)
+}
+
 struct ImplementationMatch {
     language: &'static str,
     implementor: String,
@@ -2760,7 +5963,13 @@ struct ImplementationMatch {
     context: String,
 }
 
-pub fn find_implementations(target_dir: &Path, trait_or_interface: &str) -> Result<String> {
+/// Types implementing `trait_or_interface` under `target_dir`, grouped by trait.
+/// Paths are printed per `paths`.
+pub fn find_implementations(
+    target_dir: &Path,
+    trait_or_interface: &str,
+    paths: PathStyle,
+) -> Result<String> {
     use ignore::WalkBuilder;
     use std::collections::BTreeMap;
 
@@ -2781,6 +5990,7 @@ pub fn find_implementations(target_dir: &Path, trait_or_interface: &str) -> Resu
         .standard_filters(true)
         .hidden(true)
         .build();
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
 
     let cfg_lock = language_config().read().unwrap();
     let cfg = &*cfg_lock;
@@ -2789,7 +5999,7 @@ pub fn find_implementations(target_dir: &Path, trait_or_interface: &str) -> Resu
     for entry_result in walker {
         let Ok(entry) = entry_result else { continue };
         let path = entry.path();
-        if !path.is_file() {
+        if !path.is_file() || linguist.classify(path).is_some() {
             continue;
         }
 
@@ -2835,13 +6045,13 @@ pub fn find_implementations(target_dir: &Path, trait_or_interface: &str) -> Resu
                 continue;
             }
         };
-        let Some(tree) = parser.parse(source_text, None) else {
+        let Ok(tree) = parse_with_limits(&mut parser, source_text) else {
             continue;
         };
         let root = tree.root_node();
 
         let text_lines: Vec<&str> = source_text.lines().collect();
-        let display_path = path.to_string_lossy().to_string();
+        let display_path = paths.show(path);
 
         match lang {
             "rust" => {
@@ -3012,13 +6222,79 @@ pub fn find_implementations(target_dir: &Path, trait_or_interface: &str) -> Resu
                 .then_with(|| a.line_1.cmp(&b.line_1))
                 .then_with(|| a.implementor.cmp(&b.implementor))
         });
-        out.push_str(&format!("### {lang} ({})\n\n", items.len()));
-        for m in &items {
-            out.push_str(&format!("[{}:{}] {}\n", m.file, m.line_1, m.implementor));
-            out.push_str(&format!("Context:\n{}\n\n", m.context));
+        out.push_str(&format!("### {lang} ({})\n\n", items.len()));
+        for m in &items {
+            out.push_str(&format!("[{}:{}] {}\n", m.file, m.line_1, m.implementor));
+            out.push_str(&format!("Context:\n{}\n\n", m.context));
+        }
+    }
+    Ok(out)
+}
+
+/// Extensions never searched lexically: prose and data, where a name match is
+/// almost never a code reference.
+const LEXICAL_SKIP_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "txt", "rst", "adoc", "log", "lock", "csv", "tsv", "svg", "map",
+];
+
+fn lexical_candidate(path: &Path) -> bool {
+    !LEXICAL_SKIP_EXTENSIONS.contains(&path_ext_lower(path).as_str())
+}
+
+/// Word-boundary matches of `symbol_name` in a file no driver can parse, as
+/// `(row_0, "Lexical")` hits. Without a syntax tree, comments and strings are
+/// guessed per line: lines opening with a comment marker are skipped, and so are
+/// matches inside `"..."` or after a `//` on the same line.
+fn lexical_refs(source_text: &str, symbol_name: &str) -> Vec<(u32, &'static str)> {
+    const COMMENT_PREFIXES: [&str; 7] = ["//", "#", "--", "/*", "*", ";", "%"];
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut hits = Vec::new();
+    for (row_0, line) in source_text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if COMMENT_PREFIXES.iter().any(|p| trimmed.starts_with(p)) {
+            continue;
+        }
+        let found = line.match_indices(symbol_name).any(|(at, _)| {
+            let before = line[..at].chars().next_back();
+            let after = line[at + symbol_name.len()..].chars().next();
+            !before.is_some_and(is_word)
+                && !after.is_some_and(is_word)
+                && !in_string_or_comment(&line[..at])
+        });
+        if found {
+            hits.push((row_0 as u32, "Lexical"));
         }
     }
-    Ok(out)
+    hits
+}
+
+/// Whether text following `prefix` on the same line sits inside an open `"..."`
+/// string or a `//` comment.
+fn in_string_or_comment(prefix: &str) -> bool {
+    let mut in_str = false;
+    let mut escaped = false;
+    let mut prev_slash = false;
+    for c in prefix.chars() {
+        if in_str {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_str = false;
+            }
+            continue;
+        }
+        if c == '/' && prev_slash {
+            return true;
+        }
+        prev_slash = c == '/';
+        if c == '"' {
+            in_str = true;
+        }
+    }
+    in_str
 }
 
 /// Recursively collect AST leaf identifier nodes that match `symbol_name`,
@@ -3029,6 +6305,29 @@ fn collect_identifier_refs(
     symbol_name: &str,
     out: &mut Vec<(u32, &'static str)>,
 ) {
+    visit_identifier_refs(node, source, symbol_name, &mut |hit| {
+        out.push((hit.start_position().row as u32, usage_category(hit)))
+    });
+}
+
+/// Calls `on_hit` for every identifier leaf under `node` whose text is exactly
+/// `symbol_name`, skipping comment and string subtrees.
+fn visit_identifier_refs<'t>(
+    node: Node<'t>,
+    source: &[u8],
+    symbol_name: &str,
+    on_hit: &mut dyn FnMut(Node<'t>),
+) {
+    visit_identifiers(node, source, &mut |hit, text| {
+        if text == symbol_name {
+            on_hit(hit)
+        }
+    });
+}
+
+/// Calls `on_ident` with every identifier leaf under `node` and its text, skipping
+/// comment and string subtrees.
+fn visit_identifiers<'t>(node: Node<'t>, source: &[u8], on_ident: &mut dyn FnMut(Node<'t>, &str)) {
     let kind = node.kind();
 
     // Prune entire comment / string subtrees — no matches inside these nodes.
@@ -3063,9 +6362,7 @@ fn collect_identifier_refs(
         ) {
             let slice = &source[node.start_byte()..node.end_byte()];
             if let Ok(text) = std::str::from_utf8(slice) {
-                if text == symbol_name {
-                    out.push((node.start_position().row as u32, usage_category(node)));
-                }
+                on_ident(node, text);
             }
         }
         return;
@@ -3074,7 +6371,7 @@ fn collect_identifier_refs(
     // Recurse into children.
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        collect_identifier_refs(child, source, symbol_name, out);
+        visit_identifiers(child, source, on_ident);
     }
 }
 
@@ -3092,9 +6389,38 @@ fn has_ancestor_kind(mut node: Node, target_kinds: &[&str]) -> bool {
     false
 }
 
+/// True when `node` is a `name:` child of a declaration (`fn foo`, `class Foo`,
+/// `struct Foo`, `const FOO`, a field or variable declarator), i.e. the def site
+/// rather than a usage. Import/export specifiers and keyword arguments also carry
+/// a `name:` field but are not declarations, so only declaration-like parents count.
+fn is_declaration_name(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let kind = parent.kind();
+    let declares = kind.ends_with("_item")
+        || kind.ends_with("_declaration")
+        || kind.ends_with("_definition")
+        || kind.ends_with("_declarator")
+        || kind.ends_with("_signature")
+        || kind.ends_with("_spec")
+        || kind == "enum_variant";
+    let mut cursor = parent.walk();
+    let is_name = declares
+        && parent
+            .children_by_field_name("name", &mut cursor)
+            .any(|n| n.id() == node.id());
+    is_name
+}
+
 fn usage_category(node: Node) -> &'static str {
     let kind = node.kind();
 
+    // Before the type check: `struct Foo` names are `type_identifier`s too.
+    if is_declaration_name(node) {
+        return "Definitions";
+    }
+
     if kind == "type_identifier" {
         return "Type Refs";
     }
@@ -3208,19 +6534,46 @@ fn extract_context_lines(lines: &[&str], target_0: usize, ctx: usize) -> String
 ///       [struct  ] User
 /// ```
 pub fn repo_map(target_dir: &Path) -> Result<String> {
-    repo_map_with_filter(target_dir, None, None, false, &[])
+    repo_map_with_filter(target_dir, &RepoMapOptions::default())
 }
 
-pub fn repo_map_with_filter(
-    target_dir: &Path,
-    search_filter: Option<&str>,
-    max_chars: Option<usize>,
-    ignore_gitignore: bool,
-    exclude_dirs: &[String],
-) -> Result<String> {
-    use ignore::WalkBuilder;
+/// Options for [`repo_map_with_filter`]; the default is what [`repo_map`] uses.
+#[derive(Debug, Clone, Default)]
+pub struct RepoMapOptions<'a> {
+    /// Case-insensitive substring filter (`|` for OR) on paths and, in small folders,
+    /// symbol names.
+    pub search_filter: Option<&'a str>,
+    /// Output cap in chars, itself capped at 8 000.
+    pub max_chars: Option<usize>,
+    pub ignore_mode: GitignoreMode,
+    /// Directory names left out of the walk.
+    pub exclude_dirs: &'a [String],
+    /// When set, deep-mode symbol lines for functions whose cyclomatic complexity
+    /// reaches it get a `⚠ complexity N` marker.
+    pub complexity_threshold: Option<u32>,
+    /// When set, only files at most that many levels below the target are considered
+    /// (`1` = its direct children), so a deep tree maps at a chosen zoom level instead
+    /// of collapsing into folders-only mode.
+    pub max_depth: Option<usize>,
+    /// Files it does not keep (see [`TestFilter::keeps`]) are left out of the map and
+    /// counted in a note line.
+    pub tests: TestFilter,
+}
+
+/// [`repo_map`] of `target_dir` with the given [`RepoMapOptions`].
+pub fn repo_map_with_filter(target_dir: &Path, opts: &RepoMapOptions<'_>) -> Result<String> {
     use std::collections::{BTreeMap, BTreeSet, HashSet};
 
+    let RepoMapOptions {
+        search_filter,
+        max_chars,
+        ignore_mode,
+        exclude_dirs,
+        complexity_threshold,
+        max_depth,
+        ref tests,
+    } = *opts;
+
     // Absolute hard cap to prevent MCP clients from offloading huge payloads
     // into resource files (which breaks agent loops).
     const HARD_MAX_CHARS_TOTAL: usize = 8_000;
@@ -3251,22 +6604,34 @@ pub fn repo_map_with_filter(
         .map(|s| s.trim().trim_matches('/').to_string())
         .filter(|s| !s.is_empty())
         .collect();
-    let excluded_dir_set_clone = excluded_dir_set.clone();
 
-    let walker_filtered = WalkBuilder::new(&abs_dir)
-        .standard_filters(!ignore_gitignore)
-        .hidden(true)
-        .filter_entry(move |dent| {
-            if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                if let Some(name) = dent.path().file_name().and_then(|s| s.to_str()) {
-                    if excluded_dir_set_clone.contains(name) {
-                        return false;
-                    }
-                }
-            }
-            true
-        })
-        .build();
+    // Both walks are pure directory IO, so run them concurrently and post-process the
+    // collected paths here. The unfiltered walk only exists to compute gitignore drop
+    // stats, which are meaningless when ignore files are already disabled.
+    let want_unfiltered = ignore_mode == GitignoreMode::Respect;
+    let excluded_all = excluded_dir_set.clone();
+    let (filtered_walk, unfiltered_walk) = rayon::join(
+        || {
+            collect_walk_files(build_walker_to_depth(
+                &abs_dir,
+                ignore_mode,
+                excluded_dir_set,
+                max_depth,
+            ))
+        },
+        || {
+            want_unfiltered.then(|| {
+                collect_walk_files(build_walker_to_depth(
+                    &abs_dir,
+                    GitignoreMode::Off,
+                    excluded_all,
+                    max_depth,
+                ))
+            })
+        },
+    );
+    let (filtered_files, filtered_error_count) = filtered_walk;
+    let linguist = linguist_hints(&abs_dir, ignore_mode);
 
     let cfg_lock = language_config().read().unwrap();
     let cfg = &*cfg_lock;
@@ -3286,32 +6651,22 @@ pub fn repo_map_with_filter(
     let mut kept_source_files: usize = 0;
     let mut dropped_by_unsupported_lang: usize = 0;
     let mut dropped_by_search_filter: usize = 0;
+    let mut dropped_by_test_filter: usize = 0;
 
     let mut sample_dropped: Vec<String> = Vec::new();
     let mut sample_unsupported: Vec<String> = Vec::new();
     let mut sample_filtered_out: Vec<String> = Vec::new();
     let mut filtered_paths: HashSet<String> = HashSet::new();
 
-    let mut filtered_file_count: usize = 0;
-    let mut filtered_error_count: usize = 0;
+    let filtered_file_count: usize = filtered_files.len();
 
     // (rel_path, filename, dir_rel, abs_path)
     let mut supported_candidates: Vec<(String, String, String, PathBuf)> = Vec::new();
+    // Files `.gitattributes` marks generated/vendored: listed, never parsed.
+    let mut linguist_marked: HashMap<PathBuf, LinguistKind> = HashMap::new();
 
-    for entry_result in walker_filtered {
-        let entry = match entry_result {
-            Ok(e) => e,
-            Err(_) => {
-                filtered_error_count += 1;
-                continue;
-            }
-        };
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-
-        filtered_file_count += 1;
+    for path in &filtered_files {
+        let path = path.as_path();
 
         let rel_from_target = match path.strip_prefix(&abs_dir) {
             Ok(r) => r,
@@ -3319,6 +6674,10 @@ pub fn repo_map_with_filter(
         };
         let rel_path = rel_from_target.to_string_lossy().replace('\\', "/");
         filtered_paths.insert(rel_path.clone());
+        if !tests.keeps(&rel_path) {
+            dropped_by_test_filter += 1;
+            continue;
+        }
 
         let filename = path
             .file_name()
@@ -3332,7 +6691,7 @@ pub fn repo_map_with_filter(
             .unwrap_or_default();
 
         // Track unsupported languages explicitly so agents know why a file is missing.
-        if cfg.driver_for_path(path).is_none() {
+        if cfg.driver_for_path(path).is_none() && BuildFile::for_path(path).is_none() {
             dropped_by_unsupported_lang += 1;
             if sample_unsupported.len() < 5 {
                 sample_unsupported.push(rel_path.clone());
@@ -3340,6 +6699,9 @@ pub fn repo_map_with_filter(
             continue;
         }
 
+        if let Some(kind) = linguist.classify(path) {
+            linguist_marked.insert(path.to_path_buf(), kind);
+        }
         supported_candidates.push((rel_path, filename, dir_rel, path.to_path_buf()));
     }
 
@@ -3361,8 +6723,8 @@ pub fn repo_map_with_filter(
                 .any(|t| rel_lc.contains(t) || file_lc.contains(t));
         }
 
-        if !matched && symbol_filter_enabled {
-            if let Ok(source_text) = std::fs::read_to_string(&abs_path) {
+        if !matched && symbol_filter_enabled && !linguist_marked.contains_key(&abs_path) {
+            if let Ok(Some(source_text)) = read_source_lossy(&abs_path) {
                 let syms = extract_symbols_from_source(&abs_path, &source_text);
                 matched = syms.into_iter().any(|s| {
                     let n = s.name.to_ascii_lowercase();
@@ -3390,41 +6752,12 @@ pub fn repo_map_with_filter(
     }
 
     // Compute gitignore/ignore-filter drops by comparing against an unfiltered walk.
-    let (scanned_total, dropped_by_gitignore_or_error) = if !ignore_gitignore {
-        let excluded_dir_set_all = excluded_dir_set.clone();
-        let walker_all = WalkBuilder::new(&abs_dir)
-            .standard_filters(false)
-            .hidden(true)
-            .filter_entry(move |dent| {
-                if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                    if let Some(name) = dent.path().file_name().and_then(|s| s.to_str()) {
-                        if excluded_dir_set_all.contains(name) {
-                            return false;
-                        }
-                    }
-                }
-                true
-            })
-            .build();
-
-        let mut all_file_count: usize = 0;
-        let mut all_error_count: usize = 0;
-
-        for entry_result in walker_all {
-            let entry = match entry_result {
-                Ok(e) => e,
-                Err(_) => {
-                    all_error_count += 1;
-                    continue;
+    let (scanned_total, dropped_by_gitignore_or_error) = match unfiltered_walk {
+        Some((all_files, all_error_count)) => {
+            for path in &all_files {
+                if sample_dropped.len() >= 5 {
+                    break;
                 }
-            };
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-            all_file_count += 1;
-
-            if sample_dropped.len() < 5 {
                 if let Ok(rel_from_target) = path.strip_prefix(&abs_dir) {
                     let rel_path = rel_from_target.to_string_lossy().replace('\\', "/");
                     if !filtered_paths.contains(&rel_path) {
@@ -3432,18 +6765,20 @@ pub fn repo_map_with_filter(
                     }
                 }
             }
-        }
 
-        let scanned_total = all_file_count.saturating_add(all_error_count);
-        let dropped_by_gitignore_or_error = all_file_count
-            .saturating_sub(filtered_file_count)
-            .saturating_add(filtered_error_count);
-        (scanned_total, dropped_by_gitignore_or_error)
-    } else {
-        // With ignore_gitignore=true, the filtered walker is the full view.
-        let scanned_total = filtered_file_count.saturating_add(filtered_error_count);
-        let dropped_by_gitignore_or_error = filtered_error_count;
-        (scanned_total, dropped_by_gitignore_or_error)
+            let all_file_count = all_files.len();
+            let scanned_total = all_file_count.saturating_add(all_error_count);
+            let dropped_by_gitignore_or_error = all_file_count
+                .saturating_sub(filtered_file_count)
+                .saturating_add(filtered_error_count);
+            (scanned_total, dropped_by_gitignore_or_error)
+        }
+        None => {
+            // With ignore files disabled, the filtered walker is the full view.
+            let scanned_total = filtered_file_count.saturating_add(filtered_error_count);
+            let dropped_by_gitignore_or_error = filtered_error_count;
+            (scanned_total, dropped_by_gitignore_or_error)
+        }
     };
 
     // Merge unsupported/filter samples into the dropped sample list (max 5 total).
@@ -3495,17 +6830,25 @@ pub fn repo_map_with_filter(
         } else {
             String::new()
         };
+        let tests_hint = if dropped_by_test_filter > 0 {
+            format!(
+                "\n• include_tests hid {dropped_by_test_filter} file(s) — try include_tests='all'."
+            )
+        } else {
+            String::new()
+        };
         return Err(anyhow!(
             "{}Error: 0 supported source files found in '{}'.\n\
 Diagnostics:\n\
 • Ensure the path is correct relative to the repo root.\n\
-• If files exist but are ignored, try again with `ignore_gitignore`: true.\n\
+• If files exist but are ignored, try again with `gitignore_mode`: 'exclude_vcs' (or `ignore_gitignore`: true).\n\
 • If the repo uses languages/extensions not yet supported, they will be skipped.\n\
-• If `search_filter` was set, it may have excluded everything — try without it.{}\n\
+• If `search_filter` was set, it may have excluded everything — try without it.{}{}\n\
 Supported extensions include: rs, ts, tsx, js, jsx, py, go.",
             regex_note,
             target_dir.display(),
-            filter_hint
+            filter_hint,
+            tests_hint
         ));
     }
 
@@ -3546,6 +6889,13 @@ Supported extensions include: rs, ts, tsx, js, jsx, py, go.",
                 if cut > 0 {
                     out.push_str(&s[..cut]);
                 }
+            } else {
+                // Too little room left for the marker: give back earlier output.
+                let mut cut = max_chars_total.saturating_sub(marker.len()).min(out.len());
+                while cut > 0 && !out.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                out.truncate(cut);
             }
             out.push_str(marker);
             false
@@ -3567,7 +6917,8 @@ Supported extensions include: rs, ts, tsx, js, jsx, py, go.",
 
     let dropped_total = dropped_by_gitignore_or_error
         .saturating_add(dropped_by_unsupported_lang)
-        .saturating_add(dropped_by_search_filter);
+        .saturating_add(dropped_by_search_filter)
+        .saturating_add(dropped_by_test_filter);
     push(&format!("{root_name}/   ({kept_source_files} files)\n"));
     push(&format!(
         "> 📊 Scanned: {scanned_total} items | Kept Source Files: {kept_source_files} | Dropped: {dropped_total} (ignored/errors: {dropped_by_gitignore_or_error}, unsupported: {dropped_by_unsupported_lang}, filtered_out: {dropped_by_search_filter})\n"
@@ -3580,6 +6931,20 @@ Supported extensions include: rs, ts, tsx, js, jsx, py, go.",
             .join(", ");
         push(&format!("> 🗑️ Sample dropped files: {joined}\n"));
     }
+    if dropped_by_test_filter > 0 {
+        let verb = match tests.mode() {
+            IncludeTests::Only => "non-test",
+            _ => "test",
+        };
+        push(&format!(
+            "> 🧪 {dropped_by_test_filter} {verb} file(s) hidden by include_tests.\n"
+        ));
+    }
+    if let Some(depth) = max_depth {
+        push(&format!(
+            "> 🔭 max_depth={depth}: files deeper than {depth} level(s) are not shown. Call map_overview on a sub-folder to zoom in.\n"
+        ));
+    }
     push("\n");
 
     match disclosure {
@@ -3613,97 +6978,648 @@ Supported extensions include: rs, ts, tsx, js, jsx, py, go.",
                     && !push(&format!("\n{dir_rel}/\n")) {
                         break;
                     }
-                for (filename, _abs) in files {
-                    if !push(&format!("  {filename}\n")) {
+                for (filename, abs) in files {
+                    let line = match linguist_marked.get(&abs) {
+                        Some(kind) => format!("  {filename}  ({}, not parsed)\n", kind.as_str()),
+                        None => format!("  {filename}\n"),
+                    };
+                    if !push(&line) {
+                        break;
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Disclosure::Deep => {
+            // Deep mode: read files + extract symbols. Once `push` refuses, the cap is
+            // reached and nothing more will be appended, so stop before reading or
+            // parsing any further file.
+            'dirs: for (dir_rel, mut files) in by_dir_files {
+                files.sort_by(|a, b| a.0.cmp(&b.0));
+                if !dir_rel.is_empty()
+                    && !push(&format!("\n{dir_rel}/\n")) {
                         break;
                     }
+
+                for (filename, abs_file) in files {
+                    if let Some(kind) = linguist_marked.get(&abs_file) {
+                        if !push(&format!("  {filename}  ({}, not parsed)\n", kind.as_str())) {
+                            break 'dirs;
+                        }
+                        continue;
+                    }
+                    if !push(&format!("  {filename}\n")) {
+                        break 'dirs;
+                    }
+
+                    let Ok(Some(source_text)) = read_source_lossy(&abs_file) else {
+                        continue;
+                    };
+                    let syms = if complexity_threshold.is_some() {
+                        extract_symbols_with_complexity(&abs_file, &source_text)
+                    } else {
+                        extract_symbols_from_source(&abs_file, &source_text)
+                    };
+                    let source_lines: Vec<&str> = source_text.lines().collect();
+
+                    let mut sym_pairs: Vec<(String, String, Option<u32>)> = syms
+                        .into_iter()
+                        .filter(|s| is_public_symbol(s, &source_lines, &abs_file))
+                        .take(MAX_SYMS_PER_FILE)
+                        .map(|s| (s.kind.clone(), s.name.clone(), s.complexity))
+                        .collect();
+                    sym_pairs.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+                    for (kind, name, complexity) in sym_pairs {
+                        let flag = match (complexity, complexity_threshold) {
+                            (Some(c), Some(t)) if c >= t => format!("  ⚠ complexity {c}"),
+                            _ => String::new(),
+                        };
+                        if !push(&format!("    [{:<8}] {name}{flag}\n", kind)) {
+                            break 'dirs;
+                        }
+                    }
                 }
             }
+
             Ok(out)
         }
-        Disclosure::Deep => {
-            // Deep mode: read files + extract symbols.
-            for (dir_rel, mut files) in by_dir_files {
-                files.sort_by(|a, b| a.0.cmp(&b.0));
-                if !dir_rel.is_empty()
-                    && !push(&format!("\n{dir_rel}/\n")) {
-                        break;
-                    }
+    }
+}
+
+/// Determine whether a symbol should be considered "public" for repo_map display.
+///
+/// Uses a fast source-line heuristic rather than AST predicates so it never fails.
+/// - **Rust**: declaration line contains `pub ` or `pub(`
+/// - **Python**: name does not start with `_`
+/// - **Go**: name starts with an ASCII upper-case letter
+/// - **TypeScript/JS**: show all top-level symbols (exports are shown by TS driver,
+///   but here we always include since we're doing a map, not a strict export list)
+/// - **Everything else**: include all symbols
+fn is_public_symbol(sym: &Symbol, source_lines: &[&str], path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match ext.as_str() {
+        "rs" => {
+            // For agentic repo mapping, private Rust symbols are often just as
+            // useful as `pub` ones. Also, attribute/doc/macro lines can precede
+            // the actual declaration, making naive `pub` string checks brittle.
+            //
+            // Intentionally bypass public-only filtering for Rust.
+            let _ = (sym, source_lines);
+            true
+        }
+        "py" => !sym.name.starts_with('_'),
+        "go" => sym
+            .name
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_uppercase())
+            .unwrap_or(false),
+        // TypeScript/JS/Java/C#/Dart/PHP — include everything
+        _ => true,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool: signatures — The Phonebook
+// ---------------------------------------------------------------------------
+
+/// Chars per token used to size [`signatures_dump`] output.
+pub const SIGNATURES_CHARS_PER_TOKEN: usize = 4;
+
+/// Dump every public signature under `target_dir`, grouped by file, until
+/// `budget_tokens` (~4 chars/token) is spent.
+///
+/// Denser than `repo_map` (no 8 KB cap, no folder summaries) but body-free, so it
+/// sits between map_overview and deep_slice. Files are ranked by the same
+/// centrality heuristic as the slicer: path importance + 10 × in-repo import
+/// in-degree, so the most depended-upon files are emitted first. Imports resolve
+/// against `repo_root` and ranked paths are shown relative to it.
+pub fn signatures_dump(repo_root: &Path, target_dir: &Path, budget_tokens: usize) -> Result<String> {
+    use rayon::prelude::*;
+    use std::collections::HashMap;
+
+    const CHARS_PER_TOKEN: usize = SIGNATURES_CHARS_PER_TOKEN;
+
+    let repo_root = repo_root.canonicalize().unwrap_or_else(|_| repo_root.to_path_buf());
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        repo_root.join(target_dir)
+    };
+    let abs_dir = abs_dir.canonicalize().unwrap_or(abs_dir);
+
+    let (files, _errors) = collect_walk_files(build_walker(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+    ));
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
+    let files: Vec<PathBuf> = {
+        let cfg = language_config().read().unwrap();
+        files
+            .into_iter()
+            .filter(|p| cfg.driver_for_path(p).is_some() && linguist.classify(p).is_none())
+            .collect()
+    };
+
+    // (abs_path, signature lines, resolved in-repo imports)
+    let analyzed: Vec<(PathBuf, Vec<String>, Vec<PathBuf>)> = files
+        .par_iter()
+        .filter_map(|path| {
+            let source_text = read_source_lossy(path).ok()??;
+            if is_minified_or_generated(&source_text) {
+                return None;
+            }
+            let fs = analyze_source_at(path, path, &source_text).ok()?;
+            let lines: Vec<&str> = source_text.lines().collect();
+            let is_rust = path_ext_lower(path) == "rs";
+
+            let sigs: Vec<String> = fs
+                .symbols
+                .iter()
+                .filter(|sym| is_public_symbol(sym, &lines, path))
+                .filter_map(|sym| {
+                    let decl = lines.get(sym.line as usize).copied().unwrap_or("");
+                    // Rust: is_public_symbol keeps private items for maps; here we want `pub` only.
+                    if is_rust && !decl.trim_start().starts_with("pub") {
+                        return None;
+                    }
+                    let sig = sym
+                        .signature
+                        .clone()
+                        .unwrap_or_else(|| first_line_signature(decl));
+                    Some(format!("L{} {}", sym.line + 1, sig))
+                })
+                .collect();
+
+            let imports: Vec<PathBuf> = fs
+                .imports
+                .iter()
+                .filter_map(|imp| crate::mapper::resolve_import_path(&repo_root, path, imp))
+                .collect();
+
+            Some((path.clone(), sigs, imports))
+        })
+        .collect();
+
+    let mut indegree: HashMap<PathBuf, i64> = HashMap::new();
+    for (src, _, imports) in &analyzed {
+        for dst in imports {
+            if dst != src {
+                *indegree.entry(dst.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(i64, String, Vec<String>)> = analyzed
+        .into_iter()
+        .filter(|(_, sigs, _)| !sigs.is_empty())
+        .map(|(abs, sigs, _)| {
+            let rel = abs
+                .strip_prefix(&repo_root)
+                .unwrap_or(&abs)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let score = crate::slicer::importance_score(&rel)
+                + indegree.get(&abs).copied().unwrap_or(0) * 10;
+            (score, rel, sigs)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let budget_chars = budget_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let total_files = ranked.len();
+    let mut body = String::new();
+    let mut emitted_files = 0usize;
+    for (_, rel, sigs) in &ranked {
+        let mut block = format!("\n## {rel}\n");
+        for sig in sigs {
+            block.push_str(sig);
+            block.push('\n');
+        }
+        if body.len() + block.len() > budget_chars {
+            break;
+        }
+        body.push_str(&block);
+        emitted_files += 1;
+    }
+
+    if total_files == 0 {
+        return Err(anyhow!(
+            "No public signatures found under '{}'. Check the path, or use \
+            cortex_code_explorer(action=map_overview) to see what is supported.",
+            abs_dir.display()
+        ));
+    }
+
+    let mut out = format!(
+        "# Signatures — {} ({emitted_files}/{total_files} files, ~{} / {budget_tokens} tokens)\n",
+        abs_dir
+            .file_name()
+            .unwrap_or(abs_dir.as_os_str())
+            .to_string_lossy(),
+        body.len().div_ceil(CHARS_PER_TOKEN),
+    );
+    out.push_str(&body);
+    if emitted_files < total_files {
+        out.push_str(&format!(
+            "\n> ✂️ Budget reached: {} lower-ranked files omitted. \
+            Raise `budget_tokens` or narrow `target_dir`.\n",
+            total_files - emitted_files
+        ));
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tool: skeleton_dir — render_skeleton for a whole folder
+// ---------------------------------------------------------------------------
+
+/// [`render_skeleton`] of every supported file under `target_dir`, in path order,
+/// each under a `## <path>` header (relative to `target_dir`), until `budget_tokens`
+/// (~4 chars/token) is spent.
+///
+/// Binary, minified and oversized files keep their one-line stubs, so the listing
+/// still shows they exist. The CLI form of `deep_slice` with `skeleton_only=true`,
+/// minus the query ranking.
+pub fn skeleton_dir(target_dir: &Path, budget_tokens: usize) -> Result<String> {
+    use rayon::prelude::*;
+
+    const CHARS_PER_TOKEN: usize = 4;
+
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get cwd")?
+            .join(target_dir)
+    };
+    if !abs_dir.is_dir() {
+        return Err(anyhow!("Not a directory: {}", abs_dir.display()));
+    }
+
+    let (files, _errors) = collect_walk_files(build_walker(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+    ));
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
+    let mut files: Vec<PathBuf> = {
+        let cfg = language_config().read().unwrap();
+        files
+            .into_iter()
+            .filter(|p| cfg.driver_for_path(p).is_some() && linguist.classify(p).is_none())
+            .collect()
+    };
+    files.sort();
+
+    let skeletons: Vec<(String, String)> = files
+        .par_iter()
+        .filter_map(|path| {
+            let skel = render_skeleton(path).ok()?;
+            let rel = path
+                .strip_prefix(&abs_dir)
+                .map(normalize_path_for_output)
+                .unwrap_or_else(|_| normalize_path_for_output(path));
+            Some((rel, skel))
+        })
+        .collect();
+
+    if skeletons.is_empty() {
+        return Err(anyhow!(
+            "No supported source files under '{}'.",
+            abs_dir.display()
+        ));
+    }
+
+    let budget_chars = budget_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let mut body = String::new();
+    let mut emitted_files = 0usize;
+    for (rel, skel) in &skeletons {
+        let mut block = format!("\n## {rel}\n{skel}");
+        if !block.ends_with('\n') {
+            block.push('\n');
+        }
+        if body.len() + block.len() > budget_chars {
+            break;
+        }
+        body.push_str(&block);
+        emitted_files += 1;
+    }
 
-                for (filename, abs_file) in files {
-                    if !push(&format!("  {filename}\n")) {
-                        break;
-                    }
+    let total_files = skeletons.len();
+    let mut out = format!(
+        "# Skeleton — {} ({emitted_files}/{total_files} files, ~{} / {budget_tokens} tokens)\n",
+        abs_dir
+            .file_name()
+            .unwrap_or(abs_dir.as_os_str())
+            .to_string_lossy(),
+        body.len().div_ceil(CHARS_PER_TOKEN),
+    );
+    out.push_str(&body);
+    if emitted_files < total_files {
+        out.push_str(&format!(
+            "\n> ✂️ Budget reached: {} later files omitted. \
+            Raise `budget_tokens` or narrow the directory.\n",
+            total_files - emitted_files
+        ));
+    }
+    Ok(out)
+}
 
-                    let Ok(source_text) = std::fs::read_to_string(&abs_file) else {
-                        continue;
-                    };
-                    let syms = extract_symbols_from_source(&abs_file, &source_text);
-                    let source_lines: Vec<&str> = source_text.lines().collect();
+// ---------------------------------------------------------------------------
+// Tool: ctags — editor tag file
+// ---------------------------------------------------------------------------
 
-                    let mut sym_pairs: Vec<(String, String)> = syms
-                        .into_iter()
-                        .filter(|s| is_public_symbol(s, &source_lines, &abs_file))
-                        .take(MAX_SYMS_PER_FILE)
-                        .map(|s| (s.kind.clone(), s.name.clone()))
-                        .collect();
-                    sym_pairs.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+/// A ctags `tags` file (extended format, sorted) for every declaration under
+/// `target_dir`, with file paths relative to `repo_root`.
+///
+/// Each entry is `name<TAB>file<TAB>/^decl line$/;"<TAB>kind<TAB>line:N`, where the
+/// search pattern is the declaration's first source line. Backs `cortexast --tags`.
+pub fn ctags(repo_root: &Path, target_dir: &Path) -> Result<String> {
+    use rayon::prelude::*;
 
-                    for (kind, name) in sym_pairs {
-                        if !push(&format!("    [{:<8}] {name}\n", kind)) {
-                            break;
-                        }
-                    }
-                }
-            }
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        repo_root.join(target_dir)
+    };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
 
-            Ok(out)
-        }
+    let (files, _errors) = collect_walk_files(build_walker(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+    ));
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
+    let files: Vec<PathBuf> = {
+        let cfg = language_config().read().unwrap();
+        files
+            .into_iter()
+            .filter(|p| cfg.driver_for_path(p).is_some() && linguist.classify(p).is_none())
+            .collect()
+    };
+
+    let mut entries: Vec<String> = files
+        .par_iter()
+        .flat_map_iter(|path| {
+            let text = std::fs::read_to_string(path).unwrap_or_default();
+            let rel = normalize_path_for_output(path.strip_prefix(repo_root).unwrap_or(path));
+            let lines: Vec<&str> = text.lines().collect();
+            extract_symbols_from_source(path, &text)
+                .into_iter()
+                .filter(|sym| !sym.name.contains(['\t', '\n']))
+                .map(|sym| {
+                    let decl = lines.get(sym.line as usize).copied().unwrap_or("");
+                    let pattern = decl.replace('\\', "\\\\").replace('/', "\\/");
+                    format!(
+                        "{}\t{rel}\t/^{pattern}$/;\"\t{}\tline:{}",
+                        sym.name,
+                        sym.kind,
+                        sym.line + 1
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    // `_TAG_FILE_SORTED 1` promises byte order, which editors binary-search.
+    entries.sort();
+    entries.dedup();
+
+    let mut out = String::from(
+        "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+         !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+         !_TAG_PROGRAM_NAME\tcortexast\t//\n",
+    );
+    for e in entries {
+        out.push_str(&e);
+        out.push('\n');
     }
+    Ok(out)
 }
 
-/// Determine whether a symbol should be considered "public" for repo_map display.
+// ---------------------------------------------------------------------------
+// Tool: repo_summary — first-contact profile
+// ---------------------------------------------------------------------------
+
+/// Files and lines one language driver parses, as counted by [`repo_summary`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageStats {
+    /// Driver name, as in `cortexast --languages`.
+    pub driver: String,
+    pub files: usize,
+    pub lines: usize,
+}
+
+/// Compact profile of a repository, as returned by [`repo_summary`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoSummary {
+    /// Summarised directory, relative to the repo root (`.` for the root).
+    pub root: String,
+    /// Source files with a language driver.
+    pub files: usize,
+    /// Lines across those files.
+    pub lines: usize,
+    /// Per-driver counts, most files first.
+    pub languages: Vec<LanguageStats>,
+    /// Declarations by symbol kind (`function`, `struct`, `class`, ...).
+    pub symbols: std::collections::BTreeMap<String, usize>,
+    /// Non-hidden directories directly under the root, sorted.
+    pub top_level_dirs: Vec<String>,
+    /// Manifest at the root (`cargo`, `npm`, `python`, `go`), if any.
+    pub manifest: Option<String>,
+    /// Workspace members found under the root (relative paths), shallowest first.
+    pub workspace_members: Vec<String>,
+    /// Two or more workspace members.
+    pub monorepo: bool,
+}
+
+/// Profile `target_dir` for a first look: languages with file and line counts per
+/// driver, declarations by kind, top-level directories and workspace layout.
 ///
-/// Uses a fast source-line heuristic rather than AST predicates so it never fails.
-/// - **Rust**: declaration line contains `pub ` or `pub(`
-/// - **Python**: name does not start with `_`
-/// - **Go**: name starts with an ASCII upper-case letter
-/// - **TypeScript/JS**: show all top-level symbols (exports are shown by TS driver,
-///   but here we always include since we're doing a map, not a strict export list)
-/// - **Everything else**: include all symbols
-fn is_public_symbol(sym: &Symbol, source_lines: &[&str], path: &Path) -> bool {
-    let ext = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase();
+/// Walks the same files as [`ctags`] (gitignore and `.gitattributes` linguist hints
+/// respected) and discovers members like `cortexast --list-members`.
+pub fn repo_summary(repo_root: &Path, target_dir: &Path) -> Result<RepoSummary> {
+    use rayon::prelude::*;
+    use std::collections::BTreeMap;
 
-    match ext.as_str() {
-        "rs" => {
-            // For agentic repo mapping, private Rust symbols are often just as
-            // useful as `pub` ones. Also, attribute/doc/macro lines can precede
-            // the actual declaration, making naive `pub` string checks brittle.
-            //
-            // Intentionally bypass public-only filtering for Rust.
-            let _ = (sym, source_lines);
-            true
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        repo_root.join(target_dir)
+    };
+    if !abs_dir.is_dir() {
+        return Err(anyhow!("not a directory: {}", abs_dir.display()));
+    }
+
+    let (files, _errors) = collect_walk_files(build_walker(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+    ));
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
+    let files: Vec<(PathBuf, &'static str)> = {
+        let cfg = language_config().read().unwrap();
+        files
+            .into_iter()
+            .filter(|p| linguist.classify(p).is_none())
+            .filter_map(|p| {
+                let name = cfg.driver_for_path(&p)?.name();
+                Some((p, name))
+            })
+            .collect()
+    };
+
+    let per_file: Vec<(&'static str, usize, Vec<String>)> = files
+        .par_iter()
+        .map(|(path, driver)| {
+            let text = std::fs::read_to_string(path).unwrap_or_default();
+            let kinds = extract_symbols_from_source(path, &text)
+                .into_iter()
+                .map(|s| s.kind)
+                .collect();
+            (*driver, text.lines().count(), kinds)
+        })
+        .collect();
+
+    let mut by_driver: BTreeMap<&'static str, (usize, usize)> = BTreeMap::new();
+    let mut symbols: BTreeMap<String, usize> = BTreeMap::new();
+    let mut lines = 0usize;
+    for (driver, n, kinds) in per_file {
+        let e = by_driver.entry(driver).or_default();
+        e.0 += 1;
+        e.1 += n;
+        lines += n;
+        for kind in kinds {
+            *symbols.entry(kind).or_insert(0) += 1;
         }
-        "py" => !sym.name.starts_with('_'),
-        "go" => sym
-            .name
-            .chars()
-            .next()
-            .map(|c| c.is_ascii_uppercase())
-            .unwrap_or(false),
-        // TypeScript/JS/Java/C#/Dart/PHP — include everything
-        _ => true,
     }
+    let mut languages: Vec<LanguageStats> = by_driver
+        .into_iter()
+        .map(|(driver, (files, lines))| LanguageStats {
+            driver: driver.to_string(),
+            files,
+            lines,
+        })
+        .collect();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.driver.cmp(&b.driver)));
+
+    let mut top_level_dirs: Vec<String> = build_walker_to_depth(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+        Some(1),
+    )
+    .filter_map(|e| e.ok())
+    .filter(|e| e.depth() == 1 && e.file_type().is_some_and(|t| t.is_dir()))
+    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+    .collect();
+    top_level_dirs.sort();
+
+    let manifest = match detect_manifest_kind(&abs_dir) {
+        ManifestKind::Unknown => None,
+        kind => Some(kind.to_string()),
+    };
+    let workspace_members: Vec<String> =
+        crate::workspace::discover_workspace_members(&abs_dir, &Default::default())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.rel_path)
+            .collect();
+
+    let rel = abs_dir.strip_prefix(repo_root).unwrap_or(&abs_dir);
+    let root = match normalize_path_for_output(rel) {
+        r if r.is_empty() => ".".to_string(),
+        r => r,
+    };
+    Ok(RepoSummary {
+        root,
+        files: files.len(),
+        lines,
+        languages,
+        symbols,
+        top_level_dirs,
+        manifest,
+        monorepo: workspace_members.len() >= 2,
+        workspace_members,
+    })
+}
+
+/// Markdown rendering of [`repo_summary`] for agents.
+pub fn format_repo_summary(s: &RepoSummary) -> String {
+    let mut out = format!(
+        "## Repo summary: `{}`\n\n{} source file(s), {} line(s)\n",
+        s.root, s.files, s.lines
+    );
+
+    out.push_str("\n### Languages\n\n");
+    if s.languages.is_empty() {
+        out.push_str("(no files with a language driver)\n");
+    }
+    for l in &s.languages {
+        out.push_str(&format!(
+            "- {}: {} file(s), {} line(s)\n",
+            l.driver, l.files, l.lines
+        ));
+    }
+
+    if !s.symbols.is_empty() {
+        let mut kinds: Vec<(&String, &usize)> = s.symbols.iter().collect();
+        kinds.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let kinds: Vec<String> = kinds.iter().map(|(k, n)| format!("{k} {n}")).collect();
+        out.push_str(&format!("\n### Symbols\n\n{}\n", kinds.join(", ")));
+    }
+
+    if !s.top_level_dirs.is_empty() {
+        out.push_str(&format!(
+            "\n### Top-level directories\n\n{}\n",
+            s.top_level_dirs.join(", ")
+        ));
+    }
+
+    out.push_str("\n### Layout\n\n");
+    match &s.manifest {
+        Some(m) => out.push_str(&format!("- Root manifest: {m}\n")),
+        None => out.push_str("- Root manifest: none\n"),
+    }
+    if s.monorepo {
+        out.push_str(&format!(
+            "- Monorepo: {} workspace members ({})\n",
+            s.workspace_members.len(),
+            s.workspace_members.join(", ")
+        ));
+    } else if let Some(m) = s.workspace_members.first() {
+        out.push_str(&format!("- Single project (member: {m})\n"));
+    } else {
+        out.push_str("- Single project\n");
+    }
+    out
 }
 
 // ---------------------------------------------------------------------------
 // Tool: call_hierarchy — The Call Graph
 // ---------------------------------------------------------------------------
 
+/// What [`call_hierarchy_with_options`] does with outgoing calls listed in [`CALL_NOISE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CallNoise {
+    /// Leave them out of the outgoing call list.
+    #[default]
+    Hide,
+    /// Leave them out, but close the list with how many were left out and which.
+    Count,
+    /// List them like any other call (`filter_noise: false`).
+    Keep,
+}
+
 /// Language-agnostic deny-list of common stdlib / runtime method names that
 /// produce noise in the outgoing call list without conveying domain intent.
 ///
@@ -3920,7 +7836,19 @@ static CALL_NOISE: &[&str] = &[
 /// # Arguments
 /// * `target_dir`   — directory to search (respects `.gitignore`)
 /// * `symbol_name`  — exact symbol name (case-sensitive)
-pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
+/// * `paths`        — how file paths are printed
+pub fn call_hierarchy(target_dir: &Path, symbol_name: &str, paths: PathStyle) -> Result<String> {
+    call_hierarchy_with_options(target_dir, symbol_name, paths, CallNoise::Hide)
+}
+
+/// [`call_hierarchy`] with a choice of what happens to stdlib / built-in outgoing
+/// calls ([`CALL_NOISE`]).
+pub fn call_hierarchy_with_options(
+    target_dir: &Path,
+    symbol_name: &str,
+    paths: PathStyle,
+    noise: CallNoise,
+) -> Result<String> {
     use ignore::WalkBuilder;
 
     let abs_dir: PathBuf = if target_dir.is_absolute() {
@@ -3938,21 +7866,25 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
         file: String,
         line_1: u32,
         kind: String,
+        exported: bool,
     }
 
     let mut definitions: Vec<DefSite> = Vec::new();
     let mut outgoing_calls: Vec<(String, u32, String)> = Vec::new(); // (callee, abs_line_1, file)
     let mut callers: Vec<(String, u32, Option<String>, String)> = Vec::new(); // (file, line_1, enclosing, ctx)
+    let mut exported_callers: Vec<String> = Vec::new(); // "pub fn x (file:L)" — callers that are public API
+    let mut risk_files: Vec<PathBuf> = Vec::new(); // one per definition / call site
 
     let walker = WalkBuilder::new(&abs_dir)
         .standard_filters(true)
         .hidden(true)
         .build();
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
 
     for entry_result in walker {
         let Ok(entry) = entry_result else { continue };
         let path = entry.path();
-        if !path.is_file() {
+        if !path.is_file() || linguist.classify(path).is_some() {
             continue;
         }
         if cfg.driver_for_path(path).is_none() {
@@ -3983,13 +7915,13 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
                 continue;
             }
         };
-        let Some(tree) = parser.parse(source_text, None) else {
+        let Ok(tree) = parse_with_limits(&mut parser, source_text) else {
             continue;
         };
         let root = tree.root_node();
 
         let text_lines: Vec<&str> = source_text.lines().collect();
-        let display_path = path.to_string_lossy().to_string();
+        let display_path = paths.show(path);
 
         // Extract skeleton (symbol list) for this file — used for definition
         // detection AND for resolving enclosing function context.
@@ -4004,7 +7936,11 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
                 file: display_path.clone(),
                 line_1: sym.line + 1,
                 kind: sym.kind.clone(),
+                exported: is_exported_decl(
+                    text_lines.get(sym.line as usize).copied().unwrap_or(""),
+                ),
             });
+            risk_files.push(path.to_path_buf());
 
             // Re-parse the definition body text to extract outgoing call targets.
             let body_start = sym.line as usize;
@@ -4013,7 +7949,7 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
             let body_bytes = body_text.as_bytes();
 
             if let Ok(mut body_parser) = driver.make_parser(path) {
-                if let Some(body_tree) = body_parser.parse(&body_text, None) {
+                if let Ok(body_tree) = parse_with_limits(&mut body_parser, &body_text) {
                     let body_root = body_tree.root_node();
                     let mut raw_calls: Vec<(String, u32)> = Vec::new();
                     extract_call_targets_from_body(body_root, body_bytes, &mut raw_calls);
@@ -4033,27 +7969,32 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
 
         for row_0 in call_rows {
             // Find the tightest enclosing function/method
-            let enclosing = syms
-                .iter()
-                .filter(|s| {
-                    s.line <= row_0
-                        && row_0 <= s.line_end
-                        && matches!(
-                            s.kind.as_str(),
-                            "fn" | "function" | "method" | "arrow_function"
-                        )
-                })
-                .min_by_key(|s| row_0 - s.line)
-                .map(|s| format!("{} {}()", s.kind, s.name));
+            let enclosing_sym = enclosing_callable(&syms, row_0);
+            let enclosing = enclosing_sym.map(|s| format!("{} {}()", s.kind, s.name));
+            if let Some(s) = enclosing_sym {
+                if is_exported_decl(text_lines.get(s.line as usize).copied().unwrap_or("")) {
+                    exported_callers.push(format!("{} ({display_path}:L{})", s.name, s.line + 1));
+                }
+            }
 
             let ctx = extract_context_lines(&text_lines, row_0 as usize, 2);
             callers.push((display_path.clone(), row_0 + 1, enclosing, ctx));
+            risk_files.push(path.to_path_buf());
         }
     }
 
     // ── Format Markdown output ────────────────────────────────────────────
     let mut out = format!("## Call Hierarchy: `{symbol_name}`\n\n");
 
+    let files: Vec<&Path> = risk_files.iter().map(PathBuf::as_path).collect();
+    let mut exported: Vec<String> = definitions
+        .iter()
+        .filter(|d| d.exported)
+        .map(|d| format!("definition ({}:L{})", d.file, d.line_1))
+        .collect();
+    exported.extend(exported_callers);
+    out.push_str(&rename_risk(&abs_dir, &files, &exported).render());
+
     if definitions.is_empty() {
         out.push_str("> No definition found in target_dir — showing inbound call sites only.\n\n");
     } else {
@@ -4070,40 +8011,442 @@ pub fn call_hierarchy(target_dir: &Path, symbol_name: &str) -> Result<String> {
     } else {
         outgoing_calls.sort_by_key(|(_, line, _)| *line);
         let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut filtered: Vec<&str> = Vec::new();
         for (callee, line, file) in &outgoing_calls {
             // Skip common stdlib / language-runtime noise that produces no signal.
-            if CALL_NOISE.contains(&callee.as_str()) {
+            if noise != CallNoise::Keep && CALL_NOISE.contains(&callee.as_str()) {
+                if !filtered.contains(&callee.as_str()) {
+                    filtered.push(callee);
+                }
                 continue;
             }
             if seen.insert(callee.clone()) {
                 out.push_str(&format!("- `{callee}` — {file}:L{line}\n"));
             }
         }
-        if seen.is_empty() {
-            out.push_str("- *(stdlib/built-in methods only — no domain calls detected)*\n");
+        if seen.is_empty() {
+            out.push_str("- *(stdlib/built-in methods only — no domain calls detected)*\n");
+        }
+        if noise == CallNoise::Count && !filtered.is_empty() {
+            let names: Vec<String> = filtered.iter().map(|n| format!("`{n}`")).collect();
+            out.push_str(&format!(
+                "- *({} stdlib/built-in calls filtered: {})*\n",
+                filtered.len(),
+                names.join(", ")
+            ));
+        }
+    }
+    out.push('\n');
+
+    const MAX_CALLERS: usize = 30;
+    out.push_str("### Incoming Calls (callers of this symbol)\n");
+    if callers.is_empty() {
+        out.push_str("- *(none detected)*\n");
+    } else {
+        for (file, line_1, enclosing, ctx) in callers.iter().take(MAX_CALLERS) {
+            let enc_str = enclosing.as_deref().unwrap_or("(top-level)");
+            out.push_str(&format!("\n**{file}:{line_1}** in `{enc_str}`\n"));
+            out.push_str(&format!("```\n{ctx}\n```\n"));
+        }
+        if callers.len() > MAX_CALLERS {
+            out.push_str(&format!(
+                "\n*... {} more callers not shown*\n",
+                callers.len() - MAX_CALLERS
+            ));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Go/no-go summary printed at the top of `blast_radius`.
+struct RenameRisk {
+    level: &'static str,
+    files: usize,
+    packages: Vec<String>,
+    exported: Vec<String>,
+    ubiquitous: bool,
+}
+
+impl RenameRisk {
+    fn render(&self) -> String {
+        let mut out = format!("### Rename Risk: **{}**\n", self.level);
+        out.push_str(&format!("- {} file(s) define or call it\n", self.files));
+        match self.packages.len() {
+            0 => out.push_str("- no package manifest found above these files\n"),
+            1 => out.push_str(&format!(
+                "- stays within one package (`{}`)\n",
+                self.packages[0]
+            )),
+            n => out.push_str(&format!(
+                "- crosses {n} packages: {}\n",
+                self.packages
+                    .iter()
+                    .map(|p| format!("`{p}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+        if self.exported.is_empty() {
+            out.push_str("- no public API (exported) definition or caller\n");
+        } else {
+            const SHOWN: usize = 3;
+            let more = self.exported.len().saturating_sub(SHOWN);
+            let mut list = self.exported[..self.exported.len().min(SHOWN)].join(", ");
+            if more > 0 {
+                list.push_str(&format!(" (+{more} more)"));
+            }
+            out.push_str(&format!("- public API: {list}\n"));
+        }
+        if self.ubiquitous {
+            out.push_str(&format!(
+                "- ubiquitous: more than {MAX_CHECKLIST_FILES} files — scope the rename with a narrower target_dir\n"
+            ));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Scores a rename of a symbol seen in `files` (definitions and call sites, repeats
+/// allowed). Many files, several packages and public-API exposure each raise the
+/// level; more than [`MAX_CHECKLIST_FILES`] files is always High.
+fn rename_risk(scope: &Path, files: &[&Path], exported: &[String]) -> RenameRisk {
+    use std::collections::BTreeSet;
+
+    let distinct: BTreeSet<&Path> = files.iter().copied().collect();
+    let packages: BTreeSet<String> = distinct
+        .iter()
+        .filter_map(|f| package_root(f))
+        .map(|root| match root.strip_prefix(scope) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => normalize_path_for_output(rel),
+            Err(_) => normalize_path_for_output(&root),
+        })
+        .collect();
+    let ubiquitous = distinct.len() > MAX_CHECKLIST_FILES;
+
+    let mut score = 0;
+    if distinct.len() >= 10 {
+        score += 2;
+    } else if distinct.len() >= 3 {
+        score += 1;
+    }
+    if packages.len() > 1 {
+        score += 2;
+    }
+    if !exported.is_empty() {
+        score += 1;
+    }
+    let level = if ubiquitous || score >= 3 {
+        "High"
+    } else if score >= 1 {
+        "Medium"
+    } else {
+        "Low"
+    };
+
+    RenameRisk {
+        level,
+        files: distinct.len(),
+        packages: packages.into_iter().collect(),
+        exported: exported.to_vec(),
+        ubiquitous,
+    }
+}
+
+/// Nearest directory above `file` holding a package manifest (`Cargo.toml`,
+/// `package.json`, `pyproject.toml`, `setup.py`, `go.mod`), not climbing past the
+/// repository root.
+fn package_root(file: &Path) -> Option<PathBuf> {
+    for dir in file.ancestors().skip(1) {
+        if detect_manifest_kind(dir) != ManifestKind::Unknown {
+            return Some(dir.to_path_buf());
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Tool: apply_rename — opt-in mechanical rename (the only tool that writes code)
+// ---------------------------------------------------------------------------
+
+/// One file touched by [`apply_rename`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedFile {
+    /// Path relative to `target_dir`.
+    pub path: String,
+    /// Identifier occurrences rewritten in this file.
+    pub occurrences: usize,
+}
+
+/// Result of [`apply_rename`]: the files touched and a unified diff of the edit.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameOutcome {
+    pub files: Vec<RenamedFile>,
+    pub diff: String,
+    /// False for a dry run: nothing was written.
+    pub applied: bool,
+}
+
+/// Rename every AST identifier named `old` under `target_dir` to `new`, using the
+/// same matching as [`find_usages`] (comments and string literals are never
+/// touched). Matching is by name, not by scope: every same-named identifier in
+/// the walked files is renamed, so scope `target_dir` accordingly.
+///
+/// Refuses a symbol that `blast_radius` would flag as ubiquitous (more than
+/// [`MAX_CHECKLIST_FILES`] files) unless `force` is set, and a `new` name that is a
+/// keyword in the language of any file it would be written to. With `dry_run` the
+/// diff is computed but no file is written; otherwise every renamed file is staged
+/// beside its original and only moved into place once all of them were written.
+pub fn apply_rename(
+    target_dir: &Path,
+    old: &str,
+    new: &str,
+    dry_run: bool,
+    force: bool,
+) -> Result<RenameOutcome> {
+    let is_ident = |s: &str| {
+        s.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+            && s.chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    };
+    if !is_ident(old) || !is_ident(new) {
+        return Err(anyhow!(
+            "apply_rename needs two plain identifiers (got `{old}` -> `{new}`)"
+        ));
+    }
+    if old == new {
+        return Err(anyhow!("apply_rename: `{old}` is already named `{new}`"));
+    }
+
+    let mut matched: Vec<(PathBuf, String)> = Vec::new();
+    let abs_dir = walk_usages(
+        target_dir,
+        old,
+        GitignoreMode::Respect,
+        &[],
+        false,
+        false,
+        |path, source_text, _, _| matched.push((path.to_path_buf(), source_text.to_string())),
+    )?;
+    if matched.is_empty() {
+        return Err(anyhow!(
+            "No usages of `{old}` found in {}.",
+            abs_dir.display()
+        ));
+    }
+
+    if let Some((path, _)) = matched.iter().find(|(p, _)| is_reserved_word(p, new)) {
+        return Err(anyhow!(
+            "apply_rename: `{new}` is a keyword in {} and cannot name an identifier",
+            normalize_path_for_output(path.strip_prefix(&abs_dir).unwrap_or(path))
+        ));
+    }
+
+    let files: Vec<&Path> = matched.iter().map(|(p, _)| p.as_path()).collect();
+    let risk = rename_risk(&abs_dir, &files, &[]);
+    if risk.ubiquitous && !force {
+        return Err(anyhow!(
+            "Refusing to rename `{old}`: it appears in {} files (more than {MAX_CHECKLIST_FILES}). \
+            Narrow target_dir, or pass --force to rename it everywhere.",
+            risk.files
+        ));
+    }
+
+    let cfg_lock = language_config().read().unwrap();
+    let cfg = &*cfg_lock;
+    let mut edits: Vec<(PathBuf, String, RenamedFile)> = Vec::new();
+    let mut diff = String::new();
+    for (path, source_text) in matched {
+        let Some(driver) = cfg.driver_for_path(&path) else {
+            continue;
+        };
+        let mut parser = driver.make_parser(&path)?;
+        let Ok(tree) = parse_with_limits(&mut parser, &source_text) else {
+            continue;
+        };
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        visit_identifier_refs(tree.root_node(), source_text.as_bytes(), old, &mut |hit| {
+            spans.push((hit.start_byte(), hit.end_byte()))
+        });
+        spans.sort_unstable();
+        spans.dedup();
+        if spans.is_empty() {
+            continue;
+        }
+
+        let mut renamed = String::with_capacity(source_text.len());
+        let mut last = 0;
+        for &(start, end) in &spans {
+            renamed.push_str(&source_text[last..start]);
+            renamed.push_str(new);
+            last = end;
         }
+        renamed.push_str(&source_text[last..]);
+
+        let rel = path
+            .strip_prefix(&abs_dir)
+            .map(normalize_path_for_output)
+            .unwrap_or_else(|_| normalize_path_for_output(&path));
+        diff.push_str(&unified_line_diff(&rel, &source_text, &renamed));
+        edits.push((
+            path,
+            renamed,
+            RenamedFile {
+                path: rel,
+                occurrences: spans.len(),
+            },
+        ));
     }
-    out.push('\n');
+    drop(cfg_lock);
 
-    const MAX_CALLERS: usize = 30;
-    out.push_str("### Incoming Calls (callers of this symbol)\n");
-    if callers.is_empty() {
-        out.push_str("- *(none detected)*\n");
-    } else {
-        for (file, line_1, enclosing, ctx) in callers.iter().take(MAX_CALLERS) {
-            let enc_str = enclosing.as_deref().unwrap_or("(top-level)");
-            out.push_str(&format!("\n**{file}:{line_1}** in `{enc_str}`\n"));
-            out.push_str(&format!("```\n{ctx}\n```\n"));
+    edits.sort_by(|a, b| a.2.path.cmp(&b.2.path));
+    if !dry_run {
+        write_all_or_nothing(edits.iter().map(|(path, renamed, _)| (path.as_path(), renamed)))?;
+    }
+    Ok(RenameOutcome {
+        files: edits.into_iter().map(|(_, _, f)| f).collect(),
+        diff,
+        applied: !dry_run,
+    })
+}
+
+/// Write every `(path, content)` pair as one batch: all contents go to staging files
+/// beside their targets first, and only when every one was written are they renamed
+/// over the originals (an atomic replace per file). A failed write removes the
+/// staged files and leaves every original untouched.
+fn write_all_or_nothing<'a>(
+    writes: impl Iterator<Item = (&'a Path, &'a String)>,
+) -> Result<()> {
+    let staging = |path: &Path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!(".{name}.cortexast-rename"))
+    };
+    let mut staged: Vec<(PathBuf, &Path)> = Vec::new();
+    for (path, content) in writes {
+        let tmp = staging(path);
+        let written = std::fs::write(&tmp, content).and_then(|()| {
+            // Keep the original's permissions (e.g. an executable script).
+            let perms = std::fs::metadata(path)?.permissions();
+            std::fs::set_permissions(&tmp, perms)
+        });
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&tmp);
+            for (t, _) in &staged {
+                let _ = std::fs::remove_file(t);
+            }
+            return Err(e).with_context(|| format!("Failed to write {}", path.display()));
         }
-        if callers.len() > MAX_CALLERS {
-            out.push_str(&format!(
-                "\n*... {} more callers not shown*\n",
-                callers.len() - MAX_CALLERS
-            ));
+        staged.push((tmp, path));
+    }
+    for (tmp, path) in &staged {
+        std::fs::rename(tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Whether `word` is reserved in the language of `path` (by extension), so it
+/// cannot be used as an identifier there.
+fn is_reserved_word(path: &Path, word: &str) -> bool {
+    const RUST: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do",
+        "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+    ];
+    const JS_TS: &[&str] = &[
+        "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete",
+        "do", "else", "enum", "export", "extends", "false", "finally", "for", "function", "if",
+        "import", "in", "instanceof", "new", "null", "return", "super", "switch", "this",
+        "throw", "true", "try", "typeof", "var", "void", "while", "with", "let", "static",
+        "yield", "await", "implements", "interface", "package", "private", "protected",
+        "public",
+    ];
+    const PYTHON: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise",
+        "return", "try", "while", "with", "yield",
+    ];
+    const GO: &[&str] = &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough",
+        "for", "func", "go", "goto", "if", "import", "interface", "map", "package", "range",
+        "return", "select", "struct", "switch", "type", "var",
+    ];
+    const JAVA: &[&str] = &[
+        "abstract", "assert", "boolean", "break", "byte", "case", "catch", "char", "class",
+        "const", "continue", "default", "do", "double", "else", "enum", "extends", "final",
+        "finally", "float", "for", "goto", "if", "implements", "import", "instanceof", "int",
+        "interface", "long", "native", "new", "package", "private", "protected", "public",
+        "return", "short", "static", "strictfp", "super", "switch", "synchronized", "this",
+        "throw", "throws", "transient", "try", "void", "volatile", "while", "true", "false",
+        "null",
+    ];
+    const C_FAMILY: &[&str] = &[
+        "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+        "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+        "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union",
+        "unsigned", "void", "volatile", "while", "class", "namespace", "new", "delete",
+        "private", "protected", "public", "template", "this", "throw", "try", "catch",
+        "virtual", "using", "true", "false", "bool",
+    ];
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let reserved: &[&str] = match ext.as_str() {
+        "rs" => RUST,
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => JS_TS,
+        "py" | "pyi" => PYTHON,
+        "go" => GO,
+        "java" => JAVA,
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "cs" => C_FAMILY,
+        _ => &[],
+    };
+    reserved.contains(&word)
+}
+
+/// Unified diff (3 lines of context) between two versions of `rel` that have the
+/// same number of lines, as produced by an in-line identifier rename. Lines pair up
+/// by position, so no LCS is needed; each changed run is its removals, then its
+/// additions, and [`unified_hunks`] lays out the hunks.
+fn unified_line_diff(rel: &str, before: &str, after: &str) -> String {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+    let n = a.len().min(b.len());
+    let mut ops: Vec<(char, &str)> = Vec::with_capacity(n);
+    let mut k = 0;
+    while k < n {
+        if a[k] == b[k] {
+            ops.push((' ', a[k]));
+            k += 1;
+            continue;
         }
+        let run_end = (k..n).find(|&r| a[r] == b[r]).unwrap_or(n);
+        ops.extend(a[k..run_end].iter().map(|l| ('-', *l)));
+        ops.extend(b[k..run_end].iter().map(|l| ('+', *l)));
+        k = run_end;
+    }
+    if ops.iter().all(|(op, _)| *op == ' ') {
+        return String::new();
     }
+    format!("--- a/{rel}\n+++ b/{rel}\n{}", unified_hunks(&ops))
+}
 
-    Ok(out)
+/// Whether a declaration's first line marks it as public API (`pub`, `export`,
+/// `public`). Restricted visibility such as `pub(crate)` does not count.
+fn is_exported_decl(line: &str) -> bool {
+    let t = line.trim_start();
+    ["pub ", "export ", "public "]
+        .iter()
+        .any(|kw| t.starts_with(kw))
 }
 
 /// Collect all call sites of `symbol_name` by walking the AST for call nodes
@@ -4203,10 +8546,339 @@ fn extract_trailing_call_identifier<'a>(target: Node, source: &'a [u8]) -> Optio
     Some(last)
 }
 
+// ---------------------------------------------------------------------------
+// Tool: symbol_dependencies — what to read to understand a symbol
+// ---------------------------------------------------------------------------
+
+/// Max dependencies returned by [`symbol_dependencies`].
+const DEPENDENCIES_MAX: usize = 200;
+/// Max capitalised type names taken from one declaration.
+const DEPENDENCY_TYPES_MAX: usize = 32;
+
+/// A definition that a symbol depends on, as found by [`symbol_dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolDependency {
+    pub name: String,
+    /// Raw declaration kind (`function`, `struct`, `class`, ...).
+    pub kind: String,
+    /// Defining file, relative to the workspace root.
+    pub file: String,
+    /// 1-based declaration line.
+    pub line: u32,
+    /// Hops from the queried symbol (1 = referenced directly).
+    pub depth: usize,
+}
+
+/// Dependency closure of `symbol_name` in `path`: the functions its body calls and
+/// the types it names, each resolved to a definition (same file first, then across
+/// `workspace_root`), expanded breadth-first for `depth` hops (minimum 1).
+///
+/// Names without a definition in the workspace (std, third-party, built-ins) are
+/// dropped. Each name is reported once, at the shallowest depth it appears.
+pub fn symbol_dependencies(
+    path: &Path,
+    symbol_name: &str,
+    depth: usize,
+    workspace_root: &Path,
+) -> Result<Vec<SymbolDependency>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if !extract_symbols_from_source(path, &text)
+        .iter()
+        .any(|s| s.name == symbol_name)
+    {
+        return Err(anyhow!(
+            "Symbol `{symbol_name}` not found in {}",
+            path.display()
+        ));
+    }
+
+    let mut seen: std::collections::HashSet<String> =
+        std::iter::once(symbol_name.to_string()).collect();
+    let mut out: Vec<SymbolDependency> = Vec::new();
+    let mut frontier: Vec<(PathBuf, String)> = vec![(path.to_path_buf(), symbol_name.to_string())];
+
+    for hop in 1..=depth.max(1) {
+        let mut wanted: Vec<(String, PathBuf)> = Vec::new();
+        for (file, name) in &frontier {
+            for r in symbol_references(file, name) {
+                if seen.insert(r.clone()) {
+                    wanted.push((r, file.clone()));
+                }
+            }
+        }
+        if wanted.is_empty() {
+            break;
+        }
+
+        let resolved = resolve_definitions(&wanted, workspace_root);
+        frontier.clear();
+        for (name, _) in &wanted {
+            let Some((file, sym)) = resolved.get(name) else {
+                continue;
+            };
+            out.push(SymbolDependency {
+                name: name.clone(),
+                kind: sym.kind.clone(),
+                file: normalize_path_for_output(file.strip_prefix(workspace_root).unwrap_or(file)),
+                line: sym.line + 1,
+                depth: hop,
+            });
+            if out.len() >= DEPENDENCIES_MAX {
+                return Ok(out);
+            }
+            frontier.push((file.clone(), name.clone()));
+        }
+    }
+    Ok(out)
+}
+
+/// Markdown rendering of [`symbol_dependencies`], grouped by depth.
+pub fn format_symbol_dependencies(symbol_name: &str, deps: &[SymbolDependency]) -> String {
+    let mut out = format!("## Dependencies: `{symbol_name}`\n");
+    if deps.is_empty() {
+        out.push_str("\n- *(no workspace definitions referenced)*\n");
+        return out;
+    }
+    let mut current = 0usize;
+    for d in deps {
+        if d.depth != current {
+            current = d.depth;
+            out.push_str(&format!("\n### Depth {current}\n"));
+        }
+        out.push_str(&format!(
+            "- `{}` {} — {}:L{}\n",
+            d.name, d.kind, d.file, d.line
+        ));
+    }
+    out
+}
+
+/// Names referenced by the first declaration of `symbol_name` in `file`: call
+/// targets from its AST (minus [`CALL_NOISE`] names the file does not declare), then capitalised type names from its
+/// text, in order of appearance.
+fn symbol_references(file: &Path, symbol_name: &str) -> Vec<String> {
+    let Ok(text) = std::fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    let syms = extract_symbols_from_source(file, &text);
+    let Some(sym) = syms
+        .iter()
+        .find(|s| s.name == symbol_name && s.kind != "impl")
+        .or_else(|| syms.iter().find(|s| s.name == symbol_name))
+    else {
+        return Vec::new();
+    };
+    let end = sym.end_byte.min(text.len());
+    let start = sym.start_byte.min(end);
+
+    let mut calls: Vec<(String, u32)> = Vec::new();
+    {
+        let cfg = language_config().read().unwrap();
+        if let Some(driver) = cfg.driver_for_path(file) {
+            if let Some(tree) = driver
+                .make_parser(file)
+                .ok()
+                .and_then(|mut p| parse_with_limits(&mut p, &text).ok())
+            {
+                if let Some(decl) = decl_node_at(tree.root_node(), text.as_bytes(), start, end) {
+                    extract_call_targets_from_body(decl, text.as_bytes(), &mut calls);
+                }
+            }
+        }
+    }
+
+    // A noise name the file declares itself (a local `parse`) is a real dependency.
+    let is_noise = |name: &str| CALL_NOISE.contains(&name) && !syms.iter().any(|s| s.name == name);
+    let mut out: Vec<String> = Vec::new();
+    for (callee, _) in calls {
+        if callee != symbol_name && !is_noise(&callee) && !out.contains(&callee) {
+            out.push(callee);
+        }
+    }
+    for ty in signature_type_names(&text[start..end], symbol_name, DEPENDENCY_TYPES_MAX) {
+        if !out.contains(&ty) {
+            out.push(ty);
+        }
+    }
+    out
+}
+
+/// Definition of each name in `wanted` (`(name, referencing file)`): the
+/// referencing file first, then the shared definition walk of `workspace_root` for
+/// whatever is still missing. `impl` blocks never count as definitions.
+fn resolve_definitions(
+    wanted: &[(String, PathBuf)],
+    workspace_root: &Path,
+) -> HashMap<String, (PathBuf, Symbol)> {
+    let mut resolved: HashMap<String, (PathBuf, Symbol)> = HashMap::new();
+    let mut local: HashMap<PathBuf, Vec<Symbol>> = HashMap::new();
+    for (name, file) in wanted {
+        let syms = local.entry(file.clone()).or_insert_with(|| {
+            std::fs::read_to_string(file)
+                .map(|t| extract_symbols_from_source(file, &t))
+                .unwrap_or_default()
+        });
+        if let Some(sym) = syms.iter().find(|s| &s.name == name && s.kind != "impl") {
+            resolved.insert(name.clone(), (file.clone(), sym.clone()));
+        }
+    }
+    let pending: Vec<&str> = wanted
+        .iter()
+        .map(|(n, _)| n.as_str())
+        .filter(|n| !resolved.contains_key(*n))
+        .collect();
+    resolved.extend(first_definitions(workspace_root, &pending, |s| s.kind != "impl"));
+    resolved
+}
+
+// ---------------------------------------------------------------------------
+// Tool: reference_matrix — symbol-level coupling between files
+// ---------------------------------------------------------------------------
+
+/// Files [`reference_matrix`] will analyze; past this it asks for a narrower dir.
+const REFERENCE_MATRIX_MAX_FILES: usize = 5_000;
+
+/// `from_file -> { to_file: reference count }`, as returned by [`reference_matrix`].
+pub type ReferenceMatrix =
+    std::collections::BTreeMap<String, std::collections::BTreeMap<String, usize>>;
+
+/// Symbol-level coupling under `target_dir`: for every file, how many identifiers in
+/// it refer to a symbol defined in each other file, as `from_file -> { to_file: count }`
+/// with paths relative to `target_dir`.
+///
+/// Unlike the import graph this also sees coupling through glob imports, re-exports
+/// and same-package references. A name is resolved only when exactly one file under
+/// `target_dir` declares it (`impl` blocks aside), so overloaded names (`new`, `run`)
+/// and external symbols add no noise. Declaration sites and references within the
+/// defining file are not counted; files that reference no other file are left out.
+pub fn reference_matrix(target_dir: &Path) -> Result<ReferenceMatrix> {
+    use rayon::prelude::*;
+
+    let abs_dir: PathBuf = if target_dir.is_absolute() {
+        target_dir.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("Failed to get cwd")?
+            .join(target_dir)
+    };
+    if !abs_dir.exists() {
+        return Err(anyhow!("target_dir does not exist: {}", abs_dir.display()));
+    }
+
+    let (files, _errors) = collect_walk_files(build_walker(
+        &abs_dir,
+        GitignoreMode::Respect,
+        Default::default(),
+    ));
+    let linguist = linguist_hints(&abs_dir, GitignoreMode::Respect);
+    let files: Vec<PathBuf> = {
+        let cfg = language_config().read().unwrap();
+        files
+            .into_iter()
+            .filter(|p| cfg.driver_for_path(p).is_some() && linguist.classify(p).is_none())
+            .collect()
+    };
+    if files.len() > REFERENCE_MATRIX_MAX_FILES {
+        return Err(anyhow!(
+            "{} source files under '{}' (limit {REFERENCE_MATRIX_MAX_FILES}); narrow target_dir.",
+            files.len(),
+            abs_dir.display()
+        ));
+    }
+
+    // Pass 1: who declares what. `None` marks a name declared in several files.
+    let sources: Vec<(PathBuf, String, Vec<Symbol>)> = files
+        .into_par_iter()
+        .filter_map(|path| {
+            let text = read_source_lossy(&path).ok()??;
+            let syms = extract_symbols_from_source(&path, &text);
+            Some((path, text, syms))
+        })
+        .collect();
+    let mut definer: HashMap<&str, Option<usize>> = HashMap::new();
+    for (i, (_, _, syms)) in sources.iter().enumerate() {
+        for sym in syms.iter().filter(|s| s.kind != "impl") {
+            definer
+                .entry(sym.name.as_str())
+                .and_modify(|d| {
+                    if *d != Some(i) {
+                        *d = None;
+                    }
+                })
+                .or_insert(Some(i));
+        }
+    }
+
+    // Pass 2: resolve every identifier in every file against that table.
+    let rel = |i: usize| {
+        let path = &sources[i].0;
+        normalize_path_for_output(path.strip_prefix(&abs_dir).unwrap_or(path))
+    };
+    let rows: Vec<(usize, HashMap<usize, usize>)> = (0..sources.len())
+        .into_par_iter()
+        .filter_map(|i| {
+            let (path, text, _) = &sources[i];
+            let mut parser = {
+                let cfg = language_config().read().unwrap();
+                cfg.driver_for_path(path)?.make_parser(path).ok()?
+            };
+            let tree = parse_with_limits(&mut parser, text).ok()?;
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            visit_identifiers(tree.root_node(), text.as_bytes(), &mut |node, name| {
+                if let Some(Some(j)) = definer.get(name) {
+                    if *j != i && !is_declaration_name(node) {
+                        *counts.entry(*j).or_insert(0) += 1;
+                    }
+                }
+            });
+            (!counts.is_empty()).then_some((i, counts))
+        })
+        .collect();
+
+    Ok(rows
+        .into_iter()
+        .map(|(i, counts)| {
+            let targets = counts.into_iter().map(|(j, n)| (rel(j), n)).collect();
+            (rel(i), targets)
+        })
+        .collect())
+}
+
 // ---------------------------------------------------------------------------
 // Tool: run_diagnostics — The Compiler Oracle
 // ---------------------------------------------------------------------------
 
+/// Output format for [`run_diagnostics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagnosticsFormat {
+    /// Human/LLM-oriented report with capped error + warning sections (default).
+    #[default]
+    Markdown,
+    /// Uncapped JSON array of [`Diagnostic`] for CI and editor integrations.
+    Json,
+}
+
+/// One compiler diagnostic, as emitted by `run_diagnostics` with `format: "json"`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// `"error"` or `"warning"`.
+    pub level: String,
+    /// Compiler code, e.g. `E0308` (rustc) or `TS2322` (tsc).
+    pub code: Option<String>,
+    pub message: String,
+    /// Path as reported by the compiler (relative to the project root).
+    pub file: Option<String>,
+    /// 1-based line.
+    pub line: Option<u64>,
+    /// 1-based column, in the compiler's own unit: characters for rustc, UTF-16
+    /// code units for tsc. Not comparable with tree-sitter byte columns on lines
+    /// containing multibyte text.
+    pub column: Option<u64>,
+    /// Source lines around `line`, with the offending line marked `>>>`.
+    pub context: Option<String>,
+}
+
 /// Run the project's native diagnostics tool and return a structured report
 /// of errors and warnings, each pinned to its source location with inline
 /// code context.
@@ -4215,13 +8887,23 @@ fn extract_trailing_call_identifier<'a>(target: Node, source: &'a [u8]) -> Optio
 /// - `Cargo.toml` present → `cargo check --message-format=json --quiet`
 /// - `package.json` present → `npx tsc --noEmit --pretty false`
 ///
-/// Errors are capped at 20; warnings at 10. Each entry includes a 1-line
-/// code context window extracted from the source file.
+/// In Markdown mode errors are capped at 20 and warnings at 10; each entry
+/// includes a 1-line code context window extracted from the source file.
+/// JSON mode returns every diagnostic as a [`Diagnostic`] array.
+///
+/// The compiler is killed once it has run for `timeout_secs` (`0` = no limit),
+/// and the call fails with a "diagnostics timed out" error instead of hanging.
 ///
 /// # Arguments
-/// * `repo_root` — root directory of the project
-pub fn run_diagnostics(repo_root: &Path) -> Result<String> {
-    use std::process::{Command, Stdio};
+/// * `repo_root`    — root directory of the project
+/// * `format`       — Markdown (default) or JSON
+/// * `timeout_secs` — `diagnostics.timeout_secs` from the config
+pub fn run_diagnostics(
+    repo_root: &Path,
+    format: DiagnosticsFormat,
+    timeout_secs: u64,
+) -> Result<String> {
+    use std::process::Command;
 
     let abs_root: PathBuf = if repo_root.is_absolute() {
         repo_root.to_path_buf()
@@ -4235,48 +8917,136 @@ pub fn run_diagnostics(repo_root: &Path) -> Result<String> {
     let has_package_json = abs_root.join("package.json").exists();
 
     if !has_cargo && !has_package_json {
-        return Ok(format!(
+        let msg = format!(
             "No Cargo.toml or package.json found in {}.\n\
              `run_diagnostics` supports Rust (`cargo check`) and \
              TypeScript (`tsc --noEmit`) projects.",
             abs_root.display()
-        ));
+        );
+        return match format {
+            DiagnosticsFormat::Markdown => Ok(msg),
+            DiagnosticsFormat::Json => Err(anyhow!(msg)),
+        };
     }
 
     if has_cargo {
-        let output = Command::new("cargo")
-            .args(["check", "--message-format=json", "--quiet"])
-            .current_dir(&abs_root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .context("Failed to run `cargo check` — is Rust installed?")?;
+        let mut cmd = Command::new("cargo");
+        cmd.args(["check", "--message-format=json", "--quiet"])
+            .current_dir(&abs_root);
+        let output = diagnostics_output(
+            cmd,
+            "cargo check",
+            "install Rust (https://rustup.rs) to check Rust projects",
+            timeout_secs,
+        )?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        diagnostics_parse_cargo(&stdout, &abs_root)
+        let diags = diagnostics_parse_cargo(&stdout, &abs_root);
+        match format {
+            DiagnosticsFormat::Markdown => Ok(diagnostics_cargo_markdown(&diags)),
+            DiagnosticsFormat::Json => Ok(serde_json::to_string_pretty(&diags)?),
+        }
     } else {
-        let output = Command::new("npx")
-            .args(["tsc", "--noEmit", "--pretty", "false"])
-            .current_dir(&abs_root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .context("Failed to run `npx tsc` — is TypeScript installed?")?;
+        let mut cmd = Command::new("npx");
+        cmd.args(["tsc", "--noEmit", "--pretty", "false"])
+            .current_dir(&abs_root);
+        let output = diagnostics_output(
+            cmd,
+            "npx tsc",
+            "install Node.js and add `typescript` to the project to check TypeScript projects",
+            timeout_secs,
+        )?;
 
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        diagnostics_parse_tsc(&stdout, &stderr)
+        match format {
+            DiagnosticsFormat::Markdown => diagnostics_parse_tsc(&stdout, &stderr),
+            DiagnosticsFormat::Json => {
+                let diags = diagnostics_collect_tsc(&stdout, &stderr, &abs_root);
+                Ok(serde_json::to_string_pretty(&diags)?)
+            }
+        }
     }
 }
 
-fn diagnostics_parse_cargo(cargo_output: &str, repo_root: &Path) -> Result<String> {
-    use serde_json::Value;
+/// Run a diagnostics command to completion and collect its output, killing it once it
+/// has run for `timeout_secs` (`0` = no limit). `label` names the command in errors;
+/// `install_hint` is shown when the program is not on `PATH`.
+fn diagnostics_output(
+    mut cmd: std::process::Command,
+    label: &str,
+    install_hint: &str,
+    timeout_secs: u64,
+) -> Result<std::process::Output> {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let program = cmd.get_program().to_string_lossy().to_string();
+    // stdin is closed so `npx` can't stall on an "Ok to proceed?" install prompt.
+    let mut child = match cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(anyhow!(
+                "`{program}` is not installed or not on PATH — {install_hint}."
+            ));
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to run `{label}`")),
+    };
 
-    const MAX_ERRORS: usize = 20;
-    const MAX_WARNINGS: usize = 10;
+    // Drain both pipes off-thread: a compiler blocked on a full pipe never exits.
+    fn drain(mut pipe: impl Read + Send + 'static) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    }
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
 
-    let mut errors: Vec<String> = Vec::new();
-    let mut warnings: Vec<String> = Vec::new();
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .with_context(|| format!("Failed to wait for `{label}`"))?
+        {
+            break status;
+        }
+        if timeout_secs > 0 && started.elapsed() >= Duration::from_secs(timeout_secs) {
+            let _ = child.kill();
+            let _ = child.wait();
+            // The drain threads are left behind: grandchildren (e.g. rustc) may still
+            // hold the pipes open.
+            return Err(anyhow!(
+                "diagnostics timed out: `{label}` was still running after {timeout_secs}s and \
+                 was killed (network fetch or a build lock held by another process?). \
+                 Raise `diagnostics.timeout_secs` in .cortexast.json, or set it to 0 for no limit."
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    let collect = |h: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        h.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+    Ok(std::process::Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Extract error/warning `compiler-message`s from `cargo check --message-format=json`.
+fn diagnostics_parse_cargo(cargo_output: &str, repo_root: &Path) -> Vec<Diagnostic> {
+    use serde_json::Value;
+
+    let mut diags: Vec<Diagnostic> = Vec::new();
 
     for line in cargo_output.lines() {
         let line = line.trim();
@@ -4300,48 +9070,84 @@ fn diagnostics_parse_cargo(cargo_output: &str, repo_root: &Path) -> Result<Strin
             continue;
         }
 
-        let message_text = msg
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("(no message)");
-        let code_str = msg
-            .get("code")
-            .and_then(|c| c.get("code"))
-            .and_then(|c| c.as_str())
-            .map(|c| format!("[{c}] "))
-            .unwrap_or_default();
+        let mut diag = Diagnostic {
+            level: level.to_string(),
+            code: msg
+                .get("code")
+                .and_then(|c| c.get("code"))
+                .and_then(|c| c.as_str())
+                .map(str::to_string),
+            message: msg
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("(no message)")
+                .to_string(),
+            file: None,
+            line: None,
+            column: None,
+            context: None,
+        };
 
-        let spans = msg.get("spans").and_then(|s| s.as_array());
-        let mut location = String::new();
-        let mut context_block = String::new();
-
-        if let Some(spans_arr) = spans {
-            if let Some(span) = spans_arr.first() {
-                let file = span
-                    .get("file_name")
-                    .and_then(|f| f.as_str())
-                    .unwrap_or("?");
-                let line_start = span.get("line_start").and_then(|l| l.as_u64()).unwrap_or(0);
-                let col = span
-                    .get("column_start")
-                    .and_then(|c| c.as_u64())
-                    .unwrap_or(0);
-                location = format!("{file}:{line_start}:{col}");
-
-                if let Ok(contents) = std::fs::read_to_string(repo_root.join(file)) {
-                    let text_lines: Vec<&str> = contents.lines().collect();
-                    let target_0 = (line_start as usize).saturating_sub(1);
-                    context_block = extract_context_lines(&text_lines, target_0, 1);
-                }
-            }
+        let span = msg
+            .get("spans")
+            .and_then(|s| s.as_array())
+            .and_then(|s| s.first());
+        if let Some(span) = span {
+            let file = span
+                .get("file_name")
+                .and_then(|f| f.as_str())
+                .unwrap_or("?");
+            let line_start = span.get("line_start").and_then(|l| l.as_u64()).unwrap_or(0);
+            let col = span
+                .get("column_start")
+                .and_then(|c| c.as_u64())
+                .unwrap_or(0);
+            diag.context = diagnostic_context(repo_root, file, line_start);
+            diag.file = Some(file.to_string());
+            diag.line = Some(line_start);
+            diag.column = Some(col);
         }
 
-        let mut entry = format!("**{level}**: {code_str}{message_text}\n  → {location}");
-        if !context_block.is_empty() {
-            entry.push_str(&format!("\n```\n{context_block}\n```"));
+        diags.push(diag);
+    }
+
+    diags
+}
+
+/// 1-line context window around `line_1` in `repo_root/file`, if readable.
+fn diagnostic_context(repo_root: &Path, file: &str, line_1: u64) -> Option<String> {
+    let contents = std::fs::read_to_string(repo_root.join(file)).ok()?;
+    let text_lines: Vec<&str> = contents.lines().collect();
+    let target_0 = (line_1 as usize).saturating_sub(1);
+    if target_0 >= text_lines.len() {
+        return None;
+    }
+    Some(extract_context_lines(&text_lines, target_0, 1))
+}
+
+fn diagnostics_cargo_markdown(diags: &[Diagnostic]) -> String {
+    const MAX_ERRORS: usize = 20;
+    const MAX_WARNINGS: usize = 10;
+
+    let mut errors: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    for d in diags {
+        let code_str = d
+            .code
+            .as_deref()
+            .map(|c| format!("[{c}] "))
+            .unwrap_or_default();
+        let location = match (&d.file, d.line, d.column) {
+            (Some(file), Some(line), Some(col)) => format!("{file}:{line}:{col}"),
+            _ => String::new(),
+        };
+        let mut entry = format!("**{}**: {code_str}{}\n  → {location}", d.level, d.message);
+        if let Some(ctx) = d.context.as_deref().filter(|c| !c.is_empty()) {
+            entry.push_str(&format!("\n```\n{ctx}\n```"));
         }
 
-        if level == "error" {
+        if d.level == "error" {
             errors.push(entry);
         } else {
             warnings.push(entry);
@@ -4349,7 +9155,7 @@ fn diagnostics_parse_cargo(cargo_output: &str, repo_root: &Path) -> Result<Strin
     }
 
     if errors.is_empty() && warnings.is_empty() {
-        return Ok("Project compiles cleanly — no errors or warnings.\n".to_string());
+        return "Project compiles cleanly — no errors or warnings.\n".to_string();
     }
 
     let mut out = String::new();
@@ -4386,7 +9192,7 @@ fn diagnostics_parse_cargo(cargo_output: &str, repo_root: &Path) -> Result<Strin
         }
     }
 
-    Ok(out)
+    out
 }
 
 fn diagnostics_parse_tsc(stdout: &str, stderr: &str) -> Result<String> {
@@ -4416,9 +9222,81 @@ fn diagnostics_parse_tsc(stdout: &str, stderr: &str) -> Result<String> {
 
     if count == 0 {
         // Fallback: include raw output (truncated)
-        let snippet = &combined[..combined.len().min(3_000)];
-        out.push_str(snippet);
+        let mut cut = combined.len().min(3_000);
+        while !combined.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        out.push_str(&combined[..cut]);
     }
 
     Ok(out)
 }
+
+/// Parse `tsc --pretty false` output (`file(line,col): error TS1234: message`).
+/// If tsc printed something but no line matched (e.g. npx failed), the raw output
+/// is returned as a single location-less error so JSON consumers still see a failure.
+fn diagnostics_collect_tsc(stdout: &str, stderr: &str, repo_root: &Path) -> Vec<Diagnostic> {
+    let combined = if stdout.trim().is_empty() {
+        stderr
+    } else {
+        stdout
+    };
+    if combined.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let mut diags: Vec<Diagnostic> = combined
+        .lines()
+        .filter_map(|line| parse_tsc_line(line.trim()))
+        .map(|mut d| {
+            if let (Some(file), Some(line)) = (d.file.as_deref(), d.line) {
+                d.context = diagnostic_context(repo_root, file, line);
+            }
+            d
+        })
+        .collect();
+
+    if diags.is_empty() {
+        let mut cut = combined.len().min(3_000);
+        while !combined.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diags.push(Diagnostic {
+            level: "error".to_string(),
+            code: None,
+            message: combined[..cut].trim().to_string(),
+            file: None,
+            line: None,
+            column: None,
+            context: None,
+        });
+    }
+    diags
+}
+
+fn parse_tsc_line(t: &str) -> Option<Diagnostic> {
+    let (head, level, rest) = [": error ", ": warning "].iter().find_map(|sep| {
+        t.split_once(sep)
+            .map(|(head, rest)| (head, sep.trim_matches([':', ' ']), rest))
+    })?;
+    let (code, message) = rest.split_once(": ")?;
+    if !code.starts_with("TS") {
+        return None;
+    }
+    let (file, line, column) = match head.strip_suffix(')').and_then(|h| h.rsplit_once('(')) {
+        Some((file, pos)) => {
+            let (l, c) = pos.split_once(',')?;
+            (file, l.trim().parse().ok(), c.trim().parse().ok())
+        }
+        None => (head, None, None),
+    };
+    Some(Diagnostic {
+        level: level.to_string(),
+        code: Some(code.to_string()),
+        message: message.to_string(),
+        file: Some(file.to_string()),
+        line,
+        column,
+        context: None,
+    })
+}
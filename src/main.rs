@@ -1,26 +1,38 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use cortexast::config::load_config;
+use cortexast::config::{load_config, Config, IncludeTests};
 use cortexast::inspector::analyze_file;
-use cortexast::inspector::render_skeleton;
+use cortexast::inspector::apply_rename;
+use cortexast::inspector::ctags;
+use cortexast::inspector::exported_language_config;
+use cortexast::inspector::repo_summary;
+use cortexast::inspector::{apply_language_overrides, apply_parse_limits};
+use cortexast::inspector::{render_skeleton, skeleton_dir};
 use cortexast::mapper::{
     build_map_from_manifests, build_module_graph, build_repo_map, build_repo_map_scoped,
+    centrality, merge_maps,
 };
-use cortexast::scanner::{scan_workspace, ScanOptions};
+use cortexast::memory::{backfill_vectors, resolve_journal_path, MemoryStore, MEMORY_EMBED_MODEL};
+use cortexast::scanner::{scan_workspace, ScanOptions, TestFilter};
 use cortexast::server::run_stdio_server;
-use cortexast::slicer::{slice_paths_to_xml, slice_to_xml};
-use cortexast::vector_store::CodebaseIndex;
+use cortexast::slicer::{slice_paths_to_xml, slice_plan, slice_to_writer, slice_to_xml};
+use cortexast::vector_store::{CodebaseIndex, RefreshScope};
 use cortexast::workspace::{discover_workspace_members, WorkspaceDiscoveryOptions};
 use indicatif::{ProgressBar, ProgressStyle};
+use model2vec_rs::model::StaticModel;
 use serde_json::json;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(Debug, Parser)]
 #[command(name = "cortexast")]
 #[command(version)]
 #[command(about = "High-performance LLM context optimizer (Pure Rust MCP server)")]
 struct Cli {
-    /// Output a repo map JSON to stdout (nodes + edges)
+    /// Output a repo map JSON to stdout (nodes + edges). With two or more paths, maps
+    /// each one as its own repo and merges them, node ids prefixed with the repo name.
     #[arg(long)]
     map: bool,
 
@@ -28,21 +40,43 @@ struct Cli {
     #[arg(long, value_name = "ROOT", num_args = 0..=1, default_missing_value = ".")]
     graph_modules: Option<PathBuf>,
 
+    /// Output the most depended-upon files as JSON (in-degree, out-degree, PageRank).
+    /// Optional ROOT scopes scanning.
+    #[arg(long, value_name = "ROOT", num_args = 0..=1, default_missing_value = ".")]
+    centrality: Option<PathBuf>,
+
+    /// Number of files reported by --centrality.
+    #[arg(long, value_name = "N", default_value_t = 20, requires = "centrality")]
+    centrality_top: usize,
+
+    /// Output a first-contact repo profile as JSON: languages (files and lines per
+    /// driver), symbols by kind, top-level directories and workspace layout.
+    /// Optional ROOT scopes it.
+    #[arg(long, value_name = "ROOT", num_args = 0..=1, default_missing_value = ".")]
+    summary: Option<PathBuf>,
+
     /// Build a module graph strictly from the directories containing these manifest files.
     /// Example: --manifests apps/a/package.json libs/b/Cargo.toml
     #[arg(long, num_args = 1.., value_name = "MANIFEST_PATHS")]
     manifests: Option<Vec<PathBuf>>,
 
-    /// Optional subdirectory path to scope mapping (only valid with --map)
+    /// Optional subdirectory path to scope mapping, or several repo roots to merge
+    /// (only valid with --map)
     #[arg(value_name = "SUBDIR_PATH", requires = "map")]
-    map_target: Option<PathBuf>,
+    map_targets: Vec<PathBuf>,
+
+    /// Write a ctags-format tags file (default `tags` in the current dir) for every
+    /// declaration under --target (default: the whole repo), for Vim/Emacs jump-to-definition.
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "tags")]
+    tags: Option<PathBuf>,
 
     /// Inspect a single file and output extracted symbols as JSON
     #[arg(long, value_name = "FILE_PATH")]
     inspect: Option<PathBuf>,
 
-    /// Output a pruned "skeleton" view of a single file (function bodies replaced with /* ... */)
-    #[arg(long, value_name = "FILE_PATH")]
+    /// Output a pruned "skeleton" view of a file (function bodies replaced with /* ... */).
+    /// A directory gets every supported file under it, within --budget-tokens.
+    #[arg(long, value_name = "PATH")]
     skeleton: Option<PathBuf>,
 
     /// Target module/directory path (relative to repo root)
@@ -70,6 +104,23 @@ struct Cli {
     #[arg(long)]
     xml: bool,
 
+    /// Print `{ xml, meta, included, excluded }` as one JSON object on stdout and keep
+    /// stderr quiet (no progress spinners or confirmation line). Still writes the
+    /// usual {output_dir}/active_context.* files.
+    #[arg(long, conflicts_with = "xml")]
+    json: bool,
+
+    /// Time each phase of the run (scan, model load, refresh, search, slice) and print
+    /// the breakdown to stderr at the end; the meta JSON gets it as `profileMs`.
+    /// Also enabled by CORTEXAST_PROFILE=1.
+    #[arg(long)]
+    profile: bool,
+
+    /// Dry run: print the per-file slice plan for --target as JSON (mode, fate and
+    /// estimated tokens of each file) without emitting any content.
+    #[arg(long, conflicts_with_all = ["query", "xml", "json"])]
+    plan: bool,
+
     /// Disable skeleton mode (emit full file contents into XML)
     #[arg(long)]
     full: bool,
@@ -83,10 +134,19 @@ struct Cli {
     #[arg(long)]
     list_members: bool,
 
+    /// List registered language drivers (extensions + capabilities) as JSON and exit.
+    #[arg(long)]
+    languages: bool,
+
     /// Token budget override
     #[arg(long, default_value_t = 32_000)]
     budget_tokens: usize,
 
+    /// Override `output_dir` for this run (context XML + vector DB).
+    /// Also accepted via the CORTEXAST_OUT_DIR environment variable.
+    #[arg(long, value_name = "PATH")]
+    out_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     cmd: Option<Command>,
 }
@@ -102,6 +162,173 @@ enum Command {
         #[arg(long, value_name = "PATH")]
         root: Option<PathBuf>,
     },
+    /// Build or update the vector index without running a query.
+    Index {
+        /// Only embed recently changed files: a duration (`90s`, `30m`, `1h`, `2d`, `1w`)
+        /// for files modified within that window, or a git ref (`HEAD~1`, `main`) for
+        /// files changed against it. Omit to refresh the whole target.
+        #[arg(long, value_name = "DURATION|REF")]
+        since: Option<String>,
+    },
+    /// Rename an identifier (AST-accurate: comments and strings are left alone) and
+    /// print a unified diff. A dry run unless `--apply` is given; the only command
+    /// that edits source files.
+    Rename {
+        /// Current identifier.
+        old: String,
+        /// New identifier.
+        new: String,
+        /// Directory to rename within (every same-named identifier under it is renamed).
+        #[arg(long, value_name = "PATH", default_value = ".")]
+        dir: PathBuf,
+        /// Write the renamed files. Without it only the diff is printed.
+        #[arg(long)]
+        apply: bool,
+        /// Rename even when the symbol is ubiquitous (see blast_radius).
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the memory journal used for this directory (entries, how many are vectorized).
+    Memory {
+        /// Embed entries that have no vector yet (e.g. written while CortexSync was
+        /// offline) and write the vectors back into the journal.
+        #[arg(long)]
+        reindex: bool,
+    },
+}
+
+/// Parse a `--since` window like `90s`, `30m`, `1h`, `2d` or `1w`.
+fn parse_since_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num.parse().ok()?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    Some(Duration::from_secs(n.checked_mul(secs)?))
+}
+
+/// Repo-relative paths changed against `git_ref` (working tree included), plus
+/// untracked files, so uncommitted edits are indexed too.
+fn git_changed_paths(repo_root: &Path, git_ref: &str) -> Result<HashSet<String>> {
+    let git = |args: &[&str]| -> Result<String> {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_root)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let changed = git(&["diff", "--name-only", "--relative", git_ref, "--"])?;
+    let untracked = git(&["ls-files", "--others", "--exclude-standard"])?;
+    Ok(changed
+        .lines()
+        .chain(untracked.lines())
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Scan options shared by the `--query` refresh and the `index` subcommand.
+fn index_scan_options(repo_root: &Path, target: &Path, cfg: &Config) -> ScanOptions {
+    let mut exclude_dir_names = vec![
+        ".git".into(),
+        "node_modules".into(),
+        "dist".into(),
+        "target".into(),
+    ];
+    exclude_dir_names.extend(cfg.scan.exclude_dir_names.iter().cloned());
+    ScanOptions {
+        repo_root: repo_root.to_path_buf(),
+        target: target.to_path_buf(),
+        max_file_bytes: cfg.token_estimator.max_file_bytes,
+        exclude_dir_names,
+        exclude_dir_paths: vec![cfg.output_dir.clone()],
+        include_linguist_generated: cfg.scan.include_linguist_generated,
+        // The index covers tests too; `include_tests` only shapes slices and maps.
+        test_patterns: cfg.scan.test_patterns.clone(),
+        include_tests: IncludeTests::All,
+    }
+}
+
+/// Environment variable that turns on `--profile` when set to a truthy value (`1`,
+/// `true`, `yes`).
+const PROFILE_ENV: &str = "CORTEXAST_PROFILE";
+
+/// Wall-clock durations of the phases of one run, recorded with [`PhaseTimer::lap`].
+struct PhaseTimer {
+    enabled: bool,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl PhaseTimer {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Close `phase`: it took the time since the previous lap (or since creation).
+    fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        if self.enabled {
+            self.phases.push((phase, now - self.last));
+        }
+        self.last = now;
+    }
+
+    /// `{ phase: milliseconds, ..., "total": ... }`, or `None` when profiling is off.
+    fn to_json(&self) -> Option<serde_json::Value> {
+        if !self.enabled {
+            return None;
+        }
+        let mut map = serde_json::Map::new();
+        for (phase, took) in &self.phases {
+            map.insert((*phase).to_string(), json!(took.as_millis() as u64));
+        }
+        let total: Duration = self.phases.iter().map(|(_, took)| *took).sum();
+        map.insert("total".to_string(), json!(total.as_millis() as u64));
+        Some(serde_json::Value::Object(map))
+    }
+
+    /// Print the breakdown to stderr, one phase per line.
+    fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        let total: Duration = self.phases.iter().map(|(_, took)| *took).sum();
+        eprintln!("profile:");
+        for (phase, took) in self.phases.iter().copied().chain([("total", total)]) {
+            eprintln!("  {phase:<12} {:>8.1} ms", took.as_secs_f64() * 1_000.0);
+        }
+    }
+}
+
+/// Progress spinner for the slice run; hidden under `--json` so stderr stays clean.
+fn run_spinner(quiet: bool) -> ProgressBar {
+    if quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    }
 }
 
 fn auto_query_limit(budget_tokens: usize, entry_count: usize, configured_default: usize) -> usize {
@@ -115,14 +342,124 @@ fn auto_query_limit(budget_tokens: usize, entry_count: usize, configured_default
     out.max(1)
 }
 
+/// Write a small meta file for UIs next to `active_context.xml`.
+/// (Keeps format similar to legacy implementations.)
+fn write_slice_meta(
+    out_dir: &Path,
+    repo_root: &Path,
+    target_label: &str,
+    budget_tokens: usize,
+    xml_len: usize,
+    query_cache_hit: Option<bool>,
+    timer: &PhaseTimer,
+) -> Result<serde_json::Value> {
+    let mut meta_json = json!({
+        "repoRoot": repo_root.to_string_lossy(),
+        "target": target_label,
+        "budgetTokens": budget_tokens,
+        "totalTokens": (xml_len as f64 / 4.0).ceil() as u64,
+        "totalChars": xml_len
+    });
+    if let Some(hit) = query_cache_hit {
+        meta_json["queryCacheHit"] = json!(hit);
+    }
+    if let Some(profile) = timer.to_json() {
+        meta_json["profileMs"] = profile;
+    }
+    let _ = std::fs::write(
+        out_dir.join("active_context.meta.json"),
+        serde_json::to_vec_pretty(&meta_json)?,
+    );
+    Ok(meta_json)
+}
+
+/// Copies every write to both sinks: the slice file and, with `--xml`, stdout.
+struct Tee<A, B>(A, B);
+
+impl<A: Write, B: Write> Write for Tee<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write_all(buf)?;
+        self.1.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()?;
+        self.1.flush()
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let mut timer = PhaseTimer::new(
+        cli.profile
+            || std::env::var(PROFILE_ENV).is_ok_and(|v| {
+                matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes")
+            }),
+    );
 
-    if let Some(Command::Mcp { root }) = cli.cmd {
-        return run_stdio_server(root);
+    if let Some(Command::Mcp { root }) = &cli.cmd {
+        return run_stdio_server(root.clone());
     }
 
     let repo_root = std::env::current_dir().context("Failed to get current dir")?;
+    let cfg = load_config(&repo_root);
+    cfg.performance.install_thread_pool();
+    apply_language_overrides(&cfg.language_overrides);
+    apply_parse_limits(&cfg.parse);
+
+    if let Some(Command::Memory { reindex }) = &cli.cmd {
+        let path = resolve_journal_path(Some(&repo_root));
+        if !path.exists() {
+            anyhow::bail!("No memory journal at {}", path.display());
+        }
+        if *reindex {
+            let spinner = ProgressBar::new_spinner();
+            spinner.enable_steady_tick(Duration::from_millis(80));
+            spinner.set_message("loading embedding model...");
+            let model = StaticModel::from_pretrained(MEMORY_EMBED_MODEL, None, None, None)?;
+            spinner.set_message("embedding entries without vectors...");
+            let filled = backfill_vectors(&path, |text| model.encode_single(text))?;
+            spinner.finish_with_message(format!("memory reindexed: {filled} entries embedded"));
+        }
+        let store = MemoryStore::load(&path)?;
+        let vectorized = store.vectors.iter().filter(|v| !v.is_empty()).count();
+        println!(
+            "{}: {} entries, {vectorized} vectorized",
+            path.display(),
+            store.entries().len()
+        );
+        return Ok(());
+    }
+
+    if let Some(Command::Rename {
+        old,
+        new,
+        dir,
+        apply,
+        force,
+    }) = &cli.cmd
+    {
+        let outcome = apply_rename(&repo_root.join(dir), old, new, !*apply, *force)?;
+        print!("{}", outcome.diff);
+        let total: usize = outcome.files.iter().map(|f| f.occurrences).sum();
+        let verb = if outcome.applied {
+            "renamed"
+        } else {
+            "dry run (pass --apply to write): would rename"
+        };
+        eprintln!(
+            "{verb} {total} occurrence(s) of `{old}` in {} file(s)",
+            outcome.files.len()
+        );
+        return Ok(());
+    }
+
+    if cli.languages {
+        let info = exported_language_config().read().unwrap().describe();
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
 
     if let Some(manifests) = cli.manifests.as_ref() {
         let graph = build_map_from_manifests(&repo_root, manifests)?;
@@ -136,6 +473,28 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(root) = cli.centrality.as_ref() {
+        let ranked = centrality(&repo_root, root, cli.centrality_top.max(1))?;
+        println!("{}", serde_json::to_string(&ranked)?);
+        return Ok(());
+    }
+
+    if let Some(root) = cli.summary.as_ref() {
+        let summary = repo_summary(&repo_root, root)?;
+        println!("{}", serde_json::to_string(&summary)?);
+        return Ok(());
+    }
+
+    if let Some(out_path) = cli.tags.as_ref() {
+        let target = cli.target.clone().unwrap_or_else(|| PathBuf::from("."));
+        let tags = ctags(&repo_root, &target)?;
+        let count = tags.lines().filter(|l| !l.starts_with("!_TAG_")).count();
+        std::fs::write(out_path, tags)
+            .with_context(|| format!("Failed to write {}", out_path.display()))?;
+        eprintln!("Wrote {count} tags to {}", out_path.display());
+        return Ok(());
+    }
+
     if let Some(p) = cli.inspect {
         let abs = if p.is_absolute() {
             p
@@ -159,22 +518,44 @@ fn main() -> Result<()> {
         } else {
             repo_root.join(&p)
         };
-        let skel = render_skeleton(&abs)?;
+        let skel = if abs.is_dir() {
+            skeleton_dir(&abs, cli.budget_tokens)?
+        } else {
+            render_skeleton(&abs)?
+        };
         print!("{}", skel);
         return Ok(());
     }
 
     if cli.map {
-        let map = if let Some(scope) = cli.map_target.as_ref() {
-            build_repo_map_scoped(&repo_root, scope)?
-        } else {
-            build_repo_map(&repo_root)?
+        let map = match cli.map_targets.as_slice() {
+            [] => build_repo_map(&repo_root)?,
+            [scope] => build_repo_map_scoped(&repo_root, scope)?,
+            roots => {
+                let mut maps = Vec::with_capacity(roots.len());
+                for root in roots {
+                    let abs = repo_root.join(root);
+                    let abs = abs
+                        .canonicalize()
+                        .with_context(|| format!("Repo path not found: {}", abs.display()))?;
+                    let name = abs
+                        .file_name()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("root")
+                        .to_string();
+                    maps.push((name, build_repo_map(&abs)?));
+                }
+                merge_maps(&maps)
+            }
         };
         println!("{}", serde_json::to_string(&map)?);
         return Ok(());
     }
 
     let mut cfg = load_config(&repo_root);
+    if let Some(dir) = cli.out_dir.clone() {
+        cfg.output_dir = dir;
+    }
     if cli.full {
         cfg.skeleton_mode = false;
     }
@@ -195,25 +576,113 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Hybrid search mode: build/update local vector index, retrieve relevant files, then slice only those.
-    let (xml, target_label) = if let Some(q) = cli.query.as_ref() {
+    // ── --plan: what a slice of --target would contain, without slicing ──
+    if cli.plan {
+        let target = cli.target.clone().context("--plan requires --target")?;
+        let plan = slice_plan(&repo_root, &target, cli.budget_tokens, &cfg, false)?;
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    // ── index: warm the vector index (optionally only recent changes) ──────
+    if let Some(Command::Index { since }) = &cli.cmd {
+        let scope =
+            match since.as_deref() {
+                None => RefreshScope::All,
+                Some(s) => match parse_since_duration(s) {
+                    Some(window) => RefreshScope::Since(
+                        SystemTime::now()
+                            .checked_sub(window)
+                            .unwrap_or(SystemTime::UNIX_EPOCH),
+                    ),
+                    None => RefreshScope::Paths(git_changed_paths(&repo_root, s).with_context(
+                        || format!("--since '{s}' is neither a duration nor a git ref"),
+                    )?),
+                },
+            };
+
         let index_target = cli.target.clone().unwrap_or_else(|| PathBuf::from("."));
-        let mut exclude_dir_names = vec![
-            ".git".into(),
-            "node_modules".into(),
-            "dist".into(),
-            "target".into(),
-            cfg.output_dir.to_string_lossy().to_string(),
-        ];
-        exclude_dir_names.extend(cfg.scan.exclude_dir_names.iter().cloned());
-        let opts = ScanOptions {
-            repo_root: repo_root.clone(),
-            target: index_target.clone(),
-            max_file_bytes: cfg.token_estimator.max_file_bytes,
-            exclude_dir_names,
+        let opts = index_scan_options(&repo_root, &index_target, &cfg);
+        let db_dir = repo_root.join(&cfg.output_dir).join("db");
+        let model_id = cli
+            .embed_model
+            .as_deref()
+            .unwrap_or(cfg.vector_search.model.as_str());
+        let chunk_lines = cli.chunk_lines.unwrap_or(cfg.vector_search.chunk_lines);
+
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(
+            ProgressStyle::with_template("{spinner} {msg}")
+                .unwrap()
+                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+        );
+        spinner.enable_steady_tick(Duration::from_millis(80));
+        spinner.set_message("loading embedding model...");
+        timer.lap("setup");
+        let mut index = CodebaseIndex::open(&repo_root, &db_dir, model_id, chunk_lines)?;
+        index.set_index_extensions(&cfg.vector_search.index_extensions);
+        timer.lap("model_load");
+        match index.interrupted_progress() {
+            Some(p) => spinner.set_message(format!(
+                "resuming interrupted index ({}/{} files committed)...",
+                p.done, p.total
+            )),
+            None => spinner.set_message("indexing..."),
+        }
+        let (added, updated, deleted) = index.refresh_scoped(&opts, &scope)?;
+        spinner.finish_with_message(format!("index updated: +{added} ~{updated} -{deleted}"));
+        timer.lap("index");
+        timer.report();
+        return Ok(());
+    }
+
+    // ── Plain target slice: stream to disk (and stdout with --xml) as it renders ──
+    // Only --json needs the whole document in memory.
+    if cli.query.is_none() && !cli.json {
+        let target = cli
+            .target
+            .clone()
+            .context("Missing --target (or provide --query)")?;
+        let out_dir = repo_root.join(&cfg.output_dir);
+        std::fs::create_dir_all(&out_dir)?;
+        let xml_path = out_dir.join("active_context.xml");
+        let file = std::io::BufWriter::new(
+            std::fs::File::create(&xml_path)
+                .with_context(|| format!("Failed to create {}", xml_path.display()))?,
+        );
+        let sink: Box<dyn Write> = if cli.xml {
+            Box::new(Tee(file, std::io::stdout().lock()))
+        } else {
+            Box::new(file)
         };
+        timer.lap("setup");
+        slice_to_writer(&repo_root, &target, cli.budget_tokens, &cfg, false, sink)?;
+        timer.lap("slice");
+
+        let xml_len = std::fs::metadata(&xml_path)?.len() as usize;
+        write_slice_meta(
+            &out_dir,
+            &repo_root,
+            &target.to_string_lossy(),
+            cli.budget_tokens,
+            xml_len,
+            None,
+            &timer,
+        )?;
+        if !cli.xml {
+            eprintln!("Wrote {} bytes to {}", xml_len, xml_path.display());
+        }
+        timer.report();
+        return Ok(());
+    }
 
-        let scan_spinner = ProgressBar::new_spinner();
+    // Hybrid search mode: build/update local vector index, retrieve relevant files, then slice only those.
+    let (xml, meta, target_label, query_cache_hit) = if let Some(q) = cli.query.as_ref() {
+        let index_target = cli.target.clone().unwrap_or_else(|| PathBuf::from("."));
+        let opts = index_scan_options(&repo_root, &index_target, &cfg);
+        timer.lap("setup");
+
+        let scan_spinner = run_spinner(cli.json);
         scan_spinner.set_style(
             ProgressStyle::with_template("{spinner} scanning files...")
                 .unwrap()
@@ -222,15 +691,16 @@ fn main() -> Result<()> {
         scan_spinner.enable_steady_tick(std::time::Duration::from_millis(80));
         let entries = scan_workspace(&opts)?;
         scan_spinner.finish_with_message(format!("scanned {} files", entries.len()));
+        timer.lap("scan");
 
-        let db_dir = cfg.output_dir.join("db");
+        let db_dir = repo_root.join(&cfg.output_dir).join("db");
         let model_id = cli
             .embed_model
             .as_deref()
             .unwrap_or(cfg.vector_search.model.as_str());
         let chunk_lines = cli.chunk_lines.unwrap_or(cfg.vector_search.chunk_lines);
 
-        let model_spinner = ProgressBar::new_spinner();
+        let model_spinner = run_spinner(cli.json);
         model_spinner.set_style(
             ProgressStyle::with_template("{spinner} loading embedding model...")
                 .unwrap()
@@ -238,12 +708,17 @@ fn main() -> Result<()> {
         );
         model_spinner.enable_steady_tick(std::time::Duration::from_millis(100));
         let mut index = CodebaseIndex::open(&repo_root, &db_dir, model_id, chunk_lines)?;
+        index.persist_query_cache(cfg.vector_search.persist_query_cache);
+        index.set_min_score(cfg.vector_search.min_score);
+        index.set_index_extensions(&cfg.vector_search.index_extensions);
         model_spinner.finish_with_message("model ready".to_string());
+        timer.lap("model_load");
 
         // ── JIT Incremental Refresh ──────────────────────────────────────
         // Before every search, sweep file mtimes and embed only dirty delta.
         // This guarantees the index is always current without a background watcher.
-        let refresh_spinner = ProgressBar::new_spinner();
+        // Skipped when `vector_search.auto_refresh = false` / CORTEXAST_NO_REFRESH=1.
+        let refresh_spinner = run_spinner(cli.json);
         refresh_spinner.set_style(
             ProgressStyle::with_template("{spinner} {msg}")
                 .unwrap()
@@ -251,18 +726,25 @@ fn main() -> Result<()> {
         );
         refresh_spinner.enable_steady_tick(std::time::Duration::from_millis(80));
         refresh_spinner.set_message("checking index freshness...");
-        match index.refresh(&opts) {
-            Ok((added, updated, deleted)) if added + updated + deleted > 0 => {
-                refresh_spinner
-                    .finish_with_message(format!("index updated: +{added} ~{updated} -{deleted}"));
-            }
-            Ok(_) => {
-                refresh_spinner.finish_with_message("index fresh (no changes)");
-            }
-            Err(e) => {
-                refresh_spinner.finish_with_message(format!("refresh warning: {e}"));
+        if !cfg.vector_search.auto_refresh {
+            refresh_spinner
+                .finish_with_message("refresh skipped (auto_refresh off): results may be stale");
+        } else {
+            match index.refresh(&opts) {
+                Ok((added, updated, deleted)) if added + updated + deleted > 0 => {
+                    refresh_spinner.finish_with_message(format!(
+                        "index updated: +{added} ~{updated} -{deleted}"
+                    ));
+                }
+                Ok(_) => {
+                    refresh_spinner.finish_with_message("index fresh (no changes)");
+                }
+                Err(e) => {
+                    refresh_spinner.finish_with_message(format!("refresh warning: {e}"));
+                }
             }
         }
+        timer.lap("refresh");
 
         // Run async search on a small runtime.
         let rt = tokio::runtime::Runtime::new()?;
@@ -275,22 +757,31 @@ fn main() -> Result<()> {
             )
         });
 
-        let rel_paths: Vec<String> =
-            rt.block_on(async move { (index.search(&q_owned, limit).await).unwrap_or_default() });
+        let (mut rel_paths, cache_hit): (Vec<String>, bool) = rt.block_on(async move {
+            let paths = index.search(&q_owned, limit).await.unwrap_or_default();
+            (paths, index.last_search_cached())
+        });
+        timer.lap("search");
+
+        let tests = TestFilter::new(&cfg.scan.test_patterns, cfg.scan.include_tests);
+        rel_paths.retain(|p| tests.keeps(p));
 
-        let (xml, _meta) = if rel_paths.is_empty() {
+        let (xml, meta) = if rel_paths.is_empty() {
             slice_to_xml(&repo_root, &index_target, cli.budget_tokens, &cfg, false)?
         } else {
             slice_paths_to_xml(&repo_root, &rel_paths, cli.budget_tokens, &cfg, false)?
         };
-        (xml, format!("query:{}", q))
+        timer.lap("slice");
+        (xml, meta, format!("query:{}", q), Some(cache_hit))
     } else {
         let target = cli
             .target
             .clone()
             .context("Missing --target (or provide --query)")?;
-        let (xml, _meta) = slice_to_xml(&repo_root, &target, cli.budget_tokens, &cfg, false)?;
-        (xml, target.to_string_lossy().to_string())
+        timer.lap("setup");
+        let (xml, meta) = slice_to_xml(&repo_root, &target, cli.budget_tokens, &cfg, false)?;
+        timer.lap("slice");
+        (xml, meta, target.to_string_lossy().to_string(), None)
     };
 
     // Ensure output dir exists and write file.
@@ -298,21 +789,25 @@ fn main() -> Result<()> {
     std::fs::create_dir_all(&out_dir)?;
     std::fs::write(out_dir.join("active_context.xml"), &xml)?;
 
-    // Write a small meta file for UIs.
-    // (Keeps format similar to legacy implementations.)
-    let meta_json = json!({
-        "repoRoot": repo_root.to_string_lossy(),
-        "target": target_label,
-        "budgetTokens": cli.budget_tokens,
-        "totalTokens": (xml.len() as f64 / 4.0).ceil() as u64,
-        "totalChars": xml.len()
-    });
-    let _ = std::fs::write(
-        out_dir.join("active_context.meta.json"),
-        serde_json::to_vec_pretty(&meta_json)?,
-    );
-
-    if cli.xml {
+    let meta_json = write_slice_meta(
+        &out_dir,
+        &repo_root,
+        &target_label,
+        cli.budget_tokens,
+        xml.len(),
+        query_cache_hit,
+        &timer,
+    )?;
+
+    if cli.json {
+        let out = json!({
+            "xml": xml,
+            "meta": meta_json,
+            "included": meta.included,
+            "excluded": meta.excluded,
+        });
+        println!("{}", serde_json::to_string(&out)?);
+    } else if cli.xml {
         print!("{}", xml);
     } else {
         // Default to printing JSON meta later; for now just confirm success.
@@ -322,6 +817,65 @@ fn main() -> Result<()> {
             out_dir.join("active_context.xml").display()
         );
     }
+    // `--json` promises a quiet stderr; the breakdown is in `meta.profileMs` there.
+    if !cli.json {
+        timer.report();
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since_durations_take_one_unit_suffix() {
+        let secs = |s: &str| parse_since_duration(s).map(|d| d.as_secs());
+        assert_eq!(secs("90s"), Some(90));
+        assert_eq!(secs("30m"), Some(1_800));
+        assert_eq!(secs(" 1h "), Some(3_600));
+        assert_eq!(secs("2d"), Some(172_800));
+        assert_eq!(secs("1w"), Some(604_800));
+        assert_eq!(secs("0h"), Some(0));
+    }
+
+    #[test]
+    fn refs_and_malformed_windows_are_not_durations() {
+        for s in ["HEAD~1", "main", "h", "10", "1.5h", "1H", "2 days", "-1h", "", "99999999999999999999w"] {
+            assert_eq!(parse_since_duration(s), None, "{s:?}");
+        }
+        assert_eq!(parse_since_duration(&format!("{}w", u64::MAX / 604_800 + 1)), None);
+    }
+
+    #[test]
+    fn git_changed_paths_include_edits_and_untracked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            let ok = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t", "-c", "commit.gpgsign=false"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status
+                .success();
+            assert!(ok, "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        std::fs::write(root.join("a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(root.join("b.rs"), "fn b() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        std::fs::write(root.join("a.rs"), "fn a() { 1; }\n").unwrap();
+        std::fs::write(root.join("new.rs"), "fn n() {}\n").unwrap();
+        let changed = git_changed_paths(root, "HEAD").unwrap();
+        let want: HashSet<String> = ["a.rs", "new.rs"].into_iter().map(String::from).collect();
+        assert_eq!(changed, want);
+
+        let err = git_changed_paths(root, "no-such-ref").unwrap_err().to_string();
+        assert!(err.contains("git diff"), "{err}");
+    }
+}
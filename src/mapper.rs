@@ -1,10 +1,12 @@
 use anyhow::Result;
 use ignore::WalkBuilder;
 use serde::Serialize;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 use crate::inspector::analyze_file;
+use crate::scanner::LinguistAttributes;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct MapNode {
@@ -22,6 +24,8 @@ pub struct MapEdge {
     pub id: String,
     pub source: String,
     pub target: String,
+    /// `true` for import edges whose target is outside the repo (`external:<name>`).
+    pub external: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -87,21 +91,26 @@ fn read_pubspec_name(pubspec_yaml: &Path) -> Option<String> {
 }
 
 fn read_go_module_name(go_mod: &Path) -> Option<String> {
+    let module_path = read_go_module_path(go_mod)?;
+    // Prefer a short label: last segment of module path.
+    let short = module_path
+        .split('/')
+        .rfind(|s| !s.is_empty())
+        .unwrap_or(&module_path);
+    Some(short.to_string())
+}
+
+/// Full module path from `go.mod`'s `module` line (`github.com/acme/shop`).
+fn read_go_module_path(go_mod: &Path) -> Option<String> {
     let text = std::fs::read_to_string(go_mod).ok()?;
     for line in text.lines() {
-        let l = line.trim();
-        if l.starts_with("module ") {
-            let module_path = l.trim_start_matches("module ").trim();
-            if module_path.is_empty() {
-                return None;
-            }
-            // Prefer a short label: last segment of module path.
-            let short = module_path
-                .split('/')
-                .filter(|s| !s.is_empty())
-                .next_back()
-                .unwrap_or(module_path);
-            return Some(short.to_string());
+        if let Some(rest) = line.trim().strip_prefix("module ") {
+            let module_path = rest
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .trim_matches(|c| c == '"' || c == '`');
+            return (!module_path.is_empty()).then(|| module_path.to_string());
         }
     }
     None
@@ -364,6 +373,7 @@ pub fn build_map_from_manifests(repo_root: &Path, manifests: &[PathBuf]) -> Resu
                 true
             })
             .build();
+        let linguist = LinguistAttributes::discover(d);
 
         for ent in walker {
             let Ok(ent) = ent else { continue };
@@ -374,7 +384,7 @@ pub fn build_map_from_manifests(repo_root: &Path, manifests: &[PathBuf]) -> Resu
             if !is_allowed_source_ext(p) {
                 continue;
             }
-            if path_has_forbidden_component(p) {
+            if path_has_forbidden_component(p) || linguist.classify(p).is_some() {
                 continue;
             }
             let sz = ent.metadata().map(|m| m.len()).unwrap_or(0);
@@ -500,8 +510,8 @@ pub fn build_map_from_manifests(repo_root: &Path, manifests: &[PathBuf]) -> Resu
                     continue;
                 }
 
-                // TS/JS: resolve relative import to a file, then map to a selected module by prefix.
-                let Some(dst_file_abs) = resolve_ts_import(repo_root, file_abs, &imp) else {
+                // TS/JS/Python: resolve the import to a file, then map to a selected module by prefix.
+                let Some(dst_file_abs) = resolve_import_path(repo_root, file_abs, &imp) else {
                     continue;
                 };
                 let dst_file_abs = dst_file_abs.canonicalize().unwrap_or(dst_file_abs);
@@ -563,6 +573,12 @@ fn is_module_marker_file(name: &str) -> bool {
             | "index.js"
             | "index.jsx"
             | "mod.rs"
+            | "__init__.py"
+            // JVM / Swift build files
+            | "pom.xml"
+            | "build.gradle"
+            | "build.gradle.kts"
+            | "Package.swift"
     )
         // Practical Rust crate roots (often no mod.rs at root)
         || matches!(name, "lib.rs" | "main.rs")
@@ -617,6 +633,429 @@ fn resolve_ts_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> Optio
     None
 }
 
+enum ImportTarget {
+    /// Resolved to a file inside the repo (canonical absolute path).
+    Internal(PathBuf),
+    /// Third-party / stdlib import, bucketed by its top-level package name.
+    External(String),
+    /// Looked in-repo (relative / `crate::`) but no file matched.
+    Unknown,
+}
+
+/// Resolve a raw import string (as returned by `find_imports`) to an in-repo file.
+fn resolve_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    let imp = imp.trim();
+    if imp.is_empty() {
+        return ImportTarget::Unknown;
+    }
+    let ext = from_file_abs
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    match ext {
+        "rs" => resolve_rust_import(repo_root, from_file_abs, imp),
+        "py" => resolve_py_import(repo_root, from_file_abs, imp),
+        "java" | "kt" => resolve_jvm_import(repo_root, from_file_abs, imp),
+        "swift" => resolve_swift_import(repo_root, from_file_abs, imp),
+        "go" => resolve_go_import(repo_root, from_file_abs, imp),
+        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" => {
+            if let Some(p) = resolve_ts_import(repo_root, from_file_abs, imp) {
+                return ImportTarget::Internal(p);
+            }
+            if imp.starts_with('.') {
+                return ImportTarget::Unknown;
+            }
+            // Package name: `@scope/pkg/sub` -> `@scope/pkg`, `pkg/sub` -> `pkg`.
+            let parts: Vec<&str> = imp.split('/').collect();
+            let name = if imp.starts_with('@') && parts.len() >= 2 {
+                format!("{}/{}", parts[0], parts[1])
+            } else {
+                parts[0].to_string()
+            };
+            ImportTarget::External(name)
+        }
+        _ => ImportTarget::Unknown,
+    }
+}
+
+/// In-repo file an import string resolves to, if any (external / unresolved -> `None`).
+pub(crate) fn resolve_import_path(
+    repo_root: &Path,
+    from_file_abs: &Path,
+    imp: &str,
+) -> Option<PathBuf> {
+    match resolve_import(repo_root, from_file_abs, imp) {
+        ImportTarget::Internal(p) => Some(p),
+        ImportTarget::External(_) | ImportTarget::Unknown => None,
+    }
+}
+
+fn first_existing_in_repo(repo_root: &Path, candidates: Vec<PathBuf>) -> Option<PathBuf> {
+    for cand in candidates {
+        if !cand.is_file() {
+            continue;
+        }
+        let cand_abs = cand.canonicalize().unwrap_or(cand);
+        if cand_abs.strip_prefix(repo_root).is_ok() {
+            return Some(cand_abs);
+        }
+    }
+    None
+}
+
+/// Directory that holds the child modules of a Rust source file
+/// (`src/lib.rs` -> `src/`, `src/foo.rs` -> `src/foo/`, `src/foo/mod.rs` -> `src/foo/`).
+fn rust_module_dir(file_abs: &Path) -> Option<PathBuf> {
+    let dir = file_abs.parent()?;
+    let stem = file_abs.file_stem()?.to_str()?;
+    if matches!(stem, "mod" | "lib" | "main") {
+        Some(dir.to_path_buf())
+    } else {
+        Some(dir.join(stem))
+    }
+}
+
+fn resolve_rust_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    // `crate::a::{b, c}` / `crate::a::b as c` / `super::*` -> path segments only.
+    let path = imp.split(" as ").next().unwrap_or(imp);
+    let path = path.split('{').next().unwrap_or(path);
+    let segs: Vec<&str> = path
+        .split("::")
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && *s != "*")
+        .collect();
+    let Some(first) = segs.first().copied() else {
+        return ImportTarget::Unknown;
+    };
+
+    let (base, rest): (PathBuf, &[&str]) = match first {
+        "crate" => {
+            // Crate root = `src/` next to the nearest Cargo.toml.
+            let mut dir = from_file_abs.parent();
+            let mut found: Option<PathBuf> = None;
+            while let Some(d) = dir {
+                if d.join("Cargo.toml").is_file() {
+                    found = Some(d.join("src"));
+                    break;
+                }
+                if d == repo_root {
+                    break;
+                }
+                dir = d.parent();
+            }
+            let Some(src) = found else {
+                return ImportTarget::Unknown;
+            };
+            (src, &segs[1..])
+        }
+        "self" | "super" => {
+            let Some(mut base) = rust_module_dir(from_file_abs) else {
+                return ImportTarget::Unknown;
+            };
+            let mut i = 0;
+            while i < segs.len() && matches!(segs[i], "self" | "super") {
+                if segs[i] == "super" {
+                    base = match base.parent() {
+                        Some(p) => p.to_path_buf(),
+                        None => return ImportTarget::Unknown,
+                    };
+                }
+                i += 1;
+            }
+            (base, &segs[i..])
+        }
+        other => return ImportTarget::External(other.to_string()),
+    };
+
+    // Longest prefix that maps to a module file wins (`a::b::Item` -> `a/b.rs` or `a.rs`).
+    for k in (1..=rest.len()).rev() {
+        let joined = rest[..k].join("/");
+        let found = first_existing_in_repo(
+            repo_root,
+            vec![
+                base.join(format!("{}.rs", joined)),
+                base.join(&joined).join("mod.rs"),
+            ],
+        );
+        if let Some(p) = found {
+            return ImportTarget::Internal(p);
+        }
+    }
+    ImportTarget::Unknown
+}
+
+/// `dots` leading dots climb packages from the importing file's directory (`.` = its
+/// own package). The longest module prefix that exists wins, so `.utils.Helper` lands
+/// on `utils.py` and `.name` falls back to the package `__init__.py`.
+fn resolve_py_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    let dots = imp.chars().take_while(|c| *c == '.').count();
+    let module = &imp[dots..];
+    let segs: Vec<&str> = module.split('.').filter(|s| !s.is_empty()).collect();
+
+    let mut bases: Vec<PathBuf> = Vec::new();
+    if dots > 0 {
+        let mut base = match from_file_abs.parent() {
+            Some(p) => p.to_path_buf(),
+            None => return ImportTarget::Unknown,
+        };
+        for _ in 1..dots {
+            base = match base.parent() {
+                Some(p) => p.to_path_buf(),
+                None => return ImportTarget::Unknown,
+            };
+        }
+        bases.push(base);
+    } else {
+        bases.push(repo_root.to_path_buf());
+        bases.push(repo_root.join("src"));
+        if let Some(dir) = from_file_abs.parent() {
+            bases.push(dir.to_path_buf());
+        }
+    }
+
+    // A relative import may shrink down to the package itself; an absolute one keeps
+    // at least its top-level name.
+    let min_len = if dots > 0 { 0 } else { 1 };
+    for k in (min_len..=segs.len()).rev() {
+        let rel = segs[..k].join("/");
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        for base in &bases {
+            if rel.is_empty() {
+                candidates.push(base.join("__init__.py"));
+            } else {
+                candidates.push(base.join(format!("{}.py", rel)));
+                candidates.push(base.join(&rel).join("__init__.py"));
+            }
+        }
+        if let Some(p) = first_existing_in_repo(repo_root, candidates) {
+            return ImportTarget::Internal(p);
+        }
+    }
+    if dots > 0 {
+        return ImportTarget::Unknown;
+    }
+    ImportTarget::External(module.split('.').next().unwrap_or(module).to_string())
+}
+
+/// Java / Kotlin: `com.acme.util.Strings` -> `<source root>/com/acme/util/Strings.{java,kt}`.
+/// Source roots are the importing file's own (its directory minus its `package`
+/// path), every Maven / Gradle `src/{main,test}/{java,kotlin}` in the repo, and the
+/// repo root. The longest dotted prefix naming a file wins (static imports, nested
+/// classes); failing that, the package directory (wildcard imports, Kotlin top-level
+/// functions) stands in via its first source file, which is all the module graph needs.
+fn resolve_jvm_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    const EXTS: [&str; 2] = ["java", "kt"];
+    let segs: Vec<&str> = imp
+        .split('.')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let wildcard = segs.last() == Some(&"*");
+    let segs = if wildcard {
+        &segs[..segs.len() - 1]
+    } else {
+        &segs[..]
+    };
+    if segs.is_empty() {
+        return ImportTarget::Unknown;
+    }
+
+    let mut bases: Vec<PathBuf> = Vec::new();
+    bases.extend(jvm_own_source_root(from_file_abs));
+    bases.extend(jvm_source_roots(repo_root));
+    bases.push(repo_root.to_path_buf());
+
+    if !wildcard {
+        for k in (1..=segs.len()).rev() {
+            let rel = segs[..k].join("/");
+            let rel = rel.as_str();
+            let candidates = bases
+                .iter()
+                .flat_map(|b| EXTS.iter().map(move |e| b.join(format!("{rel}.{e}"))))
+                .collect();
+            if let Some(p) = first_existing_in_repo(repo_root, candidates) {
+                return ImportTarget::Internal(p);
+            }
+        }
+    }
+    let package_len = if wildcard { segs.len() } else { segs.len() - 1 };
+    if package_len > 0 {
+        let rel = segs[..package_len].join("/");
+        let candidates = bases
+            .iter()
+            .filter_map(|b| first_source_file(&b.join(&rel), &EXTS, 0))
+            .collect();
+        if let Some(p) = first_existing_in_repo(repo_root, candidates) {
+            return ImportTarget::Internal(p);
+        }
+    }
+    ImportTarget::External(segs[..segs.len().min(2)].join("."))
+}
+
+/// Directory above `file`'s `package a.b.c` path, when its location matches the
+/// declaration (`.../src/main/java/a/b/c/X.java` -> `.../src/main/java`).
+fn jvm_own_source_root(file: &Path) -> Option<PathBuf> {
+    let text = std::fs::read_to_string(file).ok()?;
+    let package = text
+        .lines()
+        .map(str::trim)
+        .find_map(|l| l.strip_prefix("package "))?
+        .trim()
+        .trim_end_matches(';');
+    let mut dir = file.parent()?;
+    for seg in package.rsplit('.') {
+        if dir.file_name()?.to_str()? != seg.trim() {
+            return None;
+        }
+        dir = dir.parent()?;
+    }
+    Some(dir.to_path_buf())
+}
+
+/// Maven / Gradle source roots (`src/{main,test}/{java,kotlin}`) anywhere under
+/// `repo_root`. Walked once per root for the life of the process, so resolving
+/// each import does not re-walk the repo.
+fn jvm_source_roots(repo_root: &Path) -> Vec<PathBuf> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Vec<PathBuf>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(roots) = cache.lock().unwrap().get(repo_root) {
+        return roots.clone();
+    }
+
+    let root_owned = repo_root.to_path_buf();
+    let walker = WalkBuilder::new(repo_root)
+        .standard_filters(true)
+        .hidden(false)
+        .max_depth(Some(12))
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let name = entry.file_name().to_str().unwrap_or("");
+            entry.file_type().is_some_and(|t| t.is_dir())
+                && !should_skip_dir_name(name)
+                && !forbidden_below(&root_owned, entry.path())
+        })
+        .build();
+    let roots: Vec<PathBuf> = walker
+        .flatten()
+        .map(|ent| ent.into_path())
+        .filter(|p| {
+            let s = normalize_slash(p);
+            [
+                "/src/main/java",
+                "/src/main/kotlin",
+                "/src/test/java",
+                "/src/test/kotlin",
+            ]
+            .iter()
+            .any(|suffix| s.ends_with(suffix))
+        })
+        .collect();
+    cache
+        .lock()
+        .unwrap()
+        .insert(repo_root.to_path_buf(), roots.clone());
+    roots
+}
+
+/// Swift: `import Foo` (or `Foo.Bar`) -> the `Foo` target of the enclosing SwiftPM
+/// package (`Sources/Foo/`, `Tests/Foo/`) or a top-level `Foo/` directory,
+/// represented by its first `.swift` file. Anything else is an external module.
+fn resolve_swift_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    let module = imp.split('.').next().unwrap_or(imp).trim();
+    if module.is_empty() {
+        return ImportTarget::Unknown;
+    }
+
+    let mut bases: Vec<PathBuf> = Vec::new();
+    let mut dir = from_file_abs.parent();
+    while let Some(d) = dir {
+        if d.join("Package.swift").is_file() {
+            bases.push(d.join("Sources"));
+            bases.push(d.join("Tests"));
+            break;
+        }
+        if d == repo_root {
+            break;
+        }
+        dir = d.parent();
+    }
+    bases.push(repo_root.join("Sources"));
+    bases.push(repo_root.to_path_buf());
+
+    let candidates = bases
+        .iter()
+        .filter_map(|b| first_source_file(&b.join(module), &["swift"], 3))
+        .collect();
+    match first_existing_in_repo(repo_root, candidates) {
+        Some(p) => ImportTarget::Internal(p),
+        None => ImportTarget::External(module.to_string()),
+    }
+}
+
+/// Go: an import under the enclosing module's path (the nearest `go.mod`'s `module`
+/// line), e.g. `github.com/acme/shop/internal/cart`, is the `internal/cart` package
+/// directory next to that `go.mod`, represented by its first `.go` file. Anything else
+/// is external: the standard library (`net/http` -> `net`) or another module
+/// (`github.com/google/uuid`).
+fn resolve_go_import(repo_root: &Path, from_file_abs: &Path, imp: &str) -> ImportTarget {
+    let imp = imp.trim_matches(|c| c == '"' || c == '`');
+    let mut dir = from_file_abs.parent();
+    while let Some(d) = dir {
+        if let Some(module) = read_go_module_path(&d.join("go.mod")) {
+            let rel = if imp == module {
+                Some("")
+            } else {
+                imp.strip_prefix(module.as_str())
+                    .and_then(|r| r.strip_prefix('/'))
+            };
+            if let Some(rel) = rel {
+                let candidates = first_source_file(&d.join(rel), &["go"], 0)
+                    .into_iter()
+                    .collect();
+                return match first_existing_in_repo(repo_root, candidates) {
+                    Some(p) => ImportTarget::Internal(p),
+                    None => ImportTarget::Unknown,
+                };
+            }
+            break;
+        }
+        if d == repo_root {
+            break;
+        }
+        dir = d.parent();
+    }
+    let segs: Vec<&str> = imp.split('/').collect();
+    let host_path = segs[0].contains('.');
+    ImportTarget::External(segs[..segs.len().min(if host_path { 3 } else { 1 })].join("/"))
+}
+
+/// First file (by name) in `dir` with one of `exts`, descending up to `depth`
+/// levels into subdirectories when `dir` itself has none.
+fn first_source_file(dir: &Path, exts: &[&str], depth: usize) -> Option<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    entries.sort();
+    let file = entries.iter().find(|p| {
+        p.is_file()
+            && p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| exts.contains(&e))
+    });
+    if let Some(f) = file {
+        return Some(f.clone());
+    }
+    if depth == 0 {
+        return None;
+    }
+    entries
+        .iter()
+        .filter(|p| p.is_dir())
+        .find_map(|d| first_source_file(d, exts, depth - 1))
+}
+
 fn find_owner_module(
     mut dir: &Path,
     stop_at: &Path,
@@ -633,8 +1072,8 @@ fn find_owner_module(
     }
 }
 
-/// High-level architecture graph: nodes are module roots; edges are weighted imports between modules.
-pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph> {
+/// Canonical absolute form of a graph `root`, which must be an existing directory.
+fn resolve_graph_root(repo_root: &Path, root: &Path) -> Result<PathBuf> {
     let root_abs = if root.is_absolute() {
         root.to_path_buf()
     } else {
@@ -649,21 +1088,34 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
     if !root_abs.is_dir() {
         anyhow::bail!("Graph root is not a directory: {}", root_abs.display());
     }
+    Ok(root_abs)
+}
+
+#[derive(Default)]
+struct ModuleAcc {
+    bytes: u64,
+    file_count: u64,
+    files: Vec<PathBuf>,
+}
 
+/// Module roots under `root_abs` (dirs holding a marker file, plus `root_abs` itself)
+/// and the source files each one owns (nearest ancestor module root).
+fn collect_module_files(root_abs: &Path) -> (BTreeSet<PathBuf>, BTreeMap<PathBuf, ModuleAcc>) {
     // 1) Discover module roots (directories containing marker files).
     let mut module_roots: BTreeSet<PathBuf> = BTreeSet::new();
-    module_roots.insert(root_abs.clone());
+    module_roots.insert(root_abs.to_path_buf());
 
-    let walker = WalkBuilder::new(&root_abs)
+    let root_owned = root_abs.to_path_buf();
+    let walker = WalkBuilder::new(root_abs)
         .standard_filters(true)
         .hidden(false)
         .max_depth(Some(25))
-        .filter_entry(|entry| {
+        .filter_entry(move |entry| {
             let name = entry.file_name().to_str().unwrap_or("");
             if should_skip_dir_name(name) {
                 return false;
             }
-            if path_has_forbidden_component(entry.path()) {
+            if forbidden_below(&root_owned, entry.path()) {
                 return false;
             }
             true
@@ -687,33 +1139,28 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
     }
 
     // 2) Assign files to their owning module (nearest ancestor module root).
-    #[derive(Default)]
-    struct ModuleAcc {
-        bytes: u64,
-        file_count: u64,
-        files: Vec<PathBuf>,
-    }
-
     let mut modules: BTreeMap<PathBuf, ModuleAcc> = BTreeMap::new();
     for r in &module_roots {
         modules.entry(r.clone()).or_default();
     }
 
-    let walker2 = WalkBuilder::new(&root_abs)
+    let root_owned = root_abs.to_path_buf();
+    let walker2 = WalkBuilder::new(root_abs)
         .standard_filters(true)
         .hidden(false)
         .max_depth(Some(25))
-        .filter_entry(|entry| {
+        .filter_entry(move |entry| {
             let name = entry.file_name().to_str().unwrap_or("");
             if should_skip_dir_name(name) {
                 return false;
             }
-            if path_has_forbidden_component(entry.path()) {
+            if forbidden_below(&root_owned, entry.path()) {
                 return false;
             }
             true
         })
         .build();
+    let linguist = LinguistAttributes::discover(root_abs);
 
     for ent in walker2 {
         let Ok(ent) = ent else { continue };
@@ -721,15 +1168,15 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
             continue;
         }
         let p = ent.path();
-        if path_has_forbidden_component(p) {
+        if forbidden_below(root_abs, p) || linguist.classify(p).is_some() {
             continue;
         }
         if !is_allowed_ext(p) {
             continue;
         }
         let Some(parent) = p.parent() else { continue };
-        let owner =
-            find_owner_module(parent, &root_abs, &module_roots).unwrap_or_else(|| root_abs.clone());
+        let owner = find_owner_module(parent, root_abs, &module_roots)
+            .unwrap_or_else(|| root_abs.to_path_buf());
         let acc = modules.entry(owner).or_default();
         let sz = ent.metadata().map(|m| m.len()).unwrap_or(0);
         acc.bytes += sz;
@@ -737,6 +1184,18 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
         acc.files.push(p.to_path_buf());
     }
 
+    (module_roots, modules)
+}
+
+/// High-level architecture graph: nodes are module roots; edges are weighted imports between modules.
+///
+/// The output is stable: nodes are sorted by id and edges by `(source, target)`, and
+/// nothing depends on walk or hash order, so the same tree always serializes to the
+/// same bytes — safe to commit as an artifact or use in snapshot tests.
+pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph> {
+    let root_abs = resolve_graph_root(repo_root, root)?;
+    let (module_roots, modules) = collect_module_files(&root_abs);
+
     // 3) Build nodes.
     let mut nodes: Vec<ModuleNode> = Vec::new();
     let mut module_id_by_abs: BTreeMap<PathBuf, String> = BTreeMap::new();
@@ -774,7 +1233,7 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
             };
 
             for imp in analyzed.imports {
-                let Some(dst_file_abs) = resolve_ts_import(repo_root, file_abs, &imp) else {
+                let Some(dst_file_abs) = resolve_import_path(repo_root, file_abs, &imp) else {
                     continue;
                 };
                 let Some(dst_parent) = dst_file_abs.parent() else {
@@ -802,11 +1261,170 @@ pub fn build_module_graph(repo_root: &Path, root: &Path) -> Result<ModuleGraph>
             weight: w,
         });
     }
-    edges.sort_by(|a, b| a.id.cmp(&b.id));
+    edges.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
 
     Ok(ModuleGraph { nodes, edges })
 }
 
+/// One file's place in the import graph, as returned by [`centrality`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCentrality {
+    /// Repo-relative path.
+    pub path: String,
+    /// Id of the owning module (same ids as [`build_module_graph`] nodes).
+    pub module: String,
+    /// Distinct in-repo files importing this one.
+    pub in_degree: u64,
+    /// Distinct in-repo files this one imports.
+    pub out_degree: u64,
+    /// PageRank over the file import graph (sums to 1.0 across all files).
+    pub pagerank: f64,
+}
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERS: usize = 50;
+const PAGERANK_EPSILON: f64 = 1e-9;
+
+/// Rank the source files under `root` by how much the rest of the repo depends on them.
+///
+/// Walks the same files as [`build_module_graph`] but keeps the import edges at file
+/// level: each file's in-degree (distinct importers), out-degree and PageRank.
+/// Returns the `top_n` files ordered by in-degree, then PageRank, then path.
+pub fn centrality(repo_root: &Path, root: &Path, top_n: usize) -> Result<Vec<FileCentrality>> {
+    use rayon::prelude::*;
+
+    // Import resolution yields canonical paths, so compare against a canonical root.
+    let repo_root = &repo_root
+        .canonicalize()
+        .unwrap_or_else(|_| repo_root.to_path_buf());
+    let root_abs = resolve_graph_root(repo_root, root)?;
+    let (_, modules) = collect_module_files(&root_abs);
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for (module_abs, acc) in &modules {
+        let rel = rel_str(repo_root, module_abs);
+        let module_id = normalize_module_id(rel.as_deref().unwrap_or("."));
+        for f in &acc.files {
+            let f = f.canonicalize().unwrap_or_else(|_| f.clone());
+            files.push((f, module_id.clone()));
+        }
+    }
+    files.sort();
+    files.dedup_by(|a, b| a.0 == b.0);
+
+    let index_of: BTreeMap<&Path, usize> = files
+        .iter()
+        .enumerate()
+        .map(|(i, (p, _))| (p.as_path(), i))
+        .collect();
+
+    // Out-edges per file: distinct in-graph import targets, self-imports dropped.
+    let out_edges: Vec<Vec<usize>> = files
+        .par_iter()
+        .enumerate()
+        .map(|(i, (abs, _))| {
+            let Ok(analyzed) = analyze_file(abs) else {
+                return Vec::new();
+            };
+            let targets: BTreeSet<usize> = analyzed
+                .imports
+                .iter()
+                .filter_map(|imp| resolve_import_path(repo_root, abs, imp))
+                .filter_map(|dst| index_of.get(dst.as_path()).copied())
+                .filter(|&j| j != i)
+                .collect();
+            targets.into_iter().collect()
+        })
+        .collect();
+
+    let n = files.len();
+    let mut in_degree = vec![0u64; n];
+    for targets in &out_edges {
+        for &j in targets {
+            in_degree[j] += 1;
+        }
+    }
+    let pagerank = pagerank(&out_edges);
+
+    let mut ranked: Vec<FileCentrality> = files
+        .into_iter()
+        .enumerate()
+        .map(|(i, (abs, module))| FileCentrality {
+            path: rel_str(repo_root, &abs).unwrap_or_else(|| normalize_slash(&abs)),
+            module,
+            in_degree: in_degree[i],
+            out_degree: out_edges[i].len() as u64,
+            pagerank: pagerank[i],
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.in_degree
+            .cmp(&a.in_degree)
+            .then_with(|| b.pagerank.total_cmp(&a.pagerank))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    ranked.truncate(top_n);
+    Ok(ranked)
+}
+
+/// Power-iteration PageRank; rank held by files with no out-edges is spread evenly.
+fn pagerank(out_edges: &[Vec<usize>]) -> Vec<f64> {
+    let n = out_edges.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_MAX_ITERS {
+        let dangling: f64 = out_edges
+            .iter()
+            .zip(&rank)
+            .filter(|(targets, _)| targets.is_empty())
+            .map(|(_, r)| r)
+            .sum();
+        let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+        for (targets, r) in out_edges.iter().zip(&rank) {
+            if targets.is_empty() {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * r / targets.len() as f64;
+            for &j in targets {
+                next[j] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_EPSILON {
+            break;
+        }
+    }
+    rank
+}
+
+/// Markdown table of [`centrality`] results for agents.
+pub fn format_centrality(ranked: &[FileCentrality]) -> String {
+    if ranked.is_empty() {
+        return "No source files found.".to_string();
+    }
+    let mut out = format!(
+        "## Centrality: top {} file(s) by in-degree (importers), then PageRank\n\n\
+         | # | File | Module | In | Out | PageRank |\n|---|---|---|---|---|---|\n",
+        ranked.len()
+    );
+    for (i, f) in ranked.iter().enumerate() {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {:.4} |\n",
+            i + 1,
+            f.path,
+            f.module,
+            f.in_degree,
+            f.out_degree,
+            f.pagerank
+        ));
+    }
+    out
+}
+
 /// Core path normalization helper: ALWAYS converts backslashes to forward slashes.
 /// This ensures cross-platform consistency (Windows \ vs Unix /).
 fn normalize_slash(p: &Path) -> String {
@@ -863,6 +1481,12 @@ fn should_skip_dir_name(name: &str) -> bool {
     )
 }
 
+/// [`path_has_forbidden_component`] on the part of `path` below `root`, so a repo
+/// that itself lives under e.g. `/tmp` or `target/` is still walked.
+fn forbidden_below(root: &Path, path: &Path) -> bool {
+    path_has_forbidden_component(path.strip_prefix(root).unwrap_or(path))
+}
+
 fn path_has_forbidden_component(path: &Path) -> bool {
     for comp in path.components() {
         let std::path::Component::Normal(os) = comp else {
@@ -884,8 +1508,10 @@ fn is_allowed_ext(path: &Path) -> bool {
     };
     matches!(
         ext,
-        // Rust / JS / TS source
-        "rs" | "ts" | "tsx" | "js" | "jsx" |
+        // Rust / JS / TS / Python source
+        "rs" | "ts" | "tsx" | "js" | "jsx" | "py" |
+        // JVM / Swift source
+        "java" | "kt" | "swift" |
         // Config / docs
         "json" | "md" | "toml" |
         // Web / styles (small allowlist, safe to count)
@@ -899,7 +1525,7 @@ fn is_allowed_source_ext(path: &Path) -> bool {
     };
     matches!(
         ext,
-        "rs" | "ts" | "tsx" | "js" | "jsx" | "py" | "go" | "dart"
+        "rs" | "ts" | "tsx" | "js" | "jsx" | "py" | "go" | "dart" | "java" | "kt" | "swift"
     )
 }
 
@@ -914,6 +1540,8 @@ pub fn build_repo_map(repo_root: &Path) -> Result<RepoMap> {
 /// - Hard-excludes forbidden folders (node_modules, .git, target, dist, build, etc).
 /// - File nodes are only included for allowlisted text/source extensions.
 /// - Edges connect `parent_id -> child_id`.
+/// - Import edges may leave the scope; their targets get nodes too (in-repo files
+///   as `file`, packages as `external`), so every edge has both ends in `nodes`.
 pub fn build_repo_map_scoped(repo_root: &Path, scope: &Path) -> Result<RepoMap> {
     let scope_abs = if scope.is_absolute() {
         scope.to_path_buf()
@@ -1004,6 +1632,7 @@ pub fn build_repo_map_scoped(repo_root: &Path, scope: &Path) -> Result<RepoMap>
                 id: format!("{}->{}", parent_id, id),
                 source: parent_id.clone(),
                 target: id,
+                external: false,
             });
 
             continue;
@@ -1036,26 +1665,23 @@ pub fn build_repo_map_scoped(repo_root: &Path, scope: &Path) -> Result<RepoMap>
                 id: format!("{}->{}", parent_id, id),
                 source: parent_id.clone(),
                 target: id,
+                external: false,
             });
         }
     }
 
-    // Smart edges: resolve file-to-file imports (relative imports for TS/JS).
-    let mut id_set: BTreeSet<String> = BTreeSet::new();
-    for n in &nodes {
-        id_set.insert(n.id.clone());
-    }
-
-    // Build a quick lookup of existing file ids.
-    let mut file_ids: Vec<String> = Vec::new();
-    for n in &nodes {
-        if n.kind == "file" {
-            file_ids.push(n.id.clone());
-        }
-    }
+    // Smart edges: resolve file-to-file imports with the per-language `find_imports`.
+    // Targets may live anywhere in the repo (not only among the immediate children),
+    // so the edge list is a real dependency graph. Imports that do not resolve to an
+    // in-repo file are kept as `external:<name>` edges with `external = true`,
+    // pointing at an `external` node.
+    let file_ids: Vec<String> = nodes
+        .iter()
+        .filter(|n| n.kind == "file")
+        .map(|n| n.id.clone())
+        .collect();
+    let mut node_ids: BTreeSet<String> = nodes.iter().map(|n| n.id.clone()).collect();
 
-    // Attempt to resolve relative imports within the repo.
-    let exts = ["ts", "tsx", "js", "jsx", "json", "md"];
     for src_id in &file_ids {
         let src_abs = repo_root.join(src_id);
         let analyzed = match analyze_file(&src_abs) {
@@ -1064,55 +1690,437 @@ pub fn build_repo_map_scoped(repo_root: &Path, scope: &Path) -> Result<RepoMap>
         };
 
         for imp in analyzed.imports {
-            let imp = imp.trim();
-            if !imp.starts_with('.') {
-                continue;
-            }
-
-            let base_dir = src_abs.parent().unwrap_or(repo_root);
-            let mut candidates: Vec<PathBuf> = Vec::new();
-
-            let raw = base_dir.join(imp);
-            candidates.push(raw.clone());
-            for e in exts {
-                candidates.push(base_dir.join(format!("{}.{}", imp, e)));
-            }
-            // Directory-style imports: ./foo -> ./foo/index.ts
-            for e in ["ts", "tsx", "js", "jsx"] {
-                candidates.push(base_dir.join(imp).join(format!("index.{}", e)));
-            }
-
-            let mut resolved: Option<String> = None;
-            for cand in candidates {
-                if !cand.exists() {
-                    continue;
-                }
-                let cand_abs = cand.canonicalize().unwrap_or(cand);
-                if let Ok(rel) = cand_abs.strip_prefix(repo_root) {
-                    let rel_str = rel.to_string_lossy().replace('\\', "/");
-                    let id = normalize_module_id(&rel_str);
-                    if id_set.contains(&id) {
-                        resolved = Some(id);
-                        break;
-                    }
+            let (dst_id, external, dst_abs) = match resolve_import(repo_root, &src_abs, &imp) {
+                ImportTarget::Internal(dst_abs) => {
+                    let Some(rel) = rel_str(repo_root, &dst_abs) else {
+                        continue;
+                    };
+                    (normalize_module_id(&rel), false, Some(dst_abs))
                 }
-            }
-
-            let Some(dst_id) = resolved else { continue };
+                ImportTarget::External(name) => (format!("external:{}", name), true, None),
+                ImportTarget::Unknown => continue,
+            };
             if dst_id == *src_id {
                 continue;
             }
+            if node_ids.insert(dst_id.clone()) {
+                nodes.push(import_target_node(&dst_id, dst_abs.as_deref()));
+            }
 
             edges.push(MapEdge {
                 id: format!("import:{}->{}", src_id, dst_id),
                 source: src_id.clone(),
                 target: dst_id,
+                external,
             });
         }
     }
 
     nodes.sort_by(|a, b| a.id.cmp(&b.id));
     edges.sort_by(|a, b| a.id.cmp(&b.id));
+    edges.dedup_by(|a, b| a.id == b.id);
 
     Ok(RepoMap { nodes, edges })
 }
+
+/// Node for an import target outside the scoped listing: the in-repo file at
+/// `abs`, or the `external:<name>` package when `abs` is `None`.
+fn import_target_node(id: &str, abs: Option<&Path>) -> MapNode {
+    let (kind, name, bytes) = match abs {
+        Some(abs) => (
+            "file",
+            abs.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| id.to_string()),
+            std::fs::metadata(abs).map(|m| m.len()).unwrap_or(0),
+        ),
+        None => (
+            "external",
+            id.trim_start_matches("external:").to_string(),
+            0,
+        ),
+    };
+    MapNode {
+        id: id.to_string(),
+        label: clamp_label(&name),
+        path: id.to_string(),
+        kind: kind.to_string(),
+        size_class: size_class_from_bytes(bytes),
+        bytes,
+        est_tokens: est_tokens_from_bytes(bytes),
+    }
+}
+
+/// Merge the maps of several repos into one polyrepo overview.
+///
+/// Each map is paired with its repo name, which namespaces every node id and path
+/// (`api/src/main.rs`; the map's root `.` becomes `api`), so equal paths in different
+/// repos stay distinct. A name already taken gets a `-2`, `-3`, ... suffix.
+/// `external:<name>` nodes and targets stay un-prefixed, so a dependency several
+/// repos share is a single node.
+pub fn merge_maps(maps: &[(String, RepoMap)]) -> RepoMap {
+    let mut used: BTreeSet<String> = BTreeSet::new();
+    let mut nodes: Vec<MapNode> = Vec::new();
+    let mut edges: Vec<MapEdge> = Vec::new();
+
+    for (name, map) in maps {
+        let mut prefix = name.clone();
+        let mut n = 1;
+        while !used.insert(prefix.clone()) {
+            n += 1;
+            prefix = format!("{name}-{n}");
+        }
+        let ns = |id: &str| {
+            if id == "." {
+                prefix.clone()
+            } else if id.starts_with("external:") {
+                id.to_string()
+            } else {
+                format!("{prefix}/{id}")
+            }
+        };
+
+        for node in &map.nodes {
+            nodes.push(MapNode {
+                id: ns(&node.id),
+                path: ns(&node.path),
+                ..node.clone()
+            });
+        }
+        for edge in &map.edges {
+            let source = ns(&edge.source);
+            let target = ns(&edge.target);
+            let id = if edge.id.starts_with("import:") {
+                format!("import:{}->{}", source, target)
+            } else {
+                format!("{}->{}", source, target)
+            };
+            edges.push(MapEdge {
+                id,
+                source,
+                target,
+                external: edge.external,
+            });
+        }
+    }
+
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    nodes.dedup_by(|a, b| a.id == b.id);
+    edges.sort_by(|a, b| a.id.cmp(&b.id));
+    RepoMap { nodes, edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, text: &str) -> PathBuf {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn centrality_walks_a_repo_under_a_skipped_dir_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tmp").join("repo");
+        write(&root, "app/main.py", "from lib.util import helper\n");
+        write(&root, "lib/util.py", "def helper():\n    pass\n");
+        write(&root, "build/gen.py", "from lib.util import helper\n");
+
+        let ranked = centrality(&root, &root, usize::MAX).unwrap();
+        let mut paths: Vec<&str> = ranked.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        // `build/` inside the repo is still skipped.
+        assert_eq!(paths, ["app/main.py", "lib/util.py"]);
+        let util = ranked.iter().find(|f| f.path == "lib/util.py").unwrap();
+        assert_eq!(util.in_degree, 1);
+    }
+
+    #[test]
+    fn jvm_imports_resolve_across_gradle_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let strings = write(
+            &root,
+            "core/src/main/java/com/acme/util/Strings.java",
+            "package com.acme.util;\npublic class Strings {}\n",
+        );
+        write(
+            &root,
+            "core/src/main/kotlin/com/acme/util/Ext.kt",
+            "package com.acme.util\nfun String.shout() = uppercase()\n",
+        );
+        let app = write(
+            &root,
+            "app/src/main/java/com/acme/app/Main.java",
+            "package com.acme.app;\nimport com.acme.util.Strings;\n",
+        );
+
+        let resolve = |imp: &str| resolve_import_path(&root, &app, imp);
+        assert_eq!(resolve("com.acme.util.Strings"), Some(strings.clone()));
+        assert_eq!(resolve("com.acme.util.Strings.trim"), Some(strings.clone()));
+        assert_eq!(resolve("com.acme.util.*"), Some(strings));
+        assert_eq!(resolve("java.util.List"), None);
+
+        let kt = write(
+            &root,
+            "app/src/main/kotlin/com/acme/app/Cli.kt",
+            "package com.acme.app\nimport com.acme.util.shout\n",
+        );
+        // Top-level functions resolve to their package directory, in some source set.
+        let shout = resolve_import_path(&root, &kt, "com.acme.util.shout").unwrap();
+        assert!(shout.starts_with(root.join("core")), "{shout:?}");
+        assert!(
+            shout.parent().unwrap().ends_with("com/acme/util"),
+            "{shout:?}"
+        );
+    }
+
+    #[test]
+    fn jvm_imports_resolve_between_java_and_kotlin_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let strings = write(
+            &root,
+            "core/src/main/java/com/acme/util/Strings.java",
+            "package com.acme.util;\npublic class Strings {}\n",
+        );
+        let money = write(
+            &root,
+            "core/src/main/kotlin/com/acme/money/Money.kt",
+            "package com.acme.money\ndata class Money(val cents: Long)\n",
+        );
+        let java = write(
+            &root,
+            "app/src/main/java/com/acme/app/Main.java",
+            "package com.acme.app;\nimport com.acme.money.Money;\n",
+        );
+        let kt = write(
+            &root,
+            "app/src/test/kotlin/com/acme/app/MainTest.kt",
+            "package com.acme.app\nimport com.acme.util.Strings\nimport com.acme.money.*\n",
+        );
+
+        let cases = [
+            (&java, "com.acme.money.Money", Some(&money)),
+            (&kt, "com.acme.util.Strings", Some(&strings)),
+            (&kt, "com.acme.money.*", Some(&money)),
+            (&kt, "kotlin.collections.List", None),
+        ];
+        for (from, imp, want) in cases {
+            assert_eq!(
+                resolve_import_path(&root, from, imp).as_ref(),
+                want,
+                "{imp} from {from:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn swift_imports_resolve_to_swiftpm_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "Package.swift", "// swift-tools-version:5.9\n");
+        let model = write(
+            &root,
+            "Sources/Core/Model.swift",
+            "public struct Model {}\n",
+        );
+        let app = write(
+            &root,
+            "Sources/App/main.swift",
+            "import Core\nimport Foundation\n",
+        );
+
+        assert_eq!(resolve_import_path(&root, &app, "Core"), Some(model));
+        assert_eq!(resolve_import_path(&root, &app, "Foundation"), None);
+    }
+
+    #[test]
+    fn swift_test_targets_resolve_within_their_own_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "Package.swift", "// swift-tools-version:5.9\n");
+        write(&root, "Packages/Kit/Package.swift", "// swift-tools-version:5.9\n");
+        let api = write(
+            &root,
+            "Packages/Kit/Sources/KitCore/Api.swift",
+            "public enum Api {}\n",
+        );
+        let test = write(
+            &root,
+            "Packages/Kit/Tests/KitCoreTests/ApiTests.swift",
+            "import XCTest\n@testable import KitCore\n",
+        );
+
+        assert_eq!(resolve_import_path(&root, &test, "KitCore"), Some(api));
+        assert_eq!(resolve_import_path(&root, &test, "XCTest"), None);
+    }
+
+    #[test]
+    fn go_imports_resolve_under_the_module_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "go.mod", "module github.com/acme/shop\n\ngo 1.22\n");
+        let cart = write(
+            &root,
+            "internal/cart/cart.go",
+            "package cart\n\nfunc Total() int { return 0 }\n",
+        );
+        let main = write(
+            &root,
+            "cmd/shop/main.go",
+            "package main\n\nimport (\n\t\"fmt\"\n\t\"github.com/acme/shop/internal/cart\"\n)\n",
+        );
+
+        let resolve = |imp: &str| resolve_import_path(&root, &main, imp);
+        assert_eq!(resolve("github.com/acme/shop/internal/cart"), Some(cart));
+        assert_eq!(resolve("github.com/acme/shop/internal/missing"), None);
+        assert_eq!(resolve("github.com/acme/shopping/cart"), None);
+        assert_eq!(resolve("fmt"), None);
+        assert!(matches!(
+            resolve_import(&root, &main, "github.com/google/uuid/v2"),
+            ImportTarget::External(name) if name == "github.com/google/uuid"
+        ));
+    }
+
+    #[test]
+    fn rust_imports_resolve_crate_self_and_super_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "Cargo.toml", "[package]\nname = \"shop\"\n");
+        write(&root, "src/lib.rs", "pub mod net;\npub mod util;\n");
+        let util = write(&root, "src/util.rs", "pub fn helper() {}\n");
+        let net = write(&root, "src/net/mod.rs", "pub mod http;\n");
+        let http = write(&root, "src/net/http.rs", "pub fn get() {}\n");
+
+        assert_eq!(
+            resolve_import_path(&root, &http, "crate::util::{helper, other}"),
+            Some(util.clone())
+        );
+        assert_eq!(
+            resolve_import_path(&root, &util, "crate::net::http::get as fetch"),
+            Some(http.clone())
+        );
+        assert_eq!(resolve_import_path(&root, &net, "self::http::*"), Some(http));
+        assert_eq!(resolve_import_path(&root, &net, "super::util::helper"), Some(util));
+        assert_eq!(resolve_import_path(&root, &net, "crate::missing::Thing"), None);
+        assert!(matches!(
+            resolve_import(&root, &net, "serde::Serialize"),
+            ImportTarget::External(name) if name == "serde"
+        ));
+    }
+
+    #[test]
+    fn python_imports_resolve_relative_and_absolute_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let init = write(&root, "pkg/__init__.py", "");
+        let utils = write(&root, "pkg/utils.py", "class Helper: pass\n");
+        let module = write(&root, "pkg/sub/mod.py", "from ..utils import Helper\n");
+
+        let resolve = |imp: &str| resolve_import_path(&root, &module, imp);
+        assert_eq!(resolve("..utils.Helper"), Some(utils.clone()));
+        assert_eq!(resolve(".."), Some(init));
+        assert_eq!(resolve("pkg.utils"), Some(utils));
+        assert_eq!(resolve(".missing"), None);
+        assert!(matches!(
+            resolve_import(&root, &module, "requests.adapters"),
+            ImportTarget::External(name) if name == "requests"
+        ));
+    }
+
+    #[test]
+    fn typescript_imports_bucket_by_package_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let util = write(&root, "src/util.ts", "export function f() {}\n");
+        let index = write(&root, "src/index.ts", "import { f } from \"./util\";\n");
+
+        let external = |imp: &str| match resolve_import(&root, &index, imp) {
+            ImportTarget::External(name) => Some(name),
+            _ => None,
+        };
+        assert_eq!(resolve_import_path(&root, &index, "./util"), Some(util));
+        assert_eq!(external("@scope/pkg/sub/path").as_deref(), Some("@scope/pkg"));
+        assert_eq!(external("lodash/fp").as_deref(), Some("lodash"));
+        assert_eq!(external("react").as_deref(), Some("react"));
+        assert!(matches!(
+            resolve_import(&root, &index, "./missing"),
+            ImportTarget::Unknown
+        ));
+    }
+
+    #[test]
+    fn every_map_edge_has_both_nodes() {
+        let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "lib/util.ts", "export function f() {}\n");
+        write(
+            &root,
+            "app/index.ts",
+            "import { f } from \"../lib/util\";\nimport _ from \"lodash\";\n",
+        );
+
+        let map = build_repo_map_scoped(&root, Path::new("app")).unwrap();
+        let ids: BTreeSet<&str> = map.nodes.iter().map(|n| n.id.as_str()).collect();
+        for edge in &map.edges {
+            assert!(ids.contains(edge.source.as_str()), "dangling source {}", edge.id);
+            assert!(ids.contains(edge.target.as_str()), "dangling target {}", edge.id);
+        }
+        let kind = |id: &str| map.nodes.iter().find(|n| n.id == id).unwrap().kind.as_str();
+        assert_eq!(kind("lib/util.ts"), "file");
+        assert_eq!(kind("external:lodash"), "external");
+    }
+
+    #[test]
+    fn merged_maps_namespace_each_repo() {
+        // Repo maps skip forbidden path components such as `tmp`, so stay out of /tmp.
+        let tempdir = || tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let dirs = [tempdir(), tempdir()];
+        let mut maps = Vec::new();
+        for dir in &dirs {
+            let root = dir.path().canonicalize().unwrap();
+            write(
+                &root,
+                "index.ts",
+                "import { f } from \"./util\";\nimport _ from \"lodash\";\n",
+            );
+            write(&root, "util.ts", "export function f() {}\n");
+            maps.push(("svc".to_string(), build_repo_map(&root).unwrap()));
+        }
+        let merged = merge_maps(&maps);
+
+        let ids: Vec<&str> = merged.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "external:lodash",
+                "svc",
+                "svc-2",
+                "svc-2/index.ts",
+                "svc-2/util.ts",
+                "svc/index.ts",
+                "svc/util.ts"
+            ]
+        );
+        assert!(merged.nodes.iter().all(|n| n.path == n.id));
+        assert!(merged
+            .edges
+            .iter()
+            .any(|e| e.id == "svc-2->svc-2/index.ts" && e.source == "svc-2"));
+        assert!(merged
+            .edges
+            .iter()
+            .any(|e| e.id == "import:svc/index.ts->svc/util.ts" && !e.external));
+        let externals: Vec<&str> = merged
+            .edges
+            .iter()
+            .filter(|e| e.external)
+            .map(|e| e.target.as_str())
+            .collect();
+        assert_eq!(externals, ["external:lodash", "external:lodash"]);
+    }
+}
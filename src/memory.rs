@@ -3,6 +3,11 @@
 //! Deserializes `MemoryEntry` records written by the `CortexSync` daemon into
 //! `~/.cortexast/global_memory.jsonl`.
 //!
+//! ## Journal location (first match wins)
+//!  1. `$CORTEXAST_MEMORY_PATH` — explicit override of the journal file.
+//!  2. `{project_path}/.cortex_memory.jsonl` — per-project journal, used when it exists.
+//!  3. `~/.cortexast/global_memory.jsonl` — the CortexSync default.
+//!
 //! ## Schema contract (CortexSync schema_version "1.0")
 //!
 //! ```text
@@ -71,15 +76,47 @@ pub struct MemoryEntry {
 // Default journal path
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Return the default path where CortexSync writes its journal.
+/// Environment variable that overrides the journal path for every project.
+pub const MEMORY_PATH_ENV: &str = "CORTEXAST_MEMORY_PATH";
+
+/// File name of the per-project journal, relative to the project root.
+pub const PROJECT_JOURNAL_FILE: &str = ".cortex_memory.jsonl";
+
+/// Return the path where CortexSync writes its journal.
 /// Mirrors CortexSync's `writer::default_output_path()`.
-pub fn default_journal_path() -> std::path::PathBuf {
+pub fn cortexsync_journal_path() -> std::path::PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join(".cortexast")
         .join("global_memory.jsonl")
 }
 
+/// Return the default journal path: `$CORTEXAST_MEMORY_PATH` when set to a
+/// non-empty path, otherwise [`cortexsync_journal_path`].
+pub fn default_journal_path() -> std::path::PathBuf {
+    match std::env::var_os(MEMORY_PATH_ENV).filter(|p| !p.is_empty()) {
+        Some(p) => PathBuf::from(p),
+        None => cortexsync_journal_path(),
+    }
+}
+
+/// Path of the per-project journal for `project_dir` (it may not exist).
+pub fn project_journal_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(PROJECT_JOURNAL_FILE)
+}
+
+/// Pick the journal for `project_dir`: the env override, then the project's
+/// own `.cortex_memory.jsonl` if it exists, then the global default.
+pub fn resolve_journal_path(project_dir: Option<&Path>) -> PathBuf {
+    if std::env::var_os(MEMORY_PATH_ENV).is_some_and(|p| !p.is_empty()) {
+        return default_journal_path();
+    }
+    match project_dir.map(project_journal_path) {
+        Some(local) if local.is_file() => local,
+        _ => default_journal_path(),
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Loader
 // ─────────────────────────────────────────────────────────────────────────────
@@ -145,10 +182,21 @@ impl MemoryStore {
         })
     }
 
-    /// Load from the default journal path (`~/.cortexast/global_memory.jsonl`).
+    /// Load from the default journal path (`~/.cortexast/global_memory.jsonl`,
+    /// or `$CORTEXAST_MEMORY_PATH`).
     /// Returns an empty store if the file does not yet exist.
     pub fn from_default() -> Self {
-        let path = default_journal_path();
+        Self::open(resolve_journal_path(None))
+    }
+
+    /// Load the journal for `project_dir`, preferring its local
+    /// `.cortex_memory.jsonl` over the global one (see [`resolve_journal_path`]).
+    /// Returns an empty store if the file does not yet exist.
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self::open(resolve_journal_path(Some(project_dir)))
+    }
+
+    fn open(path: PathBuf) -> Self {
         if path.exists() {
             Self::load(&path).unwrap_or_else(|_| Self {
                 entries: Vec::new(),
@@ -166,6 +214,40 @@ impl MemoryStore {
         }
     }
 
+    /// Journal file backing this store.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `entry` to the journal as one JSONL line and to the in-memory index.
+    ///
+    /// Creates the journal (and its parent directory) on first write. The
+    /// recorded mtime is refreshed so the next [`reload`](Self::reload) does
+    /// not re-read our own write.
+    pub fn append(&mut self, entry: MemoryEntry) -> Result<()> {
+        use std::io::Write;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Cannot create {}", parent.display()))?;
+        }
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .with_context(|| format!("Cannot append to journal: {}", self.path.display()))?;
+
+        self.mtime = std::fs::metadata(&self.path)
+            .ok()
+            .and_then(|m| m.modified().ok());
+        self.vectors.push(entry.vector.clone().unwrap_or_default());
+        self.entries.push(entry);
+        Ok(())
+    }
+
     /// Re-reads the journal if the file mtime has changed.
     ///
     /// Returns `true` when the store was reloaded, `false` when unchanged.
@@ -191,6 +273,69 @@ impl MemoryStore {
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Vector back-fill
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Embedding model for memory vectors and retriever queries. Its 512-dim
+/// output matches the vectors CortexSync writes in Phase 2.
+pub const MEMORY_EMBED_MODEL: &str = "minishlab/potion-retrieval-32M";
+
+/// Text embedded for an entry's vector. The `passage:` prefix pairs with the
+/// retriever's `query:` prefix, as in the code index.
+pub fn entry_embedding_text(entry: &MemoryEntry) -> String {
+    format!("passage: {}\n{}", entry.intent, entry.decision)
+}
+
+/// Embed every entry in the journal at `path` that has no `vector` yet and
+/// write the vectors back. Returns how many entries were filled.
+///
+/// Lines that are not entries, or already carry a vector, are kept
+/// byte-for-byte; fields this reader doesn't know survive the rewrite. The
+/// journal is replaced via temp file + rename, and lines appended by another
+/// writer while embedding ran are carried over.
+pub fn backfill_vectors(path: &Path, mut embed: impl FnMut(&str) -> Vec<f32>) -> Result<usize> {
+    let original = std::fs::read_to_string(path)
+        .with_context(|| format!("Cannot read journal: {}", path.display()))?;
+
+    let mut out = String::with_capacity(original.len());
+    let mut filled = 0usize;
+    for line in original.split_inclusive('\n') {
+        let body = line.trim_end();
+        let missing = serde_json::from_str::<MemoryEntry>(body)
+            .ok()
+            .filter(|e| e.vector.is_none());
+        match (missing, serde_json::from_str::<serde_json::Value>(body)) {
+            (Some(entry), Ok(serde_json::Value::Object(mut obj))) => {
+                let vector = embed(&entry_embedding_text(&entry));
+                obj.insert("vector".to_string(), serde_json::to_value(vector)?);
+                out.push_str(&serde_json::to_string(&obj)?);
+                out.push('\n');
+                filled += 1;
+            }
+            _ => out.push_str(line),
+        }
+    }
+    if filled == 0 {
+        return Ok(0);
+    }
+
+    // Keep anything appended while we were embedding.
+    let current = std::fs::read_to_string(path).unwrap_or_default();
+    if let Some(tail) = current.strip_prefix(original.as_str()) {
+        if !tail.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(tail);
+    }
+
+    let tmp = path.with_extension("jsonl.reindex");
+    std::fs::write(&tmp, out).with_context(|| format!("Cannot write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("Cannot replace journal: {}", path.display()))?;
+    Ok(filled)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Search primitives
 // ─────────────────────────────────────────────────────────────────────────────
@@ -431,4 +576,86 @@ mod tests {
         assert_eq!(results.len(), 1, "only one entry has tag 'bugfix'");
         assert_eq!(results[0].entry.id, "id-tagged");
     }
+
+    /// `MemoryStore::append` must create the journal, index the entry and
+    /// write a line that `load_journal` reads back.
+    #[test]
+    fn append_creates_journal_and_round_trips() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("nested").join(PROJECT_JOURNAL_FILE);
+
+        let mut store = MemoryStore::open(path.clone());
+        assert!(store.entries().is_empty());
+
+        let entry: MemoryEntry = serde_json::from_str(PHASE1_LINE).unwrap();
+        store.append(entry.clone()).expect("append 1");
+        store.append(entry).expect("append 2");
+
+        assert_eq!(store.entries().len(), 2);
+        assert_eq!(store.vectors.len(), 2, "parallel vecs must stay in step");
+        assert!(!store.reload(), "our own write must not trigger a reload");
+
+        let on_disk = load_journal(&path).expect("load journal");
+        assert_eq!(on_disk.len(), 2);
+        assert_eq!(on_disk[1].intent, "User asked to refactor parser.");
+    }
+
+    /// A project's `.cortex_memory.jsonl` takes over from the global journal
+    /// only once it exists.
+    #[test]
+    fn resolve_prefers_existing_project_journal() {
+        if std::env::var_os(MEMORY_PATH_ENV).is_some() {
+            return; // explicit override wins by design
+        }
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        assert_eq!(
+            resolve_journal_path(Some(dir.path())),
+            default_journal_path()
+        );
+
+        std::fs::write(project_journal_path(dir.path()), "").unwrap();
+        assert_eq!(
+            resolve_journal_path(Some(dir.path())),
+            dir.path().join(PROJECT_JOURNAL_FILE)
+        );
+        assert_eq!(
+            MemoryStore::for_project(dir.path()).path(),
+            dir.path().join(PROJECT_JOURNAL_FILE)
+        );
+    }
+
+    /// `backfill_vectors` must embed only vector-less entries, keep every
+    /// other line intact, and be a no-op the second time round.
+    #[test]
+    fn backfill_vectors_fills_missing_only() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("journal.jsonl");
+        let phase2 = r#"{"schema_version":"1.0","id":"id-vec","session_id":"s1","timestamp":"2026-01-01T00:00:00Z","source_ide":"cursor","project_path":"/proj","intent":"x","decision":"y","vector":[0.5,0.5]}"#;
+        std::fs::write(&path, format!("{PHASE1_LINE}\n{{bad json}}\n{phase2}")).unwrap();
+
+        let mut seen = Vec::new();
+        let filled = backfill_vectors(&path, |text| {
+            seen.push(text.to_string());
+            vec![1.0, 0.0]
+        })
+        .expect("backfill");
+        assert_eq!(filled, 1);
+        assert!(seen[0].starts_with("passage: User asked to refactor parser."));
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            raw.contains("{bad json}\n"),
+            "unparseable lines must survive: {raw}"
+        );
+        assert!(
+            raw.ends_with(phase2),
+            "vectored lines must be untouched: {raw}"
+        );
+
+        let store = MemoryStore::load(&path).expect("store");
+        assert_eq!(store.vectors[0], vec![1.0, 0.0]);
+        assert_eq!(store.vectors[1], vec![0.5, 0.5]);
+
+        assert_eq!(backfill_vectors(&path, |_| unreachable!()).unwrap(), 0);
+    }
 }
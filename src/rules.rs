@@ -8,6 +8,9 @@
 //!  2. **Tier 2 — Team**     `~/.cortexast/cluster/{team_cluster_id}_rules.yml`
 //!                           (team_cluster_id sourced from `.cortexast.json` in project root)
 //!  3. **Tier 3 — Project**  `{project_path}/.cortex_rules.yml`
+//!
+//! [`validate_rules`] walks the same tiers without swallowing anything and
+//! reports syntax errors, suspicious keys and which tier won each key.
 
 use anyhow::{Context, Result};
 use serde_json::{Map, Value};
@@ -197,6 +200,298 @@ pub fn get_merged_rules(project_path: &str, file_path_context: Option<&str>) ->
     Ok(merged)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Validation
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Keys the engine writes itself; a tier that sets them is silently overwritten.
+const RESERVED_KEYS: &[&str] = &["SYSTEM_OVERRIDE", "SYSTEM_OVERRIDE_CACHE", "status"];
+
+/// One rule tier as seen by [`validate_rules`].
+struct RuleTier {
+    label: String,
+    path: std::path::PathBuf,
+    /// Why the tier is not consulted at all (e.g. `enable_sync=false`).
+    skipped: Option<String>,
+}
+
+/// A tier after parsing: either its top-level value or a located syntax error.
+enum ParsedTier {
+    Missing,
+    Skipped(String),
+    Invalid(String),
+    Loaded(Value),
+}
+
+/// Validate every rule tier for `project_path` and explain how the merge resolves.
+///
+/// Unlike [`get_merged_rules`], nothing is swallowed: the report lists each
+/// tier's file and status, YAML syntax errors with line/column, key warnings
+/// (engine-reserved keys, spelling variants of the same key across tiers that
+/// will not merge, type changes that replace instead of union) and, per key,
+/// which tier won. Returns Markdown.
+pub fn validate_rules(project_path: &str) -> String {
+    let project_dir = Path::new(project_path);
+    let mut tiers = vec![RuleTier {
+        label: "global".to_string(),
+        path: global_rules_path(),
+        skipped: None,
+    }];
+
+    let config_path = project_dir.join(".cortexast.json");
+    let (enable_sync, team_cluster_id) = if config_path.exists() {
+        read_cortexast_json(&config_path)
+    } else {
+        (true, None)
+    };
+    match team_cluster_id {
+        Some(id) => tiers.push(RuleTier {
+            label: "team".to_string(),
+            path: cluster_rules_path(&id),
+            skipped: (!enable_sync).then(|| "enable_sync=false in .cortexast.json".to_string()),
+        }),
+        None => tiers.push(RuleTier {
+            label: "team".to_string(),
+            path: config_path,
+            skipped: Some("no rules_engine.team_cluster_id in .cortexast.json".to_string()),
+        }),
+    }
+
+    tiers.push(RuleTier {
+        label: "project".to_string(),
+        path: project_dir.join(".cortex_rules.yml"),
+        skipped: None,
+    });
+
+    render_validation(project_path, &tiers)
+}
+
+fn parse_tier(tier: &RuleTier) -> ParsedTier {
+    if let Some(reason) = &tier.skipped {
+        return ParsedTier::Skipped(reason.clone());
+    }
+    let content = match std::fs::read_to_string(&tier.path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return ParsedTier::Missing,
+        Err(e) => return ParsedTier::Invalid(format!("unreadable: {e}")),
+    };
+    match serde_yaml::from_str::<serde_yaml::Value>(&content) {
+        Ok(yaml_val) => match serde_json::to_value(&yaml_val) {
+            Ok(v) => ParsedTier::Loaded(v),
+            Err(e) => ParsedTier::Invalid(format!("not representable as JSON: {e}")),
+        },
+        Err(e) => {
+            let mut msg = e.to_string();
+            match e.location() {
+                Some(loc) => {
+                    // serde_yaml appends " at line L column C"; we lead with it instead.
+                    if let Some(idx) = msg.find(" at line ") {
+                        msg.truncate(idx);
+                    }
+                    ParsedTier::Invalid(format!(
+                        "line {}, column {}: {msg}",
+                        loc.line(),
+                        loc.column()
+                    ))
+                }
+                None => ParsedTier::Invalid(msg),
+            }
+        }
+    }
+}
+
+fn json_kind(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "mapping",
+    }
+}
+
+/// Fold `_`, `-` and case so `banned-tools`, `bannedTools` and `banned_tools` compare equal.
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Flatten nested mappings into dotted leaf paths (lists and scalars are leaves).
+fn flatten_into(prefix: &str, v: &Value, out: &mut Vec<(String, Value)>) {
+    match v {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, child) in map {
+                let path = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten_into(&path, child, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), v.clone())),
+    }
+}
+
+fn render_validation(project_path: &str, tiers: &[RuleTier]) -> String {
+    let mut out = format!(
+        "## Rules validation for `{project_path}`\n\
+         **Tier resolution:** Global → Team → Project (project wins)\n\n\
+         ### Tiers\n"
+    );
+
+    let mut loaded: Vec<(&str, Value)> = Vec::new();
+    let mut problems = 0usize;
+    for tier in tiers {
+        let path = tier.path.display();
+        match parse_tier(tier) {
+            ParsedTier::Missing => {
+                out.push_str(&format!("- {}: not found (`{path}`)\n", tier.label))
+            }
+            ParsedTier::Skipped(reason) => {
+                out.push_str(&format!("- {}: skipped — {reason}\n", tier.label))
+            }
+            ParsedTier::Invalid(msg) => {
+                problems += 1;
+                out.push_str(&format!(
+                    "- {}: **error** in `{path}` — {msg} (tier ignored)\n",
+                    tier.label
+                ));
+            }
+            ParsedTier::Loaded(v) => {
+                let detail = match &v {
+                    Value::Object(map) => format!("{} top-level keys", map.len()),
+                    Value::Null => "empty".to_string(),
+                    other => {
+                        problems += 1;
+                        format!(
+                            "**top level is a {}, not a mapping** — it replaces every lower tier",
+                            json_kind(other)
+                        )
+                    }
+                };
+                out.push_str(&format!("- {}: ok (`{path}`, {detail})\n", tier.label));
+                loaded.push((tier.label.as_str(), v));
+            }
+        }
+    }
+
+    // ── Key warnings ─────────────────────────────────────────────────────────
+    let mut warnings: Vec<String> = Vec::new();
+    let mut spellings: std::collections::BTreeMap<String, Vec<(String, &str)>> =
+        std::collections::BTreeMap::new();
+    let mut kinds: std::collections::BTreeMap<&str, Vec<(&str, &'static str)>> =
+        std::collections::BTreeMap::new();
+    for (label, v) in &loaded {
+        let Some(map) = v.as_object() else { continue };
+        for (key, val) in map {
+            if RESERVED_KEYS.contains(&key.as_str()) {
+                warnings.push(format!(
+                    "`{key}` ({label}): reserved — the engine overwrites it, so this value never applies"
+                ));
+            }
+            let variants = spellings.entry(normalize_key(key)).or_default();
+            if !variants.iter().any(|(k, _)| k == key) {
+                variants.push((key.clone(), *label));
+            }
+            kinds
+                .entry(key.as_str())
+                .or_default()
+                .push((*label, json_kind(val)));
+        }
+    }
+    for variants in spellings.values().filter(|v| v.len() > 1) {
+        let listed: Vec<String> = variants
+            .iter()
+            .map(|(k, label)| format!("`{k}` ({label})"))
+            .collect();
+        warnings.push(format!(
+            "unknown spelling: {} look like the same rule but are merged as separate keys",
+            listed.join(", ")
+        ));
+    }
+    for (key, per_tier) in &kinds {
+        let first = per_tier[0].1;
+        if per_tier.iter().any(|(_, k)| *k != first) {
+            let listed: Vec<String> = per_tier
+                .iter()
+                .map(|(label, k)| format!("{k} in {label}"))
+                .collect();
+            warnings.push(format!(
+                "`{key}` changes type ({}) — the higher tier replaces it instead of merging",
+                listed.join(", ")
+            ));
+        }
+    }
+    problems += warnings.len();
+
+    out.push_str("\n### Key warnings\n");
+    if warnings.is_empty() {
+        out.push_str("- none\n");
+    } else {
+        for w in &warnings {
+            out.push_str(&format!("- {w}\n"));
+        }
+    }
+
+    // ── Per-key resolution ───────────────────────────────────────────────────
+    let mut merged = Value::Object(Map::new());
+    let mut setters: std::collections::BTreeMap<String, Vec<(&str, Value)>> =
+        std::collections::BTreeMap::new();
+    for (label, v) in &loaded {
+        deep_merge(&mut merged, v.clone());
+        let mut leaves = Vec::new();
+        flatten_into("", v, &mut leaves);
+        for (path, val) in leaves {
+            if !path.is_empty() {
+                setters.entry(path).or_default().push((*label, val));
+            }
+        }
+    }
+    let mut final_leaves = Vec::new();
+    flatten_into("", &merged, &mut final_leaves);
+
+    out.push_str("\n### Resolution\n");
+    if final_leaves.iter().all(|(path, _)| path.is_empty()) {
+        out.push_str("- no keys (no tier contributed rules)\n");
+    }
+    for (path, val) in final_leaves.iter().filter(|(p, _)| !p.is_empty()) {
+        let Some(sources) = setters.get(path) else {
+            continue;
+        };
+        let labels: Vec<&str> = sources.iter().map(|(label, _)| *label).collect();
+        let how = if val.is_array() && sources.iter().filter(|(_, v)| v.is_array()).count() > 1 {
+            format!("union of {}", labels.join(" + "))
+        } else {
+            let winner = labels[labels.len() - 1];
+            let overridden: Vec<&str> = sources[..sources.len() - 1]
+                .iter()
+                .filter(|(_, v)| v != val)
+                .map(|(label, _)| *label)
+                .collect();
+            if overridden.is_empty() {
+                winner.to_string()
+            } else {
+                format!("{winner} (overrides {})", overridden.join(", "))
+            }
+        };
+        out.push_str(&format!("- `{path}` = `{val}` ← {how}\n"));
+    }
+
+    out.push_str(&format!(
+        "\n**Result:** {}\n",
+        if problems == 0 {
+            "no problems found.".to_string()
+        } else {
+            format!("{problems} problem(s) found.")
+        }
+    ));
+    out
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Helpers
 // ─────────────────────────────────────────────────────────────────────────────
@@ -463,4 +758,111 @@ mod tests {
             "Must return sentinel when no rule files exist");
         drop(merged);
     }
+
+    // ── validate_rules: report rendering ─────────────────────────────────────
+
+    fn tier(label: &str, path: std::path::PathBuf) -> RuleTier {
+        RuleTier {
+            label: label.to_string(),
+            path,
+            skipped: None,
+        }
+    }
+
+    #[test]
+    fn validate_reports_syntax_error_with_line() {
+        let tmp = TempDir::new().unwrap();
+        let good = write_yaml(tmp.path(), "global_rules.yml", "persona: verbose\n");
+        let bad = write_yaml(
+            tmp.path(),
+            ".cortex_rules.yml",
+            "persona: silent\nvision_model: mlx: fast\n",
+        );
+
+        let report = render_validation("proj", &[tier("global", good), tier("project", bad)]);
+        println!("{report}");
+        assert!(report.contains("- global: ok"), "{report}");
+        assert!(report.contains("- project: **error**"), "{report}");
+        assert!(
+            report.contains("line 2"),
+            "error must carry a line number: {report}"
+        );
+        assert!(
+            report.contains("`persona` = `\"verbose\"` ← global"),
+            "invalid tier must not take part in the merge: {report}"
+        );
+    }
+
+    #[test]
+    fn validate_shows_winner_union_and_key_warnings() {
+        let tmp = TempDir::new().unwrap();
+        let global = write_yaml(
+            tmp.path(),
+            "global_rules.yml",
+            "persona: verbose\nbanned_tools:\n  - rm\nrequire_tests: true\n",
+        );
+        let team = write_yaml(
+            tmp.path(),
+            "alpha_rules.yml",
+            "banned_tools:\n  - git push\nrequire_tests: [unit]\n",
+        );
+        let project = write_yaml(
+            tmp.path(),
+            ".cortex_rules.yml",
+            "persona: silent\nbanned-tools:\n  - curl\nstatus: draft\n",
+        );
+        let mut skipped = tier("team2", tmp.path().join("missing.yml"));
+        skipped.skipped = Some("enable_sync=false in .cortexast.json".to_string());
+
+        let report = render_validation(
+            "proj",
+            &[
+                tier("global", global),
+                tier("team", team),
+                skipped,
+                tier("project", project),
+            ],
+        );
+        println!("{report}");
+        assert!(
+            report.contains("- team2: skipped — enable_sync=false"),
+            "{report}"
+        );
+        assert!(
+            report.contains("`persona` = `\"silent\"` ← project (overrides global)"),
+            "{report}"
+        );
+        assert!(report.contains("← union of global + team"), "{report}");
+        assert!(report.contains("`status` (project): reserved"), "{report}");
+        assert!(
+            report.contains("`banned-tools` (project)")
+                && report.contains("`banned_tools` (global)"),
+            "spelling variants must be flagged: {report}"
+        );
+        assert!(
+            report.contains("`require_tests` changes type (bool in global, list in team)"),
+            "{report}"
+        );
+        assert!(report.contains("problem(s) found"), "{report}");
+    }
+
+    #[test]
+    fn validate_clean_rules_report_no_problems() {
+        let tmp = TempDir::new().unwrap();
+        let project = write_yaml(tmp.path(), ".cortex_rules.yml", "db:\n  engine: postgres\n");
+        let report = render_validation(
+            "proj",
+            &[
+                tier("global", tmp.path().join("absent.yml")),
+                tier("project", project),
+            ],
+        );
+        println!("{report}");
+        assert!(report.contains("- global: not found"), "{report}");
+        assert!(
+            report.contains("`db.engine` = `\"postgres\"` ← project"),
+            "{report}"
+        );
+        assert!(report.contains("no problems found"), "{report}");
+    }
 }
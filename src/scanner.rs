@@ -1,10 +1,11 @@
 use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
-use crate::config::ABSOLUTE_MAX_FILE_BYTES;
+use crate::config::{IncludeTests, ABSOLUTE_MAX_FILE_BYTES};
 
 fn repomix_default_overrides(repo_root: &Path, exclude_dir_names: &[String]) -> Result<Override> {
     let mut ob = OverrideBuilder::new(repo_root);
@@ -120,6 +121,150 @@ fn repomix_default_overrides(repo_root: &Path, exclude_dir_names: &[String]) ->
     Ok(ob.build()?)
 }
 
+/// Why `.gitattributes` keeps a file out of slices and maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinguistKind {
+    Generated,
+    Vendored,
+}
+
+impl LinguistKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LinguistKind::Generated => "generated",
+            LinguistKind::Vendored => "vendored",
+        }
+    }
+}
+
+/// `linguist-generated` / `linguist-vendored` hints read from `.gitattributes`.
+///
+/// Files are collected from every directory between the walk root and the repo root;
+/// a deeper file overrides a shallower one, and within a file the last matching line
+/// wins (`-attr`, `!attr` and `attr=false` clear a hint). Nested `.gitattributes`
+/// below the walk root are not consulted.
+#[derive(Debug, Clone, Default)]
+pub struct LinguistAttributes {
+    /// Deepest directory first.
+    layers: Vec<LinguistLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct LinguistLayer {
+    dir: PathBuf,
+    generated: Gitignore,
+    vendored: Gitignore,
+}
+
+impl LinguistAttributes {
+    /// Load `.gitattributes` from `start` and each ancestor up to and including `root`.
+    /// When `start` is not under `root`, only `start` itself is consulted.
+    pub fn load(root: &Path, start: &Path) -> Self {
+        let mut layers = Vec::new();
+        for dir in start.ancestors() {
+            if let Some(layer) = LinguistLayer::read(dir) {
+                layers.push(layer);
+            }
+            if dir == root || !dir.starts_with(root) {
+                break;
+            }
+        }
+        Self { layers }
+    }
+
+    /// Like [`LinguistAttributes::load`], using the nearest ancestor of `start` that
+    /// contains `.git` as the root (or `start` alone outside a git checkout).
+    pub fn discover(start: &Path) -> Self {
+        let root = start
+            .ancestors()
+            .find(|d| d.join(".git").exists())
+            .unwrap_or(start);
+        Self::load(root, start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Classify an absolute file path; `None` when neither hint is set.
+    pub fn classify(&self, abs_path: &Path) -> Option<LinguistKind> {
+        if self.hint(abs_path, LinguistKind::Generated) {
+            Some(LinguistKind::Generated)
+        } else if self.hint(abs_path, LinguistKind::Vendored) {
+            Some(LinguistKind::Vendored)
+        } else {
+            None
+        }
+    }
+
+    fn hint(&self, abs_path: &Path, kind: LinguistKind) -> bool {
+        self.layers
+            .iter()
+            .filter(|l| abs_path.starts_with(&l.dir))
+            .map(|l| match kind {
+                LinguistKind::Generated => l.generated.matched(abs_path, false),
+                LinguistKind::Vendored => l.vendored.matched(abs_path, false),
+            })
+            .find(|m| !m.is_none())
+            .is_some_and(|m| m.is_ignore())
+    }
+}
+
+impl LinguistLayer {
+    fn read(dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(dir.join(".gitattributes")).ok()?;
+        let mut generated = GitignoreBuilder::new(dir);
+        let mut vendored = GitignoreBuilder::new(dir);
+        let mut any = false;
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            // Quoted patterns and git's negative-pattern syntax are not supported.
+            if pattern.starts_with('"') || pattern.starts_with('!') {
+                continue;
+            }
+            for attr in parts {
+                let (name, set) = if let Some(n) = attr.strip_prefix('-') {
+                    (n, false)
+                } else if let Some(n) = attr.strip_prefix('!') {
+                    (n, false)
+                } else if let Some((n, v)) = attr.split_once('=') {
+                    (n, v != "false")
+                } else {
+                    (attr, true)
+                };
+                let builder = match name {
+                    "linguist-generated" => &mut generated,
+                    "linguist-vendored" => &mut vendored,
+                    _ => continue,
+                };
+                let glob = if set {
+                    pattern.to_string()
+                } else {
+                    format!("!{pattern}")
+                };
+                if builder.add_line(None, &glob).is_ok() {
+                    any = true;
+                }
+            }
+        }
+        if !any {
+            return None;
+        }
+        Some(Self {
+            dir: dir.to_path_buf(),
+            generated: generated.build().ok()?,
+            vendored: vendored.build().ok()?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub abs_path: PathBuf,
@@ -133,6 +278,15 @@ pub struct ScanOptions {
     pub target: PathBuf,
     pub max_file_bytes: u64,
     pub exclude_dir_names: Vec<String>,
+    /// Directories skipped by path, relative to `repo_root` (or absolute), e.g. the
+    /// configured `output_dir`, which is a path rather than a bare name.
+    pub exclude_dir_paths: Vec<PathBuf>,
+    /// Scan files marked `linguist-generated` / `linguist-vendored` like any other
+    /// instead of reporting them in [`ScanReport::linguist`].
+    pub include_linguist_generated: bool,
+    /// Globs classifying test files (see [`TestFilter`]).
+    pub test_patterns: Vec<String>,
+    pub include_tests: IncludeTests,
 }
 
 impl ScanOptions {
@@ -145,19 +299,89 @@ impl ScanOptions {
     }
 }
 
+/// Test-file classifier: `scan.test_patterns` plus an [`IncludeTests`] mode.
+///
+/// Patterns containing `/` are matched against the relative path, the rest against
+/// the file name, so `test_*.py` catches `pkg/test_api.py` without a `**/` prefix.
+#[derive(Debug, Clone, Default)]
+pub struct TestFilter {
+    path_patterns: Vec<glob::Pattern>,
+    name_patterns: Vec<glob::Pattern>,
+    mode: IncludeTests,
+}
+
+impl TestFilter {
+    pub fn new(patterns: &[String], mode: IncludeTests) -> Self {
+        let mut filter = Self {
+            mode,
+            ..Self::default()
+        };
+        for p in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let Ok(pat) = glob::Pattern::new(p) else {
+                continue;
+            };
+            if p.contains('/') {
+                filter.path_patterns.push(pat);
+            } else {
+                filter.name_patterns.push(pat);
+            }
+        }
+        filter
+    }
+
+    pub fn mode(&self) -> IncludeTests {
+        self.mode
+    }
+
+    /// Whether `rel_path` (forward slashes) is a test file.
+    pub fn is_test(&self, rel_path: &str) -> bool {
+        let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        self.name_patterns.iter().any(|p| p.matches(name))
+            || self.path_patterns.iter().any(|p| p.matches(rel_path))
+    }
+
+    /// Whether a scan in this mode keeps `rel_path`.
+    pub fn keeps(&self, rel_path: &str) -> bool {
+        match self.mode {
+            IncludeTests::All => true,
+            IncludeTests::Exclude => !self.is_test(rel_path),
+            IncludeTests::Only => self.is_test(rel_path),
+        }
+    }
+}
+
+/// Result of a workspace scan that also reports files dropped for exceeding
+/// `max_file_bytes`, so callers can surface them instead of leaving silent holes.
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    pub entries: Vec<FileEntry>,
+    pub oversized: Vec<FileEntry>,
+    /// Files flagged generated/vendored by `.gitattributes`: listed, never read.
+    pub linguist: Vec<(FileEntry, LinguistKind)>,
+}
+
 pub fn scan_workspace(opts: &ScanOptions) -> Result<Vec<FileEntry>> {
+    scan_workspace_report(opts).map(|r| r.entries)
+}
+
+pub fn scan_workspace_report(opts: &ScanOptions) -> Result<ScanReport> {
     let target_root = opts.target_root();
 
     let meta = std::fs::metadata(&target_root)
         .with_context(|| format!("Target does not exist: {}", target_root.display()))?;
 
     if meta.is_file() {
-        return scan_single_file(&opts.repo_root, &target_root, opts.max_file_bytes)
-            .map(|v| v.into_iter().collect());
+        return scan_single_file(&opts.repo_root, &target_root, opts.max_file_bytes);
     }
 
-    let mut entries = Vec::new();
+    let mut report = ScanReport::default();
     let overrides = repomix_default_overrides(&opts.repo_root, &opts.exclude_dir_names)?;
+    let tests = TestFilter::new(&opts.test_patterns, opts.include_tests);
+    let linguist = if opts.include_linguist_generated {
+        LinguistAttributes::default()
+    } else {
+        LinguistAttributes::load(&opts.repo_root, &target_root)
+    };
 
     // Hard exclude by directory component name. This is intentionally redundant with overrides,
     // because overrides alone are easy to misconfigure and we must never descend into heavy dirs
@@ -170,17 +394,26 @@ pub fn scan_workspace(opts: &ScanOptions) -> Result<Vec<FileEntry>> {
         }
     }
 
+    let excluded_dir_paths: Vec<PathBuf> = opts
+        .exclude_dir_paths
+        .iter()
+        .map(|p| opts.repo_root.join(p))
+        .collect();
+
     let walker = WalkBuilder::new(&target_root)
         .standard_filters(true) // .gitignore, .ignore, hidden, etc.
         .overrides(overrides)
         .filter_entry(move |dent| {
-            // Skip excluded directories by name (prevents descending).
+            // Skip excluded directories by name or path (prevents descending).
             if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
                 if let Some(name) = dent.path().file_name().and_then(|s| s.to_str()) {
                     if excluded_dir_names.contains(name) {
                         return false;
                     }
                 }
+                if excluded_dir_paths.iter().any(|p| dent.path() == p) {
+                    return false;
+                }
             }
             true
         })
@@ -203,32 +436,48 @@ pub fn scan_workspace(opts: &ScanOptions) -> Result<Vec<FileEntry>> {
             Err(_) => continue,
         };
 
-        // Hard absolute cap — always skip before any config override can raise it.
-        if bytes > ABSOLUTE_MAX_FILE_BYTES {
-            crate::debug_log!(
-                "[cortexast] skipping large file ({}): {}",
-                humanize_bytes(bytes),
-                abs_path.display()
-            );
-            continue;
-        }
-
-        if bytes == 0 || bytes > opts.max_file_bytes {
+        if bytes == 0 {
             continue;
         }
 
         let rel_path = path_relative_to(&abs_path, &opts.repo_root)
             .with_context(|| format!("Failed to relativize path: {}", abs_path.display()))?;
 
-        entries.push(FileEntry {
+        if !tests.keeps(&rel_path.to_string_lossy().replace('\\', "/")) {
+            continue;
+        }
+
+        let entry = FileEntry {
             abs_path,
             rel_path,
             bytes,
-        });
+        };
+
+        if let Some(kind) = linguist.classify(&entry.abs_path) {
+            report.linguist.push((entry, kind));
+            continue;
+        }
+
+        // Hard absolute cap — always skip before any config override can raise it.
+        if bytes > ABSOLUTE_MAX_FILE_BYTES || bytes > opts.max_file_bytes {
+            crate::debug_log!(
+                "[cortexast] skipping large file ({}): {}",
+                humanize_bytes(bytes),
+                entry.abs_path.display()
+            );
+            report.oversized.push(entry);
+            continue;
+        }
+
+        report.entries.push(entry);
     }
 
-    entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
-    Ok(entries)
+    report.entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    report.oversized.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    report
+        .linguist
+        .sort_by(|a, b| a.0.rel_path.cmp(&b.0.rel_path));
+    Ok(report)
 }
 
 #[cfg(debug_assertions)]
@@ -242,37 +491,41 @@ fn humanize_bytes(bytes: u64) -> String {
     }
 }
 
-fn scan_single_file(
-    repo_root: &Path,
-    abs_path: &Path,
-    max_file_bytes: u64,
-) -> Result<Vec<FileEntry>> {
+fn scan_single_file(repo_root: &Path, abs_path: &Path, max_file_bytes: u64) -> Result<ScanReport> {
     // Apply the same default overrides for consistency.
     let ov = repomix_default_overrides(repo_root, &[])?;
 
     let rel_path = path_relative_to(abs_path, repo_root)?;
     if ov.matched(&rel_path, /* is_dir */ false).is_ignore() {
-        return Ok(vec![]);
+        return Ok(ScanReport::default());
     }
 
     let bytes = std::fs::metadata(abs_path)?.len();
-    if bytes > ABSOLUTE_MAX_FILE_BYTES {
+    if bytes == 0 {
+        return Ok(ScanReport::default());
+    }
+
+    let entry = FileEntry {
+        abs_path: abs_path.to_path_buf(),
+        rel_path,
+        bytes,
+    };
+    if bytes > ABSOLUTE_MAX_FILE_BYTES || bytes > max_file_bytes {
         crate::debug_log!(
             "[cortexast] skipping large file ({}): {}",
             humanize_bytes(bytes),
             abs_path.display()
         );
-        return Ok(vec![]);
-    }
-    if bytes == 0 || bytes > max_file_bytes {
-        return Ok(vec![]);
+        return Ok(ScanReport {
+            oversized: vec![entry],
+            ..Default::default()
+        });
     }
 
-    Ok(vec![FileEntry {
-        abs_path: abs_path.to_path_buf(),
-        rel_path,
-        bytes,
-    }])
+    Ok(ScanReport {
+        entries: vec![entry],
+        ..Default::default()
+    })
 }
 
 fn path_relative_to(path: &Path, base: &Path) -> Result<PathBuf> {
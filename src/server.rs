@@ -4,18 +4,31 @@ use serde_json::json;
 use std::io::{BufRead, Write};
 use std::path::PathBuf;
 
-use crate::chronos::{checkpoint_symbol, compare_symbol, list_checkpoints};
-use crate::config::load_config;
+use crate::chronos::{checkpoint_symbol, compare_symbol, compare_symbols, list_checkpoints};
+use crate::config::{load_config, IncludeTests};
 use crate::inspector::{
-    call_hierarchy, extract_symbols_from_source, find_implementations, find_usages,
-    propagation_checklist, read_symbol_with_options, render_skeleton, repo_map_with_filter,
-    run_diagnostics,
+    analyze_source, apply_language_overrides, apply_parse_limits, call_hierarchy_with_options,
+    definition_candidate_files, expand_symbol_types, extract_symbols_from_source,
+    find_by_attribute, find_definition_sites, find_implementations, find_symbol_tests,
+    find_usages_with_sort, format_attribute_matches, format_repo_summary,
+    format_symbol_dependencies, format_symbol_matches, format_symbols_by_kind,
+    format_usage_counts, imports_exports, list_symbols_by_kind, propagation_checklist,
+    read_symbol_at_line, read_symbol_with_options, read_symbols_within_budget, reference_matrix,
+    render_skeleton, render_skeleton_window, repo_map_with_filter, repo_summary,
+    run_diagnostics, search_symbols_fuzzy, signatures_dump, symbol_dependencies, usage_counts,
+    CallNoise, ChecklistFormat, DiagnosticsFormat, GitignoreMode, PathStyle, RepoMapOptions,
+    UsageSort, SIGNATURES_CHARS_PER_TOKEN,
 };
-use crate::memory::{hybrid_search, MemoryStore};
-use crate::rules::get_merged_rules;
-use crate::scanner::{scan_workspace, ScanOptions};
-use crate::slicer::{slice_paths_to_xml, slice_to_xml};
-use crate::vector_store::{CodebaseIndex, IndexJob};
+use crate::mapper::{centrality, format_centrality};
+use crate::memory::{hybrid_search, MemoryEntry, MemoryStore, MEMORY_EMBED_MODEL};
+use crate::rules::{get_merged_rules, validate_rules};
+use crate::scanner::{scan_workspace, ScanOptions, TestFilter};
+use crate::slicer::{
+    estimate_tokens_from_bytes, expand_glob_target, format_slice_plan, is_glob_target,
+    slice_around_symbol, slice_paths_to_xml, slice_plan, slice_to_files, slice_to_xml, SliceFiles,
+};
+use crate::vector_store::{model_db_dir, CodebaseIndex, IndexJob, SymbolIndex};
+use crate::xml_builder::build_context_with_budget;
 use rayon::prelude::*;
 
 #[derive(Default)]
@@ -28,8 +41,18 @@ pub struct ServerState {
     ///   5. Find-up heuristic on tool args (`path` / `target_dir` / `target`).
     ///   6. `cwd` — last resort; refused if it equals $HOME or OS root.
     repo_root: Option<PathBuf>,
+    /// Root fixed by sources 2–4 above (startup / `initialize`). Per-call `repoPath`
+    /// never moves it, so the tool exposure policy read from it can't be sidestepped.
+    workspace_root: Option<PathBuf>,
+    /// Memory embedding model, loaded on the first `cortex_memory_retriever` call.
+    memory_model: Option<StaticModel>,
+    /// Query → vector for recent memory retrievals (cleared when it grows past
+    /// `MEMORY_QUERY_CACHE_MAX`).
+    memory_query_vectors: std::collections::HashMap<String, Vec<f32>>,
 }
 
+const MEMORY_QUERY_CACHE_MAX: usize = 256;
+
 /// Returns `true` for "useless" roots that indicate the server started with the
 /// wrong cwd (usually $HOME or filesystem root on any OS).
 fn is_dead_root(p: &std::path::Path) -> bool {
@@ -145,6 +168,7 @@ impl ServerState {
         // The protocol root is authoritative — overwrite any earlier bootstrap
         // value (env vars / --root) so the editor's own answer always wins.
         if let Some(r) = root {
+            self.workspace_root = Some(r.clone());
             self.repo_root = Some(r);
         }
     }
@@ -291,7 +315,48 @@ impl ServerState {
         Ok(base_root)
     }
 
+    /// Root whose `.cortexast.json` sets the tool exposure policy: the server's
+    /// workspace root (CLI / initialize / env), or cwd before one is known.
+    fn policy_root(&self) -> PathBuf {
+        self.workspace_root
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Repo whose `token_estimator` sizes the inline cap: `repoPath` when given, else
+    /// the current repo root, else [`Self::policy_root`]. Unlike
+    /// [`Self::repo_root_from_params`] it caches nothing.
+    fn cap_config_root(&self, args: &serde_json::Value) -> PathBuf {
+        args.get("repoPath")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .or_else(|| self.repo_root.clone())
+            .unwrap_or_else(|| self.policy_root())
+    }
+
+    /// `tools/list`, minus tools switched off by `mcp.disabled_tools` / `mcp.enabled_tools`.
     fn tool_list(&self, id: serde_json::Value) -> serde_json::Value {
+        let policy = load_config(&self.policy_root()).mcp;
+        let mut resp = self.all_tools(id);
+        if let Some(tools) = resp["result"]["tools"].as_array_mut() {
+            tools.retain(|t| {
+                t["name"]
+                    .as_str()
+                    .is_some_and(|n| policy.is_tool_enabled(n))
+            });
+        }
+        resp
+    }
+
+    /// True for tools advertised in `tools/list` (legacy aliases are not).
+    fn is_listed_tool(&self, name: &str) -> bool {
+        self.all_tools(json!(null))["result"]["tools"]
+            .as_array()
+            .is_some_and(|tools| tools.iter().any(|t| t["name"] == name))
+    }
+
+    fn all_tools(&self, id: serde_json::Value) -> serde_json::Value {
         json!({
             "jsonrpc": "2.0",
             "id": id,
@@ -299,82 +364,122 @@ impl ServerState {
                 "tools": [
                     {
                         "name": "cortex_code_explorer",
-                        "description": "Codebase explorer. Use INSTEAD of ls/tree/find/cat. Two modes: `map_overview` (fast symbol map, near-zero tokens — run first on any repo) and `deep_slice` (token-budgeted XML with function bodies, vector-ranked by query). Use map_overview to orient; deep_slice to get code for editing.",
+                        "description": "Codebase explorer. Use INSTEAD of ls/tree/find/cat. Modes: `repo_summary` (one-screen profile: languages, LOC, symbol kinds, top-level dirs, monorepo or not), `map_overview` (fast symbol map, near-zero tokens — run first on any repo), `signatures` (every public signature in the repo, centrality-ranked and token-budgeted, no bodies), `deep_slice` (token-budgeted XML with function bodies, vector-ranked by query), `plan` (dry run of deep_slice: per-file mode and token cost, no content), `similar_files` (files semantically closest to a given file), `centrality` (most depended-upon files by import in-degree and PageRank), `reference_matrix` (JSON of how often each file references symbols defined in each other file — symbol-level coupling the import graph misses) and `context_pack` (repo map + query-ranked slices + compiler diagnostics in one budget-capped document). Use repo_summary on first contact; map_overview to orient; centrality to find the entry points worth reading first; signatures to onboard onto a whole service; plan to size a budget before a big deep_slice; deep_slice to get code for editing; context_pack for a one-shot prompt.",
                         "inputSchema": {
                             "type": "object",
                             "properties": {
                                 "action": {
                                     "type": "string",
-                                    "enum": ["map_overview", "deep_slice"],
-                                    "description": "map_overview: bird's-eye symbol map of a dir (requires target_dir='.'). deep_slice: token-budgeted XML with bodies (requires target file/dir; use single_file=true for a specific file, query for semantic ranking)."
+                                    "enum": ["repo_summary", "map_overview", "signatures", "deep_slice", "plan", "similar_files", "centrality", "reference_matrix", "context_pack"],
+                                    "description": "repo_summary: languages with files and lines per driver, total LOC, symbols by kind, top-level dirs, root manifest and workspace members (target_dir optional, default '.'). map_overview: bird's-eye symbol map of a dir (requires target_dir='.'). signatures: public signatures grouped by file, most central files first, until budget_tokens is hit (target_dir optional, default '.'; the default max_chars fits the whole budget). deep_slice: token-budgeted XML with bodies (requires target file/dir; use single_file=true for a specific file, query for semantic ranking). plan: what deep_slice of target (without query) would do per file — full/compacted/skeleton/head/parse_failed/too_large/generated/vendored, included/truncated/dropped/skipped, estimated tokens — without emitting content (requires target). similar_files: nearest-neighbour files by embedding (requires path). centrality: files ranked by how many in-repo files import them, with out-degree and PageRank (target_dir optional, default '.'; limit default 20). reference_matrix: JSON {from_file: {to_file: count}} of identifiers in each file that resolve to a symbol declared in exactly one other file, paths relative to target_dir — for coupling heatmaps (target_dir optional, default '.'). context_pack: one budget-capped document = repo map + query-ranked slices + compiler diagnostics (target optional, default '.'; query recommended; the default max_chars fits the whole budget)."
                                 },
                                 "repoPath": { "type": "string", "description": "Abs path to repo root. Default: cwd." },
                                 "target_project": { "type": "string", "description": "Cross-project: ID or abs path from network map. Overrides repoPath." },
-                                "target_dir": { "type": "string", "description": "(map_overview) Dir to map. Use '.' for repo root." },
+                                "target_dir": { "type": "string", "description": "(repo_summary, map_overview, signatures, centrality, reference_matrix) Dir to map. Use '.' for repo root." },
                                 "search_filter": { "type": "string", "description": "(map_overview) Case-insensitive substring filter. OR via 'foo|bar'." },
-                                "max_chars": { "type": "integer", "description": "Max output chars. Default 8000." },
+                                "max_chars": { "type": "integer", "description": "Max output chars. Default 8000; for signatures and context_pack, enough for budget_tokens." },
+                                "max_tokens": { "type": "integer", "description": "Cap output by estimated tokens (token_estimator.chars_per_token) instead of chars. Overrides max_chars." },
                                 "ignore_gitignore": { "type": "boolean", "description": "(map_overview) Include git-ignored files." },
+                                "gitignore_mode": { "type": "string", "enum": ["respect", "exclude_vcs", "off"], "description": "(map_overview) respect (default) | exclude_vcs: ignore .gitignore but still skip .git + config exclude_dir_names | off: same as ignore_gitignore=true." },
                                 "exclude": { "type": "array", "items": { "type": "string" }, "description": "Dir names to skip (e.g. ['node_modules','build'])." },
-                                "target": { "type": "string", "description": "(deep_slice) Relative path to file or dir." },
-                                "budget_tokens": { "type": "integer", "exclusiveMinimum": 0, "description": "(deep_slice) Token budget. Default 32000." },
-                                "skeleton_only": { "type": "boolean", "description": "(deep_slice) Strip function bodies, return signatures only." },
-                                "query": { "type": "string", "description": "(deep_slice) Semantic query for vector-ranked file selection." },
+                                "flag_complexity": { "type": "integer", "exclusiveMinimum": 0, "description": "(map_overview) Mark functions whose cyclomatic complexity is >= this (e.g. 10) with '⚠ complexity N'. Symbol-level listings only." },
+                                "include_tests": { "type": "string", "enum": ["all", "exclude", "only"], "description": "(map_overview, deep_slice, plan) Test files, as classified by config scan.test_patterns: all (default), exclude (feature work) or only (test writing). Default: config scan.include_tests." },
+                                "max_depth": { "type": "integer", "exclusiveMinimum": 0, "description": "(map_overview) Only map files up to this many levels below target_dir (1 = direct children). Use 2-3 on deep trees to keep symbols visible instead of folders-only mode." },
+                                "target": { "type": "string", "description": "(deep_slice, plan, context_pack) Relative path to file or dir. deep_slice also takes a glob such as 'src/**/*_controller.rs' ('*' within one dir, '**' across dirs; ignore rules apply): its matches are sliced like 'paths' under one budget, and a glob matching nothing is an error." },
+                                "budget_tokens": { "type": "integer", "exclusiveMinimum": 0, "description": "(deep_slice, plan, signatures, context_pack) Token budget. Default 32000." },
+                                "skeleton_only": { "type": "boolean", "description": "(deep_slice, plan) Strip function bodies, return signatures only." },
+                                "api_only": { "type": "boolean", "description": "(deep_slice, plan) Public contract only: skeleton_only plus dropping private / unexported declarations (Rust non-`pub`, TS/JS unexported, Go lower-case, Python `_`-prefixed). Tightest 'how do I use this module' view." },
+                                "max_file_bytes": { "type": "integer", "exclusiveMinimum": 0, "description": "(deep_slice, plan) Per-call file-size cap. Larger files appear as <file skipped=\"too-large\" bytes=\"N\"/> stubs. Default: config token_estimator.max_file_bytes (512 KB)." },
+                                "query": { "type": "string", "description": "(deep_slice, context_pack) Semantic query for vector-ranked file selection." },
+                                "diagnostics": { "type": "boolean", "description": "(context_pack) Include compiler diagnostics. Default true." },
                                 "query_limit": { "type": "integer", "description": "(deep_slice) Max files returned in query mode." },
+                                "embed_model": { "type": "string", "description": "(deep_slice) Embedding model for this query (Model2Vec id, e.g. 'minishlab/potion-base-8M'). Each model keeps a separate index, built on first use. Must be vector_search.model or listed in vector_search.allowed_models. Default: config vector_search.model." },
+                                "min_score": { "type": "number", "minimum": 0, "maximum": 1, "description": "(deep_slice) Drop query results with cosine similarity below this (exact symbol hits always pass). If nothing passes, the plain target slice is returned. Default: config vector_search.min_score (0 = off)." },
                                 "single_file": { "type": "boolean", "description": "(deep_slice) Skip vector search; return only the exact target file." },
-                                "only_dir": { "type": "string", "description": "(deep_slice) Restrict semantic search to this subdir only." }
+                                "paths": { "type": "array", "items": { "type": "string" }, "description": "(deep_slice) Exact repo-relative files to slice, in this order, under the shared budget_tokens. Skips directory walking and vector search; 'target' is then optional. Not combinable with query, focus_symbol, focus_lines or output='files'." },
+                                "output": { "type": "string", "enum": ["xml", "files"], "description": "(deep_slice) 'xml' (default): one XML document. 'files': a summary text block followed by one embedded resource block (file:// uri + text) per sliced file, same selection and budget. Plain target slices only (not with query or focus_symbol)." },
+                                "only_dir": { "type": "string", "description": "(deep_slice) Restrict semantic search to this subdir only." },
+                                "focus_lines": { "type": "string", "description": "(deep_slice) 'START-END' (1-based, inclusive) or a single line: skeleton of the whole target file with that line window kept verbatim (bodies overlapping it unpruned). Target must be a file; query is ignored." },
+                                "focus_symbol": { "type": "string", "description": "(deep_slice) Symbol in the target file to centre on: it comes back in full, its nearest sibling declarations as skeletons, the rest of the file as '... (lines A-B omitted)'. Target must be a file; query is ignored." },
+                                "context_symbols": { "type": "integer", "minimum": 0, "description": "(deep_slice with focus_symbol) Sibling declarations to keep on each side. Default 2." },
+                                "path": { "type": "string", "description": "(similar_files) File to find neighbours of." },
+                                "limit": { "type": "integer", "description": "(similar_files, centrality) Max results. Default 10 (similar_files), 20 (centrality)." }
                             },
                             "required": ["action"]
                         }
                     },
                     {
                         "name": "cortex_symbol_analyzer",
-                        "description": "AST symbol analysis. Use INSTEAD of grep/rg. Actions: read_source (extract exact source of a symbol from a file — do this before editing), find_usages (all call/type/field sites), find_implementations (structs implementing a trait), blast_radius (callers + callees — run before rename/delete), propagation_checklist (exhaustive update checklist for shared types).",
+                        "description": "AST symbol analysis. Use INSTEAD of grep/rg. Actions: read_source (extract exact source of a symbol from a file — do this before editing), find_definition (where a symbol is declared — instant when the symbol index is warm), search_symbols (declarations whose names look like a half-remembered one), list_symbols (every declaration of one kind, e.g. all traits), find_by_attribute (declarations carrying an attribute/decorator/annotation, e.g. all #[test] fns or @app.route handlers), find_usages (all call/type/field sites), find_implementations (structs implementing a trait), blast_radius (callers + callees — run before rename/delete), dependencies (what to read to understand a symbol), propagation_checklist (exhaustive update checklist for shared types), analyze_source (symbols/imports/exports of a code string you haven't written to disk yet), imports_exports (just the imports and exports of a file).",
                         "inputSchema": {
                             "type": "object",
                             "properties": {
                                 "action": {
                                     "type": "string",
-                                    "enum": ["read_source", "find_usages", "find_implementations", "blast_radius", "propagation_checklist"],
-                                    "description": "read_source: exact symbol body (needs path+symbol_name; use symbol_names[] for batch). find_usages: all call/type/field sites (needs symbol_name+target_dir). find_implementations: structs that impl a trait. blast_radius: full caller+callee hierarchy (run before rename/delete). propagation_checklist: Markdown checklist of all update sites for a shared type."
+                                    "enum": ["read_source", "find_definition", "search_symbols", "list_symbols", "find_by_attribute", "find_usages", "find_implementations", "blast_radius", "dependencies", "propagation_checklist", "analyze_source", "imports_exports"],
+                                    "description": "read_source: exact symbol body (needs path+symbol_name; use symbol_names[] for batch, or line=N for the declaration enclosing a line). find_definition: file:line + kind of every declaration of symbol_name (target_dir optional, default '.'). search_symbols: declarations whose names look like symbol_name — case/underscore-insensitive exact, prefix, substring, then small-typo matches, ranked, as kind + name + file:line (target_dir optional, default '.'); lexical over names, unlike deep_slice query. list_symbols: every declaration of the given kind (function, method, type, trait, const, module, or a raw driver kind like impl) as file-grouped L<line> kind name — signature, budget-capped (needs kind; target_dir optional, default '.'; budget_tokens default 8000). find_by_attribute: declarations whose attributes, decorators or annotations contain the attribute text (case-insensitive substring, e.g. '#[test]', 'route', 'Deprecated'), file-grouped as L<line> kind name — attribute, budget-capped (needs attribute; target_dir optional, default '.'; budget_tokens default 8000). find_usages: all call/type/field sites, with declaration sites listed apart under Definitions (needs symbol_name+target_dir; counts_only=true for per-file counts only; sort='relevance' puts the defining file, its module and high-centrality files first). find_implementations: structs that impl a trait. blast_radius: full caller+callee hierarchy (run before rename/delete), headed by a Low/Medium/High rename-risk label with reasons. dependencies: workspace functions/types the symbol uses, resolved to file:line, expanded up to depth hops (needs path+symbol_name). propagation_checklist: Markdown checklist of all update sites for a shared type. analyze_source: FileSymbols JSON (symbols with 0-indexed lines, imports, exports) for in-memory code (needs content+ext; no filesystem access). imports_exports: compact JSON {file, imports, exports} of one file (needs path) — cheapest way to see a module's dependencies and public surface."
                                 },
                                 "repoPath": { "type": "string", "description": "Abs path to repo root." },
                                 "target_project": { "type": "string", "description": "Cross-project: ID or abs path. Overrides repoPath." },
-                                "symbol_name": { "type": "string", "description": "Target symbol name (exact, no regex)." },
+                                "symbol_name": { "type": "string", "description": "Target symbol name (exact, no regex; search_symbols takes a partial or misspelled one). read_source also takes qualified names (Type::method, Class.method, module.func) to pick one of several same-name declarations." },
                                 "target_dir": { "type": "string", "description": "Scope dir ('.' = whole repo). Required for find_usages/blast_radius." },
                                 "ignore_gitignore": { "type": "boolean", "description": "(propagation_checklist) Include git-ignored files." },
+                                "gitignore_mode": { "type": "string", "enum": ["respect", "exclude_vcs", "off"], "description": "(find_usages) respect (default) | exclude_vcs: ignore .gitignore but still skip .git + config exclude_dir_names | off: no ignore rules." },
                                 "max_chars": { "type": "integer", "description": "Max output chars. Default 8000." },
+                                "max_tokens": { "type": "integer", "description": "Cap output by estimated tokens (token_estimator.chars_per_token) instead of chars. Overrides max_chars." },
                                 "only_dir": { "type": "string", "description": "(propagation_checklist) Restrict scan to this subdir." },
                                 "aliases": { "type": "array", "items": { "type": "string" }, "description": "(propagation_checklist) Alternative names across language boundaries." },
-                                "path": { "type": "string", "description": "(read_source) Source file. Required." },
+                                "path": { "type": "string", "description": "(read_source, dependencies, imports_exports) Source file. Required." },
                                 "symbol_names": { "type": "array", "items": { "type": "string" }, "description": "(read_source) Batch: extract multiple symbols from path." },
+                                "kind": { "type": "string", "description": "(list_symbols) Symbol kind: function, method, type, trait, const, module (plurals accepted), or a raw driver kind such as struct, enum or impl." },
+                                "attribute": { "type": "string", "description": "(find_by_attribute) Text to look for in attributes, decorators and annotations, matched case-insensitively as a substring (e.g. '#[get(', '@app.route', 'Deprecated')." },
+                                "budget_tokens": { "type": "integer", "exclusiveMinimum": 0, "description": "(list_symbols, find_by_attribute) Token cap for the listing, default 8000. (read_source with symbol_names) Token cap for the batch: symbols fill in request order, the first that doesn't fit is skeletonized or truncated at a line boundary, and the rest are listed as omitted. Default: no cap." },
                                 "skeleton_only": { "type": "boolean", "description": "(read_source) Return signatures only, strip bodies." },
                                 "instance_index": { "type": "integer", "description": "(read_source) 0-based index when symbol has multiple definitions in the file." },
+                                "line": { "type": "integer", "exclusiveMinimum": 0, "description": "(read_source) 1-based line number to use instead of symbol_name: returns the innermost declaration enclosing that line (e.g. from a stack trace or diagnostic). expand_types is not applied." },
+                                "depth": { "type": "integer", "minimum": 1, "description": "(dependencies) Hops to follow through resolved definitions. Default 1." },
+                                "counts_only": { "type": "boolean", "description": "(find_usages) Return only 'path: count' lines sorted by count (no context windows) — a cheap blast-radius estimate." },
+                                "sort": { "type": "string", "enum": ["path", "relevance"], "description": "(find_usages) Order within each category. path (default): by file path, then line — deterministic. relevance: the defining file first, then files sharing more of its directory path, ties broken by import-graph PageRank — surfaces the usages that matter when the list is long. Costs one extra import-graph pass." },
+                                "filter_noise": { "type": "boolean", "description": "(blast_radius) Drop stdlib/built-in method calls (clone, unwrap, map, push, …) from the outgoing call list. Default true; false lists every call." },
+                                "show_filtered": { "type": "boolean", "description": "(blast_radius) With filter_noise on, end the outgoing call list with how many stdlib/built-in calls were dropped and their names. Default false." },
+                                "absolute_paths": { "type": "boolean", "description": "(find_usages, find_implementations, blast_radius, propagation_checklist) Print absolute file paths instead of repo-relative ones. Default false." },
+                                "force_walk": { "type": "boolean", "description": "(find_definition) Skip the persistent symbol index and walk the AST (exhaustive, slower)." },
+                                "include_tests": { "type": "boolean", "description": "(read_source) Append skeletonized test functions whose name contains the symbol name (e.g. parse_config_works for parse_config): functions in scan.test_patterns files, under a #[test]-style attribute or @test decorator, or named test*. Up to 8 tests / ~4000 tokens." },
+                                "expand_types": { "type": "boolean", "description": "(read_source) Append skeletonized definitions of the types in the symbol's signature (same file first, then workspace; ~4000-token cap)." },
                                 "changed_path": { "type": "string", "description": "(propagation_checklist) Contract file path (e.g. .proto) — overrides symbol mode." },
-                                "max_symbols": { "type": "integer", "description": "(propagation_checklist) Max extracted symbols. Default 20." }
+                                "max_symbols": { "type": "integer", "description": "(propagation_checklist) Max extracted symbols. Default 20." },
+                                "format": { "type": "string", "enum": ["markdown", "json"], "description": "(propagation_checklist) markdown (default, capped checklist) | json: {symbol, groups: {proto|rust|ts|tauri|python|other: [{path, count, lines}]}, truncated} for CI gates. Raise max_chars so the JSON isn't cut." },
+                                "content": { "type": "string", "description": "(analyze_source) Source code to parse. Never written to disk." },
+                                "ext": { "type": "string", "description": "(analyze_source) File extension selecting the parser, e.g. 'rs', 'ts', 'tsx', 'py', 'go'." }
                             },
                             "required": ["action"]
                         }
                     },
                     {
                         "name": "cortex_chronos",
-                        "description": "AST snapshot tool for safe refactors. Workflow: save_checkpoint (before edit) → edit → compare_checkpoint (verify). Use instead of git diff — AST-level, ignores formatting noise. Actions: save_checkpoint, list_checkpoints, compare_checkpoint, delete_checkpoint.",
+                        "description": "AST snapshot tool for safe refactors. Workflow: save_checkpoint (before edit) → edit → compare_checkpoint (verify). Use instead of git diff — AST-level, ignores formatting noise. Actions: save_checkpoint, list_checkpoints, compare_checkpoint, compare_symbols, delete_checkpoint.",
                         "inputSchema": {
                             "type": "object",
                             "properties": {
                                 "action": {
                                     "type": "string",
-                                    "enum": ["save_checkpoint", "list_checkpoints", "compare_checkpoint", "delete_checkpoint"],
-                                    "description": "save_checkpoint: snapshot symbol before edit (needs path+symbol_name+tag). list_checkpoints: list all saved tags. compare_checkpoint: AST diff between two tags (needs symbol_name+tag_a+tag_b; tag_b='__live__' for on-disk state). delete_checkpoint: remove by namespace/symbol/tag."
+                                    "enum": ["save_checkpoint", "list_checkpoints", "compare_checkpoint", "compare_symbols", "delete_checkpoint"],
+                                    "description": "save_checkpoint: snapshot symbol before edit (needs path+symbol_name+tag). list_checkpoints: list all saved tags. compare_checkpoint: AST diff between two tags (needs symbol_name+tag_a+tag_b; tag_b='__live__' for on-disk state). compare_symbols: line diff between two different live symbols, e.g. handle_v1 vs handle_v2 (needs path_a+symbol_a+path_b+symbol_b). delete_checkpoint: remove by namespace/symbol/tag."
                                 },
                                 "repoPath": { "type": "string", "description": "Abs path to repo root." },
                                 "namespace": { "type": "string", "description": "Checkpoint group (default 'default'). delete_checkpoint with namespace only purges the whole group." },
                                 "max_chars": { "type": "integer", "description": "Max output chars. Default 8000." },
+                                "max_tokens": { "type": "integer", "description": "Cap output by estimated tokens (token_estimator.chars_per_token) instead of chars. Overrides max_chars." },
                                 "path": { "type": "string", "description": "Source file (required for save; optional for compare)." },
                                 "symbol_name": { "type": "string", "description": "Target symbol name." },
                                 "semantic_tag": { "type": "string", "description": "Tag name (e.g. 'pre-refactor')." },
                                 "tag": { "type": "string", "description": "Alias for semantic_tag." },
                                 "tag_a": { "type": "string", "description": "(compare) First tag." },
-                                "tag_b": { "type": "string", "description": "(compare) Second tag. '__live__' = current file on disk." }
+                                "tag_b": { "type": "string", "description": "(compare) Second tag. '__live__' = current file on disk." },
+                                "path_a": { "type": "string", "description": "(compare_symbols) File containing symbol_a." },
+                                "symbol_a": { "type": "string", "description": "(compare_symbols) First symbol; qualified names like Type::method work." },
+                                "path_b": { "type": "string", "description": "(compare_symbols) File containing symbol_b (may equal path_a)." },
+                                "symbol_b": { "type": "string", "description": "(compare_symbols) Second symbol." }
                             },
                             "required": ["action"]
                         }
@@ -387,7 +492,9 @@ impl ServerState {
                             "properties": {
                                 "repoPath": { "type": "string" },
                                 "target_project": { "type": "string", "description": "OMNI-AST: Optional ID or absolute path of another codebase in the network map. Overrides repoPath for cross-project exploration." },
-                                "max_chars": { "type": "integer", "description": "Optional: Limit output length. Default 8000 (safe for VS Code Copilot inline)." }
+                                "format": { "type": "string", "enum": ["markdown", "json"], "description": "markdown (default, capped for LLMs) | json: uncapped array of {level, code, message, file, line, column, context} for CI/editor tooling. Raise max_chars so the array isn't truncated." },
+                                "max_chars": { "type": "integer", "description": "Optional: Limit output length. Default 8000 (safe for VS Code Copilot inline)." },
+                                "max_tokens": { "type": "integer", "description": "Optional: cap output by estimated tokens (token_estimator.chars_per_token) instead of chars. Overrides max_chars." }
                             },
                             "required": ["repoPath"]
                         }
@@ -402,17 +509,19 @@ impl ServerState {
                                 "top_k": { "type": "integer", "description": "Max results. Default 5.", "default": 5 },
                                 "tags": { "type": "array", "items": { "type": "string" }, "description": "Filter by tags (case-insensitive)." },
                                 "project_path": { "type": "string", "description": "Filter to entries matching this project path substring." },
-                                "max_chars": { "type": "integer", "description": "Max output chars. Default 8000." }
+                                "max_chars": { "type": "integer", "description": "Max output chars. Default 8000." },
+                                "max_tokens": { "type": "integer", "description": "Cap output by estimated tokens (token_estimator.chars_per_token) instead of chars. Overrides max_chars." }
                             },
                             "required": ["query"]
                         }
                     },
                     {
                         "name": "cortex_get_rules",
-                        "description": "Fetch codebase AI rules for the current context. Returns merged rules filtered by file_path (frontend/backend/db context). Call before starting any task in a new project.",
+                        "description": "Fetch codebase AI rules for the current context. Returns merged rules filtered by file_path (frontend/backend/db context). Call before starting any task in a new project. Use action='validate_rules' to debug a rule that isn't applying: reports each tier's file, YAML syntax errors with line numbers, reserved / misspelled / type-changing keys, and which tier won each key.",
                         "inputSchema": {
                             "type": "object",
                             "properties": {
+                                "action": { "type": "string", "enum": ["get", "validate_rules"], "description": "'get' (default) returns the merged rules. 'validate_rules' returns a per-tier validation report with merge resolution per key (file_path is ignored)." },
                                 "project_path": { "type": "string", "description": "Abs path to project workspace. Locates .cortexast.json / .cortex_rules.yml." },
                                 "file_path": { "type": "string", "description": "Current file path for context filtering (frontend/backend/db). Rules apply to whole task scope." }
                             },
@@ -456,14 +565,14 @@ impl ServerState {
                     },
                     {
                         "name": "cortex_manage_ast_languages",
-                        "description": "Manage Wasm grammar parsers for non-core languages. Core (always active): rust, typescript, python. Call status to see active/available languages. Call add with languages[] to download and hot-reload parsers from GitHub tree-sitter releases. Available: go, php, cpp, c, c_sharp, java, ruby, dart.",
+                        "description": "Manage Wasm grammar parsers for non-core languages. Core (always active): rust, typescript, python. Call status to see active/available languages. Call languages to list each active driver's extensions and capabilities (imports / exports / body_prune). Call add with languages[] to download and hot-reload parsers from GitHub tree-sitter releases. Available: go, php, cpp, c, c_sharp, java, ruby, dart.",
                         "inputSchema": {
                             "type": "object",
                             "properties": {
                                 "action": {
                                     "type": "string",
-                                    "description": "status: list active and downloadable languages. add: download and hot-reload parser(s).",
-                                    "enum": ["status", "add"]
+                                    "description": "status: list active and downloadable languages. languages: per-driver extensions + capabilities (imports/exports/body_prune). add: download and hot-reload parser(s).",
+                                    "enum": ["status", "languages", "add"]
                                 },
                                 "languages": {
                                     "type": "array",
@@ -498,6 +607,10 @@ impl ServerState {
                                     "type": "integer",
                                     "description": "Hard output cap in characters (default 8000).",
                                     "default": 8000
+                                },
+                                "max_tokens": {
+                                    "type": "integer",
+                                    "description": "Hard output cap in estimated tokens (token_estimator.chars_per_token). Overrides max_chars."
                                 }
                             },
                             "required": ["path"]
@@ -524,10 +637,20 @@ impl ServerState {
     ) -> serde_json::Value {
         let name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
         let args = params.get("arguments").cloned().unwrap_or(json!({}));
-        let max_chars = negotiated_max_chars(&args);
+        let server_cfg = load_config(&self.policy_root());
+        // Token caps use the target repo's estimator, like its slices' meta does.
+        let chars_per_token = load_config(&self.cap_config_root(&args))
+            .token_estimator
+            .chars_per_token;
+        let cap = negotiated_cap(
+            &args,
+            chars_per_token,
+            budget_sized_max_chars(name, &args, chars_per_token),
+        );
+        let max_chars = cap.max_chars();
 
         let ok = |text: String| {
-            let text = force_inline_truncate(text, max_chars);
+            let text = cap.truncate(text);
             json!({
                 "jsonrpc": "2.0",
                 "id": id,
@@ -536,7 +659,7 @@ impl ServerState {
         };
 
         let err = |msg: String| {
-            let msg = force_inline_truncate(msg, max_chars);
+            let msg = cap.truncate(msg);
             json!({
                 "jsonrpc": "2.0",
                 "id": id,
@@ -544,6 +667,24 @@ impl ServerState {
             })
         };
 
+        // Exposure policy. Legacy aliases aren't listed tools; they are checked when
+        // they re-dispatch to their megatool below.
+        let policy = server_cfg.mcp;
+        if self.is_listed_tool(name) && !policy.is_tool_enabled(name) {
+            return err(format!(
+                "Error: tool '{name}' is disabled on this server by the `mcp` section of .cortexast.json \
+                (disabled_tools / enabled_tools). Do not retry it; use another tool or ask the operator to enable it."
+            ));
+        }
+
+        // The target project's `language_overrides` and `parse` limits apply to every
+        // AST tool in this call.
+        if let Ok(root) = self.resolve_target_project(&args) {
+            let cfg = load_config(&root);
+            apply_language_overrides(&cfg.language_overrides);
+            apply_parse_limits(&cfg.parse);
+        }
+
         match name {
             // ── Megatools ────────────────────────────────────────────────
             "cortex_manage_ast_languages" => {
@@ -561,6 +702,10 @@ impl ServerState {
                             "available_to_download": available_to_download
                         })).unwrap_or_default())
                     }
+                    "languages" => {
+                        let info = crate::inspector::exported_language_config().read().unwrap().describe();
+                        ok(serde_json::to_string_pretty(&info).unwrap_or_default())
+                    }
                     "add" => {
                         let mut loaded_langs = Vec::new();
                         let mut failed_langs = Vec::new();
@@ -612,7 +757,7 @@ impl ServerState {
                             )
                         })).unwrap_or_default())
                     }
-                    _ => err("Invalid action. Must be 'status' or 'add'.".to_string()),
+                    _ => err("Invalid action. Must be 'status', 'languages' or 'add'.".to_string()),
                 }
             }
             // ── CortexAct tools have been migrated to the standalone cortex-act binary ──
@@ -643,8 +788,8 @@ impl ServerState {
                             .map(|s| s.trim())
                             .filter(|s| !s.is_empty());
                         let max_chars = Some(max_chars);
-                        let ignore_gitignore = args.get("ignore_gitignore").and_then(|v| v.as_bool()).unwrap_or(false);
-                        let exclude_dirs: Vec<String> = args
+                        let ignore_mode = gitignore_mode_from_args(&args);
+                        let mut exclude_dirs: Vec<String> = args
                             .get("exclude")
                             .and_then(|v| v.as_array())
                             .map(|arr| {
@@ -653,6 +798,9 @@ impl ServerState {
                                     .collect()
                             })
                             .unwrap_or_default();
+                        if ignore_mode == GitignoreMode::ExcludeVcs {
+                            exclude_dirs.extend(load_config(&repo_root).scan.exclude_dir_names);
+                        }
                         let target_dir = resolve_path(&repo_root, target_str);
 
                         // Proactive guardrail: agents often hallucinate paths.
@@ -681,17 +829,55 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             ));
                         }
 
-                        match repo_map_with_filter(&target_dir, search_filter, max_chars, ignore_gitignore, &exclude_dirs) {
+                        let complexity_threshold = args
+                            .get("flag_complexity")
+                            .and_then(|v| v.as_u64())
+                            .filter(|n| *n > 0)
+                            .map(|n| n as u32);
+                        let max_depth = args
+                            .get("max_depth")
+                            .and_then(|v| v.as_u64())
+                            .filter(|n| *n > 0)
+                            .map(|n| n as usize);
+                        let scan_cfg = load_config(&repo_root).scan;
+                        let include_tests = args
+                            .get("include_tests")
+                            .and_then(|v| v.as_str())
+                            .and_then(IncludeTests::parse)
+                            .unwrap_or(scan_cfg.include_tests);
+                        let tests = TestFilter::new(&scan_cfg.test_patterns, include_tests);
+                        let opts = RepoMapOptions {
+                            search_filter,
+                            max_chars,
+                            ignore_mode,
+                            exclude_dirs: &exclude_dirs,
+                            complexity_threshold,
+                            max_depth,
+                            tests,
+                        };
+                        match repo_map_with_filter(&target_dir, &opts) {
                             Ok(s) => ok(s),
                             Err(e) => err(format!("repo_map failed: {e}")),
                         }
                     }
                     "deep_slice" => {
                         let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
-                        let Some(target_str) = args.get("target").and_then(|v| v.as_str()) else {
+                        // `paths`: an explicit file list, sliced in the given order; no walk, no search.
+                        let paths: Option<Vec<String>> = args
+                            .get("paths")
+                            .and_then(|v| v.as_array())
+                            .map(|arr| {
+                                arr.iter()
+                                    .filter_map(|x| x.as_str())
+                                    .map(|s| s.trim().replace('\\', "/"))
+                                    .filter(|s| !s.is_empty())
+                                    .collect::<Vec<_>>()
+                            })
+                            .filter(|v| !v.is_empty());
+                        let Some(target_str) = args.get("target").and_then(|v| v.as_str()).or(paths.as_ref().map(|_| ".")) else {
                             return err(
                                 "Error: action 'deep_slice' requires the 'target' parameter \
-                                (relative path to a file or directory within the repo, e.g. 'src'). \
+                                (relative path to a file or directory within the repo, e.g. 'src') or a 'paths' file list. \
                                 Please call cortex_code_explorer again with action='deep_slice' and target='<path>'.".to_string()
                             );
                         };
@@ -705,7 +891,7 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             } else {
                                 repo_root.join(&target)
                             };
-                            if !target_abs.exists() {
+                            if !is_glob_target(target_str) && !target_abs.exists() {
                                 let stem = target_abs
                                     .file_stem()
                                     .and_then(|s| s.to_str())
@@ -752,8 +938,11 @@ Please correct your target_dir (or pass repoPath explicitly).",
                         }
 
                         let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(32_000) as usize;
-                        let skeleton_only = args.get("skeleton_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        // `api_only` narrows a skeleton to the public contract, so it implies skeleton_only.
+                        let api_only = args.get("api_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let skeleton_only = api_only || args.get("skeleton_only").and_then(|v| v.as_bool()).unwrap_or(false);
                         let mut cfg = load_config(&repo_root);
+                        cfg.skeleton.api_only |= api_only;
 
                         // Merge per-call exclude dirs into config so build_scan_options picks them up.
                         if let Some(arr) = args.get("exclude").and_then(|v| v.as_array()) {
@@ -764,6 +953,39 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             cfg.scan.exclude_dir_names.extend(extra);
                         }
 
+                        // Per-call file-size cap (still bounded by the scanner's absolute cap).
+                        if let Some(n) = args.get("max_file_bytes").and_then(|v| v.as_u64()).filter(|n| *n > 0) {
+                            cfg.token_estimator.max_file_bytes = n;
+                        }
+
+                        // Per-call relevance threshold. If every result falls below it, the query
+                        // slice falls back to the plain target slice.
+                        if let Some(n) = args.get("min_score").and_then(|v| v.as_f64()) {
+                            cfg.vector_search.min_score = n as f32;
+                        }
+
+                        // Per-call test-file treatment (`scan.test_patterns` classify).
+                        if let Some(mode) = args.get("include_tests").and_then(|v| v.as_str()).and_then(IncludeTests::parse) {
+                            cfg.scan.include_tests = mode;
+                        }
+
+                        // Per-call embedding model. Each model keeps its own index under
+                        // db/models/<model>/, so this never disturbs the configured one.
+                        // Only configured models: the id is passed straight to the model loader.
+                        if let Some(m) = args.get("embed_model").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) {
+                            if !cfg.vector_search.allows_model(m) {
+                                return err(format!(
+                                    "Error: embed_model '{m}' is not allowed. Use one of: {} (config vector_search.allowed_models).",
+                                    std::iter::once(&cfg.vector_search.model)
+                                        .chain(&cfg.vector_search.allowed_models)
+                                        .map(|m| format!("'{m}'"))
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                ));
+                            }
+                            cfg.vector_search.model = m.to_string();
+                        }
+
                         // `single_file=true` bypasses all vector search — returns exactly the
                         // target file/dir without any semantic cross-file expansion.
                         let single_file = args.get("single_file").and_then(|v| v.as_bool()).unwrap_or(false);
@@ -776,6 +998,104 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             .filter(|s| !s.is_empty())
                             .map(|s| resolve_path(&repo_root, s));
 
+                        // `output="files"`: the plain slice as one resource block per file.
+                        let as_files = match args.get("output").and_then(|v| v.as_str()).unwrap_or("xml") {
+                            "xml" => false,
+                            "files" => true,
+                            other => return err(format!("Error: unknown output '{other}'. Must be 'xml' or 'files'.")),
+                        };
+                        let with_query = !single_file && args.get("query").and_then(|v| v.as_str()).is_some_and(|s| !s.is_empty());
+                        let with_focus = ["focus_symbol", "focus_lines"].iter().any(|k| args.get(*k).and_then(|v| v.as_str()).is_some_and(|s| !s.trim().is_empty()));
+                        // A glob target (`src/**/*_controller.rs`) becomes the `paths` list of its matches.
+                        let paths = if is_glob_target(target_str) {
+                            if paths.is_some() || with_query || with_focus || as_files {
+                                return err("Error: a glob 'target' slices exactly the matching files; drop 'paths' / 'query' / 'focus_symbol' / 'focus_lines' / output='files'.".to_string());
+                            }
+                            match expand_glob_target(&repo_root, target_str, &cfg) {
+                                Ok(matched) => Some(matched),
+                                Err(e) => return err(format!("Error: {e}")),
+                            }
+                        } else {
+                            paths
+                        };
+                        if let Some(paths) = paths {
+                            if with_query || with_focus || as_files {
+                                return err("Error: 'paths' slices exactly the listed files; drop 'query' / 'focus_symbol' / 'focus_lines' / output='files'.".to_string());
+                            }
+                            let mut rel_paths: Vec<String> = Vec::with_capacity(paths.len());
+                            let mut missing: Vec<String> = Vec::new();
+                            for p in paths {
+                                let abs = resolve_path(&repo_root, &p);
+                                match abs.strip_prefix(&repo_root) {
+                                    Ok(rel) if abs.is_file() && !p.split('/').any(|c| c == "..") => {
+                                        rel_paths.push(rel.to_string_lossy().replace('\\', "/"))
+                                    }
+                                    _ => missing.push(p),
+                                }
+                            }
+                            if !missing.is_empty() {
+                                return err(format!(
+                                    "Error: 'paths' entries are not files inside repo root '{}': {}",
+                                    repo_root.display(),
+                                    missing.join(", ")
+                                ));
+                            }
+                            return match slice_paths_to_xml(&repo_root, &rel_paths, budget_tokens, &cfg, skeleton_only) {
+                                Ok((xml, _meta)) => ok(xml),
+                                Err(e) => err(format!("slice failed: {e}")),
+                            };
+                        }
+                        if as_files {
+                            if with_query || with_focus {
+                                return err("Error: output='files' supports plain target slices only; drop 'query' / 'focus_symbol' / 'focus_lines' or use output='xml'.".to_string());
+                            }
+                            return match slice_to_files(&repo_root, &target, budget_tokens, &cfg, skeleton_only) {
+                                Ok(sliced) => slice_files_result(&id, target_str, sliced, max_chars),
+                                Err(e) => err(format!("slice failed: {e}")),
+                            };
+                        }
+
+                        // `focus_lines`: skeleton everywhere, the given line window in full.
+                        if let Some(lines) = args.get("focus_lines").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) {
+                            let window = match lines.split_once('-') {
+                                Some((a, b)) => a.trim().parse::<usize>().ok().zip(b.trim().parse::<usize>().ok()),
+                                None => lines.parse::<usize>().ok().map(|n| (n, n)),
+                            };
+                            let Some((start_line, end_line)) = window else {
+                                return err(format!("Error: focus_lines must be 'START-END' or a line number, got '{lines}'."));
+                            };
+                            let abs = repo_root.join(&target);
+                            if !abs.is_file() {
+                                return err(format!(
+                                    "Error: focus_lines requires 'target' to be a file, but '{}' is not.",
+                                    target.display()
+                                ));
+                            }
+                            let rel = target.to_string_lossy().replace('\\', "/");
+                            return match render_skeleton_window(&abs, start_line, end_line)
+                                .and_then(|s| build_context_with_budget(cfg.slicer.output_format(), None, &[(rel, s)], &[], &[], None))
+                            {
+                                Ok(xml) => ok(xml),
+                                Err(e) => err(format!("focus_lines slice failed: {e}")),
+                            };
+                        }
+
+                        // `focus_symbol`: the target file trimmed to one symbol and its neighbours.
+                        if let Some(focus) = args.get("focus_symbol").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) {
+                            if !repo_root.join(&target).is_file() {
+                                return err(format!(
+                                    "Error: focus_symbol requires 'target' to be a file, but '{}' is not. \
+                                    Pass the file that declares '{focus}' (cortex_symbol_analyzer(action=find_definition) locates it).",
+                                    target.display()
+                                ));
+                            }
+                            let context_symbols = args.get("context_symbols").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+                            return match slice_around_symbol(&repo_root, &target, focus, context_symbols, budget_tokens, &cfg) {
+                                Ok((xml, _meta)) => ok(xml),
+                                Err(e) => err(format!("focus slice failed: {e}")),
+                            };
+                        }
+
                         // Optional vector search query (skipped when single_file=true).
                         if !single_file {
                             if let Some(q) = args.get("query").and_then(|v| v.as_str()).filter(|s| !s.is_empty()) {
@@ -792,9 +1112,114 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             Err(e) => err(format!("slice failed: {e}")),
                         }
                     }
+                    "plan" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(target_str) = args.get("target").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()) else {
+                            return err(
+                                "Error: action 'plan' requires the 'target' parameter (file or directory to plan a slice of, e.g. 'src'). \
+                                Please call cortex_code_explorer again with action='plan' and target='<path>'.".to_string()
+                            );
+                        };
+                        let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(32_000) as usize;
+                        // `api_only` narrows a skeleton to the public contract, so it implies skeleton_only.
+                        let api_only = args.get("api_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let skeleton_only = api_only || args.get("skeleton_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let mut cfg = load_config(&repo_root);
+                        cfg.skeleton.api_only |= api_only;
+                        if let Some(arr) = args.get("exclude").and_then(|v| v.as_array()) {
+                            cfg.scan.exclude_dir_names.extend(arr.iter().filter_map(|x| x.as_str().map(|s| s.to_string())));
+                        }
+                        if let Some(n) = args.get("max_file_bytes").and_then(|v| v.as_u64()).filter(|n| *n > 0) {
+                            cfg.token_estimator.max_file_bytes = n;
+                        }
+                        if let Some(mode) = args.get("include_tests").and_then(|v| v.as_str()).and_then(IncludeTests::parse) {
+                            cfg.scan.include_tests = mode;
+                        }
+                        match slice_plan(&repo_root, &PathBuf::from(target_str), budget_tokens, &cfg, skeleton_only) {
+                            Ok(plan) => ok(format_slice_plan(&plan)),
+                            Err(e) => err(format!("plan failed: {e}")),
+                        }
+                    }
+                    "signatures" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let target_dir = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(32_000) as usize;
+                        match signatures_dump(&repo_root, &resolve_path(&repo_root, target_dir), budget_tokens) {
+                            Ok(s) => ok(s),
+                            Err(e) => err(format!("signatures failed: {e}")),
+                        }
+                    }
+                    "similar_files" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(p) = args.get("path").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()) else {
+                            return err(
+                                "Error: action 'similar_files' requires the 'path' parameter (the file to find neighbours of). \
+                                Please call cortex_code_explorer again with action='similar_files' and path='<file>'.".to_string()
+                            );
+                        };
+                        let abs = resolve_path(&repo_root, p);
+                        if !abs.is_file() {
+                            return err(format!(
+                                "Error: '{}' is not a file in repo root '{}'.",
+                                p,
+                                repo_root.display()
+                            ));
+                        }
+                        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10).max(1) as usize;
+                        let cfg = load_config(&repo_root);
+                        match self.run_similar_files(&repo_root, &abs, limit, &cfg) {
+                            Ok(s) => ok(s),
+                            Err(e) => err(format!("similar_files failed: {e}")),
+                        }
+                    }
+                    "repo_summary" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let target_dir = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        match repo_summary(&repo_root, &resolve_path(&repo_root, target_dir)) {
+                            Ok(summary) => ok(format_repo_summary(&summary)),
+                            Err(e) => err(format!("repo_summary failed: {e}")),
+                        }
+                    }
+                    "centrality" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let target_dir = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20).max(1) as usize;
+                        match centrality(&repo_root, &resolve_path(&repo_root, target_dir), limit) {
+                            Ok(ranked) => ok(format_centrality(&ranked)),
+                            Err(e) => err(format!("centrality failed: {e}")),
+                        }
+                    }
+                    "reference_matrix" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let target_dir = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        match reference_matrix(&resolve_path(&repo_root, target_dir)) {
+                            Ok(matrix) => ok(serde_json::to_string(&matrix).unwrap_or_default()),
+                            Err(e) => err(format!("reference_matrix failed: {e}")),
+                        }
+                    }
+                    "context_pack" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let target_str = args.get("target").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let target = PathBuf::from(target_str);
+                        if !resolve_path(&repo_root, target_str).exists() {
+                            return err(format!(
+                                "Error: Target '{}' does not exist in repo root '{}'.",
+                                target_str,
+                                repo_root.display()
+                            ));
+                        }
+                        let query = args.get("query").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty());
+                        let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(32_000) as usize;
+                        let with_diagnostics = args.get("diagnostics").and_then(|v| v.as_bool()).unwrap_or(true);
+                        let cfg = load_config(&repo_root);
+                        match self.run_context_pack(&repo_root, &target, query, budget_tokens, with_diagnostics, &cfg) {
+                            Ok(s) => ok(s),
+                            Err(e) => err(format!("context_pack failed: {e}")),
+                        }
+                    }
                     _ => err(format!(
                         "Error: Invalid or missing 'action' for cortex_code_explorer: received '{action}'. \
-                        Choose one of: 'map_overview' (repo structure map) or 'deep_slice' (token-budgeted content slice). \
+                        Choose one of: 'repo_summary' (languages, LOC and layout profile), 'map_overview' (repo structure map), 'signatures' (budgeted public-signature dump), 'deep_slice' (token-budgeted content slice), 'plan' (dry run of deep_slice), 'similar_files' (semantic neighbours of a file), 'centrality' (most depended-upon files), 'reference_matrix' (file-to-file symbol reference counts) or 'context_pack' (map + slices + diagnostics in one budget). \
                         Example: cortex_code_explorer with action='map_overview' and target_dir='.'"
                     )),
                 }
@@ -818,37 +1243,72 @@ Please correct your target_dir (or pass repoPath explicitly).",
                         };
                         let abs = resolve_path(&repo_root, p);
                         let skeleton_only = args.get("skeleton_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let expand_types = args.get("expand_types").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let test_patterns: Option<Vec<String>> = args
+                            .get("include_tests")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false)
+                            .then(|| load_config(&repo_root).scan.test_patterns);
+                        let with_extras = |mut text: String, sym: &str, instance_index: Option<usize>| {
+                            if expand_types {
+                                match expand_symbol_types(&abs, sym, instance_index, &repo_root) {
+                                    Ok(extra) => text.push_str(&extra),
+                                    Err(e) => text.push_str(&format!("\n\n// expand_types failed: {e}")),
+                                }
+                            }
+                            if let Some(patterns) = &test_patterns {
+                                match find_symbol_tests(sym, &repo_root, patterns) {
+                                    Ok(extra) => text.push_str(&extra),
+                                    Err(e) => text.push_str(&format!("\n\n// include_tests failed: {e}")),
+                                }
+                            }
+                            text
+                        };
 
                         // Multi-symbol batching: symbol_names: ["A", "B", ...]
                         if let Some(arr) = args.get("symbol_names").and_then(|v| v.as_array()) {
-                            let mut out_parts: Vec<String> = Vec::new();
-                            for v in arr {
-                                let Some(sym) = v.as_str().filter(|s| !s.trim().is_empty()) else { continue };
-                                match read_symbol_with_options(&abs, sym, skeleton_only, None) {
-                                    Ok(s) => out_parts.push(s),
-                                    Err(e) => out_parts.push(format!("// ERROR reading `{sym}`: {e}")),
-                                }
-                            }
-                            if out_parts.is_empty() {
+                            let names: Vec<&str> = arr.iter().filter_map(|v| v.as_str()).filter(|s| !s.trim().is_empty()).collect();
+                            if names.is_empty() {
                                 return err(
                                     "Error: action 'read_source' with 'symbol_names' requires a non-empty array of symbol name strings. \
                                     You provided an empty array or all entries were blank. \
                                     Example: symbol_names=['process_request', 'handle_error']".to_string()
                                 );
                             }
+                            // Budgeted batch: fill in request order, skeletonize/truncate the overflow.
+                            if let Some(budget_tokens) = args.get("budget_tokens").and_then(|v| v.as_u64()) {
+                                return ok(read_symbols_within_budget(&abs, &names, skeleton_only, budget_tokens as usize, |sym| {
+                                    with_extras(String::new(), sym, None)
+                                }));
+                            }
+                            let mut out_parts: Vec<String> = Vec::new();
+                            for sym in names {
+                                match read_symbol_with_options(&abs, sym, skeleton_only, None) {
+                                    Ok(s) => out_parts.push(with_extras(s, sym, None)),
+                                    Err(e) => out_parts.push(format!("// ERROR reading `{sym}`: {e}")),
+                                }
+                            }
                             return ok(out_parts.join("\n\n"));
                         }
 
                         let Some(sym) = args.get("symbol_name").and_then(|v| v.as_str()) else {
+                            // Line variant: innermost declaration enclosing a stack-trace / diagnostic line.
+                            if let Some(line) = args.get("line").and_then(|v| v.as_u64()) {
+                                return match read_symbol_at_line(&abs, line as usize, skeleton_only) {
+                                    Ok(s) => ok(s),
+                                    Err(e) => err(format!("read_symbol failed: {e}")),
+                                };
+                            }
                             return err(
                                 "Error: action 'read_source' requires both 'path' and 'symbol_name'. You omitted 'symbol_name'. \
                                 Please call cortex_symbol_analyzer again with action='read_source', path='<file>', and symbol_name='<name>'. \
-                                For batch extraction of multiple symbols from the same file, use symbol_names=['A','B'] instead.".to_string()
+                                For batch extraction of multiple symbols from the same file, use symbol_names=['A','B'] instead; \
+                                if you only have a line number (e.g. from a diagnostic), pass line=<N> instead of symbol_name.".to_string()
                             );
                         };
                         let instance_index = args.get("instance_index").and_then(|v| v.as_u64()).map(|n| n as usize);
                         match read_symbol_with_options(&abs, sym, skeleton_only, instance_index) {
-                            Ok(s) => ok(s),
+                            Ok(s) => ok(with_extras(s, sym, instance_index)),
                             Err(e) => err(format!("read_symbol failed: {e}")),
                         }
                     }
@@ -868,11 +1328,149 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             );
                         };
                         let target_dir = resolve_path(&repo_root, target_str);
-                        match find_usages(&target_dir, sym) {
+                        let ignore_mode = gitignore_mode_from_args(&args);
+                        let exclude_dirs: Vec<String> = if ignore_mode == GitignoreMode::ExcludeVcs {
+                            load_config(&repo_root).scan.exclude_dir_names
+                        } else {
+                            Vec::new()
+                        };
+                        let counts_only = args.get("counts_only").and_then(|v| v.as_bool()).unwrap_or(false);
+                        let sort = match args.get("sort").and_then(|v| v.as_str()) {
+                            None | Some("path") => UsageSort::Path,
+                            Some("relevance") => UsageSort::Relevance,
+                            Some(other) => return err(format!("Error: invalid 'sort' for find_usages: '{other}'. Use 'path' (default) or 'relevance'.")),
+                        };
+                        let paths = path_style_from_args(&args, &repo_root);
+                        let result = if counts_only {
+                            usage_counts(&target_dir, sym, ignore_mode, &exclude_dirs, paths).map(|c| format_usage_counts(sym, &c))
+                        } else {
+                            find_usages_with_sort(&target_dir, sym, ignore_mode, &exclude_dirs, paths, sort)
+                        };
+                        match result {
                             Ok(s) => ok(s),
                             Err(e) => err(format!("find_usages failed: {e}")),
                         }
                     }
+                    "find_definition" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(sym) = args.get("symbol_name").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()) else {
+                            return err(
+                                "Error: action 'find_definition' requires 'symbol_name'. \
+                                Please call cortex_symbol_analyzer again with action='find_definition' and symbol_name='<name>' (target_dir optional, default '.').".to_string()
+                            );
+                        };
+                        let target_str = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let target_dir = resolve_path(&repo_root, target_str);
+                        let force_walk = args.get("force_walk").and_then(|v| v.as_bool()).unwrap_or(false);
+                        match run_find_definition(&repo_root, &target_dir, sym, force_walk) {
+                            Ok(s) => ok(s),
+                            Err(e) => err(format!("find_definition failed: {e}")),
+                        }
+                    }
+                    "search_symbols" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(pattern) = args.get("symbol_name").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) else {
+                            return err(
+                                "Error: action 'search_symbols' requires 'symbol_name' (the name or part of it, e.g. 'processPayment'). \
+                                Please call cortex_symbol_analyzer again with action='search_symbols' and symbol_name='<partial name>' (target_dir optional, default '.').".to_string()
+                            );
+                        };
+                        let target_str = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let target_dir = resolve_path(&repo_root, target_str);
+                        // Report paths relative to the repo root, ready for read_source.
+                        let scope = target_dir.strip_prefix(&repo_root).unwrap_or(&target_dir).to_path_buf();
+                        match search_symbols_fuzzy(&target_dir, pattern) {
+                            Ok(mut matches) => {
+                                for m in &mut matches {
+                                    m.file = scope.join(&m.file).to_string_lossy().replace('\\', "/");
+                                }
+                                ok(format_symbol_matches(pattern, &matches, 50))
+                            }
+                            Err(e) => err(format!("search_symbols failed: {e}")),
+                        }
+                    }
+                    "list_symbols" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(kind) = args.get("kind").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) else {
+                            return err(
+                                "Error: action 'list_symbols' requires 'kind' (e.g. 'trait', 'function', 'type', 'const'). \
+                                Please call cortex_symbol_analyzer again with action='list_symbols' and kind='<kind>' (target_dir optional, default '.').".to_string()
+                            );
+                        };
+                        let target_str = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let target_dir = resolve_path(&repo_root, target_str);
+                        let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(8_000) as usize;
+                        // Report paths relative to the repo root, ready for read_source.
+                        let scope = target_dir.strip_prefix(&repo_root).unwrap_or(&target_dir).to_path_buf();
+                        match list_symbols_by_kind(&target_dir, kind) {
+                            Ok(mut listings) => {
+                                for l in &mut listings {
+                                    l.file = scope.join(&l.file).to_string_lossy().replace('\\', "/");
+                                }
+                                ok(format_symbols_by_kind(kind, &listings, budget_tokens))
+                            }
+                            Err(e) => err(format!("list_symbols failed: {e}")),
+                        }
+                    }
+                    "find_by_attribute" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(pattern) = args.get("attribute").and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()) else {
+                            return err(
+                                "Error: action 'find_by_attribute' requires 'attribute' (e.g. '#[test]', 'route', 'Deprecated'). \
+                                Please call cortex_symbol_analyzer again with action='find_by_attribute' and attribute='<text>' (target_dir optional, default '.').".to_string()
+                            );
+                        };
+                        let target_str = args.get("target_dir").and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty()).unwrap_or(".");
+                        let target_dir = resolve_path(&repo_root, target_str);
+                        let budget_tokens = args.get("budget_tokens").and_then(|v| v.as_u64()).unwrap_or(8_000) as usize;
+                        let scope = target_dir.strip_prefix(&repo_root).unwrap_or(&target_dir).to_path_buf();
+                        match find_by_attribute(&target_dir, pattern) {
+                            Ok(mut matches) => {
+                                for m in &mut matches {
+                                    m.file = scope.join(&m.file).to_string_lossy().replace('\\', "/");
+                                }
+                                ok(format_attribute_matches(pattern, &matches, budget_tokens))
+                            }
+                            Err(e) => err(format!("find_by_attribute failed: {e}")),
+                        }
+                    }
+                    "analyze_source" => {
+                        let Some(content) = args.get("content").and_then(|v| v.as_str()) else {
+                            return err(
+                                "Error: action 'analyze_source' requires 'content' (the source code string) and 'ext' (language extension, e.g. 'rs', 'ts', 'py'). \
+                                You omitted 'content'.".to_string()
+                            );
+                        };
+                        let Some(ext) = args.get("ext").and_then(|v| v.as_str()).map(|s| s.trim().trim_start_matches('.')).filter(|s| !s.is_empty()) else {
+                            return err(
+                                "Error: action 'analyze_source' requires 'ext' to pick the parser (e.g. 'rs', 'ts', 'tsx', 'py', 'go'). \
+                                Please call cortex_symbol_analyzer again with action='analyze_source', content='<code>', and ext='<extension>'.".to_string()
+                            );
+                        };
+                        // Nothing is read or written: the name only selects the language driver.
+                        let virtual_path = PathBuf::from(format!("<source>.{ext}"));
+                        match analyze_source(&virtual_path, content) {
+                            Ok(fs) => ok(serde_json::to_string_pretty(&fs).unwrap_or_default()),
+                            Err(e) => err(format!("analyze_source failed: {e}")),
+                        }
+                    }
+                    "imports_exports" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(p) = args.get("path").and_then(|v| v.as_str()) else {
+                            return err(
+                                "Error: action 'imports_exports' requires 'path' (the source file to inspect). You omitted 'path'. \
+                                Please call cortex_symbol_analyzer again with action='imports_exports' and path='<file>'.".to_string()
+                            );
+                        };
+                        let abs = resolve_path(&repo_root, p);
+                        match imports_exports(&abs) {
+                            Ok(mut surface) => {
+                                surface.file = p.to_string();
+                                ok(serde_json::to_string(&surface).unwrap_or_default())
+                            }
+                            Err(e) => err(format!("imports_exports failed: {e}")),
+                        }
+                    }
                     "find_implementations" => {
                         let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
                         let Some(target_str) = args.get("target_dir").and_then(|v| v.as_str()) else {
@@ -889,7 +1487,7 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             );
                         };
                         let target_dir = resolve_path(&repo_root, target_str);
-                        match find_implementations(&target_dir, sym) {
+                        match find_implementations(&target_dir, sym, path_style_from_args(&args, &repo_root)) {
                             Ok(s) => ok(s),
                             Err(e) => err(format!("find_implementations failed: {e}")),
                         }
@@ -910,11 +1508,40 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             );
                         };
                         let target_dir = resolve_path(&repo_root, target_str);
-                        match call_hierarchy(&target_dir, sym) {
+                        let noise = if !args.get("filter_noise").and_then(|v| v.as_bool()).unwrap_or(true) {
+                            CallNoise::Keep
+                        } else if args.get("show_filtered").and_then(|v| v.as_bool()).unwrap_or(false) {
+                            CallNoise::Count
+                        } else {
+                            CallNoise::Hide
+                        };
+                        let paths = path_style_from_args(&args, &repo_root);
+                        match call_hierarchy_with_options(&target_dir, sym, paths, noise) {
                             Ok(s) => ok(s),
                             Err(e) => err(format!("call_hierarchy failed: {e}")),
                         }
                     }
+                    "dependencies" => {
+                        let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let Some(p) = args.get("path").and_then(|v| v.as_str()) else {
+                            return err(
+                                "Error: action 'dependencies' requires both 'path' (source file containing the symbol) and 'symbol_name'. You omitted 'path'. \
+                                Please call cortex_symbol_analyzer again with action='dependencies', path='<file>', and symbol_name='<name>'.".to_string()
+                            );
+                        };
+                        let Some(sym) = args.get("symbol_name").and_then(|v| v.as_str()) else {
+                            return err(
+                                "Error: action 'dependencies' requires both 'path' and 'symbol_name'. You omitted 'symbol_name'. \
+                                Please call cortex_symbol_analyzer again with action='dependencies', path='<file>', and symbol_name='<name>'.".to_string()
+                            );
+                        };
+                        let depth = args.get("depth").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+                        let abs = resolve_path(&repo_root, p);
+                        match symbol_dependencies(&abs, sym, depth, &repo_root) {
+                            Ok(deps) => ok(format_symbol_dependencies(sym, &deps)),
+                            Err(e) => err(format!("symbol_dependencies failed: {e}")),
+                        }
+                    }
                     "propagation_checklist" => {
                         let repo_root = match self.resolve_target_project(&args) { Ok(r) => r, Err(e) => return err(e) };
                         // Legacy mode: changed_path checklist (if provided).
@@ -1001,15 +1628,21 @@ Please correct your target_dir (or pass repoPath explicitly).",
                             })
                             .unwrap_or_default();
 
-                        match propagation_checklist(&scan_dir, sym, &aliases, ignore_gitignore) {
+                        let format = match args.get("format").and_then(|v| v.as_str()).map(str::trim) {
+                            Some("json") => ChecklistFormat::Json,
+                            _ => ChecklistFormat::Markdown,
+                        };
+                        let paths = path_style_from_args(&args, &repo_root);
+                        match propagation_checklist(&scan_dir, sym, &aliases, ignore_gitignore, format, paths) {
                             Ok(s) => ok(s),
                             Err(e) => err(format!("propagation_checklist failed: {e}")),
                         }
                     }
                     _ => err(format!(
                         "Error: Invalid or missing 'action' for cortex_symbol_analyzer: received '{action}'. \
-                        Choose one of: 'read_source' (extract symbol AST), 'find_usages' (trace all call sites), 'find_implementations' (find implementors of a trait/interface), \
-                        'blast_radius' (call hierarchy before rename/delete), or 'propagation_checklist' (cross-module update checklist). \
+                        Choose one of: 'read_source' (extract symbol AST), 'find_definition' (where a symbol is declared), 'search_symbols' (fuzzy symbol-name lookup), 'list_symbols' (all declarations of one kind), 'find_by_attribute' (declarations with a given attribute/decorator), 'find_usages' (trace all call sites), 'find_implementations' (find implementors of a trait/interface), \
+                        'analyze_source' (symbols of an in-memory code string), 'imports_exports' (a file's imports and exports), \
+                        'blast_radius' (call hierarchy before rename/delete), 'dependencies' (definitions a symbol relies on), or 'propagation_checklist' (cross-module update checklist). \
                         Example: cortex_symbol_analyzer with action='find_usages', symbol_name='my_fn', and target_dir='.'"
                     )),
                 }
@@ -1113,6 +1746,23 @@ Common cause: you saved a checkpoint for a different symbol or under a different
                             }
                         }
                     }
+                    "compare_symbols" => {
+                        let repo_root = match self.repo_root_from_params(&args) { Ok(r) => r, Err(e) => return err(e) };
+                        let get = |k: &str| args.get(k).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+                        let (Some(path_a), Some(symbol_a), Some(path_b), Some(symbol_b)) =
+                            (get("path_a"), get("symbol_a"), get("path_b"), get("symbol_b"))
+                        else {
+                            return err(
+                                "Error: action 'compare_symbols' requires 'path_a', 'symbol_a', 'path_b' and 'symbol_b'. \
+                                Example: cortex_chronos with action='compare_symbols', path_a='src/api.rs', symbol_a='handle_v1', \
+                                path_b='src/api.rs', symbol_b='handle_v2'. To compare one symbol over time, use save_checkpoint + compare_checkpoint instead.".to_string()
+                            );
+                        };
+                        match compare_symbols(&repo_root, path_a, symbol_a, path_b, symbol_b) {
+                            Ok(s) => ok(s),
+                            Err(e) => err(format!("compare_symbols failed: {e}")),
+                        }
+                    }
                     "delete_checkpoint" => {
                         let repo_root = match self.repo_root_from_params(&args) { Ok(r) => r, Err(e) => return err(e) };
                         let cfg = load_config(&repo_root);
@@ -1151,7 +1801,7 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     _ => err(format!(
                         "Error: Invalid or missing 'action' for cortex_chronos: received '{action}'. \
                         Choose one of: 'save_checkpoint' (snapshot before edit), 'list_checkpoints' (show all snapshots), \
-                        'compare_checkpoint' (AST diff after edit), or 'delete_checkpoint' (remove saved checkpoints). \
+                        'compare_checkpoint' (AST diff after edit), 'compare_symbols' (diff two different symbols), or 'delete_checkpoint' (remove saved checkpoints). \
                         Example: cortex_chronos with action='save_checkpoint', path='src/main.rs', symbol_name='my_fn', and semantic_tag='pre-refactor'"
                     )),
                 }
@@ -1163,7 +1813,12 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     Ok(r) => r,
                     Err(e) => return err(e),
                 };
-                match run_diagnostics(&repo_root) {
+                let format = match args.get("format").and_then(|v| v.as_str()).map(str::trim) {
+                    Some("json") => DiagnosticsFormat::Json,
+                    _ => DiagnosticsFormat::Markdown,
+                };
+                let timeout_secs = load_config(&repo_root).diagnostics.timeout_secs;
+                match run_diagnostics(&repo_root, format, timeout_secs) {
                     Ok(s) => ok(s),
                     Err(e) => err(format!("diagnostics failed: {e}")),
                 }
@@ -1181,26 +1836,23 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     .map(|arr| arr.iter().filter_map(|x| x.as_str().map(String::from)).collect())
                     .unwrap_or_default();
 
-                // Load the memory store from the default journal path.
-                let store = MemoryStore::from_default();
+                // Load the memory store: $CORTEXAST_MEMORY_PATH, then the workspace's
+                // own .cortex_memory.jsonl, then the global CortexSync journal.
+                let store = match self.repo_root.as_deref() {
+                    Some(root) => MemoryStore::for_project(root),
+                    None => MemoryStore::from_default(),
+                };
                 if store.entries().is_empty() {
                     return ok(format!(
                         "Memory journal is empty or does not exist yet.\n\
                          Expected location: {}\n\n\
-                         Run CortexSync at least once to populate the journal.",
-                        crate::memory::default_journal_path().display()
+                         Run CortexSync at least once (or call cortex_remember) to populate the journal.",
+                        store.path().display()
                     ));
                 }
 
                 // Embed the query. Load model lazily; graceful fallback to keyword-only on failure.
-                let query_vec: Option<Vec<f32>> = StaticModel::from_pretrained(
-                    "minishlab/potion-retrieval-32M",
-                    None,
-                    None,
-                    None,
-                )
-                .ok()
-                .map(|m| m.encode_single(&format!("query: {}", query)));
+                let query_vec = self.memory_query_vector(&query);
 
                 // Tokenise the raw query for keyword scoring.
                 let tokens_owned: Vec<String> = query
@@ -1269,6 +1921,13 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     Some(p) if !p.trim().is_empty() => p.trim().to_string(),
                     _ => return err("cortex_get_rules requires a non-empty 'project_path' parameter.".to_string()),
                 };
+                let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("get");
+                if action == "validate_rules" {
+                    return ok(validate_rules(&project_path));
+                }
+                if action != "get" {
+                    return err("Invalid action. Must be 'get' or 'validate_rules'.".to_string());
+                }
                 let file_path_context = args.get("file_path").and_then(|v| v.as_str());
 
                 match get_merged_rules(&project_path, file_path_context) {
@@ -1459,6 +2118,41 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     "heavy_artifacts": heavy_artifacts
                 });
 
+                let nanos = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0);
+                let entry = MemoryEntry {
+                    schema_version: "1.0".to_string(),
+                    id: format!("mcp-{:x}-{nanos:x}", std::process::id()),
+                    session_id: format!("mcp-{:x}", std::process::id()),
+                    timestamp: utc_timestamp(),
+                    source_ide: "unknown".to_string(),
+                    project_path,
+                    intent,
+                    decision,
+                    tool_calls: vec!["cortex_remember".to_string()],
+                    files_touched,
+                    tags,
+                    vector: None,
+                };
+
+                // A project-local or $CORTEXAST_MEMORY_PATH journal is ours to write
+                // directly; the global journal belongs to CortexSync.
+                let mut store = match self.repo_root.as_deref() {
+                    Some(root) => MemoryStore::for_project(root),
+                    None => MemoryStore::from_default(),
+                };
+                if store.path() != crate::memory::cortexsync_journal_path() {
+                    return match store.append(entry) {
+                        Ok(()) => ok(format!(
+                            "Memory appended to {} (keyword-searchable; not vectorized).",
+                            store.path().display()
+                        )),
+                        Err(e) => err(format!("cortex_remember: {e:#}")),
+                    };
+                }
+
                 match ureq::post("http://127.0.0.1:14333/api/remember").send_json(payload) {
                     Ok(_) => ok(
                         "Memory successfully vectorized and committed to the global ledger."
@@ -1466,14 +2160,21 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                     ),
                     // CortexSync is offline — do NOT fail the MCP call. The LLM
                     // has already completed its task; failing here would cause
-                    // confusing error noise. Return a warning so the agent knows
-                    // memory was not persisted, but the task outcome is unaffected.
-                    Err(_) => ok(
-                        "[WARNING] CortexSync background daemon is offline. \
-                         Memory could not be saved to the vector ledger, \
-                         but your task is complete."
-                            .to_string(),
-                    ),
+                    // confusing error noise. Append an unvectorized entry so the
+                    // decision is still keyword-searchable, and warn the agent.
+                    Err(_) => {
+                        let fallback = match store.append(entry) {
+                            Ok(()) => format!(
+                                "Memory was appended to {} without a vector (keyword search only)",
+                                store.path().display()
+                            ),
+                            Err(_) => "Memory could not be saved to the vector ledger".to_string(),
+                        };
+                        ok(format!(
+                            "[WARNING] CortexSync background daemon is offline. \
+                             {fallback}, but your task is complete."
+                        ))
+                    }
                 }
             }
 
@@ -1548,6 +2249,28 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
         }
     }
 
+    /// Embed a memory query, reusing the cached model and recent query vectors.
+    /// `None` when the model can't be loaded (the next call retries).
+    fn memory_query_vector(&mut self, query: &str) -> Option<Vec<f32>> {
+        if let Some(v) = self.memory_query_vectors.get(query) {
+            return Some(v.clone());
+        }
+        if self.memory_model.is_none() {
+            self.memory_model =
+                StaticModel::from_pretrained(MEMORY_EMBED_MODEL, None, None, None).ok();
+        }
+        let vector = self
+            .memory_model
+            .as_ref()?
+            .encode_single(&format!("query: {query}"));
+        if self.memory_query_vectors.len() >= MEMORY_QUERY_CACHE_MAX {
+            self.memory_query_vectors.clear();
+        }
+        self.memory_query_vectors
+            .insert(query.to_string(), vector.clone());
+        Some(vector)
+    }
+
     /// Run vector-search-based slicing (query mode) from the MCP server.
     #[allow(clippy::too_many_arguments)]
     fn run_query_slice(
@@ -1566,7 +2289,6 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
             "node_modules".into(),
             "dist".into(),
             "target".into(),
-            cfg.output_dir.to_string_lossy().to_string(),
         ];
         exclude_dir_names.extend(cfg.scan.exclude_dir_names.iter().cloned());
 
@@ -1575,6 +2297,10 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
             target: target.to_path_buf(),
             max_file_bytes: cfg.token_estimator.max_file_bytes,
             exclude_dir_names,
+            exclude_dir_paths: vec![cfg.output_dir.clone()],
+            include_linguist_generated: cfg.scan.include_linguist_generated,
+            test_patterns: cfg.scan.test_patterns.clone(),
+            include_tests: IncludeTests::All,
         };
         let entries = scan_workspace(&opts)?;
 
@@ -1582,6 +2308,9 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
         let model_id = cfg.vector_search.model.as_str();
         let chunk_lines = cfg.vector_search.chunk_lines;
         let mut index = CodebaseIndex::open(repo_root, &db_dir, model_id, chunk_lines)?;
+        index.persist_query_cache(cfg.vector_search.persist_query_cache);
+        index.set_min_score(cfg.vector_search.min_score);
+        index.set_index_extensions(&cfg.vector_search.index_extensions);
 
         let limit = query_limit.unwrap_or_else(|| {
             let budget_based = (budget_tokens / 1_500).clamp(8, 60);
@@ -1589,19 +2318,24 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                 .min(cfg.vector_search.default_query_limit)
                 .max(1)
         });
-        let max_candidates = (limit * 12).clamp(80, 400);
-        let terms: Vec<String> = query
+        let max_candidates = cfg.vector_search.candidate_cap(limit);
+        let query_terms: Vec<String> = query
             .split_whitespace()
             .map(|s| s.trim().to_ascii_lowercase())
             .filter(|s| s.len() >= 2)
             .collect();
+        // `query_expansions` synonyms widen the path prefilter (and, opted in, the search).
+        let terms = cfg.vector_search.expand_query_terms(&query_terms);
+        let expanded_query = (cfg.vector_search.expand_embedding_query
+            && terms.len() > query_terms.len())
+        .then(|| format!("{query} {}", terms[query_terms.len()..].join(" ")));
 
         let mut scored: Vec<(i32, usize)> = entries
             .iter()
             .enumerate()
             .map(|(i, e)| {
                 let rel = e.rel_path.to_string_lossy().replace('\\', "/");
-                (score_path(&rel, &terms), i)
+                (score_path(&rel, &terms, &cfg.vector_search), i)
             })
             .collect();
         scored.sort_by(|(sa, ia), (sb, ib)| {
@@ -1609,15 +2343,31 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
                 .then_with(|| entries[*ia].bytes.cmp(&entries[*ib].bytes))
         });
 
+        // `auto_refresh = false` / CORTEXAST_NO_REFRESH=1: search the index as-is.
+        let auto_refresh = cfg.vector_search.auto_refresh;
         let mut to_index: Vec<(String, PathBuf)> = Vec::new();
-        for (_score, idx) in scored.iter().take(max_candidates) {
-            let e = &entries[*idx];
-            let rel = e.rel_path.to_string_lossy().replace('\\', "/");
-            if matches!(index.needs_reindex_path(&rel, &e.abs_path), Ok(true)) {
-                to_index.push((rel, e.abs_path.clone()));
+        if auto_refresh {
+            // The extension allowlist is applied first so skipped files don't use up candidate slots.
+            let candidates = scored
+                .iter()
+                .map(|(_score, idx)| &entries[*idx])
+                .map(|e| (e.rel_path.to_string_lossy().replace('\\', "/"), e))
+                .filter(|(rel, _)| index.is_indexable_path(rel))
+                .take(max_candidates);
+            for (rel, e) in candidates {
+                if matches!(index.needs_reindex_path(&rel, &e.abs_path), Ok(true)) {
+                    to_index.push((rel, e.abs_path.clone()));
+                }
             }
         }
 
+        // Candidates with no entry yet; after the pass, the ones that got one were added.
+        let unindexed: Vec<String> = to_index
+            .iter()
+            .filter(|(rel, _)| !index.is_indexed(rel))
+            .map(|(rel, _)| rel.clone())
+            .collect();
+
         let jobs: Vec<IndexJob> = to_index
             .par_iter()
             .filter_map(|(rel, abs)| {
@@ -1634,9 +2384,31 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
 
         let rt = tokio::runtime::Runtime::new()?;
         let q_owned = query.to_string();
-        let mut rel_paths: Vec<String> = rt.block_on(async move {
-            let _ = index.index_jobs(&jobs, || {}).await;
-            index.search(&q_owned, limit).await.unwrap_or_default()
+        let (mut rel_paths, cache_hit, refreshed) = rt.block_on(async move {
+            let refreshed = if jobs.is_empty() {
+                Ok((0, 0))
+            } else {
+                index.index_jobs(&jobs, || {}).await.map(|indexed| {
+                    let added = unindexed.iter().filter(|rel| index.is_indexed(rel)).count();
+                    (added, indexed - added)
+                })
+            };
+            let paths = index.search(&q_owned, limit).await.unwrap_or_default();
+            let cached = index.last_search_cached();
+            let Some(expanded) = expanded_query else {
+                return (paths, cached, refreshed);
+            };
+            // Alternate the two rankings so synonym-only hits make the cut.
+            let extra = index.search(&expanded, limit).await.unwrap_or_default();
+            let mut merged: Vec<String> = Vec::with_capacity(limit);
+            for i in 0..paths.len().max(extra.len()) {
+                for p in [paths.get(i), extra.get(i)].into_iter().flatten() {
+                    if merged.len() < limit && !merged.contains(p) {
+                        merged.push(p.clone());
+                    }
+                }
+            }
+            (merged, cached, refreshed)
         });
 
         // Scope results to `only_dir` when provided, or auto-scope to the target's
@@ -1665,16 +2437,273 @@ Call cortex_chronos with action='list_checkpoints' first to see what exists.".to
         if let Some(ref prefix) = scope_prefix {
             rel_paths.retain(|p| p.starts_with(prefix.as_str()));
         }
+        // The index covers every file; `include_tests` is applied to the ranked hits.
+        let tests = TestFilter::new(&cfg.scan.test_patterns, cfg.scan.include_tests);
+        rel_paths.retain(|p| tests.keeps(p));
 
         let (xml, _meta) = if rel_paths.is_empty() {
             slice_to_xml(repo_root, target, budget_tokens, cfg, skeleton_only)?
         } else {
             slice_paths_to_xml(repo_root, &rel_paths, budget_tokens, cfg, skeleton_only)?
         };
-        Ok(xml)
+        let xml = if cache_hit {
+            format!("<!-- query cache hit: index unchanged since this query last ran -->\n{xml}")
+        } else {
+            xml
+        };
+        if !auto_refresh {
+            return Ok(format!(
+                "<!-- ⚠️ Index refresh skipped (vector_search.auto_refresh=false or CORTEXAST_NO_REFRESH): \
+                results may be stale if files changed since the last indexed query. -->\n{xml}"
+            ));
+        }
+        Ok(format!("{}{xml}", index_refresh_note(&refreshed)))
+    }
+
+    /// One-shot context document: a compact repo map, the query-ranked slice of `target`
+    /// and current compiler diagnostics, in that order, under one `budget_tokens` cap.
+    ///
+    /// The map gets at most 10% of the budget and diagnostics at most 15%; the slice is
+    /// sized to whatever those two actually left over.
+    fn run_context_pack(
+        &mut self,
+        repo_root: &std::path::Path,
+        target: &std::path::Path,
+        query: Option<&str>,
+        budget_tokens: usize,
+        with_diagnostics: bool,
+        cfg: &crate::config::Config,
+    ) -> anyhow::Result<String> {
+        let chars_per_token = cfg.token_estimator.chars_per_token.max(1);
+        let budget_chars = budget_tokens.saturating_mul(chars_per_token);
+
+        let opts = RepoMapOptions {
+            max_chars: Some(budget_chars / 10),
+            exclude_dirs: &cfg.scan.exclude_dir_names,
+            tests: TestFilter::new(&cfg.scan.test_patterns, cfg.scan.include_tests),
+            ..RepoMapOptions::default()
+        };
+        let map = repo_map_with_filter(repo_root, &opts)
+            .unwrap_or_else(|e| format!("(repo map unavailable: {e})"));
+
+        let diagnostics = if with_diagnostics {
+            let text = run_diagnostics(
+                repo_root,
+                DiagnosticsFormat::Markdown,
+                cfg.diagnostics.timeout_secs,
+            )
+            .unwrap_or_else(|e| format!("(diagnostics unavailable: {e})"));
+            Some(force_inline_truncate(text, budget_chars * 15 / 100))
+        } else {
+            None
+        };
+
+        let used_chars = map.len() + diagnostics.as_ref().map_or(0, |d| d.len()) + 512;
+        let slice_budget = budget_tokens
+            .saturating_sub(used_chars / chars_per_token)
+            .max(1);
+        let slice = match query {
+            Some(q) => {
+                self.run_query_slice(repo_root, target, None, q, None, slice_budget, false, cfg)?
+            }
+            None => slice_to_xml(repo_root, target, slice_budget, cfg, false)?.0,
+        };
+
+        let mut out = format!(
+            "# Context pack: `{}` (budget {budget_tokens} tokens)\n\n## 1. Repository map\n\n{}\n",
+            target.display(),
+            map.trim_end()
+        );
+        match query {
+            Some(q) => out.push_str(&format!("\n## 2. Relevant code (query: \"{q}\")\n\n")),
+            None => out.push_str("\n## 2. Code\n\n"),
+        }
+        out.push_str(slice.trim_end());
+        out.push('\n');
+        if let Some(d) = diagnostics {
+            out.push_str("\n## 3. Diagnostics\n\n");
+            out.push_str(d.trim_end());
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Refresh the vector index (unless `auto_refresh` is off) and list the files most
+    /// similar to `abs_path`.
+    fn run_similar_files(
+        &mut self,
+        repo_root: &std::path::Path,
+        abs_path: &std::path::Path,
+        limit: usize,
+        cfg: &crate::config::Config,
+    ) -> anyhow::Result<String> {
+        let mut exclude_dir_names = vec![
+            ".git".into(),
+            "node_modules".into(),
+            "dist".into(),
+            "target".into(),
+        ];
+        exclude_dir_names.extend(cfg.scan.exclude_dir_names.iter().cloned());
+
+        let opts = ScanOptions {
+            repo_root: repo_root.to_path_buf(),
+            target: PathBuf::from("."),
+            max_file_bytes: cfg.token_estimator.max_file_bytes,
+            exclude_dir_names,
+            exclude_dir_paths: vec![cfg.output_dir.clone()],
+            include_linguist_generated: cfg.scan.include_linguist_generated,
+            test_patterns: cfg.scan.test_patterns.clone(),
+            include_tests: IncludeTests::All,
+        };
+
+        let db_dir = repo_root.join(&cfg.output_dir).join("db");
+        let model_id = cfg.vector_search.model.as_str();
+        let chunk_lines = cfg.vector_search.chunk_lines;
+        let mut index = CodebaseIndex::open(repo_root, &db_dir, model_id, chunk_lines)?;
+        index.set_index_extensions(&cfg.vector_search.index_extensions);
+        if cfg.vector_search.auto_refresh {
+            index.refresh(&opts)?;
+        }
+
+        let rel = abs_path
+            .strip_prefix(repo_root)
+            .unwrap_or(abs_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let rt = tokio::runtime::Runtime::new()?;
+        let hits = rt.block_on(index.similar_files(&rel, limit))?;
+
+        let mut out = format!("## Files similar to `{rel}`\n\n");
+        if hits.is_empty() {
+            out.push_str("_No other indexed files._\n");
+        }
+        for (i, (path, score)) in hits.iter().enumerate() {
+            out.push_str(&format!("{}. `{}` — {:.3}\n", i + 1, path, score));
+        }
+        if !cfg.vector_search.auto_refresh {
+            out.push_str("\n> ⚠️ Index refresh skipped (auto_refresh=false / CORTEXAST_NO_REFRESH): results may be stale.\n");
+        }
+        Ok(out)
+    }
+}
+
+/// Repo-relative paths unless the caller passed `absolute_paths: true`.
+fn path_style_from_args<'a>(
+    args: &serde_json::Value,
+    repo_root: &'a std::path::Path,
+) -> PathStyle<'a> {
+    if args
+        .get("absolute_paths")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        PathStyle::Absolute
+    } else {
+        PathStyle::RelativeTo(repo_root)
+    }
+}
+
+/// Read `gitignore_mode` ("respect" | "exclude_vcs" | "off"), falling back to the
+/// legacy `ignore_gitignore` boolean.
+fn gitignore_mode_from_args(args: &serde_json::Value) -> GitignoreMode {
+    let mode = args.get("gitignore_mode").and_then(|v| v.as_str());
+    let legacy = args.get("ignore_gitignore").and_then(|v| v.as_bool());
+    match mode.map(str::trim) {
+        Some("exclude_vcs") => GitignoreMode::ExcludeVcs,
+        Some("off") => GitignoreMode::Off,
+        Some("respect") => GitignoreMode::Respect,
+        _ if legacy.unwrap_or(false) => GitignoreMode::Off,
+        _ => GitignoreMode::Respect,
     }
 }
 
+/// Definition sites of `symbol_name` under `target_dir`. Answered from the persistent
+/// symbol index (`{output_dir}/db/models/<model>/symbols.json`) only when it covers
+/// every source file in scope at its current content and has hits there; otherwise —
+/// partial coverage (query mode embeds only the top candidates), cold, stale, miss or
+/// `force_walk` — by the AST walk, which stays the ground truth.
+fn run_find_definition(
+    repo_root: &std::path::Path,
+    target_dir: &std::path::Path,
+    symbol_name: &str,
+    force_walk: bool,
+) -> anyhow::Result<String> {
+    let cfg = load_config(repo_root);
+    let db_dir = repo_root.join(&cfg.output_dir).join("db");
+    let scope: PathBuf = target_dir
+        .strip_prefix(repo_root)
+        .unwrap_or(target_dir)
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect();
+
+    let indexed = SymbolIndex::load(&model_db_dir(&db_dir, &cfg.vector_search.model))
+        .filter(|_| !force_walk)
+        .filter(|index| {
+            index.covers(repo_root, &definition_candidate_files(&repo_root.join(target_dir)))
+        })
+        .and_then(|index| {
+            let hits: Vec<(String, u32, String)> = index
+                .lookup(symbol_name)
+                .iter()
+                .filter(|h| std::path::Path::new(&h.file).starts_with(&scope))
+                .map(|h| (h.file.clone(), h.line, h.kind.clone()))
+                .collect();
+            let fresh = !hits.is_empty()
+                && hits.iter().all(|(file, line, _)| {
+                    definition_still_at(repo_root, file, *line, symbol_name)
+                });
+            fresh.then_some(hits)
+        });
+
+    let from_index = indexed.is_some();
+    let sites = match indexed {
+        Some(hits) => hits,
+        None => find_definition_sites(repo_root, target_dir, symbol_name)?,
+    };
+
+    if sites.is_empty() {
+        return Ok(format!(
+            "No declaration named `{symbol_name}` found under `{}`.\n\
+            Tip: names are matched exactly; use find_usages for references.",
+            target_dir.display()
+        ));
+    }
+
+    let source = if from_index {
+        "symbol index"
+    } else {
+        "AST walk"
+    };
+    let mut out = format!(
+        "## Definitions of `{symbol_name}` ({}, via {source})\n\n",
+        sites.len()
+    );
+    for (file, line, kind) in &sites {
+        out.push_str(&format!("- `{kind}` {file}:L{line}\n"));
+    }
+    if from_index {
+        out.push_str(
+            "\n> Served from the symbol index, which covers every file in scope. \
+            Pass `force_walk: true` for a fresh AST walk.\n",
+        );
+    }
+    Ok(out)
+}
+
+/// True when line `line` (1-based) of `repo_root/file` still mentions `name`.
+fn definition_still_at(repo_root: &std::path::Path, file: &str, line: u32, name: &str) -> bool {
+    std::fs::read_to_string(repo_root.join(file))
+        .ok()
+        .and_then(|text| {
+            text.lines()
+                .nth(line.saturating_sub(1) as usize)
+                .map(|l| l.contains(name))
+        })
+        .unwrap_or(false)
+}
+
 /// Resolve a path parameter: if absolute, use as-is; otherwise join to repo_root.
 fn resolve_path(repo_root: &std::path::Path, p: &str) -> PathBuf {
     let pb = PathBuf::from(p);
@@ -1685,23 +2714,114 @@ fn resolve_path(repo_root: &std::path::Path, p: &str) -> PathBuf {
     }
 }
 
-fn score_path(rel_path: &str, terms: &[String]) -> i32 {
+fn score_path(
+    rel_path: &str,
+    terms: &[String],
+    weights: &crate::config::VectorSearchConfig,
+) -> i32 {
     let p = rel_path.to_ascii_lowercase();
     let filename = p.rsplit('/').next().unwrap_or(&p);
     let mut score = 0i32;
     for t in terms {
         if filename.contains(t.as_str()) {
-            score += 30;
+            score += weights.filename_term_weight;
         } else if p.contains(t.as_str()) {
-            score += 10;
+            score += weights.path_term_weight;
         }
     }
     score
 }
 
+/// The `index_refresh` note for a query's JIT pass, from what `index_jobs` actually
+/// embedded: `+added ~updated`, as in the CLI's refresh spinner. The pass only
+/// re-embeds existing candidates, so it has no deletions to report.
+fn index_refresh_note(refreshed: &Result<(usize, usize)>) -> String {
+    match refreshed {
+        Ok((0, 0)) => "<!-- index_refresh: warm (no changes) -->\n".to_string(),
+        Ok((added, updated)) => {
+            format!("<!-- index_refresh: +{added} ~{updated} (re-embedded before searching) -->\n")
+        }
+        Err(e) => format!("<!-- index_refresh: failed ({e}); results may be stale -->\n"),
+    }
+}
+
+/// Environment variable naming a file that receives a timestamped trace of every
+/// JSON-RPC line the stdio server reads and writes. Never touches stdout.
+pub const MCP_LOG_ENV: &str = "CORTEXAST_MCP_LOG";
+
+/// Longest message body written to the trace; the rest is elided with a byte count.
+const MCP_LOG_MAX_ENTRY_BYTES: usize = 16 * 1024;
+
+/// Append-only JSON-RPC trace enabled by [`MCP_LOG_ENV`].
+struct McpTrace {
+    file: std::fs::File,
+}
+
+impl McpTrace {
+    fn from_env() -> Option<Self> {
+        let path = std::env::var_os(MCP_LOG_ENV).filter(|v| !v.is_empty())?;
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+        {
+            Ok(file) => Some(Self { file }),
+            Err(e) => {
+                eprintln!(
+                    "[cortexast] {MCP_LOG_ENV}: cannot open {}: {e}",
+                    PathBuf::from(path).display()
+                );
+                None
+            }
+        }
+    }
+
+    /// `direction` is `<-` for inbound, `->` for outbound.
+    fn record(&mut self, direction: &str, body: &str) {
+        let mut cut = body.len().min(MCP_LOG_MAX_ENTRY_BYTES);
+        while !body.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let mut entry = format!("{} {direction} {}", utc_timestamp(), &body[..cut]);
+        if cut < body.len() {
+            entry.push_str(&format!(" …[{} more bytes]", body.len() - cut));
+        }
+        entry.push('\n');
+        // One write per entry keeps lines whole; tracing must never break the server.
+        let _ = self.file.write_all(entry.as_bytes());
+    }
+}
+
+/// Current UTC time as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+fn utc_timestamp() -> String {
+    let ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let (days, ms_of_day) = (ms.div_euclid(86_400_000), ms.rem_euclid(86_400_000));
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1_000 % 60,
+        ms_of_day % 1_000
+    )
+}
+
 pub fn run_stdio_server(startup_root: Option<PathBuf>) -> Result<()> {
     let stdin = std::io::stdin();
     let mut stdout = std::io::stdout();
+    let mut trace = McpTrace::from_env();
 
     let mut state = ServerState::default();
     // ── Bootstrap repo_root before the first tool call arrives ──────────────
@@ -1739,14 +2859,22 @@ pub fn run_stdio_server(startup_root: Option<PathBuf>) -> Result<()> {
         .filter(|s| !s.is_empty())
         .map(PathBuf::from);
     if let Some(r) = startup_root.or(env_root) {
+        state.workspace_root = Some(r.clone());
         state.repo_root = Some(r);
     }
+    // The rayon pool is process-wide, so it is sized once from the startup workspace.
+    load_config(&state.policy_root())
+        .performance
+        .install_thread_pool();
 
     for line in stdin.lock().lines() {
         let Ok(line) = line else { continue };
         if line.trim().is_empty() {
             continue;
         }
+        if let Some(t) = trace.as_mut() {
+            t.record("<-", &line);
+        }
 
         let msg: serde_json::Value = match serde_json::from_str(&line) {
             Ok(v) => v,
@@ -1774,7 +2902,7 @@ pub fn run_stdio_server(startup_root: Option<PathBuf>) -> Result<()> {
                     "jsonrpc": "2.0",
                     "id": id,
                     "result": {
-                        "protocolVersion": msg.get("params").and_then(|p| p.get("protocolVersion")).cloned().unwrap_or(json!("2024-11-05")),
+                        "protocolVersion": negotiated_protocol_version(msg.get("params").and_then(|p| p.get("protocolVersion")).and_then(|v| v.as_str())),
                         "capabilities": { "tools": { "listChanged": true } },
                         "serverInfo": { "name": "cortexast", "version": env!("CARGO_PKG_VERSION") }
                     }
@@ -1808,6 +2936,10 @@ pub fn run_stdio_server(startup_root: Option<PathBuf>) -> Result<()> {
             }),
         };
 
+        let reply = reply.to_string();
+        if let Some(t) = trace.as_mut() {
+            t.record("->", &reply);
+        }
         writeln!(stdout, "{}", reply)?;
         stdout.flush()?;
     }
@@ -1815,14 +2947,199 @@ pub fn run_stdio_server(startup_root: Option<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// MCP spec revisions this server implements, oldest first. `2025-03-26` is left out:
+/// it requires JSON-RPC batches, which the line-per-message loop does not handle.
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-06-18"];
+
+/// Revision answered when the client asks for one we don't know (or none at all).
+const DEFAULT_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// The client's requested `protocolVersion` when we support it, otherwise
+/// [`DEFAULT_PROTOCOL_VERSION`]; the client then decides whether it can proceed.
+fn negotiated_protocol_version(requested: Option<&str>) -> &'static str {
+    requested
+        .and_then(|r| SUPPORTED_PROTOCOL_VERSIONS.iter().find(|v| **v == r.trim()))
+        .copied()
+        .unwrap_or(DEFAULT_PROTOCOL_VERSION)
+}
+
+/// `file://` URI for `path` (`file:///C:/...` for Windows drive paths).
+fn path_to_file_uri(path: &std::path::Path) -> String {
+    let p = path.to_string_lossy().replace('\\', "/");
+    if p.starts_with('/') {
+        format!("file://{p}")
+    } else {
+        format!("file:///{p}")
+    }
+}
+
+/// JSON-RPC result for `deep_slice` with `output="files"`: one text block that
+/// summarises the slice, then one embedded `resource` block per file. The
+/// `max_chars` cap spans all files; files past it are listed as omitted.
+fn slice_files_result(
+    id: &serde_json::Value,
+    target: &str,
+    sliced: SliceFiles,
+    max_chars: usize,
+) -> serde_json::Value {
+    let SliceFiles {
+        files,
+        skipped,
+        budget,
+        meta,
+    } = sliced;
+
+    let mut summary = format!(
+        "deep_slice `{target}`: {} files, ~{} / {} tokens.",
+        files.len(),
+        meta.total_tokens,
+        meta.budget_tokens
+    );
+    let mut blocks: Vec<serde_json::Value> = Vec::new();
+    let mut remaining = max_chars;
+    let mut omitted: Vec<String> = Vec::new();
+    for f in files {
+        if remaining == 0 {
+            omitted.push(f.path);
+            continue;
+        }
+        if let Some(t) = &f.truncation {
+            summary.push_str(&format!(
+                "\n- `{}` cut at line {} ({} bytes left out) to fit the budget.",
+                f.path, t.at_line, t.remaining_bytes
+            ));
+        }
+        let len = f.content.len();
+        let text = force_inline_truncate(f.content, remaining);
+        remaining = remaining.saturating_sub(len);
+        blocks.push(json!({
+            "type": "resource",
+            "resource": {
+                "uri": path_to_file_uri(&meta.repo_root.join(&f.path)),
+                "mimeType": "text/plain",
+                "text": text
+            }
+        }));
+    }
+    if let Some(b) = &budget {
+        summary.push_str(&format!(
+            "\n- Budget: {} files dropped; the whole target is ~{} tokens.",
+            b.dropped_files, b.full_tokens
+        ));
+    }
+    for (path, bytes) in &skipped {
+        summary.push_str(&format!("\n- `{path}` skipped: too large ({bytes} bytes)."));
+    }
+    if !omitted.is_empty() {
+        summary.push_str(&format!(
+            "\n- ✂️ {} more files omitted by max_chars={max_chars}: {}",
+            omitted.len(),
+            omitted.join(", ")
+        ));
+    }
+    blocks.insert(0, json!({ "type": "text", "text": summary }));
+
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": { "content": blocks, "isError": false }
+    })
+}
+
 const DEFAULT_MAX_CHARS: usize = 8_000;
 
-fn negotiated_max_chars(args: &serde_json::Value) -> usize {
+/// Inline response cap. `max_chars` (default 8000) is the IDE-spill guard;
+/// `max_tokens` caps by estimated tokens instead, using the same
+/// `token_estimator.chars_per_token` heuristic as the slice meta (from the target
+/// repo's config, see `cap_config_root`).
+#[derive(Clone, Copy)]
+enum InlineCap {
+    Chars(usize),
+    Tokens {
+        max_tokens: usize,
+        chars_per_token: usize,
+    },
+}
+
+impl InlineCap {
+    /// Byte length that fits the cap; handlers use it to size their own output.
+    fn max_chars(self) -> usize {
+        match self {
+            InlineCap::Chars(n) => n,
+            InlineCap::Tokens {
+                max_tokens,
+                chars_per_token,
+            } => max_tokens.saturating_mul(chars_per_token.max(1)),
+        }
+    }
+
+    fn truncate(self, content: String) -> String {
+        match self {
+            InlineCap::Chars(n) => force_inline_truncate(content, n),
+            InlineCap::Tokens {
+                max_tokens,
+                chars_per_token,
+            } => force_inline_truncate_tokens(content, max_tokens, chars_per_token),
+        }
+    }
+}
+
+/// Room for the header and footer that budget-sized actions add around their body.
+const BUDGET_FRAME_CHARS: usize = 1_024;
+
+/// `max_tokens` wins over `max_chars` when both are given. Without either,
+/// `default_max_chars` (or [`DEFAULT_MAX_CHARS`]) applies.
+fn negotiated_cap(
+    args: &serde_json::Value,
+    chars_per_token: usize,
+    default_max_chars: Option<usize>,
+) -> InlineCap {
+    match args
+        .get("max_tokens")
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n > 0)
+    {
+        Some(n) => InlineCap::Tokens {
+            max_tokens: n as usize,
+            chars_per_token,
+        },
+        None => InlineCap::Chars(
+            explicit_max_chars(args).unwrap_or(default_max_chars.unwrap_or(DEFAULT_MAX_CHARS)),
+        ),
+    }
+}
+
+fn explicit_max_chars(args: &serde_json::Value) -> Option<usize> {
     args.get("max_chars")
         .and_then(|v| v.as_u64())
         .map(|n| n as usize)
         .filter(|n| *n > 0)
-        .unwrap_or(DEFAULT_MAX_CHARS)
+}
+
+/// Default cap for actions that already size their output to `budget_tokens`
+/// (`signatures`, `context_pack`): the whole budget, not [`DEFAULT_MAX_CHARS`].
+fn budget_sized_max_chars(
+    tool: &str,
+    args: &serde_json::Value,
+    chars_per_token: usize,
+) -> Option<usize> {
+    if tool != "cortex_code_explorer" {
+        return None;
+    }
+    let chars_per_token = match args.get("action").and_then(|v| v.as_str())? {
+        "signatures" => SIGNATURES_CHARS_PER_TOKEN,
+        "context_pack" => chars_per_token.max(1),
+        _ => return None,
+    };
+    let budget_tokens = args
+        .get("budget_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(32_000) as usize;
+    Some(
+        budget_tokens
+            .saturating_mul(chars_per_token)
+            .saturating_add(BUDGET_FRAME_CHARS),
+    )
 }
 
 /// Hard inline cap: always truncates in the response body — never writes to disk.
@@ -1842,3 +3159,119 @@ fn force_inline_truncate(mut content: String, max_chars: usize) -> String {
     ));
     content
 }
+
+/// Token-budget variant of [`force_inline_truncate`]: keeps the longest prefix whose
+/// estimated token count (`estimate_tokens_from_bytes`) fits `max_tokens`.
+fn force_inline_truncate_tokens(
+    mut content: String,
+    max_tokens: usize,
+    chars_per_token: usize,
+) -> String {
+    let total_tokens = estimate_tokens_from_bytes(content.len() as u64, chars_per_token);
+    if total_tokens <= max_tokens {
+        return content;
+    }
+    let mut cut = max_tokens
+        .saturating_mul(chars_per_token.max(1))
+        .min(content.len());
+    while cut > 0 && !content.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    content.truncate(cut);
+    content.push_str(&format!(
+        "\n\n... ✂️ [TRUNCATED: ~{max_tokens}/~{total_tokens} estimated tokens to fit max_tokens]"
+    ));
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_refresh_note_reports_what_was_embedded() {
+        assert_eq!(
+            index_refresh_note(&Ok((0, 0))),
+            "<!-- index_refresh: warm (no changes) -->\n"
+        );
+        assert_eq!(
+            index_refresh_note(&Ok((2, 1))),
+            "<!-- index_refresh: +2 ~1 (re-embedded before searching) -->\n"
+        );
+        let failed = index_refresh_note(&Err(anyhow::anyhow!("index locked")));
+        assert!(failed.contains("failed (index locked)"), "{failed}");
+    }
+
+    #[test]
+    fn max_tokens_wins_over_max_chars() {
+        let args = json!({ "max_chars": 50, "max_tokens": 10 });
+        let cap = negotiated_cap(&args, 3, None);
+        assert!(matches!(
+            cap,
+            InlineCap::Tokens {
+                max_tokens: 10,
+                chars_per_token: 3
+            }
+        ));
+        assert_eq!(cap.max_chars(), 30);
+
+        // Zero means "unset", for both.
+        let args = json!({ "max_chars": 50, "max_tokens": 0 });
+        assert!(matches!(negotiated_cap(&args, 3, None), InlineCap::Chars(50)));
+        let args = json!({ "max_chars": 0 });
+        assert_eq!(negotiated_cap(&args, 3, None).max_chars(), DEFAULT_MAX_CHARS);
+    }
+
+    #[test]
+    fn budget_sized_actions_default_to_their_budget() {
+        let args = json!({ "action": "signatures", "budget_tokens": 10_000 });
+        let default = budget_sized_max_chars("cortex_code_explorer", &args, 2);
+        assert_eq!(default, Some(10_000 * SIGNATURES_CHARS_PER_TOKEN + BUDGET_FRAME_CHARS));
+        assert_eq!(
+            negotiated_cap(&args, 2, default).max_chars(),
+            default.unwrap()
+        );
+
+        let args = json!({ "action": "context_pack" });
+        assert_eq!(
+            budget_sized_max_chars("cortex_code_explorer", &args, 3),
+            Some(32_000 * 3 + BUDGET_FRAME_CHARS)
+        );
+        let args = json!({ "action": "context_pack", "max_chars": 500 });
+        let default = budget_sized_max_chars("cortex_code_explorer", &args, 3);
+        assert_eq!(negotiated_cap(&args, 3, default).max_chars(), 500);
+
+        let args = json!({ "action": "deep_slice" });
+        assert_eq!(budget_sized_max_chars("cortex_code_explorer", &args, 4), None);
+        let args = json!({ "action": "signatures" });
+        assert_eq!(budget_sized_max_chars("cortex_symbol_analyzer", &args, 4), None);
+    }
+
+    #[test]
+    fn token_cap_cuts_at_max_tokens_times_chars_per_token() {
+        let content = "x".repeat(100);
+        assert_eq!(force_inline_truncate_tokens(content.clone(), 25, 4), content);
+
+        let out = force_inline_truncate_tokens(content, 10, 4);
+        let (kept, marker) = out.split_once("\n\n").unwrap();
+        assert_eq!(kept, "x".repeat(40));
+        assert!(marker.contains("~10/~25 estimated tokens"), "{marker}");
+
+        // chars_per_token = 0 counts bytes.
+        let out = force_inline_truncate_tokens("y".repeat(20), 5, 0);
+        assert!(out.starts_with("yyyyy\n\n"), "{out}");
+    }
+
+    #[test]
+    fn truncation_never_splits_a_multibyte_char() {
+        // "€" is 3 bytes: a 20-byte cut falls inside the 7th one.
+        let content = "€".repeat(30);
+        let out = force_inline_truncate_tokens(content.clone(), 5, 4);
+        assert!(out.starts_with(&"€".repeat(6)), "{out}");
+        assert!(!out.starts_with(&"€".repeat(7)), "{out}");
+
+        let out = force_inline_truncate(content, 10);
+        assert!(out.starts_with(&format!("{}\n\n", "€".repeat(3))), "{out}");
+        assert!(out.contains("[TRUNCATED: 10/90 chars"), "{out}");
+    }
+}
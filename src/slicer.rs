@@ -1,12 +1,18 @@
-use crate::config::Config;
-use crate::inspector::try_render_skeleton_from_source;
+use crate::config::{Config, ParseFailure, SlicerConfig};
+use crate::inspector::{
+    is_minified_or_generated, module_header, symbol_focus, try_render_skeleton_from_source,
+    FocusedDecl, SymbolFocus,
+};
 use crate::mapper::build_repo_map_scoped;
-use crate::scanner::{scan_workspace, FileEntry, ScanOptions};
+use crate::scanner::{scan_workspace_report, FileEntry, LinguistKind, ScanOptions, ScanReport};
 use crate::workspace::{discover_workspace_members, WorkspaceDiscoveryOptions};
-use crate::xml_builder::build_context_xml;
+use crate::xml_builder::{build_context_with_budget, BudgetSummary, ContextXmlWriter, Truncation};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
 pub struct SliceMeta {
@@ -16,6 +22,10 @@ pub struct SliceMeta {
     pub total_tokens: usize,
     pub total_files: usize,
     pub total_bytes: u64,
+    /// Repo-relative paths inlined into the XML, in output order.
+    pub included: Vec<String>,
+    /// Candidate paths left out (over budget, too large, unreadable or linguist-flagged).
+    pub excluded: Vec<String>,
 }
 
 pub fn estimate_tokens_from_bytes(total_bytes: u64, chars_per_token: usize) -> usize {
@@ -37,11 +47,15 @@ pub fn slice_paths_to_xml(
     cfg: &Config,
     skeleton_only: bool,
 ) -> Result<(String, SliceMeta)> {
+    // `slicer.profile` may keep headroom below the requested budget.
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
     let repo_root = repo_root.to_path_buf();
     let target = PathBuf::from(".");
+    let candidates: Vec<String> = rel_paths.iter().map(|r| r.replace('\\', "/")).collect();
 
     // Build entries in the provided order (assumed relevance-ranked).
     let mut entries: Vec<crate::scanner::FileEntry> = Vec::new();
+    let mut oversized: Vec<crate::scanner::FileEntry> = Vec::new();
     for rel in rel_paths {
         let rel_norm = rel.replace('\\', "/");
         let abs = repo_root.join(&rel_norm);
@@ -53,46 +67,69 @@ pub fn slice_paths_to_xml(
             continue;
         }
         let bytes = meta.len();
-        if bytes == 0 || bytes > cfg.token_estimator.max_file_bytes {
+        if bytes == 0 {
             continue;
         }
-        entries.push(crate::scanner::FileEntry {
+        let entry = crate::scanner::FileEntry {
             abs_path: abs,
             rel_path: PathBuf::from(rel_norm),
             bytes,
-        });
+        };
+        if bytes > cfg.token_estimator.max_file_bytes {
+            oversized.push(entry);
+        } else {
+            entries.push(entry);
+        }
     }
 
-    let all_paths: Vec<String> = entries
+    let mut all_paths: Vec<String> = entries
         .iter()
         .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
         .collect();
+    all_paths.extend(oversized.iter().map(skipped_map_line));
     let repository_map_text = build_repository_map_text(&all_paths);
+    let skipped = skipped_stubs(&oversized);
 
     let mut files_for_xml: Vec<(String, String)> = Vec::new();
-    let mut total_bytes: u64 = 64;
-    total_bytes = total_bytes
-        .saturating_add(estimate_xml_repository_map_overhead_bytes())
-        .saturating_add(repository_map_text.len() as u64);
+    let mut over_budget = OverBudget::default();
+    let mut total_bytes = header_bytes(&repository_map_text, &skipped);
 
+    let redactor = SecretRedactor::new(&cfg.slicer);
     for e in entries.iter() {
         let bytes = match std::fs::read(&e.abs_path) {
             Ok(b) => b,
             Err(_) => continue,
         };
-        let content_full = String::from_utf8(bytes)
-            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).to_string());
         let rel = e.rel_path.to_string_lossy().replace('\\', "/");
+        let content_full = redactor.redact(
+            &rel,
+            String::from_utf8(bytes)
+                .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).to_string()),
+        );
 
-        let content = if cfg.skeleton_mode || skeleton_only {
-            match try_render_skeleton_from_source(&e.abs_path, &content_full) {
+        let skeleton = cfg.skeleton_mode || skeleton_only;
+        let mut parse_failed = None;
+        let content = if skeleton {
+            match render_skeleton_for_slice(&e.abs_path, &content_full, cfg) {
                 Ok(Some(s)) => s,
                 Ok(None) => truncate_unknown(&rel, &content_full),
-                Err(_) => truncate_unknown(&rel, &content_full),
+                Err(err) => {
+                    let policy = cfg.slicer.parse_failure;
+                    parse_failed = Some(parse_failure_marker(&err, policy));
+                    parse_failure_content(&rel, content_full, policy).1
+                }
             }
         } else {
             content_full
         };
+        let content = match cfg.slicer.annotate_recent_commits {
+            0 => content,
+            n => annotate_recent_lines(&e.abs_path, content, !skeleton, n),
+        };
+        let content = match parse_failed {
+            Some(marker) => marker + &content,
+            None => content,
+        };
 
         let overhead = estimate_xml_file_overhead_bytes(&rel);
         let new_total = total_bytes
@@ -100,6 +137,7 @@ pub fn slice_paths_to_xml(
             .saturating_add(content.len() as u64);
         let est = estimate_tokens_from_bytes(new_total, cfg.token_estimator.chars_per_token);
         if est > budget_tokens {
+            over_budget.push(rel, content);
             continue;
         }
 
@@ -107,8 +145,22 @@ pub fn slice_paths_to_xml(
         files_for_xml.push((rel, content));
     }
 
+    let (truncated, budget) = fill_with_truncated(
+        &mut files_for_xml,
+        &mut total_bytes,
+        over_budget,
+        budget_tokens,
+        cfg.token_estimator.chars_per_token,
+    );
     let total_tokens = estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token);
-    let xml = build_context_xml(Some(&repository_map_text), &files_for_xml)?;
+    let xml = build_context_with_budget(
+        cfg.slicer.output_format(),
+        Some(&repository_map_text),
+        &files_for_xml,
+        &skipped,
+        &truncated,
+        budget.as_ref(),
+    )?;
 
     let meta = SliceMeta {
         repo_root,
@@ -117,11 +169,154 @@ pub fn slice_paths_to_xml(
         total_tokens,
         total_files: files_for_xml.len(),
         total_bytes,
+        excluded: excluded_paths(candidates, &included_paths(&files_for_xml)),
+        included: included_paths(&files_for_xml),
     };
 
     Ok((xml, meta))
 }
 
+/// Slice one file centred on `symbol`: the symbol in full, up to `context_symbols`
+/// sibling declarations on each side as skeletons, and a `... (lines A-B omitted)`
+/// comment for everything else. Neighbours are dropped farthest-first until the
+/// slice fits `budget_tokens`; a symbol too big on its own is cut at a line boundary
+/// like any over-budget file.
+pub fn slice_around_symbol(
+    repo_root: &Path,
+    path: &Path,
+    symbol: &str,
+    context_symbols: usize,
+    budget_tokens: usize,
+    cfg: &Config,
+) -> Result<(String, SliceMeta)> {
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
+    let abs = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        repo_root.join(path)
+    };
+    let rel = abs
+        .strip_prefix(repo_root)
+        .unwrap_or(&abs)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let source = std::fs::read_to_string(&abs)
+        .with_context(|| format!("Failed to read {}", abs.display()))?;
+    let source = SecretRedactor::new(&cfg.slicer).redact(&rel, source);
+    let focus = symbol_focus(&abs, &source, symbol, context_symbols)?;
+
+    let repository_map_text = build_repository_map_text(std::slice::from_ref(&rel));
+    let chars_per_token = cfg.token_estimator.chars_per_token;
+    let fixed = header_bytes(&repository_map_text, &[]) + estimate_xml_file_overhead_bytes(&rel);
+
+    let marker = omitted_lines_marker(&rel);
+    let lines: Vec<&str> = source.lines().collect();
+    let (mut before, mut after) = (focus.before.len(), focus.after.len());
+    let mut content = focus_content(&focus, &lines, before, after, marker);
+    while before + after > 0
+        && estimate_tokens_from_bytes(fixed + content.len() as u64, chars_per_token) > budget_tokens
+    {
+        if before >= after {
+            before -= 1;
+        } else {
+            after -= 1;
+        }
+        content = focus_content(&focus, &lines, before, after, marker);
+    }
+
+    let mut files_for_xml: Vec<(String, String)> = Vec::new();
+    let mut total_bytes = header_bytes(&repository_map_text, &[]);
+    let mut over_budget = OverBudget::default();
+    if estimate_tokens_from_bytes(fixed + content.len() as u64, chars_per_token) > budget_tokens {
+        over_budget.push(rel.clone(), content);
+    } else {
+        total_bytes = fixed + content.len() as u64;
+        files_for_xml.push((rel.clone(), content));
+    }
+    let (truncated, budget) = fill_with_truncated(
+        &mut files_for_xml,
+        &mut total_bytes,
+        over_budget,
+        budget_tokens,
+        chars_per_token,
+    );
+    let xml = build_context_with_budget(
+        cfg.slicer.output_format(),
+        Some(&repository_map_text),
+        &files_for_xml,
+        &[],
+        &truncated,
+        budget.as_ref(),
+    )?;
+
+    let meta = SliceMeta {
+        repo_root: repo_root.to_path_buf(),
+        target: PathBuf::from(&rel),
+        budget_tokens,
+        total_tokens: estimate_tokens_from_bytes(total_bytes, chars_per_token),
+        total_files: files_for_xml.len(),
+        total_bytes,
+        excluded: excluded_paths(vec![rel], &included_paths(&files_for_xml)),
+        included: included_paths(&files_for_xml),
+    };
+    Ok((xml, meta))
+}
+
+/// The `before` nearest preceding and `after` nearest following siblings of
+/// `focus` around its target, in file order. Gaps between them are marked as
+/// omitted unless they are only blank lines.
+fn focus_content(
+    focus: &SymbolFocus,
+    lines: &[&str],
+    before: usize,
+    after: usize,
+    marker: &str,
+) -> String {
+    let mut decls: Vec<&FocusedDecl> = focus.before[..before].iter().rev().collect();
+    decls.push(&focus.target);
+    decls.extend(&focus.after[..after]);
+
+    let mut out = String::new();
+    // 1-based, inclusive.
+    let gap = |out: &mut String, from: usize, to: usize| {
+        let skipped = lines.get(from - 1..to).unwrap_or_default();
+        if skipped.iter().all(|l| l.trim().is_empty()) {
+            out.push_str(&"\n".repeat(skipped.len()));
+        } else {
+            out.push_str(&format!("{marker} ... (lines {from}-{to} omitted)\n"));
+        }
+    };
+    let mut next_line = 1;
+    for decl in decls {
+        if decl.start_line > next_line {
+            gap(&mut out, next_line, decl.start_line - 1);
+        }
+        out.push_str(&decl.text);
+        if !decl.text.ends_with('\n') {
+            out.push('\n');
+        }
+        next_line = decl.end_line + 1;
+    }
+    if next_line <= focus.total_lines {
+        gap(&mut out, next_line, focus.total_lines);
+    }
+    out
+}
+
+/// Line-comment leader for the omitted-lines markers of [`slice_around_symbol`].
+fn omitted_lines_marker(rel_path: &str) -> &'static str {
+    let ext = Path::new(rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "py" | "rb" | "sh" | "bash" | "pl" | "r" | "ex" | "exs" | "yaml" | "yml" | "toml" => "#",
+        "sql" | "lua" | "hs" | "elm" => "--",
+        _ => "//",
+    }
+}
+
 fn estimate_xml_file_overhead_bytes(rel_path: &str) -> u64 {
     // Rough but consistent overhead estimate for:
     // <file path="{path}"><![CDATA[{content}]]></file>
@@ -136,12 +331,156 @@ fn estimate_xml_file_overhead_bytes(rel_path: &str) -> u64 {
     33u64 + rel_path.len() as u64
 }
 
+fn estimate_xml_skipped_stubs_bytes(skipped: &[(String, u64)]) -> u64 {
+    // <file path="{path}" skipped="too-large" bytes="{n}"/>
+    // Constant parts: 12 + 21 + 8 + 3 = 44 bytes, plus path and digit count.
+    skipped
+        .iter()
+        .map(|(path, bytes)| 44u64 + path.len() as u64 + bytes.to_string().len() as u64)
+        .sum()
+}
+
+/// `<budget .../>` element, upper bound.
+const BUDGET_SUMMARY_BYTES: u64 = 160;
+/// `<truncated at="line N" remaining_bytes="M"/>` element, upper bound.
+const TRUNCATION_MARKER_BYTES: u64 = 64;
+
+/// Rendered files that did not fit whole, in rank order. Only the best-ranked one
+/// can still be cut into the slice, so only its content is kept; the rest count
+/// toward the summary's `full_tokens` and `dropped_files`.
+#[derive(Default)]
+struct OverBudget {
+    first: Option<(String, String)>,
+    files: usize,
+    bytes: u64,
+}
+
+impl OverBudget {
+    fn push(&mut self, rel: String, content: String) {
+        self.files += 1;
+        self.bytes = self
+            .bytes
+            .saturating_add(estimate_xml_file_overhead_bytes(&rel))
+            .saturating_add(content.len() as u64);
+        if self.first.is_none() {
+            self.first = Some((rel, content));
+        }
+    }
+}
+
+/// Cut the best-ranked file in `over_budget` down to the whole lines the leftover
+/// budget still holds, and summarise how far over budget the complete slice would
+/// have been. Files that fit whole keep their place; the cut file goes last, and
+/// the rest of `over_budget` is dropped. Returns no summary when nothing overflowed.
+fn fill_with_truncated(
+    files_for_xml: &mut Vec<(String, String)>,
+    total_bytes: &mut u64,
+    over_budget: OverBudget,
+    budget_tokens: usize,
+    chars_per_token: usize,
+) -> (Vec<(String, Truncation)>, Option<BudgetSummary>) {
+    if over_budget.files == 0 {
+        return (Vec::new(), None);
+    }
+
+    let full_bytes = total_bytes.saturating_add(over_budget.bytes);
+    *total_bytes = total_bytes.saturating_add(BUDGET_SUMMARY_BYTES);
+
+    let capacity = (budget_tokens as u64).saturating_mul(chars_per_token.max(1) as u64);
+    let mut truncated: Vec<(String, Truncation)> = Vec::new();
+    if let Some((rel, content)) = over_budget.first {
+        let overhead = estimate_xml_file_overhead_bytes(&rel) + TRUNCATION_MARKER_BYTES;
+        let avail = capacity
+            .saturating_sub(*total_bytes)
+            .saturating_sub(overhead)
+            .min(content.len() as u64) as usize;
+        // Cut after the last newline that fits; `\n` is ASCII, so this is a char boundary.
+        if let Some(nl) = content.as_bytes()[..avail]
+            .iter()
+            .rposition(|b| *b == b'\n')
+        {
+            let kept = &content[..=nl];
+            truncated.push((
+                rel.clone(),
+                Truncation {
+                    at_line: kept.matches('\n').count() + 1,
+                    remaining_bytes: (content.len() - kept.len()) as u64,
+                },
+            ));
+            *total_bytes = total_bytes
+                .saturating_add(overhead)
+                .saturating_add(kept.len() as u64);
+            files_for_xml.push((rel, kept.to_string()));
+        }
+    }
+
+    let dropped_files = over_budget.files - truncated.len();
+    let summary = BudgetSummary {
+        budget_tokens,
+        used_tokens: estimate_tokens_from_bytes(*total_bytes, chars_per_token),
+        full_tokens: estimate_tokens_from_bytes(full_bytes, chars_per_token),
+        truncated_files: truncated.len(),
+        dropped_files,
+    };
+    (truncated, Some(summary))
+}
+
+/// Paths of the `(path, content)` pairs a slice wrote, in output order, for
+/// [`SliceMeta::included`].
+fn included_paths(files: &[(String, String)]) -> Vec<String> {
+    files.iter().map(|(p, _)| p.clone()).collect()
+}
+
+/// `candidates` minus everything `included`, first occurrence order, no duplicates.
+fn excluded_paths(candidates: Vec<String>, included: &[String]) -> Vec<String> {
+    let mut seen: HashSet<String> = included.iter().cloned().collect();
+    candidates
+        .into_iter()
+        .filter(|p| seen.insert(p.clone()))
+        .collect()
+}
+
+/// `(path, bytes)` pairs for the XML stubs of files dropped by `max_file_bytes`.
+fn skipped_stubs(oversized: &[FileEntry]) -> Vec<(String, u64)> {
+    oversized
+        .iter()
+        .map(|e| (e.rel_path.to_string_lossy().replace('\\', "/"), e.bytes))
+        .collect()
+}
+
+/// Repository-map line for a file dropped by `max_file_bytes`, so it never looks absent.
+fn skipped_map_line(e: &FileEntry) -> String {
+    format!(
+        "{} ({} bytes, skipped)",
+        e.rel_path.to_string_lossy().replace('\\', "/"),
+        e.bytes
+    )
+}
+
+/// Repository-map line for a file `.gitattributes` marks generated or vendored.
+fn linguist_map_line((e, kind): &(FileEntry, LinguistKind)) -> String {
+    format!(
+        "{} ({}, skipped)",
+        e.rel_path.to_string_lossy().replace('\\', "/"),
+        kind.as_str()
+    )
+}
+
 fn estimate_xml_repository_map_overhead_bytes() -> u64 {
     // <repository_map><![CDATA[...]]></repository_map>
     // Rough constant overhead (not counting map content bytes).
     40
 }
 
+/// Estimated bytes of everything but the file blocks: XML declaration and root
+/// element, the repository map and the too-large stubs.
+fn header_bytes(repository_map_text: &str, skipped: &[(String, u64)]) -> u64 {
+    64u64
+        .saturating_add(estimate_xml_repository_map_overhead_bytes())
+        .saturating_add(repository_map_text.len() as u64)
+        .saturating_add(estimate_xml_skipped_stubs_bytes(skipped))
+}
+
 fn truncation_header_for_path(rel_path: &str) -> &'static str {
     let p = rel_path.to_lowercase();
     if p.ends_with(".md")
@@ -156,6 +495,43 @@ fn truncation_header_for_path(rel_path: &str) -> &'static str {
     }
 }
 
+/// [`try_render_skeleton_from_source`] with `cfg.skeleton`, plus the file's
+/// [`module_header`] on top when `slicer.preserve_module_header` is set and the
+/// skeleton dropped it.
+fn render_skeleton_for_slice(
+    path: &Path,
+    source_text: &str,
+    cfg: &Config,
+) -> Result<Option<String>> {
+    let Some(skeleton) = try_render_skeleton_from_source(path, source_text, &cfg.skeleton)? else {
+        return Ok(None);
+    };
+    if !cfg.slicer.preserve_module_header || is_minified_or_generated(source_text) {
+        return Ok(Some(skeleton));
+    }
+    let Some(header) = module_header(path, source_text) else {
+        return Ok(Some(skeleton));
+    };
+    // Already there, e.g. kept by `skeleton.keep_all_comments` or a comment tag.
+    let first_text = header
+        .lines()
+        .find(|l| !l.trim().trim_matches(['"', '\'']).is_empty())
+        .unwrap_or_default();
+    if skeleton.contains(first_text) {
+        return Ok(Some(skeleton));
+    }
+    // A shebang has to stay on the first line.
+    let split = match skeleton.strip_prefix("#!") {
+        Some(rest) if !rest.starts_with('[') => rest.find('\n').map_or(skeleton.len(), |n| n + 3),
+        _ => 0,
+    };
+    Ok(Some(format!(
+        "{}{header}{}",
+        &skeleton[..split],
+        &skeleton[split..]
+    )))
+}
+
 fn truncate_unknown(rel_path: &str, content: &str) -> String {
     let max_lines: usize = 50;
     let max_bytes: usize = 2048;
@@ -182,6 +558,175 @@ fn truncate_unknown(rel_path: &str, content: &str) -> String {
     out
 }
 
+/// Placeholder written over redacted secret values.
+pub const REDACTED: &str = "<REDACTED>";
+
+const SECRET_NAME_MATCH: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+/// Masks credentials in file content before it is sliced (`slicer.redact_secrets`).
+///
+/// Config/data files (`.env`, YAML, JSON, TOML, INI, `.properties`) matching
+/// `slicer.secret_patterns` lose the value of every `key = value` / `key: value`
+/// line; source files such as `secret_manager.rs` never do. All files lose well-known API-key shapes, quoted values of
+/// secret-sounding keys (`api_key = "..."`) and the body of private-key blocks.
+#[derive(Debug, Clone, Default)]
+pub struct SecretRedactor {
+    enabled: bool,
+    path_patterns: Vec<glob::Pattern>,
+    name_patterns: Vec<glob::Pattern>,
+}
+
+impl SecretRedactor {
+    pub fn new(cfg: &SlicerConfig) -> Self {
+        let mut redactor = Self {
+            enabled: cfg.redact_secrets,
+            ..Self::default()
+        };
+        for p in cfg
+            .secret_patterns
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+        {
+            let Ok(pat) = glob::Pattern::new(p) else {
+                continue;
+            };
+            if p.contains('/') {
+                redactor.path_patterns.push(pat);
+            } else {
+                redactor.name_patterns.push(pat);
+            }
+        }
+        redactor
+    }
+
+    /// Whether `rel_path` (forward slashes) is a config/data file matching
+    /// `slicer.secret_patterns`, i.e. one whose values are all blanked.
+    pub fn is_secret_file(&self, rel_path: &str) -> bool {
+        let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        if !is_config_data_file(name) {
+            return false;
+        }
+        self.name_patterns
+            .iter()
+            .any(|p| p.matches_with(name, SECRET_NAME_MATCH))
+            || self
+                .path_patterns
+                .iter()
+                .any(|p| p.matches_with(rel_path, SECRET_NAME_MATCH))
+    }
+
+    /// `content` with secrets replaced by [`REDACTED`]; unchanged when disabled.
+    pub fn redact(&self, rel_path: &str, content: String) -> String {
+        if !self.enabled {
+            return content;
+        }
+        let secret_file = self.is_secret_file(rel_path);
+        let mut out = String::with_capacity(content.len());
+        let mut in_private_key = false;
+        let mut key_redacted = false;
+        for line in content.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\n', '\r']);
+            let eol = &line[body.len()..];
+            if in_private_key {
+                if body.contains("-----END") && body.contains("PRIVATE KEY") {
+                    in_private_key = false;
+                    out.push_str(line);
+                } else if !key_redacted {
+                    // One marker for the whole key body.
+                    key_redacted = true;
+                    let indent = &body[..body.len() - body.trim_start().len()];
+                    out.push_str(indent);
+                    out.push_str(REDACTED);
+                    out.push_str(eol);
+                }
+                continue;
+            }
+            if body.contains("-----BEGIN") && body.contains("PRIVATE KEY") {
+                in_private_key = true;
+                key_redacted = false;
+                out.push_str(line);
+                continue;
+            }
+            let masked = redact_known_secrets(body);
+            let masked = if secret_file {
+                redact_assignment_value(&masked)
+            } else {
+                masked
+            };
+            out.push_str(&masked);
+            out.push_str(eol);
+        }
+        out
+    }
+}
+
+/// Files whose lines are plain `key = value` / `key: value` data, so blanking every
+/// value keeps their shape. Anything else (source code) keeps its values.
+fn is_config_data_file(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if name == ".env" || name.starts_with(".env.") || name.ends_with(".env") {
+        return true;
+    }
+    matches!(
+        name.rsplit_once('.').map(|(_, ext)| ext),
+        Some("yaml" | "yml" | "json" | "toml" | "ini" | "properties")
+    )
+}
+
+/// Well-known token shapes (AWS, GitHub, Slack, OpenAI/Anthropic, Google, Stripe)
+/// and quoted literals assigned to secret-sounding keys.
+fn redact_known_secrets(line: &str) -> String {
+    static RE: OnceLock<(regex::Regex, regex::Regex)> = OnceLock::new();
+    let (tokens, assigned) = RE.get_or_init(|| {
+        (
+            regex::Regex::new(
+                r"\b(?:AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{20,}|xox[abprs]-[A-Za-z0-9-]{10,}|sk-[A-Za-z0-9_-]{20,}|AIza[0-9A-Za-z_-]{35}|[sr]k_live_[0-9A-Za-z]{16,})",
+            )
+            .unwrap(),
+            regex::Regex::new(
+                r#"(?i)(\b[\w-]*(?:api[_-]?key|secret|token|passw(?:or)?d|credential|private[_-]?key|access[_-]?key)[\w-]*["']?\s*(?::=|=>|:|=)\s*["'])([^"'\s]{8,})(["'])"#,
+            )
+            .unwrap(),
+        )
+    });
+    let line = tokens.replace_all(line, REDACTED);
+    assigned
+        .replace_all(&line, format!("${{1}}{REDACTED}${{3}}"))
+        .into_owned()
+}
+
+/// In a secret file, blank the value of a `key = value` / `key: value` line.
+/// Comments, bare keys (`section:`) and lines whose left side is not a plain
+/// key (code such as `use a::b;` or `let x = y;`) are kept.
+fn redact_assignment_value(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with(['#', ';']) || trimmed.starts_with("//") {
+        return line.to_string();
+    }
+    let Some(sep) = line.find(['=', ':']) else {
+        return line.to_string();
+    };
+    let (key, rest) = line.split_at(sep + 1);
+    let name = key[..sep].trim().trim_start_matches("- ");
+    let name = name.strip_prefix("export ").unwrap_or(name);
+    let name = name.trim_matches(['"', '\'']);
+    let plain_key = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    let value = rest.trim();
+    if !plain_key || rest.starts_with(':') || value.is_empty() || value == REDACTED {
+        return line.to_string();
+    }
+    let gap = &rest[..rest.len() - rest.trim_start().len()];
+    format!("{key}{gap}{REDACTED}")
+}
+
 fn is_manifest_file(rel_path: &str) -> bool {
     let p = rel_path.to_lowercase();
     p.ends_with("cargo.toml") || p.ends_with("package.json")
@@ -235,7 +780,7 @@ fn compact_package_json(content: &str) -> Option<String> {
     serde_json::to_string_pretty(&serde_json::Value::Object(out)).ok()
 }
 
-fn importance_score(rel_path: &str) -> i64 {
+pub(crate) fn importance_score(rel_path: &str) -> i64 {
     let p = rel_path.to_lowercase();
     let file = p.rsplit('/').next().unwrap_or(p.as_str());
 
@@ -370,6 +915,9 @@ fn compute_repo_map_indegree(repo_root: &Path, target: &Path) -> HashMap<String,
 
     let mut indegree: HashMap<String, u32> = HashMap::new();
     for e in map.edges {
+        if e.external {
+            continue;
+        }
         if let Some(dst_path) = id_to_path.get(&e.target) {
             *indegree.entry(dst_path.clone()).or_insert(0) += 1;
         }
@@ -428,7 +976,11 @@ fn build_repository_map_text_raw(sections_text: &str) -> String {
     let max_bytes: usize = 96 * 1024; // slightly larger limit for monorepos
 
     let mut out = String::from("# REPOSITORY_MAP\n");
-    let to_add = &sections_text[..sections_text.len().min(max_bytes)];
+    let mut cut = sections_text.len().min(max_bytes);
+    while !sections_text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let to_add = &sections_text[..cut];
     out.push_str(to_add);
     if sections_text.len() > max_bytes {
         out.push_str("\n# ... (truncated)\n");
@@ -436,30 +988,379 @@ fn build_repository_map_text_raw(sections_text: &str) -> String {
     out
 }
 
-/// Shared inner function: convert a ranked list of `FileEntry` into context XML.
-fn build_xml_from_entries(
-    entries: Vec<crate::scanner::FileEntry>,
-    repo_root: &Path,
-    target: &Path,
-    budget_tokens: usize,
+/// How the slicer renders (or skips) one candidate file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SliceMode {
+    /// Inlined verbatim (the focus file, or any file outside skeleton mode).
+    Full,
+    /// `Cargo.toml` / `package.json` reduced to its structural keys.
+    Compacted,
+    /// Signatures only, bodies elided.
+    Skeleton,
+    /// Skeleton mode hit overlong lines (minified or machine-generated): stub only.
+    Minified,
+    /// Contains NUL bytes: stub only.
+    Binary,
+    /// No skeleton driver for the language: first 50 lines / 2 KB.
+    Head,
+    /// The driver failed to parse it and `slicer.parse_failure` is `"skip"`: stub only.
+    ParseFailed,
+    /// Over `max_file_bytes`: listed in the map, never read.
+    TooLarge,
+    /// `.gitattributes` marks it `linguist-generated`: listed, never read.
+    Generated,
+    /// `.gitattributes` marks it `linguist-vendored`: listed, never read.
+    Vendored,
+}
+
+impl SliceMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SliceMode::Full => "full",
+            SliceMode::Compacted => "compacted",
+            SliceMode::Skeleton => "skeleton",
+            SliceMode::Minified => "minified",
+            SliceMode::Binary => "binary",
+            SliceMode::Head => "head",
+            SliceMode::ParseFailed => "parse_failed",
+            SliceMode::TooLarge => "too_large",
+            SliceMode::Generated => "generated",
+            SliceMode::Vendored => "vendored",
+        }
+    }
+}
+
+impl From<LinguistKind> for SliceMode {
+    fn from(kind: LinguistKind) -> Self {
+        match kind {
+            LinguistKind::Generated => SliceMode::Generated,
+            LinguistKind::Vendored => SliceMode::Vendored,
+        }
+    }
+}
+
+/// What the budget does with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SliceFate {
+    Included,
+    /// Cut to the whole lines the leftover budget holds.
+    Truncated,
+    /// Rendered, but over budget.
+    Dropped,
+    /// Never read (too large, generated or vendored).
+    Skipped,
+}
+
+impl SliceFate {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SliceFate::Included => "included",
+            SliceFate::Truncated => "truncated",
+            SliceFate::Dropped => "dropped",
+            SliceFate::Skipped => "skipped",
+        }
+    }
+}
+
+/// One row of a [`SlicePlan`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedFile {
+    /// Repo-relative path.
+    pub path: String,
+    pub mode: SliceMode,
+    pub fate: SliceFate,
+    /// Size on disk.
+    pub bytes: u64,
+    /// Estimated tokens of the rendered file, XML wrapper included (0 when skipped).
+    pub tokens: usize,
+}
+
+/// Dry run of [`slice_to_xml`]: per-file decisions and costs, no content.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlicePlan {
+    pub budget_tokens: usize,
+    /// Estimated size of the slice the same call would emit.
+    pub used_tokens: usize,
+    /// Estimated size had every rendered file fit whole.
+    pub full_tokens: usize,
+    /// Rendered files in rank order, then skipped files by path.
+    pub files: Vec<PlannedFile>,
+}
+
+/// Render one scanned file the way it appears in a slice.
+fn render_entry(
+    e: &FileEntry,
+    rel: &str,
+    content_full: String,
     cfg: &Config,
-    focus_full_rel: Option<String>,
-    skeleton_only: bool,
-) -> Result<(String, SliceMeta)> {
-    let mut all_paths: Vec<String> = entries
+    skeleton_mode: bool,
+    is_focus_full: bool,
+) -> (SliceMode, String) {
+    if content_full.contains('\0') {
+        return (
+            SliceMode::Binary,
+            "/* BINARY_FILE — skipped */\n".to_string(),
+        );
+    }
+    let lower = rel.to_lowercase();
+    let mut parse_failed = None;
+    let (mode, content) = if is_focus_full {
+        (SliceMode::Full, content_full)
+    } else if lower.ends_with("cargo.toml") {
+        match compact_cargo_toml(&content_full) {
+            Some(c) => (SliceMode::Compacted, c),
+            None => (SliceMode::Full, content_full),
+        }
+    } else if lower.ends_with("package.json") {
+        match compact_package_json(&content_full) {
+            Some(c) => (SliceMode::Compacted, c),
+            None => (SliceMode::Full, content_full),
+        }
+    } else if skeleton_mode {
+        match render_skeleton_for_slice(&e.abs_path, &content_full, cfg) {
+            Ok(Some(s)) if is_minified_or_generated(&content_full) => (SliceMode::Minified, s),
+            Ok(Some(s)) => (SliceMode::Skeleton, s),
+            Ok(None) => (SliceMode::Head, truncate_unknown(rel, &content_full)),
+            Err(err) => {
+                let policy = cfg.slicer.parse_failure;
+                parse_failed = Some(parse_failure_marker(&err, policy));
+                parse_failure_content(rel, content_full, policy)
+            }
+        }
+    } else {
+        (SliceMode::Full, content_full)
+    };
+    let content = match cfg.slicer.annotate_recent_commits {
+        0 => content,
+        _ if mode == SliceMode::Minified => content,
+        n => annotate_recent_lines(&e.abs_path, content, mode == SliceMode::Full, n),
+    };
+    // The marker goes on last so recent-line annotation still lines up with the file.
+    match parse_failed {
+        Some(marker) => (mode, marker + &content),
+        None => (mode, content),
+    }
+}
+
+/// What a skeleton slice shows for a file its driver failed to parse, per
+/// `slicer.parse_failure`, without the [`parse_failure_marker`].
+fn parse_failure_content(
+    rel: &str,
+    content_full: String,
+    policy: ParseFailure,
+) -> (SliceMode, String) {
+    match policy {
+        ParseFailure::Full => (SliceMode::Full, content_full),
+        ParseFailure::Truncate => (SliceMode::Head, truncate_unknown(rel, &content_full)),
+        ParseFailure::Skip => (SliceMode::ParseFailed, String::new()),
+    }
+}
+
+/// First line of a parse-failure fallback, so the agent knows the content is raw.
+fn parse_failure_marker(err: &anyhow::Error, policy: ParseFailure) -> String {
+    let what = match policy {
+        ParseFailure::Full => "raw content, not a skeleton",
+        ParseFailure::Truncate => "raw head, not a skeleton",
+        ParseFailure::Skip => "skipped",
+    };
+    format!("/* PARSE_FAILED ({err}) — {what} */\n")
+}
+
+/// Prefix for slice lines changed in the last `slicer.annotate_recent_commits` commits.
+const RECENT_LINE_MARKER: char = '~';
+
+/// Lines of one file that `git blame` attributes to a recent commit or to uncommitted
+/// edits: by 1-based line number and by trimmed text.
+#[derive(Debug, Default)]
+struct RecentLines {
+    lines: HashSet<usize>,
+    texts: HashSet<String>,
+    total_lines: usize,
+}
+
+/// `content` with [`RECENT_LINE_MARKER`] in front of every recently changed line.
+/// `verbatim` content (the file as-is) is matched line for line; rendered content
+/// (skeletons, heads, compacted manifests) by line text, ignoring lines under four
+/// characters such as lone braces. Unchanged outside a git checkout.
+fn annotate_recent_lines(abs_path: &Path, content: String, verbatim: bool, n: usize) -> String {
+    let Some(recent) = recent_lines(abs_path, n) else {
+        return content;
+    };
+    if recent.lines.is_empty() {
+        return content;
+    }
+    // Redaction can fold a private key into one line; fall back to text matching then.
+    let by_number = verbatim && content.lines().count() == recent.total_lines;
+    let mut out = String::with_capacity(content.len() + recent.lines.len());
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let hit = if by_number {
+            recent.lines.contains(&(i + 1))
+        } else {
+            let t = line.trim();
+            t.len() >= 4 && recent.texts.contains(t)
+        };
+        if hit {
+            out.push(RECENT_LINE_MARKER);
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// Most blame results [`recent_lines`] keeps; the cache starts over once full.
+const RECENT_LINES_CACHE_CAP: usize = 4096;
+
+/// [`blame_recent_lines`], cached per file until its mtime or the checkout's `HEAD`
+/// changes (a new commit shifts which lines count as recent without touching the file).
+fn recent_lines(abs_path: &Path, n: usize) -> Option<Arc<RecentLines>> {
+    type Key = (PathBuf, Option<SystemTime>, usize, Option<String>);
+    static CACHE: OnceLock<Mutex<HashMap<Key, Option<Arc<RecentLines>>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let mtime = std::fs::metadata(abs_path).and_then(|m| m.modified()).ok();
+    let key = (abs_path.to_path_buf(), mtime, n, head_commit(abs_path.parent()?));
+    if let Some(hit) = cache.lock().unwrap().get(&key) {
+        return hit.clone();
+    }
+    let computed = blame_recent_lines(abs_path, n).map(Arc::new);
+    let mut cache = cache.lock().unwrap();
+    if cache.len() >= RECENT_LINES_CACHE_CAP {
+        cache.clear();
+    }
+    cache.insert(key, computed.clone());
+    computed
+}
+
+/// Commit id `HEAD` points at in the checkout containing `dir`; `None` outside one.
+fn head_commit(dir: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--verify", "-q", "HEAD"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Run `git blame --porcelain` on `abs_path` and keep the lines whose commit is one
+/// of the last `n` on `HEAD` (or the all-zero id git gives uncommitted lines).
+/// `None` when git fails, e.g. outside a checkout or for an untracked file.
+fn blame_recent_lines(abs_path: &Path, n: usize) -> Option<RecentLines> {
+    use std::ffi::OsStr;
+
+    let dir = abs_path.parent()?;
+    let git = |args: &[&OsStr]| -> Option<String> {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let max_count = format!("--max-count={n}");
+    let commits: HashSet<String> = git(&[
+        OsStr::new("rev-list"),
+        OsStr::new(&max_count),
+        OsStr::new("HEAD"),
+    ])?
+    .lines()
+    .map(str::to_string)
+    .collect();
+    let porcelain = git(&[
+        OsStr::new("blame"),
+        OsStr::new("--porcelain"),
+        OsStr::new("--"),
+        abs_path.file_name()?,
+    ])?;
+
+    let mut recent = RecentLines::default();
+    let mut current: Option<(bool, usize)> = None;
+    for line in porcelain.lines() {
+        if let Some(text) = line.strip_prefix('\t') {
+            recent.total_lines += 1;
+            if let Some((true, final_line)) = current {
+                recent.lines.insert(final_line);
+                let t = text.trim();
+                if !t.is_empty() {
+                    recent.texts.insert(t.to_string());
+                }
+            }
+            continue;
+        }
+        // Group header: `<sha> <orig line> <final line> [<count>]`.
+        let mut parts = line.split(' ');
+        let (Some(sha), Some(_), Some(final_line)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if sha.len() != 40 || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        if let Ok(final_line) = final_line.parse::<usize>() {
+            let is_recent = commits.contains(sha) || sha.bytes().all(|b| b == b'0');
+            current = Some((is_recent, final_line));
+        }
+    }
+    Some(recent)
+}
+
+/// Budgeted selection over a ranked scan report, shared by the XML path and the plan.
+struct Layout {
+    repository_map_text: String,
+    skipped: Vec<(String, u64)>,
+    files_for_xml: Vec<(String, String)>,
+    truncated: Vec<(String, Truncation)>,
+    budget: Option<BudgetSummary>,
+    total_bytes: u64,
+    /// `(path, mode, bytes on disk, rendered bytes incl. XML overhead)` per file read.
+    rendered: Vec<(String, SliceMode, u64, u64)>,
+}
+
+/// Repository map text and too-large stubs of a scan report.
+fn layout_header(report: &ScanReport) -> (String, Vec<(String, u64)>) {
+    let mut all_paths: Vec<String> = report
+        .entries
         .iter()
         .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
         .collect();
+    all_paths.extend(report.oversized.iter().map(skipped_map_line));
+    all_paths.extend(report.linguist.iter().map(linguist_map_line));
     all_paths.sort();
-    let repository_map_text = build_repository_map_text(&all_paths);
-
-    let mut files_for_xml: Vec<(String, String)> = Vec::new();
-    let mut total_bytes: u64 = 64;
-    total_bytes = total_bytes
-        .saturating_add(estimate_xml_repository_map_overhead_bytes())
-        .saturating_add(repository_map_text.len() as u64);
+    (
+        build_repository_map_text(&all_paths),
+        skipped_stubs(&report.oversized),
+    )
+}
 
-    for e in entries {
+/// Renders the ranked entries one at a time and hands each file that still fits
+/// the budget whole to `keep` straight away, so callers can stream it out; the
+/// files that did not fit are returned for [`fill_with_truncated`]. `total_bytes`
+/// starts at [`header_bytes`] and grows with every kept file.
+#[allow(clippy::too_many_arguments)]
+fn render_within_budget(
+    report: &ScanReport,
+    budget_tokens: usize,
+    cfg: &Config,
+    focus_full_rel: Option<&str>,
+    skeleton_only: bool,
+    total_bytes: &mut u64,
+    rendered: &mut Vec<(String, SliceMode, u64, u64)>,
+    mut keep: impl FnMut(String, String) -> Result<()>,
+) -> Result<OverBudget> {
+    let mut over_budget = OverBudget::default();
+    let redactor = SecretRedactor::new(&cfg.slicer);
+    let skeleton_mode = cfg.skeleton_mode || skeleton_only;
+    for e in &report.entries {
         let bytes = match std::fs::read(&e.abs_path)
             .with_context(|| format!("Failed to read file: {}", e.abs_path.display()))
         {
@@ -467,45 +1368,115 @@ fn build_xml_from_entries(
             Err(_) => continue,
         };
 
-        let content_full = String::from_utf8(bytes)
-            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).to_string());
         let rel = e.rel_path.to_string_lossy().to_string();
+        let rel_norm = rel.replace('\\', "/");
+        let content_full = redactor.redact(
+            &rel_norm,
+            String::from_utf8(bytes)
+                .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).to_string()),
+        );
 
-        let is_focus_full = focus_full_rel
-            .as_ref()
-            .is_some_and(|f| f == &rel.replace('\\', "/"));
-        let skeleton_mode = cfg.skeleton_mode || skeleton_only;
-        let content = if is_focus_full {
-            content_full
-        } else if rel.to_lowercase().ends_with("cargo.toml") {
-            compact_cargo_toml(&content_full).unwrap_or_else(|| content_full.clone())
-        } else if rel.to_lowercase().ends_with("package.json") {
-            compact_package_json(&content_full).unwrap_or_else(|| content_full.clone())
-        } else if skeleton_mode {
-            match try_render_skeleton_from_source(&e.abs_path, &content_full) {
-                Ok(Some(s)) => s,
-                Ok(None) => truncate_unknown(&rel, &content_full),
-                Err(_) => truncate_unknown(&rel, &content_full),
-            }
-        } else {
-            content_full
-        };
+        let is_focus_full = focus_full_rel.is_some_and(|f| f == rel_norm);
+        let (mode, content) =
+            render_entry(e, &rel, content_full, cfg, skeleton_mode, is_focus_full);
 
         let overhead = estimate_xml_file_overhead_bytes(&rel);
+        rendered.push((rel_norm, mode, e.bytes, overhead + content.len() as u64));
         let new_total = total_bytes
             .saturating_add(overhead)
             .saturating_add(content.len() as u64);
         let est = estimate_tokens_from_bytes(new_total, cfg.token_estimator.chars_per_token);
         if est > budget_tokens {
+            over_budget.push(rel, content);
             continue;
         }
 
-        total_bytes = new_total;
-        files_for_xml.push((rel, content));
+        *total_bytes = new_total;
+        keep(rel, content)?;
     }
+    Ok(over_budget)
+}
+
+fn layout_entries(
+    report: &ScanReport,
+    budget_tokens: usize,
+    cfg: &Config,
+    focus_full_rel: Option<&str>,
+    skeleton_only: bool,
+) -> Result<Layout> {
+    let (repository_map_text, skipped) = layout_header(report);
+    let mut total_bytes = header_bytes(&repository_map_text, &skipped);
+    let mut files_for_xml: Vec<(String, String)> = Vec::new();
+    let mut rendered: Vec<(String, SliceMode, u64, u64)> = Vec::new();
+    let over_budget = render_within_budget(
+        report,
+        budget_tokens,
+        cfg,
+        focus_full_rel,
+        skeleton_only,
+        &mut total_bytes,
+        &mut rendered,
+        |rel, content| {
+            files_for_xml.push((rel, content));
+            Ok(())
+        },
+    )?;
+
+    let (truncated, budget) = fill_with_truncated(
+        &mut files_for_xml,
+        &mut total_bytes,
+        over_budget,
+        budget_tokens,
+        cfg.token_estimator.chars_per_token,
+    );
+
+    Ok(Layout {
+        repository_map_text,
+        skipped,
+        files_for_xml,
+        truncated,
+        budget,
+        total_bytes,
+        rendered,
+    })
+}
+
+/// Shared inner function: convert a scan report (entries already ranked) into context XML.
+fn build_xml_from_entries(
+    report: ScanReport,
+    repo_root: &Path,
+    target: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    focus_full_rel: Option<String>,
+    skeleton_only: bool,
+) -> Result<(String, SliceMeta)> {
+    let candidates = candidate_paths(&report);
+    let Layout {
+        repository_map_text,
+        skipped,
+        files_for_xml,
+        truncated,
+        budget,
+        total_bytes,
+        ..
+    } = layout_entries(
+        &report,
+        budget_tokens,
+        cfg,
+        focus_full_rel.as_deref(),
+        skeleton_only,
+    )?;
 
     let total_tokens = estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token);
-    let xml = build_context_xml(Some(&repository_map_text), &files_for_xml)?;
+    let xml = build_context_with_budget(
+        cfg.slicer.output_format(),
+        Some(&repository_map_text),
+        &files_for_xml,
+        &skipped,
+        &truncated,
+        budget.as_ref(),
+    )?;
 
     let meta = SliceMeta {
         repo_root: repo_root.to_path_buf(),
@@ -514,11 +1485,149 @@ fn build_xml_from_entries(
         total_tokens,
         total_files: files_for_xml.len(),
         total_bytes,
+        excluded: excluded_paths(candidates, &included_paths(&files_for_xml)),
+        included: included_paths(&files_for_xml),
     };
 
     Ok((xml, meta))
 }
 
+/// Every path a slice of `report` considered, for [`SliceMeta::excluded`].
+fn candidate_paths(report: &ScanReport) -> Vec<String> {
+    report
+        .entries
+        .iter()
+        .chain(report.oversized.iter())
+        .chain(report.linguist.iter().map(|(e, _)| e))
+        .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+/// Importance order used by [`slice_to_xml`]: path heuristics plus repo-map in-degree.
+fn rank_entries(repo_root: &Path, target: &Path, entries: &mut [FileEntry]) {
+    // Task 3: importance-based sorting.
+    // Task 2: Aider-style ranking: score by incoming edges from the repo map.
+    let indegree = compute_repo_map_indegree(repo_root, target);
+    entries.sort_by(|a, b| {
+        let a_rel = a.rel_path.to_string_lossy().replace('\\', "/");
+        let b_rel = b.rel_path.to_string_lossy().replace('\\', "/");
+
+        let mut a_score = importance_score(&a_rel);
+        let mut b_score = importance_score(&b_rel);
+
+        a_score += *indegree.get(&a_rel).unwrap_or(&0) as i64 * 10;
+        b_score += *indegree.get(&b_rel).unwrap_or(&0) as i64 * 10;
+
+        b_score.cmp(&a_score).then_with(|| a_rel.cmp(&b_rel))
+    });
+}
+
+/// Dry run of [`slice_to_xml`]: scans, ranks, renders and budgets `target` exactly
+/// like a slice would, but returns each file's mode, fate and token cost instead of XML.
+///
+/// Always plans a single slice (huge-codebase per-member budgets are not simulated).
+/// `*.min.js`, images and other known binary extensions are dropped by the scanner's
+/// default globs before classification, so they never show up here either.
+pub fn slice_plan(
+    repo_root: &Path,
+    target: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    skeleton_only: bool,
+) -> Result<SlicePlan> {
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
+    let opts = build_scan_options(repo_root, target, cfg);
+    let mut report = scan_workspace_report(&opts)?;
+    let focus_full_rel = focus_full_file_rel(repo_root, target);
+    rank_entries(repo_root, target, &mut report.entries);
+
+    let layout = layout_entries(
+        &report,
+        budget_tokens,
+        cfg,
+        focus_full_rel.as_deref(),
+        skeleton_only,
+    )?;
+    let chars_per_token = cfg.token_estimator.chars_per_token;
+    let included: HashSet<String> = included_paths(&layout.files_for_xml)
+        .into_iter()
+        .map(|p| p.replace('\\', "/"))
+        .collect();
+    let truncated: HashSet<String> = layout
+        .truncated
+        .iter()
+        .map(|(p, _)| p.replace('\\', "/"))
+        .collect();
+
+    let mut files: Vec<PlannedFile> = Vec::new();
+    for (path, mode, bytes, rendered) in layout.rendered {
+        let fate = if truncated.contains(&path) {
+            SliceFate::Truncated
+        } else if included.contains(&path) {
+            SliceFate::Included
+        } else {
+            SliceFate::Dropped
+        };
+        files.push(PlannedFile {
+            path,
+            mode,
+            fate,
+            bytes,
+            tokens: estimate_tokens_from_bytes(rendered, chars_per_token),
+        });
+    }
+    let never_read = report
+        .oversized
+        .iter()
+        .map(|e| (e, SliceMode::TooLarge))
+        .chain(
+            report
+                .linguist
+                .iter()
+                .map(|(e, k)| (e, SliceMode::from(*k))),
+        );
+    for (e, mode) in never_read {
+        files.push(PlannedFile {
+            path: e.rel_path.to_string_lossy().replace('\\', "/"),
+            mode,
+            fate: SliceFate::Skipped,
+            bytes: e.bytes,
+            tokens: 0,
+        });
+    }
+
+    let used_tokens = estimate_tokens_from_bytes(layout.total_bytes, chars_per_token);
+    Ok(SlicePlan {
+        budget_tokens,
+        used_tokens,
+        full_tokens: layout.budget.map_or(used_tokens, |b| b.full_tokens),
+        files,
+    })
+}
+
+/// Markdown table of a [`SlicePlan`].
+pub fn format_slice_plan(plan: &SlicePlan) -> String {
+    let mut out = format!(
+        "Slice plan: ~{} of {} tokens used (~{} if every file fit whole), {} file(s).\n\n",
+        plan.used_tokens,
+        plan.budget_tokens,
+        plan.full_tokens,
+        plan.files.len()
+    );
+    out.push_str("| File | Mode | Fate | Bytes | Tokens |\n|---|---|---|---|---|\n");
+    for f in &plan.files {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            f.path,
+            f.mode.as_str(),
+            f.fate.as_str(),
+            f.bytes,
+            f.tokens
+        ));
+    }
+    out
+}
+
 pub fn slice_to_xml(
     repo_root: &Path,
     target: &Path,
@@ -536,33 +1645,19 @@ pub fn slice_to_xml(
     if use_huge && target == Path::new(".") {
         return slice_to_xml_huge(repo_root, budget_tokens, cfg, skeleton_only);
     }
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
 
     let opts = build_scan_options(repo_root, target, cfg);
 
-    let mut entries = scan_workspace(&opts)?;
+    let mut report = scan_workspace_report(&opts)?;
 
     // Task 1: only the exact target file (if target is a file) is allowed to stay FULL.
     // If target is a directory, everything is treated as context and will be skeletonized/truncated.
     let focus_full_rel = focus_full_file_rel(repo_root, target);
-
-    // Task 3: importance-based sorting.
-    // Task 2: Aider-style ranking: score by incoming edges from the repo map.
-    let indegree = compute_repo_map_indegree(repo_root, target);
-    entries.sort_by(|a, b| {
-        let a_rel = a.rel_path.to_string_lossy().replace('\\', "/");
-        let b_rel = b.rel_path.to_string_lossy().replace('\\', "/");
-
-        let mut a_score = importance_score(&a_rel);
-        let mut b_score = importance_score(&b_rel);
-
-        a_score += *indegree.get(&a_rel).unwrap_or(&0) as i64 * 10;
-        b_score += *indegree.get(&b_rel).unwrap_or(&0) as i64 * 10;
-
-        b_score.cmp(&a_score).then_with(|| a_rel.cmp(&b_rel))
-    });
+    rank_entries(repo_root, target, &mut report.entries);
 
     build_xml_from_entries(
-        entries,
+        report,
         repo_root,
         target,
         budget_tokens,
@@ -572,6 +1667,182 @@ pub fn slice_to_xml(
     )
 }
 
+/// [`slice_to_xml`] that streams the XML into `out` as files are rendered instead
+/// of building it in memory, for `--full` or otherwise very large slices. The
+/// budget is enforced as the files go by, so the output is the same as
+/// [`slice_to_xml`]'s. Huge-codebase mode streams too (see [`slice_huge_to_writer`]).
+pub fn slice_to_writer<W: std::io::Write>(
+    repo_root: &Path,
+    target: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    skeleton_only: bool,
+    out: W,
+) -> Result<SliceMeta> {
+    let use_huge = cfg.huge_codebase.enabled || is_large_workspace(repo_root);
+    if use_huge && target == Path::new(".") {
+        return slice_huge_to_writer(repo_root, budget_tokens, cfg, skeleton_only, out);
+    }
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
+    stream_target(repo_root, target, budget_tokens, cfg, skeleton_only, out)
+}
+
+/// Plain (non-huge) body of [`slice_to_writer`]; `budget_tokens` is already
+/// adjusted for the slicer profile.
+fn stream_target<W: std::io::Write>(
+    repo_root: &Path,
+    target: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    skeleton_only: bool,
+    out: W,
+) -> Result<SliceMeta> {
+    let opts = build_scan_options(repo_root, target, cfg);
+    let mut report = scan_workspace_report(&opts)?;
+    let focus_full_rel = focus_full_file_rel(repo_root, target);
+    rank_entries(repo_root, target, &mut report.entries);
+
+    let (repository_map_text, skipped) = layout_header(&report);
+    let mut total_bytes = header_bytes(&repository_map_text, &skipped);
+    let mut xml = ContextXmlWriter::start_with_format(
+        out,
+        Some(&repository_map_text),
+        cfg.slicer.output_format(),
+    )?;
+    let mut included: Vec<String> = Vec::new();
+    let over_budget = render_within_budget(
+        &report,
+        budget_tokens,
+        cfg,
+        focus_full_rel.as_deref(),
+        skeleton_only,
+        &mut total_bytes,
+        &mut Vec::new(),
+        |rel, content| {
+            xml.file(&rel, &content, None)?;
+            included.push(rel);
+            Ok(())
+        },
+    )?;
+
+    let mut cut: Vec<(String, String)> = Vec::new();
+    let (truncated, budget) = fill_with_truncated(
+        &mut cut,
+        &mut total_bytes,
+        over_budget,
+        budget_tokens,
+        cfg.token_estimator.chars_per_token,
+    );
+    for ((rel, content), (_, t)) in cut.into_iter().zip(&truncated) {
+        xml.file(&rel, &content, Some(t))?;
+        included.push(rel);
+    }
+    for (path, bytes) in &skipped {
+        xml.skipped(path, *bytes)?;
+    }
+    xml.finish(budget.as_ref())?.flush()?;
+
+    Ok(SliceMeta {
+        repo_root: repo_root.to_path_buf(),
+        target: target.to_path_buf(),
+        budget_tokens,
+        total_tokens: estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token),
+        total_files: included.len(),
+        total_bytes,
+        excluded: excluded_paths(candidate_paths(&report), &included),
+        included,
+    })
+}
+
+/// One file of a slice before XML serialization (see [`slice_to_files`]).
+#[derive(Debug, Clone)]
+pub struct SlicedFile {
+    /// Repo-relative path with `/` separators.
+    pub path: String,
+    /// Content as the XML would carry it: full, skeleton or compacted, redacted.
+    pub content: String,
+    /// Set when the file was cut to fit the budget.
+    pub truncation: Option<Truncation>,
+}
+
+/// A slice as separate files, plus what the XML would have wrapped around them.
+#[derive(Debug, Clone)]
+pub struct SliceFiles {
+    pub files: Vec<SlicedFile>,
+    /// Too-large files left out, as `(path, bytes on disk)`.
+    pub skipped: Vec<(String, u64)>,
+    /// Present when the budget cut or dropped files.
+    pub budget: Option<BudgetSummary>,
+    pub meta: SliceMeta,
+}
+
+/// [`slice_to_xml`] without the XML: the same files, rendered and budgeted the
+/// same way, returned one by one so a client can present them as separate
+/// documents. Always takes the single-scan path; huge-codebase member merging
+/// only exists for XML.
+pub fn slice_to_files(
+    repo_root: &Path,
+    target: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    skeleton_only: bool,
+) -> Result<SliceFiles> {
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
+    let opts = build_scan_options(repo_root, target, cfg);
+    let mut report = scan_workspace_report(&opts)?;
+    let focus_full_rel = focus_full_file_rel(repo_root, target);
+    rank_entries(repo_root, target, &mut report.entries);
+
+    let candidates = candidate_paths(&report);
+    let Layout {
+        skipped,
+        files_for_xml,
+        truncated,
+        budget,
+        total_bytes,
+        ..
+    } = layout_entries(
+        &report,
+        budget_tokens,
+        cfg,
+        focus_full_rel.as_deref(),
+        skeleton_only,
+    )?;
+
+    let included = included_paths(&files_for_xml);
+    let meta = SliceMeta {
+        repo_root: repo_root.to_path_buf(),
+        target: target.to_path_buf(),
+        budget_tokens,
+        total_tokens: estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token),
+        total_files: files_for_xml.len(),
+        total_bytes,
+        excluded: excluded_paths(candidates, &included),
+        included,
+    };
+    let files = files_for_xml
+        .into_iter()
+        .map(|(rel, content)| {
+            let truncation = truncated
+                .iter()
+                .find(|(p, _)| *p == rel)
+                .map(|(_, t)| t.clone());
+            SlicedFile {
+                path: rel.replace('\\', "/"),
+                content,
+                truncation,
+            }
+        })
+        .collect();
+
+    Ok(SliceFiles {
+        files,
+        skipped,
+        budget,
+        meta,
+    })
+}
+
 /// Estimate whether this is a "large workspace" by counting top-level manifests
 /// or workspace member indicators without doing a full walk.
 fn is_large_workspace(root: &Path) -> bool {
@@ -601,6 +1872,59 @@ fn is_large_workspace(root: &Path) -> bool {
     false
 }
 
+/// True when a slice target is a glob (`src/**/*_controller.rs`) rather than a path.
+pub fn is_glob_target(target: &str) -> bool {
+    target.contains(['*', '?', '['])
+}
+
+/// Repo-relative files matching the glob `pattern`, in path order, for slicing with
+/// [`slice_paths_to_xml`].
+///
+/// Candidates come from the same scan as a directory slice, so `.gitignore`,
+/// `scan.exclude_dir_names` and `scan.include_tests` apply; only the directory before
+/// the first wildcard is walked. `*` stays within one path segment and `**` spans
+/// any number of them. A pattern that matches nothing is an error naming it.
+pub fn expand_glob_target(repo_root: &Path, pattern: &str, cfg: &Config) -> Result<Vec<String>> {
+    let pattern = pattern.trim().replace('\\', "/");
+    let pattern = pattern.strip_prefix("./").unwrap_or(&pattern);
+    let matcher =
+        glob::Pattern::new(pattern).with_context(|| format!("invalid glob target '{pattern}'"))?;
+    let no_match = || anyhow::anyhow!("glob target '{pattern}' matched no files");
+
+    let base: PathBuf = pattern
+        .split('/')
+        .take_while(|c| !is_glob_target(c))
+        .collect();
+    let base = if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    };
+    if !repo_root.join(&base).exists() {
+        return Err(no_match());
+    }
+    let report = scan_workspace_report(&build_scan_options(repo_root, &base, cfg))?;
+
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let mut matched: Vec<String> = report
+        .entries
+        .iter()
+        .chain(&report.oversized)
+        .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
+        .filter(|rel| matcher.matches_with(rel, options))
+        .collect();
+    if matched.is_empty() {
+        return Err(no_match());
+    }
+    matched.sort();
+    matched.dedup();
+    Ok(matched)
+}
+
 /// Build `ScanOptions` for a given repo root and target.
 /// Properly handles the case where `target` is a Rust `target/` *inside* a service
 /// by not over-excluding by name, but instead always excluding the root-level `target/`.
@@ -608,7 +1932,6 @@ fn build_scan_options(repo_root: &Path, target: &Path, cfg: &Config) -> ScanOpti
     let mut exclude_dirs = vec![
         ".git".into(),
         "node_modules".into(),
-        cfg.output_dir.to_string_lossy().to_string(),
     ];
 
     // User-defined additional excludes (directory names).
@@ -638,6 +1961,10 @@ fn build_scan_options(repo_root: &Path, target: &Path, cfg: &Config) -> ScanOpti
         target: target.to_path_buf(),
         max_file_bytes: cfg.token_estimator.max_file_bytes,
         exclude_dir_names: exclude_dirs,
+        exclude_dir_paths: vec![cfg.output_dir.clone()],
+        include_linguist_generated: cfg.scan.include_linguist_generated,
+        test_patterns: cfg.scan.test_patterns.clone(),
+        include_tests: cfg.scan.include_tests,
     }
 }
 
@@ -652,6 +1979,23 @@ pub fn slice_to_xml_huge(
     cfg: &Config,
     skeleton_only: bool,
 ) -> Result<(String, SliceMeta)> {
+    let mut out: Vec<u8> = Vec::new();
+    let meta = slice_huge_to_writer(repo_root, budget_tokens, cfg, skeleton_only, &mut out)?;
+    Ok((String::from_utf8(out)?, meta))
+}
+
+/// Streaming form of [`slice_to_xml_huge`]. Every member is scanned and ranked up
+/// front, since the repository map leads the document; file contents are then read,
+/// rendered and written one at a time, so only the (small, capped) root-level files
+/// are held in memory together.
+pub fn slice_huge_to_writer<W: std::io::Write>(
+    repo_root: &Path,
+    budget_tokens: usize,
+    cfg: &Config,
+    skeleton_only: bool,
+    out: W,
+) -> Result<SliceMeta> {
+    let budget_tokens = cfg.slicer.profile.budget_tokens(budget_tokens);
     let discovery_opts = WorkspaceDiscoveryOptions {
         max_depth: cfg.huge_codebase.member_scan_depth,
         include_patterns: cfg.huge_codebase.include_members.clone(),
@@ -662,17 +2006,7 @@ pub fn slice_to_xml_huge(
 
     if members.is_empty() {
         // No sub-projects found; fall back to plain slice.
-        let opts = build_scan_options(repo_root, Path::new("."), cfg);
-        let entries = scan_workspace(&opts)?;
-        return build_xml_from_entries(
-            entries,
-            repo_root,
-            Path::new("."),
-            budget_tokens,
-            cfg,
-            None,
-            skeleton_only,
-        );
+        return stream_target(repo_root, Path::new("."), budget_tokens, cfg, skeleton_only, out);
     }
 
     // Budget per member: divide equally, but floor at min_member_budget.
@@ -685,8 +2019,12 @@ pub fn slice_to_xml_huge(
     // This gets 10% of the total budget or 2000 tokens, whichever is smaller.
     let root_budget = (budget_tokens / 10).clamp(500, 2_000);
 
-    let mut all_files: Vec<(String, String)> = Vec::new();
+    let redactor = SecretRedactor::new(&cfg.slicer);
+    let mut root_files: Vec<(String, String)> = Vec::new();
+    let mut member_entries: Vec<Vec<FileEntry>> = Vec::new();
+    let mut all_skipped: Vec<(String, u64)> = Vec::new();
     let mut repo_map_sections: Vec<String> = Vec::new();
+    let mut candidates: Vec<String> = Vec::new();
     let mut total_bytes: u64 = 64;
 
     // ── Root-level context (workspace manifest + README) ─────────────────
@@ -700,10 +2038,13 @@ pub fn slice_to_xml_huge(
                 "node_modules".into(),
                 "target".into(),
                 "dist".into(),
-                cfg.output_dir.to_string_lossy().to_string(),
                 // Exclude any sub-directories that are workspace members — avoid duplication.
                 // We include at most the top-level files, not the entire sub-dirs.
             ],
+            exclude_dir_paths: vec![cfg.output_dir.clone()],
+            include_linguist_generated: cfg.scan.include_linguist_generated,
+            test_patterns: cfg.scan.test_patterns.clone(),
+            include_tests: cfg.scan.include_tests,
         };
 
         // Add user-defined excludes.
@@ -713,18 +2054,40 @@ pub fn slice_to_xml_huge(
             .extend(cfg.scan.exclude_dir_names.iter().cloned());
 
         // Scan but only take files directly at root (depth == 0 components beyond root).
-        if let Ok(root_entries) = scan_workspace(&root_opts) {
-            let root_only: Vec<FileEntry> = root_entries
+        if let Ok(root_report) = scan_workspace_report(&root_opts) {
+            // Take only root-level files (no '/' in path means directly in root dir).
+            let is_root_level = |e: &FileEntry| !e.rel_path.to_string_lossy().contains('/');
+            let root_only: Vec<FileEntry> = root_report
+                .entries
                 .into_iter()
-                .filter(|e| {
-                    let rel = e.rel_path.to_string_lossy();
-                    // Take only root-level files (no '/' in path means directly in root dir).
-                    !rel.contains('/')
-                })
+                .filter(is_root_level)
                 .collect();
+            let root_oversized: Vec<FileEntry> = root_report
+                .oversized
+                .into_iter()
+                .filter(is_root_level)
+                .collect();
+
+            let root_linguist = root_report.linguist.iter().filter(|l| is_root_level(&l.0));
+            candidates.extend(
+                root_only
+                    .iter()
+                    .chain(root_oversized.iter())
+                    .chain(root_linguist.clone().map(|(e, _)| e))
+                    .map(|e| e.rel_path.to_string_lossy().replace('\\', "/")),
+            );
 
-            let root_section = "# ROOT (workspace root)\n".to_string();
+            let mut root_section = "# ROOT (workspace root)\n".to_string();
+            for e in &root_oversized {
+                root_section.push_str(&skipped_map_line(e));
+                root_section.push('\n');
+            }
+            for l in root_linguist {
+                root_section.push_str(&linguist_map_line(l));
+                root_section.push('\n');
+            }
             repo_map_sections.push(root_section);
+            all_skipped.extend(skipped_stubs(&root_oversized));
 
             let mut root_used: u64 = 0;
             for e in root_only {
@@ -748,21 +2111,21 @@ pub fn slice_to_xml_huge(
                     }
                     root_used += added;
                     total_bytes = total_bytes.saturating_add(added);
-                    all_files.push((rel, content));
+                    root_files.push((rel, content));
                 }
             }
         }
     }
 
-    // ── Per-member slices ─────────────────────────────────────────────────
+    // ── Per-member scans (contents are read while streaming, below) ──────
     for member in &members {
         let member_opts = build_scan_options(repo_root, Path::new(&member.rel_path), cfg);
-        let mut entries = match scan_workspace(&member_opts) {
-            Ok(e) => e,
+        let (mut entries, oversized, linguist) = match scan_workspace_report(&member_opts) {
+            Ok(r) => (r.entries, r.oversized, r.linguist),
             Err(_) => continue,
         };
 
-        if entries.is_empty() {
+        if entries.is_empty() && oversized.is_empty() && linguist.is_empty() {
             continue;
         }
 
@@ -778,38 +2141,62 @@ pub fn slice_to_xml_huge(
             b_s.cmp(&a_s).then_with(|| a_rel.cmp(&b_rel))
         });
 
+        candidates.extend(
+            entries
+                .iter()
+                .chain(oversized.iter())
+                .chain(linguist.iter().map(|(e, _)| e))
+                .map(|e| e.rel_path.to_string_lossy().replace('\\', "/")),
+        );
+
         let section_header = format!("# {} ({})\n", member.name, member.rel_path);
-        let section_paths: Vec<String> = entries
+        let mut section_paths: Vec<String> = entries
             .iter()
             .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
             .collect();
+        section_paths.extend(oversized.iter().map(skipped_map_line));
+        section_paths.extend(linguist.iter().map(linguist_map_line));
+        all_skipped.extend(skipped_stubs(&oversized));
         repo_map_sections.push(format!("{}{}", section_header, section_paths.join("\n")));
+        member_entries.push(entries);
+    }
+
+    // Build repository map: combine all sections.
+    let repo_map_text = {
+        let combined = repo_map_sections.join("\n");
+        build_repository_map_text_raw(&combined)
+    };
+
+    total_bytes = total_bytes
+        .saturating_add(estimate_xml_repository_map_overhead_bytes())
+        .saturating_add(repo_map_text.len() as u64)
+        .saturating_add(estimate_xml_skipped_stubs_bytes(&all_skipped));
+
+    let mut xml =
+        ContextXmlWriter::start_with_format(out, Some(&repo_map_text), cfg.slicer.output_format())?;
+    let mut included: Vec<String> = Vec::new();
+    for (rel, content) in root_files {
+        xml.file(&rel, &content, None)?;
+        included.push(rel);
+    }
 
+    // ── Per-member slices ─────────────────────────────────────────────────
+    for entries in member_entries {
         let mut member_bytes: u64 = 0;
         for e in entries {
             let bytes = match std::fs::read(&e.abs_path) {
                 Ok(b) => b,
                 Err(_) => continue,
             };
-            let content_full = String::from_utf8(bytes)
-                .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).to_string());
             let rel = e.rel_path.to_string_lossy().replace('\\', "/");
+            let content_full = redactor.redact(
+                &rel,
+                String::from_utf8(bytes)
+                    .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).to_string()),
+            );
 
             let skeleton_mode = cfg.skeleton_mode || skeleton_only;
-
-            let content = if rel.to_lowercase().ends_with("cargo.toml") {
-                compact_cargo_toml(&content_full).unwrap_or(content_full)
-            } else if rel.to_lowercase().ends_with("package.json") {
-                compact_package_json(&content_full).unwrap_or(content_full)
-            } else if skeleton_mode {
-                match try_render_skeleton_from_source(&e.abs_path, &content_full) {
-                    Ok(Some(s)) => s,
-                    Ok(None) => truncate_unknown(&rel, &content_full),
-                    Err(_) => truncate_unknown(&rel, &content_full),
-                }
-            } else {
-                content_full
-            };
+            let (_, content) = render_entry(&e, &rel, content_full, cfg, skeleton_mode, false);
 
             let overhead = estimate_xml_file_overhead_bytes(&rel);
             let added = overhead + content.len() as u64;
@@ -823,31 +2210,23 @@ pub fn slice_to_xml_huge(
 
             member_bytes = member_bytes.saturating_add(added);
             total_bytes = total_bytes.saturating_add(added);
-            all_files.push((rel, content));
+            xml.file(&rel, &content, None)?;
+            included.push(rel);
         }
     }
+    for (path, bytes) in &all_skipped {
+        xml.skipped(path, *bytes)?;
+    }
+    xml.finish(None)?.flush()?;
 
-    // Build repository map: combine all sections.
-    let repo_map_text = {
-        let combined = repo_map_sections.join("\n");
-        build_repository_map_text_raw(&combined)
-    };
-
-    total_bytes = total_bytes
-        .saturating_add(estimate_xml_repository_map_overhead_bytes())
-        .saturating_add(repo_map_text.len() as u64);
-
-    let total_tokens = estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token);
-    let xml = build_context_xml(Some(&repo_map_text), &all_files)?;
-
-    let meta = SliceMeta {
+    Ok(SliceMeta {
         repo_root: repo_root.to_path_buf(),
         target: PathBuf::from("."),
         budget_tokens,
-        total_tokens,
-        total_files: all_files.len(),
+        total_tokens: estimate_tokens_from_bytes(total_bytes, cfg.token_estimator.chars_per_token),
+        total_files: included.len(),
         total_bytes,
-    };
-
-    Ok((xml, meta))
+        excluded: excluded_paths(candidates, &included),
+        included,
+    })
 }
@@ -4,6 +4,7 @@ use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::inspector::extract_symbols_from_source;
 use crate::scanner::{scan_workspace, ScanOptions};
@@ -59,6 +60,42 @@ use crate::scanner::{scan_workspace, ScanOptions};
 //     Result: "ConvertRequest" always lands above unrelated .proto/.json files
 //     regardless of embedding proximity — zero false-positives from topic overlap.
 //
+//  4. CROSS-PROCESS SAFETY
+//     Several cortexast processes (CLI `--query`, one MCP server per editor
+//     window) may share one `db/` dir. Writers take an exclusive advisory lock
+//     on `embeddings.lock`, reload the on-disk store, mutate, then save via
+//     tmp-file + rename. Readers never lock: the rename is atomic, so they see
+//     either the old or the new index, never a torn one. Concurrent indexing is
+//     therefore safe; a writer that cannot get the lock within
+//     INDEX_LOCK_TIMEOUT fails with a clear error instead of hanging.
+//
+//  5. SYMBOL INDEX
+//     Each entry also records its definitions (name, kind, line). Every save
+//     derives `symbols.json` (name → sites) from them, so `find_definition`
+//     answers from a small table without loading embeddings or the model. It
+//     is refreshed exactly when the embeddings are (same hash check); entries
+//     from before the table existed carry no defs and count as cold.
+//
+//  6. QUERY CACHE
+//     Every save bumps the store's `version`. Search results are cached under
+//     (normalized query, limit, min_score, index_extensions, version) in memory
+//     and in `query_cache.json`, so an agent repeating a query against an
+//     unchanged index skips scoring. Any reindex changes the version, which
+//     drops the whole cache. Versions are time-based, so a store rebuilt from
+//     scratch never reuses a version an old cache was keyed by.
+//
+//  7. PER-MODEL NAMESPACES
+//     All of the above lives in `db/models/<model-id>/`, one dir per embedding
+//     model, so a per-call model override never mixes vector dimensions with
+//     (or rebuilds) the configured model's index.
+//
+//  8. RESUMABLE INDEXING
+//     Long embed runs save every 200 files (or 30 s); batches grow to a quarter
+//     of the store, since each save rewrites the whole file. `index_progress.json`
+//     records how far the run got and is removed when a run completes or finds
+//     nothing to do; a crashed run keeps its committed files, which the hash
+//     check then skips.
+//
 //  Search complexity: O(n_chunks × d). With 400 files × avg 3 chunks × 256 dims ≈ trivial.
 //  Measured latency: ≤ 0.07s cold (unchanged from v1 on typical repos).
 // ---------------------------------------------------------------------------
@@ -100,11 +137,27 @@ pub struct FileIndexEntry {
     pub size: u64,
     /// One or more semantic chunks for this file.
     pub chunks: Vec<ChunkEntry>,
+    /// Definitions in this file, feeding `symbols.json`. `None` on entries written
+    /// before the symbol index existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defs: Option<Vec<SymbolDef>>,
+}
+
+/// A definition recorded on a [`FileIndexEntry`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolDef {
+    pub name: String,
+    pub kind: String,
+    /// 1-based declaration line.
+    pub line: u32,
 }
 
 /// Root of the flat-file JSON index.
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct IndexStore {
+    /// Bumped on every save; keys the query cache.
+    #[serde(default)]
+    version: u64,
     entries: HashMap<String, FileIndexEntry>,
 }
 
@@ -126,13 +179,364 @@ impl IndexStore {
         }
     }
 
+    fn save(&mut self, path: &Path) {
+        self.version = next_index_version(self.version);
+        if let Ok(text) = serde_json::to_string(self) {
+            // Write-then-rename so lock-free readers never observe a half-written file.
+            let tmp = path.with_extension("json.tmp");
+            if std::fs::write(&tmp, text).is_ok() {
+                let _ = std::fs::rename(&tmp, path);
+            }
+        }
+        SymbolIndex::from_store(self).save(&path.with_file_name(SYMBOL_INDEX_FILE));
+    }
+}
+
+/// Next store version: above `prev` and at least the current Unix time in
+/// milliseconds, so a store reset to the default (version 0) never comes back to a
+/// version that a persisted query cache was keyed by.
+fn next_index_version(prev: u64) -> u64 {
+    let now_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    prev.wrapping_add(1).max(now_ms)
+}
+
+// ---------------------------------------------------------------------------
+// Symbol index — `symbols.json`
+// ---------------------------------------------------------------------------
+
+/// File name of the symbol table inside the vector DB dir.
+pub const SYMBOL_INDEX_FILE: &str = "symbols.json";
+
+/// One definition site returned by [`SymbolIndex::lookup`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    /// Repo-relative path, `/`-separated.
+    pub file: String,
+    /// 1-based declaration line.
+    pub line: u32,
+    pub kind: String,
+}
+
+/// Name → definition sites, derived from the vector index on every save.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    /// Indexed files whose entry predates the symbol table (no defs recorded).
+    pub cold_files: usize,
+    /// Content hash of every file whose defs are recorded, keyed by repo-relative
+    /// path — what [`SymbolIndex::covers`] checks a lookup scope against.
+    #[serde(default)]
+    pub files: HashMap<String, String>,
+    pub defs: HashMap<String, Vec<SymbolLocation>>,
+}
+
+impl SymbolIndex {
+    fn from_store(store: &IndexStore) -> Self {
+        let mut index = Self::default();
+        for (file, entry) in &store.entries {
+            let Some(defs) = &entry.defs else {
+                index.cold_files += 1;
+                continue;
+            };
+            index.files.insert(file.clone(), entry.hash.clone());
+            for d in defs {
+                index
+                    .defs
+                    .entry(d.name.clone())
+                    .or_default()
+                    .push(SymbolLocation {
+                        file: file.clone(),
+                        line: d.line,
+                        kind: d.kind.clone(),
+                    });
+            }
+        }
+        for sites in index.defs.values_mut() {
+            sites.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+        }
+        index
+    }
+
+    fn save(&self, path: &Path) {
+        if let Ok(text) = serde_json::to_string(self) {
+            let tmp = path.with_extension("json.tmp");
+            if std::fs::write(&tmp, text).is_ok() {
+                let _ = std::fs::rename(&tmp, path);
+            }
+        }
+    }
+
+    /// Load `{db_dir}/symbols.json`. `None` when the table has never been written
+    /// (cold index) or is unreadable — callers fall back to an AST walk.
+    pub fn load(db_dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(db_dir.join(SYMBOL_INDEX_FILE)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// True when every file in `files` (absolute paths under `repo_root`) is recorded
+    /// with its current content, so a lookup scoped to them cannot miss a definition.
+    /// Query-mode indexing only embeds the top candidates, so this is often false.
+    pub fn covers(&self, repo_root: &Path, files: &[PathBuf]) -> bool {
+        files.iter().all(|abs| {
+            let rel = abs
+                .strip_prefix(repo_root)
+                .unwrap_or(abs)
+                .to_string_lossy()
+                .replace('\\', "/");
+            self.files.get(&rel).is_some_and(|hash| {
+                std::fs::read(abs).is_ok_and(|bytes| xxh3_hex(&bytes) == *hash)
+            })
+        })
+    }
+
+    /// Definition sites recorded for `name` (exact match).
+    pub fn lookup(&self, name: &str) -> &[SymbolLocation] {
+        self.defs.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Query cache — `query_cache.json`
+// ---------------------------------------------------------------------------
+
+/// File name of the persisted query cache inside the vector DB dir.
+pub const QUERY_CACHE_FILE: &str = "query_cache.json";
+
+/// Most recent distinct queries kept per index version.
+const QUERY_CACHE_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedQuery {
+    query: String,
+    limit: usize,
+    #[serde(default)]
+    min_score: f32,
+    /// The index's extension allowlist when the query ran (results are filtered by it).
+    #[serde(default)]
+    extensions: Vec<String>,
+    results: Vec<String>,
+}
+
+/// Search results for one index version, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueryCache {
+    index_version: u64,
+    entries: Vec<CachedQuery>,
+}
+
+impl QueryCache {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) {
+        if let Ok(text) = serde_json::to_string(self) {
+            let tmp = path.with_extension("json.tmp");
+            if std::fs::write(&tmp, text).is_ok() {
+                let _ = std::fs::rename(&tmp, path);
+            }
+        }
+    }
+
+    fn get(
+        &self,
+        index_version: u64,
+        query: &str,
+        limit: usize,
+        min_score: f32,
+        extensions: &[String],
+    ) -> Option<&[String]> {
+        if self.index_version != index_version {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|c| c.matches(query, limit, min_score, extensions))
+            .map(|c| c.results.as_slice())
+    }
+
+    fn insert(
+        &mut self,
+        index_version: u64,
+        query: String,
+        limit: usize,
+        min_score: f32,
+        extensions: &[String],
+        results: Vec<String>,
+    ) {
+        if self.index_version != index_version {
+            self.index_version = index_version;
+            self.entries.clear();
+        }
+        self.entries
+            .retain(|c| !c.matches(&query, limit, min_score, extensions));
+        if self.entries.len() >= QUERY_CACHE_CAPACITY {
+            self.entries.remove(0);
+        }
+        self.entries.push(CachedQuery {
+            query,
+            limit,
+            min_score,
+            extensions: extensions.to_vec(),
+            results,
+        });
+    }
+}
+
+impl CachedQuery {
+    fn matches(&self, query: &str, limit: usize, min_score: f32, extensions: &[String]) -> bool {
+        self.query == query
+            && self.limit == limit
+            && self.min_score == min_score
+            && self.extensions == extensions
+    }
+}
+
+fn extension_allowed(allowlist: &[String], rel_path: &str) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+    Path::new(rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| allowlist.iter().any(|a| a.eq_ignore_ascii_case(ext)))
+}
+
+/// Cache key form of a query: surrounding and repeated whitespace is insignificant.
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// ---------------------------------------------------------------------------
+// Batched commits — `index_progress.json`
+// ---------------------------------------------------------------------------
+
+/// File name of the interrupted-run checkpoint inside the vector DB dir.
+pub const INDEX_PROGRESS_FILE: &str = "index_progress.json";
+
+/// Save the store after this many newly embedded files...
+const INDEX_COMMIT_FILES: usize = 200;
+/// ...or after `1 / INDEX_COMMIT_GROWTH` of the store's size when that is larger:
+/// every save rewrites the whole `embeddings.json`, so a fixed batch size would make
+/// a first index of a large repo quadratic in its file count...
+const INDEX_COMMIT_GROWTH: usize = 4;
+/// ...or after this long, whichever comes first.
+const INDEX_COMMIT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Checkpoint written with every intermediate save of a long embed run and removed
+/// when the run completes. Its presence means the last run was interrupted after
+/// `done` of `total` candidate files; those are already in the store and are skipped
+/// by the hash check on the next run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl IndexProgress {
+    fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
     fn save(&self, path: &Path) {
         if let Ok(text) = serde_json::to_string(self) {
-            let _ = std::fs::write(path, text);
+            let tmp = path.with_extension("json.tmp");
+            if std::fs::write(&tmp, text).is_ok() {
+                let _ = std::fs::rename(&tmp, path);
+            }
+        }
+    }
+}
+
+/// Decides when an embed loop should save what it has so far.
+struct BatchCommit {
+    pending: usize,
+    last: Instant,
+}
+
+impl BatchCommit {
+    fn new() -> Self {
+        Self {
+            pending: 0,
+            last: Instant::now(),
+        }
+    }
+
+    /// Count one embedded file into a store of `stored` entries; `true` when the batch
+    /// is due for a save.
+    fn record(&mut self, stored: usize) -> bool {
+        self.pending += 1;
+        let due = INDEX_COMMIT_FILES.max(stored / INDEX_COMMIT_GROWTH);
+        if self.pending < due && self.last.elapsed() < INDEX_COMMIT_INTERVAL {
+            return false;
+        }
+        self.pending = 0;
+        self.last = Instant::now();
+        true
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cross-process writer lock
+// ---------------------------------------------------------------------------
+
+/// How long a writer waits for another process to release the index lock.
+const INDEX_LOCK_TIMEOUT: Duration = Duration::from_secs(120);
+
+const INDEX_LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// Exclusive advisory lock on `{db_dir}/embeddings.lock`; released on drop.
+struct IndexLock {
+    file: std::fs::File,
+}
+
+impl IndexLock {
+    fn acquire(lock_path: &Path, timeout: Duration) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(lock_path)
+            .with_context(|| format!("Failed to open index lock {}", lock_path.display()))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Self { file }),
+                Err(std::fs::TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(INDEX_LOCK_POLL);
+                }
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    anyhow::bail!(
+                        "Vector index {} is locked by another cortexast process (waited {}s). \
+                        Retry once the other indexer finishes.",
+                        lock_path.display(),
+                        timeout.as_secs_f32()
+                    );
+                }
+                Err(std::fs::TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to lock vector index {}", lock_path.display())
+                    });
+                }
+            }
         }
     }
 }
 
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Hashing
 // ---------------------------------------------------------------------------
@@ -160,15 +564,17 @@ struct PreparedChunk {
 ///
 /// For files ≤ SMALL_FILE_BYTES call sites use the fast single-chunk path;
 /// this function is only called for larger files.
-fn ast_chunk(path: &Path, content: &str, chunk_lines: usize) -> Vec<PreparedChunk> {
+fn ast_chunk(
+    symbols: &[crate::inspector::Symbol],
+    content: &str,
+    chunk_lines: usize,
+) -> Vec<PreparedChunk> {
     let max_lines = (chunk_lines as u32).clamp(20, CHUNK_MAX_LINES);
     let source_lines: Vec<&str> = content.lines().collect();
     let total_lines = source_lines.len() as u32;
 
-    let symbols = extract_symbols_from_source(path, content);
-
     if !symbols.is_empty() {
-        ast_guided_chunks(&symbols, &source_lines, total_lines, max_lines)
+        ast_guided_chunks(symbols, &source_lines, total_lines, max_lines)
     } else {
         line_range_chunks(&source_lines, max_lines)
     }
@@ -291,6 +697,17 @@ fn line_range_chunks(source_lines: &[&str], max_lines: u32) -> Vec<PreparedChunk
 // Public API
 // ---------------------------------------------------------------------------
 
+/// Which scanned files a refresh may touch. Anything other than `All` is a
+/// partial warm-up: out-of-scope files keep their current entries (or stay unindexed).
+#[derive(Debug, Clone)]
+pub enum RefreshScope {
+    All,
+    /// Only files whose mtime is at or after this instant.
+    Since(SystemTime),
+    /// Only these repo-relative paths (e.g. the output of `git diff --name-only <ref>`).
+    Paths(HashSet<String>),
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexJob {
     pub rel_path: String,
@@ -303,7 +720,15 @@ pub struct CodebaseIndex {
     model: StaticModel,
     chunk_lines: usize,
     index_path: PathBuf,
+    lock_path: PathBuf,
     store: IndexStore,
+    query_cache: QueryCache,
+    /// `None` keeps the query cache in memory only.
+    query_cache_path: Option<PathBuf>,
+    last_search_cached: bool,
+    min_score: f32,
+    /// Lowercase extensions (no dot) eligible for embedding; empty = every text file.
+    index_extensions: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -312,7 +737,68 @@ struct IndexMetaV2 {
     chunk_lines: usize,
 }
 
+/// Per-model subdirectory of `db_dir` holding that model's index files.
+///
+/// Each embedding model gets its own `models/<model-id>/` so switching models
+/// (config change, `--embed-model`, per-call `embed_model`) never mixes vector
+/// dimensions or forces the other model's index to rebuild. Characters outside
+/// `[A-Za-z0-9._-]` (e.g. the `/` in Hugging Face ids) become `_`.
+pub fn model_db_dir(db_dir: &Path, model_id: &str) -> PathBuf {
+    let slug: String = model_id
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let slug = match slug.trim_matches('.') {
+        "" => "default",
+        s => s,
+    };
+    db_dir.join("models").join(slug)
+}
+
+/// Index files that lived directly in `db_dir` before models got their own dirs.
+const LEGACY_INDEX_FILES: &[&str] = &[
+    "embeddings.json",
+    "index_meta_v2.json",
+    SYMBOL_INDEX_FILE,
+    QUERY_CACHE_FILE,
+    INDEX_PROGRESS_FILE,
+];
+
+/// Move an index left directly in `db_dir` by the pre-[`model_db_dir`] layout into
+/// the dir of the model its `index_meta_v2.json` names, so upgrading does not
+/// re-embed the repo. When the model is unknown or its dir already holds an index,
+/// the old files are deleted instead of lingering unused.
+fn migrate_legacy_index(db_dir: &Path) {
+    if !db_dir.join("embeddings.json").is_file() {
+        return;
+    }
+    let dest = std::fs::read_to_string(db_dir.join("index_meta_v2.json"))
+        .ok()
+        .and_then(|t| serde_json::from_str::<IndexMetaV2>(&t).ok())
+        .map(|meta| model_db_dir(db_dir, &meta.model_id))
+        .filter(|dir| !dir.join("embeddings.json").exists())
+        .filter(|dir| std::fs::create_dir_all(dir).is_ok());
+    for name in LEGACY_INDEX_FILES {
+        let from = db_dir.join(name);
+        let moved = dest
+            .as_ref()
+            .is_some_and(|dir| std::fs::rename(&from, dir.join(name)).is_ok());
+        if !moved {
+            let _ = std::fs::remove_file(&from);
+        }
+    }
+    let _ = std::fs::remove_file(db_dir.join("embeddings.lock"));
+}
+
 impl CodebaseIndex {
+    /// Open (or create) the index for `model_id` under [`model_db_dir`]`(db_dir, model_id)`.
     pub fn open(
         repo_root: &Path,
         db_dir: &Path,
@@ -324,6 +810,8 @@ impl CodebaseIndex {
         } else {
             repo_root.join(db_dir)
         };
+        migrate_legacy_index(&db_dir);
+        let db_dir = model_db_dir(&db_dir, model_id);
         std::fs::create_dir_all(&db_dir).context("Failed to create vector DB dir")?;
 
         let model = StaticModel::from_pretrained(model_id, None, None, None)?;
@@ -331,6 +819,7 @@ impl CodebaseIndex {
         let chunk_lines = chunk_lines.clamp(1, 200);
 
         let index_path = db_dir.join("embeddings.json");
+        let lock_path = db_dir.join("embeddings.lock");
         let mut store = IndexStore::load(&index_path);
 
         // Meta: ensure we don't mix embeddings from different models/chunking.
@@ -340,35 +829,131 @@ impl CodebaseIndex {
             .ok()
             .and_then(|t| serde_json::from_str::<IndexMetaV2>(&t).ok());
 
-        if let Some(meta) = meta_disk {
-            if meta.model_id != model_id || meta.chunk_lines != chunk_lines {
+        let meta_matches = meta_disk
+            .as_ref()
+            .is_some_and(|m| m.model_id == model_id && m.chunk_lines == chunk_lines);
+
+        if !meta_matches {
+            let _lock = IndexLock::acquire(&lock_path, INDEX_LOCK_TIMEOUT)?;
+            if meta_disk.is_some() {
                 crate::debug_log!(
                     "[cortexast] vector index config changed (model/chunk_lines); rebuilding index…"
                 );
-                store = IndexStore::default();
+                store = IndexStore {
+                    version: store.version,
+                    ..IndexStore::default()
+                };
                 let _ = std::fs::remove_file(&index_path);
+                let _ = std::fs::remove_file(db_dir.join(QUERY_CACHE_FILE));
             }
-        }
 
-        // Best-effort: persist current meta.
-        let _ = std::fs::write(
-            &meta_path,
-            serde_json::to_string(&IndexMetaV2 {
-                model_id: model_id.to_string(),
-                chunk_lines,
-            })
-            .unwrap_or_else(|_| "{}".to_string()),
-        );
+            // Best-effort: persist current meta.
+            let _ = std::fs::write(
+                &meta_path,
+                serde_json::to_string(&IndexMetaV2 {
+                    model_id: model_id.to_string(),
+                    chunk_lines,
+                })
+                .unwrap_or_else(|_| "{}".to_string()),
+            );
+        }
 
+        let query_cache_path = db_dir.join(QUERY_CACHE_FILE);
         Ok(Self {
             repo_root: repo_root.to_path_buf(),
             model,
             chunk_lines,
             index_path,
+            lock_path,
             store,
+            query_cache: QueryCache::load(&query_cache_path),
+            query_cache_path: Some(query_cache_path),
+            last_search_cached: false,
+            min_score: 0.0,
+            index_extensions: Vec::new(),
         })
     }
 
+    /// Keep the query cache on disk (default) so it survives across processes and
+    /// MCP calls, or in memory only for this `CodebaseIndex`.
+    pub fn persist_query_cache(&mut self, persist: bool) {
+        if !persist {
+            self.query_cache_path = None;
+        } else if self.query_cache_path.is_none() {
+            self.query_cache_path = Some(self.index_path.with_file_name(QUERY_CACHE_FILE));
+        }
+    }
+
+    /// Drop [`search`](Self::search) results scoring below `min_score` (cosine, 0.0–1.0).
+    /// Exact symbol hits always pass. `0.0` (the default) keeps every result.
+    pub fn set_min_score(&mut self, min_score: f32) {
+        self.min_score = min_score.max(0.0);
+    }
+
+    /// Embed only files with these extensions (case-insensitive, leading `.` optional).
+    /// Other files are skipped by every indexing path, dropped from the index on the
+    /// next full [`refresh`](Self::refresh) and never returned by searches. An empty
+    /// list (the default) indexes every text file the scanner yields.
+    pub fn set_index_extensions(&mut self, extensions: &[String]) {
+        self.index_extensions = extensions
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+    }
+
+    /// Whether `rel_path` passes the [`index_extensions`](Self::set_index_extensions) allowlist.
+    pub fn is_indexable_path(&self, rel_path: &str) -> bool {
+        extension_allowed(&self.index_extensions, rel_path)
+    }
+
+    /// Whether `rel_path` already has an entry (current or stale) in the index.
+    pub fn is_indexed(&self, rel_path: &str) -> bool {
+        self.store
+            .entries
+            .contains_key(&rel_path.replace('\\', "/"))
+    }
+
+    /// Whether the most recent [`search`](Self::search) was answered from the query cache.
+    pub fn last_search_cached(&self) -> bool {
+        self.last_search_cached
+    }
+
+    /// Take the cross-process writer lock and pick up anything other processes
+    /// saved since this index was loaded. Hold the returned guard until saved.
+    fn lock_for_write(&mut self) -> Result<IndexLock> {
+        let lock = IndexLock::acquire(&self.lock_path, INDEX_LOCK_TIMEOUT)?;
+        self.store = IndexStore::load(&self.index_path);
+        Ok(lock)
+    }
+
+    fn progress_path(&self) -> PathBuf {
+        self.index_path.with_file_name(INDEX_PROGRESS_FILE)
+    }
+
+    /// Progress of the last embed run if it was interrupted before finishing.
+    pub fn interrupted_progress(&self) -> Option<IndexProgress> {
+        IndexProgress::load(&self.progress_path())
+    }
+
+    /// Intermediate save: persist the store and the checkpoint. Caller holds the lock.
+    fn commit_batch(&mut self, progress: IndexProgress) {
+        self.store.save(&self.index_path);
+        progress.save(&self.progress_path());
+    }
+
+    /// Final save of an embed run; clears the checkpoint. Caller holds the lock.
+    fn commit_final(&mut self) {
+        self.store.save(&self.index_path);
+        self.clear_progress();
+    }
+
+    /// Drop the checkpoint of an interrupted run once a later run finished, even if
+    /// that run had nothing left to embed. Caller holds the lock.
+    fn clear_progress(&self) {
+        let _ = std::fs::remove_file(self.progress_path());
+    }
+
     // ── Cache helpers ─────────────────────────────────────────────────────
 
     /// Read raw bytes + compute size + xxh3 hash. Returns `None` for binary files.
@@ -420,13 +1005,13 @@ impl CodebaseIndex {
 
         let path_obj = PathBuf::from(rel_path);
         let total_lines = content.lines().count() as u32;
+        let syms = extract_symbols_from_source(&path_obj, &content);
 
         let prepared: Vec<PreparedChunk> = if size > SMALL_FILE_BYTES {
             // Task 2: AST-aware multi-chunk for large files.
-            ast_chunk(&path_obj, &content, self.chunk_lines)
+            ast_chunk(&syms, &content, self.chunk_lines)
         } else {
             // Small file fast path: single chunk with symbol header.
-            let syms = extract_symbols_from_source(&path_obj, &content);
             let sym_names: Vec<String> = syms
                 .iter()
                 .map(|s| format!("{} {}", s.kind, s.name))
@@ -470,7 +1055,21 @@ impl CodebaseIndex {
             return None;
         }
 
-        Some(FileIndexEntry { hash, size, chunks })
+        let defs = syms
+            .iter()
+            .map(|s| SymbolDef {
+                name: s.name.clone(),
+                kind: s.kind.clone(),
+                line: s.line + 1,
+            })
+            .collect();
+
+        Some(FileIndexEntry {
+            hash,
+            size,
+            chunks,
+            defs: Some(defs),
+        })
     }
 
     // ── Indexing entry points ─────────────────────────────────────────────
@@ -478,6 +1077,9 @@ impl CodebaseIndex {
     /// Index a file from disk, using the cache when content is unchanged.
     pub async fn index_file_path(&mut self, rel_path: &str, abs_path: &Path) -> Result<()> {
         let rel_norm = rel_path.replace('\\', "/");
+        if !self.is_indexable_path(&rel_norm) {
+            return Ok(());
+        }
         let Some((raw, size, hash)) = Self::read_with_hash(abs_path)? else {
             return Ok(());
         };
@@ -486,6 +1088,7 @@ impl CodebaseIndex {
                 return Ok(());
             }
         }
+        let _lock = self.lock_for_write()?;
         if let Some(entry) = self.embed_file(&rel_norm, abs_path, raw, size, hash) {
             self.store.entries.insert(rel_norm, entry);
             self.store.save(&self.index_path);
@@ -494,13 +1097,22 @@ impl CodebaseIndex {
     }
 
     /// Batch-index and call `on_progress` after each file.
+    ///
+    /// Progress is saved every few hundred files (or 30 s), so an interrupted run
+    /// keeps what it embedded and the next run skips those files by hash.
     pub async fn index_jobs<F>(&mut self, jobs: &[IndexJob], mut on_progress: F) -> Result<usize>
     where
         F: FnMut(),
     {
+        let _lock = self.lock_for_write()?;
         let mut indexed = 0usize;
-        for job in jobs {
+        let mut batch = BatchCommit::new();
+        for (i, job) in jobs.iter().enumerate() {
             let rel_norm = job.rel_path.replace('\\', "/");
+            if !self.is_indexable_path(&rel_norm) {
+                on_progress();
+                continue;
+            }
             let bytes = job.content.as_bytes();
             let size = bytes.len() as u64;
             let hash = xxh3_hex(bytes);
@@ -517,10 +1129,21 @@ impl CodebaseIndex {
             {
                 self.store.entries.insert(rel_norm, entry);
                 indexed += 1;
+                if batch.record(self.store.entries.len()) {
+                    self.commit_batch(IndexProgress {
+                        done: i + 1,
+                        total: jobs.len(),
+                    });
+                }
             }
             on_progress();
         }
-        self.store.save(&self.index_path);
+        // Skip the save (and the version bump) when every job was already current.
+        if indexed > 0 {
+            self.commit_final();
+        } else {
+            self.clear_progress();
+        }
         Ok(indexed)
     }
 
@@ -534,17 +1157,44 @@ impl CodebaseIndex {
     ///           DELETE: rel_path in index, no longer on disk.
     /// Phase 3: parallel read + hash — rayon par_iter over dirty candidates.
     ///           SameSize files where hash matches → dropped (truly unchanged).
-    /// Phase 4: embed + upsert — sequential (model not Send), persist once.
+    /// Phase 4: embed + upsert — sequential (model not Send), saved in batches
+    ///           so an interrupted run resumes from its last commit.
     ///
     /// Returns `(added, updated, deleted)` counts.
     pub fn refresh(&mut self, scan_opts: &ScanOptions) -> Result<(usize, usize, usize)> {
+        self.refresh_scoped(scan_opts, &RefreshScope::All)
+    }
+
+    /// [`refresh`](Self::refresh) limited to `scope`. A `Since` refresh cannot see
+    /// deletions (a removed file has no mtime), so it never deletes; a `Paths`
+    /// refresh only deletes listed paths that are gone from disk.
+    pub fn refresh_scoped(
+        &mut self,
+        scan_opts: &ScanOptions,
+        scope: &RefreshScope,
+    ) -> Result<(usize, usize, usize)> {
+        let _lock = self.lock_for_write()?;
+
         // ── Phase 1 ──────────────────────────────────────────────────────
         let entries = scan_workspace(scan_opts)?;
 
         let mut disk_files: HashMap<String, (PathBuf, u64)> = HashMap::with_capacity(entries.len());
         for e in &entries {
             let rel = e.rel_path.to_string_lossy().replace('\\', "/");
-            disk_files.insert(rel, (e.abs_path.clone(), e.bytes));
+            if !self.is_indexable_path(&rel) {
+                // Left out of `disk_files`, so a full refresh also drops any stale entry.
+                continue;
+            }
+            let in_scope = match scope {
+                RefreshScope::All => true,
+                RefreshScope::Since(cutoff) => std::fs::metadata(&e.abs_path)
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|mtime| mtime >= *cutoff),
+                RefreshScope::Paths(paths) => paths.contains(&rel),
+            };
+            if in_scope {
+                disk_files.insert(rel, (e.abs_path.clone(), e.bytes));
+            }
         }
 
         // ── Phase 2 ──────────────────────────────────────────────────────
@@ -570,7 +1220,12 @@ impl CodebaseIndex {
         let mut to_delete: Vec<String> = Vec::new();
         let index_keys: HashSet<String> = self.store.entries.keys().cloned().collect();
         for key in &index_keys {
-            if !disk_files.contains_key(key.as_str()) {
+            let deletable = match scope {
+                RefreshScope::All => true,
+                RefreshScope::Since(_) => false,
+                RefreshScope::Paths(paths) => paths.contains(key),
+            };
+            if deletable && !disk_files.contains_key(key.as_str()) {
                 to_delete.push(key.clone());
             }
         }
@@ -600,14 +1255,17 @@ impl CodebaseIndex {
 
         let deleted = to_delete.len();
         if read_results.is_empty() && deleted == 0 {
+            self.clear_progress();
             return Ok((0, 0, 0));
         }
 
-        // ── Phase 4: embed + upsert ───────────────────────────────────────
+        // ── Phase 4: embed + upsert (saved in batches) ──────────────────────
         let mut added = 0usize;
         let mut updated = 0usize;
+        let total = read_results.len();
+        let mut batch = BatchCommit::new();
 
-        for (rel, abs, raw, size, hash, is_new) in read_results {
+        for (i, (rel, abs, raw, size, hash, is_new)) in read_results.into_iter().enumerate() {
             if let Some(entry) = self.embed_file(&rel, &abs, raw, size, hash) {
                 self.store.entries.insert(rel, entry);
                 if is_new {
@@ -615,6 +1273,9 @@ impl CodebaseIndex {
                 } else {
                     updated += 1;
                 }
+                if batch.record(self.store.entries.len()) {
+                    self.commit_batch(IndexProgress { done: i + 1, total });
+                }
             }
         }
 
@@ -622,7 +1283,7 @@ impl CodebaseIndex {
             self.store.entries.remove(key);
         }
 
-        self.store.save(&self.index_path);
+        self.commit_final();
         Ok((added, updated, deleted))
     }
 
@@ -640,6 +1301,7 @@ impl CodebaseIndex {
             }
         }
 
+        let _lock = self.lock_for_write()?;
         if let Some(entry) = self.embed_file(&rel_norm, &abs, bytes.to_vec(), size, hash) {
             self.store.entries.insert(rel_norm, entry);
             self.store.save(&self.index_path);
@@ -661,11 +1323,31 @@ impl CodebaseIndex {
     /// Files with no exact symbol match are scored by the max cosine similarity
     /// across all their chunks (range 0.0–1.0). Since 1.0 < 2.0, no semantic
     /// result can ever outrank a sniper hit.
+    ///
+    /// Files scoring below the [`min_score`](Self::set_min_score) threshold are dropped,
+    /// so an off-topic query can return fewer than `limit` paths — or none.
+    ///
+    /// Ties (e.g. two files whose best chunks embed identically) are broken by
+    /// `rel_path`, so identical inputs always yield identical file ordering.
+    ///
+    /// Repeating a query (up to whitespace) with the same `limit`, score threshold and
+    /// extension allowlist against an unchanged index returns the cached result; see [`last_search_cached`](Self::last_search_cached).
     pub async fn search(&mut self, query: &str, limit: usize) -> Result<Vec<String>> {
+        self.last_search_cached = false;
         if self.store.entries.is_empty() {
             return Ok(vec![]);
         }
 
+        let key = normalize_query(query);
+        let min_score = self.min_score;
+        if let Some(hit) = self
+            .query_cache
+            .get(self.store.version, &key, limit, min_score, &self.index_extensions)
+        {
+            self.last_search_cached = true;
+            return Ok(hit.to_vec());
+        }
+
         let qv = self.model.encode_single(&format!("query: {}", query));
         let query_lower = query.to_lowercase();
 
@@ -681,22 +1363,81 @@ impl CodebaseIndex {
             .store
             .entries
             .iter()
+            .filter(|(path, _)| self.is_indexable_path(path))
             .map(|(path, file_entry)| {
                 let score = score_file_entry(&query_tokens, &qv, file_entry);
                 (score, path.as_str())
             })
             .collect();
 
-        scores.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        sort_by_score_desc(&mut scores);
 
-        Ok(scores
+        let results: Vec<String> = scores
             .into_iter()
+            .filter(|(score, _)| min_score <= 0.0 || *score >= min_score)
             .take(limit)
             .map(|(_, p)| p.replace('\\', "/"))
+            .collect();
+
+        self.query_cache.insert(
+            self.store.version,
+            key,
+            limit,
+            min_score,
+            &self.index_extensions,
+            results.clone(),
+        );
+        if let Some(path) = &self.query_cache_path {
+            self.query_cache.save(path);
+        }
+        Ok(results)
+    }
+
+    /// Nearest-neighbour search seeded by a file instead of a text query.
+    ///
+    /// The target file is (re-)embedded through the normal cache path, then every
+    /// other indexed file is scored by the best cosine over all chunk pairs.
+    /// The target itself is excluded. Returns `(rel_path, score)` sorted desc.
+    pub async fn similar_files(
+        &mut self,
+        rel_path: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>> {
+        let rel_norm = rel_path.replace('\\', "/");
+        let abs = self.repo_root.join(&rel_norm);
+        if !self.is_indexable_path(&rel_norm) {
+            anyhow::bail!(
+                "'{}' is outside vector_search.index_extensions; nothing to compare",
+                rel_norm
+            );
+        }
+        self.index_file_path(&rel_norm, &abs).await?;
+
+        let Some(target) = self.store.entries.get(&rel_norm) else {
+            anyhow::bail!("'{}' is empty or binary; nothing to compare", rel_norm);
+        };
+
+        let mut scores: Vec<(f32, &str)> = self
+            .store
+            .entries
+            .iter()
+            .filter(|(path, _)| path.as_str() != rel_norm && self.is_indexable_path(path))
+            .map(|(path, file_entry)| (score_file_similarity(target, file_entry), path.as_str()))
+            .collect();
+
+        sort_by_score_desc(&mut scores);
+
+        Ok(scores
+            .into_iter()
+            .take(limit)
+            .map(|(s, p)| (p.replace('\\', "/"), s))
             .collect())
     }
 
     pub fn invalidate_extensions(&mut self, exts: &[&str]) -> usize {
+        let Ok(_lock) = self.lock_for_write() else {
+            return 0;
+        };
         let mut count = 0;
         let mut to_remove = Vec::new();
         for key in self.store.entries.keys() {
@@ -782,6 +1523,29 @@ fn score_file_entry(
         .fold(f32::NEG_INFINITY, f32::max)
 }
 
+/// Best score first; equal scores fall back to `rel_path` order. The index is a
+/// `HashMap`, so without the tiebreak equally scored files would come out in a
+/// different order on every run.
+fn sort_by_score_desc(scores: &mut [(f32, &str)]) {
+    scores.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.1.cmp(b.1))
+    });
+}
+
+/// File-to-file similarity: max cosine over every (chunk_a, chunk_b) pair.
+fn score_file_similarity(a: &FileIndexEntry, b: &FileIndexEntry) -> f32 {
+    a.chunks
+        .iter()
+        .flat_map(|ca| {
+            b.chunks
+                .iter()
+                .map(move |cb| cosine_similarity(&ca.vector, &cb.vector))
+        })
+        .fold(f32::NEG_INFINITY, f32::max)
+}
+
 // ---------------------------------------------------------------------------
 // Unit tests — Symbol Sniper proof
 // ---------------------------------------------------------------------------
@@ -802,6 +1566,7 @@ mod tests {
                 end_line: 10,
                 vector,
             }],
+            defs: None,
         }
     }
 
@@ -889,4 +1654,299 @@ mod tests {
             "Partial substring 'request' must not trigger sniper for 'ConvertRequest'"
         );
     }
+
+    /// Env var that turns [`index_lock_writer_process`] into a worker writing to the
+    /// db dir it names.
+    const LOCK_WORKER_DIR: &str = "CORTEXAST_LOCK_WORKER_DIR";
+
+    /// Two indexer processes writing the same db dir must serialize on the lock
+    /// file: every insert from both survives (no lost updates, no torn JSON).
+    /// The workers are this test binary re-run on [`index_lock_writer_process`],
+    /// since an advisory lock is only meaningful between processes.
+    #[test]
+    fn concurrent_indexers_serialize_on_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = std::env::current_exe().unwrap();
+
+        let workers: Vec<_> = (0..2)
+            .map(|w| {
+                std::process::Command::new(&exe)
+                    .args([
+                        "--exact",
+                        "vector_store::tests::index_lock_writer_process",
+                        "--ignored",
+                        "--test-threads=1",
+                    ])
+                    .env(LOCK_WORKER_DIR, dir.path())
+                    .env("CORTEXAST_LOCK_WORKER_ID", w.to_string())
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        for mut child in workers {
+            assert!(child.wait().unwrap().success());
+        }
+
+        let store = IndexStore::load(&dir.path().join("embeddings.json"));
+        assert_eq!(store.entries.len(), 50);
+    }
+
+    /// Worker half of [`concurrent_indexers_serialize_on_lock`]; does nothing
+    /// unless started by it.
+    #[test]
+    #[ignore = "spawned by concurrent_indexers_serialize_on_lock"]
+    fn index_lock_writer_process() {
+        let Some(dir) = std::env::var_os(LOCK_WORKER_DIR).map(PathBuf::from) else {
+            return;
+        };
+        let w = std::env::var("CORTEXAST_LOCK_WORKER_ID").unwrap();
+        let index_path = dir.join("embeddings.json");
+        let lock_path = dir.join("embeddings.lock");
+        for i in 0..25 {
+            let _lock = IndexLock::acquire(&lock_path, INDEX_LOCK_TIMEOUT).unwrap();
+            let mut store = IndexStore::load(&index_path);
+            store
+                .entries
+                .insert(format!("w{w}/f{i}.rs"), mock_entry(vec![], vec![0.0; 4]));
+            store.save(&index_path);
+        }
+    }
+
+    /// A writer that cannot get the lock fails with a clear error instead of hanging.
+    #[test]
+    fn index_lock_times_out_with_clear_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("embeddings.lock");
+
+        let _held = IndexLock::acquire(&lock_path, INDEX_LOCK_TIMEOUT).unwrap();
+        let err = IndexLock::acquire(&lock_path, Duration::from_millis(100))
+            .err()
+            .expect("second writer must time out");
+        let msg = err.to_string();
+        assert!(msg.contains("locked by another"), "unexpected error: {msg}");
+    }
+
+    /// Batches close on file count; the time bound is covered by the same branch.
+    #[test]
+    fn batch_commit_fires_every_n_files() {
+        let mut batch = BatchCommit::new();
+        let fired = (0..INDEX_COMMIT_FILES * 2 + 1)
+            .filter(|_| batch.record(0))
+            .count();
+        assert_eq!(fired, 2);
+        assert_eq!(batch.pending, 1);
+    }
+
+    /// Batch size grows with the store, so the number of full rewrites of a growing
+    /// index stays logarithmic in its size rather than linear.
+    #[test]
+    fn batch_commit_grows_with_the_store() {
+        let mut batch = BatchCommit::new();
+        let mut stored = 0;
+        let mut saves = 0;
+        for _ in 0..100_000 {
+            stored += 1;
+            if batch.record(stored) {
+                saves += 1;
+            }
+        }
+        assert!(saves < 40, "{saves} saves for 100k files");
+    }
+
+    /// The checkpoint round-trips and a missing file means "not interrupted".
+    #[test]
+    fn index_progress_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(INDEX_PROGRESS_FILE);
+        assert_eq!(IndexProgress::load(&path), None);
+
+        let progress = IndexProgress {
+            done: 400,
+            total: 1200,
+        };
+        progress.save(&path);
+        assert_eq!(IndexProgress::load(&path), Some(progress));
+    }
+
+    /// File-to-file similarity picks the best chunk pair, so a shared chunk
+    /// outranks files that are only loosely related.
+    #[test]
+    fn similarity_uses_best_chunk_pair() {
+        let target = mock_entry(vec![], vec![1.0, 0.0, 0.0, 0.0]);
+        let close = mock_entry(vec![], vec![0.9, 0.1, 0.0, 0.0]);
+        let far = mock_entry(vec![], vec![0.0, 0.0, 1.0, 0.0]);
+        let close_score = score_file_similarity(&target, &close);
+        let far_score = score_file_similarity(&target, &far);
+        assert!(
+            close_score > far_score,
+            "close ({close_score}) must outrank far ({far_score})"
+        );
+    }
+
+    /// Saving the vector index refreshes `symbols.json`; legacy entries without
+    /// defs are counted as cold rather than silently reported as symbol-free.
+    #[test]
+    fn symbol_index_follows_saved_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("embeddings.json");
+
+        let mut with_defs = mock_entry(vec![], vec![0.0; 4]);
+        with_defs.defs = Some(vec![SymbolDef {
+            name: "Config".into(),
+            kind: "struct".into(),
+            line: 12,
+        }]);
+        let mut store = IndexStore::default();
+        store.entries.insert("src/config.rs".into(), with_defs);
+        store
+            .entries
+            .insert("src/legacy.rs".into(), mock_entry(vec![], vec![0.0; 4]));
+        store.save(&index_path);
+
+        let index = SymbolIndex::load(dir.path()).expect("symbols.json written on save");
+        assert_eq!(index.cold_files, 1);
+        let sites = index.lookup("Config");
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].file, "src/config.rs");
+        assert_eq!(sites[0].line, 12);
+
+        store.entries.remove("src/config.rs");
+        store.save(&index_path);
+        let index = SymbolIndex::load(dir.path()).unwrap();
+        assert!(index.lookup("Config").is_empty());
+    }
+
+    /// A scope is covered only when every file in it is recorded with its current
+    /// content; unindexed, edited or legacy (def-less) files break coverage.
+    #[test]
+    fn symbol_index_coverage_tracks_file_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.rs"), "pub struct A;\n").unwrap();
+        std::fs::write(root.join("b.rs"), "pub struct B;\n").unwrap();
+
+        let mut entry = mock_entry(vec![], vec![0.0; 4]);
+        entry.hash = xxh3_hex(b"pub struct A;\n");
+        entry.defs = Some(Vec::new());
+        let mut store = IndexStore::default();
+        store.entries.insert("a.rs".into(), entry);
+        store.save(&root.join("embeddings.json"));
+        let index = SymbolIndex::load(root).unwrap();
+
+        assert!(index.covers(root, &[root.join("a.rs")]));
+        assert!(!index.covers(root, &[root.join("a.rs"), root.join("b.rs")]));
+        std::fs::write(root.join("a.rs"), "pub struct A2;\n").unwrap();
+        assert!(!index.covers(root, &[root.join("a.rs")]));
+    }
+
+    /// Each model gets its own index dir, so switching models never shares embeddings.
+    #[test]
+    fn model_db_dir_separates_models() {
+        let db = Path::new("/tmp/cortexast/db");
+        let a = model_db_dir(db, "minishlab/potion-retrieval-32M");
+        let b = model_db_dir(db, "minishlab/potion-base-8M");
+        assert_eq!(a, db.join("models").join("minishlab_potion-retrieval-32M"));
+        assert_ne!(a, b);
+        assert_eq!(model_db_dir(db, " minishlab/potion-base-8M "), b);
+        assert_eq!(model_db_dir(db, ".."), db.join("models").join("default"));
+    }
+
+    /// Every save bumps the persisted version, which is what invalidates cached
+    /// queries — also across a reset to an empty store, which must not reuse an
+    /// earlier version.
+    #[test]
+    fn each_save_bumps_index_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("embeddings.json");
+
+        let mut store = IndexStore::default();
+        store.save(&index_path);
+        let first = IndexStore::load(&index_path).version;
+        store.save(&index_path);
+        let second = IndexStore::load(&index_path).version;
+        assert!(second > first, "{second} <= {first}");
+
+        std::thread::sleep(Duration::from_millis(2));
+        let mut reset = IndexStore::default();
+        reset.save(&index_path);
+        assert!(IndexStore::load(&index_path).version > second);
+    }
+
+    /// Cached results are served only for the same normalized query, limit,
+    /// score threshold, extension allowlist and index version; a new version drops
+    /// everything cached for the old one.
+    #[test]
+    fn query_cache_is_keyed_by_index_version() {
+        let mut cache = QueryCache::default();
+        let hit = vec!["src/config.rs".to_string()];
+        let rs = vec!["rs".to_string()];
+        cache.insert(
+            3,
+            normalize_query("  parse   config "),
+            10,
+            0.0,
+            &[],
+            hit.clone(),
+        );
+
+        assert_eq!(
+            cache.get(3, &normalize_query("parse config"), 10, 0.0, &[]),
+            Some(&hit[..])
+        );
+        assert!(cache.get(3, "parse config", 5, 0.0, &[]).is_none());
+        assert!(cache.get(3, "parse config", 10, 0.4, &[]).is_none());
+        assert!(cache.get(3, "parse config", 10, 0.0, &rs).is_none());
+        assert!(cache.get(4, "parse config", 10, 0.0, &[]).is_none());
+
+        cache.insert(4, "load rules".into(), 10, 0.0, &[], vec![]);
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.get(4, "parse config", 10, 0.0, &[]).is_none());
+    }
+
+    /// An empty allowlist admits everything; otherwise only listed extensions
+    /// (case-insensitively) pass, and extensionless files never do.
+    #[test]
+    fn index_extensions_allowlist() {
+        assert!(extension_allowed(&[], "fixtures/big.json"));
+
+        let allow = vec!["rs".to_string(), "ts".to_string()];
+        assert!(extension_allowed(&allow, "src/lib.rs"));
+        assert!(extension_allowed(&allow, "web/App.TS"));
+        assert!(!extension_allowed(&allow, "fixtures/big.json"));
+        assert!(!extension_allowed(&allow, "docs/README.md"));
+        assert!(!extension_allowed(&allow, "Makefile"));
+    }
+
+    /// Files that score the same come back in `rel_path` order, however the
+    /// index map happens to iterate.
+    #[test]
+    fn equal_scores_rank_by_path() {
+        let qv = vec![1.0f32, 0.0, 0.0, 0.0];
+        let toks = tokens("load the settings");
+        let paths = ["src/b.rs", "src/c.rs", "src/a.rs", "lib/z.rs"];
+
+        let rank = |order: &[&str]| -> Vec<String> {
+            let mut entries: HashMap<String, FileIndexEntry> = HashMap::new();
+            for p in order {
+                let v = if *p == "lib/z.rs" {
+                    vec![0.0, 1.0, 0.0, 0.0]
+                } else {
+                    vec![1.0, 1.0, 0.0, 0.0]
+                };
+                entries.insert(p.to_string(), mock_entry(vec!["fn helper"], v));
+            }
+            let mut scores: Vec<(f32, &str)> = entries
+                .iter()
+                .map(|(p, e)| (score_file_entry(&toks, &qv, e), p.as_str()))
+                .collect();
+            sort_by_score_desc(&mut scores);
+            scores.into_iter().map(|(_, p)| p.to_string()).collect()
+        };
+
+        let first = rank(&paths);
+        let mut reversed = paths;
+        reversed.reverse();
+        assert_eq!(first, rank(&reversed));
+        assert_eq!(first, ["src/a.rs", "src/b.rs", "src/c.rs", "lib/z.rs"]);
+    }
 }
@@ -183,7 +183,7 @@ fn member_name(abs_path: &Path) -> String {
         .unwrap_or_else(|| "unknown".to_string())
 }
 
-fn detect_manifest_kind(abs_path: &Path) -> ManifestKind {
+pub(crate) fn detect_manifest_kind(abs_path: &Path) -> ManifestKind {
     if abs_path.join("Cargo.toml").exists() {
         ManifestKind::Cargo
     } else if abs_path.join("package.json").exists() {
@@ -1,3 +1,4 @@
+use crate::config::SliceFormat;
 use anyhow::Result;
 use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, Event};
 use quick_xml::Writer;
@@ -37,9 +38,81 @@ fn crunch_text_for_cdata(input: &str) -> String {
     out.trim_end().to_string()
 }
 
+/// Replace characters XML 1.0 forbids anywhere in a document (C0 controls other than
+/// tab/LF/CR, and the U+FFFE / U+FFFF non-characters) with U+FFFD. Neither CDATA nor
+/// entity escaping can carry them, so strict parsers would reject the whole slice.
+fn sanitize_xml_chars(input: &str) -> std::borrow::Cow<'_, str> {
+    let is_illegal = |c: char| {
+        matches!(c, '\u{0}'..='\u{8}' | '\u{B}' | '\u{C}' | '\u{E}'..='\u{1F}')
+            || matches!(c, '\u{FFFE}' | '\u{FFFF}')
+    };
+    if !input.chars().any(is_illegal) {
+        return std::borrow::Cow::Borrowed(input);
+    }
+    std::borrow::Cow::Owned(
+        input
+            .chars()
+            .map(|c| if is_illegal(c) { '\u{FFFD}' } else { c })
+            .collect(),
+    )
+}
+
+/// Write `text` as CDATA. A literal `]]>` would terminate the section early, so the
+/// text is split between `]]` and `>` into consecutive CDATA sections, which parsers
+/// concatenate back into the original content.
+fn write_cdata<W: std::io::Write>(writer: &mut Writer<W>, text: &str) -> Result<()> {
+    let text = sanitize_xml_chars(text);
+    let mut rest: &str = &text;
+    while let Some(pos) = rest.find("]]>") {
+        writer.write_event(Event::CData(BytesCData::new(&rest[..pos + 2])))?;
+        rest = &rest[pos + 2..];
+    }
+    writer.write_event(Event::CData(BytesCData::new(rest)))?;
+    Ok(())
+}
+
+/// Where a file was cut to fit the token budget. `at_line` is the first line (1-based,
+/// in the file's rendered text) that was left out; `remaining_bytes` is what followed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+    pub at_line: usize,
+    pub remaining_bytes: u64,
+}
+
+/// How the complete slice compares to its token budget, for a slice that had to cut
+/// or drop files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetSummary {
+    pub budget_tokens: usize,
+    pub used_tokens: usize,
+    /// Estimated tokens had every candidate file been included whole.
+    pub full_tokens: usize,
+    pub truncated_files: usize,
+    pub dropped_files: usize,
+}
+
+/// Build the slice XML. `skipped` lists `(path, bytes)` of files left out for
+/// exceeding `max_file_bytes`; each becomes an empty
+/// `<file path="..." skipped="too-large" bytes="N"/>` stub so the agent knows it exists.
 pub fn build_context_xml(
     repository_map: Option<&str>,
     files: &[(String, String)],
+    skipped: &[(String, u64)],
+) -> Result<String> {
+    build_context_xml_with_budget(repository_map, files, skipped, &[], None)
+}
+
+/// [`build_context_xml`] for a slice that overflowed its budget: `budget` becomes a
+/// trailing `<budget limit_tokens=".." used_tokens=".." full_tokens=".." over_by_tokens=".."
+/// truncated_files=".." dropped_files=".."/>` element (last, as [`ContextXmlWriter`]
+/// must write it), and each file in `truncated` ends with
+/// `<truncated at="line N" remaining_bytes="M"/>` after its content.
+pub fn build_context_xml_with_budget(
+    repository_map: Option<&str>,
+    files: &[(String, String)],
+    skipped: &[(String, u64)],
+    truncated: &[(String, Truncation)],
+    budget: Option<&BudgetSummary>,
 ) -> Result<String> {
     let mut writer = Writer::new(Cursor::new(Vec::new()));
 
@@ -49,22 +122,20 @@ pub fn build_context_xml(
     writer.write_event(Event::Start(root))?;
 
     if let Some(map_text) = repository_map {
-        let map_el = BytesStart::new("repository_map");
-        writer.write_event(Event::Start(map_el))?;
-        let map_text = crunch_text_for_cdata(map_text);
-        writer.write_event(Event::CData(BytesCData::new(map_text.as_str())))?;
-        writer.write_event(Event::End(BytesEnd::new("repository_map")))?;
+        write_repository_map(&mut writer, map_text)?;
     }
 
     for (path, content) in files {
-        let mut file_el = BytesStart::new("file");
-        file_el.push_attribute(("path", path.as_str()));
-        writer.write_event(Event::Start(file_el))?;
+        let t = truncated.iter().find(|(p, _)| p == path).map(|(_, t)| t);
+        write_file(&mut writer, path, content, t)?;
+    }
+
+    for (path, bytes) in skipped {
+        write_skipped(&mut writer, path, *bytes)?;
+    }
 
-        // Write CDATA content.
-        let content = crunch_text_for_cdata(content.as_str());
-        writer.write_event(Event::CData(BytesCData::new(content.as_str())))?;
-        writer.write_event(Event::End(BytesEnd::new("file")))?;
+    if let Some(b) = budget {
+        write_budget(&mut writer, b)?;
     }
 
     writer.write_event(Event::End(BytesEnd::new("cortexast")))?;
@@ -72,3 +143,392 @@ pub fn build_context_xml(
     let bytes = writer.into_inner().into_inner();
     Ok(String::from_utf8(bytes)?)
 }
+
+/// [`build_context_xml_with_budget`] in the given [`SliceFormat`]. Markdown carries the
+/// same parts: the repository map, a `## path` heading with a fenced block per file
+/// (truncation and too-large notes in italics), then the budget note.
+pub fn build_context_with_budget(
+    format: SliceFormat,
+    repository_map: Option<&str>,
+    files: &[(String, String)],
+    skipped: &[(String, u64)],
+    truncated: &[(String, Truncation)],
+    budget: Option<&BudgetSummary>,
+) -> Result<String> {
+    if format == SliceFormat::Xml {
+        return build_context_xml_with_budget(repository_map, files, skipped, truncated, budget);
+    }
+    let mut out: Vec<u8> = Vec::new();
+    if let Some(map_text) = repository_map {
+        write_repository_map_md(&mut out, map_text)?;
+    }
+    for (path, content) in files {
+        let t = truncated.iter().find(|(p, _)| p == path).map(|(_, t)| t);
+        write_file_md(&mut out, path, content, t)?;
+    }
+    for (path, bytes) in skipped {
+        write_skipped_md(&mut out, path, *bytes)?;
+    }
+    if let Some(b) = budget {
+        write_budget_md(&mut out, b)?;
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+/// Streaming form of [`build_context_xml_with_budget`]: each element is written to
+/// the underlying writer as soon as it is added, so a large slice never sits in
+/// memory whole. The output is byte-for-byte the same; the budget summary is only
+/// known once every file has been seen, which is why both put `<budget/>` last.
+pub struct ContextXmlWriter<W: std::io::Write> {
+    writer: Writer<W>,
+    format: SliceFormat,
+}
+
+impl<W: std::io::Write> ContextXmlWriter<W> {
+    /// Write the XML declaration, the root start tag and the repository map.
+    pub fn start(out: W, repository_map: Option<&str>) -> Result<Self> {
+        Self::start_with_format(out, repository_map, SliceFormat::Xml)
+    }
+
+    /// [`ContextXmlWriter::start`] in the given [`SliceFormat`] (see
+    /// [`build_context_with_budget`]); Markdown has no declaration or root element.
+    pub fn start_with_format(
+        out: W,
+        repository_map: Option<&str>,
+        format: SliceFormat,
+    ) -> Result<Self> {
+        let mut writer = Writer::new(out);
+        if format == SliceFormat::Markdown {
+            if let Some(map_text) = repository_map {
+                write_repository_map_md(writer.get_mut(), map_text)?;
+            }
+            return Ok(Self { writer, format });
+        }
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        writer.write_event(Event::Start(BytesStart::new("cortexast")))?;
+        if let Some(map_text) = repository_map {
+            write_repository_map(&mut writer, map_text)?;
+        }
+        Ok(Self { writer, format })
+    }
+
+    /// One `<file>` block; `truncated` adds the `<truncated/>` marker.
+    pub fn file(
+        &mut self,
+        path: &str,
+        content: &str,
+        truncated: Option<&Truncation>,
+    ) -> Result<()> {
+        match self.format {
+            SliceFormat::Xml => write_file(&mut self.writer, path, content, truncated),
+            SliceFormat::Markdown => write_file_md(self.writer.get_mut(), path, content, truncated),
+        }
+    }
+
+    /// One `skipped="too-large"` stub.
+    pub fn skipped(&mut self, path: &str, bytes: u64) -> Result<()> {
+        match self.format {
+            SliceFormat::Xml => write_skipped(&mut self.writer, path, bytes),
+            SliceFormat::Markdown => write_skipped_md(self.writer.get_mut(), path, bytes),
+        }
+    }
+
+    /// Write the budget summary (if any) and the closing tag; returns the writer.
+    pub fn finish(mut self, budget: Option<&BudgetSummary>) -> Result<W> {
+        if self.format == SliceFormat::Markdown {
+            if let Some(b) = budget {
+                write_budget_md(self.writer.get_mut(), b)?;
+            }
+            return Ok(self.writer.into_inner());
+        }
+        if let Some(b) = budget {
+            write_budget(&mut self.writer, b)?;
+        }
+        self.writer
+            .write_event(Event::End(BytesEnd::new("cortexast")))?;
+        Ok(self.writer.into_inner())
+    }
+}
+
+fn write_budget<W: std::io::Write>(writer: &mut Writer<W>, b: &BudgetSummary) -> Result<()> {
+    let mut el = BytesStart::new("budget");
+    el.push_attribute(("limit_tokens", b.budget_tokens.to_string().as_str()));
+    el.push_attribute(("used_tokens", b.used_tokens.to_string().as_str()));
+    el.push_attribute(("full_tokens", b.full_tokens.to_string().as_str()));
+    let over_by = b.full_tokens.saturating_sub(b.budget_tokens);
+    el.push_attribute(("over_by_tokens", over_by.to_string().as_str()));
+    el.push_attribute(("truncated_files", b.truncated_files.to_string().as_str()));
+    el.push_attribute(("dropped_files", b.dropped_files.to_string().as_str()));
+    writer.write_event(Event::Empty(el))?;
+    Ok(())
+}
+
+fn write_repository_map<W: std::io::Write>(writer: &mut Writer<W>, map_text: &str) -> Result<()> {
+    writer.write_event(Event::Start(BytesStart::new("repository_map")))?;
+    let map_text = crunch_text_for_cdata(map_text);
+    write_cdata(writer, &map_text)?;
+    writer.write_event(Event::End(BytesEnd::new("repository_map")))?;
+    Ok(())
+}
+
+fn write_file<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    path: &str,
+    content: &str,
+    truncated: Option<&Truncation>,
+) -> Result<()> {
+    // `push_attribute` entity-escapes `<`, `&`, `"` etc.; illegal chars are replaced first.
+    let mut file_el = BytesStart::new("file");
+    file_el.push_attribute(("path", sanitize_xml_chars(path).as_ref()));
+    writer.write_event(Event::Start(file_el))?;
+
+    // Write CDATA content.
+    let content = crunch_text_for_cdata(content);
+    write_cdata(writer, &content)?;
+    if let Some(t) = truncated {
+        let mut el = BytesStart::new("truncated");
+        el.push_attribute(("at", format!("line {}", t.at_line).as_str()));
+        el.push_attribute(("remaining_bytes", t.remaining_bytes.to_string().as_str()));
+        writer.write_event(Event::Empty(el))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("file")))?;
+    Ok(())
+}
+
+fn write_skipped<W: std::io::Write>(writer: &mut Writer<W>, path: &str, bytes: u64) -> Result<()> {
+    let mut file_el = BytesStart::new("file");
+    file_el.push_attribute(("path", sanitize_xml_chars(path).as_ref()));
+    file_el.push_attribute(("skipped", "too-large"));
+    file_el.push_attribute(("bytes", bytes.to_string().as_str()));
+    writer.write_event(Event::Empty(file_el))?;
+    Ok(())
+}
+
+fn write_budget_md<W: std::io::Write>(out: &mut W, b: &BudgetSummary) -> Result<()> {
+    let over_by = b.full_tokens.saturating_sub(b.budget_tokens);
+    writeln!(
+        out,
+        "> **Budget:** ~{} of {} tokens used; the full slice is ~{} (over by {}). \
+         {} file(s) truncated, {} dropped.\n",
+        b.used_tokens, b.budget_tokens, b.full_tokens, over_by, b.truncated_files, b.dropped_files
+    )?;
+    Ok(())
+}
+
+fn write_repository_map_md<W: std::io::Write>(out: &mut W, map_text: &str) -> Result<()> {
+    let map_text = crunch_text_for_cdata(map_text);
+    let fence = code_fence(&map_text);
+    writeln!(
+        out,
+        "## Repository map\n\n{fence}text\n{map_text}\n{fence}\n"
+    )?;
+    Ok(())
+}
+
+fn write_file_md<W: std::io::Write>(
+    out: &mut W,
+    path: &str,
+    content: &str,
+    truncated: Option<&Truncation>,
+) -> Result<()> {
+    let content = crunch_text_for_cdata(content);
+    let fence = code_fence(&content);
+    let lang = fence_language(path);
+    writeln!(out, "## {path}\n\n{fence}{lang}\n{content}\n{fence}")?;
+    if let Some(t) = truncated {
+        writeln!(
+            out,
+            "\n_Truncated at line {}; {} bytes remaining._",
+            t.at_line, t.remaining_bytes
+        )?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+fn write_skipped_md<W: std::io::Write>(out: &mut W, path: &str, bytes: u64) -> Result<()> {
+    writeln!(out, "## {path}\n\n_Skipped: too large ({bytes} bytes)._\n")?;
+    Ok(())
+}
+
+/// A backtick fence longer than any backtick run inside `text` (at least three), so
+/// embedded Markdown fences can't close the block early.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat((longest + 1).max(3))
+}
+
+/// Info string for a file's fenced block: the language most renderers know for the
+/// extension, or the bare extension.
+fn fence_language(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let Some((_, ext)) = name.rsplit_once('.') else {
+        return "";
+    };
+    match ext {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "go" => "go",
+        "rb" => "ruby",
+        "kt" | "kts" => "kotlin",
+        "cs" => "csharp",
+        "sh" | "bash" => "bash",
+        "yml" => "yaml",
+        "md" => "markdown",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::Reader;
+
+    /// Parse `xml` strictly and return `(path attribute, concatenated CDATA)` per `<file>`.
+    fn parse_files(xml: &str) -> Vec<(String, String)> {
+        let mut reader = Reader::from_str(xml);
+        let mut files: Vec<(String, String)> = Vec::new();
+        loop {
+            match reader.read_event().expect("output must be well-formed XML") {
+                Event::Start(e) if e.name().as_ref() == b"file" => {
+                    let path = e
+                        .try_get_attribute("path")
+                        .unwrap()
+                        .expect("path attribute")
+                        .unescape_value()
+                        .unwrap()
+                        .into_owned();
+                    files.push((path, String::new()));
+                }
+                Event::CData(e) => {
+                    if let Some((_, body)) = files.last_mut() {
+                        body.push_str(std::str::from_utf8(&e.into_inner()).unwrap());
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn metacharacters_and_cdata_terminator_round_trip() {
+        let content = "if a < b && c > d { x }\nlet s = \"]]>\";\nlet t = \"]]]]>>\";";
+        let files = vec![(r#"src/a&b "<q>".rs"#.to_string(), content.to_string())];
+        let xml = build_context_xml(Some("map ]]> here"), &files, &[]).unwrap();
+
+        let parsed = parse_files(&xml);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].0, r#"src/a&b "<q>".rs"#);
+        assert_eq!(parsed[0].1, content);
+    }
+
+    #[test]
+    fn illegal_control_chars_are_replaced() {
+        let files = vec![(
+            "src/ansi\u{1}.rs".to_string(),
+            "let red = \"\u{1b}[31m\";\u{0}".to_string(),
+        )];
+        let xml = build_context_xml(None, &files, &[]).unwrap();
+
+        assert!(!xml.contains('\u{1b}') && !xml.contains('\u{0}') && !xml.contains('\u{1}'));
+        let parsed = parse_files(&xml);
+        assert_eq!(parsed[0].0, "src/ansi\u{FFFD}.rs");
+        assert_eq!(parsed[0].1, "let red = \"\u{FFFD}[31m\";\u{FFFD}");
+    }
+
+    #[test]
+    fn oversized_files_become_empty_stubs() {
+        let files = vec![("src/lib.rs".to_string(), "pub fn f() {}".to_string())];
+        let skipped = vec![("data/dump.sql".to_string(), 2_097_152u64)];
+        let xml = build_context_xml(None, &files, &skipped).unwrap();
+
+        assert!(xml.contains(r#"<file path="data/dump.sql" skipped="too-large" bytes="2097152"/>"#));
+        // Stubs carry no body, so the parsed content of real files is unaffected.
+        let parsed = parse_files(&xml);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].1, "pub fn f() {}");
+    }
+
+    #[test]
+    fn budget_summary_and_truncation_markers() {
+        let files = vec![
+            ("src/a.rs".to_string(), "fn a() {}\n".to_string()),
+            ("src/b.rs".to_string(), "fn b() {\n".to_string()),
+        ];
+        let truncated = vec![(
+            "src/b.rs".to_string(),
+            Truncation {
+                at_line: 2,
+                remaining_bytes: 40,
+            },
+        )];
+        let budget = BudgetSummary {
+            budget_tokens: 100,
+            used_tokens: 98,
+            full_tokens: 250,
+            truncated_files: 1,
+            dropped_files: 3,
+        };
+        let xml =
+            build_context_xml_with_budget(None, &files, &[], &truncated, Some(&budget)).unwrap();
+
+        assert!(xml.contains(
+            r#"<budget limit_tokens="100" used_tokens="98" full_tokens="250" over_by_tokens="150" truncated_files="1" dropped_files="3"/>"#
+        ));
+        assert!(xml.ends_with(r#"dropped_files="3"/></cortexast>"#), "{xml}");
+        assert!(xml.contains(r#"]]><truncated at="line 2" remaining_bytes="40"/></file>"#));
+        assert_eq!(xml.matches("<truncated").count(), 1);
+        // Markers are elements, not content.
+        let parsed = parse_files(&xml);
+        assert_eq!(parsed[1].1, "fn b() {");
+    }
+
+    #[test]
+    fn markdown_fences_outlast_embedded_backticks() {
+        let files = vec![
+            ("src/lib.rs".to_string(), "pub fn f() {}\n".to_string()),
+            (
+                "README.md".to_string(),
+                "Usage:\n```sh\ncargo run\n```\n".to_string(),
+            ),
+        ];
+        let skipped = vec![("dist/app.js".to_string(), 900_000)];
+        let truncated = vec![(
+            "src/lib.rs".to_string(),
+            Truncation {
+                at_line: 2,
+                remaining_bytes: 12,
+            },
+        )];
+        let md = build_context_with_budget(
+            SliceFormat::Markdown,
+            Some("src/lib.rs"),
+            &files,
+            &skipped,
+            &truncated,
+            None,
+        )
+        .unwrap();
+
+        assert!(
+            md.starts_with("## Repository map\n\n```text\nsrc/lib.rs\n```\n"),
+            "{md}"
+        );
+        assert!(md.contains("## src/lib.rs\n\n```rust\npub fn f() {}\n```\n\n_Truncated at line 2; 12 bytes remaining._"), "{md}");
+        assert!(
+            md.contains("## README.md\n\n````markdown\nUsage:\n```sh\ncargo run\n```\n````"),
+            "{md}"
+        );
+        assert!(
+            md.contains("## dist/app.js\n\n_Skipped: too large (900000 bytes)._"),
+            "{md}"
+        );
+        assert!(!md.contains("<cortexast"), "{md}");
+    }
+}
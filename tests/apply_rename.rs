@@ -0,0 +1,161 @@
+//! `apply_rename`: AST-accurate in-place rename with a dry run, a ubiquity guard and
+//! a keyword check.
+
+use cortexast::inspector::apply_rename;
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+const LIB: &str = "// load_config reads the file\npub fn load_config() -> u32 {\n    let msg = \"load_config\";\n    msg.len() as u32\n}\n";
+const MAIN: &str = "fn main() {\n    let n = lib::load_config();\n    println!(\"{n}\");\n}\n";
+
+#[test]
+fn dry_run_reports_a_diff_and_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/lib.rs", LIB);
+    write(dir.path(), "src/main.rs", MAIN);
+
+    let out = apply_rename(dir.path(), "load_config", "read_config", true, false).unwrap();
+    assert!(!out.applied);
+    let files: Vec<(&str, usize)> = out
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), f.occurrences))
+        .collect();
+    assert_eq!(files, [("src/lib.rs", 1), ("src/main.rs", 1)]);
+    assert!(
+        out.diff.contains("--- a/src/lib.rs\n+++ b/src/lib.rs\n"),
+        "{}",
+        out.diff
+    );
+    assert!(
+        out.diff
+            .contains("-pub fn load_config() -> u32 {\n+pub fn read_config() -> u32 {\n"),
+        "{}",
+        out.diff
+    );
+    assert!(
+        out.diff.contains("+    let n = lib::read_config();\n"),
+        "{}",
+        out.diff
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+        LIB
+    );
+}
+
+#[test]
+fn apply_skips_comments_and_strings() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/lib.rs", LIB);
+    write(dir.path(), "src/main.rs", MAIN);
+
+    let out = apply_rename(dir.path(), "load_config", "read_config", false, false).unwrap();
+    assert!(out.applied);
+    let lib = std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap();
+    assert_eq!(
+        lib,
+        LIB.replacen("pub fn load_config", "pub fn read_config", 1)
+    );
+    let main = std::fs::read_to_string(dir.path().join("src/main.rs")).unwrap();
+    assert!(main.contains("lib::read_config()"), "{main}");
+}
+
+#[test]
+fn ubiquitous_symbols_need_force() {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..51 {
+        write(dir.path(), &format!("m{i}.rs"), "fn f() { shared(); }\n");
+    }
+
+    let err = apply_rename(dir.path(), "shared", "common", true, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Refusing to rename `shared`"), "{err}");
+
+    let out = apply_rename(dir.path(), "shared", "common", true, true).unwrap();
+    assert_eq!(out.files.len(), 51);
+}
+
+#[test]
+fn rejects_non_identifiers() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "a.rs", "fn a() {}\n");
+    assert!(apply_rename(dir.path(), "a", "b c", true, false).is_err());
+    assert!(apply_rename(dir.path(), "a", "a", true, false).is_err());
+}
+
+#[test]
+fn rejects_keywords_of_the_target_language() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "a.rs", "fn helper() {}
+");
+    write(dir.path(), "b.py", "def helper():\n    pass\n");
+
+    let err = apply_rename(dir.path(), "helper", "match", false, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("`match` is a keyword in a.rs"), "{err}");
+    let err = apply_rename(dir.path(), "helper", "lambda", false, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("`lambda` is a keyword in b.py"), "{err}");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("a.rs")).unwrap(),
+        "fn helper() {}\n"
+    );
+}
+
+#[test]
+fn failed_write_leaves_every_file_untouched() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/lib.rs", LIB);
+    write(dir.path(), "src/main.rs", MAIN);
+    // A directory squatting on main.rs's staging path makes its write fail after
+    // lib.rs was already staged.
+    std::fs::create_dir(dir.path().join("src/.main.rs.cortexast-rename")).unwrap();
+
+    assert!(apply_rename(dir.path(), "load_config", "read_config", false, false).is_err());
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+        LIB
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/main.rs")).unwrap(),
+        MAIN
+    );
+    assert!(!dir.path().join("src/.lib.rs.cortexast-rename").exists());
+}
+
+#[test]
+fn cli_writes_only_with_apply() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/lib.rs", LIB);
+    write(dir.path(), "src/main.rs", MAIN);
+    let rename = |extra: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_cortexast"))
+            .current_dir(dir.path())
+            .args(["rename", "load_config", "read_config"])
+            .args(extra)
+            .output()
+            .expect("run cortexast rename");
+        assert!(output.status.success(), "exit: {:?}", output.status);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let diff = rename(&[]);
+    assert!(diff.contains("+pub fn read_config() -> u32 {"), "{diff}");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+        LIB
+    );
+
+    rename(&["--apply"]);
+    let lib = std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap();
+    assert!(lib.contains("pub fn read_config()"), "{lib}");
+}
@@ -0,0 +1,58 @@
+//! `blast_radius` (call_hierarchy) opens with a rename-risk summary.
+
+use cortexast::inspector::{call_hierarchy, PathStyle};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn private_helper_in_one_file_is_low_risk() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "Cargo.toml", "[package]\nname = \"solo\"\n");
+    write(
+        dir.path(),
+        "src/lib.rs",
+        "fn helper() -> u32 {\n    1\n}\n\nfn run() -> u32 {\n    helper()\n}\n",
+    );
+
+    let out = call_hierarchy(dir.path(), "helper", PathStyle::RelativeTo(dir.path())).unwrap();
+    assert!(out.contains("### Rename Risk: **Low**"), "{out}");
+    assert!(out.contains("- 1 file(s) define or call it"), "{out}");
+    assert!(out.contains("stays within one package (`.`)"), "{out}");
+    assert!(out.contains("no public API"), "{out}");
+}
+
+#[test]
+fn exported_symbol_used_across_packages_is_high_risk() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "core/Cargo.toml", "[package]\nname = \"core\"\n");
+    write(
+        root,
+        "core/src/lib.rs",
+        "pub fn shared() -> u32 {\n    1\n}\n",
+    );
+    write(root, "app/Cargo.toml", "[package]\nname = \"app\"\n");
+    write(
+        root,
+        "app/src/main.rs",
+        "fn main() {\n    core::shared();\n}\n",
+    );
+    write(
+        root,
+        "app/src/api.rs",
+        "pub fn handler() -> u32 {\n    core::shared()\n}\n",
+    );
+
+    let out = call_hierarchy(root, "shared", PathStyle::RelativeTo(root)).unwrap();
+    assert!(out.contains("### Rename Risk: **High**"), "{out}");
+    assert!(out.contains("- 3 file(s) define or call it"), "{out}");
+    assert!(out.contains("crosses 2 packages: `app`, `core`"), "{out}");
+    assert!(out.contains("public API: definition ("), "{out}");
+    assert!(out.contains("handler ("), "{out}");
+    assert!(!out.contains("ubiquitous"), "{out}");
+}
@@ -0,0 +1,111 @@
+//! Makefile and CMake build targets come out as symbols (line-based, no grammar).
+
+use cortexast::inspector::{analyze_file, repo_map_with_filter, RepoMapOptions};
+
+const MAKEFILE: &str = "\
+CC := gcc
+FLAGS = -O2 -DMODE=a:b
+BIN = app
+
+.PHONY: all test clean
+
+all: $(BIN) docs
+
+$(BIN): main.o util.o
+\t$(CC) -o $@ $^
+
+%.o: %.c
+\t$(CC) -c $<
+
+test: all \\
+      fixtures
+\t./run-tests --fast
+\t@echo done
+
+define BANNER
+fake: rule
+endef
+
+clean:
+\trm -f *.o
+docs:: README.md
+";
+
+const CMAKE: &str = "\
+cmake_minimum_required(VERSION 3.20)
+project(Widget VERSION 1.0 LANGUAGES CXX)
+
+# add_executable(commented_out main.cpp)
+add_library(widget_core STATIC
+    src/core.cpp
+)
+ADD_EXECUTABLE(\"widget\" src/main.cpp)
+add_custom_target(format COMMAND clang-format -i src/*.cpp)
+add_library(${ALIAS_NAME} ALIAS widget_core)
+";
+
+fn names_and_kinds(path: &std::path::Path) -> Vec<(String, String, u32)> {
+    analyze_file(path)
+        .unwrap()
+        .symbols
+        .into_iter()
+        .map(|s| (s.name, s.kind, s.line + 1))
+        .collect()
+}
+
+#[test]
+fn makefile_rule_targets_and_phony_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Makefile");
+    std::fs::write(&path, MAKEFILE).unwrap();
+
+    let out = analyze_file(&path).unwrap();
+    let got: Vec<(&str, &str, u32, u32)> = out
+        .symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind.as_str(), s.line + 1, s.line_end + 1))
+        .collect();
+    assert_eq!(
+        got,
+        [
+            ("all", "phony", 7, 7),
+            ("test", "phony", 15, 18),
+            ("clean", "phony", 24, 25),
+            ("docs", "target", 26, 26),
+        ]
+    );
+    assert_eq!(
+        out.symbols[1].signature.as_deref(),
+        Some("test: all fixtures")
+    );
+    assert!(out.imports.is_empty() && out.exports.is_empty());
+}
+
+#[test]
+fn cmake_projects_executables_libraries_and_custom_targets() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("CMakeLists.txt");
+    std::fs::write(&path, CMAKE).unwrap();
+
+    assert_eq!(
+        names_and_kinds(&path),
+        [
+            ("Widget".to_string(), "project".to_string(), 2),
+            ("widget_core".to_string(), "library".to_string(), 5),
+            ("widget".to_string(), "executable".to_string(), 8),
+            ("format".to_string(), "target".to_string(), 9),
+        ]
+    );
+}
+
+#[test]
+fn repo_map_lists_build_targets() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Makefile"), MAKEFILE).unwrap();
+    std::fs::write(dir.path().join("CMakeLists.txt"), CMAKE).unwrap();
+
+    let map = repo_map_with_filter(dir.path(), &RepoMapOptions::default()).unwrap();
+    assert!(map.contains("Makefile"), "{map}");
+    assert!(map.contains("] test"), "{map}");
+    assert!(map.contains("] widget_core"), "{map}");
+}
@@ -0,0 +1,51 @@
+//! `blast_radius` outgoing calls: stdlib noise hidden, counted or kept.
+
+use cortexast::inspector::{call_hierarchy_with_options, CallNoise, PathStyle};
+
+fn outgoing(noise: CallNoise) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("lib.rs"),
+        "fn audit(v: Vec<u32>) -> usize {\n    let w = v.clone();\n    record(w.len());\n    \
+         w.iter().count()\n}\n\nfn record(_n: usize) {}\n",
+    )
+    .unwrap();
+    let out = call_hierarchy_with_options(
+        dir.path(),
+        "audit",
+        PathStyle::RelativeTo(dir.path()),
+        noise,
+    )
+    .unwrap();
+    let start = out.find("### Outgoing Calls").unwrap();
+    let end = out.find("### Incoming Calls").unwrap();
+    out[start..end].to_string()
+}
+
+#[test]
+fn noise_is_hidden_by_default() {
+    let calls = outgoing(CallNoise::Hide);
+    assert!(calls.contains("- `record` — lib.rs:L3"), "{calls}");
+    assert!(!calls.contains("`clone`"), "{calls}");
+    assert!(!calls.contains("filtered"), "{calls}");
+}
+
+#[test]
+fn count_names_what_was_filtered() {
+    let calls = outgoing(CallNoise::Count);
+    assert!(calls.contains("- `record` — lib.rs:L3"), "{calls}");
+    assert!(
+        calls.contains("stdlib/built-in calls filtered: `clone`"),
+        "{calls}"
+    );
+    assert!(calls.contains("`len`"), "{calls}");
+    assert!(!calls.contains("- `clone` —"), "{calls}");
+}
+
+#[test]
+fn keep_lists_every_call() {
+    let calls = outgoing(CallNoise::Keep);
+    assert!(calls.contains("- `clone` — lib.rs:L2"), "{calls}");
+    assert!(calls.contains("- `record` — lib.rs:L3"), "{calls}");
+    assert!(!calls.contains("filtered"), "{calls}");
+}
@@ -0,0 +1,61 @@
+use cortexast::mapper::{centrality, format_centrality};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn most_imported_file_ranks_first() {
+    // Module graphs skip forbidden path components such as `tmp`, so stay out of /tmp.
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "src/util.ts", "export function helper() {}\n");
+    write(
+        root,
+        "src/api.ts",
+        "import { helper } from './util';\nexport function serve() { helper(); }\n",
+    );
+    write(
+        root,
+        "src/main.ts",
+        "import { helper } from './util';\nimport { serve } from './api';\nserve(); helper();\n",
+    );
+    write(root, "src/lonely.ts", "export const X = 1;\n");
+
+    let ranked = centrality(root, Path::new("."), 10).unwrap();
+    let paths: Vec<&str> = ranked.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(&paths[..2], &["src/util.ts", "src/api.ts"], "{ranked:?}");
+
+    let util = &ranked[0];
+    assert_eq!((util.in_degree, util.out_degree), (2, 0));
+    let main = ranked.iter().find(|f| f.path == "src/main.ts").unwrap();
+    assert_eq!((main.in_degree, main.out_degree), (0, 2));
+
+    let total: f64 = ranked.iter().map(|f| f.pagerank).sum();
+    assert!((total - 1.0).abs() < 1e-6, "pagerank sums to {total}");
+    let lonely = ranked.iter().find(|f| f.path == "src/lonely.ts").unwrap();
+    assert!(util.pagerank > lonely.pagerank, "{ranked:?}");
+
+    let table = format_centrality(&ranked);
+    assert!(table.contains("| 1 | src/util.ts |"), "{table}");
+}
+
+#[test]
+fn top_n_truncates_and_missing_root_errors() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "a.ts", "import { b } from './b';\n");
+    write(root, "b.ts", "export const b = 1;\n");
+
+    let ranked = centrality(root, Path::new("."), 1).unwrap();
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].path, "b.ts");
+
+    let err = centrality(root, Path::new("nope"), 5)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Graph root not found"), "{err}");
+}
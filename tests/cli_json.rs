@@ -0,0 +1,74 @@
+use std::process::Command;
+
+#[test]
+fn json_flag_prints_slice_result_as_one_object() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn answer() -> u32 {\n    42\n}\n",
+    )
+    .unwrap();
+    // Far over the default max_file_bytes, so it is listed but never inlined.
+    std::fs::write(dir.path().join("src/huge.rs"), "// x\n".repeat(200_000)).unwrap();
+
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .args(["--target", "src", "--json"])
+        .output()
+        .expect("run cortexast --json");
+    assert!(output.status.success(), "exit: {:?}", output.status);
+    // Debug builds still print `debug_log!` diagnostics such as the large-file skip.
+    if !cfg!(debug_assertions) {
+        assert!(
+            output.stderr.is_empty(),
+            "--json must keep stderr quiet, got: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let v: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is a single JSON object");
+    let xml = v["xml"].as_str().expect("xml string");
+    assert!(xml.contains("pub fn answer"));
+    assert_eq!(v["meta"]["target"], "src");
+    assert_eq!(v["meta"]["totalChars"], xml.len());
+    assert_eq!(v["included"], serde_json::json!(["src/lib.rs"]));
+    assert_eq!(v["excluded"], serde_json::json!(["src/huge.rs"]));
+
+    // The usual output files are still written.
+    assert!(dir.path().join(".cortexast/active_context.xml").is_file());
+}
+
+#[test]
+fn profile_flag_records_phase_timings() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "pub fn answer() {}\n").unwrap();
+
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .args(["--target", "src", "--json", "--profile"])
+        .output()
+        .expect("run cortexast --json --profile");
+    assert!(output.status.success(), "exit: {:?}", output.status);
+    assert!(output.stderr.is_empty(), "--json keeps stderr quiet");
+    let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let profile = &v["meta"]["profileMs"];
+    assert!(profile["slice"].is_u64(), "{profile}");
+    assert!(profile["total"].is_u64(), "{profile}");
+
+    // Without --json the breakdown goes to stderr; the env var works like the flag.
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .env("CORTEXAST_PROFILE", "1")
+        .args(["--target", "src"])
+        .output()
+        .expect("run cortexast with CORTEXAST_PROFILE");
+    assert!(output.status.success(), "exit: {:?}", output.status);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("profile:"), "{stderr}");
+    assert!(stderr.contains("slice"), "{stderr}");
+}
@@ -0,0 +1,84 @@
+use cortexast::chronos::compare_symbols;
+
+const SOURCE: &str = "\
+pub fn handle_v1(x: u32) -> u32 {
+    let y = x + 1;
+    y * 2
+}
+
+pub fn handle_v2(x: u32) -> u32 {
+    let y = x + 1;
+    let z = y * 2;
+    z - 3
+}
+
+pub fn handle_copy(x: u32) -> u32 {
+    let y = x + 1;
+    y * 2
+}
+";
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("api.rs"), SOURCE).unwrap();
+    dir
+}
+
+#[test]
+fn drifted_symbols_get_a_line_diff() {
+    let dir = fixture();
+    let out = compare_symbols(dir.path(), "api.rs", "handle_v1", "api.rs", "handle_v2").unwrap();
+    assert!(out.contains("```diff"), "{out}");
+    assert!(out.contains("-    y * 2\n"), "{out}");
+    assert!(out.contains("+    let z = y * 2;\n"), "{out}");
+    assert!(out.contains("+    z - 3\n"), "{out}");
+    assert!(out.contains("     let y = x + 1;\n"), "{out}");
+    assert!(out.contains("4 → 5 lines: +3 / -2"), "{out}");
+}
+
+#[test]
+fn copies_differing_only_by_name_report_no_diff() {
+    let dir = fixture();
+    let out = compare_symbols(dir.path(), "api.rs", "handle_v1", "api.rs", "handle_copy").unwrap();
+    assert!(out.contains("NO STRUCTURAL DIFF"), "{out}");
+    assert!(out.contains("apart from the name"), "{out}");
+}
+
+#[test]
+fn only_whole_identifiers_count_as_the_name() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("short.rs"),
+        "\
+pub fn f(buf: u32) -> u32 {
+    if buf == 0 { 0 } else { f(buf - 1) }
+}
+
+pub fn g(bug: u32) -> u32 {
+    if bug == 0 { 0 } else { g(bug - 1) }
+}
+
+pub fn h(buf: u32) -> u32 {
+    if buf == 0 { 0 } else { h(buf - 1) }
+}
+",
+    )
+    .unwrap();
+
+    // Replacing the substring `f` would also turn `buf` into `bug`.
+    let out = compare_symbols(dir.path(), "short.rs", "f", "short.rs", "g").unwrap();
+    assert!(!out.contains("NO STRUCTURAL DIFF"), "{out}");
+    assert!(out.contains("-    if buf == 0 { 0 } else { f(buf - 1) }\n"), "{out}");
+
+    let out = compare_symbols(dir.path(), "short.rs", "f", "short.rs", "h").unwrap();
+    assert!(out.contains("apart from the name"), "{out}");
+}
+
+#[test]
+fn missing_symbol_is_an_error() {
+    let dir = fixture();
+    let err = compare_symbols(dir.path(), "api.rs", "handle_v1", "api.rs", "handle_v9")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("handle_v9"), "{err}");
+}
@@ -0,0 +1,134 @@
+//! Cyclomatic complexity: 1 + decision points per callable, on `Symbol::complexity`
+//! and as `⚠ complexity N` markers in deep repo maps.
+
+use cortexast::inspector::{analyze_source, repo_map_with_filter, RepoMapOptions};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// name -> complexity for every symbol of `source` parsed as `file`.
+fn complexities(file: &str, source: &str) -> BTreeMap<String, Option<u32>> {
+    analyze_source(Path::new(file), source)
+        .unwrap()
+        .symbols
+        .into_iter()
+        .map(|s| (s.name, s.complexity))
+        .collect()
+}
+
+const RUST: &str = "\
+pub struct Config;
+
+pub fn flat(x: u32) -> u32 {
+    x + 1
+}
+
+pub fn branchy(x: u32, ok: bool) -> u32 {
+    if x > 10 && ok {
+        1
+    } else if x > 5 {
+        2
+    } else {
+        match x {
+            0 => 0,
+            1 | 2 => 1,
+            _ => 3,
+        }
+    }
+}
+
+pub fn loops(v: &[u32]) -> usize {
+    let mut n = 0;
+    for x in v {
+        while n < *x {
+            n += 1;
+        }
+    }
+    v.iter().filter(|x| **x > 1 || **x == 0).count()
+}
+";
+
+#[test]
+fn rust_counts_branches_arms_loops_and_short_circuits() {
+    let c = complexities("lib.rs", RUST);
+    assert_eq!(c["flat"], Some(1));
+    // if, &&, else if, three match arms.
+    assert_eq!(c["branchy"], Some(7));
+    // for, while, and the `||` inside the closure.
+    assert_eq!(c["loops"], Some(4));
+    assert_eq!(c["Config"], None, "only callables get a complexity");
+}
+
+const PYTHON: &str = "\
+def classify(n, strict):
+    if n < 0 and strict:
+        raise ValueError(n)
+    elif n == 0 or n is None:
+        return 'zero'
+    try:
+        for i in range(n):
+            pass
+    except KeyError:
+        pass
+    except ValueError:
+        pass
+    return 'big' if n > 100 else 'small'
+
+
+class Plain:
+    def get(self):
+        return 1
+";
+
+#[test]
+fn python_counts_elif_except_and_conditional_expressions() {
+    let c = complexities("m.py", PYTHON);
+    // if, and, elif, or, for, two excepts, conditional expression.
+    assert_eq!(c["classify"], Some(9));
+    assert_eq!(c["get"], Some(1));
+    assert_eq!(c["Plain"], None);
+}
+
+const TYPESCRIPT: &str = "\
+export function label(kind: string, n?: number): string {
+  switch (kind) {
+    case 'a':
+      return 'A';
+    case 'b':
+      return 'B';
+    default:
+      break;
+  }
+  for (const c of kind) {
+    if (c === 'x' || c === 'y') {
+      return c;
+    }
+  }
+  return n ? String(n) : (kind ?? 'none');
+}
+";
+
+#[test]
+fn typescript_counts_cases_loops_and_ternaries() {
+    let c = complexities("label.ts", TYPESCRIPT);
+    // Two cases (not `default`), for-of, if, ||, ternary; `??` is not a branch.
+    assert_eq!(c["label"], Some(7));
+}
+
+#[test]
+fn repo_map_flags_functions_at_the_threshold() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lib.rs"), RUST).unwrap();
+
+    let map = |threshold| {
+        let opts = RepoMapOptions {
+            complexity_threshold: threshold,
+            ..RepoMapOptions::default()
+        };
+        repo_map_with_filter(dir.path(), &opts).unwrap()
+    };
+    let flagged = map(Some(4));
+    assert!(flagged.contains("branchy  ⚠ complexity 7"), "{flagged}");
+    assert!(flagged.contains("loops  ⚠ complexity 4"), "{flagged}");
+    assert!(!flagged.contains("flat  ⚠"), "{flagged}");
+    assert!(!map(None).contains('⚠'), "{}", map(None));
+}
@@ -0,0 +1,93 @@
+//! C# namespaces, records and properties in the symbol list, and accessor bodies
+//! pruned from the skeleton like method bodies.
+//!
+//! C# uses a Wasm grammar, which is downloaded on demand rather than built in, so
+//! the test is ignored by default. With `c_sharp.wasm` in `~/.cortex-works/grammars`
+//! (and no stale `c_sharp_prune.scm` beside it), run it with
+//! `cargo test --test csharp_skeleton -- --ignored`.
+
+use cortexast::inspector::{analyze_file, render_skeleton};
+
+const ACCOUNT_CS: &str = r#"namespace Acme.Billing
+{
+    public record Invoice(string Id, decimal Total);
+
+    public class Account
+    {
+        private decimal _balance;
+
+        public decimal Balance
+        {
+            get { return _balance * 100m; }
+            set { _balance = value / 100m; }
+        }
+
+        public string Owner { get; init; }
+
+        public void Deposit(decimal amount)
+        {
+            _balance += amount;
+        }
+    }
+}
+"#;
+
+const LEDGER_CS: &str = r#"namespace Acme.Ledger;
+
+public record Entry
+{
+    public int Line { get; set; }
+}
+"#;
+
+#[test]
+#[ignore = "needs the c_sharp Wasm grammar"]
+fn namespaces_records_and_properties_are_extracted_and_accessors_pruned() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let account = dir.path().join("Account.cs");
+    let ledger = dir.path().join("Ledger.cs");
+    std::fs::write(&account, ACCOUNT_CS).unwrap();
+    std::fs::write(&ledger, LEDGER_CS).unwrap();
+
+    let kinds = |path| -> Vec<(String, String)> {
+        analyze_file(path)
+            .unwrap()
+            .symbols
+            .into_iter()
+            .map(|s| (s.name, s.kind))
+            .collect()
+    };
+    let symbols = kinds(&account);
+    for (name, kind) in [
+        ("Acme.Billing", "namespace"),
+        ("Invoice", "class"),
+        ("Account", "class"),
+        ("Balance", "property"),
+        ("Owner", "property"),
+        ("Deposit", "function"),
+    ] {
+        assert!(
+            symbols.contains(&(name.to_string(), kind.to_string())),
+            "{name} ({kind}) missing: {symbols:?}"
+        );
+    }
+    let symbols = kinds(&ledger);
+    for (name, kind) in [
+        ("Acme.Ledger", "namespace"),
+        ("Entry", "class"),
+        ("Line", "property"),
+    ] {
+        assert!(
+            symbols.contains(&(name.to_string(), kind.to_string())),
+            "{name} ({kind}) missing: {symbols:?}"
+        );
+    }
+
+    let skeleton = render_skeleton(&account).unwrap();
+    assert!(skeleton.contains("public decimal Balance"), "{skeleton}");
+    assert!(skeleton.contains("get"), "{skeleton}");
+    assert!(!skeleton.contains("_balance * 100m"), "{skeleton}");
+    assert!(!skeleton.contains("_balance = value / 100m"), "{skeleton}");
+    assert!(!skeleton.contains("_balance += amount"), "{skeleton}");
+    assert!(skeleton.contains("public string Owner { get; init; }"), "{skeleton}");
+}
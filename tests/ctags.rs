@@ -0,0 +1,79 @@
+use cortexast::inspector::ctags;
+use std::path::Path;
+use std::process::Command;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    write(
+        dir.path(),
+        "src/lib.rs",
+        "pub struct Config {\n    pub path: String,\n}\n\npub fn load(p: &str) -> Config {\n    Config { path: p.into() }\n}\n\npub fn ratio(a: f64, b: f64) -> f64 { a / b }\n",
+    );
+    write(
+        dir.path(),
+        "web/api.ts",
+        "export function fetchUser(url: string) {\n  return fetch(url + '/user');\n}\n",
+    );
+    dir
+}
+
+#[test]
+fn tags_file_is_sorted_extended_ctags() {
+    let dir = fixture();
+    let tags = ctags(dir.path(), Path::new(".")).unwrap();
+    let lines: Vec<&str> = tags.lines().collect();
+
+    assert!(lines[0].starts_with("!_TAG_FILE_FORMAT\t2"), "{tags}");
+    assert!(lines[1].starts_with("!_TAG_FILE_SORTED\t1"), "{tags}");
+    let entries: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|l| !l.starts_with("!_TAG_"))
+        .collect();
+    let mut sorted = entries.clone();
+    sorted.sort();
+    assert_eq!(entries, sorted);
+
+    assert!(
+        entries.contains(
+            &"load\tsrc/lib.rs\t/^pub fn load(p: &str) -> Config {$/;\"\tfunction\tline:5"
+        ),
+        "{tags}"
+    );
+    assert!(
+        entries.contains(&"Config\tsrc/lib.rs\t/^pub struct Config {$/;\"\tstruct\tline:1"),
+        "{tags}"
+    );
+    // `/` in the declaration line is escaped inside the search pattern.
+    assert!(
+        entries.contains(&"ratio\tsrc/lib.rs\t/^pub fn ratio(a: f64, b: f64) -> f64 { a \\/ b }$/;\"\tfunction\tline:9"),
+        "{tags}"
+    );
+    assert!(
+        entries
+            .iter()
+            .any(|l| l.starts_with("fetchUser\tweb/api.ts\t")),
+        "{tags}"
+    );
+}
+
+#[test]
+fn cli_writes_tags_file_scoped_to_target() {
+    let dir = fixture();
+    let output = Command::new(env!("CARGO_BIN_EXE_cortexast"))
+        .current_dir(dir.path())
+        .args(["--target", "src", "--tags"])
+        .output()
+        .expect("run cortexast --tags");
+    assert!(output.status.success(), "exit: {:?}", output.status);
+
+    let tags = std::fs::read_to_string(dir.path().join("tags")).unwrap();
+    assert!(tags.contains("load\tsrc/lib.rs\t"), "{tags}");
+    assert!(!tags.contains("fetchUser"), "{tags}");
+}
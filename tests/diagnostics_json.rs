@@ -0,0 +1,116 @@
+//! `run_diagnostics` with `format: "json"`: cargo and tsc output parsed into
+//! `{level, code, message, file, line, column, context}` records.
+#![cfg(unix)]
+
+use cortexast::inspector::{run_diagnostics, DiagnosticsFormat};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+fn fake_tool(bin: &Path, name: &str, stdout: &str) {
+    let path = bin.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\ncat <<'OUT'\n{stdout}\nOUT\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn json_diagnostics(project: &Path) -> Vec<serde_json::Value> {
+    let text = run_diagnostics(project, DiagnosticsFormat::Json, 30).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+const CARGO_OUTPUT: &str = r#"{"reason":"compiler-artifact","package_id":"demo"}
+{"reason":"compiler-message","message":{"level":"error","code":{"code":"E0308","explanation":null},"message":"mismatched types","spans":[{"file_name":"src/lib.rs","line_start":2,"column_start":17}]}}
+{"reason":"compiler-message","message":{"level":"warning","code":null,"message":"unused variable: `x`","spans":[{"file_name":"src/lib.rs","line_start":3,"column_start":9}]}}
+{"reason":"compiler-message","message":{"level":"note","code":null,"message":"ignored","spans":[]}}
+{"reason":"compiler-message","message":{"level":"error","code":null,"message":"aborting due to 1 previous error","spans":[]}}
+not json at all
+{"reason":"build-finished","success":false}"#;
+
+const TSC_OUTPUT: &str = "src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.
+tsconfig.json: error TS5023: Unknown compiler option 'strictest'.
+src/b.ts(1,1): warning TS6133: 'x' is declared but its value is never read.
+Found 2 errors in 2 files.";
+
+// One test, because every case swaps out the process-wide PATH.
+#[test]
+fn cargo_and_tsc_output_become_structured_records() {
+    let cargo_project = tempfile::tempdir().unwrap();
+    let root = cargo_project.path();
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/lib.rs"),
+        "pub fn f() -> u32 {\n    let s: u32 = \"no\";\n    let x = 1;\n    s\n}\n",
+    )
+    .unwrap();
+
+    let ts_project = tempfile::tempdir().unwrap();
+    std::fs::write(ts_project.path().join("package.json"), "{}").unwrap();
+    std::fs::create_dir_all(ts_project.path().join("src")).unwrap();
+    std::fs::write(
+        ts_project.path().join("src/a.ts"),
+        "export const a = 1;\n\nconst n: number = 'x';\n",
+    )
+    .unwrap();
+
+    let bin = tempfile::tempdir().unwrap();
+    fake_tool(bin.path(), "cargo", CARGO_OUTPUT);
+    fake_tool(bin.path(), "npx", TSC_OUTPUT);
+    let original_path = std::env::var_os("PATH").unwrap_or_default();
+    let mut path = std::ffi::OsString::from(bin.path());
+    path.push(":");
+    path.push(&original_path);
+    std::env::set_var("PATH", path);
+
+    let cargo = json_diagnostics(root);
+    let tsc = json_diagnostics(ts_project.path());
+    fake_tool(bin.path(), "npx", "npm ERR! could not determine executable to run");
+    let npx_failed = json_diagnostics(ts_project.path());
+    let markdown = run_diagnostics(root, DiagnosticsFormat::Markdown, 30).unwrap();
+
+    std::env::set_var("PATH", original_path);
+
+    // cargo: errors and warnings only; span-less messages keep no location.
+    assert_eq!(cargo.len(), 3, "{cargo:#?}");
+    assert_eq!(cargo[0]["level"], "error");
+    assert_eq!(cargo[0]["code"], "E0308");
+    assert_eq!(cargo[0]["message"], "mismatched types");
+    assert_eq!(cargo[0]["file"], "src/lib.rs");
+    assert_eq!(cargo[0]["line"], 2);
+    assert_eq!(cargo[0]["column"], 17);
+    let context = cargo[0]["context"].as_str().unwrap();
+    assert!(context.contains(">>>") && context.contains("let s: u32"), "{context}");
+    assert_eq!(cargo[1]["level"], "warning");
+    assert!(cargo[1]["code"].is_null(), "{:?}", cargo[1]);
+    assert_eq!(cargo[1]["line"], 3);
+    assert!(cargo[2]["file"].is_null() && cargo[2]["line"].is_null(), "{:?}", cargo[2]);
+    assert!(markdown.contains("mismatched types"), "{markdown}");
+
+    // tsc: `file(line,col): level TSxxxx: message`, or `file: ...` without a position.
+    assert_eq!(tsc.len(), 3, "{tsc:#?}");
+    assert_eq!(tsc[0]["level"], "error");
+    assert_eq!(tsc[0]["code"], "TS2322");
+    assert_eq!(
+        tsc[0]["message"],
+        "Type 'string' is not assignable to type 'number'."
+    );
+    assert_eq!(tsc[0]["file"], "src/a.ts");
+    assert_eq!((tsc[0]["line"].clone(), tsc[0]["column"].clone()), (3.into(), 7.into()));
+    assert!(tsc[0]["context"].as_str().unwrap().contains("const n"), "{:?}", tsc[0]);
+    assert_eq!(tsc[1]["file"], "tsconfig.json");
+    assert!(tsc[1]["line"].is_null() && tsc[1]["column"].is_null(), "{:?}", tsc[1]);
+    assert_eq!(tsc[2]["level"], "warning");
+    assert_eq!(tsc[2]["code"], "TS6133");
+
+    // Unparseable output is still reported as one error rather than "no diagnostics".
+    assert_eq!(npx_failed.len(), 1, "{npx_failed:#?}");
+    assert_eq!(npx_failed[0]["level"], "error");
+    assert!(npx_failed[0]["file"].is_null());
+    assert!(
+        npx_failed[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("could not determine executable"),
+        "{:?}",
+        npx_failed[0]
+    );
+}
@@ -0,0 +1,50 @@
+//! `run_diagnostics` subprocess hardening: a stalled compiler is killed after
+//! `timeout_secs`, and a missing one is reported plainly.
+#![cfg(unix)]
+
+use cortexast::inspector::{run_diagnostics, DiagnosticsFormat};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+fn fake_cargo(bin: &Path, script: &str) {
+    let path = bin.join("cargo");
+    std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+// One test, because both cases swap out the process-wide PATH.
+#[test]
+fn stalled_or_missing_cargo_fails_fast() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    )
+    .unwrap();
+    let bin = tempfile::tempdir().unwrap();
+    let original_path = std::env::var_os("PATH");
+
+    std::env::set_var("PATH", bin.path());
+    let missing = run_diagnostics(project.path(), DiagnosticsFormat::Markdown, 5)
+        .unwrap_err()
+        .to_string();
+
+    // Spin on shell builtins only: PATH no longer reaches `sleep`.
+    fake_cargo(bin.path(), "while :; do :; done");
+    let started = Instant::now();
+    let stalled = run_diagnostics(project.path(), DiagnosticsFormat::Json, 1)
+        .unwrap_err()
+        .to_string();
+    let elapsed = started.elapsed();
+
+    match original_path {
+        Some(p) => std::env::set_var("PATH", p),
+        None => std::env::remove_var("PATH"),
+    }
+
+    assert!(missing.contains("`cargo` is not installed"), "{missing}");
+    assert!(stalled.contains("diagnostics timed out"), "{stalled}");
+    assert!(stalled.contains("timeout_secs"), "{stalled}");
+    assert!(elapsed < Duration::from_secs(15), "took {elapsed:?}");
+}
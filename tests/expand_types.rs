@@ -0,0 +1,35 @@
+use cortexast::inspector::expand_symbol_types;
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn signature_types_resolve_across_the_workspace() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "src/handler.rs",
+        "use crate::model::Request;\n\
+         \n\
+         pub fn handle(req: Request) -> Reply {\n    \
+             Reply { ok: !req.body.is_empty() }\n\
+         }\n\
+         \n\
+         pub struct Reply {\n    pub ok: bool,\n}\n",
+    );
+    write(
+        root,
+        "src/model.rs",
+        "pub struct Request {\n    pub body: String,\n}\n\nimpl Request {\n    pub fn new() -> Self {\n        Request { body: String::new() }\n    }\n}\n",
+    );
+
+    let out = expand_symbol_types(&root.join("src/handler.rs"), "handle", None, root).unwrap();
+    assert!(out.contains("expand_types: 2 related type(s)"), "{out}");
+    assert!(out.contains("pub struct Request"), "{out}");
+    assert!(out.contains("pub struct Reply"), "{out}");
+}
@@ -0,0 +1,78 @@
+//! `find_by_attribute`: declarations carrying an attribute, decorator or annotation.
+
+use cortexast::inspector::{
+    extract_symbols_from_source, find_by_attribute, format_attribute_matches,
+};
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/handlers.rs"),
+        "#[get(\"/users\")]\n\
+         // Lists every user.\n\
+         pub async fn list_users() -> String {\n    String::new()\n}\n\n\
+         #[derive(Debug, Clone)]\n\
+         pub struct User;\n\n\
+         pub fn plain() {}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("app.py"),
+        "@app.route(\"/\")\n\
+         def index():\n    return 'ok'\n\n\
+         def helper():\n    pass\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn symbols_carry_their_attributes_in_source_order() {
+    let src = "#[derive(Debug)]\n#[serde(rename_all = \"camelCase\")]\npub struct Config {\n    x: u32,\n}\n\npub fn bare() {}\n";
+    let symbols = extract_symbols_from_source(std::path::Path::new("lib.rs"), src);
+    let config = symbols.iter().find(|s| s.name == "Config").unwrap();
+    assert_eq!(
+        config.attributes,
+        ["#[derive(Debug)]", "#[serde(rename_all = \"camelCase\")]"]
+    );
+    let bare = symbols.iter().find(|s| s.name == "bare").unwrap();
+    assert!(bare.attributes.is_empty(), "{bare:?}");
+}
+
+#[test]
+fn finds_rust_attributes_and_python_decorators() {
+    let dir = fixture();
+    let routes = find_by_attribute(dir.path(), "#[GET(").unwrap();
+    assert_eq!(routes.len(), 1, "{routes:?}");
+    assert_eq!(routes[0].name, "list_users");
+    assert_eq!(routes[0].file, "src/handlers.rs");
+    assert_eq!(routes[0].attribute, "#[get(\"/users\")]");
+
+    let python = find_by_attribute(dir.path(), "app.route").unwrap();
+    let names: Vec<&str> = python.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["index"], "{python:?}");
+    assert_eq!(python[0].file, "app.py");
+
+    let derives = find_by_attribute(dir.path(), "derive").unwrap();
+    let names: Vec<&str> = derives.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["User"], "{derives:?}");
+
+    assert!(find_by_attribute(dir.path(), "  ").is_err());
+}
+
+#[test]
+fn formatting_lists_matches_by_file() {
+    let dir = fixture();
+    let matches = find_by_attribute(dir.path(), "route").unwrap();
+    let out = format_attribute_matches("route", &matches, 8_000);
+    assert!(out.contains("(1 of 1)"), "{out}");
+    assert!(out.contains("### app.py"), "{out}");
+    assert!(out.contains("index — `@app.route(\"/\")`"), "{out}");
+
+    let none = format_attribute_matches("#[test]", &[], 8_000);
+    assert!(
+        none.contains("No declarations with an attribute matching"),
+        "{none}"
+    );
+}
@@ -0,0 +1,59 @@
+//! `find_usages` files declaration sites under "Definitions", apart from real usages.
+
+use cortexast::inspector::{find_usages, GitignoreMode, PathStyle};
+
+/// `### {category} (N)` section of `text`, up to the next heading.
+fn section<'a>(text: &'a str, category: &str) -> &'a str {
+    let start = text
+        .find(&format!("### {category} ("))
+        .unwrap_or_else(|| panic!("no {category} section in:\n{text}"));
+    let rest = &text[start + 4..];
+    &rest[..rest.find("### ").unwrap_or(rest.len())]
+}
+
+#[test]
+fn declaration_names_are_definitions() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("lib.rs"),
+        "pub struct Ledger;\n\n\
+         pub fn settle(l: Ledger) -> Ledger {\n    l\n}\n\n\
+         pub fn run() {\n    settle(Ledger);\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("jobs.py"),
+        "def settle(batch):\n    return batch\n\nsettle([])\n",
+    )
+    .unwrap();
+
+    let text = find_usages(
+        dir.path(),
+        "settle",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    let defs = section(&text, "Definitions");
+    assert!(defs.starts_with("Definitions (2)"), "{text}");
+    assert!(defs.contains("lib.rs:3]"), "{text}");
+    assert!(defs.contains("jobs.py:1]"), "{text}");
+    let calls = section(&text, "Calls");
+    assert!(calls.contains("lib.rs:8]"), "{text}");
+    assert!(calls.contains("jobs.py:4]"), "{text}");
+    assert!(!calls.contains("lib.rs:3]"), "{text}");
+
+    let text = find_usages(
+        dir.path(),
+        "Ledger",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    let defs = section(&text, "Definitions");
+    assert!(defs.starts_with("Definitions (1)"), "{text}");
+    assert!(defs.contains("lib.rs:1]"), "{text}");
+    assert!(section(&text, "Type Refs").contains("lib.rs:3]"), "{text}");
+}
@@ -0,0 +1,54 @@
+//! `find_usages` names the function or method each hit sits in.
+
+use cortexast::inspector::{find_usages, GitignoreMode, PathStyle};
+
+#[test]
+fn hits_name_their_enclosing_callable() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("orders.rs"),
+        "pub const TAX: u32 = 7;\n\n\
+         pub fn process_order(total: u32) -> u32 {\n    total + TAX\n}\n\n\
+         pub fn refund(total: u32) -> u32 {\n    total - TAX\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("orders.py"),
+        "class Cart:\n    def checkout(self):\n        return process_order(3)\n",
+    )
+    .unwrap();
+
+    let text = find_usages(
+        dir.path(),
+        "TAX",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    assert!(
+        text.contains("[orders.rs:4] in function process_order()\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("[orders.rs:8] in function refund()\n"),
+        "{text}"
+    );
+    // Top-level declaration: nothing encloses it.
+    assert!(text.contains("[orders.rs:1]\n"), "{text}");
+
+    let text = find_usages(
+        dir.path(),
+        "process_order",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    // The definition is not "in" itself.
+    assert!(text.contains("[orders.rs:3]\n"), "{text}");
+    assert!(
+        text.contains("[orders.py:3] in function checkout()\n"),
+        "{text}"
+    );
+}
@@ -0,0 +1,46 @@
+//! `find_usages` falls back to a word search in files no driver parses.
+
+use cortexast::inspector::{find_usages, usage_counts, GitignoreMode, PathStyle};
+
+#[test]
+fn unparsed_files_report_lexical_hits() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lib.rs"), "pub fn process_order() {}\n").unwrap();
+    std::fs::write(
+        dir.path().join("Billing.scala"),
+        "object Billing {\n\
+         \x20 // process_order is called below\n\
+         \x20 val label = \"process_order\"\n\
+         \x20 def run() = process_order()\n\
+         \x20 def other() = process_order_v2()\n\
+         }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("NOTES.md"), "call process_order()\n").unwrap();
+
+    let text = find_usages(
+        dir.path(),
+        "process_order",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    assert!(text.contains("### Lexical (1)"), "{text}");
+    assert!(text.contains("[Billing.scala:4]\n"), "{text}");
+    assert!(!text.contains("Billing.scala:2"), "{text}");
+    assert!(!text.contains("Billing.scala:3"), "{text}");
+    assert!(!text.contains("NOTES.md"), "{text}");
+    assert!(text.contains("[lib.rs:1]"), "{text}");
+
+    // Counts (and renames) stay AST-only.
+    let counts = usage_counts(
+        dir.path(),
+        "process_order",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    assert_eq!(counts, vec![("lib.rs".to_string(), 1)]);
+}
@@ -0,0 +1,82 @@
+//! `find_usages` with `UsageSort::Relevance`: the defining file and its module first,
+//! then files the rest of the repo depends on.
+
+use cortexast::inspector::{find_usages_with_sort, GitignoreMode, PathStyle, UsageSort};
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let write = |rel: &str, text: &str| {
+        let path = dir.path().join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    };
+    write(
+        "core/ledger.py",
+        "def settle(batch):\n    return batch\n\nsettle([])\n",
+    );
+    write("core/jobs.py", "def run():\n    settle([])\n");
+    // Nothing imports the fixture; it imports the hub, which ranks it higher.
+    write(
+        "aaa/fixture.py",
+        "from zz.hub import helper\n\ndef case():\n    settle([])\n",
+    );
+    write("zz/hub.py", "def helper():\n    settle([])\n");
+    dir
+}
+
+/// `[file:line]` headers of the `### Calls` section, in output order.
+fn call_sites(text: &str) -> Vec<String> {
+    let start = text.find("### Calls (").expect("Calls section");
+    let rest = &text[start + 4..];
+    let section = &rest[..rest.find("### ").unwrap_or(rest.len())];
+    section
+        .lines()
+        .filter(|l| l.starts_with('['))
+        .map(|l| l[1..l.find(']').unwrap()].to_string())
+        .collect()
+}
+
+fn usages(dir: &tempfile::TempDir, sort: UsageSort) -> String {
+    find_usages_with_sort(
+        dir.path(),
+        "settle",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+        sort,
+    )
+    .unwrap()
+}
+
+#[test]
+fn path_sort_is_the_default_order() {
+    let dir = fixture();
+    let text = usages(&dir, UsageSort::default());
+    assert_eq!(
+        call_sites(&text),
+        [
+            "aaa/fixture.py:4",
+            "core/jobs.py:2",
+            "core/ledger.py:4",
+            "zz/hub.py:2"
+        ],
+        "{text}"
+    );
+}
+
+#[test]
+fn relevance_sort_ranks_by_proximity_then_centrality() {
+    let dir = fixture();
+    let text = usages(&dir, UsageSort::Relevance);
+    assert!(text.contains("most relevant first"), "{text}");
+    assert_eq!(
+        call_sites(&text),
+        [
+            "core/ledger.py:4",
+            "core/jobs.py:2",
+            "zz/hub.py:2",
+            "aaa/fixture.py:4"
+        ],
+        "{text}"
+    );
+}
@@ -0,0 +1,24 @@
+import { readFile } from "fs";
+
+/** Shop configuration. */
+export const MAX_ITEMS = 50;
+
+export class Cart {
+  private items: string[] = [];
+
+  add(item: string): void {
+    this.items.push(item);
+  }
+
+  get size(): number {
+    return this.items.length;
+  }
+}
+
+export function total(prices: number[]): number {
+  return prices.reduce((a, b) => a + b, 0);
+}
+
+export const format = (n: number): string => {
+  return n.toFixed(2);
+};
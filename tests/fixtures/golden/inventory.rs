@@ -0,0 +1,42 @@
+//! Inventory of stocked items.
+
+use std::collections::HashMap;
+
+/// A stocked item.
+pub struct Item {
+    pub name: String,
+    pub qty: u32,
+}
+
+pub enum Status {
+    InStock,
+    Empty,
+}
+
+pub trait Describe {
+    fn describe(&self) -> String;
+}
+
+// TODO: track reservations
+pub const LIMIT: u32 = 100;
+
+impl Item {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            qty: 0,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        if self.qty == 0 {
+            Status::Empty
+        } else {
+            Status::InStock
+        }
+    }
+}
+
+pub fn totals(items: &[Item]) -> HashMap<String, u32> {
+    items.iter().map(|i| (i.name.clone(), i.qty)).collect()
+}
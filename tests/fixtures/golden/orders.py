@@ -0,0 +1,18 @@
+"""Order helpers."""
+
+import json
+from dataclasses import dataclass
+
+
+@dataclass
+class Order:
+    id: int
+    total: float
+
+    def to_json(self) -> str:
+        return json.dumps({"id": self.id, "total": self.total})
+
+
+def load(text):
+    data = json.loads(text)
+    return Order(data["id"], data["total"])
@@ -0,0 +1,18 @@
+// 固定装置：中文注释 + emoji 🚀，用于锁定行号与字节偏移。
+// Fixture: CJK comments and emoji ahead of declarations.
+
+/// 计算总和 — sums the values.
+pub fn 计算总和(值: &[i32]) -> i32 {
+    let 标签 = "合计 ✅";
+    let _ = 标签;
+    值.iter().sum()
+}
+
+pub struct 配置 {
+    pub 名称: String, // 名称 🏷️
+}
+
+pub fn caller() -> i32 {
+    let _c = 配置 { 名称: "设置".to_string() };
+    计算总和(&[1, 2, 3]) // 调用 🚀
+}
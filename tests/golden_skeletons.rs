@@ -0,0 +1,84 @@
+//! Golden-file checks for the core language drivers.
+//!
+//! Each fixture in `tests/fixtures/golden` is run through `render_skeleton_from_source`
+//! and `extract_symbols_from_source`, and the output must match the checked-in
+//! `<fixture>.skeleton` and `<fixture>.symbols` files byte for byte.
+//!
+//! To cover a new driver, add a fixture and a `#[test]` calling [`check_golden`], then
+//! run `CORTEXAST_BLESS=1 cargo test --test golden_skeletons` to write its golden files.
+//! Review them before committing. Blessing also rewrites stale goldens after an
+//! intended rendering change.
+
+use cortexast::inspector::{extract_symbols_from_source, render_skeleton_from_source};
+use std::path::{Path, PathBuf};
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden")
+}
+
+/// One `L<start>-L<end> <kind> <name>` line per symbol (1-based lines), in source order.
+fn symbol_lines(path: &Path, source: &str) -> String {
+    let mut syms = extract_symbols_from_source(path, source);
+    syms.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.name.cmp(&b.name)));
+    syms.iter()
+        .map(|s| {
+            format!(
+                "L{}-L{} {} {}\n",
+                s.line + 1,
+                s.line_end + 1,
+                s.kind,
+                s.name
+            )
+        })
+        .collect()
+}
+
+/// Compare `fixture`'s skeleton and symbols with its golden files, or rewrite the
+/// golden files when `CORTEXAST_BLESS` is set.
+fn check_golden(fixture: &str) {
+    let path = golden_dir().join(fixture);
+    let source = std::fs::read_to_string(&path).unwrap();
+    let bless = std::env::var_os("CORTEXAST_BLESS").is_some();
+
+    let outputs = [
+        (
+            "skeleton",
+            render_skeleton_from_source(&path, &source).unwrap(),
+        ),
+        ("symbols", symbol_lines(&path, &source)),
+    ];
+    for (suffix, actual) in outputs {
+        let golden = golden_dir().join(format!("{fixture}.{suffix}"));
+        if bless {
+            std::fs::write(&golden, &actual).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap_or_else(|_| {
+            panic!(
+                "missing {}; run with CORTEXAST_BLESS=1 to create it",
+                golden.display()
+            )
+        });
+        assert_eq!(
+            actual,
+            expected,
+            "{fixture}: {suffix} differs from {}",
+            golden.display()
+        );
+    }
+}
+
+#[test]
+fn rust_driver() {
+    check_golden("inventory.rs");
+}
+
+#[test]
+fn typescript_driver() {
+    check_golden("cart.ts");
+}
+
+#[test]
+fn python_driver() {
+    check_golden("orders.py");
+}
@@ -0,0 +1,36 @@
+use cortexast::inspector::{analyze_file, imports_exports};
+
+#[test]
+fn imports_exports_matches_analyze_file_without_symbols() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("api.ts");
+    std::fs::write(
+        &path,
+        "import { a } from './a';\nimport b from 'lib-b';\n\nexport function serve() {}\nexport const PORT = 80;\nfunction internal() {}\n",
+    )
+    .unwrap();
+
+    let surface = imports_exports(&path).unwrap();
+    let full = analyze_file(&path).unwrap();
+    assert_eq!(surface.imports, full.imports);
+    assert_eq!(surface.exports, full.exports);
+    assert!(surface.imports.iter().any(|i| i == "./a"), "{surface:?}");
+    assert!(surface.exports.iter().any(|e| e == "serve"), "{surface:?}");
+    assert!(
+        !surface.exports.iter().any(|e| e == "internal"),
+        "{surface:?}"
+    );
+
+    let json = serde_json::to_value(&surface).unwrap();
+    assert!(json.get("symbols").is_none(), "{json}");
+    assert_eq!(json["file"], surface.file.as_str());
+}
+
+#[test]
+fn unsupported_extension_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.xyz");
+    std::fs::write(&path, "hello\n").unwrap();
+    let err = imports_exports(&path).unwrap_err().to_string();
+    assert!(err.contains("Unsupported file extension"), "{err}");
+}
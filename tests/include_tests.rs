@@ -0,0 +1,115 @@
+//! `scan.include_tests` keeps, drops or isolates files matched by `scan.test_patterns`.
+
+use cortexast::config::{Config, IncludeTests, ScanConfig};
+use cortexast::inspector::{repo_map_with_filter, RepoMapOptions};
+use cortexast::scanner::{scan_workspace, ScanOptions, TestFilter};
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/lib.rs", "pub fn feature() {}\n");
+    write(root, "tests/it.rs", "fn integration() {}\n");
+    write(
+        root,
+        "pkg/server_test.go",
+        "package pkg\n\nfunc TestServe() {}\n",
+    );
+    write(root, "web/app.spec.ts", "export function specCase() {}\n");
+    write(root, "py/test_api.py", "def test_api():\n    pass\n");
+    write(root, "py/api.py", "def api():\n    pass\n");
+    dir
+}
+
+fn scanned(root: &Path, mode: IncludeTests) -> Vec<String> {
+    let opts = ScanOptions {
+        repo_root: root.to_path_buf(),
+        target: PathBuf::from("."),
+        max_file_bytes: 512 * 1024,
+        exclude_dir_names: vec![],
+        exclude_dir_paths: vec![],
+        include_linguist_generated: false,
+        test_patterns: ScanConfig::default().test_patterns,
+        include_tests: mode,
+    };
+    scan_workspace(&opts)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.rel_path.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+#[test]
+fn default_patterns_classify_common_test_layouts() {
+    let tests = TestFilter::new(&ScanConfig::default().test_patterns, IncludeTests::Only);
+    for path in [
+        "tests/it.rs",
+        "crates/core/tests/it.rs",
+        "pkg/server_test.go",
+        "web/app.spec.ts",
+        "src/__tests__/app.tsx",
+        "py/test_api.py",
+    ] {
+        assert!(tests.is_test(path), "{path}");
+    }
+    for path in ["src/lib.rs", "py/api.py", "src/testing.rs", "web/app.ts"] {
+        assert!(!tests.is_test(path), "{path}");
+    }
+}
+
+#[test]
+fn scan_modes_split_tests_from_sources() {
+    let dir = fixture();
+    let root = dir.path();
+
+    assert_eq!(scanned(root, IncludeTests::All).len(), 6);
+    assert_eq!(
+        scanned(root, IncludeTests::Exclude),
+        vec!["py/api.py", "src/lib.rs"]
+    );
+    assert_eq!(
+        scanned(root, IncludeTests::Only),
+        vec![
+            "pkg/server_test.go",
+            "py/test_api.py",
+            "tests/it.rs",
+            "web/app.spec.ts"
+        ]
+    );
+}
+
+#[test]
+fn map_overview_hides_tests_and_says_so() {
+    let dir = fixture();
+    let tests = TestFilter::new(&Config::default().scan.test_patterns, IncludeTests::Exclude);
+    let opts = RepoMapOptions {
+        tests,
+        ..RepoMapOptions::default()
+    };
+    let out = repo_map_with_filter(dir.path(), &opts).unwrap();
+    assert!(out.contains("feature"), "{out}");
+    assert!(!out.contains("integration"), "{out}");
+    assert!(!out.contains("test_api"), "{out}");
+    assert!(
+        out.contains("test file(s) hidden by include_tests"),
+        "{out}"
+    );
+}
+
+#[test]
+fn include_tests_config_parses_lowercase() {
+    let cfg: Config = serde_json::from_str(r#"{"scan": {"include_tests": "only"}}"#).unwrap();
+    assert_eq!(cfg.scan.include_tests, IncludeTests::Only);
+    assert!(!cfg.scan.test_patterns.is_empty());
+    assert_eq!(
+        IncludeTests::parse(" Exclude "),
+        Some(IncludeTests::Exclude)
+    );
+    assert_eq!(IncludeTests::parse("none"), None);
+}
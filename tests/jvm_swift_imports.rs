@@ -0,0 +1,148 @@
+//! Java, Kotlin and Swift imports, parsed from real files, resolve to in-repo files
+//! in repo maps, the module graph and `centrality`.
+//!
+//! These languages use Wasm grammars, which are downloaded on demand rather than
+//! built in, so the tests are ignored by default. With `java.wasm`, `kotlin.wasm`
+//! and `swift.wasm` in `~/.cortex-works/grammars`, run them with
+//! `cargo test --test jvm_swift_imports -- --ignored`.
+
+use cortexast::inspector::analyze_file;
+use cortexast::mapper::{build_module_graph, build_repo_map_scoped, centrality};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Gradle build whose `app` module (Java + Kotlin) uses the `core` module.
+fn gradle_repo() -> tempfile::TempDir {
+    // Maps skip forbidden path components such as `tmp`, so stay out of /tmp.
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "settings.gradle.kts", "include(\":core\", \":app\")\n");
+    write(root, "core/build.gradle.kts", "plugins { kotlin(\"jvm\") }\n");
+    write(root, "app/build.gradle.kts", "plugins { kotlin(\"jvm\") }\n");
+    write(
+        root,
+        "core/src/main/java/com/acme/util/Strings.java",
+        "package com.acme.util;\n\npublic class Strings {\n    public static String trim(String s) { return s.trim(); }\n}\n",
+    );
+    write(
+        root,
+        "core/src/main/kotlin/com/acme/money/Money.kt",
+        "package com.acme.money\n\ndata class Money(val cents: Long)\n",
+    );
+    write(
+        root,
+        "app/src/main/java/com/acme/app/Main.java",
+        "package com.acme.app;\n\nimport com.acme.util.Strings;\nimport java.util.List;\n\npublic class Main {}\n",
+    );
+    write(
+        root,
+        "app/src/main/kotlin/com/acme/app/Cli.kt",
+        "package com.acme.app\n\nimport com.acme.money.Money\nimport com.acme.util.*\n\nfun main() = println(Money(1))\n",
+    );
+    dir
+}
+
+/// SwiftPM package whose `App` target imports the `Core` target.
+fn swift_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "Package.swift", "// swift-tools-version:5.9\nimport PackageDescription\n");
+    write(root, "Sources/Core/Model.swift", "public struct Model {}\n");
+    write(
+        root,
+        "Sources/App/main.swift",
+        "import Core\nimport Foundation\n\nlet model = Model()\n",
+    );
+    dir
+}
+
+fn import_edges(root: &Path, scope: &str) -> Vec<(String, String)> {
+    let map = build_repo_map_scoped(root, Path::new(scope)).unwrap();
+    let mut edges: Vec<(String, String)> = map
+        .edges
+        .into_iter()
+        .filter(|e| e.id.starts_with("import:"))
+        .map(|e| (e.source, e.target))
+        .collect();
+    edges.sort();
+    edges
+}
+
+#[test]
+#[ignore = "needs the java and kotlin Wasm grammars"]
+fn java_and_kotlin_imports_reach_the_core_module() {
+    let dir = gradle_repo();
+    let root = dir.path().canonicalize().unwrap();
+
+    let main = analyze_file(&root.join("app/src/main/java/com/acme/app/Main.java")).unwrap();
+    assert_eq!(main.imports, ["com.acme.util.Strings", "java.util.List"]);
+    let cli = analyze_file(&root.join("app/src/main/kotlin/com/acme/app/Cli.kt")).unwrap();
+    assert_eq!(cli.imports, ["com.acme.money.Money", "com.acme.util.*"]);
+
+    let edges = import_edges(&root, "app/src/main/java/com/acme/app");
+    assert!(
+        edges.contains(&(
+            "app/src/main/java/com/acme/app/Main.java".into(),
+            "core/src/main/java/com/acme/util/Strings.java".into()
+        )),
+        "{edges:?}"
+    );
+    let edges = import_edges(&root, "app/src/main/kotlin/com/acme/app");
+    assert!(
+        edges.contains(&(
+            "app/src/main/kotlin/com/acme/app/Cli.kt".into(),
+            "core/src/main/kotlin/com/acme/money/Money.kt".into()
+        )),
+        "{edges:?}"
+    );
+
+    let graph = build_module_graph(&root, Path::new(".")).unwrap();
+    let edge = graph
+        .edges
+        .iter()
+        .find(|e| e.source == "app" && e.target == "core")
+        .unwrap_or_else(|| panic!("no app -> core edge: {:?}", graph.edges));
+    assert_eq!(edge.weight, 3);
+
+    let ranked = centrality(&root, Path::new("."), 10).unwrap();
+    let strings = ranked
+        .iter()
+        .find(|f| f.path == "core/src/main/java/com/acme/util/Strings.java")
+        .unwrap_or_else(|| panic!("{ranked:?}"));
+    assert_eq!(strings.in_degree, 2);
+}
+
+#[test]
+#[ignore = "needs the swift Wasm grammar"]
+fn swift_imports_reach_other_swiftpm_targets() {
+    let dir = swift_repo();
+    let root = dir.path().canonicalize().unwrap();
+
+    let main = analyze_file(&root.join("Sources/App/main.swift")).unwrap();
+    assert_eq!(main.imports, ["Core", "Foundation"]);
+
+    let edges = import_edges(&root, "Sources/App");
+    assert!(
+        edges.contains(&(
+            "Sources/App/main.swift".into(),
+            "Sources/Core/Model.swift".into()
+        )),
+        "{edges:?}"
+    );
+    assert!(
+        edges.contains(&("Sources/App/main.swift".into(), "external:Foundation".into())),
+        "{edges:?}"
+    );
+
+    let ranked = centrality(&root, Path::new("."), 10).unwrap();
+    let model = ranked
+        .iter()
+        .find(|f| f.path == "Sources/Core/Model.swift")
+        .unwrap_or_else(|| panic!("{ranked:?}"));
+    assert_eq!(model.in_degree, 1);
+}
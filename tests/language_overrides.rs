@@ -0,0 +1,47 @@
+//! `language_overrides` routes nonstandard paths to existing drivers. The driver
+//! registry is process-wide, so everything runs in one test.
+
+use cortexast::inspector::{
+    analyze_file, apply_language_overrides, find_usages, render_skeleton, GitignoreMode, PathStyle,
+};
+use std::collections::BTreeMap;
+
+#[test]
+fn overrides_apply_by_extension_and_file_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let inc = dir.path().join("helpers.INC");
+    std::fs::write(&inc, "pub fn helper() -> u32 {\n    42\n}\n").unwrap();
+    let script = dir.path().join("build.txt");
+    std::fs::write(&script, "def build():\n    return helper()\n").unwrap();
+
+    assert!(analyze_file(&inc).is_err());
+
+    let overrides: BTreeMap<String, String> = [
+        (".inc".to_string(), "rust".to_string()),
+        ("Build.txt".to_string(), "py".to_string()),
+    ]
+    .into();
+    apply_language_overrides(&overrides);
+
+    let rust = analyze_file(&inc).unwrap();
+    assert!(rust.symbols.iter().any(|s| s.name == "helper"), "{rust:?}");
+    let skeleton = render_skeleton(&inc).unwrap();
+    assert!(!skeleton.contains("42"), "{skeleton}");
+
+    let py = analyze_file(&script).unwrap();
+    assert!(py.symbols.iter().any(|s| s.name == "build"), "{py:?}");
+
+    let usages = find_usages(
+        dir.path(),
+        "helper",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    assert!(usages.contains("helpers.INC"), "{usages}");
+    assert!(usages.contains("build.txt"), "{usages}");
+
+    apply_language_overrides(&BTreeMap::new());
+    assert!(analyze_file(&inc).is_err());
+}
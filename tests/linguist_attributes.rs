@@ -0,0 +1,96 @@
+//! `.gitattributes` `linguist-generated` / `linguist-vendored` hints at scan time.
+
+use cortexast::config::{Config, IncludeTests};
+use cortexast::scanner::{scan_workspace_report, LinguistKind, ScanOptions};
+use cortexast::slicer::slice_to_xml;
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        ".gitattributes",
+        "# hints\n\
+         gen/** linguist-generated\n\
+         gen/keep.rs -linguist-generated\n\
+         third_party/** linguist-vendored=true\n\
+         *.txt text eol=lf\n",
+    );
+    write(root, "src/main.rs", "fn main() {}\n");
+    write(root, "gen/api.rs", "pub fn generated_marker() {}\n");
+    write(root, "gen/keep.rs", "pub fn kept() {}\n");
+    write(root, "third_party/lib.rs", "pub fn vendored_marker() {}\n");
+    dir
+}
+
+fn opts(root: &Path, include: bool) -> ScanOptions {
+    ScanOptions {
+        repo_root: root.to_path_buf(),
+        target: PathBuf::from("."),
+        max_file_bytes: 512 * 1024,
+        exclude_dir_names: vec![],
+        exclude_dir_paths: vec![],
+        include_linguist_generated: include,
+        test_patterns: vec![],
+        include_tests: IncludeTests::All,
+    }
+}
+
+fn rel(p: &Path) -> String {
+    p.to_string_lossy().replace('\\', "/")
+}
+
+#[test]
+fn flagged_files_are_reported_not_scanned() {
+    let dir = fixture();
+    let report = scan_workspace_report(&opts(dir.path(), false)).unwrap();
+
+    let entries: Vec<String> = report.entries.iter().map(|e| rel(&e.rel_path)).collect();
+    assert_eq!(entries, vec!["gen/keep.rs", "src/main.rs"]);
+
+    let flagged: Vec<(String, LinguistKind)> = report
+        .linguist
+        .iter()
+        .map(|(e, k)| (rel(&e.rel_path), *k))
+        .collect();
+    assert_eq!(
+        flagged,
+        vec![
+            ("gen/api.rs".to_string(), LinguistKind::Generated),
+            ("third_party/lib.rs".to_string(), LinguistKind::Vendored),
+        ]
+    );
+}
+
+#[test]
+fn include_linguist_generated_scans_everything() {
+    let dir = fixture();
+    let report = scan_workspace_report(&opts(dir.path(), true)).unwrap();
+    assert_eq!(report.entries.len(), 4);
+    assert!(report.linguist.is_empty());
+}
+
+#[test]
+fn slice_lists_flagged_files_without_content() {
+    let dir = fixture();
+    let (xml, _) = slice_to_xml(
+        dir.path(),
+        Path::new("."),
+        10_000,
+        &Config::default(),
+        false,
+    )
+    .unwrap();
+    assert!(xml.contains("api.rs (generated, skipped)"), "{xml}");
+    assert!(xml.contains("lib.rs (vendored, skipped)"), "{xml}");
+    assert!(!xml.contains("generated_marker"), "{xml}");
+    assert!(!xml.contains("vendored_marker"), "{xml}");
+    assert!(xml.contains("kept"), "{xml}");
+}
@@ -0,0 +1,69 @@
+//! `list_symbols`: every declaration of one kind, with signatures.
+
+use cortexast::inspector::{format_symbols_by_kind, list_symbols_by_kind};
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/store.rs"),
+        "pub trait Store {\n    fn get(&self, key: &str) -> Option<String>;\n}\n\n\
+         pub struct MemStore;\n\n\
+         pub fn open_store(path: &str) -> MemStore {\n    MemStore\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("src/api.rs"),
+        "pub trait Handler {\n    fn handle(&self, req: &str) -> String;\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("src/routes.ts"),
+        "export class Router {}\n\n\
+         export function route(path: string): Router {\n  return new Router();\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn lists_every_symbol_of_the_normalized_kind() {
+    let dir = fixture();
+    let traits = list_symbols_by_kind(dir.path(), "trait").unwrap();
+    let names: Vec<&str> = traits.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["Handler", "Store"], "{traits:?}");
+    let store = &traits[1];
+    assert_eq!(store.file, "src/store.rs");
+    assert_eq!(store.line, 1);
+    assert!(store.signature.contains("pub trait Store"), "{store:?}");
+    assert!(!store.signature.contains('{'), "{store:?}");
+
+    // Plurals are accepted.
+    let functions = list_symbols_by_kind(dir.path(), "functions").unwrap();
+    let names: Vec<&str> = functions.iter().map(|l| l.name.as_str()).collect();
+    assert!(names.contains(&"open_store"), "{names:?}");
+    assert!(names.contains(&"route"), "{names:?}");
+    assert!(!names.contains(&"MemStore"), "{names:?}");
+
+    // `type` spans Rust structs and TypeScript classes.
+    let types = list_symbols_by_kind(dir.path(), "type").unwrap();
+    let names: Vec<&str> = types.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["Router", "MemStore"], "{types:?}");
+}
+
+#[test]
+fn formatting_respects_the_budget() {
+    let dir = fixture();
+    let listings = list_symbols_by_kind(dir.path(), "trait").unwrap();
+    let full = format_symbols_by_kind("trait", &listings, 8_000);
+    assert!(full.contains("(2 of 2)"), "{full}");
+    assert!(full.contains("### src/store.rs"), "{full}");
+    assert!(full.contains("- L1 `trait` Store"), "{full}");
+
+    let capped = format_symbols_by_kind("trait", &listings, 1);
+    assert!(capped.contains("(0 of 2)"), "{capped}");
+    assert!(capped.contains("Budget reached: 2 more"), "{capped}");
+
+    let none = format_symbols_by_kind("module", &[], 8_000);
+    assert!(none.contains("No `module` symbols found"), "{none}");
+}
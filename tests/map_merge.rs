@@ -0,0 +1,48 @@
+//! `--map a/ b/`: one repo map per path, merged with repo-name-prefixed node ids.
+
+use std::process::Command;
+
+#[test]
+fn map_with_several_paths_merges_namespaced_maps() {
+    // Repo maps skip forbidden path components such as `tmp`, so stay out of /tmp.
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).expect("tempdir");
+    for repo in ["billing", "users"] {
+        std::fs::create_dir_all(dir.path().join(repo)).unwrap();
+        std::fs::write(dir.path().join(repo).join("main.py"), "import os\n").unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cortexast"))
+        .current_dir(dir.path())
+        .args(["--map", "billing/", "users/"])
+        .output()
+        .expect("run cortexast --map billing/ users/");
+    assert!(output.status.success(), "exit: {:?}", output.status);
+
+    let map: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<&str> = map["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["id"].as_str().unwrap())
+        .collect();
+    // Both repos import `os`: one shared external node.
+    assert_eq!(
+        ids,
+        ["billing", "billing/main.py", "external:os", "users", "users/main.py"]
+    );
+    let edges = map["edges"].as_array().unwrap();
+    assert!(
+        edges
+            .iter()
+            .any(|e| e["source"] == "users" && e["target"] == "users/main.py"),
+        "{edges:?}"
+    );
+
+    // A missing repo path is an error, not an empty map.
+    let output = Command::new(env!("CARGO_BIN_EXE_cortexast"))
+        .current_dir(dir.path())
+        .args(["--map", "billing/", "missing/"])
+        .output()
+        .expect("run cortexast --map with a missing path");
+    assert!(!output.status.success());
+}
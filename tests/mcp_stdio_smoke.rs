@@ -140,6 +140,7 @@ fn mcp_stdio_smoke() {
         assert_eq!(v.get("jsonrpc").and_then(|x| x.as_str()), Some("2.0"));
         let result = v.get("result").expect("initialize result");
         assert!(result.get("capabilities").is_some());
+        assert_eq!(result["protocolVersion"].as_str(), Some("2024-11-05"));
     }
 
     // tools/list
@@ -344,3 +345,298 @@ fn default_truncation_caps_output() {
         );
     }
 }
+
+/// `mcp.disabled_tools` in the workspace's .cortexast.json hides a tool from
+/// tools/list and makes tools/call reject it without running it.
+#[test]
+fn disabled_tools_are_hidden_and_rejected() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let workspace = tempfile::tempdir().expect("temp workspace");
+    std::fs::write(
+        workspace.path().join(".cortexast.json"),
+        r#"{ "mcp": { "disabled_tools": ["run_diagnostics"] } }"#,
+    )
+    .unwrap();
+
+    let mut child = Command::new(bin)
+        .arg("mcp")
+        .arg("--root")
+        .arg(workspace.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn cortexast mcp");
+
+    {
+        let stdin = child.stdin.as_mut().expect("child stdin");
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })
+        )
+        .unwrap();
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "run_diagnostics", "arguments": {} }
+            })
+        )
+        .unwrap();
+    }
+    drop(child.stdin.take());
+
+    let reader = BufReader::new(child.stdout.take().expect("child stdout"));
+    let mut replies_by_id: HashMap<i64, serde_json::Value> = HashMap::new();
+    for line in reader.lines() {
+        let line = line.expect("read stdout line");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: serde_json::Value = serde_json::from_str(&line).expect("stdout is json");
+        if let Some(id) = v.get("id").and_then(|x| x.as_i64()) {
+            replies_by_id.insert(id, v);
+        }
+        if replies_by_id.len() >= 2 {
+            break;
+        }
+    }
+    let _ = child.wait();
+
+    let names: Vec<&str> = replies_by_id[&1]["result"]["tools"]
+        .as_array()
+        .expect("tools array")
+        .iter()
+        .filter_map(|t| t["name"].as_str())
+        .collect();
+    assert!(
+        !names.contains(&"run_diagnostics"),
+        "disabled tool still listed"
+    );
+    assert!(
+        names.contains(&"cortex_code_explorer"),
+        "other tools must stay listed"
+    );
+
+    let result = &replies_by_id[&2]["result"];
+    assert_eq!(result["isError"].as_bool(), Some(true));
+    let text = result["content"][0]["text"].as_str().unwrap_or("");
+    assert!(
+        text.contains("disabled"),
+        "unexpected rejection text: {text}"
+    );
+}
+
+#[test]
+fn analyze_source_parses_in_memory_code() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let workspace = tempfile::tempdir().expect("temp workspace");
+
+    let mut child = Command::new(bin)
+        .arg("mcp")
+        .arg("--root")
+        .arg(workspace.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn cortexast mcp");
+
+    {
+        let stdin = child.stdin.as_mut().expect("child stdin");
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": {
+                    "name": "cortex_symbol_analyzer",
+                    "arguments": {
+                        "action": "analyze_source",
+                        "ext": ".rs",
+                        "content": "use std::fmt;\n\npub struct Point { x: i32 }\n\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n"
+                    }
+                }
+            })
+        )
+        .unwrap();
+    }
+    drop(child.stdin.take());
+
+    let reader = BufReader::new(child.stdout.take().expect("child stdout"));
+    let mut reply: Option<serde_json::Value> = None;
+    for line in reader.lines() {
+        let line = line.expect("read stdout line");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: serde_json::Value = serde_json::from_str(&line).expect("stdout is json");
+        if v.get("id").and_then(|x| x.as_i64()) == Some(1) {
+            reply = Some(v);
+            break;
+        }
+    }
+    let _ = child.wait();
+
+    let result = &reply.expect("analyze_source reply")["result"];
+    assert_ne!(result["isError"].as_bool(), Some(true), "{result}");
+    let text = result["content"][0]["text"].as_str().unwrap_or("");
+    let parsed: serde_json::Value = serde_json::from_str(text).expect("FileSymbols JSON");
+    let add = parsed["symbols"]
+        .as_array()
+        .expect("symbols array")
+        .iter()
+        .find(|s| s["name"] == "add")
+        .expect("fn add extracted");
+    assert_eq!(add["line"], 4);
+    assert_eq!(add["line_end"], 6);
+    assert_eq!(parsed["imports"][0], "std::fmt");
+}
+
+/// `CORTEXAST_MCP_LOG` appends every inbound line and outbound reply to a file,
+/// timestamped and size-capped, while stdout stays pure JSON-RPC.
+#[test]
+fn mcp_log_traces_requests_and_replies() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let workspace = tempfile::tempdir().expect("temp workspace");
+    let log_path = workspace.path().join("mcp.log");
+
+    let mut child = Command::new(bin)
+        .arg("mcp")
+        .arg("--root")
+        .arg(workspace.path())
+        .env("CORTEXAST_MCP_LOG", &log_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn cortexast mcp");
+
+    {
+        let stdin = child.stdin.as_mut().expect("child stdin");
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+        )
+        .unwrap();
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })
+        )
+        .unwrap();
+        writeln!(
+            stdin,
+            "{}",
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "ping",
+                "params": { "pad": "x".repeat(40_000) }
+            })
+        )
+        .unwrap();
+    }
+    drop(child.stdin.take());
+
+    let reader = BufReader::new(child.stdout.take().expect("child stdout"));
+    let mut replies = 0;
+    for line in reader.lines() {
+        let line = line.expect("read stdout line");
+        if line.trim().is_empty() {
+            continue;
+        }
+        serde_json::from_str::<serde_json::Value>(&line).expect("stdout is json");
+        replies += 1;
+    }
+    assert!(child.wait().expect("wait child").success());
+    assert_eq!(replies, 2);
+
+    let log = std::fs::read_to_string(&log_path).expect("trace log written");
+    let entries: Vec<&str> = log.lines().collect();
+    assert_eq!(entries.len(), 5, "{log}");
+    let directions: Vec<&str> = entries
+        .iter()
+        .map(|l| l.split(' ').nth(1).unwrap_or(""))
+        .collect();
+    assert_eq!(directions, ["<-", "<-", "->", "<-", "->"], "{log}");
+
+    // `2026-01-31T12:34:56.789Z <- {...}`
+    let stamp = entries[0].split(' ').next().unwrap();
+    assert_eq!(stamp.len(), 24, "{stamp}");
+    assert!(
+        stamp.ends_with('Z') && stamp.as_bytes()[10] == b'T',
+        "{stamp}"
+    );
+    assert!(entries[0].contains("notifications/initialized"), "{log}");
+    assert!(entries[4].contains(r#""id":2"#), "{log}");
+
+    // The padded ping is far larger than the per-entry cap.
+    assert!(entries[3].contains("more bytes]"), "{}", entries[3].len());
+    assert!(entries[3].len() < 17 * 1024, "{}", entries[3].len());
+}
+
+/// `initialize` echoes a protocol revision the server supports and answers
+/// `2024-11-05` to one it doesn't know, instead of echoing it blindly.
+#[test]
+fn initialize_negotiates_protocol_version() {
+    let bin = env!("CARGO_BIN_EXE_cortexast");
+    let mut child = Command::new(bin)
+        .arg("mcp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn cortexast mcp");
+
+    {
+        let stdin = child.stdin.as_mut().expect("child stdin");
+        for (id, version) in [(1, "2025-06-18"), (2, "2099-01-01")] {
+            writeln!(
+                stdin,
+                "{}",
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "initialize",
+                    "params": { "protocolVersion": version }
+                })
+            )
+            .unwrap();
+        }
+    }
+    drop(child.stdin.take());
+
+    let reader = BufReader::new(child.stdout.take().expect("child stdout"));
+    let mut replies_by_id: HashMap<i64, serde_json::Value> = HashMap::new();
+    for line in reader.lines() {
+        let line = line.expect("read stdout line");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let v: serde_json::Value = serde_json::from_str(&line).expect("stdout is json");
+        if let Some(id) = v.get("id").and_then(|x| x.as_i64()) {
+            replies_by_id.insert(id, v);
+        }
+        if replies_by_id.len() >= 2 {
+            break;
+        }
+    }
+    let _ = child.wait();
+
+    assert_eq!(
+        replies_by_id[&1]["result"]["protocolVersion"].as_str(),
+        Some("2025-06-18")
+    );
+    assert_eq!(
+        replies_by_id[&2]["result"]["protocolVersion"].as_str(),
+        Some("2024-11-05")
+    );
+}
@@ -0,0 +1,327 @@
+//! Tool behaviour checked end to end over the stdio MCP server.
+
+use cortexast::config::Config;
+use cortexast::vector_store::{model_db_dir, SYMBOL_INDEX_FILE};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Call one tool on a server rooted at `root`; returns `(text, is_error)`.
+fn call_tool(root: &Path, name: &str, arguments: serde_json::Value) -> (String, bool) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cortexast"))
+        .arg("mcp")
+        .arg("--root")
+        .arg(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn cortexast mcp");
+    writeln!(
+        child.stdin.as_mut().expect("child stdin"),
+        "{}",
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": arguments }
+        })
+    )
+    .unwrap();
+    drop(child.stdin.take());
+
+    let reader = BufReader::new(child.stdout.take().expect("child stdout"));
+    let reply: serde_json::Value = reader
+        .lines()
+        .map(|l| l.expect("read stdout line"))
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str::<serde_json::Value>(&l).expect("stdout is json"))
+        .find(|v| v.get("id").and_then(|x| x.as_i64()) == Some(1))
+        .expect("tool reply");
+    let _ = child.wait();
+    let result = &reply["result"];
+    (
+        result["content"][0]["text"].as_str().unwrap_or("").to_string(),
+        result["isError"].as_bool() == Some(true),
+    )
+}
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Write a `symbols.json` recording `Widget` in `a.rs` and covering `files`.
+fn write_symbol_index(root: &Path, files: &[&str]) {
+    let db = model_db_dir(
+        &root.join(".cortexast").join("db"),
+        &Config::default().vector_search.model,
+    );
+    std::fs::create_dir_all(&db).unwrap();
+    let hashes: serde_json::Map<String, serde_json::Value> = files
+        .iter()
+        .map(|f| {
+            let bytes = std::fs::read(root.join(f)).unwrap();
+            let hash = format!("{:016x}", xxhash_rust::xxh3::xxh3_64(&bytes));
+            (f.to_string(), hash.into())
+        })
+        .collect();
+    let index = serde_json::json!({
+        "cold_files": 0,
+        "files": hashes,
+        "defs": { "Widget": [{ "file": "a.rs", "line": 1, "kind": "struct" }] }
+    });
+    std::fs::write(db.join(SYMBOL_INDEX_FILE), index.to_string()).unwrap();
+}
+
+#[test]
+fn find_definition_uses_the_index_only_when_it_covers_the_scope() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "a.rs", "pub struct Widget;\n");
+    write(root, "b.rs", "pub fn Widget() {}\n");
+    let args = serde_json::json!({ "action": "find_definition", "symbol_name": "Widget" });
+
+    // Query-mode indexing embedded only a.rs: the definition in b.rs must not be lost.
+    write_symbol_index(root, &["a.rs"]);
+    let (text, is_error) = call_tool(root, "cortex_symbol_analyzer", args.clone());
+    assert!(!is_error, "{text}");
+    assert!(text.contains("via AST walk"), "{text}");
+    assert!(text.contains("a.rs:L1") && text.contains("b.rs:L1"), "{text}");
+
+    write_symbol_index(root, &["a.rs", "b.rs"]);
+    let (text, _) = call_tool(root, "cortex_symbol_analyzer", args);
+    assert!(text.contains("via symbol index"), "{text}");
+}
+
+#[test]
+fn deep_slice_rejects_embed_models_outside_the_allowlist() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "a.rs", "pub struct Widget;\n");
+
+    for model in ["someone/untrusted-model", "/etc"] {
+        let args = serde_json::json!({
+            "action": "deep_slice",
+            "target": ".",
+            "query": "widget",
+            "embed_model": model
+        });
+        let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+        assert!(is_error, "{text}");
+        assert!(text.contains("is not allowed"), "{text}");
+    }
+}
+
+#[test]
+fn deep_slice_paths_keep_the_requested_order() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "src/alpha.rs", "pub fn alpha_marker() {}\n");
+    write(root, "src/beta.rs", "pub fn beta_marker() {}\n");
+    write(root, "src/gamma.rs", "pub fn gamma_marker() {}\n");
+
+    let args = serde_json::json!({
+        "action": "deep_slice",
+        "paths": [" src/gamma.rs ", "src\\alpha.rs", "", "src/beta.rs"]
+    });
+    let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+    assert!(!is_error, "{text}");
+    let gamma = text.find("gamma_marker").expect(&text);
+    let alpha = text.find("alpha_marker").expect(&text);
+    let beta = text.find("beta_marker").expect(&text);
+    assert!(gamma < alpha && alpha < beta, "{text}");
+}
+
+#[test]
+fn deep_slice_paths_reject_missing_and_escaping_entries() {
+    let outer = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = outer.path().join("repo");
+    write(&root, "src/lib.rs", "pub fn inside() {}\n");
+    write(outer.path(), "secret.rs", "pub fn outside() {}\n");
+
+    for bad in ["../secret.rs", "src/../../secret.rs", "src/missing.rs", "src"] {
+        let args = serde_json::json!({ "action": "deep_slice", "paths": ["src/lib.rs", bad] });
+        let (text, is_error) = call_tool(&root, "cortex_code_explorer", args);
+        assert!(is_error, "{bad}: {text}");
+        assert!(text.contains("not files inside repo root"), "{text}");
+        assert!(text.contains(bad), "{text}");
+        assert!(!text.contains("outside()"), "{text}");
+    }
+}
+
+#[test]
+fn deep_slice_paths_reject_conflicting_args() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "src/lib.rs", "pub fn widget() {}\n");
+
+    for (key, value) in [
+        ("query", "widget"),
+        ("focus_symbol", "widget"),
+        ("focus_lines", "1-1"),
+        ("output", "files"),
+    ] {
+        let mut args = serde_json::json!({ "action": "deep_slice", "paths": ["src/lib.rs"] });
+        args[key] = value.into();
+        let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+        assert!(is_error, "{key}: {text}");
+        assert!(text.contains("'paths' slices exactly the listed files"), "{text}");
+    }
+}
+
+/// Tiny word-level model2vec model (one 2-d row per word) so queries embed offline.
+fn write_model(dir: &Path) {
+    let words = ["[UNK]", "parse", "config", "render", "page", "fn"];
+    let vocab: serde_json::Map<String, serde_json::Value> = words
+        .iter()
+        .enumerate()
+        .map(|(i, w)| (w.to_string(), i.into()))
+        .collect();
+    let tokenizer = serde_json::json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": { "type": "Lowercase" },
+        "pre_tokenizer": { "type": "Whitespace" },
+        "post_processor": null,
+        "decoder": null,
+        "model": { "type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]" }
+    });
+    std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+    std::fs::write(dir.join("config.json"), r#"{"normalize":true}"#).unwrap();
+
+    let data: Vec<u8> = (0..words.len())
+        .flat_map(|i| [i as f32, 1.0])
+        .flat_map(f32::to_le_bytes)
+        .collect();
+    let header = serde_json::json!({
+        "embeddings": { "dtype": "F32", "shape": [words.len(), 2], "data_offsets": [0, data.len()] }
+    })
+    .to_string();
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(&data);
+    std::fs::write(dir.join("model.safetensors"), bytes).unwrap();
+}
+
+#[test]
+fn deep_slice_query_reports_what_its_refresh_embedded() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let model = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write_model(model.path());
+    let config = serde_json::json!({ "vector_search": { "model": model.path() } });
+    write(root, ".cortexast.json", &config.to_string());
+    write(root, "src/config.rs", "fn parse config\n");
+    write(root, "src/page.rs", "fn render page\n");
+    // Nothing to embed: always a candidate, never counted.
+    write(root, "src/blank.rs", "  \n");
+
+    let query = || {
+        let args = serde_json::json!({ "action": "deep_slice", "target": ".", "query": "parse config" });
+        let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+        assert!(!is_error, "{text}");
+        text
+    };
+    let text = query();
+    assert!(text.starts_with("<!-- index_refresh: +2 ~0 "), "{text}");
+    let text = query();
+    assert!(text.starts_with("<!-- index_refresh: warm (no changes) -->"), "{text}");
+
+    write(root, "src/config.rs", "fn parse config page\n");
+    let text = query();
+    assert!(text.starts_with("<!-- index_refresh: +0 ~1 "), "{text}");
+}
+
+#[test]
+fn signatures_default_cap_fits_the_budget() {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    let body: String = (0..400)
+        .map(|i| format!("pub fn handler_number_{i}(request: &str) -> usize {{ {i} }}\n"))
+        .collect();
+    write(root, "src/lib.rs", &body);
+
+    let args = serde_json::json!({ "action": "signatures" });
+    let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+    assert!(!is_error, "{text}");
+    assert!(text.len() > 8_000, "{}", text.len());
+    assert!(!text.contains("TRUNCATED"), "{text}");
+    assert!(text.contains("handler_number_399"), "{text}");
+
+    let args = serde_json::json!({ "action": "signatures", "max_chars": 2_000 });
+    let (text, _) = call_tool(root, "cortex_code_explorer", args);
+    assert!(text.contains("TRUNCATED: 2000/"), "{text}");
+}
+
+/// Repo whose slice alone is well over the 8000-char default inline cap.
+fn context_pack_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    for m in 0..6 {
+        let body: String = (0..60)
+            .map(|i| format!("pub fn module_{m}_step_{i}(input: u64) -> u64 {{ input + {i} }}\n"))
+            .collect();
+        write(dir.path(), &format!("src/m{m}.rs"), &body);
+    }
+    dir
+}
+
+#[test]
+fn context_pack_orders_sections_and_fits_its_budget() {
+    let dir = context_pack_repo();
+    let root = dir.path();
+
+    let args = serde_json::json!({ "action": "context_pack", "diagnostics": false });
+    let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+    assert!(!is_error, "{text}");
+    assert!(!text.contains("TRUNCATED"), "{text}");
+    assert!(text.len() > 8_000, "{}", text.len());
+    let map = text.find("## 1. Repository map").expect(&text);
+    let code = text.find("## 2. Code").expect(&text);
+    assert!(map < code, "{text}");
+    assert!(text.contains("module_5_step_59"), "{text}");
+    assert!(!text.contains("## 3. Diagnostics"), "{text}");
+
+    let args = serde_json::json!({ "action": "context_pack", "budget_tokens": 1_000 });
+    let (text, is_error) = call_tool(root, "cortex_code_explorer", args);
+    assert!(!is_error, "{text}");
+    assert!(text.len() <= 1_000 * 4 + 1_024, "{}", text.len());
+    let code = text.find("## 2. Code").expect(&text);
+    let diagnostics = text.find("## 3. Diagnostics").expect(&text);
+    assert!(code < diagnostics, "{text}");
+    assert!(text.contains("No Cargo.toml or package.json"), "{text}");
+}
+
+#[test]
+fn context_pack_rejects_a_missing_target() {
+    let dir = context_pack_repo();
+    let args = serde_json::json!({ "action": "context_pack", "target": "nope" });
+    let (text, is_error) = call_tool(dir.path(), "cortex_code_explorer", args);
+    assert!(is_error, "{text}");
+    assert!(text.contains("does not exist"), "{text}");
+}
+
+#[test]
+fn max_tokens_uses_the_target_repos_chars_per_token() {
+    let server_root = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let repo = context_pack_repo();
+    write(
+        repo.path(),
+        ".cortexast.json",
+        r#"{ "token_estimator": { "chars_per_token": 1 } }"#,
+    );
+
+    let args = serde_json::json!({
+        "action": "signatures",
+        "max_tokens": 100,
+        "repoPath": repo.path()
+    });
+    let (text, is_error) = call_tool(server_root.path(), "cortex_code_explorer", args);
+    assert!(!is_error, "{text}");
+    let (kept, marker) = text.split_once("\n\n... ✂️").expect(&text);
+    assert_eq!(kept.len(), 100, "{text}");
+    assert!(marker.contains("~100/"), "{marker}");
+}
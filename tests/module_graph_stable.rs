@@ -0,0 +1,57 @@
+//! `build_module_graph` serializes the same tree to the same bytes every time.
+
+use cortexast::mapper::build_module_graph;
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn graph_json_is_byte_identical_across_runs() {
+    // Module graphs skip forbidden path components such as `tmp`, so stay out of /tmp.
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    for name in ["zeta", "alpha", "mid", "beta"] {
+        write(&root, &format!("packages/{name}/package.json"), "{}\n");
+    }
+    write(
+        &root,
+        "packages/zeta/index.ts",
+        "import { a } from '../alpha/index';\nimport { b } from '../beta/index';\n",
+    );
+    write(
+        &root,
+        "packages/mid/index.ts",
+        "import { z } from '../zeta/index';\nimport { a } from '../alpha/index';\n",
+    );
+    write(&root, "packages/alpha/index.ts", "export const a = 1;\n");
+    write(
+        &root,
+        "packages/beta/index.ts",
+        "import { a } from '../alpha/index';\nexport const b = 2;\n",
+    );
+
+    let first = serde_json::to_string(&build_module_graph(&root, Path::new(".")).unwrap()).unwrap();
+    let second =
+        serde_json::to_string(&build_module_graph(&root, Path::new(".")).unwrap()).unwrap();
+    assert_eq!(first, second);
+
+    let graph = build_module_graph(&root, Path::new(".")).unwrap();
+    let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut sorted_ids = ids.clone();
+    sorted_ids.sort();
+    assert_eq!(ids, sorted_ids);
+
+    let edges: Vec<(&str, &str)> = graph
+        .edges
+        .iter()
+        .map(|e| (e.source.as_str(), e.target.as_str()))
+        .collect();
+    assert!(edges.len() >= 4, "{edges:?}");
+    let mut sorted_edges = edges.clone();
+    sorted_edges.sort();
+    assert_eq!(edges, sorted_edges);
+}
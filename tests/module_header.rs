@@ -0,0 +1,91 @@
+//! `slicer.preserve_module_header`: a file's opening module doc survives skeleton mode.
+
+use cortexast::config::Config;
+use cortexast::inspector::module_header;
+use cortexast::slicer::slice_paths_to_xml;
+use std::path::Path;
+
+const RUST: &str = "\
+// Copyright (c) Example Corp.
+#![allow(dead_code)]
+//! Ledger reconciliation against the bank feed.
+//!
+//! Long design notes that only cost tokens.
+
+pub fn reconcile() -> u32 {
+    // Narration that skeletons strip.
+    42
+}
+";
+
+const PYTHON: &str = "\
+#!/usr/bin/env python3
+\"\"\"Nightly export of settled invoices.
+
+Operational details nobody needs in a skeleton.
+\"\"\"
+
+def export(batch):
+    return [b for b in batch]
+";
+
+const GO: &str = "\
+//go:build linux
+
+// Package feed polls the bank feed and emits settlements.
+//
+// More detail.
+package feed
+
+func Poll() int {
+\treturn 1
+}
+";
+
+#[test]
+fn first_paragraph_of_each_header_style() {
+    assert_eq!(
+        module_header(Path::new("lib.rs"), RUST).as_deref(),
+        Some("//! Ledger reconciliation against the bank feed.\n")
+    );
+    assert_eq!(
+        module_header(Path::new("job.py"), PYTHON).as_deref(),
+        Some("\"\"\"Nightly export of settled invoices.\n\"\"\"\n")
+    );
+    assert_eq!(
+        module_header(Path::new("feed.go"), GO).as_deref(),
+        Some("// Package feed polls the bank feed and emits settlements.\n")
+    );
+    assert_eq!(module_header(Path::new("lib.rs"), "pub fn f() {}\n"), None);
+    assert_eq!(
+        module_header(Path::new("a.py"), "x = \"\"\"s\"\"\"\n"),
+        None
+    );
+}
+
+#[test]
+fn skeleton_slices_keep_the_header_unless_disabled() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lib.rs"), RUST).unwrap();
+    std::fs::write(dir.path().join("job.py"), PYTHON).unwrap();
+    let paths = vec!["lib.rs".to_string(), "job.py".to_string()];
+    let mut cfg = Config::default();
+
+    let (xml, _) = slice_paths_to_xml(dir.path(), &paths, 8_000, &cfg, true).unwrap();
+    assert!(
+        xml.contains("//! Ledger reconciliation against the bank feed."),
+        "{xml}"
+    );
+    assert!(!xml.contains("Long design notes"), "{xml}");
+    assert!(!xml.contains("Narration"), "{xml}");
+    assert!(
+        xml.contains("#!/usr/bin/env python3\n\"\"\"Nightly export of settled invoices."),
+        "{xml}"
+    );
+    assert!(!xml.contains("Operational details"), "{xml}");
+
+    cfg.slicer.preserve_module_header = false;
+    let (xml, _) = slice_paths_to_xml(dir.path(), &paths, 8_000, &cfg, true).unwrap();
+    assert!(!xml.contains("Ledger reconciliation"), "{xml}");
+    assert!(!xml.contains("Nightly export"), "{xml}");
+}
@@ -0,0 +1,60 @@
+//! Line and byte-offset reporting on a source file full of CJK identifiers,
+//! comments and emoji (tests/fixtures/multibyte/lib.rs).
+
+use cortexast::inspector::{analyze_file, find_usages, read_symbol, GitignoreMode, PathStyle};
+use std::path::PathBuf;
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/multibyte")
+}
+
+#[test]
+fn symbol_rows_and_byte_ranges_survive_multibyte_text() {
+    let path = fixture_dir().join("lib.rs");
+    let source = std::fs::read_to_string(&path).unwrap();
+    let out = analyze_file(&path).unwrap();
+
+    let sum = out
+        .symbols
+        .iter()
+        .find(|s| s.name == "计算总和")
+        .expect("CJK function extracted");
+    assert_eq!((sum.line, sum.line_end), (4, 8));
+    // Byte offsets must land on char boundaries and cover exactly the declaration.
+    let decl = &source[sum.start_byte..sum.end_byte];
+    assert!(decl.starts_with("pub fn 计算总和"), "{decl}");
+    assert!(decl.ends_with('}'), "{decl}");
+
+    let cfg = out
+        .symbols
+        .iter()
+        .find(|s| s.name == "配置")
+        .expect("CJK struct extracted");
+    assert_eq!((cfg.line, cfg.line_end), (10, 12));
+}
+
+#[test]
+fn read_symbol_header_reports_source_lines() {
+    let text = read_symbol(&fixture_dir().join("lib.rs"), "配置").unwrap();
+    assert!(text.contains(":L11-L13"), "{text}");
+    assert!(text.contains("pub 名称: String, // 名称 🏷️"), "{text}");
+}
+
+#[test]
+fn usages_land_on_the_right_lines() {
+    let text = find_usages(
+        &fixture_dir(),
+        "计算总和",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::Absolute,
+    )
+    .unwrap();
+    assert!(text.contains("lib.rs:17]"), "{text}");
+    assert!(
+        text.contains(">>>   17 |     计算总和(&[1, 2, 3]) // 调用 🚀"),
+        "{text}"
+    );
+    // The doc comment on line 4 mentions the name but is not a usage.
+    assert!(!text.contains("lib.rs:4]"), "{text}");
+}
@@ -0,0 +1,35 @@
+//! Latin-1 and other non-UTF-8 sources decode lossily instead of failing.
+
+use cortexast::inspector::{analyze_file, read_symbol_with_options, render_skeleton};
+
+const LATIN1: &[u8] = b"// Caf\xe9 menu (legacy Latin-1 file)\n\
+pub fn greeting() -> &'static str {\n    \"bon app\xe9tit\"\n}\n\n\
+pub struct Menu {\n    pub items: u32,\n}\n";
+
+#[test]
+fn latin1_file_is_analyzed() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("menu.rs");
+    std::fs::write(&path, LATIN1).unwrap();
+
+    let symbols = analyze_file(&path).unwrap();
+    let names: Vec<&str> = symbols.symbols.iter().map(|s| s.name.as_str()).collect();
+    assert!(names.contains(&"greeting"), "{names:?}");
+    assert!(names.contains(&"Menu"), "{names:?}");
+
+    let body = read_symbol_with_options(&path, "greeting", false, None).unwrap();
+    assert!(body.contains("bon app\u{FFFD}tit"), "{body}");
+
+    let skeleton = render_skeleton(&path).unwrap();
+    assert!(skeleton.contains("pub struct Menu"), "{skeleton}");
+}
+
+#[test]
+fn binary_file_is_rejected_plainly() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blob.rs");
+    std::fs::write(&path, b"pub fn x() {}\0\0\x01").unwrap();
+
+    let err = analyze_file(&path).unwrap_err().to_string();
+    assert!(err.contains("Binary file"), "{err}");
+}
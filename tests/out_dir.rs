@@ -0,0 +1,45 @@
+//! A custom `output_dir` (`--out-dir`, `CORTEXAST_OUT_DIR`) is kept out of slices
+//! by its path, not by its last component's name.
+
+use cortexast::config::Config;
+use cortexast::slicer::slice_to_xml;
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+fn cfg(output_dir: PathBuf) -> Config {
+    Config {
+        skeleton_mode: false,
+        output_dir,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn absolute_output_dir_inside_the_repo_is_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/lib.rs", "pub fn kept() {}\n");
+    write(root, "ci/job-1/notes.rs", "pub fn previous_run() {}\n");
+
+    let (xml, _) =
+        slice_to_xml(root, Path::new("."), 100_000, &cfg(root.join("ci/job-1")), false).unwrap();
+    assert!(xml.contains("kept"), "{xml}");
+    assert!(!xml.contains("previous_run"), "{xml}");
+}
+
+#[test]
+fn same_named_dirs_elsewhere_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "ctx/notes.rs", "pub fn previous_run() {}\n");
+    write(root, "src/ctx/lib.rs", "pub fn nested_out() {}\n");
+
+    let (xml, _) = slice_to_xml(root, Path::new("."), 100_000, &cfg("ctx".into()), false).unwrap();
+    assert!(xml.contains("nested_out"), "{xml}");
+    assert!(!xml.contains("previous_run"), "{xml}");
+}
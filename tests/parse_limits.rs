@@ -0,0 +1,46 @@
+//! `parse.max_parse_bytes`: oversized files get a stub instead of a parse.
+
+use cortexast::config::ParseConfig;
+use cortexast::inspector::{
+    apply_parse_limits, find_usages, render_skeleton, GitignoreMode, PathStyle,
+};
+
+// One test, because the limits are process-wide.
+#[test]
+fn files_over_the_cap_are_not_parsed() {
+    let dir = tempfile::tempdir().unwrap();
+    let small = dir.path().join("small.rs");
+    let big = dir.path().join("big.rs");
+    std::fs::write(&small, "fn audit() {}\nfn run() { audit(); }\n").unwrap();
+    let mut generated = String::from("fn audit_all() { audit(); }\n");
+    for i in 0..200 {
+        generated.push_str(&format!("pub const C{i}: u32 = {i};\n"));
+    }
+    std::fs::write(&big, &generated).unwrap();
+
+    apply_parse_limits(&ParseConfig {
+        max_parse_bytes: 1_000,
+        ..ParseConfig::default()
+    });
+    let stub = render_skeleton(&big).unwrap();
+    let usages = find_usages(
+        dir.path(),
+        "audit",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    let skeleton = render_skeleton(&small).unwrap();
+    apply_parse_limits(&ParseConfig::default());
+
+    assert!(stub.contains("TOO_LARGE_TO_PARSE"), "{stub}");
+    assert!(stub.contains("parse.max_parse_bytes (1000)"), "{stub}");
+    assert!(!skeleton.contains("TOO_LARGE_TO_PARSE"), "{skeleton}");
+    assert!(usages.contains("small.rs"), "{usages}");
+    assert!(!usages.contains("big.rs"), "{usages}");
+
+    assert!(!render_skeleton(&big)
+        .unwrap()
+        .contains("TOO_LARGE_TO_PARSE"));
+}
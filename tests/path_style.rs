@@ -0,0 +1,73 @@
+//! Analyzer outputs print repo-relative paths unless `PathStyle::Absolute` is asked for.
+
+use cortexast::inspector::{call_hierarchy, find_usages, usage_counts, GitignoreMode, PathStyle};
+use std::path::Path;
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(
+        src.join("lib.rs"),
+        "pub fn tally() -> u32 {\n    1\n}\n\npub fn run() -> u32 {\n    tally()\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn abs(root: &Path) -> String {
+    root.join("src")
+        .join("lib.rs")
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[test]
+fn relative_by_root_absolute_on_request() {
+    let dir = fixture();
+    let root = dir.path();
+    let abs_file = abs(root);
+
+    let rel = find_usages(
+        root,
+        "tally",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(root),
+    )
+    .unwrap();
+    assert!(rel.contains("[src/lib.rs:6]"), "{rel}");
+    assert!(!rel.contains(&abs_file), "{rel}");
+
+    let full = find_usages(
+        root,
+        "tally",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::Absolute,
+    )
+    .unwrap();
+    assert!(full.contains(&format!("[{abs_file}:6]")), "{full}");
+
+    let tree = call_hierarchy(root, "tally", PathStyle::RelativeTo(root)).unwrap();
+    assert!(tree.contains("src/lib.rs:L1"), "{tree}");
+    assert!(!tree.contains(&abs_file), "{tree}");
+    assert!(call_hierarchy(root, "tally", PathStyle::Absolute)
+        .unwrap()
+        .contains(&format!("{abs_file}:L1")));
+}
+
+#[test]
+fn scope_below_the_root_keeps_the_root_prefix() {
+    let dir = fixture();
+    let root = dir.path();
+    let counts = usage_counts(
+        &root.join("src"),
+        "tally",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(root),
+    )
+    .unwrap();
+    assert_eq!(counts[0].0, "src/lib.rs", "{counts:?}");
+}
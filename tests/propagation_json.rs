@@ -0,0 +1,78 @@
+//! `propagation_checklist` with `ChecklistFormat::Json` for CI gates.
+
+use cortexast::inspector::{propagation_checklist, ChecklistFormat, PathStyle};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "api/src/order.rs",
+        "pub struct OrderStatus;\n\npub fn load() -> OrderStatus {\n    OrderStatus\n}\n",
+    );
+    write(
+        root,
+        "web/src/order.ts",
+        "export interface OrderStatus {}\nconst s: OrderStatus = {};\n",
+    );
+    write(root, "docs/notes.md", "OrderStatus is not code here.\n");
+    dir
+}
+
+#[test]
+fn json_groups_files_by_domain() {
+    let dir = fixture();
+    let out = propagation_checklist(
+        dir.path(),
+        "OrderStatus",
+        &[],
+        false,
+        ChecklistFormat::Json,
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    assert_eq!(v["symbol"], "OrderStatus");
+    assert_eq!(v["truncated"], false);
+
+    let rust = v["groups"]["rust"].as_array().unwrap();
+    assert_eq!(rust.len(), 1);
+    assert_eq!(rust[0]["path"], "api/src/order.rs");
+    assert_eq!(rust[0]["lines"], serde_json::json!([1, 3, 4]));
+    assert!(rust[0]["count"].as_u64().unwrap() >= 3);
+
+    let ts = v["groups"]["ts"].as_array().unwrap();
+    assert_eq!(ts[0]["path"], "web/src/order.ts");
+
+    // Empty domains are left out rather than listed as [].
+    assert!(v["groups"].get("python").is_none(), "{out}");
+}
+
+#[test]
+fn markdown_stays_the_default_rendering() {
+    let dir = fixture();
+    let out = propagation_checklist(
+        dir.path(),
+        "OrderStatus",
+        &[],
+        false,
+        ChecklistFormat::Markdown,
+        PathStyle::RelativeTo(dir.path()),
+    )
+    .unwrap();
+    assert!(
+        out.starts_with("## 📋 Propagation Checklist for `OrderStatus`"),
+        "{out}"
+    );
+    assert!(out.contains("### 🦀 Rust (Backend/Services)"), "{out}");
+    assert!(out.contains("- [ ] `api/src/order.rs`"), "{out}");
+    assert!(out.find("🦀").unwrap() < out.find("🧩").unwrap(), "{out}");
+}
@@ -0,0 +1,59 @@
+use cortexast::inspector::analyze_file;
+use cortexast::mapper::build_module_graph;
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// app/
+///   __init__.py        HELPER = 1
+///   models.py
+///   api/__init__.py
+///   api/views.py       from .. import models / from ..models import User / from . import HELPER
+fn python_repo() -> tempfile::TempDir {
+    // Module graphs skip forbidden path components such as `tmp`, so stay out of /tmp.
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "app/__init__.py", "HELPER = 1\n");
+    write(root, "app/models.py", "class User:\n    pass\n");
+    write(root, "app/api/__init__.py", "");
+    write(
+        root,
+        "app/api/views.py",
+        "import os\nfrom .. import models\nfrom ..models import User\nfrom . import HELPER as H\n",
+    );
+    dir
+}
+
+#[test]
+fn bare_relative_from_imports_name_each_imported_module() {
+    let dir = python_repo();
+    let fs = analyze_file(&dir.path().join("app/api/views.py")).unwrap();
+    // Sorted and deduplicated: `from .. import models` and `from ..models import User`
+    // both name `..models`.
+    assert_eq!(fs.imports, vec!["..models", ".HELPER", "os"]);
+}
+
+#[test]
+fn module_graph_has_python_edges() {
+    let dir = python_repo();
+    let root = dir.path().canonicalize().unwrap();
+    let graph = build_module_graph(&root, Path::new(".")).unwrap();
+
+    let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    assert!(ids.contains(&"app"), "nodes: {ids:?}");
+    assert!(ids.contains(&"app/api"), "nodes: {ids:?}");
+
+    let edge = graph
+        .edges
+        .iter()
+        .find(|e| e.source == "app/api" && e.target == "app")
+        .unwrap_or_else(|| panic!("no app/api -> app edge: {:?}", graph.edges));
+    // `.HELPER` falls back to app/api/__init__.py (same module, no edge) and `os`
+    // is external, so only `..models` -> app/models.py counts.
+    assert_eq!(edge.weight, 1);
+    assert_eq!(graph.edges.len(), 1, "edges: {:?}", graph.edges);
+}
@@ -0,0 +1,53 @@
+//! `vector_search.max_candidates` and the term weights that rank the query candidate pool.
+
+use cortexast::config::{load_config, VectorSearchConfig};
+
+#[test]
+fn auto_cap_scales_with_the_query_limit() {
+    let cfg = VectorSearchConfig::default();
+    assert_eq!(cfg.max_candidates, 0);
+    assert_eq!(cfg.candidate_cap(1), 80);
+    assert_eq!(cfg.candidate_cap(10), 120);
+    assert_eq!(cfg.candidate_cap(60), 400);
+}
+
+#[test]
+fn configured_cap_and_weights_are_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join(".cortexast.json"),
+        r#"{ "vector_search": { "max_candidates": 2000, "filename_term_weight": 50, "path_term_weight": 0 } }"#,
+    )
+    .unwrap();
+
+    let cfg = load_config(dir.path()).vector_search;
+    assert_eq!(cfg.candidate_cap(10), 2000);
+    assert_eq!(cfg.filename_term_weight, 50);
+    assert_eq!(cfg.path_term_weight, 0);
+    // Unset fields keep their defaults.
+    assert_eq!(cfg.chunk_lines, 40);
+}
+
+#[test]
+fn query_expansions_add_synonym_terms() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join(".cortexast.json"),
+        r#"{ "vector_search": { "query_expansions": { "Auth": ["authentication", "log in", "session"] } } }"#,
+    )
+    .unwrap();
+
+    let cfg = load_config(dir.path()).vector_search;
+    assert!(!cfg.expand_embedding_query);
+    let terms =
+        |q: &[&str]| cfg.expand_query_terms(&q.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+    assert_eq!(
+        terms(&["auth", "session"]),
+        ["auth", "session", "authentication", "log", "in"]
+    );
+    assert_eq!(terms(&["authorize"]), ["authorize"]);
+    assert_eq!(
+        VectorSearchConfig::default().expand_query_terms(&["auth".to_string()]),
+        ["auth"]
+    );
+}
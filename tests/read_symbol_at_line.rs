@@ -0,0 +1,64 @@
+use cortexast::inspector::read_symbol_at_line;
+
+const SOURCE: &str = "\
+use std::fmt;
+
+pub struct Counter {
+    n: u32,
+}
+
+impl Counter {
+    pub fn bump(&mut self) {
+        self.n += 1;
+    }
+
+    pub fn get(&self) -> u32 {
+        self.n
+    }
+}
+";
+
+fn fixture() -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("counter.rs");
+    std::fs::write(&path, SOURCE).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn line_inside_method_returns_the_method() {
+    let (_dir, path) = fixture();
+    let out = read_symbol_at_line(&path, 9, false).unwrap();
+    assert!(out.contains("`bump`"), "{out}");
+    assert!(out.contains(":L8-L10"), "{out}");
+    assert!(!out.contains("pub fn get"), "{out}");
+}
+
+#[test]
+fn same_name_declarations_resolve_by_position() {
+    let (_dir, path) = fixture();
+
+    // The struct and the impl are both named `Counter`.
+    let strukt = read_symbol_at_line(&path, 4, false).unwrap();
+    assert!(strukt.contains(":L3-L5"), "{strukt}");
+    assert!(!strukt.contains("Disambiguation"), "{strukt}");
+
+    // Blank line between methods: only the impl block encloses it.
+    let imp = read_symbol_at_line(&path, 11, false).unwrap();
+    assert!(imp.contains("impl `Counter`"), "{imp}");
+    assert!(imp.contains(":L7-L15"), "{imp}");
+}
+
+#[test]
+fn lines_outside_any_declaration_are_errors() {
+    let (_dir, path) = fixture();
+    let err = read_symbol_at_line(&path, 1, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("No declaration encloses line 1"), "{err}");
+
+    let err = read_symbol_at_line(&path, 99, false)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("out of range"), "{err}");
+}
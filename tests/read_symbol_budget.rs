@@ -0,0 +1,53 @@
+use cortexast::inspector::read_symbols_within_budget;
+
+fn fixture() -> (tempfile::TempDir, std::path::PathBuf) {
+    let mut source = String::from("pub fn small() -> u32 {\n    1\n}\n\npub fn big() -> u32 {\n");
+    for i in 0..200 {
+        source.push_str(&format!("    let v{i} = {i};\n"));
+    }
+    source.push_str("    0\n}\n\npub fn tail() -> u32 {\n    2\n}\n\npub struct Wide {\n");
+    // Fields survive skeletonization, so only truncation can shrink this one.
+    for i in 0..200 {
+        source.push_str(&format!("    pub field_{i}: u32,\n"));
+    }
+    source.push_str("}\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lib.rs");
+    std::fs::write(&path, source).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn generous_budget_reads_everything_in_order() {
+    let (_dir, path) = fixture();
+    let out =
+        read_symbols_within_budget(&path, &["tail", "small"], false, 10_000, |_| String::new());
+    let tail = out.find("pub fn tail").unwrap();
+    let small = out.find("pub fn small").unwrap();
+    assert!(tail < small, "{out}");
+    assert!(!out.contains("✂️"), "{out}");
+}
+
+#[test]
+fn overflow_is_skeletonized_and_later_symbols_still_fill() {
+    let (_dir, path) = fixture();
+    let out = read_symbols_within_budget(&path, &["small", "big", "tail"], false, 300, |_| {
+        String::new()
+    });
+    assert!(out.contains("pub fn small() -> u32 {\n    1\n}"), "{out}");
+    assert!(out.contains("skeleton only"), "{out}");
+    assert!(!out.contains("let v150"), "{out}");
+    assert!(out.contains("pub fn tail"), "{out}");
+    assert!(!out.contains("omitted"), "{out}");
+}
+
+#[test]
+fn tight_budget_truncates_then_omits_the_rest() {
+    let (_dir, path) = fixture();
+    let out = read_symbols_within_budget(&path, &["Wide", "tail"], false, 100, |_| String::new());
+    assert!(out.contains("pub field_3: u32,"), "{out}");
+    assert!(out.contains("// ✂️ truncated:"), "{out}");
+    assert!(out.len() < 100 * 4 + 300, "{}", out.len());
+    assert!(out.contains("omitted `tail`"), "{out}");
+}
@@ -0,0 +1,158 @@
+//! `read_symbol` headers: the enclosing scope chain and the declaration's own doc
+//! comment, as `// scope:` and `// doc:` lines.
+
+use cortexast::inspector::read_symbol;
+
+fn write(name: &str, source: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::write(&path, source).unwrap();
+    (dir, path)
+}
+
+/// `// scope:` and `// doc:` lines of the header before the symbol body.
+fn header(out: &str) -> Vec<&str> {
+    out.lines()
+        .take_while(|l| l.starts_with("// "))
+        .filter(|l| l.starts_with("// scope:") || l.starts_with("// doc:"))
+        .collect()
+}
+
+const RUST: &str = "\
+mod server {
+    pub struct State;
+
+    /// Not attached: a blank line follows.
+
+    impl std::fmt::Display for State {
+        /// Renders the state.
+        ///
+        /// Used by the status page.
+        #[inline]
+        #[must_use = \"render\"]
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, \"state\")
+        }
+    }
+
+    impl State {
+        //// Banner, not a doc comment.
+        fn reset(&self) {}
+
+        // Plain comment.
+        /// Starts serving.
+        fn start(&self) {}
+    }
+}
+";
+
+#[test]
+fn rust_scope_chain_names_modules_and_trait_impls() {
+    let (_dir, path) = write("lib.rs", RUST);
+
+    let out = read_symbol(&path, "fmt").unwrap();
+    assert_eq!(
+        header(&out),
+        [
+            "// scope: mod server › impl std::fmt::Display for State",
+            "// doc: Renders the state.",
+            "// doc: ",
+            "// doc: Used by the status page.",
+        ],
+        "{out}"
+    );
+
+    let out = read_symbol(&path, "State::reset").unwrap();
+    assert_eq!(header(&out), ["// scope: mod server › impl State"], "{out}");
+}
+
+#[test]
+fn rust_doc_stops_at_plain_comments_and_blank_lines() {
+    let (_dir, path) = write("lib.rs", RUST);
+
+    let out = read_symbol(&path, "start").unwrap();
+    assert_eq!(
+        header(&out),
+        ["// scope: mod server › impl State", "// doc: Starts serving."],
+        "{out}"
+    );
+
+    let out = read_symbol(&path, "State").unwrap();
+    assert_eq!(header(&out), ["// scope: mod server"], "{out}");
+}
+
+const TYPESCRIPT: &str = "\
+namespace Shop {
+  export class Cart {
+    /**
+     * Adds an item.
+     * @param sku product id
+     */
+    add(sku: string): void {}
+
+    // TODO: not a doc comment
+    clear(): void {}
+  }
+}
+
+/** Totals a cart. */
+export function total(): number {
+  return 0;
+}
+";
+
+#[test]
+fn typescript_scope_chain_and_jsdoc() {
+    let (_dir, path) = write("cart.ts", TYPESCRIPT);
+
+    let out = read_symbol(&path, "add").unwrap();
+    assert_eq!(
+        header(&out),
+        [
+            "// scope: namespace Shop › class Cart",
+            "// doc: Adds an item.",
+            "// doc: @param sku product id",
+        ],
+        "{out}"
+    );
+
+    let out = read_symbol(&path, "clear").unwrap();
+    assert_eq!(header(&out), ["// scope: namespace Shop › class Cart"], "{out}");
+
+    // JSDoc above `export` belongs to the exported declaration.
+    let out = read_symbol(&path, "total").unwrap();
+    assert_eq!(header(&out), ["// doc: Totals a cart."], "{out}");
+}
+
+const PYTHON: &str = "\
+class Reader:
+    def read(self, n):
+        \"\"\"Read up to n bytes.
+
+        Returns b'' at EOF.
+        \"\"\"
+        return b''
+
+    def close(self):
+        return None
+";
+
+#[test]
+fn python_docstring_and_class_scope() {
+    let (_dir, path) = write("io.py", PYTHON);
+
+    let out = read_symbol(&path, "read").unwrap();
+    assert_eq!(
+        header(&out),
+        [
+            "// scope: class Reader",
+            "// doc: Read up to n bytes.",
+            "// doc: ",
+            "// doc: Returns b'' at EOF.",
+        ],
+        "{out}"
+    );
+
+    let out = read_symbol(&path, "close").unwrap();
+    assert_eq!(header(&out), ["// scope: class Reader"], "{out}");
+}
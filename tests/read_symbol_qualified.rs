@@ -0,0 +1,83 @@
+use cortexast::inspector::read_symbol;
+
+const RUST: &str = "\
+pub struct Foo;
+pub struct Bar;
+
+impl Foo {
+    pub fn new() -> Self {
+        Foo
+    }
+}
+
+impl Bar {
+    pub fn new() -> Self {
+        Bar
+    }
+}
+
+pub fn helper() {}
+";
+
+const PYTHON: &str = "\
+class Reader:
+    def close(self):
+        return 'reader'
+
+
+class Writer:
+    def close(self):
+        return 'writer'
+";
+
+fn write(name: &str, source: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::write(&path, source).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn rust_impl_type_scopes_the_method() {
+    let (_dir, path) = write("shapes.rs", RUST);
+
+    let bar = read_symbol(&path, "Bar::new").unwrap();
+    assert!(bar.contains(":L11-L13"), "{bar}");
+    assert!(bar.contains("Bar\n"), "{bar}");
+    assert!(!bar.contains("Disambiguation"), "{bar}");
+
+    let foo = read_symbol(&path, "Foo::new").unwrap();
+    assert!(foo.contains(":L5-L7"), "{foo}");
+}
+
+#[test]
+fn python_class_scopes_the_method() {
+    let (_dir, path) = write("io.py", PYTHON);
+
+    let out = read_symbol(&path, "Writer.close").unwrap();
+    assert!(out.contains("'writer'"), "{out}");
+    assert!(!out.contains("'reader'"), "{out}");
+}
+
+#[test]
+fn module_qualifier_matches_the_file() {
+    let (_dir, path) = write("shapes.rs", RUST);
+
+    let out = read_symbol(&path, "shapes::helper").unwrap();
+    assert!(out.contains("pub fn helper"), "{out}");
+
+    let out = read_symbol(&path, "crate::shapes::Bar::new").unwrap();
+    assert!(out.contains(":L11-L13"), "{out}");
+}
+
+#[test]
+fn wrong_qualifier_is_not_found() {
+    let (_dir, path) = write("shapes.rs", RUST);
+
+    let err = read_symbol(&path, "Baz::new").unwrap_err().to_string();
+    assert!(err.contains("Symbol `Baz::new` not found"), "{err}");
+
+    // Unqualified lookups still report every instance.
+    let out = read_symbol(&path, "new").unwrap();
+    assert!(out.contains("Found 2 instances"), "{out}");
+}
@@ -0,0 +1,54 @@
+//! `read_source(include_tests: true)`: test functions named after the symbol.
+
+use cortexast::inspector::find_symbol_tests;
+
+fn patterns() -> Vec<String> {
+    vec!["**/tests/**".to_string(), "test_*.py".to_string()]
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("tests")).unwrap();
+    std::fs::write(
+        root.join("src/config.rs"),
+        "pub fn parse_config(text: &str) -> usize {\n    text.len()\n}\n\n\
+         pub fn parse_config_lenient(text: &str) -> usize {\n    parse_config(text.trim())\n}\n\n\
+         #[cfg(test)]\nmod tests {\n    use super::*;\n\n    #[test]\n    \
+         fn parse_config_counts_bytes() {\n        assert_eq!(parse_config(\"ab\"), 2);\n    }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("tests/config.rs"),
+        "fn empty_parse_config_is_zero() {\n    assert_eq!(parse_config(\"\"), 0);\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("src/test_loader.py"),
+        "def test_parse_config():\n    assert parse_config('x') == 1\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn finds_tests_by_attribute_file_and_name() {
+    let dir = fixture();
+    let out = find_symbol_tests("parse_config", dir.path(), &patterns()).unwrap();
+    assert!(out.contains("3 of 3 test(s)"), "{out}");
+    // `#[test]` attribute inside the defining file.
+    assert!(out.contains("src/config.rs:14"), "{out}");
+    // Test file by path, and a `test_` name across languages.
+    assert!(out.contains("tests/config.rs:1"), "{out}");
+    assert!(out.contains("src/test_loader.py:1"), "{out}");
+    // The definition and non-test callers stay out.
+    assert!(!out.contains("parse_config_lenient"), "{out}");
+}
+
+#[test]
+fn reports_when_nothing_matches() {
+    let dir = fixture();
+    let out = find_symbol_tests("render_page", dir.path(), &patterns()).unwrap();
+    assert!(out.contains("no test functions"), "{out}");
+}
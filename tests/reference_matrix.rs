@@ -0,0 +1,38 @@
+//! `reference_matrix`: file-to-file symbol reference counts.
+
+use cortexast::inspector::reference_matrix;
+
+#[test]
+fn counts_references_to_the_defining_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/model.rs"),
+        "pub struct Order {\n    pub id: u32,\n}\n\npub fn new() -> u32 {\n    0\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("src/billing.rs"),
+        "pub fn charge(order: &Order) -> u32 {\n    order.id\n}\n\npub fn new() -> u32 {\n    1\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("src/api.rs"),
+        "// charge(Order) in a comment does not count\n\
+         pub fn handle(o: Order) -> u32 {\n    let a: Order = o;\n    charge(&a) + new()\n}\n",
+    )
+    .unwrap();
+
+    let matrix = reference_matrix(root).unwrap();
+    let api = &matrix["src/api.rs"];
+    assert_eq!(api["src/model.rs"], 2, "{matrix:?}");
+    assert_eq!(api["src/billing.rs"], 1, "{matrix:?}");
+    assert_eq!(matrix["src/billing.rs"]["src/model.rs"], 1, "{matrix:?}");
+    // Nothing in model.rs refers elsewhere, and `new` is declared twice so it
+    // resolves nowhere.
+    assert!(!matrix.contains_key("src/model.rs"), "{matrix:?}");
+
+    let json = serde_json::to_value(&matrix).unwrap();
+    assert_eq!(json["src/api.rs"]["src/model.rs"], 2);
+}
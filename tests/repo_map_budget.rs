@@ -0,0 +1,30 @@
+//! Deep-mode `repo_map` stops walking files once the output cap is reached.
+
+use cortexast::inspector::{repo_map_with_filter, RepoMapOptions};
+
+#[test]
+fn deep_mode_stops_at_the_cap() {
+    let dir = tempfile::tempdir().unwrap();
+    for d in ["a", "b", "c"] {
+        std::fs::create_dir_all(dir.path().join(d)).unwrap();
+        for i in 0..5 {
+            let body: String = (0..10)
+                .map(|n| format!("pub fn {d}_item_{i}_{n}() {{}}\n"))
+                .collect();
+            std::fs::write(dir.path().join(format!("{d}/mod_{i}.rs")), body).unwrap();
+        }
+    }
+    let opts = RepoMapOptions {
+        max_chars: Some(1_200),
+        ..RepoMapOptions::default()
+    };
+    let out = repo_map_with_filter(dir.path(), &opts).unwrap();
+    assert!(out.len() <= 1_200, "{}", out.len());
+    assert!(out.contains("a_item_0_0"), "{out}");
+    // Nothing follows the truncation marker, and later folders never appear.
+    assert!(
+        out.ends_with("... (output truncated — hard limit reached)\n"),
+        "{out}"
+    );
+    assert!(!out.contains("\nc/\n"), "{out}");
+}
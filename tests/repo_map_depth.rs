@@ -0,0 +1,52 @@
+use cortexast::inspector::{repo_map_with_filter, RepoMapOptions};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Two shallow files plus 40 files four levels down — enough to push the full map
+/// out of symbol-level (deep) mode.
+fn deep_tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "lib.rs", "pub fn top_level() {}\n");
+    write(root, "core/engine.rs", "pub fn run_engine() {}\n");
+    for i in 0..40 {
+        write(
+            root,
+            &format!("core/gen/v1/item_{i}.rs"),
+            &format!("pub fn item_{i}() {{}}\n"),
+        );
+    }
+    dir
+}
+
+fn map(root: &Path, max_depth: Option<usize>) -> String {
+    let opts = RepoMapOptions {
+        max_depth,
+        ..RepoMapOptions::default()
+    };
+    repo_map_with_filter(root, &opts).unwrap()
+}
+
+#[test]
+fn full_walk_hides_symbols_on_deep_trees() {
+    let dir = deep_tree();
+    let out = map(dir.path(), None);
+    assert!(out.contains("(42 files)"), "{out}");
+    assert!(!out.contains("run_engine"), "{out}");
+}
+
+#[test]
+fn max_depth_limits_walk_and_keeps_symbols() {
+    let dir = deep_tree();
+    let out = map(dir.path(), Some(2));
+    assert!(out.contains("(2 files)"), "{out}");
+    assert!(out.contains("top_level"), "{out}");
+    assert!(out.contains("run_engine"), "{out}");
+    assert!(!out.contains("item_0"), "{out}");
+    assert!(out.contains("max_depth=2"), "{out}");
+}
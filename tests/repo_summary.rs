@@ -0,0 +1,62 @@
+//! `repo_summary`: languages, LOC, symbol kinds and workspace layout in one profile.
+
+use cortexast::inspector::{format_repo_summary, repo_summary};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+#[test]
+fn summarises_a_cargo_workspace() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    );
+    write(root, "crates/a/Cargo.toml", "[package]\nname = \"a\"\n");
+    write(
+        root,
+        "crates/a/src/lib.rs",
+        "pub struct A;\n\npub fn a() {}\n",
+    );
+    write(root, "crates/b/Cargo.toml", "[package]\nname = \"b\"\n");
+    write(root, "crates/b/tool.py", "def run():\n    pass\n");
+    write(root, "docs/notes.txt", "not code\n");
+
+    let s = repo_summary(root, Path::new(".")).unwrap();
+    assert_eq!(s.root, ".");
+    let rust = s.languages.iter().find(|l| l.driver == "rust").unwrap();
+    assert_eq!((rust.files, rust.lines), (1, 3));
+    let python = s.languages.iter().find(|l| l.driver == "python").unwrap();
+    assert_eq!((python.files, python.lines), (1, 2));
+    assert_eq!(s.symbols.get("struct"), Some(&1));
+    assert_eq!(s.symbols.get("function"), Some(&2));
+    assert_eq!(s.top_level_dirs, vec!["crates", "docs"]);
+    assert_eq!(s.manifest.as_deref(), Some("cargo"));
+    assert_eq!(s.workspace_members, vec!["crates/a", "crates/b"]);
+    assert!(s.monorepo);
+
+    let text = format_repo_summary(&s);
+    assert!(text.contains("- rust: 1 file(s), 3 line(s)\n"), "{text}");
+    assert!(text.contains("Monorepo: 2 workspace members"), "{text}");
+}
+
+#[test]
+fn scoped_to_a_subdirectory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "app/main.py", "def main():\n    pass\n");
+    write(root, "other/lib.rs", "pub fn other() {}\n");
+
+    let s = repo_summary(root, Path::new("app")).unwrap();
+    assert_eq!(s.root, "app");
+    assert_eq!(s.files, 1);
+    assert!(s.manifest.is_none());
+    assert!(!s.monorepo);
+    assert!(repo_summary(root, Path::new("missing")).is_err());
+}
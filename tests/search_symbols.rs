@@ -0,0 +1,67 @@
+//! `search_symbols`: lexical, ranked lookup of half-remembered symbol names.
+
+use cortexast::inspector::search_symbols_fuzzy;
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("billing.rs"),
+        "pub fn process_payment(id: u32) -> bool {\n    id > 0\n}\n\n\
+         pub fn refund_payment(id: u32) -> bool {\n    id > 1\n}\n\n\
+         pub struct PaymentGateway;\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("jobs.py"),
+        "def processPayments(batch):\n    return [b for b in batch]\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn names(dir: &tempfile::TempDir, pattern: &str) -> Vec<String> {
+    search_symbols_fuzzy(dir.path(), pattern)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.name)
+        .collect()
+}
+
+#[test]
+fn naming_convention_is_ignored_and_exact_ranks_first() {
+    let dir = fixture();
+    let hits = search_symbols_fuzzy(dir.path(), "processPayment").unwrap();
+    assert_eq!(hits[0].name, "process_payment");
+    assert_eq!(hits[0].file, "billing.rs");
+    assert_eq!(hits[0].line, 1);
+    assert_eq!(hits[1].name, "processPayments");
+    assert_eq!(hits[1].file, "jobs.py");
+    assert_eq!(hits.len(), 2, "{hits:?}");
+}
+
+#[test]
+fn substring_matches_rank_after_prefix_matches_closest_length_first() {
+    let dir = fixture();
+    assert_eq!(
+        names(&dir, "payment"),
+        vec![
+            "PaymentGateway",
+            "refund_payment",
+            "process_payment",
+            "processPayments"
+        ]
+    );
+}
+
+#[test]
+fn typos_match_by_edit_distance() {
+    let dir = fixture();
+    assert_eq!(names(&dir, "proces_paymnet")[0], "process_payment");
+    assert!(names(&dir, "shipOrder").is_empty());
+}
+
+#[test]
+fn empty_pattern_is_an_error() {
+    let dir = fixture();
+    assert!(search_symbols_fuzzy(dir.path(), " _ ").is_err());
+}
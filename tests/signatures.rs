@@ -0,0 +1,54 @@
+//! `signatures`: public signatures grouped by file, most central first.
+
+use cortexast::inspector::signatures_dump;
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Repo under the crate dir (not /tmp, whose path component the walker skips),
+/// with no `.git` of its own.
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let root = dir.path();
+    write(root, "Cargo.toml", "[package]\nname = \"demo\"\n");
+    write(root, "src/lib.rs", "pub mod a;\npub mod b;\npub mod util;\n");
+    write(root, "src/a.rs", "use crate::util::Shared;\npub fn a_entry() {}\n");
+    write(root, "src/b.rs", "use crate::util::Shared;\npub fn b_entry() {}\n");
+    write(root, "src/util.rs", "pub struct Shared;\npub fn helper(x: u32) -> u32 { x }\nfn private() {}\n");
+    dir
+}
+
+#[test]
+fn paths_and_ranking_follow_the_given_repo_root() {
+    let dir = repo();
+    let root = dir.path();
+    let out = signatures_dump(root, &root.join("src"), 32_000).unwrap();
+
+    let util = out.find("## src/util.rs").expect(&out);
+    assert!(util < out.find("## src/a.rs").expect(&out), "{out}");
+    assert!(util < out.find("## src/b.rs").expect(&out), "{out}");
+    assert!(out.contains("pub fn helper(x: u32) -> u32"), "{out}");
+    assert!(!out.contains("private"), "{out}");
+}
+
+#[test]
+fn relative_target_resolves_against_the_repo_root() {
+    let dir = repo();
+    let root = dir.path();
+    let out = signatures_dump(root, Path::new("src"), 32_000).unwrap();
+    assert!(out.contains("## src/util.rs"), "{out}");
+}
+
+#[test]
+fn budget_drops_lower_ranked_files() {
+    let dir = repo();
+    let root = dir.path();
+    let out = signatures_dump(root, root, 20).unwrap();
+    assert!(out.contains("## src/util.rs"), "{out}");
+    assert!(out.contains("Budget reached"), "{out}");
+    assert!(!out.contains("## src/b.rs"), "{out}");
+}
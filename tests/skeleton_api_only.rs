@@ -0,0 +1,124 @@
+//! `skeleton.api_only`: only the public contract of a module survives.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+const RUST: &str = r#"pub struct Client {
+    pub url: String,
+}
+
+#[derive(Debug)]
+struct Cache {
+    hits: u32,
+}
+
+impl Client {
+    pub fn connect(url: &str) -> Client {
+        Client { url: url.to_string() }
+    }
+
+    fn retry_budget(&self) -> u32 {
+        3
+    }
+}
+
+impl Clone for Client {
+    fn clone(&self) -> Client {
+        Client { url: self.url.clone() }
+    }
+}
+
+pub(crate) fn internal_helper() {}
+
+fn private_helper() {}
+"#;
+
+const TS: &str = r#"export function fetchUser(id: string) {
+  return lookup(id);
+}
+
+function lookup(id: string) {
+  return { id };
+}
+
+export class Store {
+  get(key: string) {
+    return key;
+  }
+  private evict() {}
+}
+"#;
+
+const PYTHON: &str = r#"class Client:
+    def __init__(self, url):
+        self.url = url
+
+    def get(self, path):
+        return self._join(path)
+
+    def _join(self, path):
+        return self.url + path
+
+
+def _helper():
+    return 1
+
+
+def connect(url):
+    return Client(url)
+"#;
+
+fn render(file: &str, source: &str) -> String {
+    let opts = SkeletonConfig {
+        api_only: true,
+        ..Default::default()
+    };
+    try_render_skeleton_from_source(Path::new(file), source, &opts)
+        .unwrap()
+        .expect("language is supported")
+}
+
+#[test]
+fn rust_keeps_pub_items_and_trait_impls() {
+    let out = render("lib.rs", RUST);
+    assert!(out.contains("pub struct Client"), "{out}");
+    assert!(out.contains("pub fn connect"), "{out}");
+    assert!(out.contains("fn clone"), "{out}");
+    assert!(!out.contains("struct Cache"), "{out}");
+    assert!(!out.contains("derive(Debug)"), "{out}");
+    assert!(!out.contains("retry_budget"), "{out}");
+    assert!(!out.contains("internal_helper"), "{out}");
+    assert!(!out.contains("private_helper"), "{out}");
+}
+
+#[test]
+fn typescript_keeps_exports_only() {
+    let out = render("api.ts", TS);
+    assert!(out.contains("export function fetchUser"), "{out}");
+    assert!(out.contains("export class Store"), "{out}");
+    assert!(out.contains("get(key: string)"), "{out}");
+    assert!(!out.contains("function lookup"), "{out}");
+    assert!(!out.contains("evict"), "{out}");
+}
+
+#[test]
+fn python_drops_underscore_names_but_keeps_dunders() {
+    let out = render("client.py", PYTHON);
+    assert!(out.contains("class Client"), "{out}");
+    assert!(out.contains("def __init__"), "{out}");
+    assert!(out.contains("def get"), "{out}");
+    assert!(out.contains("def connect"), "{out}");
+    assert!(!out.contains("def _join"), "{out}");
+    assert!(!out.contains("def _helper"), "{out}");
+}
+
+#[test]
+fn default_keeps_private_declarations() {
+    let out =
+        try_render_skeleton_from_source(Path::new("lib.rs"), RUST, &SkeletonConfig::default())
+            .unwrap()
+            .unwrap();
+    assert!(out.contains("fn private_helper"), "{out}");
+    assert!(out.contains("struct Cache"), "{out}");
+}
@@ -0,0 +1,70 @@
+//! `skeleton.collapse_runs`: adjacent trivial bodies fold into one summary line.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+const SOURCE: &str = r#"pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 { self.y }
+
+    pub fn set_x(&mut self, v: i32) { self.x = v; }
+
+    /// Documented: stays visible even though its body is short.
+    pub fn norm(&self) -> i32 { self.x.abs() + self.y.abs() }
+
+    pub fn describe(&self) -> String {
+        let a = self.x.to_string();
+        let b = self.y.to_string();
+        let c = format!("{a},{b}");
+        c
+    }
+}
+"#;
+
+fn render(opts: &SkeletonConfig) -> String {
+    try_render_skeleton_from_source(Path::new("point.rs"), SOURCE, opts)
+        .unwrap()
+        .expect("rust is supported")
+}
+
+#[test]
+fn collapse_runs_is_off_by_default() {
+    let out = render(&SkeletonConfig::default());
+    assert!(out.contains("fn set_x"), "{out}");
+    assert!(!out.contains("trivial methods"), "{out}");
+}
+
+#[test]
+fn adjacent_trivial_bodies_collapse_into_a_summary() {
+    let opts = SkeletonConfig {
+        collapse_runs: true,
+        ..Default::default()
+    };
+    let out = render(&opts);
+    assert!(out.contains("// ... (3 trivial methods)"), "{out}");
+    assert!(!out.contains("fn set_x"), "{out}");
+    // A doc comment ends the run; long bodies are never collapsed.
+    assert!(out.contains("fn norm"), "{out}");
+    assert!(out.contains("fn describe"), "{out}");
+}
+
+#[test]
+fn trivial_threshold_is_configurable() {
+    let opts = SkeletonConfig {
+        collapse_runs: true,
+        trivial_max_lines: 1,
+        ..Default::default()
+    };
+    let out = render(&opts);
+    // `x()` spans three lines, so only `y` and `set_x` remain trivial.
+    assert!(out.contains("fn x(&self)"), "{out}");
+    assert!(out.contains("// ... (2 trivial methods)"), "{out}");
+}
@@ -0,0 +1,88 @@
+//! `skeleton.keep_comment_tags` / `skeleton.keep_all_comments`: which comments
+//! survive skeleton comment stripping.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+const RUST: &str = r#"// SAFETY: the caller holds the index lock.
+// Plain narration about the next function.
+// TODO: split this module.
+/* NOTE: block comments can be tagged too. */
+/* An untagged block
+   spanning two lines. */
+// This comment would denote nothing special.
+pub fn touch() -> u32 {
+    7
+}
+"#;
+
+const PYTHON: &str = r#""""Loader module docstring."""
+
+# Security: inputs are validated upstream.
+def load(path):
+    return open(path).read()
+"#;
+
+fn render(file: &str, source: &str, opts: &SkeletonConfig) -> String {
+    try_render_skeleton_from_source(Path::new(file), source, opts)
+        .unwrap()
+        .expect("language is supported")
+}
+
+#[test]
+fn default_keeps_only_todo_and_fixme() {
+    let out = render("lib.rs", RUST, &SkeletonConfig::default());
+    assert!(out.contains("TODO: split this module."), "{out}");
+    assert!(!out.contains("SAFETY"), "{out}");
+    assert!(!out.contains("NOTE"), "{out}");
+    assert!(!out.contains("Plain narration"), "{out}");
+    assert!(out.contains("pub fn touch()"), "{out}");
+}
+
+#[test]
+fn configured_tags_replace_the_defaults_and_match_whole_words() {
+    let opts = SkeletonConfig {
+        keep_comment_tags: vec!["safety".into(), "NOTE".into()],
+        ..Default::default()
+    };
+    let out = render("lib.rs", RUST, &opts);
+    assert!(
+        out.contains("SAFETY: the caller holds the index lock."),
+        "{out}"
+    );
+    assert!(
+        out.contains("NOTE: block comments can be tagged too."),
+        "{out}"
+    );
+    assert!(!out.contains("TODO"), "{out}");
+    assert!(!out.contains("denote"), "{out}");
+    assert!(!out.contains("untagged block"), "{out}");
+}
+
+#[test]
+fn keep_all_comments_skips_stripping() {
+    let opts = SkeletonConfig {
+        keep_all_comments: true,
+        ..Default::default()
+    };
+    let out = render("lib.rs", RUST, &opts);
+    for needle in [
+        "SAFETY",
+        "Plain narration",
+        "TODO",
+        "NOTE",
+        "spanning two lines",
+        "denote",
+    ] {
+        assert!(out.contains(needle), "missing {needle:?}: {out}");
+    }
+
+    let py = render("loader.py", PYTHON, &opts);
+    assert!(py.contains("Loader module docstring."), "{py}");
+    assert!(py.contains("# Security: inputs"), "{py}");
+
+    let py_default = render("loader.py", PYTHON, &SkeletonConfig::default());
+    assert!(!py_default.contains("docstring"), "{py_default}");
+    assert!(!py_default.contains("Security"), "{py_default}");
+}
@@ -0,0 +1,58 @@
+//! `skeleton_dir`: skeletons of every supported file in a folder, under one budget.
+
+use cortexast::inspector::skeleton_dir;
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("nested")).unwrap();
+    std::fs::write(
+        root.join("a.rs"),
+        "pub fn alpha() -> u32 {\n    let x = 40;\n    x + 2\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("nested/b.py"),
+        "def beta(n):\n    total = n * 2\n    return total\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("notes.txt"), "not source\n").unwrap();
+    std::fs::write(
+        root.join("bundle.js"),
+        format!("var a={};\n", "1+".repeat(3_000) + "1"),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn every_supported_file_gets_a_header_and_a_pruned_body() {
+    let dir = fixture();
+    let out = skeleton_dir(dir.path(), 32_000).unwrap();
+
+    assert!(out.contains("(3/3 files"), "{out}");
+    assert!(out.contains("\n## a.rs\n"), "{out}");
+    assert!(out.contains("\n## nested/b.py\n"), "{out}");
+    assert!(out.contains("pub fn alpha() -> u32"), "{out}");
+    assert!(!out.contains("x + 2"), "{out}");
+    assert!(!out.contains("total = n * 2"), "{out}");
+    assert!(out.contains("MINIFIED_OR_GENERATED"), "{out}");
+    assert!(!out.contains("notes.txt"), "{out}");
+    assert!(out.find("## a.rs").unwrap() < out.find("## nested/b.py").unwrap());
+}
+
+#[test]
+fn budget_cuts_off_later_files() {
+    let dir = fixture();
+    let out = skeleton_dir(dir.path(), 20).unwrap();
+    assert!(out.contains("Budget reached"), "{out}");
+    assert!(!out.contains("## nested/b.py"), "{out}");
+}
+
+#[test]
+fn a_file_or_empty_folder_is_an_error() {
+    let dir = fixture();
+    assert!(skeleton_dir(&dir.path().join("a.rs"), 1_000).is_err());
+    let empty = tempfile::tempdir().unwrap();
+    assert!(skeleton_dir(empty.path(), 1_000).is_err());
+}
@@ -0,0 +1,55 @@
+//! `skeleton.keep_imports_for`: listed extensions keep their import lines instead
+//! of the `// ... (N imports)` hint.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+const PYTHON: &str = r#"import os
+from pathlib import Path
+
+def load(path):
+    return Path(path).read_text()
+"#;
+
+const RUST: &str = r#"use std::fs;
+use std::path::Path;
+
+pub fn load(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
+"#;
+
+fn render(file: &str, source: &str, keep_imports_for: &[&str]) -> String {
+    let opts = SkeletonConfig {
+        keep_imports_for: keep_imports_for.iter().map(|e| e.to_string()).collect(),
+        ..Default::default()
+    };
+    try_render_skeleton_from_source(Path::new(file), source, &opts)
+        .unwrap()
+        .expect("language is supported")
+}
+
+#[test]
+fn imports_collapse_by_default() {
+    let out = render("loader.py", PYTHON, &[]);
+    assert!(out.contains("// ... (2 imports)"), "{out}");
+    assert!(!out.contains("import os"), "{out}");
+}
+
+#[test]
+fn listed_extensions_keep_imports() {
+    let out = render("loader.py", PYTHON, &["py", "proto"]);
+    assert!(
+        out.contains("import os\nfrom pathlib import Path\n"),
+        "{out}"
+    );
+    assert!(!out.contains("imports)"), "{out}");
+    assert!(out.contains("def load(path):"), "{out}");
+
+    // Other languages still collapse; a leading dot and any case are accepted.
+    let out = render("loader.rs", RUST, &[".PY"]);
+    assert!(out.contains("// ... (2 uses)"), "{out}");
+    let out = render("loader.py", PYTHON, &[".PY"]);
+    assert!(out.contains("import os"), "{out}");
+}
@@ -0,0 +1,62 @@
+//! `skeleton.keep_head_lines`: the first N body lines survive pruning.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+const RUST: &str = r#"pub fn load(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(text.trim().to_string())
+}
+
+pub fn bump(x: i32) -> i32 {
+    x + 1
+}
+"#;
+
+const PYTHON: &str = r#"def load(path):
+    if not path:
+        return None
+    with open(path) as f:
+        return f.read()
+"#;
+
+fn render(file: &str, source: &str, keep_head_lines: usize) -> String {
+    let opts = SkeletonConfig {
+        keep_head_lines,
+        ..Default::default()
+    };
+    try_render_skeleton_from_source(Path::new(file), source, &opts)
+        .unwrap()
+        .expect("language is supported")
+}
+
+#[test]
+fn default_prunes_whole_bodies() {
+    let out = render("lib.rs", RUST, 0);
+    assert!(!out.contains("return None"), "{out}");
+    assert!(!out.contains("x + 1"), "{out}");
+}
+
+#[test]
+fn brace_bodies_keep_guard_clause() {
+    let out = render("lib.rs", RUST, 3);
+    assert!(out.contains("return None;"), "{out}");
+    assert!(out.contains("/* ... */"), "{out}");
+    assert!(!out.contains("read_to_string"), "{out}");
+    // A body no longer than the head is kept whole.
+    assert!(out.contains("x + 1"), "{out}");
+}
+
+#[test]
+fn python_placeholder_keeps_suite_indentation() {
+    let out = render("loader.py", PYTHON, 2);
+    assert!(
+        out.contains("def load(path):\n    if not path:\n        return None\n    ...\n"),
+        "{out}"
+    );
+    assert!(!out.contains("open(path)"), "{out}");
+}
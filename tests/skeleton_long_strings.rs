@@ -0,0 +1,70 @@
+//! `skeleton.max_string_chars`: oversized string literals in kept code are elided.
+
+use cortexast::config::SkeletonConfig;
+use cortexast::inspector::try_render_skeleton_from_source;
+use std::path::Path;
+
+fn render(file: &str, source: &str, max_string_chars: usize) -> String {
+    let opts = SkeletonConfig {
+        max_string_chars,
+        ..Default::default()
+    };
+    try_render_skeleton_from_source(Path::new(file), source, &opts)
+        .unwrap()
+        .expect("language is supported")
+}
+
+fn blob(n: usize) -> String {
+    "QUJD".repeat(n / 4)
+}
+
+#[test]
+fn long_constants_are_elided_short_ones_kept() {
+    let source = format!(
+        "pub const NAME: &str = \"logo\";\npub const LOGO: &str = \"{}\";\n\n\
+         pub fn logo() -> &'static str {{\n    \"{}\"\n}}\n",
+        blob(400),
+        blob(400)
+    );
+    let out = render("assets.rs", &source, 100);
+    // 400 payload bytes plus the two quotes.
+    assert!(
+        out.contains("pub const LOGO: &str = \"...<402 bytes elided>\";"),
+        "{out}"
+    );
+    assert!(out.contains("pub const NAME: &str = \"logo\";"), "{out}");
+    // The body is pruned as usual, not elided.
+    assert!(
+        out.contains("pub fn logo() -> &'static str { /* ... */ }"),
+        "{out}"
+    );
+    assert!(!out.contains("QUJD"), "{out}");
+}
+
+#[test]
+fn zero_keeps_every_string_and_python_is_covered() {
+    let source = format!(
+        "ICON = \"{}\"\n\n\ndef icon():\n    return ICON\n",
+        blob(400)
+    );
+    assert!(render("icons.py", &source, 0).contains(&blob(400)));
+
+    let out = render("icons.py", &source, 100);
+    assert!(out.contains("ICON = \"...<402 bytes elided>\""), "{out}");
+}
+
+#[test]
+fn default_limit_is_a_thousand_bytes() {
+    let source = format!(
+        "export const SMALL = \"{}\";\nexport const BIG = \"{}\";\n",
+        blob(800),
+        blob(1200)
+    );
+    let out = render(
+        "data.ts",
+        &source,
+        SkeletonConfig::default().max_string_chars,
+    );
+    assert!(out.contains(&blob(800)), "{out}");
+    assert!(out.contains("BIG = \"...<1202 bytes elided>\";"), "{out}");
+}
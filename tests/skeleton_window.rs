@@ -0,0 +1,62 @@
+//! `render_skeleton_window`: the whole file as a skeleton, one line window verbatim.
+
+use cortexast::inspector::render_skeleton_window;
+
+const RUST: &str = r#"use std::collections::HashMap;
+
+pub fn load(path: &str) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(text.trim().to_string())
+}
+
+pub fn count(words: &[&str]) -> HashMap<String, usize> {
+    // tally every word
+    let mut out = HashMap::new();
+    for w in words {
+        *out.entry(w.to_string()).or_insert(0) += 1;
+    }
+    out
+}
+
+pub fn bump(x: i32) -> i32 {
+    x + 1
+}
+"#;
+
+fn fixture() -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lib.rs");
+    std::fs::write(&path, RUST).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn window_is_verbatim_and_the_rest_is_pruned() {
+    let (_dir, path) = fixture();
+    // Lines 9-14: the body of `count`, comment and indentation included.
+    let out = render_skeleton_window(&path, 9, 14).unwrap();
+
+    let window: String = RUST
+        .lines()
+        .skip(8)
+        .take(6)
+        .map(|l| format!("{l}\n"))
+        .collect();
+    assert!(out.contains(&window), "{out}");
+    assert!(out.contains("pub fn load"), "{out}");
+    assert!(out.contains("pub fn bump"), "{out}");
+    assert!(!out.contains("read_to_string"), "{out}");
+    assert!(!out.contains("x + 1"), "{out}");
+}
+
+#[test]
+fn window_past_the_end_is_clamped_and_bad_windows_fail() {
+    let (_dir, path) = fixture();
+    let out = render_skeleton_window(&path, 18, 500).unwrap();
+    assert!(out.contains("    x + 1\n"), "{out}");
+    assert!(!out.contains("tally every word"), "{out}");
+
+    assert!(render_skeleton_window(&path, 0, 3).is_err());
+    assert!(render_skeleton_window(&path, 5, 4).is_err());
+    assert!(render_skeleton_window(&path, 99, 120).is_err());
+}
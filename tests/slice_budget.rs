@@ -0,0 +1,75 @@
+//! Slices that overflow `budget_tokens` say so: a `<budget/>` summary plus a
+//! `<truncated/>` marker on the file that was cut.
+
+use cortexast::config::Config;
+use cortexast::slicer::slice_to_xml;
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+fn numbered_lines(prefix: &str, n: usize) -> String {
+    (1..=n)
+        .map(|i| format!("// {prefix} line {i} padding padding padding\n"))
+        .collect()
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/small.rs", "pub fn small() {}\n");
+    // ~20 KB each: neither fits a 600-token (2400-byte) budget whole.
+    write(root, "src/big_a.rs", &numbered_lines("a", 400));
+    write(root, "src/big_b.rs", &numbered_lines("b", 400));
+    dir
+}
+
+fn cfg() -> Config {
+    Config {
+        skeleton_mode: false,
+        output_dir: PathBuf::from(".cortexast"),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn overflowing_slice_reports_budget_and_cut_point() {
+    let dir = fixture();
+    let (xml, meta) = slice_to_xml(dir.path(), Path::new("."), 600, &cfg(), false).unwrap();
+
+    assert!(
+        xml.contains(r#"<budget limit_tokens="600""#),
+        "missing budget summary: {xml}"
+    );
+    assert!(
+        xml.contains(r#"truncated_files="1" dropped_files="1"/>"#),
+        "{xml}"
+    );
+    assert!(
+        meta.total_tokens <= 600,
+        "used {} tokens",
+        meta.total_tokens
+    );
+
+    // The small file is whole; one big file is cut after some whole lines.
+    assert!(xml.contains("pub fn small() {}"));
+    let marker = &xml[xml
+        .find("<truncated at=\"line ")
+        .expect("truncation marker")..];
+    assert!(marker.contains("remaining_bytes=\""), "{marker}");
+    assert!(marker.contains("/></file>"), "{marker}");
+    assert_eq!(meta.included.len(), 2, "{:?}", meta.included);
+    assert_eq!(meta.excluded.len(), 1, "{:?}", meta.excluded);
+}
+
+#[test]
+fn slice_within_budget_has_no_budget_report() {
+    let dir = fixture();
+    let (xml, _meta) =
+        slice_to_xml(dir.path(), Path::new("src/small.rs"), 32_000, &cfg(), false).unwrap();
+    assert!(!xml.contains("<budget"));
+    assert!(!xml.contains("<truncated"));
+}
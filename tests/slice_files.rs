@@ -0,0 +1,76 @@
+//! `slice_to_files` returns the files `slice_to_xml` would inline, one entry each,
+//! with the same rendering and budget cuts.
+
+use cortexast::config::Config;
+use cortexast::slicer::{slice_to_files, slice_to_xml};
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/small.rs", "pub fn small() {}\n");
+    let big: String = (1..=400)
+        .map(|i| format!("// big line {i} padding padding padding\n"))
+        .collect();
+    write(root, "src/big_a.rs", &big);
+    write(root, "src/big_b.rs", &big);
+    dir
+}
+
+fn cfg() -> Config {
+    Config {
+        skeleton_mode: false,
+        output_dir: PathBuf::from(".cortexast"),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn files_match_the_xml_slice() {
+    let dir = fixture();
+    let (xml, meta) = slice_to_xml(dir.path(), Path::new("."), 100_000, &cfg(), false).unwrap();
+    let sliced = slice_to_files(dir.path(), Path::new("."), 100_000, &cfg(), false).unwrap();
+
+    let paths: Vec<&str> = sliced.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, meta.included);
+    assert_eq!(sliced.meta.total_bytes, meta.total_bytes);
+    assert!(sliced.budget.is_none());
+
+    let small = sliced
+        .files
+        .iter()
+        .find(|f| f.path == "src/small.rs")
+        .unwrap();
+    assert_eq!(small.content, "pub fn small() {}\n");
+    assert!(small.truncation.is_none());
+    assert!(xml.contains("pub fn small() {}"), "{xml}");
+}
+
+#[test]
+fn files_carry_the_same_budget_cuts() {
+    let dir = fixture();
+    let (_, meta) = slice_to_xml(dir.path(), Path::new("."), 600, &cfg(), false).unwrap();
+    let sliced = slice_to_files(dir.path(), Path::new("."), 600, &cfg(), false).unwrap();
+
+    assert_eq!(sliced.meta.included, meta.included);
+    assert_eq!(sliced.meta.excluded, meta.excluded);
+    assert_eq!(sliced.meta.total_tokens, meta.total_tokens);
+
+    let budget = sliced
+        .budget
+        .expect("overflowing slice has a budget summary");
+    assert_eq!(budget.truncated_files, 1);
+    let cut = sliced
+        .files
+        .iter()
+        .find(|f| f.truncation.is_some())
+        .expect("one file is cut");
+    assert!(cut.path.starts_with("src/big_"), "{}", cut.path);
+    assert!(cut.content.ends_with('\n'));
+}
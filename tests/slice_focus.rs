@@ -0,0 +1,142 @@
+//! `deep_slice` with `focus_symbol`: one symbol in full, its neighbours as skeletons.
+
+use cortexast::config::Config;
+use cortexast::slicer::slice_around_symbol;
+use std::path::Path;
+
+const RUST: &str = "\
+pub fn first() -> u32 {
+    let a = 1;
+    a
+}
+
+pub fn second() -> u32 {
+    let b = 2;
+    b
+}
+
+pub fn process_order(id: u32) -> u32 {
+    let total = id * 2;
+    total + 1
+}
+
+pub fn fourth() -> u32 {
+    let d = 4;
+    d
+}
+
+pub fn fifth() -> u32 {
+    let e = 5;
+    e
+}
+";
+
+const PYTHON: &str = "\
+def helper():
+    return 0
+
+
+class Cart:
+    def add(self, item):
+        self.items.append(item)
+
+    def total(self):
+        return sum(self.items)
+
+    def clear(self):
+        self.items = []
+";
+
+fn write(root: &Path, rel: &str, text: &str) {
+    std::fs::write(root.join(rel), text).unwrap();
+}
+
+#[test]
+fn target_is_full_and_neighbours_are_skeletons() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "orders.rs", RUST);
+
+    let (xml, meta) = slice_around_symbol(
+        dir.path(),
+        Path::new("orders.rs"),
+        "process_order",
+        1,
+        32_000,
+        &Config::default(),
+    )
+    .unwrap();
+
+    assert_eq!(meta.included, vec!["orders.rs".to_string()]);
+    assert!(xml.contains("let total = id * 2;"), "{xml}");
+    assert!(xml.contains("pub fn second() -> u32"), "{xml}");
+    assert!(!xml.contains("let b = 2;"), "{xml}");
+    assert!(xml.contains("pub fn fourth() -> u32"), "{xml}");
+    assert!(!xml.contains("let d = 4;"), "{xml}");
+    assert!(!xml.contains("pub fn first"), "{xml}");
+    assert!(!xml.contains("pub fn fifth"), "{xml}");
+    assert!(xml.contains("// ... (lines 1-5 omitted)"), "{xml}");
+    assert!(xml.contains("// ... (lines 20-24 omitted)"), "{xml}");
+    // Blank separators are kept as-is rather than marked.
+    assert!(!xml.contains("lines 10-10"), "{xml}");
+}
+
+#[test]
+fn methods_are_scoped_to_their_class() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "cart.py", PYTHON);
+
+    let (xml, _) = slice_around_symbol(
+        dir.path(),
+        Path::new("cart.py"),
+        "total",
+        1,
+        32_000,
+        &Config::default(),
+    )
+    .unwrap();
+
+    assert!(xml.contains("return sum(self.items)"), "{xml}");
+    assert!(xml.contains("def add(self, item)"), "{xml}");
+    assert!(xml.contains("def clear(self)"), "{xml}");
+    // `helper` is a top-level sibling of the class, not of the method.
+    assert!(!xml.contains("def helper"), "{xml}");
+    assert!(xml.contains("# ... (lines 1-5 omitted)"), "{xml}");
+}
+
+#[test]
+fn tight_budget_drops_neighbours_first() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "orders.rs", RUST);
+
+    let (xml, _) = slice_around_symbol(
+        dir.path(),
+        Path::new("orders.rs"),
+        "process_order",
+        2,
+        90,
+        &Config::default(),
+    )
+    .unwrap();
+
+    assert!(xml.contains("let total = id * 2;"), "{xml}");
+    assert!(!xml.contains("pub fn first"), "{xml}");
+    assert!(!xml.contains("pub fn fifth"), "{xml}");
+}
+
+#[test]
+fn unknown_symbol_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "orders.rs", RUST);
+
+    let err = slice_around_symbol(
+        dir.path(),
+        Path::new("orders.rs"),
+        "ship_order",
+        2,
+        32_000,
+        &Config::default(),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("`ship_order` not found"), "{err}");
+}
@@ -0,0 +1,55 @@
+//! Glob `deep_slice` targets: every matching file, ignore rules applied.
+
+use cortexast::config::Config;
+use cortexast::slicer::{expand_glob_target, is_glob_target, slice_paths_to_xml};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+#[test]
+fn expands_globs_against_the_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, ".gitignore", "src/legacy/\n");
+    write(root, "src/order_controller.rs", "pub fn orders() {}\n");
+    write(root, "src/api/user_controller.rs", "pub fn users() {}\n");
+    write(root, "src/api/user_service.rs", "pub fn service() {}\n");
+    write(root, "src/legacy/old_controller.rs", "pub fn old() {}\n");
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+
+    assert!(is_glob_target("src/**/*_controller.rs"));
+    assert!(!is_glob_target("src/api"));
+
+    let cfg = Config::default();
+    let matched = expand_glob_target(root, "src/**/*_controller.rs", &cfg).unwrap();
+    assert_eq!(
+        matched,
+        ["src/api/user_controller.rs", "src/order_controller.rs"]
+    );
+    // `*` does not cross directories.
+    let top = expand_glob_target(root, "./src/*.rs", &cfg).unwrap();
+    assert_eq!(top, ["src/order_controller.rs"]);
+
+    let (xml, _) = slice_paths_to_xml(root, &matched, 32_000, &cfg, false).unwrap();
+    assert!(xml.contains("pub fn users()"), "{xml}");
+    assert!(xml.contains("pub fn orders()"), "{xml}");
+    assert!(!xml.contains("pub fn service()"), "{xml}");
+}
+
+#[test]
+fn a_glob_matching_nothing_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "src/lib.rs", "pub fn lib() {}\n");
+    let err = expand_glob_target(dir.path(), "src/**/*_controller.rs", &Config::default())
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("'src/**/*_controller.rs' matched no files"),
+        "{err}"
+    );
+    assert!(expand_glob_target(dir.path(), "nope/*.rs", &Config::default()).is_err());
+}
@@ -0,0 +1,62 @@
+//! `slicer.parse_failure`: what a skeleton slice shows for a file that fails to parse.
+
+use cortexast::config::{Config, ParseConfig, ParseFailure};
+use cortexast::inspector::apply_parse_limits;
+use cortexast::slicer::{slice_plan, slice_to_xml, SliceMode};
+use std::path::Path;
+
+fn slice(root: &Path, policy: ParseFailure) -> (String, SliceMode) {
+    let mut cfg = Config::default();
+    cfg.token_estimator.max_file_bytes = 64 * 1024 * 1024;
+    cfg.slicer.parse_failure = policy;
+    let (xml, _) = slice_to_xml(root, Path::new("."), 10_000_000, &cfg, false).unwrap();
+    let plan = slice_plan(root, Path::new("."), 10_000_000, &cfg, false).unwrap();
+    let mode = plan
+        .files
+        .iter()
+        .find(|f| f.path == "big.rs")
+        .map(|f| f.mode)
+        .expect("big.rs planned");
+    (xml, mode)
+}
+
+// One test, because the parse limits are process-wide.
+#[test]
+fn parse_failures_follow_the_configured_fallback() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = String::new();
+    for i in 0..18_000 {
+        source.push_str(&format!(
+            "pub fn generated_{i}(x: u32) -> u32 {{ x + {i} }}\n"
+        ));
+    }
+    std::fs::write(dir.path().join("big.rs"), &source).unwrap();
+
+    // Just under the 1 MB hard cap; a 1 ms parse budget still cancels its parse.
+    apply_parse_limits(&ParseConfig {
+        max_parse_bytes: 0,
+        max_parse_millis: 1,
+    });
+    let (truncated, truncated_mode) = slice(dir.path(), ParseFailure::Truncate);
+    let (full, full_mode) = slice(dir.path(), ParseFailure::Full);
+    let (skipped, skipped_mode) = slice(dir.path(), ParseFailure::Skip);
+    apply_parse_limits(&ParseConfig::default());
+
+    assert!(
+        truncated.contains("PARSE_FAILED (parse timed out"),
+        "{truncated:.400}"
+    );
+    assert!(truncated.contains("raw head, not a skeleton"));
+    assert!(truncated.contains("generated_0("));
+    assert!(!truncated.contains("generated_17999("));
+    assert_eq!(truncated_mode, SliceMode::Head);
+
+    assert!(full.contains("raw content, not a skeleton"));
+    assert!(full.contains("generated_17999("));
+    assert_eq!(full_mode, SliceMode::Full);
+
+    assert!(skipped.contains("PARSE_FAILED"));
+    assert!(skipped.contains("skipped"));
+    assert!(!skipped.contains("generated_0("));
+    assert_eq!(skipped_mode, SliceMode::ParseFailed);
+}
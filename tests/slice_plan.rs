@@ -0,0 +1,131 @@
+use cortexast::config::Config;
+use cortexast::slicer::{format_slice_plan, slice_plan, slice_to_xml, SliceFate, SliceMode};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+#[test]
+fn plan_reports_each_files_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "Cargo.toml",
+        "[package]\nname = \"demo\"\n\n[profile.release]\nlto = true\n",
+    );
+    write(root, "src/lib.rs", "pub fn answer() -> u32 {\n    42\n}\n");
+    write(root, "notes.txt", "plain notes\n");
+    write(root, "web/app.js", &"var a=1;".repeat(300));
+    std::fs::write(root.join("data.bin"), b"\x00\x01\x02header").unwrap();
+    write(root, "big.txt", &"x".repeat(4_000));
+    write(root, "gen/out.rs", "pub fn generated() {}\n");
+    write(root, ".gitattributes", "gen/out.rs linguist-generated\n");
+
+    let mut cfg = Config::default();
+    cfg.token_estimator.max_file_bytes = 3_000;
+    let plan = slice_plan(root, Path::new("."), 100_000, &cfg, false).unwrap();
+    let row = |p: &str| {
+        plan.files
+            .iter()
+            .find(|f| f.path == p)
+            .unwrap_or_else(|| panic!("{p} missing from {plan:?}"))
+    };
+
+    assert_eq!(row("Cargo.toml").mode, SliceMode::Compacted);
+    assert_eq!(row("src/lib.rs").mode, SliceMode::Skeleton);
+    assert_eq!(row("notes.txt").mode, SliceMode::Head);
+    assert_eq!(row("web/app.js").mode, SliceMode::Minified);
+    assert_eq!(row("data.bin").mode, SliceMode::Binary);
+    for p in [
+        "Cargo.toml",
+        "src/lib.rs",
+        "notes.txt",
+        "web/app.js",
+        "data.bin",
+    ] {
+        assert_eq!(row(p).fate, SliceFate::Included, "{p}");
+        assert!(row(p).tokens > 0, "{p}");
+    }
+
+    let big = row("big.txt");
+    assert_eq!(
+        (big.mode, big.fate, big.bytes, big.tokens),
+        (SliceMode::TooLarge, SliceFate::Skipped, 4_000, 0)
+    );
+    let generated = row("gen/out.rs");
+    assert_eq!(
+        (generated.mode, generated.fate),
+        (SliceMode::Generated, SliceFate::Skipped)
+    );
+
+    assert_eq!(plan.used_tokens, plan.full_tokens);
+    let table = format_slice_plan(&plan);
+    assert!(
+        table.contains("| src/lib.rs | skeleton | included |"),
+        "{table}"
+    );
+    assert!(
+        table.contains("| big.txt | too_large | skipped | 4000 | 0 |"),
+        "{table}"
+    );
+}
+
+#[test]
+fn plan_matches_the_slice_it_predicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for name in ["a", "b", "c", "d", "e"] {
+        let body = format!("// {name}\n{}", "let value = 1;\n".repeat(40));
+        write(root, &format!("src/{name}.txt"), &body);
+    }
+
+    let cfg = Config {
+        skeleton_mode: false,
+        ..Config::default()
+    };
+    let budget = 500;
+    let plan = slice_plan(root, Path::new("src"), budget, &cfg, false).unwrap();
+    let (_, meta) = slice_to_xml(root, Path::new("src"), budget, &cfg, false).unwrap();
+
+    let planned: Vec<&str> = plan
+        .files
+        .iter()
+        .filter(|f| matches!(f.fate, SliceFate::Included | SliceFate::Truncated))
+        .map(|f| f.path.as_str())
+        .collect();
+    let mut sliced: Vec<&str> = meta.included.iter().map(String::as_str).collect();
+    let mut planned_sorted = planned.clone();
+    planned_sorted.sort();
+    sliced.sort();
+    assert_eq!(planned_sorted, sliced, "{plan:?}");
+
+    assert!(
+        plan.files.iter().any(|f| f.fate == SliceFate::Dropped),
+        "{plan:?}"
+    );
+    assert!(plan.files.iter().all(|f| f.mode == SliceMode::Full));
+    assert_eq!(plan.used_tokens, meta.total_tokens);
+    assert!(plan.full_tokens > budget, "{plan:?}");
+}
+
+#[test]
+fn focus_file_is_planned_full() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/lib.rs", "pub fn answer() -> u32 {\n    42\n}\n");
+
+    let plan = slice_plan(
+        root,
+        Path::new("src/lib.rs"),
+        8_000,
+        &Config::default(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(plan.files.len(), 1);
+    assert_eq!(plan.files[0].mode, SliceMode::Full);
+}
@@ -0,0 +1,74 @@
+//! `slicer.profile` presets: output format and budget headroom per model family.
+
+use cortexast::config::{Config, SliceFormat, SliceProfile};
+use cortexast::slicer::slice_to_xml;
+use std::path::Path;
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn cfg(profile: SliceProfile) -> Config {
+    let mut cfg = Config {
+        skeleton_mode: false,
+        ..Config::default()
+    };
+    cfg.slicer.profile = profile;
+    cfg
+}
+
+#[test]
+fn gpt_profile_slices_to_markdown() {
+    let dir = fixture();
+    let (md, _) = slice_to_xml(
+        dir.path(),
+        Path::new("."),
+        4_000,
+        &cfg(SliceProfile::Gpt),
+        false,
+    )
+    .unwrap();
+    assert!(md.contains("## src/lib.rs\n\n```rust\npub fn add"), "{md}");
+    assert!(!md.contains("<cortexast"), "{md}");
+}
+
+#[test]
+fn explicit_format_overrides_the_profile() {
+    let dir = fixture();
+    let mut cfg = cfg(SliceProfile::Gpt);
+    cfg.slicer.format = Some(SliceFormat::Xml);
+    let (xml, _) = slice_to_xml(dir.path(), Path::new("."), 4_000, &cfg, false).unwrap();
+    assert!(xml.contains(r#"<file path="src/lib.rs">"#), "{xml}");
+}
+
+#[test]
+fn claude_profile_keeps_budget_headroom() {
+    let dir = fixture();
+    let (xml, meta) = slice_to_xml(
+        dir.path(),
+        Path::new("."),
+        4_000,
+        &cfg(SliceProfile::Claude),
+        false,
+    )
+    .unwrap();
+    assert!(xml.contains("<cortexast>"), "{xml}");
+    assert_eq!(meta.budget_tokens, 3_600);
+
+    let (_, meta) = slice_to_xml(
+        dir.path(),
+        Path::new("."),
+        4_000,
+        &cfg(SliceProfile::Generic),
+        false,
+    )
+    .unwrap();
+    assert_eq!(meta.budget_tokens, 4_000);
+}
@@ -0,0 +1,92 @@
+//! `slicer.annotate_recent_commits`: lines from the last N commits carry a `~` marker.
+
+use cortexast::config::Config;
+use cortexast::slicer::slice_to_xml;
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+fn cfg(n: usize) -> Config {
+    let mut cfg = Config {
+        skeleton_mode: false,
+        ..Config::default()
+    };
+    cfg.slicer.annotate_recent_commits = n;
+    cfg
+}
+
+const V1: &str = "pub fn total(a: u32) -> u32 {\n    a + 1\n}\n";
+const V2: &str = "pub fn total(a: u32) -> u32 {\n    a * 2 + 1\n}\n";
+
+#[test]
+fn lines_from_recent_commits_are_marked() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), V1).unwrap();
+    if !git(root, &["init", "-q"]) {
+        eprintln!("git unavailable; skipping");
+        return;
+    }
+    assert!(git(root, &["add", "."]));
+    assert!(git(root, &["commit", "-qm", "one"]));
+    std::fs::write(root.join("src/lib.rs"), V2).unwrap();
+    assert!(git(root, &["commit", "-qam", "two"]));
+
+    let (xml, _) = slice_to_xml(root, Path::new("."), 4_000, &cfg(1), false).unwrap();
+    assert!(xml.contains("~    a * 2 + 1"), "{xml}");
+    assert!(!xml.contains("~pub fn total"), "{xml}");
+
+    // Both commits are recent with N = 2.
+    let (xml, _) = slice_to_xml(root, Path::new("."), 4_000, &cfg(2), false).unwrap();
+    assert!(xml.contains("~pub fn total"), "{xml}");
+
+    // Off by default.
+    let (xml, _) = slice_to_xml(root, Path::new("."), 4_000, &cfg(0), false).unwrap();
+    assert!(!xml.contains('~'), "{xml}");
+}
+
+/// A new commit moves what counts as recent even for files it did not touch, so
+/// cached blame results must not outlive `HEAD`.
+#[test]
+fn new_commits_refresh_cached_marks() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), V1).unwrap();
+    if !git(root, &["init", "-q"]) {
+        eprintln!("git unavailable; skipping");
+        return;
+    }
+    assert!(git(root, &["add", "."]));
+    assert!(git(root, &["commit", "-qm", "one"]));
+
+    let (xml, _) = slice_to_xml(root, Path::new("."), 4_000, &cfg(1), false).unwrap();
+    assert!(xml.contains("~pub fn total"), "{xml}");
+
+    std::fs::write(root.join("README.md"), "notes\n").unwrap();
+    assert!(git(root, &["add", "README.md"]));
+    assert!(git(root, &["commit", "-qm", "two"]));
+
+    let (xml, _) = slice_to_xml(root, Path::new("."), 4_000, &cfg(1), false).unwrap();
+    assert!(!xml.contains("~pub fn total"), "{xml}");
+}
+
+#[test]
+fn non_git_directories_are_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), V1).unwrap();
+
+    let (xml, _) = slice_to_xml(dir.path(), Path::new("."), 4_000, &cfg(3), false).unwrap();
+    assert!(xml.contains("pub fn total"), "{xml}");
+    assert!(!xml.contains('~'), "{xml}");
+}
@@ -0,0 +1,106 @@
+//! `slice_to_writer` streams the same slice `slice_to_xml` builds in memory, byte
+//! for byte; an overflowing slice's `<budget/>` summary comes last in both.
+
+use cortexast::config::Config;
+use cortexast::slicer::{slice_to_writer, slice_to_xml};
+use std::path::{Path, PathBuf};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let p = root.join(rel);
+    std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+    std::fs::write(p, text).unwrap();
+}
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "src/small.rs", "pub fn small() {}\n");
+    let big: String = (1..=400)
+        .map(|i| format!("// big line {i} padding padding padding\n"))
+        .collect();
+    write(root, "src/big_a.rs", &big);
+    write(root, "src/big_b.rs", &big);
+    dir
+}
+
+fn cfg() -> Config {
+    Config {
+        skeleton_mode: false,
+        output_dir: PathBuf::from(".cortexast"),
+        ..Config::default()
+    }
+}
+
+fn stream(root: &Path, budget: usize) -> (String, cortexast::slicer::SliceMeta) {
+    let mut out: Vec<u8> = Vec::new();
+    let meta = slice_to_writer(root, Path::new("."), budget, &cfg(), false, &mut out).unwrap();
+    (String::from_utf8(out).unwrap(), meta)
+}
+
+#[test]
+fn streamed_slice_matches_in_memory_slice() {
+    let dir = fixture();
+    let (xml, meta) = slice_to_xml(dir.path(), Path::new("."), 100_000, &cfg(), false).unwrap();
+    let (streamed, streamed_meta) = stream(dir.path(), 100_000);
+
+    assert_eq!(streamed, xml);
+    assert_eq!(streamed_meta.included, meta.included);
+    assert_eq!(streamed_meta.total_bytes, meta.total_bytes);
+}
+
+#[test]
+fn streamed_overflow_cuts_the_same_files_and_summarises_last() {
+    let dir = fixture();
+    let (xml, meta) = slice_to_xml(dir.path(), Path::new("."), 600, &cfg(), false).unwrap();
+    let (streamed, streamed_meta) = stream(dir.path(), 600);
+
+    assert_eq!(streamed_meta.included, meta.included);
+    assert_eq!(streamed_meta.excluded, meta.excluded);
+    assert_eq!(streamed_meta.total_tokens, meta.total_tokens);
+
+    assert_eq!(streamed, xml);
+    let budget_start = streamed.find("<budget ").expect("budget summary");
+    let budget = &streamed[budget_start..];
+    let budget = &budget[..=budget.find("/>").unwrap() + 1];
+    assert!(
+        streamed
+            .trim_end()
+            .ends_with(&format!("{budget}</cortexast>")),
+        "{streamed}"
+    );
+    assert!(streamed.contains("<truncated at=\"line "), "{streamed}");
+}
+
+/// Huge-codebase mode streams too: root files and every member's files are
+/// written, and the metadata describes exactly what was written.
+#[test]
+fn huge_codebase_slice_streams_every_member() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/a\", \"crates/b\"]\n",
+    );
+    for m in ["a", "b"] {
+        write(
+            root,
+            &format!("crates/{m}/Cargo.toml"),
+            &format!("[package]\nname = \"{m}\"\n"),
+        );
+        write(root, &format!("crates/{m}/src/lib.rs"), &format!("pub fn {m}() {{}}\n"));
+    }
+    let mut cfg = cfg();
+    cfg.huge_codebase.enabled = true;
+
+    let mut out: Vec<u8> = Vec::new();
+    let meta = slice_to_writer(root, Path::new("."), 100_000, &cfg, false, &mut out).unwrap();
+    let xml = String::from_utf8(out).unwrap();
+
+    for path in ["Cargo.toml", "crates/a/src/lib.rs", "crates/b/src/lib.rs"] {
+        assert!(meta.included.iter().any(|p| p == path), "{path}: {:?}", meta.included);
+        assert!(xml.contains(&format!("<file path=\"{path}\"")), "{xml}");
+    }
+    assert_eq!(xml.matches("<file ").count(), meta.total_files);
+    assert!(xml.trim_end().ends_with("</cortexast>"), "{xml}");
+}
@@ -0,0 +1,91 @@
+use cortexast::inspector::{format_symbol_dependencies, symbol_dependencies};
+use std::path::Path;
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// `handle` calls `parse` (same file) and takes a `Request` (other file); `parse`
+/// in turn calls `tokenize`, which is only reachable at depth 2.
+fn workspace() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(
+        root,
+        "src/handler.rs",
+        "use crate::model::Request;\n\
+         \n\
+         pub fn handle(req: Request) -> usize {\n    \
+             let v = parse(&req.body);\n    \
+             v.len()\n\
+         }\n\
+         \n\
+         fn parse(s: &str) -> Vec<String> {\n    \
+             tokenize(s)\n\
+         }\n",
+    );
+    write(
+        root,
+        "src/model.rs",
+        "pub struct Request {\n    pub body: String,\n}\n",
+    );
+    write(
+        root,
+        "src/lexer.rs",
+        "pub fn tokenize(s: &str) -> Vec<String> {\n    vec![s.to_string()]\n}\n",
+    );
+    dir
+}
+
+#[test]
+fn direct_dependencies_resolve_to_definitions() {
+    let dir = workspace();
+    let root = dir.path();
+    let deps = symbol_dependencies(&root.join("src/handler.rs"), "handle", 1, root).unwrap();
+
+    let names: Vec<&str> = deps.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, vec!["parse", "Request"], "{deps:?}");
+
+    let parse = &deps[0];
+    assert_eq!(
+        (parse.file.as_str(), parse.line, parse.depth),
+        ("src/handler.rs", 8, 1)
+    );
+    let request = &deps[1];
+    assert_eq!((request.file.as_str(), request.line), ("src/model.rs", 1));
+    // `len` is runtime noise and `String` is a std type: neither is reported.
+}
+
+#[test]
+fn depth_two_follows_resolved_definitions() {
+    let dir = workspace();
+    let root = dir.path();
+    let deps = symbol_dependencies(&root.join("src/handler.rs"), "handle", 2, root).unwrap();
+    let tokenize = deps
+        .iter()
+        .find(|d| d.name == "tokenize")
+        .unwrap_or_else(|| panic!("tokenize missing: {deps:?}"));
+    assert_eq!(
+        (tokenize.file.as_str(), tokenize.depth),
+        ("src/lexer.rs", 2)
+    );
+
+    let out = format_symbol_dependencies("handle", &deps);
+    assert!(out.contains("### Depth 2"), "{out}");
+    assert!(
+        out.contains("`tokenize` function — src/lexer.rs:L1"),
+        "{out}"
+    );
+}
+
+#[test]
+fn unknown_symbol_is_an_error() {
+    let dir = workspace();
+    let root = dir.path();
+    let err = symbol_dependencies(&root.join("src/model.rs"), "missing", 1, root)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not found"), "{err}");
+}
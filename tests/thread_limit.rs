@@ -0,0 +1,28 @@
+//! `performance.max_threads` sizes the process-wide rayon pool.
+
+use cortexast::config::load_config;
+
+// One test, because the global pool can only be built once per process.
+#[test]
+fn max_threads_sizes_the_rayon_pool() {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::write(
+        repo.path().join(".cortexast.json"),
+        r#"{ "performance": { "max_threads": 2 } }"#,
+    )
+    .unwrap();
+    std::env::remove_var("CORTEXAST_THREADS");
+
+    let cfg = load_config(repo.path());
+    assert_eq!(cfg.performance.max_threads, 2);
+
+    std::env::set_var("CORTEXAST_THREADS", "3");
+    assert_eq!(load_config(repo.path()).performance.max_threads, 3);
+    std::env::remove_var("CORTEXAST_THREADS");
+
+    cfg.performance.install_thread_pool();
+    assert_eq!(rayon::current_num_threads(), 2);
+    // Later installs cannot resize it.
+    load_config(repo.path()).performance.install_thread_pool();
+    assert_eq!(rayon::current_num_threads(), 2);
+}
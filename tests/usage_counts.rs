@@ -0,0 +1,62 @@
+use cortexast::inspector::{format_usage_counts, usage_counts, GitignoreMode, PathStyle};
+
+#[test]
+fn counts_are_per_file_and_sorted_by_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(
+        root.join("src/lib.rs"),
+        "pub struct Config;\npub fn load() -> Config { Config }\npub fn save(_c: Config) {}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("src/main.rs"),
+        "// Config in a comment does not count\nfn main() { let _c: lib::Config; }\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("src/other.rs"), "fn unrelated() {}\n").unwrap();
+
+    let counts = usage_counts(
+        root,
+        "Config",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::RelativeTo(root),
+    )
+    .unwrap();
+    let paths: Vec<&str> = counts.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(paths, ["src/lib.rs", "src/main.rs"], "{counts:?}");
+    assert!(counts[0].1 >= 3, "{counts:?}");
+    assert_eq!(counts[1].1, 1, "comments must not count: {counts:?}");
+
+    let text = format_usage_counts("Config", &counts);
+    let total = counts[0].1 + 1;
+    assert!(
+        text.starts_with(&format!("{total} usage(s) of `Config` in 2 file(s):")),
+        "{text}"
+    );
+    assert!(
+        text.contains(&format!("\nsrc/lib.rs: {}\nsrc/main.rs: 1\n", counts[0].1)),
+        "{text}"
+    );
+}
+
+#[test]
+fn no_usages_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+    let counts = usage_counts(
+        dir.path(),
+        "Missing",
+        GitignoreMode::Respect,
+        &[],
+        PathStyle::Absolute,
+    )
+    .unwrap();
+    assert!(counts.is_empty());
+    assert_eq!(
+        format_usage_counts("Missing", &counts),
+        "No usages of `Missing` found."
+    );
+}
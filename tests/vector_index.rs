@@ -0,0 +1,252 @@
+use cortexast::config::IncludeTests;
+use cortexast::scanner::ScanOptions;
+use cortexast::vector_store::{
+    model_db_dir, CodebaseIndex, IndexJob, RefreshScope, INDEX_PROGRESS_FILE,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).unwrap();
+}
+
+/// Tiny model2vec model: a word-level tokenizer over `VOCAB` and one 4-d
+/// embedding row per word, so indexing runs offline and deterministically.
+const VOCAB: &[(&str, [f32; 4])] = &[
+    ("[UNK]", [0.0, 0.0, 0.0, 0.0]),
+    ("parse", [1.0, 0.0, 0.0, 0.0]),
+    ("config", [0.9, 0.1, 0.0, 0.0]),
+    ("render", [0.0, 1.0, 0.0, 0.0]),
+    ("page", [0.0, 0.9, 0.1, 0.0]),
+    ("store", [0.0, 0.0, 1.0, 0.0]),
+    ("fn", [0.0, 0.0, 0.0, 1.0]),
+];
+
+fn write_model(dir: &Path) {
+    let vocab: serde_json::Map<String, serde_json::Value> = VOCAB
+        .iter()
+        .enumerate()
+        .map(|(i, (word, _))| (word.to_string(), i.into()))
+        .collect();
+    let tokenizer = serde_json::json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": { "type": "Lowercase" },
+        "pre_tokenizer": { "type": "Whitespace" },
+        "post_processor": null,
+        "decoder": null,
+        "model": { "type": "WordLevel", "vocab": vocab, "unk_token": "[UNK]" }
+    });
+    std::fs::write(dir.join("tokenizer.json"), tokenizer.to_string()).unwrap();
+    std::fs::write(dir.join("config.json"), r#"{"normalize":true}"#).unwrap();
+
+    let data: Vec<u8> = VOCAB
+        .iter()
+        .flat_map(|(_, row)| row.iter().flat_map(|v| v.to_le_bytes()))
+        .collect();
+    let header = serde_json::json!({
+        "embeddings": { "dtype": "F32", "shape": [VOCAB.len(), 4], "data_offsets": [0, data.len()] }
+    })
+    .to_string();
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(&data);
+    std::fs::write(dir.join("model.safetensors"), bytes).unwrap();
+}
+
+fn scan_opts(root: &Path) -> ScanOptions {
+    ScanOptions {
+        repo_root: root.to_path_buf(),
+        target: PathBuf::from("."),
+        max_file_bytes: 512 * 1024,
+        exclude_dir_names: vec![],
+        exclude_dir_paths: vec![],
+        include_linguist_generated: false,
+        test_patterns: vec![],
+        include_tests: IncludeTests::All,
+    }
+}
+
+/// Repo, vector DB dir and model dir, each in its own tempdir (not under /tmp,
+/// whose path component the scanner skips).
+struct Fixture {
+    repo: tempfile::TempDir,
+    db: tempfile::TempDir,
+    model: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let fixture = Self {
+            repo: tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap(),
+            db: tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap(),
+            model: tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap(),
+        };
+        write_model(fixture.model.path());
+        write(fixture.repo.path(), "src/config.rs", "fn parse config\n");
+        write(fixture.repo.path(), "src/page.rs", "fn render page\n");
+        fixture
+    }
+
+    fn model_id(&self) -> String {
+        self.model.path().to_string_lossy().into_owned()
+    }
+
+    fn open(&self) -> CodebaseIndex {
+        CodebaseIndex::open(self.repo.path(), self.db.path(), &self.model_id(), 60).unwrap()
+    }
+
+    fn progress_path(&self) -> PathBuf {
+        model_db_dir(self.db.path(), &self.model_id()).join(INDEX_PROGRESS_FILE)
+    }
+}
+
+/// A checkpoint left by an interrupted run is cleared by the next run even when
+/// that run finds every file already current.
+#[tokio::test]
+async fn up_to_date_runs_clear_a_stale_checkpoint() {
+    let fixture = Fixture::new();
+    let mut index = fixture.open();
+    let (added, _, _) = index.refresh(&scan_opts(fixture.repo.path())).unwrap();
+    assert_eq!(added, 2);
+
+    std::fs::write(fixture.progress_path(), r#"{"done":1,"total":2}"#).unwrap();
+    assert!(index.interrupted_progress().is_some());
+    assert_eq!(
+        index.refresh(&scan_opts(fixture.repo.path())).unwrap(),
+        (0, 0, 0)
+    );
+    assert!(index.interrupted_progress().is_none());
+
+    std::fs::write(fixture.progress_path(), r#"{"done":1,"total":2}"#).unwrap();
+    let jobs = [IndexJob {
+        rel_path: "src/config.rs".into(),
+        abs_path: fixture.repo.path().join("src/config.rs"),
+        content: "fn parse config\n".into(),
+    }];
+    assert_eq!(index.index_jobs(&jobs, || {}).await.unwrap(), 0);
+    assert!(index.interrupted_progress().is_none());
+}
+
+/// Two independent builds of the same repo rank identically: equal scores fall
+/// back to path order instead of the store's hash-map order.
+#[tokio::test]
+async fn rebuilt_index_returns_identical_results() {
+    let fixture = Fixture::new();
+    for name in ["d", "b", "e", "a", "c"] {
+        write(fixture.repo.path(), &format!("src/{name}_config.rs"), "fn parse config\n");
+    }
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let db = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let mut index =
+            CodebaseIndex::open(fixture.repo.path(), db.path(), &fixture.model_id(), 60).unwrap();
+        index.refresh(&scan_opts(fixture.repo.path())).unwrap();
+        runs.push(index.search("parse config", 10).await.unwrap());
+    }
+
+    assert_eq!(runs[0], runs[1]);
+    let tied: Vec<&str> = runs[0]
+        .iter()
+        .map(String::as_str)
+        .filter(|p| p.ends_with("_config.rs"))
+        .collect();
+    assert_eq!(
+        tied,
+        [
+            "src/a_config.rs",
+            "src/b_config.rs",
+            "src/c_config.rs",
+            "src/d_config.rs",
+            "src/e_config.rs"
+        ]
+    );
+}
+
+/// An index from before per-model dirs is moved into its model's dir on open, so
+/// upgrading neither re-embeds the repo nor leaves the old files behind.
+#[test]
+fn legacy_index_moves_into_its_model_dir() {
+    let fixture = Fixture::new();
+    let mut index = fixture.open();
+    index.refresh(&scan_opts(fixture.repo.path())).unwrap();
+    drop(index);
+
+    let model_dir = model_db_dir(fixture.db.path(), &fixture.model_id());
+    for name in ["embeddings.json", "index_meta_v2.json"] {
+        std::fs::rename(model_dir.join(name), fixture.db.path().join(name)).unwrap();
+    }
+
+    let mut index = fixture.open();
+    assert!(!fixture.db.path().join("embeddings.json").exists());
+    assert!(model_dir.join("embeddings.json").is_file());
+    assert_eq!(
+        index.refresh(&scan_opts(fixture.repo.path())).unwrap(),
+        (0, 0, 0)
+    );
+}
+
+/// `index --since 1h`: only files modified inside the window are embedded, and a
+/// partial warm-up never deletes entries it didn't look at.
+#[test]
+fn since_scope_embeds_only_recent_files() {
+    let fixture = Fixture::new();
+    let old = SystemTime::now() - Duration::from_secs(2 * 3_600);
+    std::fs::File::options()
+        .write(true)
+        .open(fixture.repo.path().join("src/config.rs"))
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+
+    let mut index = fixture.open();
+    let cutoff = SystemTime::now() - Duration::from_secs(3_600);
+    let scope = RefreshScope::Since(cutoff);
+    assert_eq!(
+        index.refresh_scoped(&scan_opts(fixture.repo.path()), &scope).unwrap(),
+        (1, 0, 0)
+    );
+
+    std::fs::remove_file(fixture.repo.path().join("src/page.rs")).unwrap();
+    assert_eq!(
+        index.refresh_scoped(&scan_opts(fixture.repo.path()), &scope).unwrap(),
+        (0, 0, 0)
+    );
+    assert_eq!(
+        index.refresh(&scan_opts(fixture.repo.path())).unwrap(),
+        (1, 0, 1)
+    );
+}
+
+/// `index --since <ref>`: only the changed paths are touched, including deletions.
+#[test]
+fn paths_scope_touches_only_the_listed_files() {
+    let fixture = Fixture::new();
+    let mut index = fixture.open();
+    index.refresh(&scan_opts(fixture.repo.path())).unwrap();
+
+    write(fixture.repo.path(), "src/config.rs", "fn parse config store\n");
+    std::fs::remove_file(fixture.repo.path().join("src/page.rs")).unwrap();
+    write(fixture.repo.path(), "src/store.rs", "fn store\n");
+
+    let paths: HashSet<String> = ["src/page.rs", "src/store.rs"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        index
+            .refresh_scoped(&scan_opts(fixture.repo.path()), &RefreshScope::Paths(paths))
+            .unwrap(),
+        (1, 0, 1)
+    );
+    assert_eq!(
+        index.refresh(&scan_opts(fixture.repo.path())).unwrap(),
+        (0, 1, 0)
+    );
+}